@@ -1,10 +1,72 @@
 use crate::ENCLAVE_CONTEXT;
 use frame_enclave::{register_enclave_use_case, BasicEnclaveUseCase};
 use module_encrypted_sql_ops_enclave::enclave_use_cases::{
-    EncIntegerAvgFinalFuncUseCase, EncIntegerAvgStateFuncUseCase, EncIntegerFromUseCase,
+    EncBigIntCmpUseCase, EncBigIntEqUseCase, EncBigIntFromUseCase, EncBigIntMaxCombineFuncUseCase,
+    EncBigIntMaxFinalFuncUseCase, EncBigIntMaxStateFuncUseCase, EncBigIntMinCombineFuncUseCase,
+    EncBigIntMinFinalFuncUseCase, EncBigIntMinStateFuncUseCase, EncBigIntSumCombineFuncUseCase,
+    EncBigIntSumFinalFuncUseCase, EncBigIntSumStateFuncUseCase, EncBigIntTagUseCase,
+    EncIntegerAvgCombineFuncUseCase, EncIntegerAvgFinalFuncUseCase, EncIntegerAvgStateFuncUseCase,
+    EncIntegerCmpUseCase, EncIntegerCountCombineFuncUseCase, EncIntegerCountFinalFuncUseCase,
+    EncIntegerCountStateFuncUseCase, EncIntegerEqUseCase, EncIntegerFromClientUseCase,
+    EncIntegerFromUseCase, EncIntegerMaxCombineFuncUseCase, EncIntegerMaxFinalFuncUseCase,
+    EncIntegerMaxStateFuncUseCase, EncIntegerMigrateUseCase, EncIntegerMinCombineFuncUseCase,
+    EncIntegerMinFinalFuncUseCase, EncIntegerMinStateFuncUseCase, EncIntegerSumCombineFuncUseCase,
+    EncIntegerSumFinalFuncUseCase, EncIntegerSumStateFuncUseCase, EncIntegerTagUseCase,
+    EncIntegerToClientUseCase, EncRealAvgCombineFuncUseCase, EncRealAvgFinalFuncUseCase,
+    EncRealAvgStateFuncUseCase, EncRealCmpUseCase, EncRealEqUseCase, EncRealFromUseCase,
+    EncRealSumCombineFuncUseCase, EncRealSumFinalFuncUseCase, EncRealSumStateFuncUseCase,
+    EncRealTagUseCase, EncTextEqUseCase, EncTextFromUseCase, EncTextTagUseCase,
+    GetClientKeyUseCase, LoadMasterKeyUseCase,
 };
 register_enclave_use_case!(
     (EncIntegerFromUseCase, &*ENCLAVE_CONTEXT),
     (EncIntegerAvgStateFuncUseCase, &*ENCLAVE_CONTEXT),
     (EncIntegerAvgFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (LoadMasterKeyUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMigrateUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerSumStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerSumFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerCountStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerCountFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMinStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMinFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMaxStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMaxFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerCmpUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerEqUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntFromUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealFromUseCase, &*ENCLAVE_CONTEXT),
+    (EncTextFromUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntEqUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealEqUseCase, &*ENCLAVE_CONTEXT),
+    (EncTextEqUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntCmpUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealCmpUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntSumStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntSumFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMinStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMinFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMaxStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMaxFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealSumStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealSumFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealAvgStateFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealAvgFinalFuncUseCase, &*ENCLAVE_CONTEXT),
+    (GetClientKeyUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerFromClientUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerToClientUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerAvgCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerSumCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerCountCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMinCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerMaxCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntSumCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMinCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntMaxCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealSumCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealAvgCombineFuncUseCase, &*ENCLAVE_CONTEXT),
+    (EncIntegerTagUseCase, &*ENCLAVE_CONTEXT),
+    (EncBigIntTagUseCase, &*ENCLAVE_CONTEXT),
+    (EncRealTagUseCase, &*ENCLAVE_CONTEXT),
+    (EncTextTagUseCase, &*ENCLAVE_CONTEXT),
 );
@@ -0,0 +1,47 @@
+-- Per-row cost of the aggregates over ENCINTEGER, with an ecall per row and per batch.
+--
+-- Run in a database with the extension created, e.g. in `cargo pgx run pg13`:
+--   \i bench/aggregate_batch.sql
+-- It prints the microseconds per row of each aggregate with `aggregate_batch_size` of 1 and 256,
+-- measured after a warm-up run. The numbers depend on the hardware, so record them with the CPU and SGX mode.
+
+\set rows 100000
+
+DROP TABLE IF EXISTS bench_agg;
+CREATE TABLE bench_agg (c_enc ENCINTEGER);
+INSERT INTO bench_agg (c_enc) SELECT ENCINTEGER_FROM(i, 'bench_agg.c_enc') FROM generate_series(1, :rows) i;
+ANALYZE bench_agg;
+
+-- A single process scans the rows, so that the time isn't divided among parallel workers.
+SET max_parallel_workers_per_gather = 0;
+
+CREATE TEMP TABLE bench_agg_result (batch_size INTEGER, aggregate TEXT, us_per_row NUMERIC);
+
+DO $$
+DECLARE
+    row_count BIGINT := (SELECT COUNT(*) FROM bench_agg);
+    batch_size INTEGER;
+    aggregate TEXT;
+    started TIMESTAMPTZ;
+BEGIN
+    -- 1 is an ecall per row, as the state functions used to do, and 256 is the maximum batch.
+    FOREACH batch_size IN ARRAY ARRAY[1, 256] LOOP
+        PERFORM set_config('encrypted_sql_ops.aggregate_batch_size', batch_size::TEXT, true);
+        FOREACH aggregate IN ARRAY ARRAY['AVG', 'SUM', 'COUNT', 'MAX'] LOOP
+            EXECUTE format('SELECT %s(c_enc) FROM bench_agg', aggregate);
+            started := clock_timestamp();
+            EXECUTE format('SELECT %s(c_enc) FROM bench_agg', aggregate);
+            INSERT INTO bench_agg_result VALUES (
+                batch_size,
+                aggregate,
+                round((extract(epoch FROM clock_timestamp() - started) * 1000000 / row_count)::NUMERIC, 3)
+            );
+        END LOOP;
+    END LOOP;
+END $$;
+
+SELECT aggregate, batch_size, us_per_row FROM bench_agg_result ORDER BY aggregate, batch_size;
+
+DROP TABLE bench_agg_result;
+RESET max_parallel_workers_per_gather;
+DROP TABLE bench_agg;
@@ -2,12 +2,168 @@ use pgx::*;
 
 extension_sql!(
     r#"
+    -- The state functions buffer the values in the `internal` state, and accumulate them by an ecall per batch.
+    -- They are not STRICT to allocate the state on the first row, skipping NULL values by themselves.
+    CREATE OR REPLACE FUNCTION encinteger_avg_state_func(internal, EncInteger) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_avg_state_func';
+    CREATE OR REPLACE FUNCTION encinteger_sum_state_func(internal, EncInteger) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_sum_state_func';
+    CREATE OR REPLACE FUNCTION encinteger_count_state_func(internal, EncInteger) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_count_state_func';
+    CREATE OR REPLACE FUNCTION encinteger_min_state_func(internal, EncInteger) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_min_state_func';
+    CREATE OR REPLACE FUNCTION encinteger_max_state_func(internal, EncInteger) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_max_state_func';
+    CREATE OR REPLACE FUNCTION encbigint_sum_state_func(internal, EncBigInt) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_sum_state_func';
+    CREATE OR REPLACE FUNCTION encbigint_min_state_func(internal, EncBigInt) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_min_state_func';
+    CREATE OR REPLACE FUNCTION encbigint_max_state_func(internal, EncBigInt) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_max_state_func';
+    CREATE OR REPLACE FUNCTION encreal_sum_state_func(internal, EncReal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encreal_sum_state_func';
+    CREATE OR REPLACE FUNCTION encreal_avg_state_func(internal, EncReal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encreal_avg_state_func';
+
+    -- The combine functions merge the states of parallel workers by an ecall.
+    -- They are not STRICT since either state is NULL if a worker scanned no rows.
+    CREATE OR REPLACE FUNCTION encinteger_avg_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_avg_combine_func';
+    CREATE OR REPLACE FUNCTION encinteger_sum_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_sum_combine_func';
+    CREATE OR REPLACE FUNCTION encinteger_count_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_count_combine_func';
+    CREATE OR REPLACE FUNCTION encinteger_min_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_min_combine_func';
+    CREATE OR REPLACE FUNCTION encinteger_max_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_max_combine_func';
+    CREATE OR REPLACE FUNCTION encbigint_sum_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_sum_combine_func';
+    CREATE OR REPLACE FUNCTION encbigint_min_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_min_combine_func';
+    CREATE OR REPLACE FUNCTION encbigint_max_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_max_combine_func';
+    CREATE OR REPLACE FUNCTION encreal_sum_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encreal_sum_combine_func';
+    CREATE OR REPLACE FUNCTION encreal_avg_combine_func(internal, internal) RETURNS internal
+        LANGUAGE C PARALLEL SAFE AS 'MODULE_PATHNAME', 'encreal_avg_combine_func';
+
+    -- The states are serialized by `*_serial_func` to be sent from parallel workers.
+    CREATE OR REPLACE FUNCTION encinteger_aggregate_deserial_func(bytea, internal) RETURNS internal
+        LANGUAGE C STRICT PARALLEL SAFE AS 'MODULE_PATHNAME', 'encinteger_aggregate_deserial_func';
+    CREATE OR REPLACE FUNCTION encbigint_aggregate_deserial_func(bytea, internal) RETURNS internal
+        LANGUAGE C STRICT PARALLEL SAFE AS 'MODULE_PATHNAME', 'encbigint_aggregate_deserial_func';
+    CREATE OR REPLACE FUNCTION encreal_aggregate_deserial_func(bytea, internal) RETURNS internal
+        LANGUAGE C STRICT PARALLEL SAFE AS 'MODULE_PATHNAME', 'encreal_aggregate_deserial_func';
+
     CREATE AGGREGATE AVG (EncInteger)
     (
         sfunc = encinteger_avg_state_func,
-        stype = EncAvgState,
+        stype = internal,
         finalfunc = encinteger_avg_final_func,
-        initcond = '{ "current_state": null }'
+        combinefunc = encinteger_avg_combine_func,
+        serialfunc = encinteger_aggregate_serial_func,
+        deserialfunc = encinteger_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE SUM (EncInteger)
+    (
+        sfunc = encinteger_sum_state_func,
+        stype = internal,
+        finalfunc = encinteger_sum_final_func,
+        combinefunc = encinteger_sum_combine_func,
+        serialfunc = encinteger_aggregate_serial_func,
+        deserialfunc = encinteger_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE COUNT (EncInteger)
+    (
+        sfunc = encinteger_count_state_func,
+        stype = internal,
+        finalfunc = encinteger_count_final_func,
+        combinefunc = encinteger_count_combine_func,
+        serialfunc = encinteger_aggregate_serial_func,
+        deserialfunc = encinteger_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE MIN (EncInteger)
+    (
+        sfunc = encinteger_min_state_func,
+        stype = internal,
+        finalfunc = encinteger_min_final_func,
+        combinefunc = encinteger_min_combine_func,
+        serialfunc = encinteger_aggregate_serial_func,
+        deserialfunc = encinteger_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE MAX (EncInteger)
+    (
+        sfunc = encinteger_max_state_func,
+        stype = internal,
+        finalfunc = encinteger_max_final_func,
+        combinefunc = encinteger_max_combine_func,
+        serialfunc = encinteger_aggregate_serial_func,
+        deserialfunc = encinteger_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE SUM (EncBigInt)
+    (
+        sfunc = encbigint_sum_state_func,
+        stype = internal,
+        finalfunc = encbigint_sum_final_func,
+        combinefunc = encbigint_sum_combine_func,
+        serialfunc = encbigint_aggregate_serial_func,
+        deserialfunc = encbigint_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE MIN (EncBigInt)
+    (
+        sfunc = encbigint_min_state_func,
+        stype = internal,
+        finalfunc = encbigint_min_final_func,
+        combinefunc = encbigint_min_combine_func,
+        serialfunc = encbigint_aggregate_serial_func,
+        deserialfunc = encbigint_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE MAX (EncBigInt)
+    (
+        sfunc = encbigint_max_state_func,
+        stype = internal,
+        finalfunc = encbigint_max_final_func,
+        combinefunc = encbigint_max_combine_func,
+        serialfunc = encbigint_aggregate_serial_func,
+        deserialfunc = encbigint_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE SUM (EncReal)
+    (
+        sfunc = encreal_sum_state_func,
+        stype = internal,
+        finalfunc = encreal_sum_final_func,
+        combinefunc = encreal_sum_combine_func,
+        serialfunc = encreal_aggregate_serial_func,
+        deserialfunc = encreal_aggregate_deserial_func,
+        parallel = safe
+    );
+
+    CREATE AGGREGATE AVG (EncReal)
+    (
+        sfunc = encreal_avg_state_func,
+        stype = internal,
+        finalfunc = encreal_avg_final_func,
+        combinefunc = encreal_avg_combine_func,
+        serialfunc = encreal_aggregate_serial_func,
+        deserialfunc = encreal_aggregate_deserial_func,
+        parallel = safe
     );
     "#
 );
@@ -1,27 +1,44 @@
 use crate::{
-    init::Enclave,
-    typ::{EncAvgState, EncInteger},
+    init::{Enclave, AGGREGATE_BATCH_SIZE},
+    typ::{AggregateBuffer, EncAggregateBuffer, EncBigInt, EncInteger, EncReal, EncTag, EncText},
 };
 use frame_host::ecall_controller::EcallController;
 use module_encrypted_sql_ops_ecall_types::{
     ecall_cmd::*,
     enc_type::{
-        enc_aggregate_state::EncAvgState as ModuleEncAvgState, EncInteger as ModuleEncInteger,
+        enc_aggregate_state::EncAggregateState as ModuleEncAggregateState,
+        EncBigInt as ModuleEncBigInt, EncInteger as ModuleEncInteger, EncReal as ModuleEncReal,
+        EncTag as ModuleEncTag, EncText as ModuleEncText, EncType,
     },
 };
 use module_encrypted_sql_ops_host::controller::{
-    host_types::{HostEncAvgStateWithNext, HostInputEncAvgState, HostPlainInteger},
+    host_types::{
+        HostAggregateResult, HostClientCiphertext, HostEncAggregateState,
+        HostEncAggregateStateWithBatch, HostEncIntegerForClient, HostEncIntegerWithContext,
+        HostEncPair, HostEncValue, HostGetClientKey, HostPlainIntegerWithContext,
+        HostPlainWithContext,
+    },
     {
-        encinteger_avg_final_func::EncIntegerAvgFinalFuncController,
-        encinteger_avg_state_func::EncIntegerAvgStateFuncController,
+        enc_aggregate_combine_func::EncAggregateCombineFuncController,
+        enc_aggregate_final_func::EncAggregateFinalFuncController,
+        enc_aggregate_state_func::EncAggregateStateFuncController, enc_cmp::EncCmpController,
+        enc_eq::EncEqController, enc_from::EncFromController, enc_tag::EncTagController,
         encinteger_from::EncIntegerFromController,
+        encinteger_from_client::EncIntegerFromClientController,
+        encinteger_migrate::EncIntegerMigrateController,
+        encinteger_to_client::EncIntegerToClientController, get_client_key::GetClientKeyController,
     },
 };
 use pgx::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use std::cmp::Ordering;
 
+/// `context`, e.g. `'table.column'`, is authenticated with the value,
+/// so that it can't be copied to another column or aggregated with its values. It must not be empty.
 #[pg_extern]
-fn encinteger_from(raw_integer: i32) -> EncInteger {
-    let host_input = HostPlainInteger::new(raw_integer);
+fn encinteger_from(raw_integer: i32, context: &str) -> EncInteger {
+    let host_input = HostPlainIntegerWithContext::new(raw_integer, context.to_string());
     let eid = Enclave::global().geteid();
 
     let host_output = EncIntegerFromController::run(host_input, ENCINTEGER_FROM, eid)
@@ -35,40 +52,882 @@ fn encinteger_from(raw_integer: i32) -> EncInteger {
     EncInteger::from(ModuleEncInteger::from(host_output))
 }
 
+/// `context` is authenticated with the value as in `ENCINTEGER_FROM()`.
 #[pg_extern]
-fn encinteger_avg_state_func(
-    internal_state: EncAvgState,
-    next_data_value: EncInteger,
-) -> EncAvgState {
-    let host_input = HostEncAvgStateWithNext::new(
-        ModuleEncAvgState::from(internal_state),
-        ModuleEncInteger::from(next_data_value),
-    );
+fn encbigint_from(raw_bigint: i64, context: &str) -> EncBigInt {
+    EncBigInt::from(from::<ModuleEncBigInt>(raw_bigint, context, ENCBIGINT_FROM))
+}
+
+/// `context` is authenticated with the value as in `ENCINTEGER_FROM()`.
+#[pg_extern]
+fn encreal_from(raw_real: f32, context: &str) -> EncReal {
+    EncReal::from(from::<ModuleEncReal>(raw_real, context, ENCREAL_FROM))
+}
+
+/// `context` is authenticated with the value as in `ENCINTEGER_FROM()`.
+/// The text is padded up to a multiple of the bucket size of the enclave, so that its exact length is not revealed.
+#[pg_extern]
+fn enctext_from(raw_text: &str, context: &str) -> EncText {
+    EncText::from(from::<ModuleEncText>(
+        raw_text.to_string(),
+        context,
+        ENCTEXT_FROM,
+    ))
+}
+
+fn from<E: EncType>(raw: E::Raw, context: &str, cmd: u32) -> E {
+    let host_input = HostPlainWithContext::new(raw, context.to_string());
+    let eid = Enclave::global().geteid();
+
+    EncFromController::<E>::run(host_input, cmd, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to encrypt raw value in enclave (Enclave ID: {}, command: {}), {:?}",
+                eid, cmd, e
+            )
+        })
+        .into_inner()
+}
+
+/// Re-encrypt a value, e.g. a legacy one, in the current format.
+/// The values without context are bound to `context`, while the others must be in it already.
+/// It's the only function accepting the values without context.
+#[pg_extern]
+fn encinteger_migrate(value: EncInteger, context: &str) -> EncInteger {
+    let host_input =
+        HostEncIntegerWithContext::new(ModuleEncInteger::from(value), context.to_string());
     let eid = Enclave::global().geteid();
 
-    let host_output =
-        EncIntegerAvgStateFuncController::run(host_input, ENCINTEGER_AVG_STATE_FUNC, eid)
-            .unwrap_or_else(|e| {
-                panic!(
-                    "failed to calculate next avg state in enclave (Enclave ID: {}), {:?}",
-                    eid, e
-                )
-            });
+    let host_output = EncIntegerMigrateController::run(host_input, ENCINTEGER_MIGRATE, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to migrate ENCINTEGER in enclave (Enclave ID: {}), {:?}",
+                eid, e
+            )
+        });
 
-    ModuleEncAvgState::from(host_output).into()
+    EncInteger::from(ModuleEncInteger::from(host_output))
 }
 
+/// The public key which clients encrypt values to, with the attestation evidence binding it to the enclave,
+/// as `{"public_key": "<hex>", "evidence": {...}}`.
+/// Clients verify it by the client library before encrypting any value.
 #[pg_extern]
-fn encinteger_avg_final_func(internal_state: EncAvgState) -> f32 {
-    let host_input = HostInputEncAvgState::new(ModuleEncAvgState::from(internal_state));
+fn encrypted_sql_ops_client_key() -> Json {
     let eid = Enclave::global().geteid();
 
-    EncIntegerAvgFinalFuncController::run(host_input, ENCINTEGER_AVG_FINAL_FUNC, eid)
+    let host_output = GetClientKeyController::run(HostGetClientKey, GET_CLIENT_KEY, eid)
         .unwrap_or_else(|e| {
             panic!(
-                "failed to finalize avg state in enclave (Enclave ID: {}), {:?}",
+                "failed to attest the client key in enclave (Enclave ID: {}), {:?}",
                 eid, e
             )
+        });
+    let evidence: serde_json::Value = serde_json::from_slice(host_output.evidence())
+        .unwrap_or_else(|e| panic!("failed to parse the attestation evidence, {:?}", e));
+
+    Json(json!({
+        "public_key": hex::encode(host_output.public_key()),
+        "evidence": evidence,
+    }))
+}
+
+/// Re-encrypt an INTEGER encrypted by a client into `ENCINTEGER`, so that the plain value never appears in SQL.
+/// The context is the one chosen by the client.
+#[pg_extern]
+fn encinteger_from_client(ciphertext: &[u8]) -> EncInteger {
+    let host_input = HostClientCiphertext::from(ciphertext.to_vec());
+    let eid = Enclave::global().geteid();
+
+    let host_output = EncIntegerFromClientController::run(host_input, ENCINTEGER_FROM_CLIENT, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to re-encrypt client INTEGER in enclave (Enclave ID: {}), {:?}",
+                eid, e
+            )
+        });
+
+    EncInteger::from(ModuleEncInteger::from(host_output))
+}
+
+/// Re-encrypt a value to a client's public key instead of decrypting it.
+/// The key must be listed in the owner config of the enclave.
+#[pg_extern]
+fn encinteger_to_client(value: EncInteger, client_key: &[u8]) -> Vec<u8> {
+    let host_input =
+        HostEncIntegerForClient::new(ModuleEncInteger::from(value), client_key.to_vec());
+    let eid = Enclave::global().geteid();
+
+    EncIntegerToClientController::run(host_input, ENCINTEGER_TO_CLIENT, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to re-encrypt ENCINTEGER to client in enclave (Enclave ID: {}), {:?}",
+                eid, e
+            )
+        })
+        .into_inner()
+}
+
+/// Declares a hand-written function in the version-1 calling convention, as `PG_FUNCTION_INFO_V1()` in C.
+macro_rules! pg_function_info_v1 {
+    ($finfo:ident) => {
+        #[no_mangle]
+        pub extern "C" fn $finfo() -> &'static pg_sys::Pg_finfo_record {
+            const V1_API: pg_sys::Pg_finfo_record = pg_sys::Pg_finfo_record { api_version: 1 };
+            &V1_API
+        }
+    };
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_avg_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_avg_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe {
+        aggregate_state_func::<EncInteger, ModuleEncInteger>(fcinfo, ENCINTEGER_AVG_STATE_FUNC)
+    }
+}
+
+/// NaN for no values, unlike AVG of `ENCREAL` which returns NULL.
+#[pg_extern(parallel_safe)]
+fn encinteger_avg_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncInteger>>>,
+) -> f32 {
+    match aggregate_final_func(internal_state, ENCINTEGER_AVG_FINAL_FUNC) {
+        HostAggregateResult::PlainReal(avg) => avg,
+        other => panic!("AVG is expected to be in plain text, but got {:?}", other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_sum_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_sum_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe {
+        aggregate_state_func::<EncInteger, ModuleEncInteger>(fcinfo, ENCINTEGER_SUM_STATE_FUNC)
+    }
+}
+
+#[pg_extern(parallel_safe)]
+fn encinteger_sum_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncInteger>>>,
+) -> Option<EncBigInt> {
+    match aggregate_final_func(internal_state, ENCINTEGER_SUM_FINAL_FUNC) {
+        HostAggregateResult::EncBigInt(encbigint) => Some(EncBigInt::from(encbigint)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_count_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_count_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe {
+        aggregate_state_func::<EncInteger, ModuleEncInteger>(fcinfo, ENCINTEGER_COUNT_STATE_FUNC)
+    }
+}
+
+#[pg_extern(parallel_safe)]
+fn encinteger_count_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncInteger>>>,
+) -> i64 {
+    match aggregate_final_func(internal_state, ENCINTEGER_COUNT_FINAL_FUNC) {
+        HostAggregateResult::PlainBigInt(count) => count,
+        other => panic!("COUNT is expected to be in plain text, but got {:?}", other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_min_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_min_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe {
+        aggregate_state_func::<EncInteger, ModuleEncInteger>(fcinfo, ENCINTEGER_MIN_STATE_FUNC)
+    }
+}
+
+#[pg_extern(parallel_safe)]
+fn encinteger_min_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncInteger>>>,
+) -> Option<EncInteger> {
+    match aggregate_final_func(internal_state, ENCINTEGER_MIN_FINAL_FUNC) {
+        HostAggregateResult::EncInteger(encinteger) => Some(EncInteger::from(encinteger)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_max_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_max_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe {
+        aggregate_state_func::<EncInteger, ModuleEncInteger>(fcinfo, ENCINTEGER_MAX_STATE_FUNC)
+    }
+}
+
+#[pg_extern(parallel_safe)]
+fn encinteger_max_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncInteger>>>,
+) -> Option<EncInteger> {
+    match aggregate_final_func(internal_state, ENCINTEGER_MAX_FINAL_FUNC) {
+        HostAggregateResult::EncInteger(encinteger) => Some(EncInteger::from(encinteger)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_sum_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_sum_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_state_func::<EncBigInt, ModuleEncBigInt>(fcinfo, ENCBIGINT_SUM_STATE_FUNC) }
+}
+
+#[pg_extern(parallel_safe)]
+fn encbigint_sum_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncBigInt>>>,
+) -> Option<EncBigInt> {
+    match aggregate_final_func(internal_state, ENCBIGINT_SUM_FINAL_FUNC) {
+        HostAggregateResult::EncBigInt(encbigint) => Some(EncBigInt::from(encbigint)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_min_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_min_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_state_func::<EncBigInt, ModuleEncBigInt>(fcinfo, ENCBIGINT_MIN_STATE_FUNC) }
+}
+
+#[pg_extern(parallel_safe)]
+fn encbigint_min_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncBigInt>>>,
+) -> Option<EncBigInt> {
+    match aggregate_final_func(internal_state, ENCBIGINT_MIN_FINAL_FUNC) {
+        HostAggregateResult::EncBigInt(encbigint) => Some(EncBigInt::from(encbigint)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_max_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_max_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_state_func::<EncBigInt, ModuleEncBigInt>(fcinfo, ENCBIGINT_MAX_STATE_FUNC) }
+}
+
+#[pg_extern(parallel_safe)]
+fn encbigint_max_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncBigInt>>>,
+) -> Option<EncBigInt> {
+    match aggregate_final_func(internal_state, ENCBIGINT_MAX_FINAL_FUNC) {
+        HostAggregateResult::EncBigInt(encbigint) => Some(EncBigInt::from(encbigint)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encreal_sum_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encreal_sum_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_state_func::<EncReal, ModuleEncReal>(fcinfo, ENCREAL_SUM_STATE_FUNC) }
+}
+
+#[pg_extern(parallel_safe)]
+fn encreal_sum_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncReal>>>,
+) -> Option<EncReal> {
+    match aggregate_final_func(internal_state, ENCREAL_SUM_FINAL_FUNC) {
+        HostAggregateResult::EncReal(encreal) => Some(EncReal::from(encreal)),
+        other => encrypted_result(other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encreal_avg_state_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encreal_avg_state_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_state_func::<EncReal, ModuleEncReal>(fcinfo, ENCREAL_AVG_STATE_FUNC) }
+}
+
+/// NULL for no values, unlike AVG of `ENCINTEGER` which returns NaN.
+#[pg_extern(parallel_safe)]
+fn encreal_avg_final_func(
+    internal_state: Option<Internal<EncAggregateBuffer<ModuleEncReal>>>,
+) -> Option<f32> {
+    match aggregate_final_func(internal_state, ENCREAL_AVG_FINAL_FUNC) {
+        HostAggregateResult::PlainReal(avg) => Some(avg),
+        HostAggregateResult::Null => None,
+        other => panic!("AVG is expected to be in plain text, but got {:?}", other),
+    }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_avg_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_avg_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncInteger>(fcinfo, ENCINTEGER_AVG_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_sum_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_sum_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncInteger>(fcinfo, ENCINTEGER_SUM_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_count_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_count_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncInteger>(fcinfo, ENCINTEGER_COUNT_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_min_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_min_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncInteger>(fcinfo, ENCINTEGER_MIN_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_max_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_max_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncInteger>(fcinfo, ENCINTEGER_MAX_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_sum_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_sum_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncBigInt>(fcinfo, ENCBIGINT_SUM_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_min_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_min_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncBigInt>(fcinfo, ENCBIGINT_MIN_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_max_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_max_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncBigInt>(fcinfo, ENCBIGINT_MAX_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encreal_sum_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encreal_sum_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncReal>(fcinfo, ENCREAL_SUM_COMBINE_FUNC) }
+}
+
+pg_function_info_v1!(pg_finfo_encreal_avg_combine_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encreal_avg_combine_func(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+    unsafe { aggregate_combine_func::<ModuleEncReal>(fcinfo, ENCREAL_AVG_COMBINE_FUNC) }
+}
+
+/// Serialize function of the aggregates of `ENCINTEGER`, whose states are sent from parallel workers.
+#[pg_extern(parallel_safe)]
+fn encinteger_aggregate_serial_func(
+    internal_state: Internal<EncAggregateBuffer<ModuleEncInteger>>,
+) -> Vec<u8> {
+    aggregate_serial_func(internal_state)
+}
+
+pg_function_info_v1!(pg_finfo_encinteger_aggregate_deserial_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encinteger_aggregate_deserial_func(
+    fcinfo: pg_sys::FunctionCallInfo,
+) -> pg_sys::Datum {
+    unsafe { deserial_func::<ModuleEncAggregateState, ModuleEncInteger>(fcinfo) }
+}
+
+/// Serialize function of the aggregates of `ENCBIGINT`, whose states are sent from parallel workers.
+#[pg_extern(parallel_safe)]
+fn encbigint_aggregate_serial_func(
+    internal_state: Internal<EncAggregateBuffer<ModuleEncBigInt>>,
+) -> Vec<u8> {
+    aggregate_serial_func(internal_state)
+}
+
+pg_function_info_v1!(pg_finfo_encbigint_aggregate_deserial_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encbigint_aggregate_deserial_func(
+    fcinfo: pg_sys::FunctionCallInfo,
+) -> pg_sys::Datum {
+    unsafe { deserial_func::<ModuleEncAggregateState, ModuleEncBigInt>(fcinfo) }
+}
+
+/// Serialize function of the aggregates of `ENCREAL`, whose states are sent from parallel workers.
+#[pg_extern(parallel_safe)]
+fn encreal_aggregate_serial_func(
+    internal_state: Internal<EncAggregateBuffer<ModuleEncReal>>,
+) -> Vec<u8> {
+    aggregate_serial_func(internal_state)
+}
+
+pg_function_info_v1!(pg_finfo_encreal_aggregate_deserial_func);
+#[pg_guard]
+#[no_mangle]
+pub extern "C" fn encreal_aggregate_deserial_func(
+    fcinfo: pg_sys::FunctionCallInfo,
+) -> pg_sys::Datum {
+    unsafe { deserial_func::<ModuleEncAggregateState, ModuleEncReal>(fcinfo) }
+}
+
+/// Appends the next value, unless NULL, to the buffer allocated in the memory context of the aggregate,
+/// and accumulates the buffer by `flush` once it has `encrypted_sql_ops.aggregate_batch_size` values.
+///
+/// # Safety
+///
+/// `fcinfo` must be the call of a state function whose first argument is NULL or the buffer returned by the previous call.
+unsafe fn buffer_next<T, S, E>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    cmd: u32,
+    initial_state: S,
+    flush: fn(&mut AggregateBuffer<S, E>),
+) -> pg_sys::Datum
+where
+    T: FromDatum + Into<E>,
+{
+    let mut agg_context: pg_sys::MemoryContext = std::ptr::null_mut();
+    if pg_sys::AggCheckCallContext(fcinfo, &mut agg_context) == 0 {
+        panic!(
+            "aggregate state function (command: {}) is called in non-aggregate context",
+            cmd
+        );
+    }
+
+    let buffer = match pg_getarg_pointer::<AggregateBuffer<S, E>>(fcinfo, 0) {
+        Some(buffer) => buffer,
+        None => PgMemoryContexts::For(agg_context)
+            .leak_and_drop_on_delete(AggregateBuffer::new(cmd, initial_state)),
+    };
+
+    if let Some(next_data_value) = pg_getarg::<T>(fcinfo, 1) {
+        (*buffer).batch.push(next_data_value.into());
+        if (*buffer).batch.len() >= AGGREGATE_BATCH_SIZE.get() as usize {
+            flush(&mut *buffer);
+        }
+    }
+
+    buffer as pg_sys::Datum
+}
+
+unsafe fn aggregate_state_func<T, E>(fcinfo: pg_sys::FunctionCallInfo, cmd: u32) -> pg_sys::Datum
+where
+    T: FromDatum + Into<E>,
+    E: EncType,
+{
+    buffer_next::<T, _, E>(
+        fcinfo,
+        cmd,
+        ModuleEncAggregateState::Initial,
+        flush_aggregate,
+    )
+}
+
+fn flush_aggregate<E: EncType>(buffer: &mut EncAggregateBuffer<E>) {
+    if buffer.batch.is_empty() {
+        return;
+    }
+    let host_input = HostEncAggregateStateWithBatch::new(
+        buffer.state.clone(),
+        std::mem::take(&mut buffer.batch),
+    );
+    let eid = Enclave::global().geteid();
+
+    let host_output = EncAggregateStateFuncController::<E>::run(host_input, buffer.cmd, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to calculate next aggregate state in enclave (Enclave ID: {}, command: {}), {:?}",
+                eid, buffer.cmd, e
+            )
+        });
+
+    buffer.state = ModuleEncAggregateState::from(host_output);
+}
+
+/// Accumulates the values left in the buffer, if any, before finalizing its state.
+/// The buffer stays valid for further values, e.g. in window aggregates.
+fn aggregate_final_func<E: EncType>(
+    internal_state: Option<Internal<EncAggregateBuffer<E>>>,
+    cmd: u32,
+) -> HostAggregateResult {
+    let state = match internal_state {
+        Some(Internal(mut buffer)) => {
+            flush_aggregate(&mut buffer);
+            buffer.state.clone()
+        }
+        None => ModuleEncAggregateState::Initial,
+    };
+    let host_input = HostEncAggregateState::from(state);
+    let eid = Enclave::global().geteid();
+
+    EncAggregateFinalFuncController::run(host_input, cmd, eid).unwrap_or_else(|e| {
+        panic!(
+            "failed to finalize aggregate state in enclave (Enclave ID: {}, command: {}), {:?}",
+            eid, cmd, e
+        )
+    })
+}
+
+/// Merges the buffer of another partial aggregate, the second argument, into the first one,
+/// which is allocated in the memory context of the aggregate if NULL.
+///
+/// # Safety
+///
+/// `fcinfo` must be the call of a combine function whose arguments are NULL or the buffers of the aggregate.
+unsafe fn combine_func<S, E>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    cmd: u32,
+    flush: fn(&mut AggregateBuffer<S, E>),
+    combine: fn(S, S, u32) -> S,
+) -> pg_sys::Datum
+where
+    S: Clone,
+    E: Clone,
+{
+    let mut agg_context: pg_sys::MemoryContext = std::ptr::null_mut();
+    if pg_sys::AggCheckCallContext(fcinfo, &mut agg_context) == 0 {
+        panic!(
+            "aggregate combine function (command: {}) is called in non-aggregate context",
+            cmd
+        );
+    }
+
+    let other = match pg_getarg_pointer::<AggregateBuffer<S, E>>(fcinfo, 1) {
+        // The second argument is not modified, while it's flushed.
+        Some(other) => {
+            let mut other = (*other).clone();
+            flush(&mut other);
+            other
+        }
+        None if pg_arg_is_null(fcinfo, 0) => return pg_return_null(fcinfo),
+        None => return pg_getarg_datum_raw(fcinfo, 0),
+    };
+
+    let buffer = match pg_getarg_pointer::<AggregateBuffer<S, E>>(fcinfo, 0) {
+        Some(buffer) => {
+            flush(&mut *buffer);
+            (*buffer).state = combine((*buffer).state.clone(), other.state, cmd);
+            buffer
+        }
+        None => PgMemoryContexts::For(agg_context).leak_and_drop_on_delete(other),
+    };
+
+    buffer as pg_sys::Datum
+}
+
+unsafe fn aggregate_combine_func<E: EncType>(
+    fcinfo: pg_sys::FunctionCallInfo,
+    cmd: u32,
+) -> pg_sys::Datum {
+    combine_func::<_, E>(fcinfo, cmd, flush_aggregate, combine_aggregate)
+}
+
+fn combine_aggregate(
+    state: ModuleEncAggregateState,
+    other: ModuleEncAggregateState,
+    cmd: u32,
+) -> ModuleEncAggregateState {
+    // Either partial aggregate may have only NULL values.
+    match (state, other) {
+        (state, ModuleEncAggregateState::Initial) => state,
+        (ModuleEncAggregateState::Initial, other) => other,
+        (state, other) => {
+            let host_input = HostEncPair::new(state, other);
+            let eid = Enclave::global().geteid();
+
+            let host_output = EncAggregateCombineFuncController::run(host_input, cmd, eid)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "failed to combine aggregate states in enclave (Enclave ID: {}, command: {}), {:?}",
+                        eid, cmd, e
+                    )
+                });
+
+            ModuleEncAggregateState::from(host_output)
+        }
+    }
+}
+
+fn aggregate_serial_func<E: EncType>(internal_state: Internal<EncAggregateBuffer<E>>) -> Vec<u8> {
+    let Internal(mut buffer) = internal_state;
+    flush_aggregate(&mut buffer);
+    serialize(&*buffer)
+}
+
+/// The values are accumulated before serialized, so only the encrypted state is sent.
+fn serialize<S: Serialize, E: Serialize>(buffer: &AggregateBuffer<S, E>) -> Vec<u8> {
+    serde_json::to_vec(buffer)
+        .unwrap_or_else(|e| panic!("failed to serialize aggregate state, {:?}", e))
+}
+
+/// Deserializes the buffer sent from a parallel worker into the memory context of the aggregate.
+///
+/// # Safety
+///
+/// `fcinfo` must be the call of a deserialize function whose first argument is serialized from the same buffer type.
+unsafe fn deserial_func<S, E>(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum
+where
+    S: DeserializeOwned,
+    E: DeserializeOwned,
+{
+    let mut agg_context: pg_sys::MemoryContext = std::ptr::null_mut();
+    if pg_sys::AggCheckCallContext(fcinfo, &mut agg_context) == 0 {
+        panic!("aggregate deserialize function is called in non-aggregate context");
+    }
+
+    let serialized = pg_getarg::<&[u8]>(fcinfo, 0).expect("serialized state is NULL");
+    let buffer: AggregateBuffer<S, E> = serde_json::from_slice(serialized)
+        .unwrap_or_else(|e| panic!("failed to deserialize aggregate state, {:?}", e));
+
+    PgMemoryContexts::For(agg_context).leak_and_drop_on_delete(buffer) as pg_sys::Datum
+}
+
+/// The enclave declares the results of SUM, MIN and MAX to be encrypted, in BIGINT for SUM of INTEGER
+/// and in the type of the aggregated values otherwise, so any other result than them must be NULL.
+fn encrypted_result<T>(result: HostAggregateResult) -> Option<T> {
+    match result {
+        HostAggregateResult::Null => None,
+        other => panic!(
+            "aggregate result is expected to be encrypted in the type of the values, but got {:?}",
+            other
+        ),
+    }
+}
+
+/// Support function 1 of the btree operator class, which is negative, zero or positive
+/// as `lhs` is less than, equal to or greater than `rhs`.
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_cmp(lhs: EncInteger, rhs: EncInteger) -> i32 {
+    match cmp::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_CMP) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_eq(lhs: EncInteger, rhs: EncInteger) -> bool {
+    eq::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_ne(lhs: EncInteger, rhs: EncInteger) -> bool {
+    !eq::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_lt(lhs: EncInteger, rhs: EncInteger) -> bool {
+    cmp::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_CMP) == Ordering::Less
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_le(lhs: EncInteger, rhs: EncInteger) -> bool {
+    cmp::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_CMP) != Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_gt(lhs: EncInteger, rhs: EncInteger) -> bool {
+    cmp::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_CMP) == Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_ge(lhs: EncInteger, rhs: EncInteger) -> bool {
+    cmp::<ModuleEncInteger>(lhs.into(), rhs.into(), ENCINTEGER_CMP) != Ordering::Less
+}
+
+/// Support function 1 of the btree operator class, which is negative, zero or positive
+/// as `lhs` is less than, equal to or greater than `rhs`.
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_cmp(lhs: EncBigInt, rhs: EncBigInt) -> i32 {
+    match cmp::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_CMP) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_eq(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    eq::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_ne(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    !eq::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_lt(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    cmp::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_CMP) == Ordering::Less
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_le(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    cmp::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_CMP) != Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_gt(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    cmp::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_CMP) == Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_ge(lhs: EncBigInt, rhs: EncBigInt) -> bool {
+    cmp::<ModuleEncBigInt>(lhs.into(), rhs.into(), ENCBIGINT_CMP) != Ordering::Less
+}
+
+/// Support function 1 of the btree operator class, which is negative, zero or positive
+/// as `lhs` is less than, equal to or greater than `rhs`.
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_cmp(lhs: EncReal, rhs: EncReal) -> i32 {
+    match cmp::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_CMP) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_eq(lhs: EncReal, rhs: EncReal) -> bool {
+    eq::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_ne(lhs: EncReal, rhs: EncReal) -> bool {
+    !eq::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_lt(lhs: EncReal, rhs: EncReal) -> bool {
+    cmp::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_CMP) == Ordering::Less
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_le(lhs: EncReal, rhs: EncReal) -> bool {
+    cmp::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_CMP) != Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_gt(lhs: EncReal, rhs: EncReal) -> bool {
+    cmp::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_CMP) == Ordering::Greater
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_ge(lhs: EncReal, rhs: EncReal) -> bool {
+    cmp::<ModuleEncReal>(lhs.into(), rhs.into(), ENCREAL_CMP) != Ordering::Less
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn enctext_eq(lhs: EncText, rhs: EncText) -> bool {
+    eq::<ModuleEncText>(lhs.into(), rhs.into(), ENCTEXT_EQ)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn enctext_ne(lhs: EncText, rhs: EncText) -> bool {
+    !eq::<ModuleEncText>(lhs.into(), rhs.into(), ENCTEXT_EQ)
+}
+
+/// Deterministic tag of the value, equal for the equal values in the same tag domain.
+/// Fails unless the context of the value is listed in `tagged_contexts` of the enclave's owner config.
+#[pg_extern(immutable, parallel_safe)]
+fn encinteger_tag(value: EncInteger) -> EncTag {
+    EncTag::from(tag::<ModuleEncInteger>(value.into(), ENCINTEGER_TAG))
+}
+
+/// Deterministic tag of the value as in `ENCINTEGER_TAG()`.
+#[pg_extern(immutable, parallel_safe)]
+fn encbigint_tag(value: EncBigInt) -> EncTag {
+    EncTag::from(tag::<ModuleEncBigInt>(value.into(), ENCBIGINT_TAG))
+}
+
+/// Deterministic tag of the value as in `ENCINTEGER_TAG()`.
+/// All the NaNs have the same tag, and so do `0` and `-0`.
+#[pg_extern(immutable, parallel_safe)]
+fn encreal_tag(value: EncReal) -> EncTag {
+    EncTag::from(tag::<ModuleEncReal>(value.into(), ENCREAL_TAG))
+}
+
+/// Deterministic tag of the value as in `ENCINTEGER_TAG()`.
+/// It's taken from the text without padding, so it doesn't depend on the bucket size.
+#[pg_extern(immutable, parallel_safe)]
+fn enctext_tag(value: EncText) -> EncTag {
+    EncTag::from(tag::<ModuleEncText>(value.into(), ENCTEXT_TAG))
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn enctag_eq(lhs: EncTag, rhs: EncTag) -> bool {
+    ModuleEncTag::from(lhs) == ModuleEncTag::from(rhs)
+}
+
+#[pg_extern(immutable, parallel_safe)]
+fn enctag_ne(lhs: EncTag, rhs: EncTag) -> bool {
+    ModuleEncTag::from(lhs) != ModuleEncTag::from(rhs)
+}
+
+/// Support function 1 of the hash operator class.
+///
+/// Tags are keyed hashes already, so their leading bytes are used as they are.
+#[pg_extern(immutable, parallel_safe)]
+fn enctag_hash(value: EncTag) -> i32 {
+    let tag = ModuleEncTag::from(value);
+    let mut bytes = [0u8; 4];
+    bytes
+        .iter_mut()
+        .zip(tag.as_slice())
+        .for_each(|(b, t)| *b = *t);
+    i32::from_be_bytes(bytes)
+}
+
+fn cmp<E: EncType>(lhs: E, rhs: E, cmd: u32) -> Ordering {
+    let host_input = HostEncPair::new(lhs, rhs);
+    let eid = Enclave::global().geteid();
+
+    EncCmpController::<E>::run(host_input, cmd, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to compare encrypted values in enclave (Enclave ID: {}, command: {}), {:?}",
+                eid, cmd, e
+            )
+        })
+        .into()
+}
+
+fn eq<E: EncType>(lhs: E, rhs: E, cmd: u32) -> bool {
+    let host_input = HostEncPair::new(lhs, rhs);
+    let eid = Enclave::global().geteid();
+
+    EncEqController::<E>::run(host_input, cmd, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to compare encrypted values in enclave (Enclave ID: {}, command: {}), {:?}",
+                eid, cmd, e
+            )
+        })
+        .into()
+}
+
+fn tag<E: EncType>(value: E, cmd: u32) -> ModuleEncTag {
+    let host_input = HostEncValue::new(value);
+    let eid = Enclave::global().geteid();
+
+    EncTagController::<E>::run(host_input, cmd, eid)
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to tag encrypted value in enclave (Enclave ID: {}, command: {}), {:?}",
+                eid, cmd, e
+            )
         })
         .into()
 }
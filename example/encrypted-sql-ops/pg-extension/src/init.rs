@@ -1,6 +1,12 @@
 //! Initialization on extension library load.
 
-use frame_host::EnclaveDir;
+use frame_host::{ecall_controller::EcallController, EnclaveDir};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::LOAD_MASTER_KEY, enclave_types::MAX_AGGREGATE_BATCH_SIZE,
+};
+use module_encrypted_sql_ops_host::controller::{
+    host_types::HostLoadMasterKey, load_master_key::LoadMasterKeyController,
+};
 use once_cell::sync::OnceCell;
 use pgx::*;
 use sgx_urts::SgxEnclave;
@@ -8,6 +14,11 @@ use std::{env, ops::Deref};
 
 static ENCLAVE: OnceCell<Enclave> = OnceCell::new();
 
+/// `encrypted_sql_ops.aggregate_batch_size`, the number of values which the aggregates buffer
+/// before accumulating them by an ecall.
+pub(crate) static AGGREGATE_BATCH_SIZE: GucSetting<i32> =
+    GucSetting::new(MAX_AGGREGATE_BATCH_SIZE as i32);
+
 #[derive(Debug)]
 pub(crate) struct Enclave(SgxEnclave);
 
@@ -40,5 +51,26 @@ pub extern "C" fn _PG_init() {
         .init_enclave(is_debug)
         .expect("Failed to initialize enclave.");
 
+    // Fail on load rather than on the first query if the master key is unavailable.
+    let source = LoadMasterKeyController::run(HostLoadMasterKey, LOAD_MASTER_KEY, enclave.geteid())
+        .unwrap_or_else(|e| {
+            panic!(
+                "failed to load the master key in enclave (Enclave ID: {}), {:?}",
+                enclave.geteid(),
+                e
+            )
+        });
+    info!("encrypted-sql-ops master key: {:?}", source.into_inner());
+
     Enclave::init(enclave);
+
+    GucRegistry::define_int_guc(
+        "encrypted_sql_ops.aggregate_batch_size",
+        "Number of values accumulated by an ecall in the aggregates.",
+        "Larger batches take fewer enclave transitions per row. Set 1 to accumulate each row by an ecall.",
+        &AGGREGATE_BATCH_SIZE,
+        1,
+        MAX_AGGREGATE_BATCH_SIZE as i32,
+        GucContext::Userset,
+    );
 }
@@ -8,6 +8,7 @@
 mod aggregate;
 mod func;
 mod init;
+mod operator;
 mod typ;
 
 use pgx::*;
@@ -32,7 +33,7 @@ mod tests {
 
     fn prepare() {
         Spi::run("CREATE TABLE t (id INTEGER, c_enc ENCINTEGER)");
-        Spi::run("INSERT INTO t (id, c_enc) VALUES (1, ENCINTEGER_FROM(1)), (2, ENCINTEGER_FROM(2)), (3, ENCINTEGER_FROM(3)), (4, ENCINTEGER_FROM(4))");
+        Spi::run("INSERT INTO t (id, c_enc) VALUES (1, ENCINTEGER_FROM(1, 't.c_enc')), (2, ENCINTEGER_FROM(2, 't.c_enc')), (3, ENCINTEGER_FROM(3, 't.c_enc')), (4, ENCINTEGER_FROM(4, 't.c_enc'))");
     }
 
     fn encinteger_avg() -> f32 {
@@ -49,4 +50,298 @@ mod tests {
         assert_eq!(encinteger_avg(), 2.5);
         assert!(encinteger_avg_empty().is_nan());
     }
+
+    #[pg_test]
+    fn test_encinteger_aggregates() {
+        prepare();
+        // The encrypted results are revealed by AVG of themselves only for the test.
+        let reveal = |aggregate: &str| {
+            Spi::get_one::<f32>(&format!(
+                "SELECT AVG(r) FROM (SELECT {}(c_enc) AS r FROM t) s;",
+                aggregate
+            ))
+            .unwrap()
+        };
+        assert_eq!(reveal("MIN"), 1.0);
+        assert_eq!(reveal("MAX"), 4.0);
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT COUNT(c_enc) FROM t;").unwrap(),
+            4
+        );
+        // SUM is BIGINT as PostgreSQL's.
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT SUM(c_enc) = ENCBIGINT_FROM(10, 't.c_enc#SUM') FROM t;")
+                .unwrap(),
+            true
+        );
+        Spi::run("INSERT INTO t (id, c_enc) VALUES (5, ENCINTEGER_FROM(2147483647, 't.c_enc'))");
+        assert_eq!(
+            Spi::get_one::<bool>(
+                "SELECT SUM(c_enc) = ENCBIGINT_FROM(2147483657, 't.c_enc#SUM') FROM t;"
+            )
+            .unwrap(),
+            true
+        );
+    }
+
+    #[pg_test]
+    fn test_encinteger_aggregates_empty() {
+        prepare();
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT SUM(c_enc) IS NULL FROM t WHERE id > 4;").unwrap(),
+            true
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT MAX(c_enc) IS NULL FROM t WHERE id > 4;").unwrap(),
+            true
+        );
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT COUNT(c_enc) FROM t WHERE id > 4;").unwrap(),
+            0
+        );
+    }
+
+    #[pg_test]
+    fn test_aggregate_batch_size() {
+        Spi::run("CREATE TABLE g (c_enc ENCINTEGER)");
+        Spi::run("INSERT INTO g (c_enc) SELECT ENCINTEGER_FROM(i, 'g.c_enc') FROM generate_series(1, 10) i");
+        Spi::run("INSERT INTO g (c_enc) VALUES (NULL)");
+
+        // The batches of 3 values leave a value to be accumulated by the final functions.
+        for batch_size in &[1, 3, 10, 256] {
+            Spi::run(&format!(
+                "SET encrypted_sql_ops.aggregate_batch_size = {}",
+                batch_size
+            ));
+            assert_eq!(
+                Spi::get_one::<f32>("SELECT AVG(c_enc) FROM g;").unwrap(),
+                5.5
+            );
+            assert_eq!(
+                Spi::get_one::<bool>(
+                    "SELECT SUM(c_enc) = ENCBIGINT_FROM(55, 'g.c_enc#SUM') FROM g;"
+                )
+                .unwrap(),
+                true
+            );
+            assert_eq!(
+                Spi::get_one::<f32>("SELECT AVG(r) FROM (SELECT MAX(c_enc) AS r FROM g) s;")
+                    .unwrap(),
+                10.0
+            );
+            assert_eq!(
+                Spi::get_one::<i64>("SELECT COUNT(c_enc) FROM g;").unwrap(),
+                10
+            );
+        }
+    }
+
+    #[pg_test]
+    fn test_parallel_aggregates() {
+        Spi::run("CREATE TABLE p (c_int ENCINTEGER, c_bigint ENCBIGINT, c_real ENCREAL)");
+        Spi::run("INSERT INTO p (c_int, c_bigint, c_real) SELECT ENCINTEGER_FROM(i, 'p.c_int'), ENCBIGINT_FROM(i * 5000000, 'p.c_bigint'), ENCREAL_FROM(i * 0.5, 'p.c_real') FROM generate_series(1, 1000) i");
+        Spi::run("INSERT INTO p (c_int, c_bigint, c_real) VALUES (NULL, NULL, NULL)");
+        Spi::run("ANALYZE p");
+
+        let query = "SELECT AVG(c_int)::TEXT
+            || ',' || (SUM(c_int) = ENCBIGINT_FROM(500500, 'p.c_int#SUM'))::TEXT
+            || ',' || COUNT(c_int)::TEXT
+            || ',' || (MIN(c_int) = ENCINTEGER_FROM(1, 'p.c_int#MIN'))::TEXT
+            || ',' || (MAX(c_int) = ENCINTEGER_FROM(1000, 'p.c_int#MAX'))::TEXT
+            || ',' || (SUM(c_bigint) = ENCBIGINT_FROM(2502500000000, 'p.c_bigint#SUM'))::TEXT
+            || ',' || (MIN(c_bigint) = ENCBIGINT_FROM(5000000, 'p.c_bigint#MIN'))::TEXT
+            || ',' || (MAX(c_bigint) = ENCBIGINT_FROM(5000000000, 'p.c_bigint#MAX'))::TEXT
+            || ',' || (SUM(c_real) = ENCREAL_FROM(250250, 'p.c_real#SUM'))::TEXT
+            || ',' || AVG(c_real)::TEXT
+            FROM p";
+        let expected = "500.5,true,1000,true,true,true,true,true,true,250.25";
+
+        // The partial states of the workers, including the ones with small batches left, are combined.
+        Spi::run("SET parallel_setup_cost = 0");
+        Spi::run("SET parallel_tuple_cost = 0");
+        Spi::run("SET min_parallel_table_scan_size = 0");
+        Spi::run("SET max_parallel_workers_per_gather = 2");
+        Spi::run("SET encrypted_sql_ops.aggregate_batch_size = 7");
+        let plan = Spi::get_one::<Json>(&format!("EXPLAIN (FORMAT JSON) {}", query)).unwrap();
+        assert!(plan.0.to_string().contains("\"Partial Mode\":\"Partial\""));
+        assert_eq!(Spi::get_one::<String>(query).unwrap(), expected);
+
+        Spi::run("SET max_parallel_workers_per_gather = 0");
+        assert_eq!(Spi::get_one::<String>(query).unwrap(), expected);
+    }
+
+    #[pg_test]
+    fn test_encinteger_comparison() {
+        Spi::run("CREATE TABLE o (id INTEGER, c_enc ENCINTEGER)");
+        Spi::run("INSERT INTO o (id, c_enc) VALUES (1, ENCINTEGER_FROM(3, 'o.c_enc')), (2, ENCINTEGER_FROM(1, 'o.c_enc')), (3, ENCINTEGER_FROM(2, 'o.c_enc')), (4, ENCINTEGER_FROM(1, 'o.c_enc'))");
+
+        let ids = |query: &str| {
+            Spi::get_one::<String>(&format!(
+                "SELECT string_agg(id::TEXT, ',' ORDER BY id) FROM o WHERE {};",
+                query
+            ))
+            .unwrap()
+        };
+        assert_eq!(ids("c_enc = ENCINTEGER_FROM(1, 'o.c_enc')"), "2,4");
+        assert_eq!(ids("c_enc <> ENCINTEGER_FROM(1, 'o.c_enc')"), "1,3");
+        assert_eq!(ids("c_enc < ENCINTEGER_FROM(2, 'o.c_enc')"), "2,4");
+        assert_eq!(ids("c_enc <= ENCINTEGER_FROM(2, 'o.c_enc')"), "2,3,4");
+        assert_eq!(ids("c_enc > ENCINTEGER_FROM(2, 'o.c_enc')"), "1");
+        assert_eq!(ids("c_enc >= ENCINTEGER_FROM(2, 'o.c_enc')"), "1,3");
+
+        let sorted = Spi::get_one::<String>(
+            "SELECT string_agg(id::TEXT, ',') FROM (SELECT id FROM o ORDER BY c_enc DESC, id) s;",
+        )
+        .unwrap();
+        assert_eq!(sorted, "1,3,2,4");
+
+        let groups =
+            Spi::get_one::<i64>("SELECT COUNT(*) FROM (SELECT c_enc FROM o GROUP BY c_enc) g;")
+                .unwrap();
+        assert_eq!(groups, 3);
+
+        Spi::run("CREATE INDEX o_c_enc ON o (c_enc)");
+    }
+
+    #[pg_test]
+    fn test_encinteger_randomized() {
+        Spi::run("CREATE TABLE r (c_enc ENCINTEGER)");
+        Spi::run("INSERT INTO r (c_enc) VALUES (ENCINTEGER_FROM(1, 'r.c_enc')), (ENCINTEGER_FROM(1, 'r.c_enc'))");
+        let distinct = Spi::get_one::<i64>("SELECT COUNT(DISTINCT c_enc::TEXT) FROM r;").unwrap();
+        assert_eq!(distinct, 2);
+        assert_eq!(
+            Spi::get_one::<f32>("SELECT AVG(c_enc) FROM r;").unwrap(),
+            1.0
+        );
+    }
+
+    #[pg_test]
+    fn test_encinteger_migrate() {
+        Spi::run("CREATE TABLE m (c_enc ENCINTEGER)");
+        Spi::run("INSERT INTO m (c_enc) VALUES (ENCINTEGER_FROM(1, 'm.c_enc')), (ENCINTEGER_FROM(2, 'm.c_enc'))");
+        Spi::run("UPDATE m SET c_enc = ENCINTEGER_MIGRATE(c_enc, 'm.c_enc')");
+        Spi::run("INSERT INTO m (c_enc) VALUES (ENCINTEGER_FROM(3, 'm.c_enc'))");
+        assert_eq!(
+            Spi::get_one::<f32>("SELECT AVG(c_enc) FROM m;").unwrap(),
+            2.0
+        );
+    }
+
+    #[pg_test]
+    fn test_encbigint() {
+        Spi::run("CREATE TABLE b (id INTEGER, c_enc ENCBIGINT)");
+        Spi::run("INSERT INTO b (id, c_enc) VALUES (1, ENCBIGINT_FROM(5000000000, 'b.c_enc')), (2, ENCBIGINT_FROM(-1, 'b.c_enc')), (3, ENCBIGINT_FROM(5000000000, 'b.c_enc'))");
+
+        // The encrypted results are compared with the expected ones in the context derived for them.
+        let check =
+            |query: &str| Spi::get_one::<bool>(&format!("SELECT {} FROM b;", query)).unwrap();
+        assert!(check(
+            "SUM(c_enc) = ENCBIGINT_FROM(9999999999, 'b.c_enc#SUM')"
+        ));
+        assert!(check("MIN(c_enc) = ENCBIGINT_FROM(-1, 'b.c_enc#MIN')"));
+        assert!(check(
+            "MAX(c_enc) = ENCBIGINT_FROM(5000000000, 'b.c_enc#MAX')"
+        ));
+        assert_eq!(
+            Spi::get_one::<bool>(
+                "SELECT SUM(c_enc) IS NULL AND MAX(c_enc) IS NULL FROM b WHERE id > 3;"
+            )
+            .unwrap(),
+            true
+        );
+
+        let ids = Spi::get_one::<String>(
+            "SELECT string_agg(id::TEXT, ',' ORDER BY id) FROM b WHERE c_enc > ENCBIGINT_FROM(0, 'b.c_enc');",
+        )
+        .unwrap();
+        assert_eq!(ids, "1,3");
+        let sorted = Spi::get_one::<String>(
+            "SELECT string_agg(id::TEXT, ',') FROM (SELECT id FROM b ORDER BY c_enc, id) s;",
+        )
+        .unwrap();
+        assert_eq!(sorted, "2,1,3");
+    }
+
+    #[pg_test]
+    fn test_encreal() {
+        Spi::run("CREATE TABLE f (id INTEGER, c_enc ENCREAL)");
+        Spi::run("INSERT INTO f (id, c_enc) VALUES (1, ENCREAL_FROM(1.5, 'f.c_enc')), (2, ENCREAL_FROM(-0.5, 'f.c_enc')), (3, ENCREAL_FROM(2.0, 'f.c_enc'))");
+
+        assert_eq!(
+            Spi::get_one::<f32>("SELECT AVG(c_enc) FROM f;").unwrap(),
+            1.0
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT AVG(c_enc) IS NULL FROM f WHERE id > 3;").unwrap(),
+            true
+        );
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT SUM(c_enc) = ENCREAL_FROM(3.0, 'f.c_enc#SUM') FROM f;")
+                .unwrap(),
+            true
+        );
+
+        let sorted = Spi::get_one::<String>(
+            "SELECT string_agg(id::TEXT, ',') FROM (SELECT id FROM f ORDER BY c_enc DESC) s;",
+        )
+        .unwrap();
+        assert_eq!(sorted, "3,1,2");
+    }
+
+    #[pg_test]
+    fn test_enctext() {
+        Spi::run("CREATE TABLE s (id INTEGER, c_enc ENCTEXT)");
+        Spi::run("INSERT INTO s (id, c_enc) VALUES (1, ENCTEXT_FROM('apple', 's.c_enc')), (2, ENCTEXT_FROM('banana', 's.c_enc')), (3, ENCTEXT_FROM('apple', 's.c_enc'))");
+
+        let ids = |query: &str| {
+            Spi::get_one::<String>(&format!(
+                "SELECT string_agg(id::TEXT, ',' ORDER BY id) FROM s WHERE {};",
+                query
+            ))
+            .unwrap()
+        };
+        assert_eq!(ids("c_enc = ENCTEXT_FROM('apple', 's.c_enc')"), "1,3");
+        assert_eq!(ids("c_enc <> ENCTEXT_FROM('apple', 's.c_enc')"), "2");
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT COUNT(c_enc) FROM s;").unwrap(),
+            3
+        );
+    }
+
+    #[pg_test]
+    fn test_enctag_group_by() {
+        // `g.c_dept` is listed in `tagged_contexts` of the owner config signed by the test script.
+        Spi::run("CREATE TABLE g (c_dept ENCINTEGER, c_salary ENCINTEGER, c_dept_tag ENCTAG GENERATED ALWAYS AS (ENCINTEGER_TAG(c_dept)) STORED)");
+        Spi::run("INSERT INTO g (c_dept, c_salary) VALUES (ENCINTEGER_FROM(1, 'g.c_dept'), ENCINTEGER_FROM(10, 'g.c_salary')), (ENCINTEGER_FROM(2, 'g.c_dept'), ENCINTEGER_FROM(30, 'g.c_salary')), (ENCINTEGER_FROM(1, 'g.c_dept'), ENCINTEGER_FROM(20, 'g.c_salary'))");
+
+        let groups = Spi::get_one::<String>(
+            "SELECT string_agg(n::TEXT || ':' || a::TEXT, ',' ORDER BY n) FROM (SELECT COUNT(*) n, AVG(c_salary) a FROM g GROUP BY c_dept_tag) s;",
+        )
+        .unwrap();
+        assert_eq!(groups, "1:30,2:15");
+
+        Spi::run("CREATE INDEX g_c_dept_tag ON g USING hash (c_dept_tag)");
+        assert_eq!(
+            Spi::get_one::<i64>(
+                "SELECT COUNT(*) FROM g WHERE c_dept_tag = ENCINTEGER_TAG(ENCINTEGER_FROM(1, 'g.c_dept'));"
+            )
+            .unwrap(),
+            2
+        );
+    }
+
+    #[pg_test]
+    fn test_enctag_join() {
+        // `j1.c_enc` and `j2.c_enc` share the tag domain `dept`.
+        Spi::run("CREATE TABLE j1 (id INTEGER, c_enc ENCTEXT)");
+        Spi::run("CREATE TABLE j2 (id INTEGER, c_enc ENCTEXT)");
+        Spi::run("INSERT INTO j1 (id, c_enc) VALUES (1, ENCTEXT_FROM('sales', 'j1.c_enc')), (2, ENCTEXT_FROM('dev', 'j1.c_enc'))");
+        Spi::run("INSERT INTO j2 (id, c_enc) VALUES (3, ENCTEXT_FROM('dev', 'j2.c_enc')), (4, ENCTEXT_FROM('hr', 'j2.c_enc')), (5, ENCTEXT_FROM('sales', 'j2.c_enc'))");
+
+        let query = "SELECT string_agg(j1.id::TEXT || '-' || j2.id::TEXT, ',' ORDER BY j1.id) FROM j1 JOIN j2 ON ENCTEXT_TAG(j1.c_enc) = ENCTEXT_TAG(j2.c_enc)";
+        Spi::run("SET enable_nestloop = off");
+        let plan = Spi::get_one::<Json>(&format!("EXPLAIN (FORMAT JSON) {}", query)).unwrap();
+        assert!(plan.0.to_string().contains("\"Node Type\":\"Hash Join\""));
+        assert_eq!(Spi::get_one::<String>(query).unwrap(), "1-5,2-3");
+    }
 }
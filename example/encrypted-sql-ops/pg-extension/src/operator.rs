@@ -0,0 +1,258 @@
+use pgx::*;
+
+extension_sql!(
+    r#"
+    CREATE OPERATOR = (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_eq,
+        commutator = =,
+        negator = <>,
+        restrict = eqsel,
+        join = eqjoinsel
+    );
+
+    CREATE OPERATOR <> (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_ne,
+        commutator = <>,
+        negator = =,
+        restrict = neqsel,
+        join = neqjoinsel
+    );
+
+    CREATE OPERATOR < (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_lt,
+        commutator = >,
+        negator = >=,
+        restrict = scalarltsel,
+        join = scalarltjoinsel
+    );
+
+    CREATE OPERATOR <= (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_le,
+        commutator = >=,
+        negator = >,
+        restrict = scalarlesel,
+        join = scalarlejoinsel
+    );
+
+    CREATE OPERATOR > (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_gt,
+        commutator = <,
+        negator = <=,
+        restrict = scalargtsel,
+        join = scalargtjoinsel
+    );
+
+    CREATE OPERATOR >= (
+        leftarg = EncInteger,
+        rightarg = EncInteger,
+        procedure = encinteger_ge,
+        commutator = <=,
+        negator = <,
+        restrict = scalargesel,
+        join = scalargejoinsel
+    );
+
+    CREATE OPERATOR CLASS encinteger_ops
+    DEFAULT FOR TYPE EncInteger USING btree AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 encinteger_cmp(EncInteger, EncInteger);
+
+    CREATE OPERATOR = (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_eq,
+        commutator = =,
+        negator = <>,
+        restrict = eqsel,
+        join = eqjoinsel
+    );
+
+    CREATE OPERATOR <> (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_ne,
+        commutator = <>,
+        negator = =,
+        restrict = neqsel,
+        join = neqjoinsel
+    );
+
+    CREATE OPERATOR < (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_lt,
+        commutator = >,
+        negator = >=,
+        restrict = scalarltsel,
+        join = scalarltjoinsel
+    );
+
+    CREATE OPERATOR <= (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_le,
+        commutator = >=,
+        negator = >,
+        restrict = scalarlesel,
+        join = scalarlejoinsel
+    );
+
+    CREATE OPERATOR > (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_gt,
+        commutator = <,
+        negator = <=,
+        restrict = scalargtsel,
+        join = scalargtjoinsel
+    );
+
+    CREATE OPERATOR >= (
+        leftarg = EncBigInt,
+        rightarg = EncBigInt,
+        procedure = encbigint_ge,
+        commutator = <=,
+        negator = <,
+        restrict = scalargesel,
+        join = scalargejoinsel
+    );
+
+    CREATE OPERATOR CLASS encbigint_ops
+    DEFAULT FOR TYPE EncBigInt USING btree AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 encbigint_cmp(EncBigInt, EncBigInt);
+
+    CREATE OPERATOR = (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_eq,
+        commutator = =,
+        negator = <>,
+        restrict = eqsel,
+        join = eqjoinsel
+    );
+
+    CREATE OPERATOR <> (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_ne,
+        commutator = <>,
+        negator = =,
+        restrict = neqsel,
+        join = neqjoinsel
+    );
+
+    CREATE OPERATOR < (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_lt,
+        commutator = >,
+        negator = >=,
+        restrict = scalarltsel,
+        join = scalarltjoinsel
+    );
+
+    CREATE OPERATOR <= (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_le,
+        commutator = >=,
+        negator = >,
+        restrict = scalarlesel,
+        join = scalarlejoinsel
+    );
+
+    CREATE OPERATOR > (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_gt,
+        commutator = <,
+        negator = <=,
+        restrict = scalargtsel,
+        join = scalargtjoinsel
+    );
+
+    CREATE OPERATOR >= (
+        leftarg = EncReal,
+        rightarg = EncReal,
+        procedure = encreal_ge,
+        commutator = <=,
+        negator = <,
+        restrict = scalargesel,
+        join = scalargejoinsel
+    );
+
+    CREATE OPERATOR CLASS encreal_ops
+    DEFAULT FOR TYPE EncReal USING btree AS
+        OPERATOR 1 <,
+        OPERATOR 2 <=,
+        OPERATOR 3 =,
+        OPERATOR 4 >=,
+        OPERATOR 5 >,
+        FUNCTION 1 encreal_cmp(EncReal, EncReal);
+
+    CREATE OPERATOR = (
+        leftarg = EncText,
+        rightarg = EncText,
+        procedure = enctext_eq,
+        commutator = =,
+        negator = <>,
+        restrict = eqsel,
+        join = eqjoinsel
+    );
+
+    CREATE OPERATOR <> (
+        leftarg = EncText,
+        rightarg = EncText,
+        procedure = enctext_ne,
+        commutator = <>,
+        negator = =,
+        restrict = neqsel,
+        join = neqjoinsel
+    );
+
+    CREATE OPERATOR = (
+        leftarg = EncTag,
+        rightarg = EncTag,
+        procedure = enctag_eq,
+        commutator = =,
+        negator = <>,
+        restrict = eqsel,
+        join = eqjoinsel,
+        hashes
+    );
+
+    CREATE OPERATOR <> (
+        leftarg = EncTag,
+        rightarg = EncTag,
+        procedure = enctag_ne,
+        commutator = <>,
+        negator = =,
+        restrict = neqsel,
+        join = neqjoinsel
+    );
+
+    CREATE OPERATOR CLASS enctag_ops
+    DEFAULT FOR TYPE EncTag USING hash AS
+        OPERATOR 1 =,
+        FUNCTION 1 enctag_hash(EncTag);
+    "#
+);
@@ -1,5 +1,7 @@
 use module_encrypted_sql_ops_ecall_types::enc_type::{
-    enc_aggregate_state::EncAvgState as ModuleEncAvgState, EncInteger as ModuleEncInteger,
+    enc_aggregate_state::EncAggregateState as ModuleEncAggregateState,
+    EncBigInt as ModuleEncBigInt, EncInteger as ModuleEncInteger, EncReal as ModuleEncReal,
+    EncTag as ModuleEncTag, EncText as ModuleEncText,
 };
 use pgx::*;
 use serde::{Deserialize, Serialize};
@@ -20,26 +22,100 @@ impl From<EncInteger> for ModuleEncInteger {
     }
 }
 
-/// Used as intermediate state on calculating AVG for `ENCINTEGER`.
+/// `ENCBIGINT` custom SQL type, which is encrypted version of `BIGINT`.
 #[derive(Debug, Serialize, Deserialize, PostgresType)]
-pub struct EncAvgState {
-    // cannot use enum here to make initial value via `CREATE AGGREGATE`.
-    current_state: Option<ModuleEncAvgState>,
+pub struct EncBigInt(ModuleEncBigInt);
+
+impl From<ModuleEncBigInt> for EncBigInt {
+    fn from(e: ModuleEncBigInt) -> Self {
+        Self(e)
+    }
 }
 
-impl From<ModuleEncAvgState> for EncAvgState {
-    fn from(e: ModuleEncAvgState) -> Self {
-        Self {
-            current_state: Some(e),
-        }
+impl From<EncBigInt> for ModuleEncBigInt {
+    fn from(e: EncBigInt) -> Self {
+        e.0
+    }
+}
+
+/// `ENCREAL` custom SQL type, which is encrypted version of `REAL`.
+#[derive(Debug, Serialize, Deserialize, PostgresType)]
+pub struct EncReal(ModuleEncReal);
+
+impl From<ModuleEncReal> for EncReal {
+    fn from(e: ModuleEncReal) -> Self {
+        Self(e)
+    }
+}
+
+impl From<EncReal> for ModuleEncReal {
+    fn from(e: EncReal) -> Self {
+        e.0
+    }
+}
+
+/// `ENCTEXT` custom SQL type, which is encrypted version of `TEXT`.
+///
+/// Its length is only revealed rounded up to the bucket size of the enclave.
+#[derive(Debug, Serialize, Deserialize, PostgresType)]
+pub struct EncText(ModuleEncText);
+
+impl From<ModuleEncText> for EncText {
+    fn from(e: ModuleEncText) -> Self {
+        Self(e)
+    }
+}
+
+impl From<EncText> for ModuleEncText {
+    fn from(e: EncText) -> Self {
+        e.0
+    }
+}
+
+/// `ENCTAG` custom SQL type, deterministic tag of an encrypted value.
+///
+/// Equal values in the same tag domain have the same tag, so it's grouped and joined on
+/// without enclave, at the cost of revealing which values are equal.
+#[derive(Debug, Serialize, Deserialize, PostgresType)]
+pub struct EncTag(ModuleEncTag);
+
+impl From<ModuleEncTag> for EncTag {
+    fn from(e: ModuleEncTag) -> Self {
+        Self(e)
+    }
+}
+
+impl From<EncTag> for ModuleEncTag {
+    fn from(e: EncTag) -> Self {
+        e.0
     }
 }
 
-impl From<EncAvgState> for ModuleEncAvgState {
-    fn from(e: EncAvgState) -> Self {
-        match e.current_state {
-            Some(mod_e) => mod_e,
-            None => ModuleEncAvgState::Initial,
+/// Transition state of the aggregates, passed as `internal` between their functions.
+///
+/// The values are buffered in the memory context of the aggregate,
+/// and accumulated into the encrypted state by an ecall per batch instead of per row.
+/// It's serialized to be combined with the ones of the other parallel workers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AggregateBuffer<S, E> {
+    /// command of the state function accumulating the batch, e.g. `ENCINTEGER_SUM_STATE_FUNC`
+    pub cmd: u32,
+    /// encrypted state accumulated so far
+    pub state: S,
+    /// values not accumulated into the state yet
+    pub batch: Vec<E>,
+}
+
+impl<S, E> AggregateBuffer<S, E> {
+    /// Constructor
+    pub fn new(cmd: u32, state: S) -> Self {
+        Self {
+            cmd,
+            state,
+            batch: Vec::new(),
         }
     }
 }
+
+/// Used as intermediate state on calculating the aggregates, e.g. SUM, COUNT, MIN, MAX and AVG.
+pub type EncAggregateBuffer<E> = AggregateBuffer<ModuleEncAggregateState, E>;
@@ -22,8 +22,14 @@ register_enclave_use_case!(
     // Get current state of the user represented the given public key from enclave memory database.
     #[cfg(feature = "enclave_key")]
     (JoinGroupWithEnclaveKey, &*ENCLAVE_CONTEXT),
+    // Rotate the enclave decryption key and re-register the new encryption key.
+    #[cfg(feature = "enclave_key")]
+    (EnclaveKeyRotator, &*ENCLAVE_CONTEXT),
     #[cfg(feature = "treekem")]
     (JoinGroupWithTreeKem, &*ENCLAVE_CONTEXT),
+    // Inspect the TreeKEM group membership for debugging.
+    #[cfg(feature = "treekem")]
+    (GroupInfoGetter, &*ENCLAVE_CONTEXT),
     (GetState<Runtime<AnonifyEnclaveContext>,NoAuth>, &*ENCLAVE_CONTEXT),
     (RegisterNotification<NoAuth>, &*ENCLAVE_CONTEXT),
     (EncryptionKeyGetter, &*ENCLAVE_CONTEXT),
@@ -34,6 +40,14 @@ register_enclave_use_case!(
     #[cfg(feature = "treekem")]
     #[cfg(feature = "backup-enable")]
     (PathSecretsRecoverer, &*ENCLAVE_CONTEXT),
+    // Add a new member with a welcome message instead of replaying the handshakes.
+    #[cfg(feature = "treekem")]
+    #[cfg(feature = "backup-enable")]
+    (WelcomeSender, &*ENCLAVE_CONTEXT),
+    // Get the attestation evidence which the member adding this node with a welcome message verifies.
+    #[cfg(feature = "treekem")]
+    #[cfg(feature = "backup-enable")]
+    (AttestationEvidenceGetter, &*ENCLAVE_CONTEXT),
     (GetUserCounter<NoAuth>, &*ENCLAVE_CONTEXT),
     #[cfg(feature = "enclave_key")]
     #[cfg(feature = "backup-enable")]
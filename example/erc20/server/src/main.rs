@@ -39,7 +39,7 @@ async fn main() -> io::Result<()> {
     let server = Arc::new(server);
 
     HttpServer::new(move || {
-        App::new()
+        let app = App::new()
             .wrap(RequestTracing::new())
             .data(server.clone())
             .route("/api/v1/health", web::get().to(handle_health_check))
@@ -50,6 +50,7 @@ async fn main() -> io::Result<()> {
                 web::get().to(handle_get_user_counter),
             )
             .route("/api/v1/key_rotation", web::post().to(handle_key_rotation))
+            .route("/api/v1/group", web::get().to(handle_get_group))
             .route(
                 "/api/v1/register_notification",
                 web::post().to(handle_register_notification),
@@ -58,10 +59,17 @@ async fn main() -> io::Result<()> {
                 "/api/v1/enclave_encryption_key",
                 web::get().to(handle_enclave_encryption_key),
             )
+            .route(
+                "/api/v1/attestation_evidence",
+                web::get().to(handle_attestation_evidence),
+            )
             .route(
                 "/api/v1/register_report",
                 web::post().to(handle_register_report),
-            )
+            );
+        #[cfg(feature = "backup-enable")]
+        let app = app.route("/api/v1/welcome", web::post().to(handle_send_welcome));
+        app
     })
     .bind(my_node_url)?
     .workers(num_workers)
@@ -5,4 +5,5 @@ use key_vault_enclave::use_case::*;
 register_enclave_use_case!(
     (ServerStarter, &*ENCLAVE_CONTEXT),
     (ServerStopper, &*ENCLAVE_CONTEXT),
+    (MeasurementPolicyReloader, &*ENCLAVE_CONTEXT),
 );
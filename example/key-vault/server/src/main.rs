@@ -26,6 +26,10 @@ async fn main() -> io::Result<()> {
         App::new()
             .data(server.clone())
             .route("/api/v1/health", web::get().to(handle_health_check))
+            .route(
+                "/api/v1/measurement_policy/reload",
+                web::post().to(handle_reload_measurement_policy),
+            )
     })
     .bind(my_node_url)?
     .workers(num_workers)
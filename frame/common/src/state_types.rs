@@ -124,6 +124,10 @@ impl StateCounter {
         Self(counter)
     }
 
+    pub fn as_raw(self) -> u32 {
+        self.0
+    }
+
     pub fn increment(self) -> Self {
         StateCounter(self.0 + 1) // overflow should be ignored
     }
@@ -24,6 +24,64 @@ lazy_static! {
             .parse::<u64>()
             .unwrap()
     };
+    /// The validity period of the attested TLS certificates.
+    pub static ref ATTESTED_CERT_VALIDITY_SECS: u64 = {
+        match env::var("ATTESTED_CERT_VALIDITY_SECS") {
+            Ok(secs) if !secs.is_empty() => secs
+                .parse()
+                .expect("Failed to parse ATTESTED_CERT_VALIDITY_SECS"),
+            _ => 86400,
+        }
+    };
+    /// The interval of re-attesting the TLS certificates in the background,
+    /// which defaults to half the validity period.
+    pub static ref ATTESTATION_RENEWAL_INTERVAL_SECS: u64 = {
+        match env::var("ATTESTATION_RENEWAL_INTERVAL_SECS") {
+            Ok(secs) if !secs.is_empty() => secs
+                .parse()
+                .expect("Failed to parse ATTESTATION_RENEWAL_INTERVAL_SECS"),
+            _ => *ATTESTED_CERT_VALIDITY_SECS / 2,
+        }
+    };
+    /// The maximum age of the attestation reports accepted by the verifiers,
+    /// measured from the timestamp of IAS. `None` (set by `0`) accepts reports of any age.
+    pub static ref ATTESTATION_MAX_AGE_SECS: Option<u64> = {
+        match env::var("ATTESTATION_MAX_AGE_SECS") {
+            Ok(secs) if !secs.is_empty() => Some(
+                secs.parse()
+                    .expect("Failed to parse ATTESTATION_MAX_AGE_SECS"),
+            )
+            .filter(|secs| *secs > 0),
+            _ => Some(86400),
+        }
+    };
+    /// The minimum interval of recovering the enclave decryption key rotated by another node from the key-vault nodes.
+    pub static ref DEC_KEY_RECOVERY_INTERVAL_SECS: u64 = {
+        match env::var("DEC_KEY_RECOVERY_INTERVAL_SECS") {
+            Ok(secs) if !secs.is_empty() => secs
+                .parse()
+                .expect("Failed to parse DEC_KEY_RECOVERY_INTERVAL_SECS"),
+            _ => 60,
+        }
+    };
+    /// The maximum length of the frames of the key-vault requests and responses.
+    pub static ref KEY_VAULT_MAX_FRAME_LEN: u64 = {
+        match env::var("KEY_VAULT_MAX_FRAME_LEN") {
+            Ok(len) if !len.is_empty() => len
+                .parse()
+                .expect("Failed to parse KEY_VAULT_MAX_FRAME_LEN"),
+            _ => 4096,
+        }
+    };
+    /// The maximum length of the frames carrying the welcome messages, which grow with the roster size.
+    pub static ref KEY_VAULT_MAX_WELCOME_FRAME_LEN: u64 = {
+        match env::var("KEY_VAULT_MAX_WELCOME_FRAME_LEN") {
+            Ok(len) if !len.is_empty() => len
+                .parse()
+                .expect("Failed to parse KEY_VAULT_MAX_WELCOME_FRAME_LEN"),
+            _ => 1024 * 1024,
+        }
+    };
     pub static ref CMD_DEC_SECRET_DIR: String =
         env::var("CMD_DEC_SECRET_DIR").unwrap_or_else(|_| ".anonify/cmd-dec-secret".to_string());
     pub static ref PJ_ROOT_DIR: PathBuf = env::var("PJ_ROOT_DIR").map(PathBuf::from)
@@ -98,6 +156,34 @@ lazy_static! {
         let pem = crate::pem::parse(ias_root_cert).expect("Cannot parse PEM File");
         pem.contents
     };
+    /// The backend of remote attestations, either `epid` with IAS (the default) or `dcap` with a PCCS.
+    pub static ref ATTESTATION_PROVIDER: String = {
+        match env::var("ATTESTATION_PROVIDER") {
+            Ok(provider) if !provider.is_empty() => provider.to_lowercase(),
+            _ => "epid".to_string(),
+        }
+    };
+    pub static ref PCCS_URL: String =
+        env::var("PCCS_URL").unwrap_or_else(|_| "https://localhost:8081".to_string());
+    /// The Intel SGX root CA verifying ECDSA quotes and their collateral.
+    /// `None` rejects the DCAP attested peers.
+    pub static ref DCAP_ROOT_CERT: Option<Vec<u8>> = {
+        match env::var("DCAP_ROOT_CERT_PATH") {
+            Ok(dcap_root_cert_path) if !dcap_root_cert_path.is_empty() => {
+                let mut file_path = PJ_ROOT_DIR.clone();
+                file_path.push(dcap_root_cert_path);
+
+                #[cfg(feature = "sgx")]
+                let dcap_root_cert = crate::localstd::untrusted::fs::read(file_path).unwrap();
+                #[cfg(feature = "std")]
+                let dcap_root_cert = crate::localstd::fs::read(file_path).unwrap();
+
+                let pem = crate::pem::parse(dcap_root_cert).expect("Cannot parse PEM File");
+                Some(pem.contents)
+            }
+            _ => None,
+        }
+    };
 }
 
 #[cfg(feature = "sgx")]
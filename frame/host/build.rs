@@ -20,6 +20,10 @@ fn main() {
         }
     }
 
+    if env::var("CARGO_FEATURE_DCAP").is_ok() {
+        println!("cargo:rustc-link-lib=dylib=sgx_dcap_ql");
+    }
+
     let edl = format!("{}/edl", rust_sgx_sdk);
     let test_u_c_path = format!("{}/Anonify_test_u.c", build_dir);
     let common_u_c_path = format!("{}/Anonify_common_u.c", build_dir);
@@ -386,3 +386,30 @@ pub(crate) type TbsCert = asn1_seq_ty!(
 );
 pub(crate) type CertSig = BitVec;
 pub(crate) type X509 = asn1_seq_ty!(TbsCert, CertSignAlgo, CertSig);
+
+/// The fields of a certificate of the attested TLS
+pub(crate) struct AttestedCert {
+    pub(crate) pubkey: <PubKey as Asn1Ty>::ValueTy,
+    /// The validity period in seconds since the UNIX epoch
+    pub(crate) not_before: i64,
+    pub(crate) not_after: i64,
+    /// The payload of the attestation extension
+    pub(crate) cert_ext_payload: Vec<u8>,
+}
+
+/// Parse a DER encoded certificate of the attested TLS.
+pub(crate) fn parse_ra_cert(cert: &[u8]) -> ASN1Result<AttestedCert> {
+    let x509 = yasna::parse_der(cert, X509::load)?;
+    // Extract tbs (To Be Signed) Certificate
+    let tbs_cert: <TbsCert as Asn1Ty>::ValueTy = x509.0;
+    let valid_range: <ValidRange as Asn1Ty>::ValueTy = ((((tbs_cert.1).1).1).1).0;
+    let pubkey: <PubKey as Asn1Ty>::ValueTy = ((((((tbs_cert.1).1).1).1).1).1).0;
+    let cert_ext: <SgxRaCertExt as Asn1Ty>::ValueTy = (((((((tbs_cert.1).1).1).1).1).1).1).0;
+
+    Ok(AttestedCert {
+        pubkey,
+        not_before: valid_range.0.datetime().timestamp(),
+        not_after: (valid_range.1).0.datetime().timestamp(),
+        cert_ext_payload: ((cert_ext.0).1).0,
+    })
+}
@@ -1,18 +1,50 @@
 use crate::config::ClientConfig;
 use crate::connection::Connection;
+use crate::key_vault::{
+    error::KeyVaultError,
+    request::{GetCapabilitiesRequestBody, KeyVaultCmd, KeyVaultRequest, RequestBody},
+    response::{Capabilities, KeyVaultResponse},
+    KEY_VAULT_PROTOCOL_VERSION, MIN_KEY_VAULT_PROTOCOL_VERSION,
+};
 use anyhow::{anyhow, Result};
 use frame_config::{REQUEST_RETRIES, RETRY_DELAY_MILLS};
 use frame_retrier::{strategy, Retry};
 use http::Uri;
 use serde::{de::DeserializeOwned, Serialize};
-use std::sync::Arc;
+use serde_json::Value;
+use std::{string::String, sync::Arc};
+use tracing::warn;
 
 pub struct Client {
     connection: Connection<rustls::ClientSession>,
+    version: u32,
 }
 
 impl Client {
+    /// Connect to the key-vault node in the protocol version negotiated with it.
+    /// It fails with the `UnsupportedVersion` error if they have no version in common.
+    /// The key-vault node serves one request per connection, so the capabilities are got by another one.
     pub fn new(address: &str, client_config: &ClientConfig) -> Result<Self> {
+        let version = match Self::connect(address, client_config, KEY_VAULT_PROTOCOL_VERSION)?
+            .get_capabilities()
+        {
+            Ok(capabilities) => capabilities.negotiate_version()?,
+            Err(e) if e.downcast_ref::<KeyVaultError>().is_some() => return Err(e),
+            // The legacy key-vault node doesn't know the command and closes the connection.
+            Err(e) if MIN_KEY_VAULT_PROTOCOL_VERSION == 0 => {
+                warn!(
+                    "Failed to get the capabilities of {}: {:?}. Falling back to the legacy protocol.",
+                    address, e
+                );
+                0
+            }
+            Err(e) => return Err(e),
+        };
+
+        Self::connect(address, client_config, version)
+    }
+
+    fn connect(address: &str, client_config: &ClientConfig, version: u32) -> Result<Self> {
         let uri = address.parse::<Uri>()?;
         let hostname = uri.host().ok_or_else(|| anyhow!("Invalid hostname"))?;
         let hostname = webpki::DNSNameRef::try_from_ascii_str(hostname)?;
@@ -21,7 +53,15 @@ impl Client {
         let stream = std::net::TcpStream::connect(address)?;
         let connection = Connection::new(session, stream);
 
-        Ok(Client { connection })
+        Ok(Client {
+            connection,
+            version,
+        })
+    }
+
+    /// The protocol version negotiated with the key-vault node
+    pub fn version(&self) -> u32 {
+        self.version
     }
 
     pub fn send_json<SE, DE>(&mut self, json: SE) -> Result<DE>
@@ -41,4 +81,55 @@ impl Client {
         let rd = self.connection.read_frame()?;
         serde_json::from_slice(&rd).map_err(Into::into)
     }
+
+    /// Send the request to the key-vault node and unwrap the body from the response envelope.
+    pub fn send_request<B, DE>(&mut self, request: KeyVaultRequest<B>) -> Result<DE>
+    where
+        B: RequestBody,
+        DE: DeserializeOwned,
+    {
+        // The responses of some commands such as the welcome messages are larger than the others.
+        self.connection
+            .set_max_frame_len(request.cmd().max_frame_len());
+        let resp: Value = self.send_json(request.with_version(self.version))?;
+        KeyVaultResponse::decode(resp)
+    }
+
+    /// Get the protocol versions and the commands which the key-vault node supports.
+    pub fn get_capabilities(&mut self) -> Result<Capabilities> {
+        self.send_request(KeyVaultRequest::new(
+            KeyVaultCmd::GetCapabilities,
+            GetCapabilitiesRequestBody,
+        ))
+    }
+
+    /// Send the request to the key-vault nodes in order, failing over to the next one if it fails,
+    /// including the case that the node is too old to support the command.
+    pub fn send_request_with_failover<B, DE>(
+        endpoints: &[String],
+        client_config: &ClientConfig,
+        request: KeyVaultRequest<B>,
+    ) -> Result<DE>
+    where
+        B: RequestBody,
+        DE: DeserializeOwned,
+    {
+        let mut last_err = anyhow!("No endpoints are provided");
+        for endpoint in endpoints {
+            match Client::new(endpoint, client_config)
+                .and_then(|mut client| client.send_request(request.clone()))
+            {
+                Ok(resp) => return Ok(resp),
+                Err(e) => {
+                    warn!(
+                        "Failed to send a request to {}: {:?}. Failing over to the next endpoint.",
+                        endpoint, e
+                    );
+                    last_err = e;
+                }
+            }
+        }
+
+        Err(last_err)
+    }
 }
@@ -1,34 +1,193 @@
 use crate::error::Result;
 use crate::key::NistP256KeyPair;
+use crate::policy::SharedMeasurementPolicy;
 use crate::verifier::AttestedReportVerifier;
+use anyhow::anyhow;
 use core::fmt;
-use frame_config::EnclaveMeasurement;
-use remote_attestation::QuoteTarget;
-use std::{string::ToString, sync::Arc, vec::Vec};
+use frame_config::{EnclaveMeasurement, ATTESTED_CERT_VALIDITY_SECS};
+use remote_attestation::{provider_from_env, AttestationProvider};
+use rustls::sign::{self, CertifiedKey};
+use std::{
+    string::ToString,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, SgxRwLock,
+    },
+    thread,
+    time::Duration,
+    vec::Vec,
+};
+use tracing::{error, info};
 
 const CERT_ISSUER: &str = "Anonify";
 const CERT_SUBJECT: &str = "CN=Anonify";
+/// The default number of worker threads serving connections.
+/// Each worker occupies a TCS of the enclave.
+const DEFAULT_WORKERS: usize = 4;
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// The default maximum length of the frame read from a connection.
+pub(crate) const DEFAULT_MAX_FRAME_LEN: u64 = 4096;
+/// The delay of retrying a failed renewal of the attested certificate
+const RENEWAL_RETRY_DELAY_SECS: u64 = 60;
 
-#[derive(Debug, Clone)]
+/// The attested certificate and its private key, which are shared by the TLS configs built from it,
+/// so that renewing it replaces the certificate presented by the new connections of those configs.
+#[derive(Clone)]
 pub struct AttestedTlsConfig {
-    ee_cert: Vec<u8>,
-    priv_key: Vec<u8>,
+    provider: Arc<dyn AttestationProvider>,
+    certified_key: Arc<SgxRwLock<CertifiedKey>>,
 }
 
 impl AttestedTlsConfig {
+    /// Attest by the provider selected by `ATTESTATION_PROVIDER`,
+    /// where the IAS settings are used only by the EPID attestation.
     pub fn new_by_ra(spid: &str, ias_url: &str, sub_key: &str, root_cert: Vec<u8>) -> Result<Self> {
-        let key_pair = NistP256KeyPair::new()?;
-        let report_data = key_pair.report_data();
-        let attested_report = QuoteTarget::new()?
-            .set_enclave_report(&report_data)?
-            .create_quote(&spid)?
-            .remote_attestation(ias_url, sub_key, root_cert)?;
+        let provider = provider_from_env(spid, ias_url, sub_key, root_cert)?;
+        Self::new_by_provider(Arc::from(provider))
+    }
 
-        let extension = serde_json::to_vec(&attested_report)?;
-        let ee_cert = key_pair.create_cert_with_extension(CERT_ISSUER, CERT_SUBJECT, &extension);
-        let priv_key = key_pair.priv_key_into_der();
+    /// The provider is kept to attest the renewed certificates.
+    pub fn new_by_provider(provider: Arc<dyn AttestationProvider>) -> Result<Self> {
+        let certified_key = attest(provider.as_ref())?;
+        Ok(Self {
+            provider,
+            certified_key: Arc::new(SgxRwLock::new(certified_key)),
+        })
+    }
+
+    /// Attest a new key pair and replace the certificate of all the TLS configs built from this config.
+    /// The established connections keep the previous one.
+    pub fn renew(&self) -> Result<()> {
+        let certified_key = attest(self.provider.as_ref())?;
+        *self
+            .certified_key
+            .write()
+            .map_err(|e| anyhow!("Failed to acquire the attested certificate lock: {:?}", e))? =
+            certified_key;
 
-        Ok(Self { ee_cert, priv_key })
+        Ok(())
+    }
+
+    /// Renew the certificate every `interval` in a background thread, which occupies a TCS of the enclave,
+    /// until the returned handle is stopped. A failed renewal is retried sooner while the current certificate is kept.
+    pub fn spawn_renewal(&self, interval: Duration) -> Result<RenewalHandle> {
+        let handle = RenewalHandle {
+            is_stopped: Arc::new(AtomicBool::new(false)),
+        };
+        let renewal = handle.clone();
+        let config = self.clone();
+        thread::Builder::new()
+            .name("mra-tls-renewal".into())
+            .spawn(move || {
+                let mut delay = interval;
+                while renewal.sleep(delay) {
+                    match config.renew() {
+                        Ok(()) => {
+                            info!("Renewed the attested certificate");
+                            delay = interval;
+                        }
+                        Err(e) => {
+                            error!("Failed to renew the attested certificate: {:?}", e);
+                            delay = interval.min(Duration::from_secs(RENEWAL_RETRY_DELAY_SECS));
+                        }
+                    }
+                }
+            })?;
+
+        Ok(handle)
+    }
+
+    fn cert_resolver(&self) -> Arc<AttestedCertResolver> {
+        Arc::new(AttestedCertResolver(self.certified_key.clone()))
+    }
+}
+
+impl fmt::Debug for AttestedTlsConfig {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AttestedTlsConfig")
+            .field("certified_key", &"(omitted)".to_string())
+            .finish()
+    }
+}
+
+/// Create a certificate of a new key pair with the evidence attesting the public key.
+fn attest(provider: &dyn AttestationProvider) -> Result<CertifiedKey> {
+    let key_pair = NistP256KeyPair::new()?;
+    let report_data = key_pair.report_data();
+    let evidence = provider.attest(&report_data)?;
+
+    let extension = serde_json::to_vec(&evidence)?;
+    let ee_cert = key_pair.create_cert_with_extension(
+        CERT_ISSUER,
+        CERT_SUBJECT,
+        &extension,
+        Duration::from_secs(*ATTESTED_CERT_VALIDITY_SECS),
+    );
+    let priv_key = rustls::PrivateKey(key_pair.priv_key_into_der());
+    let signing_key = sign::any_supported_type(&priv_key)
+        .map_err(|_| anyhow!("Invalid private key of the attested certificate"))?;
+
+    Ok(CertifiedKey::new(
+        vec![rustls::Certificate(ee_cert)],
+        Arc::new(signing_key),
+    ))
+}
+
+/// A handle to stop the background renewal of the attested certificate.
+#[derive(Debug, Clone)]
+pub struct RenewalHandle {
+    is_stopped: Arc<AtomicBool>,
+}
+
+impl RenewalHandle {
+    pub fn stop(&self) {
+        self.is_stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Sleep by a second to observe the stop promptly. Returns false if it is stopped.
+    fn sleep(&self, duration: Duration) -> bool {
+        for _ in 0..duration.as_secs().max(1) {
+            if self.is_stopped.load(Ordering::SeqCst) {
+                return false;
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+        !self.is_stopped.load(Ordering::SeqCst)
+    }
+}
+
+/// Resolves the current attested certificate on each handshake.
+struct AttestedCertResolver(Arc<SgxRwLock<CertifiedKey>>);
+
+impl AttestedCertResolver {
+    fn current(&self) -> Option<CertifiedKey> {
+        match self.0.read() {
+            Ok(certified_key) => Some(certified_key.clone()),
+            Err(e) => {
+                error!("Failed to acquire the attested certificate lock: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+impl rustls::ResolvesServerCert for AttestedCertResolver {
+    fn resolve(&self, _client_hello: rustls::ClientHello<'_>) -> Option<CertifiedKey> {
+        self.current()
+    }
+}
+
+impl rustls::ResolvesClientCert for AttestedCertResolver {
+    fn resolve(
+        &self,
+        _acceptable_issuers: &[&[u8]],
+        _sigschemes: &[rustls::SignatureScheme],
+    ) -> Option<CertifiedKey> {
+        self.current()
+    }
+
+    fn has_certs(&self) -> bool {
+        true
     }
 }
 
@@ -38,14 +197,10 @@ pub struct ClientConfig {
 }
 
 impl ClientConfig {
+    /// The client presents the current certificate of `attested_tls_config`, which may be renewed.
     pub fn from_attested_tls_config(attested_tls_config: AttestedTlsConfig) -> Result<Self> {
-        let cert_chain = vec![rustls::Certificate(attested_tls_config.ee_cert)];
-        let key_der = rustls::PrivateKey(attested_tls_config.priv_key);
-
         let mut client_config = ClientConfig::default();
-        client_config
-            .tls
-            .set_single_client_cert(cert_chain, key_der)?;
+        client_config.tls.client_auth_cert_resolver = attested_tls_config.cert_resolver();
 
         Ok(client_config)
     }
@@ -64,6 +219,18 @@ impl ClientConfig {
 
         self
     }
+
+    /// Verify the server by the measurement policy, which may be updated while the config is in use.
+    pub fn set_measurement_policy_verifier(
+        mut self,
+        root_cert: Vec<u8>,
+        policy: SharedMeasurementPolicy,
+    ) -> Self {
+        let verifier = Arc::new(AttestedReportVerifier::with_policy(root_cert, policy));
+        self.tls.dangerous().set_certificate_verifier(verifier);
+
+        self
+    }
 }
 
 impl Default for ClientConfig {
@@ -95,15 +262,19 @@ impl fmt::Debug for ClientConfig {
 
 pub struct ServerConfig {
     tls: rustls::ServerConfig,
+    /// Kept to extract the identity of the attested client after the handshake.
+    verifier: Option<AttestedReportVerifier>,
+    workers: usize,
+    read_timeout: Duration,
+    write_timeout: Duration,
+    max_frame_len: u64,
 }
 
 impl ServerConfig {
+    /// The server presents the current certificate of `attested_tls_config`, which may be renewed.
     pub fn from_attested_tls_config(attested_tls_config: AttestedTlsConfig) -> Result<Self> {
-        let cert_chain = vec![rustls::Certificate(attested_tls_config.ee_cert)];
-        let key_der = rustls::PrivateKey(attested_tls_config.priv_key);
-
         let mut server_config = ServerConfig::default();
-        server_config.tls.set_single_cert(cert_chain, key_der)?;
+        server_config.tls.cert_resolver = attested_tls_config.cert_resolver();
 
         Ok(server_config)
     }
@@ -117,11 +288,66 @@ impl ServerConfig {
         root_cert: Vec<u8>,
         measurement: EnclaveMeasurement,
     ) -> Self {
-        let verifier = Arc::new(AttestedReportVerifier::new(root_cert, measurement));
-        self.tls.set_client_certificate_verifier(verifier);
+        let verifier = AttestedReportVerifier::new(root_cert, measurement);
+        self.tls
+            .set_client_certificate_verifier(Arc::new(verifier.clone()));
+        self.verifier = Some(verifier);
+
+        self
+    }
+
+    /// Verify the clients by the measurement policy, which may be updated while the server is running.
+    pub fn set_measurement_policy_verifier(
+        mut self,
+        root_cert: Vec<u8>,
+        policy: SharedMeasurementPolicy,
+    ) -> Self {
+        let verifier = AttestedReportVerifier::with_policy(root_cert, policy);
+        self.tls
+            .set_client_certificate_verifier(Arc::new(verifier.clone()));
+        self.verifier = Some(verifier);
+
+        self
+    }
+
+    /// Set the number of worker threads, which bounds the number of connections served concurrently.
+    pub fn set_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
 
+    /// Set the read and write timeouts of each connection,
+    /// so that a stalled client doesn't occupy a worker forever.
+    pub fn set_timeouts(mut self, read_timeout: Duration, write_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self.write_timeout = write_timeout;
         self
     }
+
+    pub fn set_max_frame_len(mut self, max_frame_len: u64) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    pub(crate) fn verifier(&self) -> Option<&AttestedReportVerifier> {
+        self.verifier.as_ref()
+    }
+
+    pub(crate) fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub(crate) fn read_timeout(&self) -> Duration {
+        self.read_timeout
+    }
+
+    pub(crate) fn write_timeout(&self) -> Duration {
+        self.write_timeout
+    }
+
+    pub(crate) fn max_frame_len(&self) -> u64 {
+        self.max_frame_len
+    }
 }
 
 impl Default for ServerConfig {
@@ -129,7 +355,14 @@ impl Default for ServerConfig {
         let client_tls = rustls::NoClientAuth::new();
         let server_tls = rustls::ServerConfig::new(client_tls);
 
-        Self { tls: server_tls }
+        Self {
+            tls: server_tls,
+            verifier: None,
+            workers: DEFAULT_WORKERS,
+            read_timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            write_timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
     }
 }
 
@@ -1,12 +1,12 @@
+use crate::config::DEFAULT_MAX_FRAME_LEN;
 use crate::server::RequestHandler;
-use anyhow::{ensure, Result};
+use crate::verifier::{AttestedReportVerifier, PeerIdentity};
+use anyhow::{anyhow, ensure, Result};
 use std::io::{Read, Write};
 use std::net::TcpStream;
 use std::vec::Vec;
 use tracing::{info, warn};
 
-const MAX_FRAME_LEN: u64 = 4096;
-
 pub struct Connection<S: rustls::Session> {
     stream: rustls::StreamOwned<S, TcpStream>,
     max_frame_len: u64,
@@ -16,16 +16,26 @@ impl<S: rustls::Session> Connection<S> {
     pub fn new(sess: S, sock: TcpStream) -> Self {
         Connection {
             stream: rustls::StreamOwned::new(sess, sock),
-            max_frame_len: MAX_FRAME_LEN,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
         }
     }
 
+    pub fn set_max_frame_len(&mut self, max_frame_len: u64) -> &mut Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
     pub fn read_frame(&mut self) -> Result<Vec<u8>> {
         let mut header = [0u8; 8];
         self.stream.read_exact(&mut header)?;
         let frame_len = u64::from_be_bytes(header);
 
-        ensure!(frame_len <= self.max_frame_len, "Exceed max frame length");
+        ensure!(
+            frame_len <= self.max_frame_len,
+            "Exceed max frame length: {} > {}",
+            frame_len,
+            self.max_frame_len
+        );
 
         let mut frame = vec![0u8; frame_len as usize];
         self.stream.read_exact(&mut frame)?;
@@ -46,14 +56,37 @@ impl<S: rustls::Session> Connection<S> {
         Ok(())
     }
 
-    pub fn serve_json<H: RequestHandler>(&mut self, handler: H) -> Result<()> {
+    pub fn serve_json<H: RequestHandler>(
+        &mut self,
+        handler: H,
+        verifier: Option<&AttestedReportVerifier>,
+    ) -> Result<()> {
         let req = self.read_frame()?;
         if req.is_empty() {
             warn!("request's length is 0");
             return Ok(());
         }
-        let resp = handler.handle_json(&req)?;
+        // The handshake has been completed by reading the request,
+        // so the client certificate is available here.
+        let peer = match verifier {
+            Some(verifier) => Some(self.peer_identity(verifier)?),
+            None => None,
+        };
+        let resp = handler.handle_json(&req, peer.as_ref())?;
         self.write_frame(&resp)?;
         Ok(())
     }
+
+    fn peer_identity(&self, verifier: &AttestedReportVerifier) -> Result<PeerIdentity> {
+        let certs = self
+            .stream
+            .sess
+            .get_peer_certificates()
+            .ok_or_else(|| anyhow!("The peer didn't present its certificate"))?;
+        let ee_cert = certs
+            .first()
+            .ok_or_else(|| anyhow!("The peer didn't present its certificate"))?;
+
+        Ok(verifier.verify_cert(&ee_cert.0)?)
+    }
 }
@@ -7,14 +7,11 @@ use num_bigint::BigUint;
 use sgx_tcrypto::SgxEccHandle;
 use sgx_types::{sgx_ec256_private_t, sgx_ec256_public_t, sgx_report_data_t};
 use std::borrow::ToOwned;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::vec::Vec;
 use yasna::models::{ObjectIdentifier, UTCTime};
 use yasna::{construct_der, Tag};
 
-/// Validation days of a certification for TLS connection
-const CERT_VALID_DAYS: i64 = 30;
-
 pub struct NistP256KeyPair {
     priv_key: sgx_ec256_private_t,
     pub_key: sgx_ec256_public_t,
@@ -65,13 +62,15 @@ impl NistP256KeyPair {
         })
     }
 
-    /// Creating a self-signed X.509 v3 certificate with remote attestation report as extensions
+    /// Creating a self-signed X.509 v3 certificate with remote attestation report as extensions,
+    /// which is valid for `validity` from now.
     /// reference: https://tools.ietf.org/html/rfc5280#section-4.1.2.1
     pub fn create_cert_with_extension(
         &self,
         issuer: &str,
         subject: &str,
         payload: &[u8],
+        validity: Duration,
     ) -> Vec<u8> {
         // http://oid-info.com/get/1.2.840.10045.4.3.2
         let ecdsa_with_sha256_oid = ObjectIdentifier::from_slice(&[1, 2, 840, 10045, 4, 3, 2]);
@@ -90,8 +89,7 @@ impl NistP256KeyPair {
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
         let issue_ts = chrono::Utc.timestamp(now.as_secs() as i64, 0);
 
-        // CERT_VALID_DAYS must not be less than zero
-        let expire = now + chrono::Duration::days(CERT_VALID_DAYS).to_std().unwrap();
+        let expire = now + validity;
         let expire_ts = chrono::Utc.timestamp(expire.as_secs() as i64, 0);
 
         let tbs_cert_der = construct_der(|writer| {
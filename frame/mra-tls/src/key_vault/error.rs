@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    string::{String, ToString},
+};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyVaultErrorCode {
+    /// The key-vault node doesn't support the command, e.g. it's older than the client.
+    UnsupportedCommand,
+    /// The client and the key-vault node have no protocol version in common.
+    UnsupportedVersion,
+    /// The request body doesn't match the command.
+    InvalidRequest,
+    /// The requesting peer isn't allowed to send the command.
+    Unauthorized,
+    /// The key-vault node failed to handle the request.
+    Internal,
+}
+
+/// A structured error returned to the client in the response envelope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[error("key-vault error ({code:?}): {message}")]
+pub struct KeyVaultError {
+    code: KeyVaultErrorCode,
+    message: String,
+}
+
+impl KeyVaultError {
+    pub fn new(code: KeyVaultErrorCode, message: impl Display) -> Self {
+        KeyVaultError {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn unsupported_command(cmd: &str) -> Self {
+        Self::new(
+            KeyVaultErrorCode::UnsupportedCommand,
+            format!("unsupported command: {}", cmd),
+        )
+    }
+
+    pub fn unsupported_version(version: u32, min_version: u32, max_version: u32) -> Self {
+        Self::new(
+            KeyVaultErrorCode::UnsupportedVersion,
+            format!(
+                "unsupported protocol version: {} (supported: {} to {})",
+                version, min_version, max_version
+            ),
+        )
+    }
+
+    pub fn invalid_request(err: impl Display) -> Self {
+        Self::new(KeyVaultErrorCode::InvalidRequest, err)
+    }
+
+    pub fn unauthorized(message: impl Display) -> Self {
+        Self::new(KeyVaultErrorCode::Unauthorized, message)
+    }
+
+    pub fn internal(err: impl Display) -> Self {
+        Self::new(KeyVaultErrorCode::Internal, err)
+    }
+
+    pub fn code(&self) -> KeyVaultErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
@@ -1,2 +1,26 @@
+pub mod error;
 pub mod request;
 pub mod response;
+
+/// The version of the key-vault protocol.
+/// Version 0 is the legacy protocol, whose requests have no version field
+/// and whose responses are the bare bodies without the envelope.
+pub const KEY_VAULT_PROTOCOL_VERSION: u32 = 1;
+/// The oldest version of the key-vault protocol which this node can still talk.
+pub const MIN_KEY_VAULT_PROTOCOL_VERSION: u32 = 0;
+
+/// Serialize the large byte strings such as the welcome messages in base64,
+/// which is far more compact in JSON than the array of numbers.
+pub(crate) mod base64_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::{string::String, vec::Vec};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::decode(&encoded).map_err(de::Error::custom)
+    }
+}
@@ -1,3 +1,8 @@
+use crate::key_vault::{
+    error::KeyVaultError, KEY_VAULT_PROTOCOL_VERSION, MIN_KEY_VAULT_PROTOCOL_VERSION,
+};
+use crate::PeerIdentity;
+use frame_config::{KEY_VAULT_MAX_FRAME_LEN, KEY_VAULT_MAX_WELCOME_FRAME_LEN};
 use frame_sodium::SodiumPrivateKey;
 #[cfg(feature = "std")]
 use rand::Rng;
@@ -6,7 +11,12 @@ use rand_core::{CryptoRng, RngCore};
 #[cfg(feature = "std")]
 use rand_os::OsRng;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{fmt::Debug, vec::Vec};
+use serde_json::{Map, Value};
+use std::{
+    fmt::Debug,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 /// A marker trait for request body
 pub trait RequestBody: DeserializeOwned + Serialize + Debug + Clone {}
@@ -105,31 +115,219 @@ impl RecoverPathSecretsRequestBody {
 
 impl RequestBody for RecoverPathSecretsRequestBody {}
 
-/// A Request body to store enclave decryption key to key-vault enclave
+/// A Request body to store enclave decryption key to key-vault enclave.
+/// Each backup is stored as a new version under the requesting enclave's MRSIGNER, ISVPRODID and roster_idx.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct BackupEnclaveDecryptionKeyRequestBody {
     dec_key: SodiumPrivateKey,
+    roster_idx: u32,
+    /// The secret sealed by the requesting node, to which the roster index is bound on its first backup.
+    #[serde(default, with = "serde_bytes")]
+    node_key: Vec<u8>,
+    /// The version to store the share of the key at, so that all key-vault nodes number it the same.
+    /// The node numbers it next to the latest version if it's not given.
+    #[serde(default)]
+    version: Option<u32>,
+    /// The commitment to the whole key and its version if the key is a share of the secret sharing.
+    #[serde(default, with = "serde_bytes")]
+    commitment: Vec<u8>,
 }
 
 impl BackupEnclaveDecryptionKeyRequestBody {
-    pub fn new(dec_key: SodiumPrivateKey) -> Self {
-        Self { dec_key }
+    pub fn new(dec_key: SodiumPrivateKey, roster_idx: u32, node_key: Vec<u8>) -> Self {
+        Self {
+            dec_key,
+            roster_idx,
+            node_key,
+            version: None,
+            commitment: Vec::new(),
+        }
+    }
+
+    pub fn with_share_commitment(mut self, version: u32, commitment: Vec<u8>) -> Self {
+        self.version = Some(version);
+        self.commitment = commitment;
+        self
     }
 
     pub fn dec_key(&self) -> &SodiumPrivateKey {
         &self.dec_key
     }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+
+    pub fn node_key(&self) -> &[u8] {
+        &self.node_key[..]
+    }
+
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
+    pub fn commitment(&self) -> &[u8] {
+        &self.commitment[..]
+    }
 }
 
 impl RequestBody for BackupEnclaveDecryptionKeyRequestBody {}
 
-/// A Request body to recover enclave decryption key from key-vault enclave
+/// A Request body to recover enclave decryption key from key-vault enclave.
+/// If version is `None`, the latest version is recovered.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct RecoverEnclaveDecryptionKeyRequestBody;
+pub struct RecoverEnclaveDecryptionKeyRequestBody {
+    roster_idx: u32,
+    version: Option<u32>,
+}
+
+impl RecoverEnclaveDecryptionKeyRequestBody {
+    pub fn new(roster_idx: u32, version: Option<u32>) -> Self {
+        Self {
+            roster_idx,
+            version,
+        }
+    }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+}
 
 impl RequestBody for RecoverEnclaveDecryptionKeyRequestBody {}
 
-#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+/// A Request body to list the backed up versions of enclave decryption key specified by roster_idx
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ListEnclaveDecryptionKeysRequestBody {
+    roster_idx: u32,
+}
+
+impl ListEnclaveDecryptionKeysRequestBody {
+    pub fn new(roster_idx: u32) -> Self {
+        Self { roster_idx }
+    }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+}
+
+impl RequestBody for ListEnclaveDecryptionKeysRequestBody {}
+
+/// A Request body to store a welcome message encrypted to the joiner's encryption key
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StoreWelcomeRequestBody {
+    roster_idx: u32,
+    /// The epoch of the group the welcome message adds the joiner to
+    epoch: u32,
+    #[serde(with = "crate::key_vault::base64_bytes")]
+    encrypted_welcome: Vec<u8>,
+}
+
+impl StoreWelcomeRequestBody {
+    pub fn new(roster_idx: u32, epoch: u32, encrypted_welcome: Vec<u8>) -> Self {
+        Self {
+            roster_idx,
+            epoch,
+            encrypted_welcome,
+        }
+    }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    pub fn encrypted_welcome(&self) -> &[u8] {
+        &self.encrypted_welcome[..]
+    }
+}
+
+impl RequestBody for StoreWelcomeRequestBody {}
+
+/// A Request body to recover the welcome message for the joiner specified by roster_idx
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RecoverWelcomeRequestBody {
+    roster_idx: u32,
+}
+
+impl RecoverWelcomeRequestBody {
+    pub fn new(roster_idx: u32) -> Self {
+        Self { roster_idx }
+    }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+}
+
+impl RequestBody for RecoverWelcomeRequestBody {}
+
+/// A request body to provision a secret key shared by the enclaves with the same MRSIGNER and ISVPRODID,
+/// e.g. the master key of encrypted-sql-ops. The proposed key is stored only if no key is stored under the name,
+/// and the stored one is returned, so that the enclaves provisioned at the same time agree on a single key.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProvisionSharedKeyRequestBody {
+    name: String,
+    #[serde(with = "serde_bytes")]
+    proposed_key: Vec<u8>,
+}
+
+impl ProvisionSharedKeyRequestBody {
+    pub fn new(name: String, proposed_key: Vec<u8>) -> Self {
+        Self { name, proposed_key }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn proposed_key(&self) -> &[u8] {
+        &self.proposed_key[..]
+    }
+}
+
+impl RequestBody for ProvisionSharedKeyRequestBody {}
+
+/// A request body to replicate a write request to the peer key-vault nodes.
+/// The origin is the identity of the enclave which sent the original request,
+/// so the replica stores the entry in the same namespace as the origin node does.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ReplicateRequestBody {
+    origin: Option<PeerIdentity>,
+    request: Value,
+}
+
+impl ReplicateRequestBody {
+    pub fn new(origin: Option<PeerIdentity>, request: Value) -> Self {
+        Self { origin, request }
+    }
+
+    pub fn origin(&self) -> Option<&PeerIdentity> {
+        self.origin.as_ref()
+    }
+
+    pub fn request(&self) -> &Value {
+        &self.request
+    }
+}
+
+impl RequestBody for ReplicateRequestBody {}
+
+/// A request body to get the capabilities of the key-vault node
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GetCapabilitiesRequestBody;
+
+impl RequestBody for GetCapabilitiesRequestBody {}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum KeyVaultCmd {
     StorePathSecret,
     RecoverPathSecret,
@@ -137,16 +335,148 @@ pub enum KeyVaultCmd {
     ManuallyRecoverPathSecrets,
     StoreEnclaveDecryptionKey,
     RecoverEnclaveDecryptionKey,
+    ListEnclaveDecryptionKeys,
+    StoreWelcome,
+    RecoverWelcome,
+    ProvisionSharedKey,
+    Replicate,
+    GetCapabilities,
+}
+
+impl KeyVaultCmd {
+    /// Whether the command modifies the stored entries, so it has to be replicated to the peers.
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            KeyVaultCmd::StorePathSecret
+                | KeyVaultCmd::ManuallyStorePathSecrets
+                | KeyVaultCmd::StoreEnclaveDecryptionKey
+                | KeyVaultCmd::StoreWelcome
+                | KeyVaultCmd::ProvisionSharedKey
+        )
+    }
+
+    /// The maximum length of the frames of the command's requests and responses.
+    /// The welcome messages grow with the roster size, and the replicated requests may carry them.
+    pub fn max_frame_len(&self) -> u64 {
+        match self {
+            KeyVaultCmd::StoreWelcome | KeyVaultCmd::RecoverWelcome | KeyVaultCmd::Replicate => {
+                *KEY_VAULT_MAX_WELCOME_FRAME_LEN
+            }
+            _ => *KEY_VAULT_MAX_FRAME_LEN,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct KeyVaultRequest<B: RequestBody> {
+    version: u32,
     cmd: KeyVaultCmd,
     body: B,
 }
 
 impl<B: RequestBody> KeyVaultRequest<B> {
     pub fn new(cmd: KeyVaultCmd, body: B) -> KeyVaultRequest<B> {
-        KeyVaultRequest { cmd, body }
+        KeyVaultRequest {
+            version: KEY_VAULT_PROTOCOL_VERSION,
+            cmd,
+            body,
+        }
+    }
+
+    pub(crate) fn cmd(&self) -> KeyVaultCmd {
+        self.cmd
+    }
+
+    /// Send the request in the protocol version negotiated with the key-vault node.
+    pub(crate) fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+}
+
+/// A typed command with its request body, which has the same encoding as `KeyVaultRequest`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", content = "body")]
+pub enum KeyVaultCommand {
+    StorePathSecret(BackupPathSecretRequestBody),
+    RecoverPathSecret(RecoverPathSecretRequestBody),
+    ManuallyStorePathSecrets(BackupPathSecretsRequestBody),
+    ManuallyRecoverPathSecrets(RecoverPathSecretsRequestBody),
+    StoreEnclaveDecryptionKey(BackupEnclaveDecryptionKeyRequestBody),
+    RecoverEnclaveDecryptionKey(RecoverEnclaveDecryptionKeyRequestBody),
+    ListEnclaveDecryptionKeys(ListEnclaveDecryptionKeysRequestBody),
+    StoreWelcome(StoreWelcomeRequestBody),
+    RecoverWelcome(RecoverWelcomeRequestBody),
+    ProvisionSharedKey(ProvisionSharedKeyRequestBody),
+    Replicate(ReplicateRequestBody),
+    GetCapabilities(GetCapabilitiesRequestBody),
+}
+
+impl KeyVaultCommand {
+    pub fn cmd(&self) -> KeyVaultCmd {
+        match self {
+            KeyVaultCommand::StorePathSecret(_) => KeyVaultCmd::StorePathSecret,
+            KeyVaultCommand::RecoverPathSecret(_) => KeyVaultCmd::RecoverPathSecret,
+            KeyVaultCommand::ManuallyStorePathSecrets(_) => KeyVaultCmd::ManuallyStorePathSecrets,
+            KeyVaultCommand::ManuallyRecoverPathSecrets(_) => {
+                KeyVaultCmd::ManuallyRecoverPathSecrets
+            }
+            KeyVaultCommand::StoreEnclaveDecryptionKey(_) => KeyVaultCmd::StoreEnclaveDecryptionKey,
+            KeyVaultCommand::RecoverEnclaveDecryptionKey(_) => {
+                KeyVaultCmd::RecoverEnclaveDecryptionKey
+            }
+            KeyVaultCommand::ListEnclaveDecryptionKeys(_) => KeyVaultCmd::ListEnclaveDecryptionKeys,
+            KeyVaultCommand::StoreWelcome(_) => KeyVaultCmd::StoreWelcome,
+            KeyVaultCommand::RecoverWelcome(_) => KeyVaultCmd::RecoverWelcome,
+            KeyVaultCommand::ProvisionSharedKey(_) => KeyVaultCmd::ProvisionSharedKey,
+            KeyVaultCommand::Replicate(_) => KeyVaultCmd::Replicate,
+            KeyVaultCommand::GetCapabilities(_) => KeyVaultCmd::GetCapabilities,
+        }
+    }
+}
+
+/// A request received by the key-vault node before the command is decoded.
+/// The legacy clients don't send the version, which is regarded as version 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawKeyVaultRequest {
+    #[serde(default)]
+    version: u32,
+    cmd: String,
+    #[serde(default)]
+    body: Value,
+}
+
+impl RawKeyVaultRequest {
+    pub fn decode(msg: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(msg)
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Decode the typed command. An unknown command is reported as an error instead of a panic,
+    /// since it's sent by a client newer than this node.
+    /// A request in a protocol version this node doesn't support is rejected too,
+    /// except for the capabilities with which the client negotiates the version.
+    pub fn into_command(self) -> Result<KeyVaultCommand, KeyVaultError> {
+        let cmd = serde_json::from_value::<KeyVaultCmd>(Value::String(self.cmd.clone()))
+            .map_err(|_| KeyVaultError::unsupported_command(&self.cmd))?;
+        if cmd != KeyVaultCmd::GetCapabilities
+            && (self.version < MIN_KEY_VAULT_PROTOCOL_VERSION
+                || self.version > KEY_VAULT_PROTOCOL_VERSION)
+        {
+            return Err(KeyVaultError::unsupported_version(
+                self.version,
+                MIN_KEY_VAULT_PROTOCOL_VERSION,
+                KEY_VAULT_PROTOCOL_VERSION,
+            ));
+        }
+        let mut request = Map::new();
+        request.insert("cmd".to_string(), Value::String(self.cmd));
+        request.insert("body".to_string(), self.body);
+
+        serde_json::from_value(Value::Object(request)).map_err(KeyVaultError::invalid_request)
     }
 }
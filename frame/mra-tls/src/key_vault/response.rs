@@ -1,5 +1,11 @@
-use serde::{Deserialize, Serialize};
-use std::{fmt::Debug, vec::Vec};
+use crate::key_vault::{
+    error::KeyVaultError, request::KeyVaultCmd, KEY_VAULT_PROTOCOL_VERSION,
+    MIN_KEY_VAULT_PROTOCOL_VERSION,
+};
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{cmp, fmt::Debug, vec::Vec};
 
 /// A request body to recover path secret from key-vault server
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -32,3 +38,168 @@ impl RecoveredPathSecret {
         &self.id[..]
     }
 }
+
+/// A response body of the welcome message encrypted to the joiner's encryption key
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RecoveredWelcome {
+    #[serde(with = "crate::key_vault::base64_bytes")]
+    encrypted_welcome: Vec<u8>,
+}
+
+impl RecoveredWelcome {
+    pub fn new(encrypted_welcome: Vec<u8>) -> Self {
+        RecoveredWelcome { encrypted_welcome }
+    }
+
+    pub fn encrypted_welcome(&self) -> &[u8] {
+        &self.encrypted_welcome[..]
+    }
+}
+
+/// A response body of the secret key shared by the enclaves with the same MRSIGNER and ISVPRODID
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProvisionedSharedKey {
+    #[serde(with = "serde_bytes")]
+    key: Vec<u8>,
+}
+
+impl ProvisionedSharedKey {
+    pub fn new(key: Vec<u8>) -> Self {
+        ProvisionedSharedKey { key }
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key[..]
+    }
+}
+
+/// A backed up version of enclave decryption key
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BackedUpEnclaveDecryptionKey {
+    roster_idx: u32,
+    version: u32,
+    /// The hash of the corresponding encryption key
+    #[serde(with = "serde_bytes")]
+    fingerprint: Vec<u8>,
+    /// The commitment to the whole key if the backup is a share of the secret sharing
+    #[serde(default, with = "serde_bytes")]
+    commitment: Vec<u8>,
+}
+
+impl BackedUpEnclaveDecryptionKey {
+    pub fn new(roster_idx: u32, version: u32, fingerprint: Vec<u8>) -> Self {
+        BackedUpEnclaveDecryptionKey {
+            roster_idx,
+            version,
+            fingerprint,
+            commitment: Vec::new(),
+        }
+    }
+
+    pub fn with_commitment(mut self, commitment: Vec<u8>) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn roster_idx(&self) -> u32 {
+        self.roster_idx
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn fingerprint(&self) -> &[u8] {
+        &self.fingerprint[..]
+    }
+
+    pub fn commitment(&self) -> &[u8] {
+        &self.commitment[..]
+    }
+}
+
+/// The protocol versions and the commands which the key-vault node supports
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Capabilities {
+    protocol_version: u32,
+    min_protocol_version: u32,
+    commands: Vec<KeyVaultCmd>,
+}
+
+impl Capabilities {
+    pub fn new(commands: Vec<KeyVaultCmd>) -> Self {
+        Capabilities {
+            protocol_version: KEY_VAULT_PROTOCOL_VERSION,
+            min_protocol_version: MIN_KEY_VAULT_PROTOCOL_VERSION,
+            commands,
+        }
+    }
+
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version
+    }
+
+    pub fn min_protocol_version(&self) -> u32 {
+        self.min_protocol_version
+    }
+
+    pub fn supports(&self, cmd: KeyVaultCmd) -> bool {
+        self.commands.contains(&cmd)
+    }
+
+    /// The newest protocol version which both this client and the key-vault node support.
+    pub fn negotiate_version(&self) -> Result<u32, KeyVaultError> {
+        let version = cmp::min(KEY_VAULT_PROTOCOL_VERSION, self.protocol_version);
+        if version < MIN_KEY_VAULT_PROTOCOL_VERSION || version < self.min_protocol_version {
+            return Err(KeyVaultError::unsupported_version(
+                KEY_VAULT_PROTOCOL_VERSION,
+                self.min_protocol_version,
+                self.protocol_version,
+            ));
+        }
+
+        Ok(version)
+    }
+}
+
+/// The envelope of the responses since protocol version 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyVaultResponse {
+    version: u32,
+    result: Result<Value, KeyVaultError>,
+}
+
+impl KeyVaultResponse {
+    /// Encode the result in the format of the requested version,
+    /// so that the legacy clients get the bare body as before.
+    /// The legacy protocol has no way to return an error, so the connection is closed without a response.
+    pub fn encode(
+        requested_version: u32,
+        result: Result<Value, KeyVaultError>,
+    ) -> anyhow::Result<Vec<u8>> {
+        if requested_version == 0 {
+            let body = result?;
+            return serde_json::to_vec(&body).map_err(Into::into);
+        }
+
+        let resp = KeyVaultResponse {
+            version: cmp::min(requested_version, KEY_VAULT_PROTOCOL_VERSION),
+            result,
+        };
+        serde_json::to_vec(&resp).map_err(Into::into)
+    }
+
+    /// Decode the body from the response, which is the bare body if the key-vault node is a legacy one.
+    pub fn decode<DE: DeserializeOwned>(resp: Value) -> anyhow::Result<DE> {
+        let is_envelope = resp.as_object().map_or(false, |obj| {
+            obj.contains_key("version") && obj.contains_key("result")
+        });
+        if !is_envelope {
+            return serde_json::from_value(resp).map_err(Into::into);
+        }
+
+        let resp: KeyVaultResponse = serde_json::from_value(resp)?;
+        let body = resp.result.map_err(|e| anyhow!(e))?;
+        serde_json::from_value(body).map_err(Into::into)
+    }
+}
@@ -9,12 +9,15 @@ mod connection;
 mod error;
 mod key;
 pub mod key_vault;
+pub mod policy;
 pub mod server;
 #[cfg(debug_assertions)]
 pub mod tests;
 mod verifier;
 
 pub use client::Client;
-pub use config::{AttestedTlsConfig, ClientConfig, ServerConfig};
+pub use config::{AttestedTlsConfig, ClientConfig, RenewalHandle, ServerConfig};
 pub use error::MraTLSError;
-pub use server::{RequestHandler, Server};
+pub use policy::{MeasurementPolicy, SharedMeasurementPolicy, SignedMeasurementPolicy, SignerRule};
+pub use server::{RequestHandler, Server, ShutdownHandle};
+pub use verifier::PeerIdentity;
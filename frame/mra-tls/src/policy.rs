@@ -0,0 +1,221 @@
+use crate::error::{MraTLSError, Result};
+use crate::verifier::PeerIdentity;
+use anyhow::anyhow;
+use frame_config::EnclaveMeasurement;
+use hex::FromHex;
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+use std::{
+    string::String,
+    sync::{Arc, SgxRwLock},
+    vec::Vec,
+};
+
+/// The policy deciding which attested enclaves are accepted as peers.
+/// An enclave is accepted if its MRENCLAVE is in the allowlist,
+/// or if it is signed by an allowed MRSIGNER with the product ID and at least the minimum ISV_SVN,
+/// so that a new enclave build can be rolled out without locking out the running one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeasurementPolicy {
+    /// A policy can only be replaced by one with a greater version,
+    /// so that an older signed policy file can't be replayed to roll back an upgrade.
+    #[serde(default)]
+    version: u64,
+    #[serde(
+        default,
+        serialize_with = "serialize_measurements",
+        deserialize_with = "deserialize_measurements"
+    )]
+    mr_enclaves: Vec<[u8; 32]>,
+    #[serde(default)]
+    signers: Vec<SignerRule>,
+}
+
+/// Accepts any enclave signed by `mr_signer` for the product `isv_prod_id`
+/// whose security version is at least `min_isv_svn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerRule {
+    #[serde(
+        serialize_with = "serialize_measurement",
+        deserialize_with = "deserialize_measurement"
+    )]
+    mr_signer: [u8; 32],
+    isv_prod_id: u16,
+    min_isv_svn: u16,
+}
+
+impl SignerRule {
+    pub fn new(mr_signer: [u8; 32], isv_prod_id: u16, min_isv_svn: u16) -> Self {
+        SignerRule {
+            mr_signer,
+            isv_prod_id,
+            min_isv_svn,
+        }
+    }
+
+    fn accepts(&self, peer: &PeerIdentity) -> bool {
+        &self.mr_signer == peer.mr_signer()
+            && self.isv_prod_id == peer.isv_prod_id()
+            && self.min_isv_svn <= peer.isv_svn()
+    }
+}
+
+impl MeasurementPolicy {
+    pub fn new(version: u64, mr_enclaves: Vec<[u8; 32]>, signers: Vec<SignerRule>) -> Self {
+        MeasurementPolicy {
+            version,
+            mr_enclaves,
+            signers,
+        }
+    }
+
+    /// The policy accepting only the enclave with exactly the given measurement.
+    pub fn from_measurement(measurement: EnclaveMeasurement) -> Self {
+        MeasurementPolicy {
+            version: 0,
+            mr_enclaves: vec![measurement.mr_enclave()],
+            signers: vec![],
+        }
+    }
+
+    /// Load the signed policy file and verify its signature by the policy signer's public key.
+    pub fn load_signed<P: AsRef<Path>>(path: P, public_key: &[u8]) -> Result<Self> {
+        let content = std::untrusted::fs::read(path)?;
+        serde_json::from_slice::<SignedMeasurementPolicy>(&content)?.verify(public_key)
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn mr_enclaves(&self) -> &[[u8; 32]] {
+        &self.mr_enclaves[..]
+    }
+
+    pub fn signers(&self) -> &[SignerRule] {
+        &self.signers[..]
+    }
+
+    pub fn verify(&self, peer: &PeerIdentity) -> Result<()> {
+        if self.mr_enclaves.contains(peer.mr_enclave())
+            || self.signers.iter().any(|signer| signer.accepts(peer))
+        {
+            return Ok(());
+        }
+
+        Err(MraTLSError::Error(anyhow!(
+            "The peer enclave is not allowed by the measurement policy version {}: mr_enclave: {:?}, mr_signer: {:?}, isv_prod_id: {}, isv_svn: {}",
+            self.version,
+            peer.mr_enclave(),
+            peer.mr_signer(),
+            peer.isv_prod_id(),
+            peer.isv_svn()
+        )))
+    }
+}
+
+/// A policy file signed by the operator's ECDSA P-256 key.
+/// `policy` is the JSON encoded `MeasurementPolicy` exactly as it was signed,
+/// and `signature` is the hex encoded ASN.1 DER signature over it with SHA-256,
+/// as produced by `openssl dgst -sha256 -sign`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMeasurementPolicy {
+    policy: String,
+    signature: String,
+}
+
+impl SignedMeasurementPolicy {
+    /// `public_key` is the uncompressed SEC1 encoded point of the policy signer's key.
+    pub fn verify(&self, public_key: &[u8]) -> Result<MeasurementPolicy> {
+        let signature = hex::decode(&self.signature)
+            .map_err(|e| anyhow!("Failed to decode the policy signature: {:?}", e))?;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+            .verify(self.policy.as_bytes(), &signature)
+            .map_err(|_| anyhow!("Invalid signature of the measurement policy"))?;
+
+        serde_json::from_str(&self.policy).map_err(Into::into)
+    }
+}
+
+/// The measurement policy shared by the verifiers of all connections,
+/// which can be replaced at runtime without restarting the servers.
+#[derive(Debug, Clone)]
+pub struct SharedMeasurementPolicy(Arc<SgxRwLock<MeasurementPolicy>>);
+
+impl SharedMeasurementPolicy {
+    pub fn new(policy: MeasurementPolicy) -> Self {
+        SharedMeasurementPolicy(Arc::new(SgxRwLock::new(policy)))
+    }
+
+    /// Replace the policy. Connections established afterwards are verified by the new one.
+    pub fn update(&self, policy: MeasurementPolicy) -> Result<()> {
+        let mut current = self
+            .0
+            .write()
+            .map_err(|e| anyhow!("Failed to acquire the measurement policy lock: {:?}", e))?;
+        if policy.version <= current.version {
+            return Err(MraTLSError::Error(anyhow!(
+                "The measurement policy version {} must be greater than the current version {}",
+                policy.version,
+                current.version
+            )));
+        }
+        *current = policy;
+
+        Ok(())
+    }
+
+    pub fn get(&self) -> Result<MeasurementPolicy> {
+        let policy = self
+            .0
+            .read()
+            .map_err(|e| anyhow!("Failed to acquire the measurement policy lock: {:?}", e))?;
+        Ok(policy.clone())
+    }
+
+    pub(crate) fn verify(&self, peer: &PeerIdentity) -> Result<()> {
+        self.0
+            .read()
+            .map_err(|e| anyhow!("Failed to acquire the measurement policy lock: {:?}", e))?
+            .verify(peer)
+    }
+}
+
+fn serialize_measurement<S>(
+    measurement: &[u8; 32],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&hex::encode(measurement))
+}
+
+fn deserialize_measurement<'de, D>(deserializer: D) -> std::result::Result<[u8; 32], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    <[u8; 32]>::from_hex(&string).map_err(de::Error::custom)
+}
+
+fn serialize_measurements<S>(
+    measurements: &[[u8; 32]],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(measurements.iter().map(hex::encode))
+}
+
+fn deserialize_measurements<'de, D>(deserializer: D) -> std::result::Result<Vec<[u8; 32]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|string| <[u8; 32]>::from_hex(string).map_err(de::Error::custom))
+        .collect()
+}
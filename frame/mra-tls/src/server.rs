@@ -1,46 +1,158 @@
 use crate::config::ServerConfig;
 use crate::connection::Connection;
 use crate::error::Result;
+use crate::verifier::{AttestedReportVerifier, PeerIdentity};
+use std::net::{TcpListener, TcpStream};
 use std::string::String;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver},
+    Arc, SgxMutex,
+};
+use std::thread;
 use std::vec::Vec;
-use tracing::error;
+use tracing::{error, info};
 
 pub trait RequestHandler {
-    fn handle_json(&self, msg: &[u8]) -> anyhow::Result<Vec<u8>>;
+    /// Handle a request. `peer` is the identity of the attested client,
+    /// which is `None` if the server doesn't verify clients.
+    fn handle_json(&self, msg: &[u8], peer: Option<&PeerIdentity>) -> anyhow::Result<Vec<u8>>;
 }
 
 pub struct Server {
     address: String,
     config: ServerConfig,
+    is_shutdown: Arc<AtomicBool>,
+}
+
+/// A handle to stop the server running in another thread.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    address: String,
+    is_shutdown: Arc<AtomicBool>,
+}
+
+impl ShutdownHandle {
+    /// Stop accepting new connections.
+    /// `Server::run` returns after the workers finish serving the accepted connections.
+    pub fn shutdown(&self) {
+        if self.is_shutdown.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake up the listener blocking on accept so that it observes the flag.
+        if let Err(e) = TcpStream::connect(&self.address) {
+            error!(
+                "Failed to wake up the listener on {}: {:?}",
+                self.address, e
+            );
+        }
+    }
 }
 
 impl Server {
     pub fn new(address: String, config: ServerConfig) -> Self {
-        Server { address, config }
+        Server {
+            address,
+            config,
+            is_shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle {
+            address: self.address.clone(),
+            is_shutdown: self.is_shutdown.clone(),
+        }
     }
 
-    pub fn run<H: RequestHandler + Clone>(&mut self, handler: H) -> Result<()> {
-        let listener = std::net::TcpListener::bind(&self.address)?;
+    /// Serve the connections by a bounded pool of worker threads until it is shut down.
+    pub fn run<H>(&mut self, handler: H) -> Result<()>
+    where
+        H: RequestHandler + Clone + Send + 'static,
+    {
+        let listener = TcpListener::bind(&self.address)?;
+        let workers = self.config.workers();
+        // The channel is bounded, so the listener stops accepting while all workers are busy.
+        let (sender, receiver) = mpsc::sync_channel::<TcpStream>(workers);
+        let receiver = Arc::new(SgxMutex::new(receiver));
+        let tls = Arc::new(self.config.tls().clone());
+
+        let mut worker_handles = Vec::with_capacity(workers);
+        for i in 0..workers {
+            let worker = Worker {
+                receiver: receiver.clone(),
+                handler: handler.clone(),
+                tls: tls.clone(),
+                verifier: self.config.verifier().cloned(),
+                max_frame_len: self.config.max_frame_len(),
+            };
+            let handle = thread::Builder::new()
+                .name(format!("mra-tls-worker-{}", i))
+                .spawn(move || worker.serve())?;
+            worker_handles.push(handle);
+        }
 
-        #[cfg(not(test))]
         for stream in listener.incoming() {
-            let session = rustls::ServerSession::new(&Arc::new(self.config.tls().clone()));
-            match Connection::new(session, stream?).serve_json(handler.clone()) {
-                Ok(_) => {}
-                Err(e) => error!("{:?}", e),
+            if self.is_shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("Failed to accept a connection: {:?}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = stream
+                .set_read_timeout(Some(self.config.read_timeout()))
+                .and_then(|_| stream.set_write_timeout(Some(self.config.write_timeout())))
+            {
+                error!("Failed to set timeouts: {:?}", e);
+                continue;
+            }
+            if sender.send(stream).is_err() {
+                error!("All workers have stopped");
+                break;
             }
         }
 
-        #[cfg(test)]
-        for stream in listener.incoming().take(1) {
-            let session = rustls::ServerSession::new(&Arc::new(self.config.tls().clone()));
-            match Connection::new(session, stream?).serve_json(handler.clone()) {
-                Ok(_) => {}
-                Err(e) => error!("{:?}", e),
+        // Closing the channel lets the workers exit after serving the queued connections.
+        drop(sender);
+        for handle in worker_handles {
+            if handle.join().is_err() {
+                error!("A worker panicked");
             }
         }
+        info!("The server on {} has been shut down", self.address);
 
         Ok(())
     }
 }
+
+struct Worker<H: RequestHandler> {
+    receiver: Arc<SgxMutex<Receiver<TcpStream>>>,
+    handler: H,
+    tls: Arc<rustls::ServerConfig>,
+    verifier: Option<AttestedReportVerifier>,
+    max_frame_len: u64,
+}
+
+impl<H: RequestHandler + Clone> Worker<H> {
+    fn serve(self) {
+        loop {
+            // The lock must be released before serving the connection.
+            let received = self.receiver.lock().unwrap().recv();
+            let stream = match received {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            let session = rustls::ServerSession::new(&self.tls);
+            if let Err(e) = Connection::new(session, stream)
+                .set_max_frame_len(self.max_frame_len)
+                .serve_json(self.handler.clone(), self.verifier.as_ref())
+            {
+                error!("{:?}", e);
+            }
+        }
+    }
+}
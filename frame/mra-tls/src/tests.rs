@@ -1,12 +1,34 @@
-use crate::{AttestedTlsConfig, Client, ClientConfig, RequestHandler, Server, ServerConfig};
+use crate::key_vault::{
+    error::{KeyVaultError, KeyVaultErrorCode},
+    request::{
+        KeyVaultCmd, KeyVaultCommand, KeyVaultRequest, ProvisionSharedKeyRequestBody,
+        RawKeyVaultRequest, RecoverWelcomeRequestBody,
+    },
+    response::{Capabilities, KeyVaultResponse, RecoveredWelcome},
+    KEY_VAULT_PROTOCOL_VERSION, MIN_KEY_VAULT_PROTOCOL_VERSION,
+};
+use crate::{cert::parse_ra_cert, key::NistP256KeyPair, verifier::AttestedReportVerifier};
+use crate::{
+    AttestedTlsConfig, Client, ClientConfig, MeasurementPolicy, PeerIdentity, RequestHandler,
+    Server, ServerConfig, SharedMeasurementPolicy, ShutdownHandle, SignedMeasurementPolicy,
+    SignerRule,
+};
 use anyhow::Result;
-use frame_config::{ENCLAVE_MEASUREMENT, IAS_ROOT_CERT};
+use frame_config::{ATTESTED_CERT_VALIDITY_SECS, ENCLAVE_MEASUREMENT, IAS_ROOT_CERT};
 use lazy_static::lazy_static;
-use serde_json::Value;
+use remote_attestation::{AttestationEvidence, AttestationProvider, AttestedReport};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+};
+use serde_json::{json, Value};
+use sgx_types::sgx_report_data_t;
 use std::{
     env,
+    net::TcpStream,
     string::{String, ToString},
-    thread,
+    sync::Arc,
+    thread::{self, JoinHandle},
     time::Duration,
     vec::Vec,
 };
@@ -19,22 +41,375 @@ lazy_static! {
     };
 }
 const LISTEN_ADDRESS: &str = "0.0.0.0:12345";
+// The root certificate and the attestation report of the mock IAS in `tests/mock-ias`,
+// which the host of the unit tests generates for each run.
+const MOCK_IAS_ROOT_CERT: &str = "MOCK_IAS_ROOT_CERT";
+const MOCK_IAS_REPORT: &str = "MOCK_IAS_REPORT";
 
 pub fn run_tests() -> bool {
-    check_all_passed!(run_tests!(test_request_response,),)
+    check_all_passed!(run_tests!(
+        test_request_response,
+        test_stalled_client_does_not_block_others,
+        test_shutdown,
+        test_key_vault_protocol_compatibility,
+        test_key_vault_version_negotiation,
+        test_provision_shared_key_request,
+        test_measurement_policy,
+        test_signed_measurement_policy,
+        test_cert_extension_parsing,
+        test_verify_mock_report,
+        test_cert_validity_and_freshness,
+        test_cert_renewal,
+    ),)
 }
 
 #[derive(Default, Clone)]
 struct EchoHandler;
 
 impl RequestHandler for EchoHandler {
-    fn handle_json(&self, msg: &[u8]) -> Result<Vec<u8>> {
+    fn handle_json(&self, msg: &[u8], _peer: Option<&PeerIdentity>) -> Result<Vec<u8>> {
         let msg_json: Value = serde_json::from_slice(&msg)?;
         serde_json::to_vec(&msg_json).map_err(Into::into)
     }
 }
 
 fn test_request_response() {
+    let (attested_tls_config, client_config) = attested_tls_configs();
+    let (shutdown_handle, server) = start_server(attested_tls_config);
+
+    let mut client = Client::new(&*SERVER_ADDRESS, &client_config).unwrap();
+    let msg = r#"{
+        "message": "Hello test_request_response"
+    }"#;
+    let resp: String = client.send_json(msg).unwrap();
+
+    assert_eq!(msg, resp);
+    shutdown_handle.shutdown();
+    server.join().unwrap();
+}
+
+fn test_stalled_client_does_not_block_others() {
+    let (attested_tls_config, client_config) = attested_tls_configs();
+    let (shutdown_handle, server) = start_server(attested_tls_config);
+
+    // A client which connects but never sends anything occupies only one worker.
+    let _stalled = TcpStream::connect(&*SERVER_ADDRESS).unwrap();
+    let mut client = Client::new(&*SERVER_ADDRESS, &client_config).unwrap();
+    let msg = r#"{
+        "message": "Hello test_stalled_client_does_not_block_others"
+    }"#;
+    let resp: String = client.send_json(msg).unwrap();
+
+    assert_eq!(msg, resp);
+    shutdown_handle.shutdown();
+    server.join().unwrap();
+}
+
+fn test_shutdown() {
+    let (attested_tls_config, client_config) = attested_tls_configs();
+    let (shutdown_handle, server) = start_server(attested_tls_config);
+
+    shutdown_handle.shutdown();
+    server.join().unwrap();
+
+    assert!(Client::new(&*SERVER_ADDRESS, &client_config).is_err());
+}
+
+fn test_provision_shared_key_request() {
+    let request = serde_json::to_vec(&KeyVaultRequest::new(
+        KeyVaultCmd::ProvisionSharedKey,
+        ProvisionSharedKeyRequestBody::new("master-key".to_string(), vec![7u8; 16]),
+    ))
+    .unwrap();
+    let command = RawKeyVaultRequest::decode(&request)
+        .unwrap()
+        .into_command()
+        .unwrap();
+    // The stored key has to be replicated, so that all the nodes provision the same one.
+    assert!(command.cmd().is_write());
+    match command {
+        KeyVaultCommand::ProvisionSharedKey(body) => {
+            assert_eq!(body.name(), "master-key");
+            assert_eq!(body.proposed_key(), &[7u8; 16]);
+        }
+        command => panic!("unexpected command: {:?}", command),
+    }
+}
+
+fn test_key_vault_protocol_compatibility() {
+    // A legacy request has no version and gets the bare body.
+    let legacy = br#"{"cmd":"RecoverWelcome","body":{"roster_idx":1}}"#;
+    let request = RawKeyVaultRequest::decode(legacy).unwrap();
+    assert_eq!(request.version(), 0);
+    let command = request.into_command().unwrap();
+    assert_eq!(command.cmd(), KeyVaultCmd::RecoverWelcome);
+    let body = serde_json::to_value(RecoveredWelcome::new(vec![1, 2, 3])).unwrap();
+    let resp = KeyVaultResponse::encode(0, Ok(body.clone())).unwrap();
+    let decoded: RecoveredWelcome =
+        KeyVaultResponse::decode(serde_json::from_slice(&resp).unwrap()).unwrap();
+    assert_eq!(decoded.encrypted_welcome(), &[1, 2, 3]);
+
+    // A versioned request gets the envelope, which carries a structured error.
+    let versioned = serde_json::to_vec(&KeyVaultRequest::new(
+        KeyVaultCmd::RecoverWelcome,
+        RecoverWelcomeRequestBody::new(1),
+    ))
+    .unwrap();
+    let request = RawKeyVaultRequest::decode(&versioned).unwrap();
+    assert_eq!(request.version(), KEY_VAULT_PROTOCOL_VERSION);
+    match request.into_command().unwrap() {
+        KeyVaultCommand::RecoverWelcome(body) => assert_eq!(body.roster_idx(), 1),
+        command => panic!("unexpected command: {:?}", command),
+    }
+    let resp = KeyVaultResponse::encode(
+        KEY_VAULT_PROTOCOL_VERSION,
+        Err(KeyVaultError::internal("not found")),
+    )
+    .unwrap();
+    let err = KeyVaultResponse::decode::<RecoveredWelcome>(serde_json::from_slice(&resp).unwrap())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<KeyVaultError>().unwrap().code(),
+        KeyVaultErrorCode::Internal
+    );
+
+    // An unknown command from a newer client is an error instead of a panic.
+    let newer = br#"{"version":2,"cmd":"SomethingNew","body":{}}"#;
+    let err = RawKeyVaultRequest::decode(newer)
+        .unwrap()
+        .into_command()
+        .unwrap_err();
+    assert_eq!(err.code(), KeyVaultErrorCode::UnsupportedCommand);
+    let invalid = br#"{"version":1,"cmd":"RecoverWelcome","body":{"unknown":1}}"#;
+    let err = RawKeyVaultRequest::decode(invalid)
+        .unwrap()
+        .into_command()
+        .unwrap_err();
+    assert_eq!(err.code(), KeyVaultErrorCode::InvalidRequest);
+}
+
+fn test_key_vault_version_negotiation() {
+    // A node of the same build negotiates the current version.
+    let capabilities = Capabilities::new(vec![KeyVaultCmd::GetCapabilities]);
+    assert_eq!(
+        capabilities.negotiate_version().unwrap(),
+        KEY_VAULT_PROTOCOL_VERSION
+    );
+
+    // A newer node which still talks this version is talked to in this version.
+    let newer: Capabilities = serde_json::from_value(json!({
+        "protocol_version": KEY_VAULT_PROTOCOL_VERSION + 1,
+        "min_protocol_version": MIN_KEY_VAULT_PROTOCOL_VERSION,
+        "commands": ["GetCapabilities"],
+    }))
+    .unwrap();
+    assert_eq!(
+        newer.negotiate_version().unwrap(),
+        KEY_VAULT_PROTOCOL_VERSION
+    );
+
+    // A node which dropped this version is a structured error.
+    let incompatible: Capabilities = serde_json::from_value(json!({
+        "protocol_version": KEY_VAULT_PROTOCOL_VERSION + 2,
+        "min_protocol_version": KEY_VAULT_PROTOCOL_VERSION + 1,
+        "commands": ["GetCapabilities"],
+    }))
+    .unwrap();
+    assert_eq!(
+        incompatible.negotiate_version().unwrap_err().code(),
+        KeyVaultErrorCode::UnsupportedVersion
+    );
+
+    // The node rejects a request in a version it doesn't know, but always answers the capabilities.
+    let newer_request = format!(
+        r#"{{"version":{},"cmd":"RecoverWelcome","body":{{"roster_idx":1}}}}"#,
+        KEY_VAULT_PROTOCOL_VERSION + 1
+    );
+    let err = RawKeyVaultRequest::decode(newer_request.as_bytes())
+        .unwrap()
+        .into_command()
+        .unwrap_err();
+    assert_eq!(err.code(), KeyVaultErrorCode::UnsupportedVersion);
+    let capabilities_request = format!(
+        r#"{{"version":{},"cmd":"GetCapabilities","body":null}}"#,
+        KEY_VAULT_PROTOCOL_VERSION + 1
+    );
+    let command = RawKeyVaultRequest::decode(capabilities_request.as_bytes())
+        .unwrap()
+        .into_command()
+        .unwrap();
+    assert_eq!(command.cmd(), KeyVaultCmd::GetCapabilities);
+}
+
+fn test_measurement_policy() {
+    let (old_enclave, new_enclave, signer) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+    let old_peer = PeerIdentity::new(old_enclave, signer, 1, 1);
+    let new_peer = PeerIdentity::new(new_enclave, signer, 1, 2);
+
+    let policy = SharedMeasurementPolicy::new(MeasurementPolicy::new(1, vec![old_enclave], vec![]));
+    assert!(policy.verify(&old_peer).is_ok());
+    assert!(policy.verify(&new_peer).is_err());
+
+    // During the upgrade both builds are accepted by the signer and the minimum security version.
+    let upgrade = MeasurementPolicy::new(2, vec![], vec![SignerRule::new(signer, 1, 1)]);
+    policy.update(upgrade.clone()).unwrap();
+    assert!(policy.verify(&old_peer).is_ok());
+    assert!(policy.verify(&new_peer).is_ok());
+    assert!(policy
+        .verify(&PeerIdentity::new(new_enclave, signer, 2, 2))
+        .is_err());
+
+    // Raising the minimum security version locks out the old build.
+    policy
+        .update(MeasurementPolicy::new(
+            3,
+            vec![],
+            vec![SignerRule::new(signer, 1, 2)],
+        ))
+        .unwrap();
+    assert!(policy.verify(&old_peer).is_err());
+    assert!(policy.verify(&new_peer).is_ok());
+
+    // An older policy can't be replayed to roll back the upgrade.
+    assert!(policy.update(upgrade).is_err());
+    assert_eq!(policy.get().unwrap().version(), 3);
+}
+
+fn test_signed_measurement_policy() {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+    let key_pair =
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref()).unwrap();
+    let policy = MeasurementPolicy::new(1, vec![[1u8; 32]], vec![SignerRule::new([3u8; 32], 1, 2)]);
+    let encoded = serde_json::to_string(&policy).unwrap();
+    let signature = key_pair.sign(&rng, encoded.as_bytes()).unwrap();
+
+    let signed: SignedMeasurementPolicy = serde_json::from_value(json!({
+        "policy": encoded,
+        "signature": hex::encode(signature.as_ref()),
+    }))
+    .unwrap();
+    assert_eq!(
+        signed.verify(key_pair.public_key().as_ref()).unwrap(),
+        policy
+    );
+
+    let tampered: SignedMeasurementPolicy = serde_json::from_value(json!({
+        "policy": encoded.replace("01", "02"),
+        "signature": hex::encode(signature.as_ref()),
+    }))
+    .unwrap();
+    assert!(tampered.verify(key_pair.public_key().as_ref()).is_err());
+}
+
+fn test_cert_extension_parsing() {
+    let key_pair = NistP256KeyPair::new().unwrap();
+    let payload = br#"{"report":"payload"}"#;
+    let cert =
+        key_pair.create_cert_with_extension("Issuer", "Subject", payload, Duration::from_secs(60));
+
+    let parsed = parse_ra_cert(&cert).unwrap();
+    assert_eq!(parsed.cert_ext_payload, payload.to_vec());
+    assert_eq!(parsed.not_after - parsed.not_before, 60);
+    // The uncompressed public key is attested as the report data.
+    let raw_pubkey = (parsed.pubkey.1).0.to_bytes();
+    assert_eq!(raw_pubkey[0], 4);
+    assert_eq!(&raw_pubkey[1..], &key_pair.report_data().d[..]);
+
+    assert!(parse_ra_cert(&cert[..cert.len() - 1]).is_err());
+    assert!(parse_ra_cert(payload).is_err());
+}
+
+fn test_verify_mock_report() {
+    let payload = serde_json::to_vec(&mock_evidence()).unwrap();
+    let cert = NistP256KeyPair::new().unwrap().create_cert_with_extension(
+        "Issuer",
+        "Subject",
+        &payload,
+        Duration::from_secs(60),
+    );
+
+    // The mock report is verified by the mock root CA,
+    // but it doesn't attest the public key of this certificate.
+    let mock_root_cert = pem::parse(env::var(MOCK_IAS_ROOT_CERT).unwrap())
+        .unwrap()
+        .contents;
+    let verifier = AttestedReportVerifier::new(mock_root_cert, *ENCLAVE_MEASUREMENT);
+    let err = verifier.verify_cert(&cert).unwrap_err();
+    assert!(err.to_string().contains("not equal to report data"));
+
+    // The mock reports are never verified by the IAS root CA.
+    let verifier = AttestedReportVerifier::new(IAS_ROOT_CERT.to_vec(), *ENCLAVE_MEASUREMENT);
+    let err = verifier.verify_cert(&cert).unwrap_err();
+    assert!(!err.to_string().contains("not equal to report data"));
+}
+
+fn test_cert_validity_and_freshness() {
+    assert!(AttestedReportVerifier::verify_validity(1000, 2000, 1500).is_ok());
+    assert!(AttestedReportVerifier::verify_validity(1000, 2000, 2001).is_err());
+    // The difference between the clocks of the peers is tolerated.
+    assert!(AttestedReportVerifier::verify_validity(1000, 2000, 900).is_ok());
+    assert!(AttestedReportVerifier::verify_validity(1000, 2000, 600).is_err());
+
+    assert!(AttestedReportVerifier::verify_freshness(Some(1000), 1060, Some(60)).is_ok());
+    assert!(AttestedReportVerifier::verify_freshness(Some(1000), 1061, Some(60)).is_err());
+    assert!(AttestedReportVerifier::verify_freshness(Some(1000), 1061, None).is_ok());
+    // The ECDSA quotes have no timestamp.
+    assert!(AttestedReportVerifier::verify_freshness(None, 1061, Some(60)).is_ok());
+}
+
+fn test_cert_renewal() {
+    let attested_tls_config =
+        AttestedTlsConfig::new_by_provider(Arc::new(MockIasProvider)).unwrap();
+    let client_config =
+        ClientConfig::from_attested_tls_config(attested_tls_config.clone()).unwrap();
+    let current_cert = || {
+        client_config
+            .tls()
+            .client_auth_cert_resolver
+            .resolve(&[], &[])
+            .unwrap()
+            .cert
+    };
+
+    let cert = current_cert();
+    attested_tls_config.renew().unwrap();
+    // The config in use presents the renewed certificate of a new key pair.
+    let renewed = current_cert();
+    assert_ne!(cert, renewed);
+    let parsed = parse_ra_cert(&renewed[0].0).unwrap();
+    assert_eq!(
+        parsed.not_after - parsed.not_before,
+        *ATTESTED_CERT_VALIDITY_SECS as i64
+    );
+}
+
+/// Attests any keys by the mock report instead of IAS.
+struct MockIasProvider;
+
+impl AttestationProvider for MockIasProvider {
+    fn attest(
+        &self,
+        _report_data: &sgx_report_data_t,
+    ) -> std::result::Result<AttestationEvidence, remote_attestation::Error> {
+        Ok(mock_evidence())
+    }
+}
+
+/// The report is `{"body": ..., "signature": ..., "signing_certificate": ...}` in JSON,
+/// whose fields are the body and the headers of the response.
+fn mock_evidence() -> AttestationEvidence {
+    let report: Value = serde_json::from_str(&env::var(MOCK_IAS_REPORT).unwrap()).unwrap();
+    let report = AttestedReport::from_ias_response(
+        report["body"].as_str().unwrap().as_bytes().to_vec(),
+        report["signature"].as_str().unwrap(),
+        report["signing_certificate"].as_str().unwrap(),
+    )
+    .unwrap();
+    AttestationEvidence::Epid(report)
+}
+
+fn attested_tls_configs() -> (AttestedTlsConfig, ClientConfig) {
     set_env_vars();
     let spid = env::var("SPID").unwrap();
     assert!(!spid.is_empty(), "SPID shouldn't be empty");
@@ -44,28 +419,26 @@ fn test_request_response() {
 
     let attested_tls_config =
         AttestedTlsConfig::new_by_ra(&spid, &ias_url, &sub_key, IAS_ROOT_CERT.to_vec()).unwrap();
-
-    start_server(attested_tls_config.clone(), IAS_ROOT_CERT.to_vec());
-    let client_config = ClientConfig::from_attested_tls_config(attested_tls_config)
+    let client_config = ClientConfig::from_attested_tls_config(attested_tls_config.clone())
         .unwrap()
         .set_attestation_report_verifier(IAS_ROOT_CERT.to_vec(), *ENCLAVE_MEASUREMENT);
-    let mut client = Client::new(&*SERVER_ADDRESS, &client_config).unwrap();
-    let msg = r#"{
-        "message": "Hello test_request_response"
-    }"#;
-    let resp: String = client.send_json(msg).unwrap();
 
-    assert_eq!(msg, resp);
+    (attested_tls_config, client_config)
 }
 
-fn start_server(attested_tls_config: AttestedTlsConfig, ias_root_cert: Vec<u8>) {
+fn start_server(attested_tls_config: AttestedTlsConfig) -> (ShutdownHandle, JoinHandle<()>) {
     let server_config = ServerConfig::from_attested_tls_config(attested_tls_config)
         .unwrap()
-        .set_attestation_report_verifier(ias_root_cert, *ENCLAVE_MEASUREMENT);
+        .set_attestation_report_verifier(IAS_ROOT_CERT.to_vec(), *ENCLAVE_MEASUREMENT)
+        .set_workers(2)
+        .set_timeouts(Duration::from_secs(3), Duration::from_secs(3));
 
     let mut server = Server::new(LISTEN_ADDRESS.to_string(), server_config);
+    let shutdown_handle = server.shutdown_handle();
     let handler = EchoHandler::default();
     let builder = thread::Builder::new().name("mra-tls-test".into());
-    builder.spawn(move || server.run(handler).unwrap()).unwrap();
+    let join_handle = builder.spawn(move || server.run(handler).unwrap()).unwrap();
     thread::sleep(Duration::from_secs(1));
+
+    (shutdown_handle, join_handle)
 }
@@ -1,42 +1,98 @@
 use crate::cert::*;
 use crate::error::{MraTLSError, Result};
+use crate::policy::{MeasurementPolicy, SharedMeasurementPolicy};
 use anyhow::anyhow;
-use frame_config::EnclaveMeasurement;
-use log::debug;
-use remote_attestation::AttestedReport;
+use frame_config::{EnclaveMeasurement, ATTESTATION_MAX_AGE_SECS, DCAP_ROOT_CERT};
+use log::{debug, warn};
+use remote_attestation::{AttestationEvidence, QuoteStatus, QuoteStatusPolicy};
+use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Read};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::vec::Vec;
 
+/// The tolerated difference between the clocks of the peers
+const CLOCK_SKEW_SECS: i64 = 300;
+
+/// The identity of the attested peer, which is extracted from its verified certificate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerIdentity {
+    mr_enclave: [u8; 32],
+    mr_signer: [u8; 32],
+    #[serde(default)]
+    isv_prod_id: u16,
+    #[serde(default)]
+    isv_svn: u16,
+}
+
+impl PeerIdentity {
+    pub fn new(mr_enclave: [u8; 32], mr_signer: [u8; 32], isv_prod_id: u16, isv_svn: u16) -> Self {
+        PeerIdentity {
+            mr_enclave,
+            mr_signer,
+            isv_prod_id,
+            isv_svn,
+        }
+    }
+
+    pub fn mr_enclave(&self) -> &[u8; 32] {
+        &self.mr_enclave
+    }
+
+    pub fn mr_signer(&self) -> &[u8; 32] {
+        &self.mr_signer
+    }
+
+    pub fn isv_prod_id(&self) -> u16 {
+        self.isv_prod_id
+    }
+
+    pub fn isv_svn(&self) -> u16 {
+        self.isv_svn
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AttestedReportVerifier {
     root_cert: Vec<u8>,
-    measurement: EnclaveMeasurement,
+    policy: SharedMeasurementPolicy,
 }
 
 impl AttestedReportVerifier {
     pub fn new(root_cert: Vec<u8>, measurement: EnclaveMeasurement) -> Self {
-        Self {
-            root_cert,
-            measurement,
-        }
+        let policy = SharedMeasurementPolicy::new(MeasurementPolicy::from_measurement(measurement));
+        Self::with_policy(root_cert, policy)
     }
 
-    fn verify_cert(&self, ee_cert: &[u8]) -> Result<()> {
+    pub fn with_policy(root_cert: Vec<u8>, policy: SharedMeasurementPolicy) -> Self {
+        Self { root_cert, policy }
+    }
+
+    /// Verify the attested certificate and return the peer's identity in it.
+    pub(crate) fn verify_cert(&self, ee_cert: &[u8]) -> Result<PeerIdentity> {
         // Parse DER formatted x.509 end entity certificate
-        let x509 = yasna::parse_der(&ee_cert, X509::load)?;
-        // Extract tbs (To Be Signed) Certificate
-        let tbs_cert: <TbsCert as Asn1Ty>::ValueTy = x509.0;
-        let pubkey: <PubKey as Asn1Ty>::ValueTy = ((((((tbs_cert.1).1).1).1).1).1).0;
-        let cert_ext: <SgxRaCertExt as Asn1Ty>::ValueTy = (((((((tbs_cert.1).1).1).1).1).1).1).0;
-        let cert_ext_payload: Vec<u8> = ((cert_ext.0).1).0;
-
-        // Verify the deserialized attested_report which is included in extension field of X.509 cert
-        let attested_report = serde_json::from_slice::<AttestedReport>(&cert_ext_payload)?
-            .verify_attested_report(self.root_cert.to_vec())?;
-
-        let mut quote = Cursor::new(attested_report.get_quote_body()?);
+        let cert = parse_ra_cert(ee_cert)?;
+
+        // Verify the deserialized evidence, either an IAS report or an ECDSA quote, which is included in extension field of X.509 cert
+        let evidence = serde_json::from_slice::<AttestationEvidence>(&cert.cert_ext_payload)?
+            .verify(
+                &self.root_cert,
+                DCAP_ROOT_CERT.as_deref(),
+                &QuoteStatusPolicy::compiled()?,
+            )?;
+        if evidence.status() != QuoteStatus::Ok {
+            warn!(
+                "The peer's quote status {} is tolerated, advisories: {:?}",
+                evidence.status(),
+                evidence.advisory_ids()
+            );
+        }
+
+        // The ECDSA quote has the same layout as the quote body of IAS
+        let mut quote = Cursor::new(evidence.quote_body());
         let mut mr_enclave = [0u8; 32];
         let mut mr_signer = [0u8; 32];
+        let mut isv_prod_id = [0u8; 2];
+        let mut isv_svn = [0u8; 2];
         let mut report_data = [0u8; 64];
 
         // Offsets are defined in "Attestation Service for Intel® Software Guard Extensions (Intel® SGX): API Documentation version 6.0"
@@ -44,40 +100,73 @@ impl AttestedReportVerifier {
         quote.read_exact(&mut mr_enclave)?;
         quote.set_position(176);
         quote.read_exact(&mut mr_signer)?;
+        quote.set_position(304);
+        quote.read_exact(&mut isv_prod_id)?;
+        quote.read_exact(&mut isv_svn)?;
         quote.set_position(368);
         quote.read_exact(&mut report_data)?;
 
-        Self::verify_pubkey_eq(pubkey, report_data)?;
-        self.verify_measurement(mr_enclave, mr_signer)?;
-
-        Ok(())
+        Self::verify_pubkey_eq(cert.pubkey, report_data)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!("{:?}", e))?
+            .as_secs();
+        Self::verify_validity(cert.not_before, cert.not_after, now)?;
+        Self::verify_freshness(evidence.timestamp(), now, *ATTESTATION_MAX_AGE_SECS)?;
+        let peer = PeerIdentity::new(
+            mr_enclave,
+            mr_signer,
+            u16::from_le_bytes(isv_prod_id),
+            u16::from_le_bytes(isv_svn),
+        );
+        self.policy.verify(&peer)?;
+
+        Ok(peer)
     }
 
-    fn verify_pubkey_eq(pubkey: <PubKey as Asn1Ty>::ValueTy, report_data: [u8; 64]) -> Result<()> {
-        let raw_pubkey = (pubkey.1).0.to_bytes();
-        let is_uncompressed = raw_pubkey[0] == 4;
-        if !is_uncompressed || raw_pubkey[1..] != report_data[..] {
+    /// Verify that the certificate is within its validity period,
+    /// which bounds how long an attested key is used.
+    pub(crate) fn verify_validity(not_before: i64, not_after: i64, now: u64) -> Result<()> {
+        let now = now as i64;
+        if now + CLOCK_SKEW_SECS < not_before || now > not_after {
             return Err(MraTLSError::Error(anyhow!(
-                "ee_cert's pubkey is not equal to report data"
+                "The certificate is not valid at {}, whose validity period is from {} to {}",
+                now,
+                not_before,
+                not_after
             )));
         }
 
         Ok(())
     }
 
-    fn verify_measurement(&self, mr_enclave: [u8; 32], mr_signer: [u8; 32]) -> Result<()> {
-        if self.measurement.mr_enclave() != mr_enclave {
-            return Err(MraTLSError::Error(anyhow!(
-                "Invalid mr_enclave: local mr_enclave: {:?}, received mr_enclave: {:?}",
-                self.measurement.mr_enclave(),
-                mr_enclave
-            )));
+    /// Verify that the attestation report was issued within `max_age` seconds.
+    /// The ECDSA quotes have no trusted timestamp, so they are bounded only by the certificate's validity.
+    pub(crate) fn verify_freshness(
+        timestamp: Option<u64>,
+        now: u64,
+        max_age: Option<u64>,
+    ) -> Result<()> {
+        if let (Some(timestamp), Some(max_age)) = (timestamp, max_age) {
+            let age = now.saturating_sub(timestamp);
+            if age > max_age {
+                return Err(MraTLSError::Error(anyhow!(
+                    "The attestation report is {} seconds old, which exceeds the maximum age {}",
+                    age,
+                    max_age
+                )));
+            }
         }
-        if self.measurement.mr_signer() != mr_signer {
+
+        Ok(())
+    }
+
+    fn verify_pubkey_eq(pubkey: <PubKey as Asn1Ty>::ValueTy, report_data: [u8; 64]) -> Result<()> {
+        let raw_pubkey = (pubkey.1).0.to_bytes();
+        let is_uncompressed = raw_pubkey[0] == 4;
+        if !is_uncompressed || raw_pubkey[1..] != report_data[..] {
             return Err(MraTLSError::Error(anyhow!(
-                "Invalid mr_signer: local mr_signer: {:?}, received mr_signer: {:?}",
-                self.measurement.mr_signer(),
-                mr_signer
+                "ee_cert's pubkey is not equal to report data"
             )));
         }
 
@@ -1,4 +1,6 @@
+use crate::dcap::parse_datetime;
 use crate::error::{FrameRAError, Result};
+use crate::quote_status::{QuoteStatus, QuoteStatusPolicy};
 use crate::{
     anyhow::anyhow,
     base64,
@@ -121,13 +123,22 @@ impl AttestedReport {
         let sig = headers
             .get("X-IASReport-Signature")
             .ok_or_else(|| anyhow!("Not found X-IASReport-Signature header"))?;
-        let report_sig = base64::decode(&sig)?;
-
         let cert = headers
             .get("X-IASReport-Signing-Certificate")
-            .ok_or_else(|| anyhow!("Not found X-IASReport-Signing-Certificate"))?
-            .replace("%0A", "");
-        let report_cert = percent_decode(cert)?;
+            .ok_or_else(|| anyhow!("Not found X-IASReport-Signing-Certificate"))?;
+
+        Self::from_ias_response(body, sig, cert)
+    }
+
+    /// Build the report from the body of a successful response of IAS and its
+    /// `X-IASReport-Signature` and `X-IASReport-Signing-Certificate` headers.
+    pub fn from_ias_response(
+        body: Vec<u8>,
+        signature_header: &str,
+        signing_certificate_header: &str,
+    ) -> Result<Self> {
+        let report_sig = base64::decode(signature_header)?;
+        let report_cert = percent_decode(signing_certificate_header.replace("%0A", ""))?;
 
         Ok(AttestedReport {
             report: body,
@@ -136,12 +147,22 @@ impl AttestedReport {
         })
     }
 
+    /// Verify the attested report with the quote status policy compiled into the enclave.
+    pub fn verify_attested_report(self, root_cert: Vec<u8>) -> Result<Self> {
+        let policy = QuoteStatusPolicy::compiled()?;
+        self.verify_attested_report_with_policy(root_cert, &policy)
+    }
+
     /// Verify that
     /// 1. TLS server certificate
     /// 2. report's signature
     /// 3. report's version
-    /// 4. quote status
-    pub fn verify_attested_report(self, root_cert: Vec<u8>) -> Result<Self> {
+    /// 4. quote status and advisories by the policy
+    pub fn verify_attested_report_with_policy(
+        self,
+        root_cert: Vec<u8>,
+        policy: &QuoteStatusPolicy,
+    ) -> Result<Self> {
         let now_func = webpki::Time::try_from(SystemTime::now()).map_err(|e| anyhow!("{:?}", e))?;
 
         let mut root_store = rustls::RootCertStore::empty();
@@ -173,7 +194,10 @@ impl AttestedReport {
 
         let report = serde_json::from_slice(&self.report)?;
         Self::verify_version(&report)?;
-        Self::verify_quote_status(&report)?;
+        policy.verify(
+            Self::parse_quote_status(&report)?,
+            Self::parse_advisory_ids(&report)?.as_deref(),
+        )?;
 
         Ok(self)
     }
@@ -186,6 +210,28 @@ impl AttestedReport {
         base64::decode(encoded_quote).map_err(Into::into)
     }
 
+    pub fn quote_status(&self) -> Result<QuoteStatus> {
+        let report: Value = serde_json::from_slice(&self.report)?;
+        Self::parse_quote_status(&report)
+    }
+
+    /// The IDs of the security advisories affecting the attested platform,
+    /// which are listed in the report since the API version 4.
+    pub fn advisory_ids(&self) -> Result<Vec<String>> {
+        let report: Value = serde_json::from_slice(&self.report)?;
+        Ok(Self::parse_advisory_ids(&report)?.unwrap_or_default())
+    }
+
+    /// The time when IAS verified the quote, in seconds since the UNIX epoch.
+    pub fn timestamp(&self) -> Result<u64> {
+        let report: Value = serde_json::from_slice(&self.report)?;
+        // The timestamp is in UTC without the time zone designator, e.g. "2021-03-18T05:12:32.118339".
+        let timestamp = report["timestamp"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Invalid timestamp"))?;
+        parse_datetime(timestamp).map_err(Into::into)
+    }
+
     pub fn report(&self) -> &[u8] {
         &self.report
     }
@@ -203,25 +249,33 @@ impl AttestedReport {
         let version = report["version"]
             .as_u64()
             .ok_or_else(|| anyhow!("The Remote Attestation API version is not valid"))?;
-        if version != 3 {
+        if version != 3 && version != 4 {
             return Err(FrameRAError::ApiVersionError(version));
         }
         Ok(())
     }
 
-    /// Verify the quote status included the attestation report is OK
-    fn verify_quote_status(report: &Value) -> Result<()> {
-        if let Value::String(quote_status) = &report["isvEnclaveQuoteStatus"] {
-            match quote_status.as_ref() {
-                "OK" => Ok(()),
-                "GROUP_OUT_OF_DATE" => {
-                    println!("Enclave Quote Status: GROUP_OUT_OF_DATE");
-                    Ok(())
-                }
-                _ => Err(FrameRAError::QuoteStatusError(quote_status.to_string())),
-            }
-        } else {
-            Err(FrameRAError::NotFoundisvEnclaveQuoteStatusError)
+    fn parse_quote_status(report: &Value) -> Result<QuoteStatus> {
+        report["isvEnclaveQuoteStatus"]
+            .as_str()
+            .ok_or(FrameRAError::NotFoundisvEnclaveQuoteStatusError)?
+            .parse()
+    }
+
+    fn parse_advisory_ids(report: &Value) -> Result<Option<Vec<String>>> {
+        match &report["advisoryIDs"] {
+            Value::Null => Ok(None),
+            Value::Array(advisory_ids) => advisory_ids
+                .iter()
+                .map(|advisory_id| {
+                    advisory_id
+                        .as_str()
+                        .map(ToString::to_string)
+                        .ok_or_else(|| anyhow!("Invalid advisoryIDs").into())
+                })
+                .collect::<Result<Vec<String>>>()
+                .map(Some),
+            _ => Err(anyhow!("Invalid advisoryIDs").into()),
         }
     }
 }
@@ -236,10 +290,33 @@ fn percent_decode(orig: String) -> Result<Vec<u8>> {
     ret.push_str(v[0]);
     if v.len() > 1 {
         for s in v[1..].iter() {
-            ret.push(u8::from_str_radix(&s[0..2], 16)? as char);
+            let hex = s
+                .get(0..2)
+                .ok_or_else(|| anyhow!("Invalid percent-encoding"))?;
+            ret.push(u8::from_str_radix(hex, 16)? as char);
             ret.push_str(&s[2..]);
         }
     }
+    // The first certificate of the chain is the signing one.
     let v: Vec<&str> = ret.split("-----").collect();
-    base64::decode(v[2]).map_err(Into::into)
+    let cert = v.get(2).ok_or(FrameRAError::BlankCertError)?;
+    base64::decode(cert).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        let cert = "-----BEGIN%20CERTIFICATE-----AQID%2BA%3D%3D-----END%20CERTIFICATE-----";
+        assert_eq!(
+            percent_decode(cert.to_string()).unwrap(),
+            vec![1, 2, 3, 0xf8]
+        );
+
+        assert!(percent_decode("AQID".to_string()).is_err());
+        assert!(percent_decode("-----BEGIN%2".to_string()).is_err());
+        assert!(percent_decode("-----BEGIN%ZZ-----".to_string()).is_err());
+    }
 }
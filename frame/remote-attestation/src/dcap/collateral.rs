@@ -0,0 +1,363 @@
+use crate::anyhow::{anyhow, bail, Result};
+use crate::{
+    base64,
+    http_req::{
+        request::{Method, Request},
+        response::Response,
+        uri::Uri,
+    },
+    localstd::{
+        string::{String, ToString},
+        vec::Vec,
+    },
+    serde::{Deserialize, Serialize},
+    serde_json::{self, Value},
+};
+use frame_config::{REQUEST_RETRIES, RETRY_DELAY_MILLS};
+use frame_retrier::{strategy, Retry};
+
+/// The collateral to verify an ECDSA quote, which is fetched from a PCCS.
+/// The TCB info and the QE identity are kept as the raw JSON exactly as they were signed,
+/// and the CRLs as the DER exactly as they were signed,
+/// so the collateral can be relayed by untrusted parties and recorded as fixtures.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct DcapCollateral {
+    tcb_info: String,
+    /// The hex encoded ECDSA signature over `tcb_info` in the raw r and s format
+    tcb_info_signature: String,
+    /// The PEM encoded TCB signing certificate and the root CA
+    tcb_info_issuer_chain: String,
+    qe_identity: String,
+    /// The hex encoded ECDSA signature over `qe_identity` in the raw r and s format
+    qe_identity_signature: String,
+    /// The PEM encoded TCB signing certificate and the root CA
+    qe_identity_issuer_chain: String,
+    /// The hex encoded DER of the CRL issued by the CA of the PCK certificate
+    pck_crl: String,
+    /// The hex encoded DER of the CRL issued by the root CA,
+    /// which revokes the CA of the PCK certificate and the TCB signing certificate
+    root_ca_crl: String,
+}
+
+impl DcapCollateral {
+    pub fn new(
+        tcb_info: String,
+        tcb_info_signature: String,
+        tcb_info_issuer_chain: String,
+        qe_identity: String,
+        qe_identity_signature: String,
+        qe_identity_issuer_chain: String,
+        pck_crl: String,
+        root_ca_crl: String,
+    ) -> Self {
+        DcapCollateral {
+            tcb_info,
+            tcb_info_signature,
+            tcb_info_issuer_chain,
+            qe_identity,
+            qe_identity_signature,
+            qe_identity_issuer_chain,
+            pck_crl,
+            root_ca_crl,
+        }
+    }
+
+    /// Build the collateral from the response bodies of the PCCS and their issuer chain headers.
+    pub fn from_pccs_responses(
+        tcb_info_body: &str,
+        tcb_info_issuer_chain: String,
+        qe_identity_body: &str,
+        qe_identity_issuer_chain: String,
+        pck_crl_body: &[u8],
+        root_ca_crl_body: &[u8],
+    ) -> Result<Self> {
+        Ok(DcapCollateral {
+            tcb_info: raw_json_field(tcb_info_body, "tcbInfo")?.to_string(),
+            tcb_info_signature: signature_field(tcb_info_body)?,
+            tcb_info_issuer_chain,
+            qe_identity: raw_json_field(qe_identity_body, "enclaveIdentity")?.to_string(),
+            qe_identity_signature: signature_field(qe_identity_body)?,
+            qe_identity_issuer_chain,
+            pck_crl: hex_crl(pck_crl_body)?,
+            root_ca_crl: hex_crl(root_ca_crl_body)?,
+        })
+    }
+
+    pub fn tcb_info(&self) -> &str {
+        &self.tcb_info
+    }
+
+    pub fn tcb_info_signature(&self) -> &str {
+        &self.tcb_info_signature
+    }
+
+    pub fn tcb_info_issuer_chain(&self) -> &str {
+        &self.tcb_info_issuer_chain
+    }
+
+    pub fn qe_identity(&self) -> &str {
+        &self.qe_identity
+    }
+
+    pub fn qe_identity_signature(&self) -> &str {
+        &self.qe_identity_signature
+    }
+
+    pub fn qe_identity_issuer_chain(&self) -> &str {
+        &self.qe_identity_issuer_chain
+    }
+
+    pub fn pck_crl(&self) -> &str {
+        &self.pck_crl
+    }
+
+    pub fn root_ca_crl(&self) -> &str {
+        &self.root_ca_crl
+    }
+}
+
+/// A client of the Provisioning Certificate Caching Service, which serves the PCS API version 3.
+/// The collateral is signed by Intel, so a PCCS doesn't need to be trusted and may be served over plain HTTP.
+pub struct PccsClient<'a> {
+    pccs_url: &'a str,
+}
+
+impl<'a> PccsClient<'a> {
+    pub fn new(pccs_url: &'a str) -> Self {
+        PccsClient {
+            pccs_url: pccs_url.trim_end_matches('/'),
+        }
+    }
+
+    /// Fetch the TCB info of the platform identified by the FMSPC, the identity of the Quoting Enclave,
+    /// the CRL of the PCK CA, which is either `processor` or `platform`, and the CRL of the root CA.
+    pub fn get_collateral(&self, fmspc: &[u8], pck_ca: &str) -> Result<DcapCollateral> {
+        let (tcb_info_body, tcb_info_issuer_chain) = self.get(
+            &format!(
+                "{}/sgx/certification/v3/tcb?fmspc={}",
+                self.pccs_url,
+                hex::encode(fmspc)
+            ),
+            "SGX-TCB-Info-Issuer-Chain",
+        )?;
+        let (qe_identity_body, qe_identity_issuer_chain) = self.get(
+            &format!("{}/sgx/certification/v3/qe/identity", self.pccs_url),
+            "SGX-Enclave-Identity-Issuer-Chain",
+        )?;
+        let (pck_crl_body, _) = self.request(&format!(
+            "{}/sgx/certification/v3/pckcrl?ca={}",
+            self.pccs_url, pck_ca
+        ))?;
+        let (root_ca_crl_body, _) =
+            self.request(&format!("{}/sgx/certification/v3/rootcacrl", self.pccs_url))?;
+
+        DcapCollateral::from_pccs_responses(
+            &tcb_info_body,
+            tcb_info_issuer_chain,
+            &qe_identity_body,
+            qe_identity_issuer_chain,
+            &pck_crl_body,
+            &root_ca_crl_body,
+        )
+    }
+
+    /// Returns the body and the percent-decoded issuer chain header of the response.
+    fn get(&self, url: &str, issuer_chain_header: &str) -> Result<(String, String)> {
+        let (body, response) = self.request(url)?;
+        let issuer_chain = response
+            .headers()
+            .get(issuer_chain_header)
+            .ok_or_else(|| anyhow!("Not found {} header", issuer_chain_header))?;
+        let body = String::from_utf8(body)?;
+
+        Ok((body, percent_decode_str(issuer_chain)?))
+    }
+
+    /// Returns the raw body of the successful response.
+    fn request(&self, url: &str) -> Result<(Vec<u8>, Response)> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|e| anyhow!("Invalid PCCS url {}: {:?}", url, e))?;
+        let (body, response) = Retry::new(
+            "pccs",
+            *REQUEST_RETRIES,
+            strategy::FixedDelay::new(*RETRY_DELAY_MILLS),
+        )
+        .set_condition(|res: &Result<(Vec<u8>, Response)>| match res {
+            Ok((_, resp)) => resp.status_code().is_server_err(),
+            Err(_) => true,
+        })
+        .spawn(|| {
+            let mut body = Vec::new();
+            let response = Request::new(&uri)
+                .method(Method::GET)
+                .header("Connection", "close")
+                .send(&mut body)
+                .map_err(|e| anyhow!("Failed to request {}: {:?}", url, e))?;
+            Ok((body, response))
+        })?;
+        if !response.status_code().is_success() {
+            bail!("{} responded {:?}", url, response.status_code());
+        }
+
+        Ok((body, response))
+    }
+}
+
+/// Normalize the CRL served by a PCCS in PEM, in hex encoded DER or in raw DER into the hex encoded DER.
+fn hex_crl(body: &[u8]) -> Result<String> {
+    const BEGIN: &str = "-----BEGIN X509 CRL-----";
+    const END: &str = "-----END X509 CRL-----";
+
+    let text = match crate::localstd::str::from_utf8(body) {
+        Ok(text) => text.trim(),
+        Err(_) => return Ok(hex::encode(body)),
+    };
+    if let Some(pem) = text.strip_prefix(BEGIN) {
+        let end = pem
+            .find(END)
+            .ok_or_else(|| anyhow!("The PEM CRL is truncated"))?;
+        let encoded: String = pem[..end].chars().filter(|c| !c.is_whitespace()).collect();
+        return Ok(hex::encode(base64::decode(&encoded)?));
+    }
+    if !text.is_empty() && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok(text.to_ascii_lowercase());
+    }
+
+    Ok(hex::encode(body))
+}
+
+fn signature_field(body: &str) -> Result<String> {
+    let body: Value = serde_json::from_str(body)?;
+    body["signature"]
+        .as_str()
+        .map(ToString::to_string)
+        .ok_or_else(|| anyhow!("Not found signature in the collateral"))
+}
+
+/// Extract the raw JSON value of the top-level field, whose bytes are signed as they are.
+fn raw_json_field<'b>(body: &'b str, field: &str) -> Result<&'b str> {
+    let key = format!("\"{}\"", field);
+    let bytes = body.as_bytes();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_string = false;
+            }
+        } else if c == b'"' {
+            if depth == 1 && body[i..].starts_with(&key) {
+                let rest = body[i + key.len()..].trim_start();
+                if let Some(value) = rest.strip_prefix(':') {
+                    let value = value.trim_start();
+                    let start = body.len() - value.len();
+                    return Ok(&body[start..start + json_value_len(value)?]);
+                }
+            }
+            in_string = true;
+        } else if c == b'{' || c == b'[' {
+            depth += 1;
+        } else if c == b'}' || c == b']' {
+            depth -= 1;
+        }
+        i += 1;
+    }
+
+    bail!("Not found {} in the collateral", field)
+}
+
+/// The length of the JSON object or array at the beginning of the string.
+fn json_value_len(value: &str) -> Result<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in value.bytes().enumerate() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_string = false;
+            }
+        } else if c == b'"' {
+            in_string = true;
+        } else if c == b'{' || c == b'[' {
+            depth += 1;
+        } else if c == b'}' || c == b']' {
+            depth -= 1;
+            if depth == 0 {
+                return Ok(i + 1);
+            }
+        }
+    }
+
+    bail!("The JSON value in the collateral is truncated")
+}
+
+fn percent_decode_str(encoded: &str) -> Result<String> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = encoded
+                .get(i + 1..i + 3)
+                .ok_or_else(|| anyhow!("Invalid percent encoding"))?;
+            decoded.push(u8::from_str_radix(hex, 16)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_json_field() {
+        let body = r#"{"signature":"ab","tcbInfo":{"fmspc":"00906ed50000","note":"a \"}\" in a string","levels":[{"a":1}]}}"#;
+        assert_eq!(
+            raw_json_field(body, "tcbInfo").unwrap(),
+            r#"{"fmspc":"00906ed50000","note":"a \"}\" in a string","levels":[{"a":1}]}"#
+        );
+        assert_eq!(signature_field(body).unwrap(), "ab");
+        // Only the top-level fields
+        assert!(raw_json_field(r#"{"a":{"tcbInfo":{}}}"#, "tcbInfo").is_err());
+    }
+
+    #[test]
+    fn test_percent_decode_str() {
+        assert_eq!(
+            percent_decode_str("-----BEGIN%20CERTIFICATE-----%0AMIIC").unwrap(),
+            "-----BEGIN CERTIFICATE-----\nMIIC"
+        );
+        assert!(percent_decode_str("%2").is_err());
+    }
+
+    #[test]
+    fn test_hex_crl() {
+        let der = [0x30, 0x03, 0x02, 0x01, 0xff];
+        assert_eq!(hex_crl(&der).unwrap(), "30030201ff");
+        assert_eq!(hex_crl(b"30030201FF\n").unwrap(), "30030201ff");
+        assert_eq!(
+            hex_crl(b"-----BEGIN X509 CRL-----\nMAMCAf8=\n-----END X509 CRL-----\n").unwrap(),
+            "30030201ff"
+        );
+        assert!(hex_crl(b"-----BEGIN X509 CRL-----\nMAMCAf8=").is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! The ECDSA attestation of Intel® SGX Data Center Attestation Primitives (DCAP).
+//! The quotes are verified in the enclaves with the PCK certificate chain in the quote
+//! and the TCB info and the QE identity fetched from a Provisioning Certificate Caching Service.
+
+mod collateral;
+mod quote;
+mod tcb;
+mod verify;
+
+pub use collateral::{DcapCollateral, PccsClient};
+pub use quote::DcapQuote;
+pub use verify::DcapEvidence;
+
+pub(crate) use tcb::parse_datetime;
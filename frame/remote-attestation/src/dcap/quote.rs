@@ -0,0 +1,145 @@
+use crate::anyhow::{anyhow, ensure, Result};
+use crate::localstd::{convert::TryInto, ops::Range};
+
+const QUOTE_VERSION: u16 = 3;
+/// ECDSA-256-with-P-256 curve
+const ATT_KEY_TYPE_ECDSA_P256: u16 = 2;
+/// Concatenated PCK Cert Chain in PEM format
+const CERT_DATA_TYPE_PCK_CERT_CHAIN: u16 = 5;
+
+const HEADER_LEN: usize = 48;
+pub(crate) const REPORT_BODY_LEN: usize = 384;
+/// The length of the header and the ISV enclave report, which is signed by the attestation key.
+/// It has the same layout as the quote body in the IAS attestation report.
+pub(crate) const QUOTE_BODY_LEN: usize = HEADER_LEN + REPORT_BODY_LEN;
+const SIGNATURE_LEN: usize = 64;
+const ATTEST_PUB_KEY_LEN: usize = 64;
+
+/// Offsets in the report body, defined in "Intel® SGX ECDSA Quote Library API"
+pub(crate) const REPORT_MISCSELECT: Range<usize> = 16..20;
+pub(crate) const REPORT_ATTRIBUTES: Range<usize> = 48..64;
+pub(crate) const REPORT_MRSIGNER: Range<usize> = 128..160;
+pub(crate) const REPORT_ISVPRODID: Range<usize> = 256..258;
+pub(crate) const REPORT_ISVSVN: Range<usize> = 258..260;
+pub(crate) const REPORT_DATA: Range<usize> = 320..384;
+
+/// A view of an ECDSA quote version 3 generated by the Quoting Enclave of DCAP.
+pub struct DcapQuote<'a> {
+    quote_body: &'a [u8],
+    isv_report_signature: &'a [u8],
+    attest_pub_key: &'a [u8],
+    qe_report: &'a [u8],
+    qe_report_signature: &'a [u8],
+    qe_auth_data: &'a [u8],
+    pck_cert_chain: &'a [u8],
+}
+
+impl<'a> DcapQuote<'a> {
+    pub fn parse(quote: &'a [u8]) -> Result<Self> {
+        let mut reader = Reader::new(quote);
+        let quote_body = reader.peek(QUOTE_BODY_LEN)?;
+        let version = reader.read_u16()?;
+        ensure!(
+            version == QUOTE_VERSION,
+            "Unsupported quote version: {}",
+            version
+        );
+        let att_key_type = reader.read_u16()?;
+        ensure!(
+            att_key_type == ATT_KEY_TYPE_ECDSA_P256,
+            "Unsupported attestation key type: {}",
+            att_key_type
+        );
+        reader.read(QUOTE_BODY_LEN - 4)?;
+
+        let signature_data_len = reader.read_u32()? as usize;
+        let mut reader = Reader::new(reader.read(signature_data_len)?);
+        let isv_report_signature = reader.read(SIGNATURE_LEN)?;
+        let attest_pub_key = reader.read(ATTEST_PUB_KEY_LEN)?;
+        let qe_report = reader.read(REPORT_BODY_LEN)?;
+        let qe_report_signature = reader.read(SIGNATURE_LEN)?;
+        let qe_auth_data_len = reader.read_u16()? as usize;
+        let qe_auth_data = reader.read(qe_auth_data_len)?;
+        let cert_data_type = reader.read_u16()?;
+        ensure!(
+            cert_data_type == CERT_DATA_TYPE_PCK_CERT_CHAIN,
+            "Unsupported certification data type: {}",
+            cert_data_type
+        );
+        let cert_data_len = reader.read_u32()? as usize;
+        let pck_cert_chain = reader.read(cert_data_len)?;
+
+        Ok(DcapQuote {
+            quote_body,
+            isv_report_signature,
+            attest_pub_key,
+            qe_report,
+            qe_report_signature,
+            qe_auth_data,
+            pck_cert_chain,
+        })
+    }
+
+    /// The header and the ISV enclave report.
+    pub fn quote_body(&self) -> &'a [u8] {
+        self.quote_body
+    }
+
+    pub fn isv_report_signature(&self) -> &'a [u8] {
+        self.isv_report_signature
+    }
+
+    /// The raw x and y coordinates of the attestation key.
+    pub fn attest_pub_key(&self) -> &'a [u8] {
+        self.attest_pub_key
+    }
+
+    pub fn qe_report(&self) -> &'a [u8] {
+        self.qe_report
+    }
+
+    pub fn qe_report_signature(&self) -> &'a [u8] {
+        self.qe_report_signature
+    }
+
+    pub fn qe_auth_data(&self) -> &'a [u8] {
+        self.qe_auth_data
+    }
+
+    /// The PEM encoded PCK certificate, the intermediate CA and the root CA.
+    pub fn pck_cert_chain(&self) -> &'a [u8] {
+        self.pck_cert_chain
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    fn peek(&self, len: usize) -> Result<&'a [u8]> {
+        self.bytes
+            .get(..len)
+            .ok_or_else(|| anyhow!("The quote is truncated"))
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8]> {
+        let read = self.peek(len)?;
+        self.bytes = &self.bytes[len..];
+        Ok(read)
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        let bytes = self.read(2)?;
+        Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.read(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
@@ -0,0 +1,470 @@
+use crate::anyhow::{anyhow, bail, ensure, Result};
+use crate::dcap::quote::{
+    REPORT_ATTRIBUTES, REPORT_BODY_LEN, REPORT_ISVPRODID, REPORT_ISVSVN, REPORT_MISCSELECT,
+    REPORT_MRSIGNER,
+};
+use crate::localstd::{
+    convert::TryInto,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::quote_status::QuoteStatus;
+use crate::serde_json::{self, Value};
+
+const SGX_TCB_COMPONENTS: usize = 16;
+
+/// The TCB of the attested platform, which is certified in its PCK certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformTcb {
+    pub(crate) fmspc: Vec<u8>,
+    pub(crate) sgx_tcb_components: [u8; SGX_TCB_COMPONENTS],
+    pub(crate) pce_svn: u16,
+}
+
+/// The TCB level matched with the platform's TCB.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcbLevelStatus {
+    pub(crate) status: QuoteStatus,
+    pub(crate) advisory_ids: Vec<String>,
+}
+
+/// The TCB info of a platform model, which is signed by the Intel SGX TCB signing key.
+/// Both the version 2 and the version 3 formats are supported.
+pub struct TcbInfo {
+    fmspc: Vec<u8>,
+    next_update: u64,
+    tcb_levels: Vec<TcbLevel>,
+}
+
+struct TcbLevel {
+    sgx_tcb_components: [u8; SGX_TCB_COMPONENTS],
+    pce_svn: u16,
+    status: QuoteStatus,
+    advisory_ids: Vec<String>,
+}
+
+impl TcbInfo {
+    pub fn parse(tcb_info: &str) -> Result<Self> {
+        let tcb_info: Value = serde_json::from_str(tcb_info)?;
+        let version = tcb_info["version"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Invalid TCB info version"))?;
+        ensure!(
+            version == 2 || version == 3,
+            "Unsupported TCB info version: {}",
+            version
+        );
+        let fmspc = hex::decode(str_field(&tcb_info, "fmspc")?)?;
+        let next_update = parse_datetime(str_field(&tcb_info, "nextUpdate")?)?;
+        let tcb_levels = tcb_info["tcbLevels"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Invalid tcbLevels"))?
+            .iter()
+            .map(|level| TcbLevel::parse(level, version))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TcbInfo {
+            fmspc,
+            next_update,
+            tcb_levels,
+        })
+    }
+
+    pub fn next_update(&self) -> u64 {
+        self.next_update
+    }
+
+    /// Find the first TCB level which the platform's TCB is higher than or equal to,
+    /// as the levels are sorted from the latest.
+    pub fn evaluate(&self, platform: &PlatformTcb) -> Result<TcbLevelStatus> {
+        ensure!(
+            self.fmspc == platform.fmspc,
+            "The TCB info is for FMSPC {}, but the platform's FMSPC is {}",
+            hex::encode(&self.fmspc),
+            hex::encode(&platform.fmspc)
+        );
+
+        self.tcb_levels
+            .iter()
+            .find(|level| {
+                platform.pce_svn >= level.pce_svn
+                    && platform
+                        .sgx_tcb_components
+                        .iter()
+                        .zip(level.sgx_tcb_components.iter())
+                        .all(|(platform_svn, level_svn)| platform_svn >= level_svn)
+            })
+            .map(|level| TcbLevelStatus {
+                status: level.status,
+                advisory_ids: level.advisory_ids.clone(),
+            })
+            .ok_or_else(|| anyhow!("The platform's TCB is lower than any TCB levels"))
+    }
+}
+
+impl TcbLevel {
+    fn parse(level: &Value, version: u64) -> Result<Self> {
+        let tcb = &level["tcb"];
+        let mut sgx_tcb_components = [0u8; SGX_TCB_COMPONENTS];
+        for (i, svn) in sgx_tcb_components.iter_mut().enumerate() {
+            let component = if version == 2 {
+                &tcb[format!("sgxtcbcomp{:02}svn", i + 1)]
+            } else {
+                &tcb["sgxtcbcomponents"][i]["svn"]
+            };
+            *svn = u8_field(component)?;
+        }
+        let pce_svn = tcb["pcesvn"]
+            .as_u64()
+            .and_then(|svn| svn.try_into().ok())
+            .ok_or_else(|| anyhow!("Invalid pcesvn"))?;
+
+        Ok(TcbLevel {
+            sgx_tcb_components,
+            pce_svn,
+            status: parse_tcb_status(str_field(level, "tcbStatus")?)?,
+            advisory_ids: advisory_ids(level)?,
+        })
+    }
+}
+
+/// The identity of the Quoting Enclave, which is signed by the Intel SGX TCB signing key.
+pub struct QeIdentity {
+    next_update: u64,
+    miscselect: u32,
+    miscselect_mask: u32,
+    attributes: Vec<u8>,
+    attributes_mask: Vec<u8>,
+    mr_signer: Vec<u8>,
+    isv_prod_id: u16,
+    tcb_levels: Vec<QeTcbLevel>,
+}
+
+struct QeTcbLevel {
+    isv_svn: u16,
+    status: QuoteStatus,
+    advisory_ids: Vec<String>,
+}
+
+impl QeIdentity {
+    pub fn parse(qe_identity: &str) -> Result<Self> {
+        let qe_identity: Value = serde_json::from_str(qe_identity)?;
+        let id = str_field(&qe_identity, "id")?;
+        ensure!(
+            id == "QE",
+            "Not the identity of the Quoting Enclave: {}",
+            id
+        );
+
+        let tcb_levels = qe_identity["tcbLevels"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Invalid tcbLevels"))?
+            .iter()
+            .map(|level| {
+                Ok(QeTcbLevel {
+                    isv_svn: u16_field(&level["tcb"]["isvsvn"])?,
+                    status: parse_tcb_status(str_field(level, "tcbStatus")?)?,
+                    advisory_ids: advisory_ids(level)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(QeIdentity {
+            next_update: parse_datetime(str_field(&qe_identity, "nextUpdate")?)?,
+            miscselect: u32::from_str_radix(str_field(&qe_identity, "miscselect")?, 16)?,
+            miscselect_mask: u32::from_str_radix(str_field(&qe_identity, "miscselectMask")?, 16)?,
+            attributes: hex::decode(str_field(&qe_identity, "attributes")?)?,
+            attributes_mask: hex::decode(str_field(&qe_identity, "attributesMask")?)?,
+            mr_signer: hex::decode(str_field(&qe_identity, "mrsigner")?)?,
+            isv_prod_id: u16_field(&qe_identity["isvprodid"])?,
+            tcb_levels,
+        })
+    }
+
+    pub fn next_update(&self) -> u64 {
+        self.next_update
+    }
+
+    /// Verify that the report is of the genuine Quoting Enclave and find its TCB level.
+    pub fn evaluate(&self, qe_report: &[u8]) -> Result<TcbLevelStatus> {
+        ensure!(
+            qe_report.len() == REPORT_BODY_LEN,
+            "Invalid length of the Quoting Enclave's report"
+        );
+        ensure!(
+            qe_report[REPORT_MRSIGNER] == self.mr_signer[..],
+            "The MRSIGNER of the Quoting Enclave doesn't match"
+        );
+        let isv_prod_id = u16::from_le_bytes(qe_report[REPORT_ISVPRODID].try_into().unwrap());
+        ensure!(
+            isv_prod_id == self.isv_prod_id,
+            "The ISVPRODID of the Quoting Enclave doesn't match"
+        );
+        let miscselect = u32::from_le_bytes(qe_report[REPORT_MISCSELECT].try_into().unwrap());
+        ensure!(
+            miscselect & self.miscselect_mask == self.miscselect & self.miscselect_mask,
+            "The MISCSELECT of the Quoting Enclave doesn't match"
+        );
+        ensure!(
+            self.attributes.len() == REPORT_ATTRIBUTES.len()
+                && self.attributes_mask.len() == REPORT_ATTRIBUTES.len(),
+            "Invalid attributes of the Quoting Enclave identity"
+        );
+        let attributes_match = qe_report[REPORT_ATTRIBUTES]
+            .iter()
+            .zip(self.attributes.iter().zip(self.attributes_mask.iter()))
+            .all(|(attribute, (expected, mask))| attribute & mask == expected & mask);
+        ensure!(
+            attributes_match,
+            "The ATTRIBUTES of the Quoting Enclave doesn't match"
+        );
+
+        let isv_svn = u16::from_le_bytes(qe_report[REPORT_ISVSVN].try_into().unwrap());
+        self.tcb_levels
+            .iter()
+            .find(|level| isv_svn >= level.isv_svn)
+            .map(|level| TcbLevelStatus {
+                status: level.status,
+                advisory_ids: level.advisory_ids.clone(),
+            })
+            .ok_or_else(|| anyhow!("The Quoting Enclave's TCB is lower than any TCB levels"))
+    }
+}
+
+/// Combine the statuses of the platform and the Quoting Enclave into the one of the quote.
+/// A configuration needed by one and a software hardening needed by the other are both needed,
+/// and otherwise the stricter status is taken, preferring the Quoting Enclave's one if both are intolerable.
+pub fn combine_statuses(platform: TcbLevelStatus, qe: TcbLevelStatus) -> TcbLevelStatus {
+    let status = match (platform.status, qe.status) {
+        (QuoteStatus::ConfigurationNeeded, QuoteStatus::SwHardeningNeeded)
+        | (QuoteStatus::SwHardeningNeeded, QuoteStatus::ConfigurationNeeded) => {
+            QuoteStatus::ConfigurationAndSwHardeningNeeded
+        }
+        (platform, qe) if strictness(platform) > strictness(qe) => platform,
+        (_, qe) => qe,
+    };
+    let mut advisory_ids = platform.advisory_ids;
+    for advisory_id in qe.advisory_ids {
+        if !advisory_ids.contains(&advisory_id) {
+            advisory_ids.push(advisory_id);
+        }
+    }
+
+    TcbLevelStatus {
+        status,
+        advisory_ids,
+    }
+}
+
+/// The order of the statuses from the one needing nothing to the intolerable ones.
+fn strictness(status: QuoteStatus) -> u8 {
+    match status {
+        QuoteStatus::Ok => 0,
+        QuoteStatus::SwHardeningNeeded => 1,
+        QuoteStatus::ConfigurationNeeded => 2,
+        QuoteStatus::ConfigurationAndSwHardeningNeeded => 3,
+        QuoteStatus::GroupOutOfDate => 4,
+        _ => 5,
+    }
+}
+
+/// Map the TCB status to the corresponding quote status of IAS,
+/// so that the same `QuoteStatusPolicy` applies to both EPID and ECDSA quotes.
+fn parse_tcb_status(status: &str) -> Result<QuoteStatus> {
+    let status = match status {
+        "UpToDate" => QuoteStatus::Ok,
+        "SWHardeningNeeded" => QuoteStatus::SwHardeningNeeded,
+        "ConfigurationNeeded" => QuoteStatus::ConfigurationNeeded,
+        "ConfigurationAndSWHardeningNeeded" => QuoteStatus::ConfigurationAndSwHardeningNeeded,
+        "OutOfDate" | "OutOfDateConfigurationNeeded" => QuoteStatus::GroupOutOfDate,
+        "Revoked" => QuoteStatus::KeyRevoked,
+        _ => bail!("Unknown TCB status: {}", status),
+    };
+
+    Ok(status)
+}
+
+fn advisory_ids(level: &Value) -> Result<Vec<String>> {
+    match &level["advisoryIDs"] {
+        Value::Null => Ok(vec![]),
+        Value::Array(advisory_ids) => advisory_ids
+            .iter()
+            .map(|advisory_id| {
+                advisory_id
+                    .as_str()
+                    .map(ToString::to_string)
+                    .ok_or_else(|| anyhow!("Invalid advisoryIDs"))
+            })
+            .collect(),
+        _ => bail!("Invalid advisoryIDs"),
+    }
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> Result<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| anyhow!("Invalid {}", field))
+}
+
+fn u8_field(value: &Value) -> Result<u8> {
+    value
+        .as_u64()
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| anyhow!("Invalid SVN: {}", value))
+}
+
+fn u16_field(value: &Value) -> Result<u16> {
+    value
+        .as_u64()
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| anyhow!("Invalid value: {}", value))
+}
+
+/// Parse the date and time in UTC, such as "2021-08-06T13:55:15Z", into the UNIX time.
+/// The fractional seconds and the time zone designator are ignored.
+pub(crate) fn parse_datetime(datetime: &str) -> Result<u64> {
+    let field = |range: crate::localstd::ops::Range<usize>| -> Result<u64> {
+        datetime
+            .get(range)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid date and time: {}", datetime))
+    };
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    ensure!(
+        (1970..10000).contains(&year) && (1..=12).contains(&month) && (1..=31).contains(&day),
+        "Invalid date and time: {}",
+        datetime
+    );
+
+    // The number of days from the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Ok(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERABLE_STATUSES: [QuoteStatus; 5] = [
+        QuoteStatus::Ok,
+        QuoteStatus::SwHardeningNeeded,
+        QuoteStatus::ConfigurationNeeded,
+        QuoteStatus::ConfigurationAndSwHardeningNeeded,
+        QuoteStatus::GroupOutOfDate,
+    ];
+    const INTOLERABLE_STATUSES: [QuoteStatus; 5] = [
+        QuoteStatus::SignatureInvalid,
+        QuoteStatus::GroupRevoked,
+        QuoteStatus::SignatureRevoked,
+        QuoteStatus::KeyRevoked,
+        QuoteStatus::SigrlVersionMismatch,
+    ];
+
+    fn combine(platform: QuoteStatus, qe: QuoteStatus) -> QuoteStatus {
+        let level = |status| TcbLevelStatus {
+            status,
+            advisory_ids: vec![],
+        };
+        combine_statuses(level(platform), level(qe)).status
+    }
+
+    #[test]
+    fn test_combine_tolerable_statuses() {
+        use QuoteStatus::*;
+        // Rows are the platform's statuses and columns are the Quoting Enclave's ones,
+        // in the order of `TOLERABLE_STATUSES`.
+        let expected = [
+            [
+                Ok,
+                SwHardeningNeeded,
+                ConfigurationNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                GroupOutOfDate,
+            ],
+            [
+                SwHardeningNeeded,
+                SwHardeningNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                GroupOutOfDate,
+            ],
+            [
+                ConfigurationNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                ConfigurationNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                GroupOutOfDate,
+            ],
+            [
+                ConfigurationAndSwHardeningNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                ConfigurationAndSwHardeningNeeded,
+                GroupOutOfDate,
+            ],
+            [
+                GroupOutOfDate,
+                GroupOutOfDate,
+                GroupOutOfDate,
+                GroupOutOfDate,
+                GroupOutOfDate,
+            ],
+        ];
+
+        for (i, &platform) in TOLERABLE_STATUSES.iter().enumerate() {
+            for (j, &qe) in TOLERABLE_STATUSES.iter().enumerate() {
+                assert_eq!(
+                    combine(platform, qe),
+                    expected[i][j],
+                    "platform: {:?}, qe: {:?}",
+                    platform,
+                    qe
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_combine_intolerable_statuses() {
+        for &intolerable in INTOLERABLE_STATUSES.iter() {
+            for &tolerable in TOLERABLE_STATUSES.iter() {
+                assert_eq!(combine(intolerable, tolerable), intolerable);
+                assert_eq!(combine(tolerable, intolerable), intolerable);
+            }
+            for &qe in INTOLERABLE_STATUSES.iter() {
+                assert_eq!(combine(intolerable, qe), qe);
+            }
+        }
+    }
+
+    #[test]
+    fn test_combine_advisory_ids() {
+        let platform = TcbLevelStatus {
+            status: QuoteStatus::SwHardeningNeeded,
+            advisory_ids: vec!["INTEL-SA-00334".to_string()],
+        };
+        let qe = TcbLevelStatus {
+            status: QuoteStatus::ConfigurationNeeded,
+            advisory_ids: vec!["INTEL-SA-00334".to_string(), "INTEL-SA-00615".to_string()],
+        };
+
+        assert_eq!(
+            combine_statuses(platform, qe),
+            TcbLevelStatus {
+                status: QuoteStatus::ConfigurationAndSwHardeningNeeded,
+                advisory_ids: vec!["INTEL-SA-00334".to_string(), "INTEL-SA-00615".to_string()],
+            }
+        );
+    }
+}
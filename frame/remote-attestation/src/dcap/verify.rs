@@ -0,0 +1,796 @@
+use crate::anyhow::{anyhow, bail, ensure, Result};
+use crate::dcap::{
+    collateral::DcapCollateral,
+    quote::{DcapQuote, REPORT_DATA},
+    tcb::{combine_statuses, parse_datetime, PlatformTcb, QeIdentity, TcbInfo},
+};
+use crate::localstd::{
+    str,
+    string::String,
+    time::{SystemTime, UNIX_EPOCH},
+    vec::Vec,
+};
+use crate::provider::VerifiedEvidence;
+use crate::{
+    base64,
+    ring::{
+        digest::{digest, SHA256},
+        signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED},
+    },
+    rustls,
+    serde::{Deserialize, Serialize},
+    webpki,
+};
+
+/// The DER encoded OID 1.2.840.113741.1.13.1, the SGX extension of the PCK certificate
+const SGX_EXTENSION_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+/// The sub OIDs in the SGX extension
+const TCB_OID: u8 = 2;
+const FMSPC_OID: u8 = 4;
+const PCESVN_TCB_COMPONENT: u8 = 17;
+/// The DER encoded OID 2.5.4.3, the common name in the distinguished names
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+/// The common names of the Intel SGX PKI
+const ROOT_CA_NAME: &str = "Intel SGX Root CA";
+const TCB_SIGNING_NAME: &str = "Intel SGX TCB Signing";
+const PCK_PROCESSOR_CA_NAME: &str = "Intel SGX PCK Processor CA";
+const PCK_PLATFORM_CA_NAME: &str = "Intel SGX PCK Platform CA";
+/// The DER tags
+const INTEGER_TAG: u8 = 0x02;
+const BIT_STRING_TAG: u8 = 0x03;
+const OID_TAG: u8 = 0x06;
+const UTF8_STRING_TAG: u8 = 0x0c;
+const PRINTABLE_STRING_TAG: u8 = 0x13;
+const SEQUENCE_TAG: u8 = 0x30;
+const SET_TAG: u8 = 0x31;
+const UTC_TIME_TAG: u8 = 0x17;
+const GENERALIZED_TIME_TAG: u8 = 0x18;
+const CONTEXT_0_TAG: u8 = 0xa0;
+
+/// An ECDSA quote with the collateral to verify it,
+/// which is embedded in the attested TLS certificates instead of an IAS report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct DcapEvidence {
+    #[serde(with = "base64_bytes")]
+    quote: Vec<u8>,
+    collateral: DcapCollateral,
+}
+
+impl DcapEvidence {
+    pub fn new(quote: Vec<u8>, collateral: DcapCollateral) -> Self {
+        DcapEvidence { quote, collateral }
+    }
+
+    pub fn quote(&self) -> &[u8] {
+        &self.quote
+    }
+
+    pub fn collateral(&self) -> &DcapCollateral {
+        &self.collateral
+    }
+
+    /// The FMSPC in the PCK certificate of the quote, which identifies the platform model
+    /// whose TCB info must be fetched as the collateral.
+    pub fn fmspc(quote: &[u8]) -> Result<Vec<u8>> {
+        let quote = DcapQuote::parse(quote)?;
+        let certs = pem_certificates(quote.pck_cert_chain())?;
+        let pck_cert = certs
+            .first()
+            .ok_or_else(|| anyhow!("Not found the PCK certificate"))?;
+        Ok(sgx_extension_value(pck_cert, &[FMSPC_OID])?.to_vec())
+    }
+
+    /// The CA which issued the PCK certificate of the quote, either `processor` or `platform`,
+    /// whose CRL must be fetched as the collateral.
+    pub fn pck_ca(quote: &[u8]) -> Result<&'static str> {
+        let quote = DcapQuote::parse(quote)?;
+        let certs = pem_certificates(quote.pck_cert_chain())?;
+        let pck_cert = certs
+            .first()
+            .ok_or_else(|| anyhow!("Not found the PCK certificate"))?;
+        let (issuer, _) = cert_names(pck_cert)?;
+        match common_name(issuer)? {
+            PCK_PROCESSOR_CA_NAME => Ok("processor"),
+            PCK_PLATFORM_CA_NAME => Ok("platform"),
+            name => bail!("Unknown CA of the PCK certificate: {}", name),
+        }
+    }
+
+    pub fn verify(&self, root_cert: &[u8]) -> Result<VerifiedEvidence> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!("{:?}", e))?
+            .as_secs();
+        self.verify_at(root_cert, now)
+    }
+
+    /// Verify the quote and the collateral at the given UNIX time,
+    /// chaining both the PCK certificate and the TCB signing certificate to the Intel SGX root CA.
+    /// The PCK certificate must not be revoked by the CRL of its CA,
+    /// nor its CA and the TCB signing certificate by the CRL of the root CA.
+    pub fn verify_at(&self, root_cert: &[u8], now: u64) -> Result<VerifiedEvidence> {
+        let quote = DcapQuote::parse(&self.quote)?;
+        let time = webpki::Time::from_seconds_since_unix_epoch(now);
+
+        // 1. The Quoting Enclave's report is signed by the PCK certificate issued by Intel.
+        let pck_certs = pem_certificates(quote.pck_cert_chain())?;
+        let pck_cert = verify_cert_chain(&pck_certs, root_cert, time)?;
+        let pck_ca_cert = pck_ca_cert(&pck_certs)?;
+        verify_crl(
+            pck_ca_cert,
+            self.collateral.pck_crl(),
+            &[&pck_cert],
+            now,
+            "PCK CRL",
+        )?;
+        verify_signature(
+            &pck_cert,
+            quote.qe_report(),
+            quote.qe_report_signature(),
+            "QE report",
+        )?;
+
+        // 2. The Quoting Enclave vouches for the attestation key by its report data.
+        let mut attest_key_and_auth_data = quote.attest_pub_key().to_vec();
+        attest_key_and_auth_data.extend_from_slice(quote.qe_auth_data());
+        let expected_report_data = digest(&SHA256, &attest_key_and_auth_data);
+        let qe_report_data = &quote.qe_report()[REPORT_DATA];
+        ensure!(
+            &qe_report_data[..32] == expected_report_data.as_ref()
+                && qe_report_data[32..].iter().all(|b| *b == 0),
+            "The attestation key is not bound to the QE report"
+        );
+
+        // 3. The ISV enclave's report is signed by the attestation key.
+        let mut attest_pub_key = vec![0x04];
+        attest_pub_key.extend_from_slice(quote.attest_pub_key());
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, &attest_pub_key)
+            .verify(quote.quote_body(), quote.isv_report_signature())
+            .map_err(|_| anyhow!("Invalid signature of the ISV enclave report"))?;
+
+        // 4. The TCB info and the QE identity are signed by the TCB signing certificate and not expired.
+        let tcb_info_signing_cert = verify_collateral(
+            self.collateral.tcb_info(),
+            self.collateral.tcb_info_signature(),
+            self.collateral.tcb_info_issuer_chain(),
+            root_cert,
+            time,
+            "TCB info",
+        )?;
+        let tcb_info = TcbInfo::parse(self.collateral.tcb_info())?;
+        ensure!(tcb_info.next_update() > now, "The TCB info is expired");
+        let qe_identity_signing_cert = verify_collateral(
+            self.collateral.qe_identity(),
+            self.collateral.qe_identity_signature(),
+            self.collateral.qe_identity_issuer_chain(),
+            root_cert,
+            time,
+            "QE identity",
+        )?;
+        let qe_identity = QeIdentity::parse(self.collateral.qe_identity())?;
+        ensure!(
+            qe_identity.next_update() > now,
+            "The QE identity is expired"
+        );
+
+        // 5. Neither the CA of the PCK certificate nor the TCB signing certificates are revoked by the root CA.
+        verify_crl(
+            root_cert,
+            self.collateral.root_ca_crl(),
+            &[
+                pck_ca_cert,
+                &tcb_info_signing_cert,
+                &qe_identity_signing_cert,
+            ],
+            now,
+            "root CA CRL",
+        )?;
+
+        // 6. Evaluate the TCB levels of the platform and the Quoting Enclave.
+        let platform_status = tcb_info.evaluate(&platform_tcb(&pck_cert)?)?;
+        let qe_status = qe_identity.evaluate(quote.qe_report())?;
+
+        let tcb_status = combine_statuses(platform_status, qe_status);
+
+        Ok(VerifiedEvidence {
+            quote_body: quote.quote_body().to_vec(),
+            status: tcb_status.status,
+            advisory_ids: tcb_status.advisory_ids,
+            timestamp: None,
+        })
+    }
+}
+
+/// Verify the certificate chain, whose first certificate is the end entity, up to the trusted root,
+/// and returns the end entity certificate.
+fn verify_cert_chain(certs: &[Vec<u8>], root_cert: &[u8], time: webpki::Time) -> Result<Vec<u8>> {
+    let (end_entity, intermediates) = certs
+        .split_first()
+        .ok_or_else(|| anyhow!("The certificate chain is empty"))?;
+
+    let mut root_store = rustls::RootCertStore::empty();
+    root_store
+        .add(&rustls::Certificate(root_cert.to_vec()))
+        .map_err(|e| anyhow!("Invalid root certificate: {:?}", e))?;
+    let trust_anchors: Vec<webpki::TrustAnchor> = root_store
+        .roots
+        .iter()
+        .map(|cert| cert.to_trust_anchor())
+        .collect();
+    let intermediates: Vec<&[u8]> = intermediates.iter().map(|cert| &cert[..]).collect();
+
+    webpki::EndEntityCert::from(end_entity)
+        .and_then(|cert| {
+            cert.verify_is_valid_tls_server_cert(
+                &[&webpki::ECDSA_P256_SHA256],
+                &webpki::TLSServerTrustAnchors(&trust_anchors),
+                &intermediates,
+                time,
+            )
+        })
+        .map_err(|e| anyhow!("Invalid certificate chain: {:?}", e))?;
+
+    Ok(end_entity.to_vec())
+}
+
+/// The CA certificate in the PCK certificate chain, which signed the PCK certificate, the first one of the chain.
+fn pck_ca_cert(pck_certs: &[Vec<u8>]) -> Result<&[u8]> {
+    let (pck_tbs, pck_signature) = signed_der(&pck_certs[0])?;
+    pck_certs
+        .iter()
+        .skip(1)
+        .find(|cert| verify_der_signature(cert, pck_tbs, pck_signature).is_ok())
+        .map(|cert| &cert[..])
+        .ok_or_else(|| anyhow!("Not found the CA certificate of the PCK certificate"))
+}
+
+/// Verify that none of the certificates issued by the CA is revoked.
+/// The CRL must be signed by the CA and must not be expired.
+fn verify_crl(ca_cert: &[u8], crl: &str, certs: &[&[u8]], now: u64, name: &str) -> Result<()> {
+    let crl = hex::decode(crl)?;
+    let (crl_tbs, crl_signature) = signed_der(&crl)?;
+    verify_der_signature(ca_cert, crl_tbs, crl_signature)
+        .map_err(|e| anyhow!("Invalid signature of the {}: {:?}", name, e))?;
+
+    // TBSCertList: version, signature, issuer, thisUpdate, nextUpdate, revokedCertificates, crlExtensions
+    let (mut fields, _) = der_expect(crl_tbs, SEQUENCE_TAG)?;
+    if der_tag(fields)? == INTEGER_TAG {
+        fields = der_expect(fields, INTEGER_TAG)?.1;
+    }
+    let (_, fields) = der_expect(fields, SEQUENCE_TAG)?;
+    let (_, fields) = der_expect(fields, SEQUENCE_TAG)?;
+    let (this_update, fields) = der_time(fields)?;
+    let (next_update, fields) = der_time(fields)?;
+    ensure!(
+        this_update <= now && now < next_update,
+        "The {} is not valid at {}",
+        name,
+        now
+    );
+
+    if fields.is_empty() || der_tag(fields)? != SEQUENCE_TAG {
+        return Ok(());
+    }
+    let serial_numbers = certs
+        .iter()
+        .map(|cert| serial_number(signed_der(cert)?.0))
+        .collect::<Result<Vec<&[u8]>>>()?;
+    let (mut revoked, _) = der_expect(fields, SEQUENCE_TAG)?;
+    while !revoked.is_empty() {
+        let (entry, rest) = der_expect(revoked, SEQUENCE_TAG)?;
+        let (revoked_serial_number, _) = der_expect(entry, INTEGER_TAG)?;
+        ensure!(
+            !serial_numbers.contains(&revoked_serial_number),
+            "The certificate is revoked by the {}",
+            name
+        );
+        revoked = rest;
+    }
+
+    Ok(())
+}
+
+/// The serial number following the optional version in the TBSCertificate.
+fn serial_number(tbs: &[u8]) -> Result<&[u8]> {
+    let (mut fields, _) = der_expect(tbs, SEQUENCE_TAG)?;
+    if der_tag(fields)? == CONTEXT_0_TAG {
+        fields = der_expect(fields, CONTEXT_0_TAG)?.1;
+    }
+    Ok(der_expect(fields, INTEGER_TAG)?.0)
+}
+
+/// The DER encoded issuer and subject names of the certificate.
+fn cert_names(cert: &[u8]) -> Result<(&[u8], &[u8])> {
+    let (tbs, _) = signed_der(cert)?;
+    // TBSCertificate: version, serialNumber, signature, issuer, validity, subject, ...
+    let (mut fields, _) = der_expect(tbs, SEQUENCE_TAG)?;
+    if der_tag(fields)? == CONTEXT_0_TAG {
+        fields = der_expect(fields, CONTEXT_0_TAG)?.1;
+    }
+    let (_, fields) = der_expect(fields, INTEGER_TAG)?;
+    let (_, fields) = der_expect(fields, SEQUENCE_TAG)?;
+    let (issuer, fields) = der_expect(fields, SEQUENCE_TAG)?;
+    let (_, fields) = der_expect(fields, SEQUENCE_TAG)?;
+    let (subject, _) = der_expect(fields, SEQUENCE_TAG)?;
+
+    Ok((issuer, subject))
+}
+
+/// The common name in the DER encoded name, which is a sequence of the sets of the attributes.
+fn common_name(name: &[u8]) -> Result<&str> {
+    let mut rdns = name;
+    while !rdns.is_empty() {
+        let (mut attributes, rest) = der_expect(rdns, SET_TAG)?;
+        while !attributes.is_empty() {
+            let (attribute, rest) = der_expect(attributes, SEQUENCE_TAG)?;
+            let (oid, value) = der_expect(attribute, OID_TAG)?;
+            if oid == COMMON_NAME_OID {
+                let (value, _) = match der_tag(value)? {
+                    tag @ UTF8_STRING_TAG | tag @ PRINTABLE_STRING_TAG => der_expect(value, tag)?,
+                    tag => bail!("Unsupported DER string tag {:#x} of the common name", tag),
+                };
+                return Ok(str::from_utf8(value)?);
+            }
+            attributes = rest;
+        }
+        rdns = rest;
+    }
+
+    bail!("Not found the common name")
+}
+
+/// Split the DER encoded certificate or CRL into the whole signed part and its DER encoded signature.
+fn signed_der(der: &[u8]) -> Result<(&[u8], &[u8])> {
+    let (signed, _) = der_expect(der, SEQUENCE_TAG)?;
+    let (_, rest) = der_expect(signed, SEQUENCE_TAG)?;
+    let tbs = &signed[..signed.len() - rest.len()];
+    let (_, rest) = der_expect(rest, SEQUENCE_TAG)?;
+    let (signature, _) = der_expect(rest, BIT_STRING_TAG)?;
+    match signature.split_first() {
+        Some((0, signature)) => Ok((tbs, signature)),
+        _ => bail!("Invalid signature of the DER"),
+    }
+}
+
+/// Verify the ECDSA signature in the DER format by the certificate.
+fn verify_der_signature(
+    cert: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> core::result::Result<(), webpki::Error> {
+    webpki::EndEntityCert::from(cert)
+        .and_then(|cert| cert.verify_signature(&webpki::ECDSA_P256_SHA256, message, signature))
+}
+
+fn der_tag(der: &[u8]) -> Result<u8> {
+    der.first()
+        .copied()
+        .ok_or_else(|| anyhow!("The DER is truncated"))
+}
+
+/// Split the DER encoded value of the tag at the beginning into its content and the rest.
+fn der_expect(der: &[u8], tag: u8) -> Result<(&[u8], &[u8])> {
+    ensure!(der_tag(der)? == tag, "Unexpected DER tag {:#x}", der[0]);
+    ensure!(der.len() >= 2, "The DER is truncated");
+    let (len, header_len) = match der[1] {
+        len if len < 0x80 => (len as usize, 2),
+        0x81 => (
+            *der.get(2).ok_or_else(|| anyhow!("The DER is truncated"))? as usize,
+            3,
+        ),
+        0x82 => {
+            let len = der
+                .get(2..4)
+                .ok_or_else(|| anyhow!("The DER is truncated"))?;
+            ((len[0] as usize) << 8 | len[1] as usize, 4)
+        }
+        0x83 => {
+            let len = der
+                .get(2..5)
+                .ok_or_else(|| anyhow!("The DER is truncated"))?;
+            (
+                (len[0] as usize) << 16 | (len[1] as usize) << 8 | len[2] as usize,
+                5,
+            )
+        }
+        _ => bail!("Unsupported DER length"),
+    };
+    ensure!(der.len() >= header_len + len, "The DER is truncated");
+
+    Ok((&der[header_len..header_len + len], &der[header_len + len..]))
+}
+
+/// Parse the UTCTime or the GeneralizedTime at the beginning into the UNIX time.
+fn der_time(der: &[u8]) -> Result<(u64, &[u8])> {
+    let (time, rest) = match der_tag(der)? {
+        UTC_TIME_TAG => {
+            let (time, rest) = der_expect(der, UTC_TIME_TAG)?;
+            let time = str::from_utf8(time)?;
+            let century = match time.get(..2).and_then(|year| year.parse::<u8>().ok()) {
+                Some(year) if year >= 50 => "19",
+                Some(_) => "20",
+                None => bail!("Invalid UTCTime: {}", time),
+            };
+            (format!("{}{}", century, time), rest)
+        }
+        _ => {
+            let (time, rest) = der_expect(der, GENERALIZED_TIME_TAG)?;
+            (String::from(str::from_utf8(time)?), rest)
+        }
+    };
+    ensure!(
+        time.len() >= 14 && time.is_ascii(),
+        "Invalid DER time: {}",
+        time
+    );
+    // YYYYMMDDHHMMSSZ into YYYY-MM-DDTHH:MM:SSZ
+    let datetime = format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &time[..4],
+        &time[4..6],
+        &time[6..8],
+        &time[8..10],
+        &time[10..12],
+        &time[12..14]
+    );
+
+    Ok((parse_datetime(&datetime)?, rest))
+}
+
+/// Verify the ECDSA signature in the raw r and s format by the certificate.
+fn verify_signature(cert: &[u8], message: &[u8], signature: &[u8], name: &str) -> Result<()> {
+    webpki::EndEntityCert::from(cert)
+        .and_then(|cert| {
+            cert.verify_signature(
+                &webpki::ECDSA_P256_SHA256,
+                message,
+                &raw_signature_to_der(signature)?,
+            )
+        })
+        .map_err(|e| anyhow!("Invalid signature of the {}: {:?}", name, e))
+}
+
+/// Verify the signature of the collateral, and returns its signing certificate.
+/// Any certificate chained to the root CA, e.g. the one of a PCK CA, must not sign the collateral,
+/// so the signing certificate is pinned to the Intel SGX TCB Signing certificate issued directly by the root CA.
+fn verify_collateral(
+    collateral: &str,
+    signature: &str,
+    issuer_chain: &str,
+    root_cert: &[u8],
+    time: webpki::Time,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let signing_cert =
+        verify_cert_chain(&pem_certificates(issuer_chain.as_bytes())?, root_cert, time)?;
+    let (issuer, subject) = cert_names(&signing_cert)?;
+    let (_, root_subject) = cert_names(root_cert)?;
+    ensure!(
+        common_name(subject)? == TCB_SIGNING_NAME,
+        "The {} is signed by {}, not the TCB signing certificate",
+        name,
+        common_name(subject)?
+    );
+    let (tbs, cert_signature) = signed_der(&signing_cert)?;
+    ensure!(
+        issuer == root_subject
+            && common_name(issuer)? == ROOT_CA_NAME
+            && verify_der_signature(root_cert, tbs, cert_signature).is_ok(),
+        "The TCB signing certificate of the {} is not issued by the root CA",
+        name
+    );
+
+    let signature = hex::decode(signature)?;
+    verify_signature(&signing_cert, collateral.as_bytes(), &signature, name)?;
+
+    Ok(signing_cert)
+}
+
+/// Encode the raw r and s of an ECDSA signature as an ASN.1 DER sequence of two integers.
+fn raw_signature_to_der(signature: &[u8]) -> core::result::Result<Vec<u8>, webpki::Error> {
+    if signature.len() != 64 {
+        return Err(webpki::Error::InvalidSignatureForPublicKey);
+    }
+
+    let mut integers = Vec::with_capacity(72);
+    for half in signature.chunks(32) {
+        let first_nonzero = half.iter().position(|b| *b != 0).unwrap_or(31);
+        let integer = &half[first_nonzero..];
+        let needs_padding = integer[0] & 0x80 != 0;
+        integers.push(0x02);
+        integers.push((integer.len() + needs_padding as usize) as u8);
+        if needs_padding {
+            integers.push(0x00);
+        }
+        integers.extend_from_slice(integer);
+    }
+
+    let mut der = vec![0x30, integers.len() as u8];
+    der.extend_from_slice(&integers);
+    Ok(der)
+}
+
+/// Decode the concatenated PEM certificates, ignoring any trailing null bytes.
+fn pem_certificates(pem: &[u8]) -> Result<Vec<Vec<u8>>> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let pem = str::from_utf8(pem)?.trim_end_matches('\0');
+    let mut certs = vec![];
+    let mut rest = pem;
+    while let Some(begin) = rest.find(BEGIN) {
+        let body = &rest[begin + BEGIN.len()..];
+        let end = body
+            .find(END)
+            .ok_or_else(|| anyhow!("The PEM certificate is truncated"))?;
+        let encoded: String = body[..end].chars().filter(|c| !c.is_whitespace()).collect();
+        certs.push(base64::decode(&encoded)?);
+        rest = &body[end + END.len()..];
+    }
+    ensure!(!certs.is_empty(), "Not found any PEM certificates");
+
+    Ok(certs)
+}
+
+fn platform_tcb(pck_cert: &[u8]) -> Result<PlatformTcb> {
+    let fmspc = sgx_extension_value(pck_cert, &[FMSPC_OID])?.to_vec();
+    let mut sgx_tcb_components = [0u8; 16];
+    for (i, svn) in sgx_tcb_components.iter_mut().enumerate() {
+        *svn = der_uint(sgx_extension_value(pck_cert, &[TCB_OID, i as u8 + 1])?)? as u8;
+    }
+    let pce_svn = der_uint(sgx_extension_value(
+        pck_cert,
+        &[TCB_OID, PCESVN_TCB_COMPONENT],
+    )?)? as u16;
+
+    Ok(PlatformTcb {
+        fmspc,
+        sgx_tcb_components,
+        pce_svn,
+    })
+}
+
+/// Find the value following the OID under the SGX extension in the DER encoded certificate.
+/// The certificate has been verified, so it's trusted that the OID appears only once.
+fn sgx_extension_value<'a>(cert: &'a [u8], sub_oid: &[u8]) -> Result<&'a [u8]> {
+    let mut oid = vec![0x06, (SGX_EXTENSION_OID.len() + sub_oid.len()) as u8];
+    oid.extend_from_slice(SGX_EXTENSION_OID);
+    oid.extend_from_slice(sub_oid);
+
+    let position = cert
+        .windows(oid.len())
+        .position(|window| window == &oid[..])
+        .ok_or_else(|| anyhow!("Not found the SGX extension {:?}", sub_oid))?;
+    let value = &cert[position + oid.len()..];
+    ensure!(value.len() >= 2, "The SGX extension is truncated");
+    let len = value[1] as usize;
+    ensure!(
+        len < 0x80 && value.len() >= 2 + len,
+        "Invalid length of the SGX extension"
+    );
+
+    Ok(&value[2..2 + len])
+}
+
+fn der_uint(bytes: &[u8]) -> Result<u64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        bail!("Invalid DER integer");
+    }
+    Ok(bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+}
+
+mod base64_bytes {
+    use crate::base64;
+    use crate::localstd::{string::String, vec::Vec};
+    use crate::serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::decode(&encoded).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote_status::{QuoteStatus, QuoteStatusPolicy};
+    use crate::serde_json;
+
+    /// 2026-01-01T00:00:00Z, while the synthetic collateral is valid
+    const NOW: u64 = 1_767_225_600;
+    const ROOT_CA: &str = include_str!("../../testdata/dcap/root_ca.pem");
+    const QUOTE: &[u8] = include_bytes!("../../testdata/dcap/quote.dat");
+    const TCB_INFO: &str = include_str!("../../testdata/dcap/tcb_info.json");
+    const QE_IDENTITY: &str = include_str!("../../testdata/dcap/qe_identity.json");
+    const ISSUER_CHAIN: &str = include_str!("../../testdata/dcap/tcb_info_issuer_chain.pem");
+    const PCK_CRL: &[u8] = include_bytes!("../../testdata/dcap/pck_crl.der");
+    const PCK_CRL_REVOKED: &[u8] = include_bytes!("../../testdata/dcap/pck_crl_revoked.der");
+    const ROOT_CA_CRL: &[u8] = include_bytes!("../../testdata/dcap/root_ca_crl.der");
+    const ROOT_CA_CRL_REVOKED: &[u8] =
+        include_bytes!("../../testdata/dcap/root_ca_crl_revoked.der");
+    const TCB_INFO_SIGNED_BY_PCK: &str =
+        include_str!("../../testdata/dcap/tcb_info_signed_by_pck.json");
+    const PCK_ISSUER_CHAIN: &str = include_str!("../../testdata/dcap/pck_issuer_chain.pem");
+
+    fn root_cert() -> Vec<u8> {
+        pem_certificates(ROOT_CA.as_bytes()).unwrap().remove(0)
+    }
+
+    fn evidence(quote: Vec<u8>) -> DcapEvidence {
+        evidence_with_crls(quote, PCK_CRL, ROOT_CA_CRL)
+    }
+
+    fn evidence_with_crls(quote: Vec<u8>, pck_crl: &[u8], root_ca_crl: &[u8]) -> DcapEvidence {
+        let collateral = DcapCollateral::from_pccs_responses(
+            TCB_INFO,
+            ISSUER_CHAIN.to_string(),
+            QE_IDENTITY,
+            ISSUER_CHAIN.to_string(),
+            pck_crl,
+            root_ca_crl,
+        )
+        .unwrap();
+        DcapEvidence::new(quote, collateral)
+    }
+
+    #[test]
+    fn test_verify_evidence() {
+        let verified = evidence(QUOTE.to_vec())
+            .verify_at(&root_cert(), NOW)
+            .unwrap();
+
+        assert_eq!(verified.status(), QuoteStatus::SwHardeningNeeded);
+        assert_eq!(verified.advisory_ids(), &["INTEL-SA-00334".to_string()]);
+        // The same offsets as the quote body of IAS
+        assert_eq!(&verified.quote_body()[112..144], &[0x11; 32]);
+        assert_eq!(&verified.quote_body()[176..208], &[0x22; 32]);
+        assert_eq!(&verified.quote_body()[368..432], &[0x33; 64][..]);
+
+        assert_eq!(
+            DcapEvidence::fmspc(QUOTE).unwrap(),
+            hex::decode("00906ed50000").unwrap()
+        );
+        assert_eq!(DcapEvidence::pck_ca(QUOTE).unwrap(), "platform");
+        assert!(QuoteStatusPolicy::default()
+            .verify(verified.status(), Some(verified.advisory_ids()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_tampered_quote() {
+        // The report data of the ISV enclave
+        let mut quote = QUOTE.to_vec();
+        quote[400] ^= 1;
+        assert!(evidence(quote).verify_at(&root_cert(), NOW).is_err());
+
+        // The MRENCLAVE of the QE report, following the ISV enclave's signature and the attestation key
+        let mut quote = QUOTE.to_vec();
+        quote[436 + 64 + 64 + 64] ^= 1;
+        assert!(evidence(quote).verify_at(&root_cert(), NOW).is_err());
+    }
+
+    #[test]
+    fn test_reject_untrusted_collateral() {
+        // Expired
+        assert!(evidence(QUOTE.to_vec())
+            .verify_at(&root_cert(), 1_893_456_000)
+            .is_err());
+
+        // Modified after signed
+        let collateral = evidence(QUOTE.to_vec()).collateral;
+        let modified = DcapCollateral::new(
+            collateral
+                .tcb_info()
+                .replace("SWHardeningNeeded", "UpToDate"),
+            collateral.tcb_info_signature().to_string(),
+            collateral.tcb_info_issuer_chain().to_string(),
+            collateral.qe_identity().to_string(),
+            collateral.qe_identity_signature().to_string(),
+            collateral.qe_identity_issuer_chain().to_string(),
+            collateral.pck_crl().to_string(),
+            collateral.root_ca_crl().to_string(),
+        );
+        assert!(DcapEvidence::new(QUOTE.to_vec(), modified)
+            .verify_at(&root_cert(), NOW)
+            .is_err());
+
+        // Chained to another root
+        let tcb_signing_cert = pem_certificates(ISSUER_CHAIN.as_bytes()).unwrap().remove(0);
+        assert!(evidence(QUOTE.to_vec())
+            .verify_at(&tcb_signing_cert, NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_collateral_of_another_signer() {
+        // The PCK certificate is chained to the root CA, but isn't trusted to sign the collateral.
+        let pck_chain = pem_certificates(PCK_ISSUER_CHAIN.as_bytes()).unwrap();
+        assert!(verify_cert_chain(
+            &pck_chain,
+            &root_cert(),
+            webpki::Time::from_seconds_since_unix_epoch(NOW)
+        )
+        .is_ok());
+
+        let collateral = DcapCollateral::from_pccs_responses(
+            TCB_INFO_SIGNED_BY_PCK,
+            PCK_ISSUER_CHAIN.to_string(),
+            QE_IDENTITY,
+            ISSUER_CHAIN.to_string(),
+            PCK_CRL,
+            ROOT_CA_CRL,
+        )
+        .unwrap();
+        assert!(DcapEvidence::new(QUOTE.to_vec(), collateral)
+            .verify_at(&root_cert(), NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn test_cert_names() {
+        let signing_cert = pem_certificates(ISSUER_CHAIN.as_bytes()).unwrap().remove(0);
+        let (issuer, subject) = cert_names(&signing_cert).unwrap();
+        assert_eq!(common_name(subject).unwrap(), TCB_SIGNING_NAME);
+        assert_eq!(common_name(issuer).unwrap(), ROOT_CA_NAME);
+        assert_eq!(issuer, cert_names(&root_cert()).unwrap().1);
+        assert!(common_name(&[]).is_err());
+    }
+
+    #[test]
+    fn test_reject_revoked_pck_cert() {
+        assert!(
+            evidence_with_crls(QUOTE.to_vec(), PCK_CRL_REVOKED, ROOT_CA_CRL)
+                .verify_at(&root_cert(), NOW)
+                .is_err()
+        );
+
+        // Not signed by the CA of the PCK certificate
+        let mut pck_crl = PCK_CRL.to_vec();
+        let last = pck_crl.len() - 1;
+        pck_crl[last] ^= 1;
+        assert!(evidence_with_crls(QUOTE.to_vec(), &pck_crl, ROOT_CA_CRL)
+            .verify_at(&root_cert(), NOW)
+            .is_err());
+
+        // Not yet issued
+        assert!(evidence(QUOTE.to_vec())
+            .verify_at(&root_cert(), 1_609_459_200)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_revoked_tcb_signing_cert() {
+        assert!(
+            evidence_with_crls(QUOTE.to_vec(), PCK_CRL, ROOT_CA_CRL_REVOKED)
+                .verify_at(&root_cert(), NOW)
+                .is_err()
+        );
+
+        // Not signed by the root CA
+        let mut root_ca_crl = ROOT_CA_CRL.to_vec();
+        let last = root_ca_crl.len() - 1;
+        root_ca_crl[last] ^= 1;
+        assert!(evidence_with_crls(QUOTE.to_vec(), PCK_CRL, &root_ca_crl)
+            .verify_at(&root_cert(), NOW)
+            .is_err());
+        // The PCK CRL in place of the root CA CRL
+        assert!(evidence_with_crls(QUOTE.to_vec(), PCK_CRL, PCK_CRL)
+            .verify_at(&root_cert(), NOW)
+            .is_err());
+    }
+
+    #[test]
+    fn test_evidence_serde() {
+        let evidence = evidence(QUOTE.to_vec());
+        let json = serde_json::to_vec(&evidence).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<DcapEvidence>(&json).unwrap(),
+            evidence
+        );
+    }
+}
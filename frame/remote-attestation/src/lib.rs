@@ -9,6 +9,7 @@ pub(crate) mod stdlib {
     pub(crate) use anyhow_std as anyhow;
     pub(crate) use base64_std as base64;
     pub(crate) use http_req_std as http_req;
+    pub(crate) use ring_std as ring;
     pub(crate) use rustls_std as rustls;
     pub(crate) use serde_json_std as serde_json;
     pub(crate) use serde_std as serde;
@@ -20,6 +21,7 @@ pub(crate) mod sgxlib {
     pub(crate) use anyhow_sgx as anyhow;
     pub(crate) use base64_sgx as base64;
     pub(crate) use http_req_sgx as http_req;
+    pub(crate) use ring_sgx as ring;
     pub(crate) use rustls_sgx as rustls;
     pub(crate) use serde_json_sgx as serde_json;
     pub(crate) use serde_sgx as serde;
@@ -33,9 +35,17 @@ pub(crate) use crate::sgxlib::*;
 pub(crate) use crate::stdlib::*;
 
 mod client;
+pub mod dcap;
 mod error;
+mod provider;
 mod quote;
+mod quote_status;
 
 pub use crate::client::AttestedReport;
 pub use crate::error::FrameRAError as Error;
+pub use crate::provider::{
+    provider_from_env, AttestationEvidence, AttestationProvider, DcapProvider, EpidProvider,
+    VerifiedEvidence,
+};
 pub use crate::quote::{EncodedQuote, QuoteTarget};
+pub use crate::quote_status::{QuoteStatus, QuoteStatusPolicy};
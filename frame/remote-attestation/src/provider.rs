@@ -0,0 +1,171 @@
+use crate::anyhow::anyhow;
+use crate::client::AttestedReport;
+use crate::dcap::{DcapEvidence, PccsClient};
+use crate::error::Result;
+use crate::localstd::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::quote::QuoteTarget;
+use crate::quote_status::{QuoteStatus, QuoteStatusPolicy};
+use crate::serde::{Deserialize, Serialize};
+use frame_config::{ATTESTATION_PROVIDER, PCCS_URL};
+use sgx_types::sgx_report_data_t;
+
+/// The evidence that the enclave is running on a genuine SGX platform,
+/// which is embedded in the attested TLS certificates.
+/// It's untagged to keep the certificates of the IAS attestation compatible.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "crate::serde", untagged)]
+pub enum AttestationEvidence {
+    /// An attestation report of an EPID quote signed by IAS
+    Epid(AttestedReport),
+    /// An ECDSA quote with the collateral from a PCCS
+    Dcap(DcapEvidence),
+}
+
+impl AttestationEvidence {
+    /// Verify the evidence by the root CA of its backend and the quote status policy.
+    /// `dcap_root_cert` is `None` if the DCAP attestation is not trusted.
+    pub fn verify(
+        self,
+        ias_root_cert: &[u8],
+        dcap_root_cert: Option<&[u8]>,
+        policy: &QuoteStatusPolicy,
+    ) -> Result<VerifiedEvidence> {
+        match self {
+            AttestationEvidence::Epid(report) => {
+                let report =
+                    report.verify_attested_report_with_policy(ias_root_cert.to_vec(), policy)?;
+                Ok(VerifiedEvidence {
+                    quote_body: report.get_quote_body()?,
+                    status: report.quote_status()?,
+                    advisory_ids: report.advisory_ids()?,
+                    timestamp: Some(report.timestamp()?),
+                })
+            }
+            AttestationEvidence::Dcap(evidence) => {
+                let root_cert = dcap_root_cert.ok_or_else(|| {
+                    anyhow!("The DCAP attestation is not trusted without its root CA")
+                })?;
+                let verified = evidence.verify(root_cert)?;
+                policy.verify(verified.status, Some(&verified.advisory_ids))?;
+                Ok(verified)
+            }
+        }
+    }
+}
+
+/// The verified quote and its status, which is common to the attestation backends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedEvidence {
+    pub(crate) quote_body: Vec<u8>,
+    pub(crate) status: QuoteStatus,
+    pub(crate) advisory_ids: Vec<String>,
+    /// The time when the attestation service verified the quote, in seconds since the UNIX epoch.
+    /// `None` for the ECDSA quotes, which are verified by the peers themselves.
+    pub(crate) timestamp: Option<u64>,
+}
+
+impl VerifiedEvidence {
+    /// The quote header and the ISV enclave report,
+    /// whose layout is the same as `isvEnclaveQuoteBody` of the IAS attestation report.
+    pub fn quote_body(&self) -> &[u8] {
+        &self.quote_body
+    }
+
+    pub fn status(&self) -> QuoteStatus {
+        self.status
+    }
+
+    pub fn advisory_ids(&self) -> &[String] {
+        &self.advisory_ids
+    }
+
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+}
+
+/// A backend of remote attestations, which proves the enclave's report data to the remote peers.
+pub trait AttestationProvider: Send + Sync {
+    fn attest(&self, report_data: &sgx_report_data_t) -> Result<AttestationEvidence>;
+}
+
+/// The EPID attestation verified by Intel Attestation Service
+pub struct EpidProvider {
+    spid: String,
+    ias_url: String,
+    sub_key: String,
+    root_cert: Vec<u8>,
+}
+
+impl EpidProvider {
+    pub fn new(spid: &str, ias_url: &str, sub_key: &str, root_cert: Vec<u8>) -> Self {
+        EpidProvider {
+            spid: spid.to_string(),
+            ias_url: ias_url.to_string(),
+            sub_key: sub_key.to_string(),
+            root_cert,
+        }
+    }
+}
+
+impl AttestationProvider for EpidProvider {
+    fn attest(&self, report_data: &sgx_report_data_t) -> Result<AttestationEvidence> {
+        let report = QuoteTarget::new()?
+            .set_enclave_report(report_data)?
+            .create_quote(&self.spid)?
+            .remote_attestation(&self.ias_url, &self.sub_key, self.root_cert.clone())?;
+        Ok(AttestationEvidence::Epid(report))
+    }
+}
+
+/// The ECDSA attestation of DCAP, whose quotes are verified by the peers themselves
+/// with the collateral fetched from a PCCS.
+pub struct DcapProvider {
+    pccs_url: String,
+}
+
+impl DcapProvider {
+    pub fn new(pccs_url: &str) -> Self {
+        DcapProvider {
+            pccs_url: pccs_url.to_string(),
+        }
+    }
+}
+
+impl AttestationProvider for DcapProvider {
+    fn attest(&self, report_data: &sgx_report_data_t) -> Result<AttestationEvidence> {
+        let quote = QuoteTarget::new_for_dcap()?
+            .set_enclave_report(report_data)?
+            .create_dcap_quote()?;
+        let fmspc = DcapEvidence::fmspc(&quote)?;
+        let pck_ca = DcapEvidence::pck_ca(&quote)?;
+        let collateral = PccsClient::new(&self.pccs_url).get_collateral(&fmspc, pck_ca)?;
+        Ok(AttestationEvidence::Dcap(DcapEvidence::new(
+            quote, collateral,
+        )))
+    }
+}
+
+/// The attestation provider selected by `ATTESTATION_PROVIDER`.
+/// The IAS settings are used only by the EPID attestation.
+pub fn provider_from_env(
+    spid: &str,
+    ias_url: &str,
+    sub_key: &str,
+    ias_root_cert: Vec<u8>,
+) -> Result<Box<dyn AttestationProvider>> {
+    match ATTESTATION_PROVIDER.as_str() {
+        "epid" => Ok(Box::new(EpidProvider::new(
+            spid,
+            ias_url,
+            sub_key,
+            ias_root_cert,
+        ))),
+        "dcap" => Ok(Box::new(DcapProvider::new(&PCCS_URL))),
+        provider => Err(anyhow!("Unsupported ATTESTATION_PROVIDER: {}", provider).into()),
+    }
+}
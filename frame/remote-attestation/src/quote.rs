@@ -30,6 +30,19 @@ extern "C" {
         maxlen: u32,
         p_quote_len: *mut u32,
     ) -> sgx_status_t;
+
+    fn ocall_sgx_qe_get_target_info(
+        retval: *mut UntrustedStatus,
+        ret_ti: *mut sgx_target_info_t,
+    ) -> sgx_status_t;
+
+    fn ocall_sgx_qe_get_quote(
+        retval: *mut UntrustedStatus,
+        report: *const sgx_report_t,
+        p_quote: *mut u8,
+        maxlen: u32,
+        p_quote_len: *mut u32,
+    ) -> sgx_status_t;
 }
 
 /// The very high level service for remote attestations
@@ -107,6 +120,40 @@ impl QuoteTarget {
         })
     }
 
+    /// Returns the target information of the Quoting Enclave of DCAP, which generates ECDSA quotes.
+    pub fn new_for_dcap() -> Result<Self> {
+        let mut rt = UntrustedStatus::default();
+        let mut target_info = sgx_target_info_t::default();
+
+        #[cfg(all(feature = "sgx", not(feature = "std")))]
+        let status = unsafe {
+            ocall_sgx_qe_get_target_info(
+                &mut rt as *mut UntrustedStatus,
+                &mut target_info as *mut sgx_target_info_t,
+            )
+        };
+        #[cfg(all(not(feature = "sgx"), feature = "std"))]
+        let status = sgx_status_t::SGX_SUCCESS; // TODO
+
+        if status != sgx_status_t::SGX_SUCCESS {
+            return Err(FrameRAError::OcallError {
+                status,
+                function: "ocall_sgx_qe_get_target_info",
+            });
+        }
+        if rt.is_err() {
+            return Err(FrameRAError::UntrustedError {
+                status: rt,
+                function: "ocall_sgx_qe_get_target_info",
+            });
+        }
+
+        Ok(Self {
+            target_info,
+            enclave_report: None,
+        })
+    }
+
     pub fn set_enclave_report(mut self, report_data: &sgx_report_data_t) -> Result<Self> {
         #[cfg(all(feature = "sgx", not(feature = "std")))]
         let enclave_report =
@@ -170,4 +217,42 @@ impl QuoteTarget {
         let _ = quote.split_off(quote_len as usize);
         Ok(EncodedQuote::new(base64::encode(&quote)))
     }
+
+    /// Create an ECDSA quote of the enclave's local report by the Quoting Enclave of DCAP.
+    /// The target information must be the one of `new_for_dcap`.
+    pub fn create_dcap_quote(self) -> Result<Vec<u8>> {
+        const RET_QUOTE_BUF_LEN: u32 = 8192;
+        let mut rt = UntrustedStatus::default();
+        let mut quote = vec![0u8; RET_QUOTE_BUF_LEN as usize];
+        let mut quote_len: u32 = 0;
+
+        #[cfg(all(feature = "sgx", not(feature = "std")))]
+        let status = unsafe {
+            ocall_sgx_qe_get_quote(
+                &mut rt as *mut UntrustedStatus,
+                &self.enclave_report.unwrap() as *const sgx_report_t, // enclave_report must be set
+                quote.as_mut_ptr(),
+                RET_QUOTE_BUF_LEN, // maxlen
+                &mut quote_len as *mut u32,
+            )
+        };
+        #[cfg(all(not(feature = "sgx"), feature = "std"))]
+        let status = sgx_status_t::SGX_SUCCESS; // TODO
+
+        if status != sgx_status_t::SGX_SUCCESS {
+            return Err(FrameRAError::OcallError {
+                status,
+                function: "ocall_sgx_qe_get_quote",
+            });
+        }
+        if rt.is_err() {
+            return Err(FrameRAError::UntrustedError {
+                status: rt,
+                function: "ocall_sgx_qe_get_quote",
+            });
+        }
+
+        quote.truncate(quote_len as usize);
+        Ok(quote)
+    }
 }
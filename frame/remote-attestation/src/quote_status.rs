@@ -0,0 +1,277 @@
+use crate::error::{FrameRAError, Result};
+use crate::localstd::{
+    fmt,
+    str::FromStr,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The comma-separated quote statuses tolerated besides `OK`, set by `IAS_ALLOWED_QUOTE_STATUSES` when building the enclave.
+/// It's compiled into the enclave and measured in MRENCLAVE, so that the host can't loosen the policy.
+const ALLOWED_QUOTE_STATUSES: Option<&str> = option_env!("IAS_ALLOWED_QUOTE_STATUSES");
+/// The comma-separated advisory IDs which a tolerated quote status may be affected by, set by `IAS_ALLOWED_ADVISORY_IDS` when building the enclave.
+const ALLOWED_ADVISORY_IDS: Option<&str> = option_env!("IAS_ALLOWED_ADVISORY_IDS");
+
+/// The status of the quote in an attestation report.
+/// Defined in "Attestation Service for Intel® Software Guard Extensions (Intel® SGX): API Documentation"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStatus {
+    Ok,
+    SignatureInvalid,
+    GroupRevoked,
+    SignatureRevoked,
+    KeyRevoked,
+    SigrlVersionMismatch,
+    GroupOutOfDate,
+    ConfigurationNeeded,
+    SwHardeningNeeded,
+    ConfigurationAndSwHardeningNeeded,
+}
+
+impl QuoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteStatus::Ok => "OK",
+            QuoteStatus::SignatureInvalid => "SIGNATURE_INVALID",
+            QuoteStatus::GroupRevoked => "GROUP_REVOKED",
+            QuoteStatus::SignatureRevoked => "SIGNATURE_REVOKED",
+            QuoteStatus::KeyRevoked => "KEY_REVOKED",
+            QuoteStatus::SigrlVersionMismatch => "SIGRL_VERSION_MISMATCH",
+            QuoteStatus::GroupOutOfDate => "GROUP_OUT_OF_DATE",
+            QuoteStatus::ConfigurationNeeded => "CONFIGURATION_NEEDED",
+            QuoteStatus::SwHardeningNeeded => "SW_HARDENING_NEEDED",
+            QuoteStatus::ConfigurationAndSwHardeningNeeded => {
+                "CONFIGURATION_AND_SW_HARDENING_NEEDED"
+            }
+        }
+    }
+
+    /// Whether the quote is genuine and only the platform needs updates or mitigations.
+    /// An invalid or revoked quote is never tolerated, whatever the policy is.
+    pub fn is_tolerable(&self) -> bool {
+        matches!(
+            self,
+            QuoteStatus::GroupOutOfDate
+                | QuoteStatus::ConfigurationNeeded
+                | QuoteStatus::SwHardeningNeeded
+                | QuoteStatus::ConfigurationAndSwHardeningNeeded
+        )
+    }
+}
+
+impl FromStr for QuoteStatus {
+    type Err = FrameRAError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let status = match s {
+            "OK" => QuoteStatus::Ok,
+            "SIGNATURE_INVALID" => QuoteStatus::SignatureInvalid,
+            "GROUP_REVOKED" => QuoteStatus::GroupRevoked,
+            "SIGNATURE_REVOKED" => QuoteStatus::SignatureRevoked,
+            "KEY_REVOKED" => QuoteStatus::KeyRevoked,
+            "SIGRL_VERSION_MISMATCH" => QuoteStatus::SigrlVersionMismatch,
+            "GROUP_OUT_OF_DATE" => QuoteStatus::GroupOutOfDate,
+            "CONFIGURATION_NEEDED" => QuoteStatus::ConfigurationNeeded,
+            "SW_HARDENING_NEEDED" => QuoteStatus::SwHardeningNeeded,
+            "CONFIGURATION_AND_SW_HARDENING_NEEDED" => {
+                QuoteStatus::ConfigurationAndSwHardeningNeeded
+            }
+            _ => return Err(FrameRAError::QuoteStatusError(s.to_string())),
+        };
+
+        Ok(status)
+    }
+}
+
+impl fmt::Display for QuoteStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The policy deciding which quote statuses are accepted besides `OK`,
+/// and which security advisories the platform may be affected by in that case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteStatusPolicy {
+    allowed_statuses: Vec<QuoteStatus>,
+    /// `None` tolerates any advisories.
+    allowed_advisory_ids: Option<Vec<String>>,
+}
+
+impl QuoteStatusPolicy {
+    /// Returns an error if an invalid or revoked status is given.
+    pub fn new(
+        allowed_statuses: Vec<QuoteStatus>,
+        allowed_advisory_ids: Option<Vec<String>>,
+    ) -> Result<Self> {
+        if let Some(status) = allowed_statuses
+            .iter()
+            .find(|status| **status != QuoteStatus::Ok && !status.is_tolerable())
+        {
+            return Err(FrameRAError::QuoteStatusError(format!(
+                "{} can't be tolerated",
+                status
+            )));
+        }
+
+        Ok(QuoteStatusPolicy {
+            allowed_statuses,
+            allowed_advisory_ids,
+        })
+    }
+
+    /// The policy compiled in by `IAS_ALLOWED_QUOTE_STATUSES` and `IAS_ALLOWED_ADVISORY_IDS`,
+    /// which is shared by all verifications of attestation reports in the enclave.
+    /// Without them, it's the strict one.
+    pub fn compiled() -> Result<Self> {
+        Self::parse(
+            ALLOWED_QUOTE_STATUSES.unwrap_or_default(),
+            ALLOWED_ADVISORY_IDS.unwrap_or_default(),
+        )
+    }
+
+    /// Parse the comma-separated statuses and advisory IDs.
+    /// The tolerated statuses must list the advisories they're affected by, all of which must be allowed.
+    fn parse(allowed_statuses: &str, allowed_advisory_ids: &str) -> Result<Self> {
+        let allowed_statuses = comma_separated(allowed_statuses)
+            .map(|status| status.parse())
+            .collect::<Result<Vec<QuoteStatus>>>()?;
+        let allowed_advisory_ids = comma_separated(allowed_advisory_ids)
+            .map(ToString::to_string)
+            .collect();
+        Self::new(allowed_statuses, Some(allowed_advisory_ids))
+    }
+
+    /// Only accepts `OK`.
+    pub fn strict() -> Self {
+        QuoteStatusPolicy {
+            allowed_statuses: vec![],
+            allowed_advisory_ids: Some(vec![]),
+        }
+    }
+
+    /// Verify the quote status and the advisories listed in the attestation report.
+    /// `advisory_ids` is `None` if the report doesn't list them, as with the API version 3.
+    pub fn verify(&self, status: QuoteStatus, advisory_ids: Option<&[String]>) -> Result<()> {
+        if status == QuoteStatus::Ok {
+            return Ok(());
+        }
+        if !self.allowed_statuses.contains(&status) {
+            return Err(FrameRAError::QuoteStatusError(status.to_string()));
+        }
+
+        if let Some(allowed_advisory_ids) = &self.allowed_advisory_ids {
+            let advisory_ids = advisory_ids.ok_or_else(|| {
+                FrameRAError::QuoteStatusError(format!(
+                    "{} without the advisories, which are required to be in the allowlist",
+                    status
+                ))
+            })?;
+            if let Some(advisory_id) = advisory_ids
+                .iter()
+                .find(|advisory_id| !allowed_advisory_ids.contains(advisory_id))
+            {
+                return Err(FrameRAError::QuoteStatusError(format!(
+                    "{} affected by the disallowed advisory {}",
+                    status, advisory_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for QuoteStatusPolicy {
+    /// Only accepts `OK`, as [strict()](Self::strict).
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+fn comma_separated(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = QuoteStatusPolicy::default();
+        assert_eq!(policy, QuoteStatusPolicy::strict());
+        assert!(policy.verify(QuoteStatus::Ok, None).is_ok());
+        assert!(policy.verify(QuoteStatus::GroupOutOfDate, None).is_err());
+        assert!(policy
+            .verify(QuoteStatus::GroupOutOfDate, Some(&[]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            QuoteStatusPolicy::parse("", "").unwrap(),
+            QuoteStatusPolicy::strict()
+        );
+
+        let policy =
+            QuoteStatusPolicy::parse("GROUP_OUT_OF_DATE, SW_HARDENING_NEEDED", "INTEL-SA-00334,")
+                .unwrap();
+        assert_eq!(
+            policy,
+            QuoteStatusPolicy::new(
+                vec![QuoteStatus::GroupOutOfDate, QuoteStatus::SwHardeningNeeded],
+                Some(vec!["INTEL-SA-00334".to_string()]),
+            )
+            .unwrap()
+        );
+        // Tolerating a status doesn't tolerate any advisories.
+        assert!(QuoteStatusPolicy::parse("GROUP_OUT_OF_DATE", "")
+            .unwrap()
+            .verify(
+                QuoteStatus::GroupOutOfDate,
+                Some(&["INTEL-SA-00334".to_string()])
+            )
+            .is_err());
+        assert!(QuoteStatusPolicy::parse("KEY_REVOKED", "").is_err());
+    }
+
+    #[test]
+    fn test_allowed_advisories() {
+        let policy = QuoteStatusPolicy::new(
+            vec![QuoteStatus::SwHardeningNeeded],
+            Some(vec!["INTEL-SA-00334".to_string()]),
+        )
+        .unwrap();
+        let allowed = vec!["INTEL-SA-00334".to_string()];
+        let disallowed = vec!["INTEL-SA-00334".to_string(), "INTEL-SA-00615".to_string()];
+
+        assert!(policy
+            .verify(QuoteStatus::SwHardeningNeeded, Some(&allowed))
+            .is_ok());
+        assert!(policy
+            .verify(QuoteStatus::SwHardeningNeeded, Some(&disallowed))
+            .is_err());
+        // The advisories can't be checked without the list in the report.
+        assert!(policy.verify(QuoteStatus::SwHardeningNeeded, None).is_err());
+        assert!(QuoteStatusPolicy::strict()
+            .verify(QuoteStatus::SwHardeningNeeded, Some(&allowed))
+            .is_err());
+    }
+
+    #[test]
+    fn test_intolerable_status() {
+        assert!(QuoteStatusPolicy::new(vec![QuoteStatus::KeyRevoked], None).is_err());
+        assert!("UNKNOWN_STATUS".parse::<QuoteStatus>().is_err());
+        assert_eq!(
+            "CONFIGURATION_AND_SW_HARDENING_NEEDED"
+                .parse::<QuoteStatus>()
+                .unwrap(),
+            QuoteStatus::ConfigurationAndSwHardeningNeeded
+        );
+    }
+}
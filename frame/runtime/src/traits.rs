@@ -1,6 +1,7 @@
 use crate::local_anyhow::Result;
 use crate::localstd::{
     fmt::Debug,
+    string::String,
     sync::{SgxRwLockReadGuard, SgxRwLockWriteGuard},
     vec::Vec,
 };
@@ -15,12 +16,13 @@ use frame_common::{
 use frame_mra_tls::key_vault::{
     request::{
         BackupPathSecretRequestBody, BackupPathSecretsRequestBody, RecoverPathSecretsRequestBody,
+        StoreWelcomeRequestBody,
     },
-    response::RecoveredPathSecret,
+    response::{BackedUpEnclaveDecryptionKey, RecoveredPathSecret},
 };
 use frame_sodium::{SodiumCiphertext, SodiumPrivateKey, SodiumPubKey, StoreEnclaveDecryptionKey};
 use frame_treekem::{handshake::HandshakeParams, PathSecret, StorePathSecrets};
-use remote_attestation::EncodedQuote;
+use remote_attestation::{AttestationEvidence, EncodedQuote};
 
 /// Execute state transition functions from runtime
 pub trait RuntimeExecutor<G: ContextOps>: Sized {
@@ -80,8 +82,9 @@ pub trait ConfigGetter {
     fn ias_url(&self) -> &str;
     fn sub_key(&self) -> &str;
     fn spid(&self) -> &str;
+    /// The endpoints of the replicated key-vault nodes, in the order of failover.
     #[cfg(feature = "backup-enable")]
-    fn key_vault_endpoint(&self) -> &str;
+    fn key_vault_endpoints(&self) -> &[String];
     fn store_path_secrets(&self) -> &StorePathSecrets;
     fn store_enclave_dec_key(&self) -> &StoreEnclaveDecryptionKey;
     fn ias_root_cert(&self) -> &[u8];
@@ -150,7 +153,7 @@ pub trait EnclaveKeyOps {
 
     fn enclave_encryption_key(&self) -> Result<SodiumPubKey>;
 
-    fn enclave_decryption_key(&self) -> Result<&SodiumPrivateKey>;
+    fn enclave_decryption_key(&self) -> Result<SodiumPrivateKey>;
 }
 
 pub trait GroupKeyOps: Sized {
@@ -162,6 +165,7 @@ pub trait GroupKeyOps: Sized {
         &mut self,
         store_path_secrets: &StorePathSecrets,
         handshake: &HandshakeParams,
+        state_counter: StateCounter,
         #[cfg(feature = "backup-enable")] recover_path_secret: F,
     ) -> Result<()>;
 
@@ -172,11 +176,15 @@ pub trait GroupKeyOps: Sized {
     /// Ratchet sender's keychain per a transaction
     fn sender_ratchet(&mut self, roster_idx: usize) -> Result<()>;
 
-    /// Ratchet receiver's keychain per a transaction
-    fn receiver_ratchet(&mut self, roster_idx: usize) -> Result<()>;
+    /// Ratchet receiver's keychain of the message's epoch per a transaction
+    fn receiver_ratchet(&mut self, roster_idx: usize, msg_epoch: u32) -> Result<()>;
 
-    /// Syncing the sender and receiver app keychains
-    fn sync_ratchet(&mut self, roster_idx: usize, msg_gen: u32) -> Result<()>;
+    /// Syncing the sender and receiver app keychains of the message's epoch
+    fn sync_ratchet(&mut self, roster_idx: usize, msg_epoch: u32, msg_gen: u32) -> Result<()>;
+
+    /// Discard the keychains retained from the previous epoch
+    /// once they are no longer needed at the given state counter.
+    fn prune_prior_epoch(&mut self, state_counter: StateCounter) -> Result<()>;
 
     fn my_roster_idx(&self) -> u32;
 }
@@ -186,6 +194,11 @@ pub trait QuoteGetter: Sized {
     /// QUOTE will be sent to Attestation Service to verify SGX's status.
     /// For more information: https://api.trustedservices.intel.com/documents/sgx-attestation-api-spec.pdf
     fn quote(&self) -> Result<EncodedQuote>;
+
+    /// Attest the enclave by the provider selected by `ATTESTATION_PROVIDER`,
+    /// whose evidence is verified by the other enclaves.
+    /// The contract only accepts the EPID attestation reports, so joining a group and registering a report still use `quote`.
+    fn attest(&self) -> Result<AttestationEvidence>;
 }
 
 #[cfg(feature = "backup-enable")]
@@ -207,4 +220,10 @@ pub trait KeyVaultOps {
     fn backup_enclave_key(&self) -> Result<()>;
 
     fn recover_enclave_key(&self) -> Result<SodiumPrivateKey>;
+
+    fn list_enclave_key_backups(&self) -> Result<Vec<BackedUpEnclaveDecryptionKey>>;
+
+    fn store_welcome(&self, store_welcome: StoreWelcomeRequestBody) -> Result<()>;
+
+    fn recover_welcome(&self, roster_idx: u32) -> Result<Vec<u8>>;
 }
@@ -227,6 +227,10 @@ impl SodiumPrivateKey {
         SodiumPubKey(self.0.public_key())
     }
 
+    pub fn to_bytes(&self) -> [u8; KEY_SIZE] {
+        self.0.to_bytes()
+    }
+
     #[cfg(feature = "sgx")]
     pub fn try_into_sealing(&self) -> Result<Vec<u8>> {
         UnsealedEnclaveDecryptionKey::from_sodium_priv_key(&self).encoded_sealing()
@@ -390,7 +394,36 @@ impl SodiumCiphertext {
         })
     }
 
-    #[cfg(any(all(feature = "std", test), feature = "sgx"))]
+    /// Encrypt by the sender's static key instead of an ephemeral one,
+    /// so that the recipient can authenticate the sender by [sender_public_key()](Self::sender_public_key).
+    pub fn encrypt_from<R>(
+        csprng: &mut R,
+        my_priv_key: &SodiumPrivateKey,
+        others_pub_key: &SodiumPubKey,
+        plaintext: &[u8],
+    ) -> Result<Self>
+    where
+        R: RngCore + CryptoRng,
+    {
+        let nonce = SodiumNonce::from_random(csprng)?;
+
+        let cbox = CryptoBox::new(&others_pub_key.0, &my_priv_key.0);
+        let ciphertext = cbox
+            .encrypt(&nonce.0, plaintext)
+            .map_err(|e| anyhow!("Failed to encrypt :{:?}", e))?;
+
+        Ok(SodiumCiphertext {
+            ephemeral_public_key: my_priv_key.public_key(),
+            ciphertext,
+            nonce,
+        })
+    }
+
+    /// The public key of the sender, which is ephemeral unless encrypted by [encrypt_from()](Self::encrypt_from).
+    pub fn sender_public_key(&self) -> &SodiumPubKey {
+        &self.ephemeral_public_key
+    }
+
     pub fn decrypt(&self, my_priv_key: &SodiumPrivateKey) -> Result<Vec<u8>> {
         let cbox = CryptoBox::new(&self.ephemeral_public_key.0, &my_priv_key.0);
         let plaintext = cbox
@@ -407,6 +440,35 @@ impl SodiumCiphertext {
     pub fn decode(bytes: &[u8]) -> crate::localstd::result::Result<Self, Box<bincode::ErrorKind>> {
         bincode::deserialize(&bytes[..])
     }
+
+    /// Concatenate the raw ephemeral public key, nonce and ciphertext.
+    /// It is half the size of `encode()`, which hex-encodes them, so it suits the large plaintexts.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(SODIUM_PUBLIC_KEY_SIZE + NONCE_SIZE + self.ciphertext.len());
+        bytes.extend_from_slice(&self.ephemeral_public_key.to_bytes());
+        bytes.extend_from_slice(self.nonce.as_slice());
+        bytes.extend_from_slice(&self.ciphertext[..]);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < SODIUM_PUBLIC_KEY_SIZE + NONCE_SIZE {
+            return Err(anyhow!(
+                "SodiumCiphertext must be at least {} bytes, got {}",
+                SODIUM_PUBLIC_KEY_SIZE + NONCE_SIZE,
+                bytes.len()
+            ));
+        }
+        let (ephemeral_public_key, rest) = bytes.split_at(SODIUM_PUBLIC_KEY_SIZE);
+        let (nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+
+        Ok(SodiumCiphertext {
+            ephemeral_public_key: SodiumPubKey::from_bytes(ephemeral_public_key)?,
+            nonce: SodiumNonce::from_bytes(nonce),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
 }
 
 fn from_hex_vec<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
@@ -444,6 +506,50 @@ pub(crate) mod tests {
         assert_eq!(plaintext, &msg[..]);
     }
 
+    #[test]
+    fn test_sodium_from_sender() {
+        let mut rng = rand::thread_rng();
+
+        let sk_server = SodiumPrivateKey::from_random(&mut rng).unwrap();
+        let sk_client = SodiumPrivateKey::from_random(&mut rng).unwrap();
+
+        let msg = b"This is a test message";
+        let ciphertext =
+            SodiumCiphertext::encrypt_from(&mut rng, &sk_server, &sk_client.public_key(), msg)
+                .unwrap();
+        assert_eq!(ciphertext.sender_public_key(), &sk_server.public_key());
+
+        let plaintext = ciphertext.decrypt(&sk_client).unwrap();
+        assert_eq!(plaintext, &msg[..]);
+    }
+
+    #[test]
+    fn test_ciphertext_bytes() {
+        let mut rng = rand::thread_rng();
+        let sk = SodiumPrivateKey::from_random(&mut rng).unwrap();
+
+        let msg = b"This is a test message";
+        let ciphertext = SodiumCiphertext::encrypt(&mut rng, &sk.public_key(), msg).unwrap();
+        let bytes = ciphertext.to_bytes();
+        assert!(bytes.len() < ciphertext.encode().len());
+
+        let recovered = SodiumCiphertext::from_bytes(&bytes).unwrap();
+        assert_eq!(recovered, ciphertext);
+        assert_eq!(recovered.decrypt(&sk).unwrap(), &msg[..]);
+        assert!(SodiumCiphertext::from_bytes(&bytes[..SODIUM_PUBLIC_KEY_SIZE]).is_err());
+    }
+
+    #[test]
+    fn test_decode_legacy_ciphertext() {
+        // Encoded by the ciphertext format already stored in the on-chain logs, which must keep decoding.
+        let legacy = hex::decode("40000000000000006635373461623837333937643833663639336465626563336530353032393032393833636535373533306238663533346333326430356633376239333433333630000000000000006132653035356439396539343364366435383265616365353138613165623965616462653939616433346138303763382c000000000000003365343233346561646535666566316232333762643331313466323664376362343736383935373034323233").unwrap();
+        let sk = SodiumPrivateKey::from_bytes(&[7u8; 32]).unwrap();
+
+        let ciphertext = SodiumCiphertext::decode(&legacy[..]).unwrap();
+        assert_eq!(ciphertext.decrypt(&sk).unwrap(), b"legacy");
+        assert_eq!(ciphertext.encode(), legacy);
+    }
+
     #[test]
     fn test_nonce_serde() {
         let mut rng = rand::thread_rng();
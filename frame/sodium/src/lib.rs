@@ -36,6 +36,7 @@ mod crypto;
 pub mod rng;
 #[cfg(feature = "sgx")]
 pub mod sealing;
+pub mod secret_sharing;
 #[cfg(feature = "sgx")]
 pub mod store_dec_key;
 
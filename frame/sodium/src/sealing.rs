@@ -38,6 +38,37 @@ impl UnsealedEnclaveDecryptionKey {
 
 unsafe impl sgx_types::marker::ContiguousMemory for UnsealedEnclaveDecryptionKey {}
 
+/// Seal a secret of any length, e.g. a symmetric key, to the enclave.
+pub fn seal_secret(secret: &[u8]) -> Result<Vec<u8>> {
+    let additional = [0u8; 0];
+    let sealed_data = SgxSealedData::<[u8]>::seal_data(&additional, secret)
+        .map_err(|e| anyhow!("error: {:?}", e))?;
+
+    let size = SgxSealedData::<[u8]>::calc_raw_sealed_data_size(0, secret.len() as u32);
+    let mut bytes = vec![0u8; size as usize];
+    unsafe { sealed_data.to_raw_sealed_data_t(bytes.as_mut_ptr() as *mut sgx_sealed_data_t, size) }
+        .ok_or_else(|| anyhow!("Failed to encode the sealed secret"))?;
+
+    Ok(bytes)
+}
+
+/// Unseal a secret sealed by `seal_secret`.
+pub fn unseal_secret(sealed: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = sealed.to_vec();
+    let sealed_data = unsafe {
+        SgxSealedData::<[u8]>::from_raw_sealed_data_t(
+            bytes.as_mut_ptr() as *mut sgx_sealed_data_t,
+            bytes.len() as u32,
+        )
+    }
+    .ok_or_else(|| anyhow!("Failed to decode the sealed secret"))?;
+    let unsealed_data = sealed_data
+        .unseal_data()
+        .map_err(|e| anyhow!("error: {:?}", e))?;
+
+    Ok(unsealed_data.get_decrypt_txt().to_vec())
+}
+
 #[derive(Default, Clone)]
 pub struct SealedEnclaveDecryptionKey<'a>(SgxSealedData<'a, UnsealedEnclaveDecryptionKey>);
 
@@ -0,0 +1,371 @@
+use crate::local_anyhow::{anyhow, Result};
+use crate::localstd::vec::Vec;
+use crate::rand_core::{CryptoRng, RngCore};
+use frame_common::traits::Keccak256;
+
+/// The maximum number of shares, since the x coordinates are the non-zero elements of GF(2^8).
+pub const MAX_SHARES: usize = 255;
+/// The size of the commitment stored with each share.
+pub const SHARE_COMMITMENT_SIZE: usize = 32;
+
+/// A share of Shamir's k-of-n secret sharing over GF(2^8).
+/// Each byte of the secret is shared by its own random polynomial of degree k-1,
+/// so the share has the same length as the secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretShare {
+    x: u8,
+    y: Vec<u8>,
+}
+
+impl SecretShare {
+    pub fn new(x: u8, y: Vec<u8>) -> Self {
+        SecretShare { x, y }
+    }
+
+    /// The x coordinate at which the polynomials are evaluated. It is never zero.
+    pub fn x(&self) -> u8 {
+        self.x
+    }
+
+    pub fn y(&self) -> &[u8] {
+        &self.y[..]
+    }
+}
+
+/// Split the secret into `share_count` shares, any `threshold` of which reconstruct it.
+/// The i-th share is evaluated at x = i + 1.
+pub fn split_secret<R>(
+    secret: &[u8],
+    threshold: usize,
+    share_count: usize,
+    rng: &mut R,
+) -> Result<Vec<SecretShare>>
+where
+    R: RngCore + CryptoRng,
+{
+    if threshold == 0 || threshold > share_count || share_count > MAX_SHARES {
+        return Err(anyhow!(
+            "Invalid secret sharing parameters: threshold {}, share count {}",
+            threshold,
+            share_count
+        ));
+    }
+
+    let mut shares: Vec<SecretShare> = (1..=share_count)
+        .map(|x| SecretShare::new(x as u8, Vec::with_capacity(secret.len())))
+        .collect();
+    let mut coefficients = vec![0u8; threshold];
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng.try_fill_bytes(&mut coefficients[1..])
+            .map_err(|e| anyhow!("Failed to generate coefficients: {:?}", e))?;
+        for share in shares.iter_mut() {
+            share.y.push(evaluate(&coefficients, share.x));
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Reconstruct the secret from the shares by the Lagrange interpolation at x = 0.
+/// The result is meaningless unless at least the threshold number of shares are given.
+pub fn combine_shares(shares: &[SecretShare]) -> Result<Vec<u8>> {
+    let len = shares
+        .first()
+        .ok_or_else(|| anyhow!("No shares are given"))?
+        .y
+        .len();
+    for (i, share) in shares.iter().enumerate() {
+        if share.x == 0 {
+            return Err(anyhow!("The x coordinate of a share must not be zero"));
+        }
+        if share.y.len() != len {
+            return Err(anyhow!("All shares must have the same length"));
+        }
+        if shares[..i].iter().any(|other| other.x == share.x) {
+            return Err(anyhow!("Duplicated share: x = {}", share.x));
+        }
+    }
+
+    let basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares
+                .iter()
+                .filter(|other| other.x != share.x)
+                .fold(1u8, |acc, other| {
+                    gf_mul(acc, gf_mul(other.x, gf_inv(other.x ^ share.x)))
+                })
+        })
+        .collect();
+
+    let secret = (0..len)
+        .map(|i| {
+            shares
+                .iter()
+                .zip(basis.iter())
+                .fold(0u8, |acc, (share, &l)| acc ^ gf_mul(share.y[i], l))
+        })
+        .collect();
+
+    Ok(secret)
+}
+
+/// A share stored with the version of the secret and the commitment to it,
+/// so that the combined secret can be checked even if a key-vault node returns a corrupt or stale share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedShare {
+    share: SecretShare,
+    version: u32,
+    commitment: Vec<u8>,
+}
+
+impl CommittedShare {
+    pub fn new(share: SecretShare, version: u32, commitment: Vec<u8>) -> Self {
+        CommittedShare {
+            share,
+            version,
+            commitment,
+        }
+    }
+
+    pub fn share(&self) -> &SecretShare {
+        &self.share
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn commitment(&self) -> &[u8] {
+        &self.commitment[..]
+    }
+}
+
+/// The commitment to the version of the secret and the secret itself.
+/// The secrets shared are uniformly random keys, so the hash doesn't tell anything about them.
+pub fn commit_secret(secret: &[u8], version: u32) -> [u8; SHARE_COMMITMENT_SIZE] {
+    let mut preimage = Vec::with_capacity(4 + secret.len());
+    preimage.extend_from_slice(&version.to_le_bytes());
+    preimage.extend_from_slice(secret);
+    preimage[..].keccak256()
+}
+
+/// Split the secret as `split_secret` does, and attach the version and the commitment to each share.
+pub fn split_committed_secret<R>(
+    secret: &[u8],
+    version: u32,
+    threshold: usize,
+    share_count: usize,
+    rng: &mut R,
+) -> Result<Vec<CommittedShare>>
+where
+    R: RngCore + CryptoRng,
+{
+    let commitment = commit_secret(secret, version).to_vec();
+    Ok(split_secret(secret, threshold, share_count, rng)?
+        .into_iter()
+        .map(|share| CommittedShare::new(share, version, commitment.clone()))
+        .collect())
+}
+
+/// Reconstruct the secret from the shares of the same version, and check it against their commitment.
+/// It fails if the shares are of different versions, or if fewer than the threshold or corrupt shares are given.
+pub fn combine_committed_shares(shares: &[CommittedShare]) -> Result<Vec<u8>> {
+    let first = shares
+        .first()
+        .ok_or_else(|| anyhow!("No shares are given"))?;
+    if first.commitment.len() != SHARE_COMMITMENT_SIZE {
+        return Err(anyhow!(
+            "The share has no commitment: x = {}",
+            first.share.x
+        ));
+    }
+    if let Some(other) = shares
+        .iter()
+        .find(|other| other.version != first.version || other.commitment != first.commitment)
+    {
+        return Err(anyhow!(
+            "The shares are of different versions: {} (x = {}) and {} (x = {})",
+            first.version,
+            first.share.x,
+            other.version,
+            other.share.x
+        ));
+    }
+
+    let secret = combine_shares(
+        &shares
+            .iter()
+            .map(|committed| committed.share.clone())
+            .collect::<Vec<_>>(),
+    )?;
+    if commit_secret(&secret, first.version)[..] != first.commitment[..] {
+        return Err(anyhow!(
+            "The combined secret of version {} doesn't match the commitment. Some shares are missing or corrupt",
+            first.version
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// Evaluate the polynomial by Horner's method.
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &coefficient| gf_mul(acc, x) ^ coefficient)
+}
+
+/// Multiplication in GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1.
+/// It doesn't branch on the operands so as not to leak the secret through timing.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(a >> 7));
+        b >>= 1;
+    }
+    product
+}
+
+/// The multiplicative inverse in GF(2^8), computed as a^254.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    for bit in 0..8 {
+        if (254u8 >> bit) & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+    }
+    result
+}
+
+#[cfg(test)]
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_inv() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_combine_any_threshold_shares() {
+        let mut rng = rand::thread_rng();
+        let secret = b"This is a test secret".to_vec();
+        let shares = split_secret(&secret, 3, 5, &mut rng).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for i in 0..5 {
+            for j in (i + 1)..5 {
+                for k in (j + 1)..5 {
+                    let subset = vec![shares[i].clone(), shares[j].clone(), shares[k].clone()];
+                    assert_eq!(combine_shares(&subset).unwrap(), secret);
+                }
+            }
+        }
+        assert_eq!(combine_shares(&shares).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_combine_fewer_shares() {
+        let mut rng = rand::thread_rng();
+        let secret = [0xffu8; 32];
+        let shares = split_secret(&secret, 3, 5, &mut rng).unwrap();
+
+        assert_ne!(combine_shares(&shares[..2]).unwrap(), secret.to_vec());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let mut rng = rand::thread_rng();
+        assert!(split_secret(b"secret", 0, 3, &mut rng).is_err());
+        assert!(split_secret(b"secret", 4, 3, &mut rng).is_err());
+        assert!(split_secret(b"secret", 2, 256, &mut rng).is_err());
+
+        let shares = split_secret(b"secret", 2, 3, &mut rng).unwrap();
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(combine_shares(&duplicated).is_err());
+    }
+
+    #[test]
+    fn test_combine_committed_threshold_shares() {
+        let mut rng = rand::thread_rng();
+        let secret = [0x5au8; 32];
+        let shares = split_committed_secret(&secret, 7, 3, 5, &mut rng).unwrap();
+
+        assert_eq!(
+            combine_committed_shares(&shares[..3]).unwrap(),
+            secret.to_vec()
+        );
+        assert_eq!(
+            combine_committed_shares(&shares[2..]).unwrap(),
+            secret.to_vec()
+        );
+        assert_eq!(combine_committed_shares(&shares).unwrap(), secret.to_vec());
+    }
+
+    #[test]
+    fn test_combine_committed_missing_shares() {
+        let mut rng = rand::thread_rng();
+        let secret = [0x5au8; 32];
+        let shares = split_committed_secret(&secret, 7, 3, 5, &mut rng).unwrap();
+
+        assert!(combine_committed_shares(&shares[..2]).is_err());
+        assert!(combine_committed_shares(&[]).is_err());
+    }
+
+    #[test]
+    fn test_combine_committed_corrupt_share() {
+        let mut rng = rand::thread_rng();
+        let secret = [0x5au8; 32];
+        let mut shares = split_committed_secret(&secret, 7, 3, 5, &mut rng).unwrap();
+        let mut corrupt = shares[1].share().y().to_vec();
+        corrupt[0] ^= 1;
+        shares[1] = CommittedShare::new(
+            SecretShare::new(shares[1].share().x(), corrupt),
+            shares[1].version(),
+            shares[1].commitment().to_vec(),
+        );
+
+        assert!(combine_committed_shares(&shares[..3]).is_err());
+        assert_eq!(
+            combine_committed_shares(&shares[2..]).unwrap(),
+            secret.to_vec()
+        );
+
+        let uncommitted: Vec<CommittedShare> = shares[2..]
+            .iter()
+            .map(|share| CommittedShare::new(share.share().clone(), share.version(), vec![]))
+            .collect();
+        assert!(combine_committed_shares(&uncommitted).is_err());
+    }
+
+    #[test]
+    fn test_combine_committed_mixed_version_shares() {
+        let mut rng = rand::thread_rng();
+        let old_shares = split_committed_secret(&[0x11u8; 32], 1, 2, 3, &mut rng).unwrap();
+        let new_shares = split_committed_secret(&[0x22u8; 32], 2, 2, 3, &mut rng).unwrap();
+
+        let mixed = vec![old_shares[0].clone(), new_shares[1].clone()];
+        assert!(combine_committed_shares(&mixed).is_err());
+
+        // A stale share relabeled with the new version doesn't match the new commitment either.
+        let relabeled = CommittedShare::new(
+            old_shares[0].share().clone(),
+            new_shares[0].version(),
+            new_shares[0].commitment().to_vec(),
+        );
+        assert!(combine_committed_shares(&[relabeled, new_shares[1].clone()]).is_err());
+        assert_eq!(
+            combine_committed_shares(&new_shares[..2]).unwrap(),
+            vec![0x22u8; 32]
+        );
+    }
+}
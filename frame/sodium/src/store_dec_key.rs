@@ -3,9 +3,12 @@ use crate::localstd::{
     fs,
     io::{Read, Write},
     path::{Path, PathBuf},
-    string::String,
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::sealing::{
+    seal_secret, unseal_secret, SealedEnclaveDecryptionKey, UnsealedEnclaveDecryptionKey,
 };
-use crate::sealing::{SealedEnclaveDecryptionKey, UnsealedEnclaveDecryptionKey};
 use frame_config::PJ_ROOT_DIR;
 use serde_json_sgx as serde_json;
 use tracing::info;
@@ -22,10 +25,32 @@ impl StoreEnclaveDecryptionKey {
         StoreEnclaveDecryptionKey { local_dir_path }
     }
 
+    pub fn create_dir_all<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        self.local_dir_path.push(path);
+        fs::create_dir_all(&self.local_dir_path)?;
+        Ok(self)
+    }
+
     pub fn local_dir_path(&self) -> &Path {
         &self.local_dir_path
     }
 
+    /// Returns the names of all files stored in the directory.
+    pub fn get_all_file_names(&self) -> Result<Vec<String>> {
+        let file_names = fs::read_dir(&self.local_dir_path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(ToString::to_string)
+            })
+            .collect();
+
+        Ok(file_names)
+    }
+
     pub fn save_to_local_filesystem<P: AsRef<Path>>(
         &self,
         sealed_dec_key: &SealedEnclaveDecryptionKey<'_>,
@@ -65,4 +90,41 @@ impl StoreEnclaveDecryptionKey {
         // so, it's unsealed here.
         sealed_dec_key.unsealing()
     }
+
+    pub fn remove_from_local_filesystem<P: AsRef<Path>>(&self, file_name: P) -> Result<()> {
+        let file_path = self.local_dir_path.join(file_name);
+        info!("Removing the file of the path: {:?}", file_path);
+        fs::remove_file(file_path)?;
+
+        Ok(())
+    }
+
+    /// Seal the secret other than the enclave decryption key, e.g. a symmetric key, and save it.
+    pub fn save_secret_to_local_filesystem<P: AsRef<Path>>(
+        &self,
+        secret: &[u8],
+        file_name: P,
+    ) -> Result<()> {
+        let file_path = self.local_dir_path.join(file_name);
+        info!("Saving a sealed secret to the path: {:?}", file_path);
+        let sealed = seal_secret(secret)?;
+        let mut file = fs::File::create(file_path)?;
+        file.write_all(&sealed)?;
+        file.flush()?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    pub fn load_secret_from_local_filesystem<P: AsRef<Path>>(
+        &self,
+        file_name: P,
+    ) -> Result<Vec<u8>> {
+        let file_path = self.local_dir_path.join(file_name);
+        info!("Loading a sealed secret from the path: {:?}", file_path);
+        let mut sealed = vec![];
+        fs::File::open(file_path)?.read_to_end(&mut sealed)?;
+
+        unseal_secret(&sealed)
+    }
 }
@@ -51,7 +51,7 @@ impl AppKeyChain {
     ) -> Result<Option<Vec<u8>>> {
         match group_state.my_node()? {
             // If current my node contains a DhKeypair, cannot decrypt message because you haven't join the group.
-            RatchetTreeNode::Blank => {
+            None | Some(RatchetTreeNode::Blank) => {
                 warn!("The received message is ignored because your enclave hasn't join the group yet");
                 Ok(None)
             }
@@ -82,11 +82,8 @@ impl AppKeyChain {
     pub(crate) fn from_app_secret(group_state: &GroupState, app_secret: AppSecret) -> Self {
         let roster_len = match group_state.epoch() {
             0 => 1, // At the very first epoch, roster length should not be considered empty.
-            _ => {
-                u32::try_from(group_state.roster_len().expect("Invalid roster length"))
-                    .expect("roster length exceeds u32::MAX")
-                    + 1
-            }
+            _ => u32::try_from(group_state.roster_len().expect("Invalid roster length"))
+                .expect("roster length exceeds u32::MAX"),
         };
         let prk = HmacKey::from(app_secret);
 
@@ -133,6 +130,15 @@ impl AppKeyChain {
         Ok(())
     }
 
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// The number of members whose secrets are held in this key chain.
+    pub fn roster_len(&self) -> usize {
+        self.member_secrets_and_gens.len()
+    }
+
     pub fn generation(&self, roster_idx: usize) -> Result<u32> {
         let (_, gen) =
             self.member_secrets_and_gens
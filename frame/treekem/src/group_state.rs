@@ -4,15 +4,20 @@ use crate::handshake::{AccessKey, Handshake, HandshakeParams, PathSecretSource};
 use crate::ratchet_tree::{RatchetTree, RatchetTreeNode};
 use crate::store_path_secrets::StorePathSecrets;
 use crate::tree_math;
+use crate::welcome::Welcome;
 use anyhow::{anyhow, ensure, Result};
 use frame_common::crypto::ExportPathSecret;
 use frame_mra_tls::{
     key_vault::response::RecoveredPathSecret, AttestedTlsConfig, Client, ClientConfig,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{env, vec::Vec};
 
-#[derive(Clone, Debug, Serialize)]
+/// The protocol-level maximum number of members in a group.
+/// The ratchet tree grows with add handshakes up to this size.
+pub const MAX_ROSTER_SIZE: u32 = 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GroupState {
     /// The current version of the group key
     epoch: u32,
@@ -30,23 +35,8 @@ pub struct GroupState {
 
 impl Handshake for GroupState {
     fn create_handshake(&self, source: &PathSecretSource) -> Result<(HandshakeParams, PathSecret)> {
-        let my_roster_idx = self.my_roster_idx;
-        let my_tree_idx = RatchetTree::roster_idx_to_tree_idx(my_roster_idx)?;
-
-        let path_secret = Self::request_new_path_secret(source, my_roster_idx, self.epoch)?;
-        let mut new_group_state = self.clone();
-
-        if my_tree_idx == self.tree.size() {
-            new_group_state.tree.add_leaf_node(RatchetTreeNode::Blank);
-            new_group_state.tree.propagate_blank(my_roster_idx as usize);
-        }
-
-        let _ = new_group_state.set_new_path_secret(path_secret.clone(), my_tree_idx)?;
-        let direct_path_msg = new_group_state
-            .tree
-            .encrypt_direct_path_secret(my_tree_idx, path_secret.clone())?;
-
-        let handshake = HandshakeParams::new(self.epoch, my_roster_idx, direct_path_msg);
+        let path_secret = Self::request_new_path_secret(source, self.my_roster_idx, self.epoch)?;
+        let handshake = self.create_handshake_with(self.my_roster_idx, path_secret.clone())?;
 
         Ok((handshake, path_secret))
     }
@@ -58,7 +48,6 @@ impl Handshake for GroupState {
         store_path_secrets: &StorePathSecrets,
         handshake: &HandshakeParams,
         source: &PathSecretSource,
-        max_roster_idx: u32,
         #[cfg(feature = "backup-enable")] recover_path_secret_from_key_vault: F,
     ) -> Result<AppKeyChain> {
         ensure!(
@@ -67,22 +56,19 @@ impl Handshake for GroupState {
             handshake.prior_epoch(),
             self.epoch
         );
+        ensure!(
+            handshake.roster_idx() < MAX_ROSTER_SIZE,
+            "Handshake's roster index ({:?}) exceeds the maximum group size ({:?})",
+            handshake.roster_idx(),
+            MAX_ROSTER_SIZE
+        );
         let sender_tree_idx = RatchetTree::roster_idx_to_tree_idx(handshake.roster_idx())?;
-        ensure!(sender_tree_idx <= self.tree.size(), "Invalid tree index");
-
         let my_tree_idx = RatchetTree::roster_idx_to_tree_idx(self.my_roster_idx)?;
-        let max_tree_size = RatchetTree::roster_idx_to_tree_idx(max_roster_idx)?;
 
-        // If sender_tree_size equals to the current tree size, the handshake contains an add operation.
-        if sender_tree_idx == self.tree.size() {
-            // This is just special operation for the very first epoch.
-            if self.tree.is_empty() {
-                self.tree.add_leaf_node(RatchetTreeNode::Blank);
-            }
-            for _ in 0..=(max_tree_size - self.tree.size()) / 2 {
-                self.tree.add_leaf_node(RatchetTreeNode::Blank);
-                self.tree.propagate_blank(sender_tree_idx);
-            }
+        // If the sender's leaf isn't in the tree yet, the handshake contains an add operation,
+        // so the tree grows just enough to include it.
+        if self.tree.grow_to_include(sender_tree_idx) {
+            self.tree.propagate_blank(sender_tree_idx);
         }
 
         let mut my_path_secret: Option<PathSecret> = None;
@@ -142,6 +128,7 @@ impl Handshake for GroupState {
             common_ancestor,
             direct_path_pub_keys.clone(),
         )?;
+        self.tree.truncate();
         self.increment_epoch()?;
 
         let app_secret = self.update_epoch_secret(&update_secret)?;
@@ -179,6 +166,107 @@ impl GroupState {
         })
     }
 
+    /// Create an add handshake on behalf of a new member and a welcome message for it.
+    /// The joiner's leaf path secret is derived here, so the joiner should send an update handshake
+    /// after joining to get rid of the secret known by the welcoming member.
+    pub fn create_welcome(
+        &self,
+        joiner_roster_idx: u32,
+        source: &PathSecretSource,
+    ) -> Result<(HandshakeParams, Welcome)> {
+        ensure!(
+            joiner_roster_idx != self.my_roster_idx,
+            "Cannot welcome myself (roster index: {:?})",
+            joiner_roster_idx
+        );
+        let joiner_tree_idx = RatchetTree::roster_idx_to_tree_idx(joiner_roster_idx)?;
+        if let Some(RatchetTreeNode::Filled { .. }) = self.tree.get(joiner_tree_idx) {
+            return Err(anyhow!(
+                "The member (roster index: {:?}) has already joined the group",
+                joiner_roster_idx
+            ));
+        }
+
+        let path_secret = Self::request_new_path_secret(source, joiner_roster_idx, self.epoch)?;
+        let handshake = self.create_handshake_with(joiner_roster_idx, path_secret.clone())?;
+        let group_state = GroupState {
+            epoch: self.epoch,
+            my_roster_idx: joiner_roster_idx,
+            tree: self.tree.without_private_keys(),
+            init_secret: HmacKey::default(),
+        };
+        let welcome = Welcome::new(group_state, handshake.clone(), &path_secret);
+
+        Ok((handshake, welcome))
+    }
+
+    /// Start from the group state in the welcome message instead of replaying previous handshakes.
+    /// The path secret is stored locally, so the add handshake is processed as my own when it's received.
+    pub fn from_welcome(
+        my_roster_idx: u32,
+        welcome: Welcome,
+        store_path_secrets: &StorePathSecrets,
+    ) -> Result<Self> {
+        let (mut group_state, handshake, path_secret) = welcome.into_parts();
+        ensure!(
+            handshake.roster_idx() == my_roster_idx,
+            "Welcome's roster index ({:?}) isn't my roster index ({:?})",
+            handshake.roster_idx(),
+            my_roster_idx
+        );
+        ensure!(
+            handshake.prior_epoch() == group_state.epoch,
+            "Welcome's handshake prior epoch ({:?}) isn't its group state's epoch ({:?})",
+            handshake.prior_epoch(),
+            group_state.epoch
+        );
+        let (leaf_pub_key, _, _, _) = path_secret.clone().derive_node_values()?;
+        ensure!(
+            handshake.path().node_msgs.first().map(|m| &m.public_key) == Some(&leaf_pub_key),
+            "Welcome's handshake isn't derived from its path secret"
+        );
+
+        let eps = path_secret.try_into_exporting(group_state.epoch, handshake.hash().as_ref())?;
+        store_path_secrets.save_to_local_filesystem(&eps)?;
+
+        group_state.my_roster_idx = my_roster_idx;
+        group_state.init_secret = HmacKey::default();
+
+        Ok(group_state)
+    }
+
+    /// Create a handshake from the provided leaf path secret.
+    /// If the leaf isn't in the tree yet, the handshake contains an add operation.
+    fn create_handshake_with(
+        &self,
+        roster_idx: u32,
+        path_secret: PathSecret,
+    ) -> Result<HandshakeParams> {
+        ensure!(
+            roster_idx < MAX_ROSTER_SIZE,
+            "Roster index ({:?}) exceeds the maximum group size ({:?})",
+            roster_idx,
+            MAX_ROSTER_SIZE
+        );
+        let tree_idx = RatchetTree::roster_idx_to_tree_idx(roster_idx)?;
+        let mut new_group_state = self.clone();
+
+        if new_group_state.tree.grow_to_include(tree_idx) {
+            new_group_state.tree.propagate_blank(tree_idx);
+        }
+
+        let _ = new_group_state.set_new_path_secret(path_secret.clone(), tree_idx)?;
+        let direct_path_msg = new_group_state
+            .tree
+            .encrypt_direct_path_secret(tree_idx, path_secret)?;
+
+        Ok(HandshakeParams::new(
+            self.epoch,
+            roster_idx,
+            direct_path_msg,
+        ))
+    }
+
     /// Request own new path secret to external key vault
     pub fn request_new_path_secret(
         source: &PathSecretSource,
@@ -217,15 +305,17 @@ impl GroupState {
             // More precisely, the member hasn't send an add handshake yet.
             // Otherwise, the handshake is an update operation,
             // so decrypt direct path message using based on current group state.
-            match self.tree.get(my_tree_idx).unwrap() {
-                RatchetTreeNode::Blank => {
-                    let num_leaves = tree_math::num_leaves_in_tree(self.tree.size());
+            let num_leaves = tree_math::num_leaves_in_tree(self.tree.size());
+            match self.tree.get(my_tree_idx) {
+                Some(RatchetTreeNode::Blank) => {
                     let common_ancestor =
                         tree_math::common_ancestor(sender_tree_idx, my_tree_idx, num_leaves);
 
                     Ok((UpdateSecret::default(), common_ancestor))
                 }
-                _ => {
+                // My leaf is out of the tree because the group hasn't grown up to my roster index yet.
+                None => Ok((UpdateSecret::default(), tree_math::root_idx(num_leaves))),
+                Some(_) => {
                     let (path_secret, common_ancestor) = self.tree.decrypt_direct_path_msg(
                         &handshake.path(),
                         sender_tree_idx,
@@ -270,24 +360,33 @@ impl GroupState {
         Ok(app_secret.into())
     }
 
-    pub(crate) fn my_node(&self) -> Result<&RatchetTreeNode> {
+    /// Returns my leaf node, or `None` if the tree hasn't grown up to my roster index yet.
+    pub(crate) fn my_node(&self) -> Result<Option<&RatchetTreeNode>> {
         let my_tree_idx = RatchetTree::roster_idx_to_tree_idx(self.my_roster_idx)?;
-        self.tree
-            .get(my_tree_idx)
-            .ok_or_else(|| anyhow!("Not found my node"))
+        Ok(self.tree.get(my_tree_idx))
     }
 
+    /// The number of leaves in the ratchet tree, which isn't necessarily a power of two.
     pub(crate) fn roster_len(&self) -> Result<usize> {
-        let tree_size = self.tree.size();
-        tree_size
-            .checked_div(2)
-            .ok_or_else(|| anyhow!("Invalid tree size."))
+        ensure!(!self.tree.is_empty(), "The ratchet tree is empty.");
+        Ok(tree_math::num_leaves_in_tree(self.tree.size()))
     }
 
     pub fn epoch(&self) -> u32 {
         self.epoch
     }
 
+    /// Returns whether each leaf of the ratchet tree is filled, in the order of roster indices.
+    pub fn occupied_leaves(&self) -> Vec<bool> {
+        (0..self.tree.size())
+            .step_by(2)
+            .map(|tree_idx| match self.tree.get(tree_idx) {
+                Some(RatchetTreeNode::Filled { .. }) => true,
+                _ => false,
+            })
+            .collect()
+    }
+
     pub fn my_roster_idx(&self) -> u32 {
         self.my_roster_idx
     }
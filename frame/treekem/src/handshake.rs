@@ -23,7 +23,6 @@ pub trait Handshake: Sized {
         store_path_secrets: &StorePathSecrets,
         handshake: &HandshakeParams,
         source: &PathSecretSource,
-        max_roster_idx: u32,
         #[cfg(feature = "backup-enable")] recover_path_secret_from_key_vault: F,
     ) -> Result<AppKeyChain>;
 }
@@ -10,6 +10,7 @@ mod group_state;
 pub mod handshake;
 mod ratchet_tree;
 mod tree_math;
+mod welcome;
 // #[cfg(debug_assertions)]
 mod store_path_secrets;
 mod test_funcs;
@@ -17,8 +18,9 @@ mod test_funcs;
 pub use crate::application::AppKeyChain;
 pub use crate::crypto::secrets::SealedPathSecret;
 pub use crate::crypto::secrets::{PathSecret, UnsealedPathSecret};
-pub use crate::group_state::GroupState;
+pub use crate::group_state::{GroupState, MAX_ROSTER_SIZE};
 pub use crate::handshake::Handshake;
+pub use crate::welcome::Welcome;
 pub use crate::test_funcs::init_path_secret_kvs;
 pub use store_path_secrets::StorePathSecrets;
 
@@ -33,6 +35,7 @@ pub mod tests {
             application::tests::run_tests(),
             crypto::ecies::tests::run_tests(),
             crypto::secrets::tests::run_tests(),
+            tree_math::tests::run_tests(),
         )
     }
 }
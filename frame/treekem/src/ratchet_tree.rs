@@ -8,10 +8,10 @@ use crate::{
     tree_math,
 };
 use anyhow::{anyhow, ensure, Result};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::vec::Vec;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RatchetTree {
     nodes: Vec<RatchetTreeNode>,
 }
@@ -25,6 +25,22 @@ impl RatchetTree {
         RatchetTree { nodes: vec![] }
     }
 
+    /// Returns a copy of the tree which contains only public keys.
+    pub fn without_private_keys(&self) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                RatchetTreeNode::Blank => RatchetTreeNode::Blank,
+                RatchetTreeNode::Filled { public_key, .. } => RatchetTreeNode::Filled {
+                    public_key: public_key.clone(),
+                    private_key: None,
+                },
+            })
+            .collect();
+        RatchetTree { nodes }
+    }
+
     /// Set my leaf node derived from path secret to the provided tree index.
     #[allow(dead_code)]
     pub fn init_path_secret_idx(path_secret: PathSecret, my_tree_idx: usize) -> Result<Self> {
@@ -162,6 +178,32 @@ impl RatchetTree {
         }
     }
 
+    /// Add blank leaves until the tree contains the leaf at `leaf_idx`.
+    /// Returns whether the tree has grown.
+    pub fn grow_to_include(&mut self, leaf_idx: usize) -> bool {
+        let mut grown = false;
+        while self.size() <= leaf_idx {
+            self.add_leaf_node(RatchetTreeNode::Blank);
+            grown = true;
+        }
+
+        grown
+    }
+
+    /// Remove the trailing blank leaves along with their parent nodes,
+    /// so the tree is no larger than needed for the rightmost member.
+    pub fn truncate(&mut self) {
+        while self.size() > 1 {
+            match self.nodes.last() {
+                Some(RatchetTreeNode::Blank) => {
+                    let new_size = self.size() - 2;
+                    self.nodes.truncate(new_size);
+                }
+                _ => break,
+            }
+        }
+    }
+
     pub fn propagate_blank(&mut self, leaf_idx: usize) {
         let num_leaves = tree_math::num_leaves_in_tree(self.size());
         let direct_path = tree_math::node_extended_direct_path(leaf_idx, num_leaves);
@@ -298,7 +340,7 @@ impl RatchetTree {
 
 /// A node in RatchetTree. Every node must have a DH public key.
 /// It may also optionally contain the corresponding private key.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RatchetTreeNode {
     Blank,
     Filled {
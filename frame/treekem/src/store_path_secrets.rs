@@ -9,11 +9,17 @@ use std::{
 };
 use tracing::info;
 
+const WELCOME_DIR_NAME: &str = "welcome";
+const WELCOME_FILE_NAME: &str = "encrypted_welcome";
+const WELCOME_EPOCH_FILE_NAME: &str = "epoch";
+const GROUP_DIR_NAME: &str = "group";
+const REQUIRES_UPDATE_FILE_NAME: &str = "requires_update";
+
 /// Store exported secret_paths in the local filesystems
 /// For anonify node, it is saved in the following location.
 ///  - PJ_ROOT_DIR/.anonify/pathsecrets/
 /// For key-vault node, it is saved in the following location.
-/// - PJ_ROOT_DIR/.anonify/pathsecrets/${roster_idx}/
+/// - PJ_ROOT_DIR/.anonify/pathsecrets/${roster_idx}/${enclave}/
 #[derive(Debug, Clone, Default)]
 pub struct StorePathSecrets {
     local_dir_path: PathBuf,
@@ -62,6 +68,95 @@ impl StorePathSecrets {
         Ok(eps)
     }
 
+    /// Save the welcome message encrypted to the joiner's encryption key with the epoch it adds the joiner at.
+    /// It's saved in a sub directory so that it isn't listed as a path secret.
+    pub fn save_welcome_to_local_filesystem(
+        &self,
+        epoch: u32,
+        encrypted_welcome: &[u8],
+    ) -> Result<()> {
+        let dir_path = self.local_dir_path.join(WELCOME_DIR_NAME);
+        fs::create_dir_all(&dir_path)?;
+        let file_path = dir_path.join(WELCOME_FILE_NAME);
+        info!("Saving an encrypted welcome to the path: {:?}", file_path);
+        let mut file = fs::File::create(file_path)?;
+        file.write_all(encrypted_welcome)?;
+        file.flush()?;
+        file.sync_all()?;
+
+        let mut file = fs::File::create(dir_path.join(WELCOME_EPOCH_FILE_NAME))?;
+        file.write_all(&epoch.to_le_bytes())?;
+        file.flush()?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Load the epoch of the saved welcome message, or `None` if no welcome message has been saved.
+    pub fn load_welcome_epoch(&self) -> Result<Option<u32>> {
+        let file_path = self
+            .local_dir_path
+            .join(WELCOME_DIR_NAME)
+            .join(WELCOME_EPOCH_FILE_NAME);
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(file_path)?;
+        let mut buf = [0u8; 4];
+        if bytes.len() != buf.len() {
+            return Err(anyhow::anyhow!("The welcome epoch file is corrupted"));
+        }
+        buf.copy_from_slice(&bytes);
+
+        Ok(Some(u32::from_le_bytes(buf)))
+    }
+
+    pub fn load_welcome_from_local_filesystem(&self) -> Result<Vec<u8>> {
+        let file_path = self
+            .local_dir_path
+            .join(WELCOME_DIR_NAME)
+            .join(WELCOME_FILE_NAME);
+        info!(
+            "Loading an encrypted welcome from the path: {:?}",
+            file_path
+        );
+        let encrypted_welcome = fs::read(file_path)?;
+
+        Ok(encrypted_welcome)
+    }
+
+    /// Save whether this member has to send an update handshake after joining with a welcome message,
+    /// so that it is still required after the node restarts.
+    /// It's saved in a sub directory so that it isn't listed as a path secret.
+    pub fn save_requires_update(&self, requires_update: bool) -> Result<()> {
+        let dir_path = self.local_dir_path.join(GROUP_DIR_NAME);
+        let file_path = dir_path.join(REQUIRES_UPDATE_FILE_NAME);
+        if !requires_update {
+            if file_path.exists() {
+                fs::remove_file(file_path)?;
+            }
+            return Ok(());
+        }
+
+        fs::create_dir_all(&dir_path)?;
+        info!("Saving the required update to the path: {:?}", file_path);
+        let mut file = fs::File::create(file_path)?;
+        file.write_all(&[1u8])?;
+        file.flush()?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    pub fn load_requires_update(&self) -> Result<bool> {
+        let file_path = self
+            .local_dir_path
+            .join(GROUP_DIR_NAME)
+            .join(REQUIRES_UPDATE_FILE_NAME);
+
+        Ok(file_path.exists())
+    }
+
     pub fn get_all_path_secret_ids(&self) -> Result<Vec<Vec<u8>>> {
         let file_paths: Vec<PathBuf> = fs::read_dir(&self.local_dir_path)?
             .filter_map(|entry| entry.ok())
@@ -5,7 +5,7 @@ use crate::{PathSecret, StorePathSecrets};
 use anyhow::anyhow;
 use frame_config::CMD_DEC_SECRET_DIR;
 use rand_core::SeedableRng;
-use std::env;
+use std::{env, string::String, vec::Vec};
 
 pub fn init_path_secret_kvs(kvs: &mut PathSecretKVS, until_roster_idx: usize, until_epoch: usize) {
     let mut csprng = rand::rngs::StdRng::seed_from_u64(1);
@@ -22,10 +22,6 @@ pub fn do_handshake_three_party(
     others_group2: &mut GroupState,
     source: &PathSecretSource,
 ) -> (AppKeyChain, AppKeyChain, AppKeyChain) {
-    let max_roster_idx = env::var("MAX_ROSTER_IDX")
-        .expect("MAX_ROSTER_IDX is not set")
-        .parse::<u32>()
-        .unwrap();
     let (handshake, _) = my_group.create_handshake(source).unwrap();
     let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
 
@@ -34,7 +30,6 @@ pub fn do_handshake_three_party(
             &store_path_secrets,
             &handshake,
             source,
-            max_roster_idx,
             #[cfg(feature = "backup-enable")]
             recover_path_secret_from_key_vault_for_test,
         )
@@ -44,7 +39,6 @@ pub fn do_handshake_three_party(
             &store_path_secrets,
             &handshake,
             source,
-            max_roster_idx,
             #[cfg(feature = "backup-enable")]
             recover_path_secret_from_key_vault_for_test,
         )
@@ -54,7 +48,6 @@ pub fn do_handshake_three_party(
             &store_path_secrets,
             &handshake,
             source,
-            max_roster_idx,
             #[cfg(feature = "backup-enable")]
             recover_path_secret_from_key_vault_for_test,
         )
@@ -100,8 +93,12 @@ fn recover_path_secret_from_key_vault_for_test(
 
     let recover_request_body = RecoverPathSecretRequestBody::new(roster_idx, id.to_vec());
     let ias_url = env::var("IAS_URL").expect("IAS_URL is not set");
-    let key_vault_endpoint = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
-        .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set");
+    let key_vault_endpoints: Vec<String> = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
+        .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set")
+        .split(',')
+        .map(|endpoint| endpoint.trim().to_string())
+        .filter(|endpoint| !endpoint.is_empty())
+        .collect();
     let spid = env::var("SPID").expect("SPID is not set");
     assert!(!spid.is_empty(), "SPID shouldn't be empty");
     let sub_key = env::var("SUB_KEY").expect("SUB_KEY is not set");
@@ -112,8 +109,8 @@ fn recover_path_secret_from_key_vault_for_test(
 
     let client_config = ClientConfig::from_attested_tls_config(attested_tls_config)?
         .set_attestation_report_verifier(IAS_ROOT_CERT.to_vec(), *KEY_VAULT_ENCLAVE_MEASUREMENT);
-    let mut mra_tls_client = Client::new(&key_vault_endpoint, &client_config)?;
     let backup_request = KeyVaultRequest::new(KeyVaultCmd::RecoverPathSecret, recover_request_body);
-    let recovered_path_secret: RecoveredPathSecret = mra_tls_client.send_json(backup_request)?;
+    let recovered_path_secret: RecoveredPathSecret =
+        Client::send_request_with_failover(&key_vault_endpoints, &client_config, backup_request)?;
     Ok(PathSecret::from(recovered_path_secret.path_secret()))
 }
@@ -276,3 +276,39 @@ impl Iterator for DirectPathIter {
         }
     }
 }
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_non_power_of_two_tree,)
+    }
+
+    fn test_non_power_of_two_tree() {
+        // A tree with 3 leaves:
+        //        3
+        //      /   \
+        //     1     \
+        //    / \     \
+        //   0   2     4
+        assert_eq!(num_leaves_in_tree(5), 3);
+        assert_eq!(root_idx(3), 3);
+        assert_eq!(node_parent(4, 3), 3);
+        assert_eq!(node_right_child(3, 3), 4);
+        assert_eq!(node_sibling(4, 3), 1);
+        assert_eq!(node_direct_path(4, 3).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(node_direct_path(2, 3).collect::<Vec<_>>(), vec![2, 1]);
+
+        // A tree with 5 leaves, where the rightmost leaf hangs directly under the root.
+        assert_eq!(num_nodes_in_tree(5), 9);
+        assert_eq!(root_idx(5), 7);
+        assert_eq!(node_parent(8, 5), 7);
+        assert_eq!(node_right_child(7, 5), 8);
+        assert_eq!(common_ancestor(0, 8, 5), 7);
+        assert_eq!(common_ancestor(2, 6, 5), 3);
+        assert!(is_ancestor(7, 8, 5));
+        assert!(!is_ancestor(3, 8, 5));
+    }
+}
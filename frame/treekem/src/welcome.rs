@@ -0,0 +1,53 @@
+use crate::crypto::secrets::PathSecret;
+use crate::group_state::GroupState;
+use crate::handshake::HandshakeParams;
+use serde::{Deserialize, Serialize};
+use std::{boxed::Box, vec::Vec};
+
+/// A welcome message lets a new member start from the current group state
+/// instead of replaying all previous handshakes.
+/// It must be sent only to the joiner's attested encryption key,
+/// because it contains the path secret of the joiner's leaf.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Welcome {
+    /// The public group state at the epoch before the add handshake is applied.
+    /// It contains no private keys and no epoch secrets,
+    /// so the joiner can't read any ciphertexts from previous epochs.
+    group_state: GroupState,
+    /// The add handshake created on behalf of the joiner.
+    handshake: HandshakeParams,
+    /// The path secret of the joiner's leaf, from which the add handshake is derived.
+    #[serde(with = "serde_bytes")]
+    path_secret: Vec<u8>,
+}
+
+impl Welcome {
+    pub(crate) fn new(
+        group_state: GroupState,
+        handshake: HandshakeParams,
+        path_secret: &PathSecret,
+    ) -> Self {
+        Welcome {
+            group_state,
+            handshake,
+            path_secret: path_secret.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(&self).unwrap() // must not fail
+    }
+
+    pub fn decode(bytes: &[u8]) -> std::result::Result<Self, Box<bincode::ErrorKind>> {
+        bincode::deserialize(bytes)
+    }
+
+    pub fn handshake(&self) -> &HandshakeParams {
+        &self.handshake
+    }
+
+    pub(crate) fn into_parts(self) -> (GroupState, HandshakeParams, PathSecret) {
+        let path_secret = PathSecret::from(&self.path_secret[..]);
+        (self.group_state, self.handshake, path_secret)
+    }
+}
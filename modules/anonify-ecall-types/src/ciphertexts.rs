@@ -2,7 +2,12 @@ use crate::bincode;
 use crate::localstd::{boxed::Box, fmt, vec::Vec};
 use crate::serde::{Deserialize, Serialize};
 use frame_common::TreeKemCiphertext;
-use frame_sodium::SodiumCiphertext;
+use frame_sodium::{SodiumCiphertext, SodiumPubKey};
+
+/// Prefixed to the encoded `EnclaveKeyCiphertext` which carries the recipient key.
+/// The ones encoded without it, which are already stored in the on-chain logs, always start with
+/// the length of the hex-encoded ephemeral public key, so they never collide with this prefix.
+const RECIPIENT_KEY_VERSION_PREFIX: [u8; 4] = [0xff, b'E', b'K', 1];
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 #[serde(crate = "crate::serde")]
@@ -23,6 +28,17 @@ impl Default for CommandCiphertext {
 pub struct EnclaveKeyCiphertext {
     pub encrypted_state: SodiumCiphertext,
     roster_idx: u32,
+    /// The public key which `encrypted_state` is encrypted to.
+    /// It is carried outside `SodiumCiphertext` to keep its encoding unchanged, and is `None` for the legacy ciphertexts.
+    recipient_public_key: Option<SodiumPubKey>,
+}
+
+/// The encoding of `EnclaveKeyCiphertext` before the recipient key was added.
+#[derive(Deserialize)]
+#[serde(crate = "crate::serde")]
+struct LegacyEnclaveKeyCiphertext {
+    encrypted_state: SodiumCiphertext,
+    roster_idx: u32,
 }
 
 impl fmt::Debug for EnclaveKeyCiphertext {
@@ -37,19 +53,34 @@ impl fmt::Debug for EnclaveKeyCiphertext {
 }
 
 impl EnclaveKeyCiphertext {
-    pub fn new(encrypted_state: SodiumCiphertext, roster_idx: u32) -> Self {
+    pub fn new(
+        encrypted_state: SodiumCiphertext,
+        roster_idx: u32,
+        recipient_public_key: SodiumPubKey,
+    ) -> Self {
         EnclaveKeyCiphertext {
             encrypted_state,
             roster_idx,
+            recipient_public_key: Some(recipient_public_key),
         }
     }
 
     pub fn decode(bytes: &[u8]) -> crate::localstd::result::Result<Self, Box<bincode::ErrorKind>> {
-        bincode::deserialize(bytes)
+        if bytes.starts_with(&RECIPIENT_KEY_VERSION_PREFIX) {
+            return bincode::deserialize(&bytes[RECIPIENT_KEY_VERSION_PREFIX.len()..]);
+        }
+        let legacy: LegacyEnclaveKeyCiphertext = bincode::deserialize(bytes)?;
+        Ok(EnclaveKeyCiphertext {
+            encrypted_state: legacy.encrypted_state,
+            roster_idx: legacy.roster_idx,
+            recipient_public_key: None,
+        })
     }
 
     pub fn encode(&self) -> Vec<u8> {
-        bincode::serialize(&self).unwrap() //must not fail
+        let mut buf = RECIPIENT_KEY_VERSION_PREFIX.to_vec();
+        buf.extend(bincode::serialize(&self).unwrap()); //must not fail
+        buf
     }
 
     pub fn encrypted_state(&self) -> &SodiumCiphertext {
@@ -59,4 +90,44 @@ impl EnclaveKeyCiphertext {
     pub fn roster_idx(&self) -> u32 {
         self.roster_idx
     }
+
+    /// The public key which the state is encrypted to, if the sender set it.
+    pub fn recipient_public_key(&self) -> Option<&SodiumPubKey> {
+        self.recipient_public_key.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_sodium::SodiumPrivateKey;
+
+    #[test]
+    fn test_decode_legacy_enclave_key_ciphertext() {
+        // Encoded by the format already stored in the on-chain logs, with the roster index 3.
+        let legacy = hex::decode("40000000000000006635373461623837333937643833663639336465626563336530353032393032393833636535373533306238663533346333326430356633376239333433333630000000000000006132653035356439396539343364366435383265616365353138613165623965616462653939616433346138303763382c00000000000000336534323334656164653566656631623233376264333131346632366437636234373638393537303432323303000000").unwrap();
+        let sk = SodiumPrivateKey::from_bytes(&[7u8; 32]).unwrap();
+
+        let ciphertext = EnclaveKeyCiphertext::decode(&legacy[..]).unwrap();
+        assert_eq!(ciphertext.roster_idx(), 3);
+        assert_eq!(ciphertext.recipient_public_key(), None);
+        assert_eq!(
+            ciphertext.encrypted_state().decrypt(&sk).unwrap(),
+            b"legacy"
+        );
+    }
+
+    #[test]
+    fn test_enclave_key_ciphertext_with_recipient_key() {
+        let mut rng = rand::thread_rng();
+        let pk = SodiumPrivateKey::from_random(&mut rng)
+            .unwrap()
+            .public_key();
+        let encrypted_state = SodiumCiphertext::encrypt(&mut rng, &pk, b"test").unwrap();
+
+        let ciphertext = EnclaveKeyCiphertext::new(encrypted_state, 1, pk.clone());
+        let decoded = EnclaveKeyCiphertext::decode(&ciphertext.encode()).unwrap();
+        assert_eq!(decoded, ciphertext);
+        assert_eq!(decoded.recipient_public_key(), Some(&pk));
+    }
 }
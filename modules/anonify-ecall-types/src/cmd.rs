@@ -16,3 +16,7 @@ pub const FETCH_CIPHERTEXT_ENCLAVE_KEY_CMD: u32 = 14;
 pub const JOIN_GROUP_ENCLAVE_KEY_CMD: u32 = 15;
 pub const BACKUP_ENCLAVE_KEY_CMD: u32 = 16;
 pub const RECOVER_ENCLAVE_KEY_CMD: u32 = 17;
+pub const ROTATE_ENCLAVE_KEY_CMD: u32 = 18;
+pub const GET_GROUP_INFO_CMD: u32 = 19;
+pub const SEND_WELCOME_TREEKEM_CMD: u32 = 20;
+pub const GET_ATTESTATION_EVIDENCE_CMD: u32 = 21;
@@ -192,6 +192,36 @@ pub mod input {
         }
     }
 
+    /// The joiner's attestation evidence binds its encryption key,
+    /// which the welcome message is encrypted to, to an attested enclave.
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    #[serde(crate = "crate::serde")]
+    pub struct SendWelcome {
+        roster_idx: u32,
+        /// The serialized attestation evidence of the joiner's enclave.
+        #[serde(with = "serde_bytes")]
+        evidence: Vec<u8>,
+    }
+
+    impl EnclaveInput for SendWelcome {}
+
+    impl SendWelcome {
+        pub fn new(roster_idx: u32, evidence: Vec<u8>) -> Self {
+            SendWelcome {
+                roster_idx,
+                evidence,
+            }
+        }
+
+        pub fn roster_idx(&self) -> u32 {
+            self.roster_idx
+        }
+
+        pub fn evidence(&self) -> &[u8] {
+            &self.evidence[..]
+        }
+    }
+
     #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     #[serde(crate = "crate::serde")]
     pub struct Empty;
@@ -356,6 +386,27 @@ pub mod output {
 
     impl EnclaveOutput for Empty {}
 
+    /// The attestation evidence binding the enclave's encryption key,
+    /// which a member verifies to add this enclave with a welcome message.
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    #[serde(crate = "crate::serde")]
+    pub struct ReturnAttestationEvidence {
+        #[serde(with = "serde_bytes")]
+        evidence: Vec<u8>,
+    }
+
+    impl EnclaveOutput for ReturnAttestationEvidence {}
+
+    impl ReturnAttestationEvidence {
+        pub fn new(evidence: Vec<u8>) -> Self {
+            ReturnAttestationEvidence { evidence }
+        }
+
+        pub fn evidence(self) -> Vec<u8> {
+            self.evidence
+        }
+    }
+
     #[derive(Serialize, Deserialize, Debug, Clone, Default)]
     #[serde(crate = "crate::serde")]
     pub struct ReturnState {
@@ -384,6 +435,57 @@ pub mod output {
         }
     }
 
+    /// A snapshot of the TreeKEM group seen from this enclave.
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    #[serde(crate = "crate::serde")]
+    pub struct ReturnGroupInfo {
+        pub epoch: u32,
+        pub my_roster_idx: u32,
+        /// The latest of the previous epochs whose keychains are still retained after the handshakes.
+        pub prior_epoch: Option<u32>,
+        pub members: Vec<GroupMember>,
+        /// Whether this member joined with a welcome message and has to send an update handshake,
+        /// because the member who sent the welcome knows the secret of its leaf.
+        pub requires_update: bool,
+    }
+
+    impl EnclaveOutput for ReturnGroupInfo {}
+
+    impl ReturnGroupInfo {
+        pub fn new(
+            epoch: u32,
+            my_roster_idx: u32,
+            prior_epoch: Option<u32>,
+            members: Vec<GroupMember>,
+            requires_update: bool,
+        ) -> Self {
+            ReturnGroupInfo {
+                epoch,
+                my_roster_idx,
+                prior_epoch,
+                members,
+                requires_update,
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    #[serde(crate = "crate::serde")]
+    pub struct GroupMember {
+        pub roster_idx: u32,
+        /// Whether the leaf of the ratchet tree is filled, otherwise it's blank.
+        pub is_occupied: bool,
+        /// The generation of the sender keychain, `None` if the keychain doesn't hold the member's secret.
+        pub sender_generation: Option<u32>,
+        /// The generation of the receiver keychain, `None` if the keychain doesn't hold the member's secret.
+        pub receiver_generation: Option<u32>,
+        /// The epoch which the member's last handshake moved the group to,
+        /// `None` if this enclave hasn't processed any handshakes of the member.
+        pub epoch: Option<u32>,
+        /// The MRENCLAVE of the member's enclave, `None` if this enclave hasn't attested it.
+        pub mr_enclave: Option<[u8; 32]>,
+    }
+
     #[derive(Serialize, Deserialize, Clone, Default)]
     #[serde(crate = "crate::serde")]
     pub struct ReturnJoinGroup {
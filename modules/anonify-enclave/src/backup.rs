@@ -164,6 +164,8 @@ impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for EnclaveKeyRec
     fn run(self) -> Result<Self::EO> {
         // fetch path_secrets from key-vault server
         let dec_key = self.enclave_context.recover_enclave_key()?;
+        self.enclave_context
+            .update_enclave_decryption_key(dec_key.clone());
 
         // save path_secrets to own file system
         let encoded = dec_key.try_into_sealing()?;
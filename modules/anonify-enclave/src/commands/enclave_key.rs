@@ -14,7 +14,7 @@ use frame_common::{
 };
 use frame_enclave::StateRuntimeEnclaveUseCase;
 use frame_runtime::traits::*;
-use frame_sodium::{rng::SgxRng, SodiumCiphertext};
+use frame_sodium::rng::SgxRng;
 use std::marker::PhantomData;
 
 /// A message sender that encrypts commands
@@ -137,8 +137,8 @@ where
     /// 2. Verify the order of transactions for each user (verify_user_counter_increment)
     /// 3. State transitions
     fn run(self) -> anyhow::Result<Self::EO> {
-        let ciphertext: &SodiumCiphertext = match self.enclave_input.ciphertext() {
-            CommandCiphertext::EnclaveKey(ciphertext) => ciphertext.encrypted_state(),
+        let ciphertext = match self.enclave_input.ciphertext() {
+            CommandCiphertext::EnclaveKey(ciphertext) => ciphertext,
             _ => return Err(anyhow!("CommandCiphertext is not for enclave_key")),
         };
 
@@ -147,12 +147,12 @@ where
             .verify_state_counter_increment(self.enclave_input.state_counter())?;
 
         let mut output = output::ReturnNotifyState::default();
-        let enclave_decryption_key = self.enclave_context.enclave_decryption_key()?;
-        let decrypted_cmds =
-            CommandExecutor::<R, AnonifyEnclaveContext, AP>::decrypt_with_enclave_key(
-                ciphertext,
-                &enclave_decryption_key,
-            )?;
+        // Decrypt through the context so that the commands encrypted before the key rotation can be decrypted as well.
+        let decrypted_cmds = CommandExecutor::<R, AnonifyEnclaveContext, AP>::decode(
+            &self
+                .enclave_context
+                .decrypt_enclave_key_ciphertext(ciphertext)?,
+        )?;
 
         // Since the command data is valid for the error at the time of state transition,
         // `user_counter` must be verified and incremented before the state transition.
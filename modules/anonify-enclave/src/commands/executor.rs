@@ -8,7 +8,7 @@ use frame_common::{
     AccessPolicy, TreeKemCiphertext,
 };
 use frame_runtime::traits::*;
-use frame_sodium::{SodiumCiphertext, SodiumPrivateKey, SodiumPubKey};
+use frame_sodium::{SodiumCiphertext, SodiumPubKey};
 use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::{marker::PhantomData, vec::Vec};
@@ -68,7 +68,11 @@ where
         let mut buf = bincode::serialize(&self).unwrap(); // must not fail
         Self::append_padding(&mut buf, cmd_cipher_padding_size);
         let encrypted_state = SodiumCiphertext::encrypt(csprng, &pubkey, &buf)?;
-        Ok(EnclaveKeyCiphertext::new(encrypted_state, roster_idx))
+        Ok(EnclaveKeyCiphertext::new(
+            encrypted_state,
+            roster_idx,
+            pubkey,
+        ))
     }
 
     pub fn decode(bytes: &[u8]) -> Result<Self> {
@@ -98,16 +102,6 @@ where
         }
     }
 
-    pub fn decrypt_with_enclave_key(
-        ciphertext: &SodiumCiphertext,
-        privkey: &SodiumPrivateKey,
-    ) -> Result<Self> {
-        ciphertext
-            .decrypt(privkey)
-            .map_err(Into::into)
-            .and_then(|bytes| CommandExecutor::decode(&bytes[..]))
-    }
-
     fn stf_call(self, ctx: CTX) -> Result<(UpdatedStates, NotifyStates)> {
         let res = R::new(ctx).execute(self.call_kind, self.my_account_id)?;
 
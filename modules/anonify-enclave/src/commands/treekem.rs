@@ -145,11 +145,14 @@ where
         };
 
         let roster_idx = treekem_ciphertext.roster_idx() as usize;
+        let msg_epoch = treekem_ciphertext.epoch();
         let msg_gen = treekem_ciphertext.generation();
 
         // Even if group_key's ratchet operations and state transitions fail, state_counter must be incremented so it doesn't get stuck.
         self.enclave_context
             .verify_state_counter_increment(self.enclave_input.state_counter())?;
+        // The keychains of the previous epoch are kept only for the messages flying out the air at the handshake.
+        group_key.prune_prior_epoch(self.enclave_input.state_counter())?;
 
         // Since the sender's keychain has already ratcheted,
         // even if an error occurs in the state transition, the receiver's keychain also ratchet.
@@ -160,8 +163,9 @@ where
         // In addition to these, `sync_ratchet` fails even if the receiver generation is larger than that of the sender
         // So if you run `sync_ratchet` first,
         // it will either succeed or both fail for the mutable `app_keychain`, so it will be atomic.
-        group_key.sync_ratchet(roster_idx, msg_gen)?;
-        group_key.receiver_ratchet(roster_idx)?;
+        // The keychains are selected by the message's epoch, so the messages encrypted in the previous epoch are applied in order as well.
+        group_key.sync_ratchet(roster_idx, msg_epoch, msg_gen)?;
+        group_key.receiver_ratchet(roster_idx, msg_epoch)?;
 
         let mut output = output::ReturnNotifyState::default();
         let decrypted_cmds = CommandExecutor::<R, AnonifyEnclaveContext, AP>::decrypt_with_treekem(
@@ -1,10 +1,15 @@
 use crate::{
     enclave_key::EnclaveKey,
     error::Result,
-    group_key::GroupKey,
+    group_key::{GroupKey, DEFAULT_PRIOR_EPOCH_RETENTION},
     kvs::{UserCounterDB, UserStateDB},
     notify::Notifier,
 };
+#[cfg(feature = "backup-enable")]
+use crate::{
+    enclave_key::{load_or_generate_node_key, KeyRecoveryLimiter},
+    shared_backup::{secret_sharing_threshold_from_env, SharedBackup},
+};
 use anonify_ecall_types::cmd::{GET_STATE_CMD, GET_USER_COUNTER_CMD, SEND_REGISTER_REPORT_CMD};
 use anonify_ecall_types::*;
 use anyhow::{anyhow, bail};
@@ -16,9 +21,12 @@ use frame_common::{
     },
     AccessPolicy,
 };
-#[cfg(feature = "backup-enable")]
-use frame_config::KEY_VAULT_ENCLAVE_MEASUREMENT;
 use frame_config::{ANONIFY_PARAMS_DIR, CMD_DEC_SECRET_DIR, IAS_ROOT_CERT};
+#[cfg(feature = "backup-enable")]
+use frame_config::{
+    ATTESTATION_RENEWAL_INTERVAL_SECS, DEC_KEY_RECOVERY_INTERVAL_SECS,
+    KEY_VAULT_ENCLAVE_MEASUREMENT,
+};
 use frame_enclave::StateRuntimeEnclaveUseCase;
 #[cfg(feature = "backup-enable")]
 use frame_mra_tls::{
@@ -26,8 +34,9 @@ use frame_mra_tls::{
         request::{
             BackupPathSecretRequestBody, BackupPathSecretsRequestBody, KeyVaultCmd,
             KeyVaultRequest, RecoverPathSecretRequestBody, RecoverPathSecretsRequestBody,
+            RecoverWelcomeRequestBody, StoreWelcomeRequestBody,
         },
-        response::RecoveredPathSecret,
+        response::{BackedUpEnclaveDecryptionKey, RecoveredPathSecret, RecoveredWelcome},
     },
     AttestedTlsConfig, Client, ClientConfig,
 };
@@ -40,7 +49,9 @@ use frame_treekem::{
     init_path_secret_kvs, StorePathSecrets,
 };
 use rand_core::{CryptoRng, RngCore};
-use remote_attestation::{EncodedQuote, QuoteTarget};
+use remote_attestation::{provider_from_env, AttestationEvidence, EncodedQuote, QuoteTarget};
+#[cfg(feature = "backup-enable")]
+use std::time::{Duration, SystemTime};
 use std::{
     env,
     prelude::v1::*,
@@ -56,9 +67,21 @@ pub struct AnonifyEnclaveContext {
     ias_url: String,
     sub_key: String,
     #[cfg(feature = "backup-enable")]
-    key_vault_endpoint: String,
+    key_vault_endpoints: Vec<String>,
+    /// The version of the enclave decryption key backup to recover. The latest one if `None`.
+    #[cfg(feature = "backup-enable")]
+    dec_key_backup_version: Option<u32>,
+    /// The threshold of Shamir's secret sharing of the backups across the key-vault nodes.
+    /// The key-vault nodes hold the whole secrets if `None`.
+    #[cfg(feature = "backup-enable")]
+    secret_sharing_threshold: Option<usize>,
+    #[cfg(feature = "backup-enable")]
+    dec_key_recovery_limiter: KeyRecoveryLimiter,
+    /// The sealed secret identifying this node, to which the key-vault nodes bind its roster index.
+    #[cfg(feature = "backup-enable")]
+    node_key: Vec<u8>,
     spid: String,
-    enclave_key: EnclaveKey,
+    enclave_key: Arc<SgxRwLock<EnclaveKey>>,
     user_state_db: UserStateDB,
     user_counter_db: UserCounterDB,
     notifier: Notifier,
@@ -85,8 +108,8 @@ impl ConfigGetter for AnonifyEnclaveContext {
     }
 
     #[cfg(feature = "backup-enable")]
-    fn key_vault_endpoint(&self) -> &str {
-        &self.key_vault_endpoint
+    fn key_vault_endpoints(&self) -> &[String] {
+        &self.key_vault_endpoints
     }
 
     fn spid(&self) -> &str {
@@ -228,34 +251,59 @@ impl EnclaveKeyOps for AnonifyEnclaveContext {
     /// This signature is used to verify enclave's program dependencies and
     /// should be verified in the public available place such as smart contract on blockchain.
     fn sign(&self, msg: &[u8]) -> anyhow::Result<(secp256k1::Signature, secp256k1::RecoveryId)> {
-        self.enclave_key.sign(msg).map_err(Into::into)
+        self.enclave_key
+            .read()
+            .unwrap()
+            .sign(msg)
+            .map_err(Into::into)
     }
 
     fn decrypt(&self, ciphertext: &SodiumCiphertext) -> anyhow::Result<Vec<u8>> {
-        self.enclave_key.decrypt(ciphertext).map_err(Into::into)
+        self.enclave_key
+            .read()
+            .unwrap()
+            .decrypt(ciphertext)
+            .map_err(Into::into)
     }
 
     fn enclave_encryption_key(&self) -> anyhow::Result<SodiumPubKey> {
         self.enclave_key
+            .read()
+            .unwrap()
             .enclave_encryption_key()
             .map_err(|e| anyhow!("{:?}", e))
     }
 
-    fn enclave_decryption_key(&self) -> anyhow::Result<&SodiumPrivateKey> {
+    fn enclave_decryption_key(&self) -> anyhow::Result<SodiumPrivateKey> {
         self.enclave_key
+            .read()
+            .unwrap()
             .enclave_decryption_key()
+            .map(Clone::clone)
             .map_err(|e| anyhow!("{:?}", e))
     }
 }
 
 impl QuoteGetter for AnonifyEnclaveContext {
     fn quote(&self) -> anyhow::Result<EncodedQuote> {
-        let report_data = &self.enclave_key.report_data()?;
+        let report_data = &self.enclave_key.read().unwrap().report_data()?;
         QuoteTarget::new()?
             .set_enclave_report(&report_data)?
             .create_quote(&self.spid)
             .map_err(|e| anyhow!("{:?}", e))
     }
+
+    fn attest(&self) -> anyhow::Result<AttestationEvidence> {
+        let report_data = &self.enclave_key.read().unwrap().report_data()?;
+        provider_from_env(
+            &self.spid,
+            &self.ias_url,
+            &self.sub_key,
+            IAS_ROOT_CERT.to_vec(),
+        )?
+        .attest(report_data)
+        .map_err(|e| anyhow!("{:?}", e))
+    }
 }
 
 #[cfg(feature = "backup-enable")]
@@ -264,20 +312,35 @@ impl KeyVaultOps for AnonifyEnclaveContext {
         &self,
         backup_path_secret: BackupPathSecretRequestBody,
     ) -> anyhow::Result<()> {
-        let mut mra_tls_client = Client::new(self.key_vault_endpoint(), &self.client_config)?;
+        if let Some(shared_backup) = self.shared_backup() {
+            return shared_backup.backup_path_secret(&backup_path_secret);
+        }
+
         let key_vault_request =
             KeyVaultRequest::new(KeyVaultCmd::StorePathSecret, backup_path_secret);
-        let _resp: serde_json::Value = mra_tls_client.send_json(key_vault_request)?;
+        let _resp: serde_json::Value = Client::send_request_with_failover(
+            self.key_vault_endpoints(),
+            &self.client_config,
+            key_vault_request,
+        )?;
 
         Ok(())
     }
 
     fn recover_path_secret(&self, ps_id: &[u8], roster_idx: u32) -> anyhow::Result<PathSecret> {
-        let recover_request = RecoverPathSecretRequestBody::new(roster_idx, ps_id.to_vec());
-        let mut mra_tls_client = Client::new(self.key_vault_endpoint(), &self.client_config)?;
-        let backup_request = KeyVaultRequest::new(KeyVaultCmd::RecoverPathSecret, recover_request);
-        let recovered_path_secret: RecoveredPathSecret =
-            mra_tls_client.send_json(backup_request)?;
+        let recovered_path_secret = match self.shared_backup() {
+            Some(shared_backup) => shared_backup.recover_path_secret(roster_idx, ps_id)?,
+            None => {
+                let recover_request = RecoverPathSecretRequestBody::new(roster_idx, ps_id.to_vec());
+                let backup_request =
+                    KeyVaultRequest::new(KeyVaultCmd::RecoverPathSecret, recover_request);
+                Client::send_request_with_failover(
+                    self.key_vault_endpoints(),
+                    &self.client_config,
+                    backup_request,
+                )?
+            }
+        };
 
         Ok(PathSecret::from(recovered_path_secret.path_secret()))
     }
@@ -286,10 +349,17 @@ impl KeyVaultOps for AnonifyEnclaveContext {
         &self,
         backup_path_secrets: BackupPathSecretsRequestBody,
     ) -> anyhow::Result<()> {
-        let mut mra_tls_client = Client::new(self.key_vault_endpoint(), &self.client_config)?;
+        if let Some(shared_backup) = self.shared_backup() {
+            return shared_backup.backup_path_secrets(&backup_path_secrets);
+        }
+
         let key_vault_request =
             KeyVaultRequest::new(KeyVaultCmd::ManuallyStorePathSecrets, backup_path_secrets);
-        let _resp: serde_json::Value = mra_tls_client.send_json(key_vault_request)?;
+        let _resp: serde_json::Value = Client::send_request_with_failover(
+            self.key_vault_endpoints(),
+            &self.client_config,
+            key_vault_request,
+        )?;
 
         Ok(())
     }
@@ -298,34 +368,168 @@ impl KeyVaultOps for AnonifyEnclaveContext {
         &self,
         recover_path_secrets: RecoverPathSecretsRequestBody,
     ) -> anyhow::Result<Vec<RecoveredPathSecret>> {
-        let mut mra_tls_client = Client::new(self.key_vault_endpoint(), &self.client_config)?;
+        if let Some(shared_backup) = self.shared_backup() {
+            return shared_backup.recover_path_secrets(recover_path_secrets);
+        }
+
         let key_vault_request = KeyVaultRequest::new(
             KeyVaultCmd::ManuallyRecoverPathSecrets,
             recover_path_secrets,
         );
-        let path_secrets: Vec<RecoveredPathSecret> = mra_tls_client.send_json(key_vault_request)?;
+        let path_secrets: Vec<RecoveredPathSecret> = Client::send_request_with_failover(
+            self.key_vault_endpoints(),
+            &self.client_config,
+            key_vault_request,
+        )?;
 
         Ok(path_secrets)
     }
 
     fn backup_enclave_key(&self) -> anyhow::Result<()> {
         self.enclave_key
-            .store_dec_key_to_remote(&self.client_config, &self.key_vault_endpoint())
+            .read()
+            .unwrap()
+            .store_dec_key_to_remote(
+                &self.client_config,
+                self.key_vault_endpoints(),
+                self.secret_sharing_threshold,
+                self.my_roster_idx as u32,
+                &self.node_key,
+            )
             .map_err(|e| anyhow!("Failed to backup enclave_key: {:?}", e))
     }
 
     fn recover_enclave_key(&self) -> anyhow::Result<SodiumPrivateKey> {
         let enclave_key = self
             .enclave_key
+            .read()
+            .unwrap()
             .clone()
-            .get_dec_key_from_remotely_sealed(&self.client_config, &self.key_vault_endpoint())?;
+            .get_dec_key_from_remotely_sealed(
+                &self.client_config,
+                self.key_vault_endpoints(),
+                self.secret_sharing_threshold,
+                self.my_roster_idx as u32,
+                self.dec_key_backup_version,
+            )?;
         let dec_key = enclave_key.enclave_decryption_key()?;
         Ok(dec_key.clone())
     }
+
+    fn list_enclave_key_backups(&self) -> anyhow::Result<Vec<BackedUpEnclaveDecryptionKey>> {
+        EnclaveKey::list_remote_dec_keys(
+            &self.client_config,
+            self.key_vault_endpoints(),
+            self.my_roster_idx as u32,
+        )
+        .map_err(Into::into)
+    }
+
+    fn store_welcome(&self, store_welcome: StoreWelcomeRequestBody) -> anyhow::Result<()> {
+        let key_vault_request = KeyVaultRequest::new(KeyVaultCmd::StoreWelcome, store_welcome);
+        let _resp: serde_json::Value = Client::send_request_with_failover(
+            self.key_vault_endpoints(),
+            &self.client_config,
+            key_vault_request,
+        )?;
+
+        Ok(())
+    }
+
+    fn recover_welcome(&self, roster_idx: u32) -> anyhow::Result<Vec<u8>> {
+        let recover_request = RecoverWelcomeRequestBody::new(roster_idx);
+        let key_vault_request = KeyVaultRequest::new(KeyVaultCmd::RecoverWelcome, recover_request);
+        let recovered_welcome: RecoveredWelcome = Client::send_request_with_failover(
+            self.key_vault_endpoints(),
+            &self.client_config,
+            key_vault_request,
+        )?;
+
+        Ok(recovered_welcome.encrypted_welcome().to_vec())
+    }
 }
 
 // TODO: Consider SGX_ERROR_BUSY.
 impl AnonifyEnclaveContext {
+    /// Returns `Some` if the backups are split into shares across the key-vault nodes.
+    #[cfg(feature = "backup-enable")]
+    fn shared_backup(&self) -> Option<SharedBackup> {
+        self.secret_sharing_threshold.map(|threshold| {
+            SharedBackup::new(self.key_vault_endpoints(), &self.client_config, threshold)
+        })
+    }
+
+    /// Generate a new enclave decryption key, back it up to the key-vault node and seal it to the local storage.
+    /// The previous key is kept to decrypt ciphertexts encrypted before the new encryption key is published.
+    pub fn rotate_enclave_key<R: RngCore + CryptoRng>(&self, rng: &mut R) -> anyhow::Result<()> {
+        let mut enclave_key = self.enclave_key.write().unwrap();
+        let mut new_enclave_key = enclave_key.clone();
+        new_enclave_key.rotate_dec_key(SodiumPrivateKey::from_random(rng)?);
+
+        #[cfg(feature = "backup-enable")]
+        new_enclave_key.store_dec_key_to_remote(
+            &self.client_config,
+            &self.key_vault_endpoints,
+            self.secret_sharing_threshold,
+            self.my_roster_idx as u32,
+            &self.node_key,
+        )?;
+        new_enclave_key.store_dec_key_to_local(&self.store_enclave_dec_key)?;
+        *enclave_key = new_enclave_key;
+
+        Ok(())
+    }
+
+    /// Decrypt the state in the ciphertext fetched from the blockchain.
+    /// Another node may have rotated the shared decryption key if it is encrypted to an unknown key,
+    /// so try again with the latest one backed up in the key-vault node.
+    pub fn decrypt_enclave_key_ciphertext(
+        &self,
+        ciphertext: &EnclaveKeyCiphertext,
+    ) -> anyhow::Result<Vec<u8>> {
+        let res = self.decrypt(ciphertext.encrypted_state());
+        match (res, ciphertext.recipient_public_key()) {
+            (Ok(plaintext), _) => Ok(plaintext),
+            #[cfg(feature = "backup-enable")]
+            (Err(e), Some(recipient_key))
+                if self
+                    .enclave_key
+                    .read()
+                    .unwrap()
+                    .is_unknown_key(recipient_key) =>
+            {
+                if !self.dec_key_recovery_limiter.try_acquire(SystemTime::now()) {
+                    bail!(
+                        "The ciphertext is encrypted to an unknown key, and the enclave decryption key was recovered within {} secs: {:?}",
+                        *DEC_KEY_RECOVERY_INTERVAL_SECS,
+                        e
+                    );
+                }
+                let dec_key = self.recover_enclave_key()?;
+                if self.update_enclave_decryption_key(dec_key) {
+                    self.enclave_key
+                        .read()
+                        .unwrap()
+                        .store_dec_key_to_local(&self.store_enclave_dec_key)?;
+                }
+                self.decrypt(ciphertext.encrypted_state())
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Set the enclave decryption key rotated by another node.
+    /// Returns false if it is the same as the current one.
+    pub fn update_enclave_decryption_key(&self, dec_key: SodiumPrivateKey) -> bool {
+        let mut enclave_key = self.enclave_key.write().unwrap();
+        if enclave_key.enclave_decryption_key().ok() == Some(&dec_key) {
+            return false;
+        }
+        enclave_key.rotate_dec_key(dec_key);
+
+        true
+    }
+
     pub fn new<R: RngCore + CryptoRng>(version: usize, rng: &mut R) -> Result<Self> {
         let user_state_db = UserStateDB::new();
         let user_counter_db = UserCounterDB::new();
@@ -350,16 +554,24 @@ impl AnonifyEnclaveContext {
             .expect("MY_ROSTER_IDX is not set")
             .parse()
             .expect("Failed to parse MY_ROSTER_IDX to usize");
-        let max_roster_idx: usize = env::var("MAX_ROSTER_IDX")
-            .expect("MAX_ROSTER_IDX is not set")
-            .parse()
-            .expect("Failed to parse MAX_ROSTER_IDX to usize");
+        let prior_epoch_retention: u32 = match env::var("PRIOR_EPOCH_RETENTION") {
+            Ok(retention) if !retention.is_empty() => retention
+                .parse()
+                .expect("Failed to parse PRIOR_EPOCH_RETENTION to u32"),
+            _ => DEFAULT_PRIOR_EPOCH_RETENTION,
+        };
 
-        let group_key = Arc::new(SgxRwLock::new(GroupKey::new(
-            my_roster_idx,
-            max_roster_idx,
-            source,
-        )?));
+        let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
+        let group_key = {
+            let mut group_key = GroupKey::new(my_roster_idx, prior_epoch_retention, source)?;
+            group_key.restore_requires_update(&store_path_secrets)?;
+            // This enclave's own measurement is attested by its self report.
+            group_key.record_member_mr_enclave(
+                my_roster_idx as u32,
+                sgx_tse::rsgx_self_report().body.mr_enclave.m,
+            );
+            Arc::new(SgxRwLock::new(group_key))
+        };
         let notifier = Notifier::new();
 
         let ias_url = env::var("IAS_URL").expect("IAS_URL is not set");
@@ -367,13 +579,32 @@ impl AnonifyEnclaveContext {
         assert!(!sub_key.is_empty(), "SUB_KEY shouldn't be empty");
 
         #[cfg(feature = "backup-enable")]
-        let key_vault_endpoint = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
-            .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set");
+        let key_vault_endpoints: Vec<String> = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
+            .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set")
+            .split(',')
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+        #[cfg(feature = "backup-enable")]
+        let dec_key_backup_version: Option<u32> = match env::var("ENCLAVE_KEY_BACKUP_VERSION") {
+            Ok(version) if !version.is_empty() => Some(
+                version
+                    .parse()
+                    .expect("Failed to parse ENCLAVE_KEY_BACKUP_VERSION to u32"),
+            ),
+            _ => None,
+        };
+        #[cfg(feature = "backup-enable")]
+        let secret_sharing_threshold =
+            secret_sharing_threshold_from_env(key_vault_endpoints.len())?;
 
         #[cfg(feature = "backup-enable")]
         let client_config = {
             let attested_tls_config =
                 AttestedTlsConfig::new_by_ra(&spid, &ias_url, &sub_key, IAS_ROOT_CERT.to_vec())?;
+            // The renewal runs as long as the enclave, so its handle is not kept.
+            attested_tls_config
+                .spawn_renewal(Duration::from_secs(*ATTESTATION_RENEWAL_INTERVAL_SECS))?;
             ClientConfig::from_attested_tls_config(attested_tls_config)?
                 .set_attestation_report_verifier(
                     IAS_ROOT_CERT.to_vec(),
@@ -381,8 +612,9 @@ impl AnonifyEnclaveContext {
                 )
         };
 
-        let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
         let store_enclave_dec_key = StoreEnclaveDecryptionKey::new(&*ANONIFY_PARAMS_DIR);
+        #[cfg(feature = "backup-enable")]
+        let node_key = load_or_generate_node_key(&store_enclave_dec_key)?;
         let state_counter = Arc::new(SgxRwLock::new(StateCounter::default()));
 
         let enclave_key = {
@@ -396,11 +628,27 @@ impl AnonifyEnclaveContext {
                 // If not, trying set the key from remote key-vault node.
                 Err(_e) => {
                     // If the backup enabled, try to fetch the enclave decryption key from the remote key_vault node.
+                    // A newly joining node has no backup of its own yet,
+                    // so it recovers the group's key backed up by the node which created the group.
                     #[cfg(feature = "backup-enable")]
                     match enc_key
                         .clone()
-                        .get_dec_key_from_remotely_sealed(&client_config, &key_vault_endpoint)
-                    {
+                        .get_dec_key_from_remotely_sealed(
+                            &client_config,
+                            &key_vault_endpoints,
+                            secret_sharing_threshold,
+                            my_roster_idx as u32,
+                            dec_key_backup_version,
+                        )
+                        .or_else(|_| {
+                            enc_key.clone().get_dec_key_from_remotely_sealed(
+                                &client_config,
+                                &key_vault_endpoints,
+                                secret_sharing_threshold,
+                                0,
+                                None,
+                            )
+                        }) {
                         Ok(enclave_key) => enclave_key,
                         Err(_e) => {
                             // new anonify group will be created.
@@ -422,11 +670,17 @@ impl AnonifyEnclaveContext {
 
         enclave_key.store_dec_key_to_local(&store_enclave_dec_key)?;
         #[cfg(feature = "backup-enable")]
-        enclave_key.store_dec_key_to_remote(&client_config, &key_vault_endpoint)?;
+        enclave_key.store_dec_key_to_remote(
+            &client_config,
+            &key_vault_endpoints,
+            secret_sharing_threshold,
+            my_roster_idx as u32,
+            &node_key,
+        )?;
 
         Ok(AnonifyEnclaveContext {
             spid,
-            enclave_key,
+            enclave_key: Arc::new(SgxRwLock::new(enclave_key)),
             user_state_db,
             user_counter_db,
             notifier,
@@ -436,7 +690,17 @@ impl AnonifyEnclaveContext {
             ias_url,
             sub_key,
             #[cfg(feature = "backup-enable")]
-            key_vault_endpoint,
+            key_vault_endpoints,
+            #[cfg(feature = "backup-enable")]
+            dec_key_backup_version,
+            #[cfg(feature = "backup-enable")]
+            secret_sharing_threshold,
+            #[cfg(feature = "backup-enable")]
+            dec_key_recovery_limiter: KeyRecoveryLimiter::new(Duration::from_secs(
+                *DEC_KEY_RECOVERY_INTERVAL_SECS,
+            )),
+            #[cfg(feature = "backup-enable")]
+            node_key,
             #[cfg(feature = "backup-enable")]
             client_config,
             store_path_secrets,
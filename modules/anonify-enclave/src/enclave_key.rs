@@ -2,22 +2,27 @@
 
 use crate::context::AnonifyEnclaveContext;
 use crate::error::{EnclaveError, Result};
-use anonify_ecall_types::cmd::GET_ENCLAVE_ENCRYPTION_KEY_CMD;
+#[cfg(feature = "backup-enable")]
+use crate::shared_backup::SharedBackup;
+use anonify_ecall_types::cmd::{GET_ENCLAVE_ENCRYPTION_KEY_CMD, ROTATE_ENCLAVE_KEY_CMD};
 use anonify_ecall_types::*;
 use anyhow::anyhow;
 use frame_common::{crypto::rand_assign, traits::Keccak256};
 use frame_enclave::StateRuntimeEnclaveUseCase;
 #[cfg(feature = "backup-enable")]
 use frame_mra_tls::{
-    key_vault::request::{
-        BackupEnclaveDecryptionKeyRequestBody, KeyVaultCmd, KeyVaultRequest,
-        RecoverEnclaveDecryptionKeyRequestBody,
+    key_vault::{
+        request::{
+            BackupEnclaveDecryptionKeyRequestBody, KeyVaultCmd, KeyVaultRequest,
+            ListEnclaveDecryptionKeysRequestBody, RecoverEnclaveDecryptionKeyRequestBody,
+        },
+        response::BackedUpEnclaveDecryptionKey,
     },
     Client, ClientConfig,
 };
 use frame_runtime::traits::*;
 use frame_sodium::{
-    SealedEnclaveDecryptionKey, SodiumCiphertext, SodiumPrivateKey, SodiumPubKey,
+    rng::SgxRng, SealedEnclaveDecryptionKey, SodiumCiphertext, SodiumPrivateKey, SodiumPubKey,
     StoreEnclaveDecryptionKey, SODIUM_PUBLIC_KEY_SIZE,
 };
 use rand_core::{CryptoRng, RngCore};
@@ -25,13 +30,21 @@ use secp256k1::{
     self, util::SECRET_KEY_SIZE, Message, PublicKey, RecoveryId, SecretKey, Signature,
 };
 use sgx_types::sgx_report_data_t;
-use std::prelude::v1::Vec;
+use std::{
+    prelude::v1::{String, Vec},
+    sync::{Arc, SgxMutex},
+    time::{Duration, SystemTime},
+};
 
 const HASHED_PUBKEY_SIZE: usize = 20;
 const ENCLAVE_ENCRYPTION_KEY_SIZE: usize = SODIUM_PUBLIC_KEY_SIZE;
 const FILLED_REPORT_DATA_SIZE: usize = HASHED_PUBKEY_SIZE + ENCLAVE_ENCRYPTION_KEY_SIZE;
 const REPORT_DATA_SIZE: usize = 64;
 pub const DEC_KEY_FILE_NAME: &str = "sr_enclave_decryption_key";
+#[cfg(feature = "backup-enable")]
+const NODE_KEY_FILE_NAME: &str = "sr_node_key";
+#[cfg(feature = "backup-enable")]
+const NODE_KEY_SIZE: usize = 32;
 
 #[derive(Debug, Clone)]
 pub struct EncryptionKeyGetter<'c> {
@@ -61,11 +74,111 @@ impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for EncryptionKey
     }
 }
 
+/// Rotate the enclave decryption key, seal it and back it up,
+/// and then return the new attested report to re-register the encryption key on-chain.
+#[derive(Debug, Clone)]
+pub struct EnclaveKeyRotator<'c> {
+    enclave_context: &'c AnonifyEnclaveContext,
+}
+
+impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for EnclaveKeyRotator<'c> {
+    type EI = input::Empty;
+    type EO = output::ReturnRegisterReport;
+    const ENCLAVE_USE_CASE_ID: u32 = ROTATE_ENCLAVE_KEY_CMD;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c AnonifyEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn eval_policy(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let mut csprng = SgxRng::new()?;
+        self.enclave_context.rotate_enclave_key(&mut csprng)?;
+
+        // The report data contains the new encryption key, so registering the report publishes it.
+        let attested_report = self.enclave_context.quote()?.remote_attestation(
+            self.enclave_context.ias_url(),
+            self.enclave_context.sub_key(),
+            self.enclave_context.ias_root_cert().to_vec(),
+        )?;
+
+        Ok(output::ReturnRegisterReport::new(
+            attested_report.report().to_vec(),
+            attested_report.report_sig().to_vec(),
+            self.enclave_context.mrenclave_ver(),
+            self.enclave_context.my_roster_idx() as u32,
+        ))
+    }
+}
+
+/// Limits how often the enclave decryption key is recovered from the key-vault nodes,
+/// so that the ciphertexts encrypted to unknown keys don't make a request to them each.
+#[derive(Debug, Clone)]
+pub struct KeyRecoveryLimiter {
+    interval: Duration,
+    last_recovery: Arc<SgxMutex<Option<SystemTime>>>,
+}
+
+impl KeyRecoveryLimiter {
+    pub fn new(interval: Duration) -> Self {
+        KeyRecoveryLimiter {
+            interval,
+            last_recovery: Arc::new(SgxMutex::new(None)),
+        }
+    }
+
+    /// Returns true and records the time if the interval has passed since the last recovery.
+    pub fn try_acquire(&self, now: SystemTime) -> bool {
+        let mut last_recovery = self.last_recovery.lock().unwrap();
+        if let Some(last) = *last_recovery {
+            // The clock going backwards is regarded as within the interval.
+            match now.duration_since(last) {
+                Ok(elapsed) if elapsed >= self.interval => {}
+                _ => return false,
+            }
+        }
+        *last_recovery = Some(now);
+
+        true
+    }
+}
+
+/// Load the node key sealed in the local storage, or generate and seal a new one.
+/// The key-vault nodes bind this node's roster index to it on the first backup of the enclave decryption key,
+/// so that another node can't add versions to its backups.
+#[cfg(feature = "backup-enable")]
+pub fn load_or_generate_node_key(store_dec_key: &StoreEnclaveDecryptionKey) -> Result<Vec<u8>> {
+    let is_stored = store_dec_key
+        .get_all_file_names()?
+        .iter()
+        .any(|file_name| file_name == NODE_KEY_FILE_NAME);
+    if is_stored {
+        return store_dec_key
+            .load_secret_from_local_filesystem(NODE_KEY_FILE_NAME)
+            .map_err(Into::into);
+    }
+
+    let mut node_key = vec![0u8; NODE_KEY_SIZE];
+    rand_assign(&mut node_key)?;
+    store_dec_key.save_secret_to_local_filesystem(&node_key, NODE_KEY_FILE_NAME)?;
+
+    Ok(node_key)
+}
+
 /// Enclave Key
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct EnclaveKey {
     signing_privkey: SecretKey,
     decryption_privkey: Option<SodiumPrivateKey>,
+    /// The decryption key before the last rotation.
+    /// It is kept to decrypt the ciphertexts which were encrypted before the new encryption key was published.
+    prior_decryption_privkey: Option<SodiumPrivateKey>,
 }
 
 impl EnclaveKey {
@@ -82,6 +195,7 @@ impl EnclaveKey {
         Ok(EnclaveKey {
             signing_privkey,
             decryption_privkey: None,
+            prior_decryption_privkey: None,
         })
     }
 
@@ -100,18 +214,32 @@ impl EnclaveKey {
     }
 
     /// Get dec_key from key-vault node in initialization when joining newly.
+    /// If version is `None`, the latest backup of the roster index is recovered.
+    /// If the secret sharing threshold is set, the key is reconstructed from the shares in the key-vault nodes.
     #[cfg(feature = "backup-enable")]
     pub fn get_dec_key_from_remotely_sealed(
         mut self,
         client_config: &ClientConfig,
-        key_vault_endpoint: &str,
+        key_vault_endpoints: &[String],
+        secret_sharing_threshold: Option<usize>,
+        roster_idx: u32,
+        version: Option<u32>,
     ) -> Result<Self> {
-        let mut mra_tls_client = Client::new(key_vault_endpoint, &client_config)?;
-        let get_dec_key_request = KeyVaultRequest::new(
-            KeyVaultCmd::RecoverEnclaveDecryptionKey,
-            RecoverEnclaveDecryptionKeyRequestBody::default(),
-        );
-        let decryption_privkey: SodiumPrivateKey = mra_tls_client.send_json(get_dec_key_request)?;
+        let decryption_privkey = match secret_sharing_threshold {
+            Some(threshold) => SharedBackup::new(key_vault_endpoints, client_config, threshold)
+                .recover_dec_key(roster_idx, version)?,
+            None => {
+                let get_dec_key_request = KeyVaultRequest::new(
+                    KeyVaultCmd::RecoverEnclaveDecryptionKey,
+                    RecoverEnclaveDecryptionKeyRequestBody::new(roster_idx, version),
+                );
+                Client::send_request_with_failover(
+                    key_vault_endpoints,
+                    client_config,
+                    get_dec_key_request,
+                )?
+            }
+        };
 
         self.decryption_privkey = Some(decryption_privkey);
         Ok(self)
@@ -123,6 +251,14 @@ impl EnclaveKey {
         Ok(self)
     }
 
+    /// Replace the decryption key with the given one, keeping the current one as the prior key.
+    pub fn rotate_dec_key(&mut self, decryption_privkey: SodiumPrivateKey) {
+        if self.decryption_privkey.as_ref() == Some(&decryption_privkey) {
+            return;
+        }
+        self.prior_decryption_privkey = self.decryption_privkey.replace(decryption_privkey);
+    }
+
     pub fn store_dec_key_to_local(&self, store_dec_key: &StoreEnclaveDecryptionKey) -> Result<()> {
         let encoded = self
             .decryption_privkey
@@ -136,25 +272,62 @@ impl EnclaveKey {
             .map_err(Into::into)
     }
 
-    /// Sealing locally, make it persistent, and save it in the key-vault node as well
+    /// Sealing locally, make it persistent, and save it in the key-vault node as well.
+    /// If the secret sharing threshold is set, each key-vault node stores only a share of it.
     #[cfg(feature = "backup-enable")]
     pub fn store_dec_key_to_remote(
         &self,
         client_config: &ClientConfig,
-        key_vault_endpoint: &str,
+        key_vault_endpoints: &[String],
+        secret_sharing_threshold: Option<usize>,
+        roster_idx: u32,
+        node_key: &[u8],
     ) -> Result<()> {
-        let mut mra_tls_client = Client::new(key_vault_endpoint, &client_config)?;
         let dec_key = self
             .decryption_privkey
             .as_ref()
             .ok_or(EnclaveError::NotSetEnclaveDecKeyError)?;
+        match secret_sharing_threshold {
+            Some(threshold) => SharedBackup::new(key_vault_endpoints, client_config, threshold)
+                .backup_dec_key(dec_key, roster_idx, node_key)?,
+            None => {
+                let key_vault_request = KeyVaultRequest::new(
+                    KeyVaultCmd::StoreEnclaveDecryptionKey,
+                    BackupEnclaveDecryptionKeyRequestBody::new(
+                        dec_key.clone(),
+                        roster_idx,
+                        node_key.to_vec(),
+                    ),
+                );
+                let _resp: serde_json::Value = Client::send_request_with_failover(
+                    key_vault_endpoints,
+                    client_config,
+                    key_vault_request,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// List the versions of dec_key backed up in the key-vault node for the roster index.
+    #[cfg(feature = "backup-enable")]
+    pub fn list_remote_dec_keys(
+        client_config: &ClientConfig,
+        key_vault_endpoints: &[String],
+        roster_idx: u32,
+    ) -> Result<Vec<BackedUpEnclaveDecryptionKey>> {
         let key_vault_request = KeyVaultRequest::new(
-            KeyVaultCmd::StoreEnclaveDecryptionKey,
-            BackupEnclaveDecryptionKeyRequestBody::new(dec_key.clone()),
+            KeyVaultCmd::ListEnclaveDecryptionKeys,
+            ListEnclaveDecryptionKeysRequestBody::new(roster_idx),
         );
-        let _resp: serde_json::Value = mra_tls_client.send_json(key_vault_request)?;
+        let versions: Vec<BackedUpEnclaveDecryptionKey> = Client::send_request_with_failover(
+            key_vault_endpoints,
+            client_config,
+            key_vault_request,
+        )?;
 
-        Ok(())
+        Ok(versions)
     }
 
     pub fn sign(&self, msg: &[u8]) -> Result<(Signature, RecoveryId)> {
@@ -163,12 +336,27 @@ impl EnclaveKey {
         Ok(sig)
     }
 
+    /// Decrypt the ciphertext with the current decryption key.
+    /// If it fails and the key has been rotated, try the prior one.
     pub fn decrypt(&self, ciphertext: &SodiumCiphertext) -> Result<Vec<u8>> {
         let dec_key = self
             .decryption_privkey
             .as_ref()
             .ok_or(EnclaveError::NotSetEnclaveDecKeyError)?;
-        ciphertext.decrypt(&dec_key).map_err(Into::into)
+        match (ciphertext.decrypt(&dec_key), &self.prior_decryption_privkey) {
+            (Ok(plaintext), _) => Ok(plaintext),
+            (Err(_), Some(prior_dec_key)) => ciphertext.decrypt(prior_dec_key).map_err(Into::into),
+            (Err(e), None) => Err(e.into()),
+        }
+    }
+
+    /// Whether the key is neither the current nor the prior one,
+    /// which means another node has rotated the shared decryption key since the last recovery.
+    pub fn is_unknown_key(&self, recipient_public_key: &SodiumPubKey) -> bool {
+        ![&self.decryption_privkey, &self.prior_decryption_privkey]
+            .iter()
+            .filter_map(|dec_key| dec_key.as_ref())
+            .any(|dec_key| &dec_key.public_key() == recipient_public_key)
     }
 
     pub fn verifying_key(&self) -> PublicKey {
@@ -206,6 +394,15 @@ impl EnclaveKey {
         Ok(sgx_report_data_t { d: report_data })
     }
 
+    /// Take the encryption public key out of REPORTDATA of another enclave laid out as `report_data`.
+    pub fn encryption_key_from_report_data(report_data: &[u8]) -> Result<SodiumPubKey> {
+        if report_data.len() != REPORT_DATA_SIZE {
+            return Err(anyhow!("The length of REPORTDATA must be {}", REPORT_DATA_SIZE).into());
+        }
+        SodiumPubKey::from_bytes(&report_data[HASHED_PUBKEY_SIZE..FILLED_REPORT_DATA_SIZE])
+            .map_err(|e| anyhow!("{:?}", e).into())
+    }
+
     fn verifying_key_into_array(&self) -> [u8; HASHED_PUBKEY_SIZE] {
         let pubkey = &self.verifying_key().serialize()[1..];
         let account_id = &pubkey.keccak256()[12..];
@@ -219,3 +416,102 @@ impl EnclaveKey {
         Ok(self.enclave_encryption_key()?.to_bytes())
     }
 }
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_rotate_dec_key,
+            test_decrypt_after_rotation,
+            test_unknown_key,
+            test_key_recovery_limiter,
+        )
+    }
+
+    fn new_enclave_key() -> EnclaveKey {
+        let mut csprng = SgxRng::new().unwrap();
+        EnclaveKey::new()
+            .unwrap()
+            .get_new_gen_dec_key(&mut csprng)
+            .unwrap()
+    }
+
+    fn test_rotate_dec_key() {
+        let mut csprng = SgxRng::new().unwrap();
+        let mut enclave_key = new_enclave_key();
+        let old_dec_key = enclave_key.enclave_decryption_key().unwrap().clone();
+
+        let new_dec_key = SodiumPrivateKey::from_random(&mut csprng).unwrap();
+        enclave_key.rotate_dec_key(new_dec_key.clone());
+        assert_eq!(
+            enclave_key.enclave_encryption_key().unwrap(),
+            new_dec_key.public_key()
+        );
+        assert_eq!(
+            enclave_key.prior_decryption_privkey,
+            Some(old_dec_key.clone())
+        );
+
+        // Rotating to the current key keeps the prior one.
+        enclave_key.rotate_dec_key(new_dec_key);
+        assert_eq!(enclave_key.prior_decryption_privkey, Some(old_dec_key));
+    }
+
+    fn test_decrypt_after_rotation() {
+        let mut csprng = SgxRng::new().unwrap();
+        let mut enclave_key = new_enclave_key();
+        let msg = b"test message";
+        let old_ciphertext = SodiumCiphertext::encrypt(
+            &mut csprng,
+            &enclave_key.enclave_encryption_key().unwrap(),
+            msg,
+        )
+        .unwrap();
+
+        // The ciphertexts encrypted before the rotation are decrypted by the prior key.
+        enclave_key.rotate_dec_key(SodiumPrivateKey::from_random(&mut csprng).unwrap());
+        assert_eq!(enclave_key.decrypt(&old_ciphertext).unwrap(), &msg[..]);
+        let new_ciphertext = SodiumCiphertext::encrypt(
+            &mut csprng,
+            &enclave_key.enclave_encryption_key().unwrap(),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(enclave_key.decrypt(&new_ciphertext).unwrap(), &msg[..]);
+
+        // Only the last prior key is kept.
+        enclave_key.rotate_dec_key(SodiumPrivateKey::from_random(&mut csprng).unwrap());
+        assert!(enclave_key.decrypt(&old_ciphertext).is_err());
+        assert_eq!(enclave_key.decrypt(&new_ciphertext).unwrap(), &msg[..]);
+    }
+
+    fn test_unknown_key() {
+        let mut csprng = SgxRng::new().unwrap();
+        let mut enclave_key = new_enclave_key();
+        let current_key = enclave_key.enclave_encryption_key().unwrap();
+        assert!(!enclave_key.is_unknown_key(&current_key));
+
+        // The key rotated by another node.
+        let others_key = SodiumPrivateKey::from_random(&mut csprng)
+            .unwrap()
+            .public_key();
+        assert!(enclave_key.is_unknown_key(&others_key));
+
+        // The prior key is still known after the rotation.
+        enclave_key.rotate_dec_key(SodiumPrivateKey::from_random(&mut csprng).unwrap());
+        assert!(!enclave_key.is_unknown_key(&current_key));
+    }
+
+    fn test_key_recovery_limiter() {
+        let limiter = KeyRecoveryLimiter::new(Duration::from_secs(60));
+        let now = SystemTime::now();
+        assert!(limiter.try_acquire(now));
+        assert!(!limiter.try_acquire(now + Duration::from_secs(59)));
+        assert!(!limiter.try_acquire(now - Duration::from_secs(1)));
+        assert!(limiter.try_acquire(now + Duration::from_secs(60)));
+        assert!(!limiter.try_acquire(now + Duration::from_secs(61)));
+    }
+}
@@ -1,25 +1,67 @@
+use crate::context::AnonifyEnclaveContext;
+use anonify_ecall_types::{cmd::GET_GROUP_INFO_CMD, *};
 use anyhow::{anyhow, Result};
-use frame_common::TreeKemCiphertext;
+use frame_common::{state_types::StateCounter, TreeKemCiphertext};
+use frame_enclave::StateRuntimeEnclaveUseCase;
 use frame_runtime::traits::*;
 use frame_treekem::{
     handshake::{HandshakeParams, PathSecretSource},
-    AppKeyChain, GroupState, Handshake, PathSecret, StorePathSecrets,
+    AppKeyChain, GroupState, Handshake, PathSecret, StorePathSecrets, Welcome,
 };
-use std::vec::Vec;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    mem,
+    vec::Vec,
+};
+
+/// The default number of state counters for which the previous epoch's keychains are retained
+/// after a handshake.
+pub const DEFAULT_PRIOR_EPOCH_RETENTION: u32 = 100;
+/// The maximum number of previous epochs whose keychains are retained at once,
+/// so that the handshakes applied in a row don't discard the messages of the earlier epochs.
+pub const MAX_PRIOR_EPOCHS: usize = 8;
 
 #[derive(Clone, Debug)]
 pub struct GroupKey {
     group_state: GroupState,
     sender_keychain: AppKeyChain,
     receiver_keychain: AppKeyChain,
-    max_roster_idx: usize,
+    /// The keychains of the previous epochs from the oldest one, retained so that the messages encrypted
+    /// before the handshakes were applied can still be processed.
+    prior_epoch_keychains: VecDeque<PriorEpochKeyChains>,
+    /// The number of state counters for which the previous epoch's keychains are retained.
+    prior_epoch_retention: u32,
     source: PathSecretSource,
+    /// The epoch which each member's last handshake moved the group to, by roster index.
+    member_epochs: BTreeMap<u32, u32>,
+    /// The MRENCLAVE of each member's enclave which this enclave has attested, by roster index.
+    member_mr_enclaves: BTreeMap<u32, [u8; 32]>,
+    /// Whether this member joined with a welcome message and hasn't sent an update handshake yet.
+    /// The member who sent the welcome knows the secret of the leaf until then.
+    requires_update: bool,
+}
+
+/// The sender and receiver keychains of a previous epoch.
+#[derive(Clone, Debug)]
+struct PriorEpochKeyChains {
+    sender_keychain: AppKeyChain,
+    receiver_keychain: AppKeyChain,
+    /// The keychains are discarded once the state counter passes this one.
+    /// This node can't tell whether the other members still have messages of the epoch in flight,
+    /// so they are retained for the whole window even after its own messages are processed.
+    retained_until: StateCounter,
+}
+
+impl PriorEpochKeyChains {
+    fn is_expired(&self, state_counter: StateCounter) -> bool {
+        state_counter > self.retained_until
+    }
 }
 
 impl GroupKey {
     pub fn new(
         my_roster_idx: usize,
-        max_roster_idx: usize,
+        prior_epoch_retention: u32,
         source: PathSecretSource,
     ) -> Result<Self> {
         let group_state = GroupState::new(my_roster_idx)?;
@@ -30,10 +72,127 @@ impl GroupKey {
             group_state,
             sender_keychain,
             receiver_keychain,
-            max_roster_idx,
+            prior_epoch_keychains: VecDeque::new(),
+            prior_epoch_retention,
             source,
+            member_epochs: BTreeMap::new(),
+            member_mr_enclaves: BTreeMap::new(),
+            requires_update: false,
         })
     }
+
+    /// Record the MRENCLAVE of a member's enclave attested by this enclave, e.g. its own one or a joiner's one.
+    pub fn record_member_mr_enclave(&mut self, roster_idx: u32, mr_enclave: [u8; 32]) {
+        self.member_mr_enclaves.insert(roster_idx, mr_enclave);
+    }
+
+    /// Returns the sender and receiver keychains used for messages encrypted in the `msg_epoch`.
+    /// A previous epoch's keychains are selected only while they are retained,
+    /// otherwise the current ones are returned and the epoch check in decryption rejects the message.
+    fn keychains_mut(&mut self, msg_epoch: u32) -> (&mut AppKeyChain, &mut AppKeyChain) {
+        match self
+            .prior_epoch_keychains
+            .iter_mut()
+            .find(|prior| prior.receiver_keychain.epoch() == msg_epoch)
+        {
+            Some(prior) => (&mut prior.sender_keychain, &mut prior.receiver_keychain),
+            None => (&mut self.sender_keychain, &mut self.receiver_keychain),
+        }
+    }
+
+    /// Returns the roster, the epoch and each member's keychain generations, epoch and MRENCLAVE for debugging desyncs.
+    pub fn group_info(&self) -> output::ReturnGroupInfo {
+        let occupied_leaves = self.group_state.occupied_leaves();
+        let roster_len = occupied_leaves
+            .len()
+            .max(self.sender_keychain.roster_len())
+            .max(self.receiver_keychain.roster_len());
+        let members = (0..roster_len)
+            .map(|roster_idx| output::GroupMember {
+                roster_idx: roster_idx as u32,
+                is_occupied: occupied_leaves.get(roster_idx).copied().unwrap_or(false),
+                sender_generation: self.sender_keychain.generation(roster_idx).ok(),
+                receiver_generation: self.receiver_keychain.generation(roster_idx).ok(),
+                epoch: self.member_epochs.get(&(roster_idx as u32)).copied(),
+                mr_enclave: self.member_mr_enclaves.get(&(roster_idx as u32)).copied(),
+            })
+            .collect();
+        let prior_epoch = self
+            .prior_epoch_keychains
+            .back()
+            .map(|prior| prior.receiver_keychain.epoch());
+
+        output::ReturnGroupInfo::new(
+            self.group_state.epoch(),
+            self.group_state.my_roster_idx(),
+            prior_epoch,
+            members,
+            self.requires_update,
+        )
+    }
+
+    /// Whether the roster index is filled in the group, so it can't be added again.
+    pub fn is_member(&self, roster_idx: u32) -> bool {
+        self.group_state
+            .occupied_leaves()
+            .get(roster_idx as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Require an update handshake of this member before it sends any messages,
+    /// after it joined with a welcome message.
+    /// It is persisted with the path secrets, so that it survives the restarts of the node.
+    pub fn require_update(&mut self, store_path_secrets: &StorePathSecrets) -> Result<()> {
+        store_path_secrets.save_requires_update(true)?;
+        self.requires_update = true;
+
+        Ok(())
+    }
+
+    /// Restore whether an update handshake is required from the local storage.
+    /// On a node recovered from the key-vault backups instead, it's required again
+    /// when the welcome message is recovered with the handshakes.
+    pub fn restore_requires_update(&mut self, store_path_secrets: &StorePathSecrets) -> Result<()> {
+        self.requires_update = store_path_secrets.load_requires_update()?;
+
+        Ok(())
+    }
+
+    /// Create an add handshake on behalf of a new member and a welcome message for it.
+    pub fn create_welcome(&self, joiner_roster_idx: u32) -> Result<(HandshakeParams, Welcome)> {
+        self.group_state
+            .create_welcome(joiner_roster_idx, &self.source)
+    }
+
+    /// Whether the received handshake adds me to the group but I haven't followed the group's handshakes,
+    /// so the group state has to be installed from a welcome message before processing it.
+    pub fn needs_welcome(&self, handshake: &HandshakeParams) -> bool {
+        let my_roster_idx = self.group_state.my_roster_idx();
+
+        handshake.roster_idx() == my_roster_idx
+            && !self.is_member(my_roster_idx)
+            && handshake.prior_epoch() != self.group_state.epoch()
+    }
+
+    /// Start from the group state in the welcome message.
+    /// The keychains are reset, so any messages encrypted before joining stay unreadable.
+    pub fn accept_welcome(
+        &mut self,
+        welcome: Welcome,
+        store_path_secrets: &StorePathSecrets,
+    ) -> Result<()> {
+        self.group_state = GroupState::from_welcome(
+            self.group_state.my_roster_idx(),
+            welcome,
+            store_path_secrets,
+        )?;
+        self.sender_keychain = AppKeyChain::default();
+        self.receiver_keychain = AppKeyChain::default();
+        self.prior_epoch_keychains.clear();
+
+        Ok(())
+    }
 }
 
 impl GroupKeyOps for GroupKey {
@@ -47,32 +206,71 @@ impl GroupKeyOps for GroupKey {
         &mut self,
         store_path_secrets: &StorePathSecrets,
         handshake: &HandshakeParams,
+        state_counter: StateCounter,
         #[cfg(feature = "backup-enable")] recover_path_secret: F,
     ) -> Result<()> {
         let keychain = self.group_state.process_handshake(
             store_path_secrets,
             handshake,
             &self.source,
-            self.max_roster_idx as u32,
             #[cfg(feature = "backup-enable")]
             recover_path_secret,
         )?;
-        // TODO: If the handshake transaction is flying out the air, wait updating the sender_keychain until the all remaining messages are processed.
-        // The number of remaining messages are difference between sender_keychain's generation and receiver_keychain's one.
-        self.sender_keychain = keychain.clone();
-        self.receiver_keychain = keychain;
+        self.member_epochs
+            .insert(handshake.roster_idx(), self.group_state.epoch());
+        // My own handshake replaces the leaf secret which the member who sent the welcome knows.
+        if handshake.roster_idx() == self.group_state.my_roster_idx() && self.requires_update {
+            store_path_secrets.save_requires_update(false)?;
+            self.requires_update = false;
+        }
+        let prior_sender_keychain = mem::replace(&mut self.sender_keychain, keychain.clone());
+        let prior_receiver_keychain = mem::replace(&mut self.receiver_keychain, keychain);
+
+        // If the handshake transaction is flying out the air, the messages encrypted in the previous epochs
+        // are still processed with their keychains until the retention period passes.
+        // The keychains are empty before joining the group, so there is nothing to retain.
+        if prior_receiver_keychain.roster_len() != 0 {
+            let retained_until = StateCounter::new(
+                state_counter
+                    .as_raw()
+                    .saturating_add(self.prior_epoch_retention),
+            );
+            if self.prior_epoch_keychains.len() == MAX_PRIOR_EPOCHS {
+                self.prior_epoch_keychains.pop_front();
+            }
+            self.prior_epoch_keychains.push_back(PriorEpochKeyChains {
+                sender_keychain: prior_sender_keychain,
+                receiver_keychain: prior_receiver_keychain,
+                retained_until,
+            });
+        }
 
         Ok(())
     }
 
     fn encrypt(&self, plaintext: Vec<u8>) -> Result<TreeKemCiphertext> {
+        if self.requires_update {
+            return Err(anyhow!(
+                "An update handshake is required after joining with a welcome message"
+            ));
+        }
         self.sender_keychain
             .encrypt_msg(plaintext, &self.group_state)
     }
 
     fn decrypt(&self, app_msg: &TreeKemCiphertext) -> Result<Option<Vec<u8>>> {
-        self.receiver_keychain
-            .decrypt_msg(&app_msg, &self.group_state)
+        match self
+            .prior_epoch_keychains
+            .iter()
+            .find(|prior| prior.receiver_keychain.epoch() == app_msg.epoch())
+        {
+            Some(prior) => prior
+                .receiver_keychain
+                .decrypt_msg(&app_msg, &self.group_state),
+            None => self
+                .receiver_keychain
+                .decrypt_msg(&app_msg, &self.group_state),
+        }
     }
 
     /// Ratchet sender's keychain per a transaction
@@ -80,71 +278,628 @@ impl GroupKeyOps for GroupKey {
         self.sender_keychain.ratchet(roster_idx)
     }
 
-    /// Ratchet receiver's keychain per a transaction
-    fn receiver_ratchet(&mut self, roster_idx: usize) -> Result<()> {
-        self.receiver_keychain.ratchet(roster_idx)
-    }
-
-    /// Syncing the sender and receiver app keychains
-    fn sync_ratchet(&mut self, roster_idx: usize, msg_gen: u32) -> Result<()> {
-        let sender_gen = self.sender_keychain.generation(roster_idx)?;
-        let receiver_gen = self.receiver_keychain.generation(roster_idx)?;
-
-        match sender_gen.checked_sub(receiver_gen) {
-            // syncing the sender and receiver app keychains
-            // Used for:
-            // - receiving messages from other TEE nodes
-            // - the recovery phase
-            Some(0) => {
-                println!(
-                    "[debug] syncing the sender and receiver app keychains in the recovery phase. The current generation is {:?}",
-                    receiver_gen
-                );
-                self.sender_ratchet(roster_idx)
-            },
-            // It's okay if the sender generation is only one bigger than receiver's.
-            Some(1) => Ok(()),
-            // The case there are multiple messages before the events are synced
-            Some(_) => {
-                // Even if the generation of the message is tampered with,
-                // there is no problem with the legitimacy of the state.
-                match msg_gen.checked_sub(receiver_gen) {
-                    Some(1) => Ok(()),
-                    // If an error occurs after ratcheting the sender's keychain,
-                    // the generation of the received message will be discontinuous against that of the receiver keychain,
-                    // so ratchet the receiver's keychain by the difference in order to be consistent.
-                    Some(diff) => {
-                        println!(
-                            "[warn] the generation of the received message will be discontinuous, so ratchet the receiver's keychain by {:?} times",
-                            diff - 1
-                        );
-                        for _ in 0..(diff - 1) {
-                            self.receiver_ratchet(roster_idx)?;
-                        }
-                        Ok(())
-                    },
-                    None => {
-                        Err(anyhow!(
-                            "The generation of the receiver keychain ({:?}) must not be bigger than the that of the received message ({:?}).
-                            Your TEE instance may not be synced to the latest state yet.",
-                            receiver_gen,
-                            msg_gen
-                        ))
+    /// Ratchet receiver's keychain of the message's epoch per a transaction
+    fn receiver_ratchet(&mut self, roster_idx: usize, msg_epoch: u32) -> Result<()> {
+        let (_, receiver_keychain) = self.keychains_mut(msg_epoch);
+        receiver_keychain.ratchet(roster_idx)
+    }
+
+    /// Syncing the sender and receiver app keychains of the message's epoch
+    fn sync_ratchet(&mut self, roster_idx: usize, msg_epoch: u32, msg_gen: u32) -> Result<()> {
+        let (sender_keychain, receiver_keychain) = self.keychains_mut(msg_epoch);
+        sync_keychains(sender_keychain, receiver_keychain, roster_idx, msg_gen)
+    }
+
+    fn prune_prior_epoch(&mut self, state_counter: StateCounter) -> Result<()> {
+        self.prior_epoch_keychains
+            .retain(|prior| !prior.is_expired(state_counter));
+
+        Ok(())
+    }
+
+    fn my_roster_idx(&self) -> u32 {
+        self.group_state.my_roster_idx()
+    }
+}
+
+/// A read-only engine to inspect the TreeKEM group membership
+#[derive(Debug, Clone)]
+pub struct GroupInfoGetter<'c> {
+    enclave_context: &'c AnonifyEnclaveContext,
+}
+
+impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for GroupInfoGetter<'c> {
+    type EI = input::Empty;
+    type EO = output::ReturnGroupInfo;
+    const ENCLAVE_USE_CASE_ID: u32 = GET_GROUP_INFO_CMD;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c AnonifyEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn eval_policy(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        Ok(self.enclave_context.read_group_key().group_info())
+    }
+}
+
+/// Syncing the sender and receiver app keychains of the same epoch
+fn sync_keychains(
+    sender_keychain: &mut AppKeyChain,
+    receiver_keychain: &mut AppKeyChain,
+    roster_idx: usize,
+    msg_gen: u32,
+) -> Result<()> {
+    let sender_gen = sender_keychain.generation(roster_idx)?;
+    let receiver_gen = receiver_keychain.generation(roster_idx)?;
+
+    match sender_gen.checked_sub(receiver_gen) {
+        // syncing the sender and receiver app keychains
+        // Used for:
+        // - receiving messages from other TEE nodes
+        // - the recovery phase
+        Some(0) => {
+            println!(
+                "[debug] syncing the sender and receiver app keychains in the recovery phase. The current generation is {:?}",
+                receiver_gen
+            );
+            sender_keychain.ratchet(roster_idx)
+        },
+        // It's okay if the sender generation is only one bigger than receiver's.
+        Some(1) => Ok(()),
+        // The case there are multiple messages before the events are synced
+        Some(_) => {
+            // Even if the generation of the message is tampered with,
+            // there is no problem with the legitimacy of the state.
+            match msg_gen.checked_sub(receiver_gen) {
+                Some(1) => Ok(()),
+                // If an error occurs after ratcheting the sender's keychain,
+                // the generation of the received message will be discontinuous against that of the receiver keychain,
+                // so ratchet the receiver's keychain by the difference in order to be consistent.
+                Some(diff) => {
+                    println!(
+                        "[warn] the generation of the received message will be discontinuous, so ratchet the receiver's keychain by {:?} times",
+                        diff - 1
+                    );
+                    for _ in 0..(diff - 1) {
+                        receiver_keychain.ratchet(roster_idx)?;
                     }
+                    Ok(())
+                },
+                None => {
+                    Err(anyhow!(
+                        "The generation of the receiver keychain ({:?}) must not be bigger than the that of the received message ({:?}).
+                        Your TEE instance may not be synced to the latest state yet.",
+                        receiver_gen,
+                        msg_gen
+                    ))
                 }
             }
-            // It's an error case if the receiver generation is bigger than sender's
-            // Here is the case when you sent a transaction with an old sender keychain during recovery
-            None => Err(anyhow!(
-                "The generation of the receiver keychain ({:?}) must not be bigger than the that of the sender keychain ({:?}).
-                Your TEE instance may not be synced to the latest state yet.",
-                receiver_gen,
-                sender_gen
-            )),
         }
+        // It's an error case if the receiver generation is bigger than sender's
+        // Here is the case when you sent a transaction with an old sender keychain during recovery
+        None => Err(anyhow!(
+            "The generation of the receiver keychain ({:?}) must not be bigger than the that of the sender keychain ({:?}).
+            Your TEE instance may not be synced to the latest state yet.",
+            receiver_gen,
+            sender_gen
+        )),
     }
+}
 
-    fn my_roster_idx(&self) -> u32 {
-        self.group_state.my_roster_idx()
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use frame_config::CMD_DEC_SECRET_DIR;
+    use frame_mra_tls::key_vault::{
+        request::{
+            KeyVaultCmd, KeyVaultCommand, KeyVaultRequest, RawKeyVaultRequest,
+            StoreWelcomeRequestBody,
+        },
+        response::{KeyVaultResponse, RecoveredWelcome},
+    };
+    use frame_sodium::{rng::SgxRng, SodiumCiphertext, SodiumPrivateKey};
+    use frame_treekem::{handshake::PathSecretKVS, init_path_secret_kvs};
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_stale_epoch_msg_after_handshake,
+            test_prior_epoch_expires_after_retention,
+            test_prior_epochs_after_handshakes_in_a_row,
+            test_group_info,
+            test_add_members_one_by_one,
+            test_join_with_welcome,
+            test_welcome_request_for_large_roster,
+        )
+    }
+
+    const PRIOR_EPOCH_RETENTION: u32 = 3;
+
+    fn setup_group_keys() -> (GroupKey, GroupKey, StorePathSecrets) {
+        let mut kvs = PathSecretKVS::new();
+        init_path_secret_kvs(&mut kvs, 10, 10);
+        let source = PathSecretSource::LocalTestKV(kvs);
+        let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
+
+        let group_key1 = GroupKey::new(0, PRIOR_EPOCH_RETENTION, source.clone()).unwrap();
+        let group_key2 = GroupKey::new(1, PRIOR_EPOCH_RETENTION, source).unwrap();
+
+        (group_key1, group_key2, store_path_secrets)
+    }
+
+    fn do_handshake(
+        sender: &mut GroupKey,
+        receiver: &mut GroupKey,
+        store_path_secrets: &StorePathSecrets,
+        state_counter: StateCounter,
+    ) {
+        let (handshake, _) = sender.create_handshake().unwrap();
+        for group_key in [sender, receiver].iter_mut() {
+            group_key
+                .process_handshake(
+                    store_path_secrets,
+                    &handshake,
+                    state_counter,
+                    #[cfg(feature = "backup-enable")]
+                    |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                )
+                .unwrap();
+        }
+    }
+
+    fn send(sender: &mut GroupKey, msg: &[u8]) -> TreeKemCiphertext {
+        let roster_idx = sender.my_roster_idx() as usize;
+        sender.sender_ratchet(roster_idx).unwrap();
+        sender.encrypt(msg.to_vec()).unwrap()
+    }
+
+    fn receive(
+        receiver: &mut GroupKey,
+        ciphertext: &TreeKemCiphertext,
+        state_counter: StateCounter,
+    ) -> Result<Option<Vec<u8>>> {
+        let roster_idx = ciphertext.roster_idx() as usize;
+        receiver.prune_prior_epoch(state_counter)?;
+        receiver.sync_ratchet(roster_idx, ciphertext.epoch(), ciphertext.generation())?;
+        receiver.receiver_ratchet(roster_idx, ciphertext.epoch())?;
+        receiver.decrypt(ciphertext)
+    }
+
+    fn test_stale_epoch_msg_after_handshake() {
+        let msg = b"stale epoch message";
+        let (mut group_key1, mut group_key2, store_path_secrets) = setup_group_keys();
+
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(1),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(2),
+        );
+
+        // Both messages are flying when the update handshake is applied.
+        let stale_ciphertext1 = send(&mut group_key1, msg);
+        let stale_ciphertext2 = send(&mut group_key1, msg);
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(3),
+        );
+        assert!(stale_ciphertext1.epoch() < group_key1.group_state.epoch());
+
+        for (i, ciphertext) in [stale_ciphertext1, stale_ciphertext2].iter().enumerate() {
+            let state_counter = StateCounter::new(4 + i as u32);
+            for group_key in [&mut group_key1, &mut group_key2].iter_mut() {
+                let plaintext = receive(group_key, ciphertext, state_counter).unwrap();
+                assert_eq!(plaintext.unwrap().as_slice(), msg);
+            }
+        }
+
+        // The other member's message of the previous epoch is still processed
+        // after all of this member's ones are consumed.
+        let stale_ciphertext3 = {
+            let roster_idx = group_key2.my_roster_idx() as usize;
+            let prior = group_key2.prior_epoch_keychains.back_mut().unwrap();
+            prior.sender_keychain.ratchet(roster_idx).unwrap();
+            prior
+                .sender_keychain
+                .encrypt_msg(msg.to_vec(), &group_key2.group_state)
+                .unwrap()
+        };
+        let plaintext = receive(&mut group_key1, &stale_ciphertext3, StateCounter::new(6)).unwrap();
+        assert_eq!(plaintext.unwrap().as_slice(), msg);
+        assert_eq!(group_key1.prior_epoch_keychains.len(), 1);
+
+        // Messages in the new epoch are processed as usual.
+        let ciphertext = send(&mut group_key1, msg);
+        for group_key in [&mut group_key1, &mut group_key2].iter_mut() {
+            let plaintext = receive(group_key, &ciphertext, StateCounter::new(6)).unwrap();
+            assert_eq!(plaintext.unwrap().as_slice(), msg);
+        }
+    }
+
+    fn test_prior_epoch_expires_after_retention() {
+        let msg = b"expired epoch message";
+        let (mut group_key1, mut group_key2, store_path_secrets) = setup_group_keys();
+
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(1),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(2),
+        );
+
+        let stale_ciphertext = send(&mut group_key1, msg);
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(3),
+        );
+
+        let expired_counter = StateCounter::new(3 + PRIOR_EPOCH_RETENTION + 1);
+        assert!(receive(&mut group_key2, &stale_ciphertext, expired_counter).is_err());
+        assert!(group_key2.prior_epoch_keychains.is_empty());
+    }
+
+    fn test_prior_epochs_after_handshakes_in_a_row() {
+        let msg = b"earlier epoch message";
+        let (mut group_key1, mut group_key2, store_path_secrets) = setup_group_keys();
+
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(1),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(2),
+        );
+
+        // Two handshakes are applied before the message is received.
+        let stale_ciphertext = send(&mut group_key1, msg);
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(3),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(4),
+        );
+        assert_eq!(stale_ciphertext.epoch() + 2, group_key2.group_state.epoch());
+
+        let plaintext = receive(&mut group_key2, &stale_ciphertext, StateCounter::new(5)).unwrap();
+        assert_eq!(plaintext.unwrap().as_slice(), msg);
+
+        // Each epoch expires by its own retention period.
+        group_key2
+            .prune_prior_epoch(StateCounter::new(3 + PRIOR_EPOCH_RETENTION + 1))
+            .unwrap();
+        assert_eq!(group_key2.prior_epoch_keychains.len(), 1);
+        group_key2
+            .prune_prior_epoch(StateCounter::new(4 + PRIOR_EPOCH_RETENTION + 1))
+            .unwrap();
+        assert!(group_key2.prior_epoch_keychains.is_empty());
+    }
+
+    fn test_group_info() {
+        let msg = b"group info message";
+        let (mut group_key1, mut group_key2, store_path_secrets) = setup_group_keys();
+
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(1),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(2),
+        );
+        let ciphertext = send(&mut group_key1, msg);
+
+        let info = group_key1.group_info();
+        assert_eq!(info.epoch, group_key1.group_state.epoch());
+        assert_eq!(info.my_roster_idx, 0);
+        assert!(info.prior_epoch.is_some());
+        assert!(info.members.iter().all(|member| member.is_occupied));
+        assert_eq!(info.members[0].sender_generation, Some(1));
+        assert_eq!(info.members[0].receiver_generation, Some(0));
+        // Each member's epoch is the one which its last handshake moved the group to.
+        assert_eq!(info.members[0].epoch, Some(info.epoch - 1));
+        assert_eq!(info.members[1].epoch, Some(info.epoch));
+        assert!(info
+            .members
+            .iter()
+            .all(|member| member.mr_enclave.is_none()));
+
+        group_key1.record_member_mr_enclave(0, [1u8; 32]);
+        let info = group_key1.group_info();
+        assert_eq!(info.members[0].mr_enclave, Some([1u8; 32]));
+        assert_eq!(info.members[1].mr_enclave, None);
+
+        receive(&mut group_key1, &ciphertext, StateCounter::new(3)).unwrap();
+        let info = group_key1.group_info();
+        assert_eq!(info.members[0].receiver_generation, Some(1));
+    }
+
+    fn test_add_members_one_by_one() {
+        let msg = b"dynamic group message";
+        let mut kvs = PathSecretKVS::new();
+        init_path_secret_kvs(&mut kvs, 10, 10);
+        let source = PathSecretSource::LocalTestKV(kvs);
+        let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
+        let mut group_keys: Vec<GroupKey> = (0..5)
+            .map(|roster_idx| {
+                GroupKey::new(roster_idx, PRIOR_EPOCH_RETENTION, source.clone()).unwrap()
+            })
+            .collect();
+
+        for new_member in 0..group_keys.len() {
+            let (handshake, _) = group_keys[new_member].create_handshake().unwrap();
+            let state_counter = StateCounter::new(new_member as u32 + 1);
+            // The members who haven't joined yet also follow the handshakes on-chain.
+            for group_key in group_keys.iter_mut() {
+                group_key
+                    .process_handshake(
+                        &store_path_secrets,
+                        &handshake,
+                        state_counter,
+                        #[cfg(feature = "backup-enable")]
+                        |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                    )
+                    .unwrap();
+            }
+
+            // The tree grows just enough to include the new member.
+            let info = group_keys[new_member].group_info();
+            assert_eq!(info.members.len(), new_member + 1);
+            assert!(info.members.iter().all(|member| member.is_occupied));
+
+            let ciphertext = send(&mut group_keys[new_member], msg);
+            for group_key in group_keys[..=new_member].iter_mut() {
+                let plaintext = receive(group_key, &ciphertext, state_counter).unwrap();
+                assert_eq!(plaintext.unwrap().as_slice(), msg);
+            }
+            // The members out of the tree just ignore the message.
+            for group_key in group_keys[new_member + 1..].iter() {
+                assert!(group_key.decrypt(&ciphertext).unwrap().is_none());
+            }
+        }
+    }
+
+    fn test_join_with_welcome() {
+        let msg = b"welcome message";
+        let (mut group_key1, mut group_key2, store_path_secrets) = setup_group_keys();
+        let source = group_key1.source.clone();
+
+        do_handshake(
+            &mut group_key1,
+            &mut group_key2,
+            &store_path_secrets,
+            StateCounter::new(1),
+        );
+        do_handshake(
+            &mut group_key2,
+            &mut group_key1,
+            &store_path_secrets,
+            StateCounter::new(2),
+        );
+        let historical_ciphertext = send(&mut group_key1, msg);
+        receive(
+            &mut group_key2,
+            &historical_ciphertext,
+            StateCounter::new(3),
+        )
+        .unwrap();
+
+        // The new member doesn't follow any previous handshakes.
+        let mut group_key3 = GroupKey::new(2, PRIOR_EPOCH_RETENTION, source).unwrap();
+        let (handshake, welcome) = group_key1.create_welcome(2).unwrap();
+        assert!(group_key3.needs_welcome(&handshake));
+        assert!(!group_key2.needs_welcome(&handshake));
+
+        let welcome = Welcome::decode(&welcome.encode()).unwrap();
+        group_key3
+            .accept_welcome(welcome, &store_path_secrets)
+            .unwrap();
+        assert!(!group_key3.needs_welcome(&handshake));
+
+        for group_key in [&mut group_key1, &mut group_key2, &mut group_key3].iter_mut() {
+            group_key
+                .process_handshake(
+                    &store_path_secrets,
+                    &handshake,
+                    StateCounter::new(4),
+                    #[cfg(feature = "backup-enable")]
+                    |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            group_key3.group_state.epoch(),
+            group_key1.group_state.epoch()
+        );
+        assert!(group_key1.is_member(2));
+
+        // The new member must send an update handshake before any messages,
+        // since the member who sent the welcome knows the secret of its leaf.
+        group_key3.require_update(&store_path_secrets).unwrap();
+        assert!(group_key3.group_info().requires_update);
+
+        // It is still required after the node restarts.
+        let mut restored_group_key3 =
+            GroupKey::new(2, PRIOR_EPOCH_RETENTION, group_key1.source.clone()).unwrap();
+        restored_group_key3
+            .restore_requires_update(&store_path_secrets)
+            .unwrap();
+        assert!(restored_group_key3.group_info().requires_update);
+        assert!(restored_group_key3.encrypt(msg.to_vec()).is_err());
+
+        assert!(group_key3.encrypt(msg.to_vec()).is_err());
+        let (update, _) = group_key3.create_handshake().unwrap();
+        for group_key in [&mut group_key1, &mut group_key2, &mut group_key3].iter_mut() {
+            group_key
+                .process_handshake(
+                    &store_path_secrets,
+                    &update,
+                    StateCounter::new(5),
+                    #[cfg(feature = "backup-enable")]
+                    |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                )
+                .unwrap();
+        }
+        assert!(!group_key3.group_info().requires_update);
+        restored_group_key3
+            .restore_requires_update(&store_path_secrets)
+            .unwrap();
+        assert!(!restored_group_key3.group_info().requires_update);
+
+        for (i, sender_idx) in [0usize, 2].iter().enumerate() {
+            let state_counter = StateCounter::new(6 + i as u32);
+            let mut group_keys = [&mut group_key1, &mut group_key2, &mut group_key3];
+            let ciphertext = send(&mut *group_keys[*sender_idx], msg);
+            for group_key in group_keys.iter_mut() {
+                let plaintext = receive(group_key, &ciphertext, state_counter).unwrap();
+                assert_eq!(plaintext.unwrap().as_slice(), msg);
+            }
+        }
+
+        // The historical ciphertexts stay unreadable to the new member.
+        assert!(!matches!(
+            group_key3.decrypt(&historical_ciphertext),
+            Ok(Some(_))
+        ));
+        assert!(group_key1.create_welcome(2).is_err());
+    }
+
+    fn test_welcome_request_for_large_roster() {
+        // The group of the state runtime nodes is expected to have dozens of members.
+        const ROSTER_LEN: u32 = 64;
+        let mut kvs = PathSecretKVS::new();
+        init_path_secret_kvs(&mut kvs, ROSTER_LEN as usize, ROSTER_LEN as usize);
+        let source = PathSecretSource::LocalTestKV(kvs);
+        let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
+
+        // The first member adds all the others with welcome messages.
+        let mut group_key = GroupKey::new(0, PRIOR_EPOCH_RETENTION, source.clone()).unwrap();
+        let (handshake, _) = group_key.create_handshake().unwrap();
+        let mut handshakes = vec![handshake];
+        for joiner in 1..ROSTER_LEN - 1 {
+            let handshake = handshakes.last().unwrap();
+            group_key
+                .process_handshake(
+                    &store_path_secrets,
+                    handshake,
+                    StateCounter::new(joiner),
+                    #[cfg(feature = "backup-enable")]
+                    |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                )
+                .unwrap();
+            let (handshake, _) = group_key.create_welcome(joiner).unwrap();
+            handshakes.push(handshake);
+        }
+        group_key
+            .process_handshake(
+                &store_path_secrets,
+                handshakes.last().unwrap(),
+                StateCounter::new(ROSTER_LEN - 1),
+                #[cfg(feature = "backup-enable")]
+                |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+            )
+            .unwrap();
+        let joiner = ROSTER_LEN - 1;
+        let (handshake, welcome) = group_key.create_welcome(joiner).unwrap();
+
+        // The welcome message goes through the key-vault node as the sender and the joiner do.
+        let mut csprng = SgxRng::new().unwrap();
+        let dec_key = SodiumPrivateKey::from_random(&mut csprng).unwrap();
+        let encrypted_welcome =
+            SodiumCiphertext::encrypt(&mut csprng, &dec_key.public_key(), &welcome.encode())
+                .unwrap();
+        let request = KeyVaultRequest::new(
+            KeyVaultCmd::StoreWelcome,
+            StoreWelcomeRequestBody::new(
+                joiner,
+                handshake.prior_epoch(),
+                encrypted_welcome.to_bytes(),
+            ),
+        );
+        let request = serde_json::to_vec(&request).unwrap();
+        assert!(request.len() as u64 <= KeyVaultCmd::StoreWelcome.max_frame_len());
+        let stored = match RawKeyVaultRequest::decode(&request)
+            .unwrap()
+            .into_command()
+            .unwrap()
+        {
+            KeyVaultCommand::StoreWelcome(body) => body.encrypted_welcome().to_vec(),
+            command => panic!("Unexpected command: {:?}", command.cmd()),
+        };
+
+        let response = KeyVaultResponse::encode(
+            1,
+            Ok(serde_json::to_value(&RecoveredWelcome::new(stored)).unwrap()),
+        )
+        .unwrap();
+        assert!(response.len() as u64 <= KeyVaultCmd::RecoverWelcome.max_frame_len());
+        let recovered: RecoveredWelcome =
+            KeyVaultResponse::decode(serde_json::from_slice(&response).unwrap()).unwrap();
+
+        let plaintext = SodiumCiphertext::from_bytes(recovered.encrypted_welcome())
+            .unwrap()
+            .decrypt(&dec_key)
+            .unwrap();
+        let welcome = Welcome::decode(&plaintext).unwrap();
+        let mut joiner_group_key = GroupKey::new(joiner, PRIOR_EPOCH_RETENTION, source).unwrap();
+        joiner_group_key
+            .accept_welcome(welcome, &store_path_secrets)
+            .unwrap();
+        for group_key in [&mut group_key, &mut joiner_group_key].iter_mut() {
+            group_key
+                .process_handshake(
+                    &store_path_secrets,
+                    &handshake,
+                    StateCounter::new(ROSTER_LEN),
+                    #[cfg(feature = "backup-enable")]
+                    |_, _| Err(anyhow!("The path secret must be provided by the test KVS")),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            joiner_group_key.group_info().members.len(),
+            ROSTER_LEN as usize
+        );
+        assert_eq!(
+            joiner_group_key.group_state.epoch(),
+            group_key.group_state.epoch()
+        );
     }
 }
@@ -1,15 +1,38 @@
 use anonify_ecall_types::cmd::FETCH_HANDSHAKE_TREEKEM_CMD;
+use anonify_ecall_types::cmd::GET_ATTESTATION_EVIDENCE_CMD;
 use anonify_ecall_types::cmd::SEND_HANDSHAKE_TREEKEM_CMD;
+#[cfg(feature = "backup-enable")]
+use anonify_ecall_types::cmd::SEND_WELCOME_TREEKEM_CMD;
 use anonify_ecall_types::*;
+#[cfg(feature = "backup-enable")]
+use anyhow::ensure;
 use anyhow::{anyhow, Result};
 use frame_common::crypto::Sha256;
+#[cfg(feature = "backup-enable")]
+use frame_config::{ATTESTATION_MAX_AGE_SECS, DCAP_ROOT_CERT, IAS_ROOT_CERT};
 use frame_enclave::StateRuntimeEnclaveUseCase;
 #[cfg(feature = "backup-enable")]
-use frame_mra_tls::key_vault::request::BackupPathSecretRequestBody;
+use frame_mra_tls::key_vault::request::{BackupPathSecretRequestBody, StoreWelcomeRequestBody};
 use frame_runtime::traits::*;
+#[cfg(feature = "backup-enable")]
+use frame_sodium::{rng::SgxRng, SodiumCiphertext, SodiumPubKey};
 use frame_treekem::handshake::HandshakeParams;
+#[cfg(feature = "backup-enable")]
+use frame_treekem::Welcome;
+#[cfg(feature = "backup-enable")]
+use remote_attestation::{AttestationEvidence, QuoteStatusPolicy};
+#[cfg(feature = "backup-enable")]
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::context::AnonifyEnclaveContext;
+#[cfg(feature = "backup-enable")]
+use crate::enclave_key::EnclaveKey;
+
+/// The offsets of MRENCLAVE and REPORTDATA in the quote body
+#[cfg(feature = "backup-enable")]
+const QUOTE_MR_ENCLAVE_OFFSET: usize = 112;
+#[cfg(feature = "backup-enable")]
+const QUOTE_REPORT_DATA_OFFSET: usize = 368;
 
 /// A update handshake sender
 #[derive(Debug, Clone)]
@@ -42,7 +65,6 @@ impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for HandshakeSend
         self.enclave_context
             .store_path_secrets()
             .save_to_local_filesystem(&export_path_secret)?;
-        let export_handshake = handshake.clone().into_export();
 
         #[cfg(feature = "backup-enable")]
         {
@@ -56,22 +78,162 @@ impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for HandshakeSend
                 .backup_path_secret(backup_path_secret)?;
         }
 
-        let msg = Sha256::hash_for_attested_treekem_tx(
-            &export_handshake.encode(),
-            handshake.roster_idx(),
-            0,         // processing handshake reset generation
-            epoch + 1, // handshaked next epoch should be counted
+        sign_handshake(self.enclave_context, handshake)
+    }
+}
+
+/// A welcome sender which adds a new member on behalf of it.
+/// The welcome message is encrypted to the joiner's encryption key and stored in the key vault
+/// before the add handshake is broadcast, so the joiner can fetch it when receiving the handshake.
+/// The encryption key is taken from the joiner's attestation evidence verified in this enclave,
+/// so the host can't have it encrypted to a key held outside an enclave with the same MRENCLAVE.
+#[cfg(feature = "backup-enable")]
+#[derive(Debug, Clone)]
+pub struct WelcomeSender<'c> {
+    enclave_input: input::SendWelcome,
+    enclave_context: &'c AnonifyEnclaveContext,
+}
+
+#[cfg(feature = "backup-enable")]
+impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for WelcomeSender<'c> {
+    type EI = input::SendWelcome;
+    type EO = output::ReturnHandshake;
+    const ENCLAVE_USE_CASE_ID: u32 = SEND_WELCOME_TREEKEM_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c AnonifyEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn eval_policy(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn run(self) -> Result<Self::EO> {
+        let (mr_enclave, encryption_key) = verify_joiner(self.enclave_input.evidence())?;
+        let group_key = &mut *self.enclave_context.write_group_key();
+        let roster_idx = self.enclave_input.roster_idx();
+        ensure!(
+            !group_key.is_member(roster_idx),
+            "The roster index {:?} is already a member of the group",
+            roster_idx
+        );
+        let (handshake, welcome) = group_key.create_welcome(roster_idx)?;
+
+        let mut csprng = SgxRng::new()?;
+        let encrypted_welcome =
+            SodiumCiphertext::encrypt(&mut csprng, &encryption_key, &welcome.encode())?;
+        self.enclave_context
+            .store_welcome(StoreWelcomeRequestBody::new(
+                roster_idx,
+                handshake.prior_epoch(),
+                encrypted_welcome.to_bytes(),
+            ))?;
+        group_key.record_member_mr_enclave(roster_idx, mr_enclave);
+
+        sign_handshake(self.enclave_context, handshake)
+    }
+}
+
+/// Verify the joiner's attestation evidence and return its MRENCLAVE and the encryption key in its report data.
+/// The joiner must run the same enclave as this one.
+#[cfg(feature = "backup-enable")]
+fn verify_joiner(evidence: &[u8]) -> Result<([u8; 32], SodiumPubKey)> {
+    let evidence = serde_json::from_slice::<AttestationEvidence>(evidence)?
+        .verify(
+            &IAS_ROOT_CERT,
+            DCAP_ROOT_CERT.as_deref(),
+            &QuoteStatusPolicy::compiled()?,
+        )
+        .map_err(|e| anyhow!("The joiner's attestation evidence is invalid: {:?}", e))?;
+    if let (Some(timestamp), Some(max_age)) = (evidence.timestamp(), *ATTESTATION_MAX_AGE_SECS) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        ensure!(
+            now.saturating_sub(timestamp) <= max_age,
+            "The joiner's attestation evidence is older than {} secs",
+            max_age
         );
-        let sig = self.enclave_context.sign(msg.as_bytes())?;
-        let enclave_sig = sig.0;
-        let recovery_id = sig.1;
-
-        Ok(output::ReturnHandshake::new(
-            export_handshake,
-            enclave_sig,
-            recovery_id,
-        ))
     }
+
+    // Offsets are defined in "Attestation Service for Intel® Software Guard Extensions (Intel® SGX): API Documentation version 6.0"
+    let quote_body = evidence.quote_body();
+    ensure!(
+        quote_body.len() >= QUOTE_REPORT_DATA_OFFSET + 64,
+        "The joiner's quote is too short"
+    );
+    let mut mr_enclave = [0u8; 32];
+    mr_enclave.copy_from_slice(&quote_body[QUOTE_MR_ENCLAVE_OFFSET..QUOTE_MR_ENCLAVE_OFFSET + 32]);
+    ensure!(
+        mr_enclave == sgx_tse::rsgx_self_report().body.mr_enclave.m,
+        "The joiner's MRENCLAVE {:?} is not the same as this enclave's one",
+        mr_enclave
+    );
+    let encryption_key = EnclaveKey::encryption_key_from_report_data(
+        &quote_body[QUOTE_REPORT_DATA_OFFSET..QUOTE_REPORT_DATA_OFFSET + 64],
+    )?;
+
+    Ok((mr_enclave, encryption_key))
+}
+
+/// Return the attestation evidence of this enclave,
+/// which the member adding it to the group with a welcome message verifies.
+#[derive(Debug, Clone)]
+pub struct AttestationEvidenceGetter<'c> {
+    enclave_context: &'c AnonifyEnclaveContext,
+}
+
+impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for AttestationEvidenceGetter<'c> {
+    type EI = input::Empty;
+    type EO = output::ReturnAttestationEvidence;
+    const ENCLAVE_USE_CASE_ID: u32 = GET_ATTESTATION_EVIDENCE_CMD;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c AnonifyEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn eval_policy(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn run(self) -> Result<Self::EO> {
+        let evidence = self.enclave_context.attest()?;
+
+        Ok(output::ReturnAttestationEvidence::new(serde_json::to_vec(
+            &evidence,
+        )?))
+    }
+}
+
+fn sign_handshake(
+    enclave_context: &AnonifyEnclaveContext,
+    handshake: HandshakeParams,
+) -> Result<output::ReturnHandshake> {
+    let roster_idx = handshake.roster_idx();
+    let epoch = handshake.prior_epoch();
+    let export_handshake = handshake.into_export();
+    let msg = Sha256::hash_for_attested_treekem_tx(
+        &export_handshake.encode(),
+        roster_idx,
+        0,         // processing handshake reset generation
+        epoch + 1, // handshaked next epoch should be counted
+    );
+    let sig = enclave_context.sign(msg.as_bytes())?;
+    let enclave_sig = sig.0;
+    let recovery_id = sig.1;
+
+    Ok(output::ReturnHandshake::new(
+        export_handshake,
+        enclave_sig,
+        recovery_id,
+    ))
 }
 
 /// A handshake receiver
@@ -108,14 +270,44 @@ impl<'c> StateRuntimeEnclaveUseCase<'c, AnonifyEnclaveContext> for HandshakeRece
         // Even if `process_handshake` fails, state_counter must be incremented so it doesn't get stuck.
         self.enclave_context
             .verify_state_counter_increment(self.enclave_input.state_counter())?;
+
+        // If another member added me with a welcome message, start from the group state in it
+        // instead of the handshakes I haven't followed.
+        #[cfg(feature = "backup-enable")]
+        let is_welcomed = if group_key.needs_welcome(&handshake) {
+            let encrypted_welcome = self
+                .enclave_context
+                .recover_welcome(handshake.roster_idx())?;
+            let welcome = SodiumCiphertext::from_bytes(&encrypted_welcome)?;
+            let welcome = Welcome::decode(&self.enclave_context.decrypt(&welcome)?)
+                .map_err(|_| anyhow!("Welcome::decode Error"))?;
+            if welcome.handshake().hash().as_ref() != handshake.hash().as_ref() {
+                return Err(anyhow!(
+                    "The welcome message isn't for the received handshake"
+                ));
+            }
+            group_key.accept_welcome(welcome, self.enclave_context.store_path_secrets())?;
+            true
+        } else {
+            false
+        };
+
         group_key.process_handshake(
             self.enclave_context.store_path_secrets(),
             &handshake,
+            self.enclave_input.state_counter(),
             #[cfg(feature = "backup-enable")]
             |ps_id, roster_idx| {
                 AnonifyEnclaveContext::recover_path_secret(self.enclave_context, ps_id, roster_idx)
             },
         )?;
+        // The member who sent the welcome knows the secret of my leaf, so it has to be updated.
+        #[cfg(feature = "backup-enable")]
+        {
+            if is_welcomed {
+                group_key.require_update(self.enclave_context.store_path_secrets())?;
+            }
+        }
 
         Ok(output::Empty::default())
     }
@@ -16,6 +16,8 @@ mod handshake;
 mod join_group;
 mod kvs;
 mod notify;
+#[cfg(feature = "backup-enable")]
+mod shared_backup;
 
 pub mod use_case {
     #[cfg(feature = "backup-enable")]
@@ -28,8 +30,11 @@ pub mod use_case {
         ContextWithCmdCipherPaddingSize,
     };
     pub use crate::context::{GetState, GetUserCounter, ReportRegistration};
-    pub use crate::enclave_key::EncryptionKeyGetter;
-    pub use crate::handshake::{HandshakeReceiver, HandshakeSender};
+    pub use crate::enclave_key::{EnclaveKeyRotator, EncryptionKeyGetter};
+    pub use crate::group_key::GroupInfoGetter;
+    #[cfg(feature = "backup-enable")]
+    pub use crate::handshake::WelcomeSender;
+    pub use crate::handshake::{AttestationEvidenceGetter, HandshakeReceiver, HandshakeSender};
     pub use crate::join_group::{
         enclave_key::JoinGroupWithEnclaveKey, treekem::JoinGroupWithTreeKem,
     };
@@ -43,6 +48,11 @@ pub mod tests {
     use test_utils::check_all_passed;
 
     pub fn run_tests() -> bool {
-        check_all_passed!(notify::tests::run_tests(),)
+        check_all_passed!(
+            notify::tests::run_tests(),
+            group_key::tests::run_tests(),
+            enclave_key::tests::run_tests(),
+            shared_backup::tests::run_tests(),
+        )
     }
 }
@@ -0,0 +1,541 @@
+use anyhow::{anyhow, bail};
+use frame_mra_tls::{
+    key_vault::{
+        request::{
+            BackupEnclaveDecryptionKeyRequestBody, BackupPathSecretRequestBody,
+            BackupPathSecretsRequestBody, KeyVaultCmd, KeyVaultRequest,
+            ListEnclaveDecryptionKeysRequestBody, RecoverEnclaveDecryptionKeyRequestBody,
+            RecoverPathSecretRequestBody, RecoverPathSecretsRequestBody, RequestBody,
+        },
+        response::{BackedUpEnclaveDecryptionKey, RecoveredPathSecret},
+    },
+    Client, ClientConfig,
+};
+use frame_sodium::{
+    rng::SgxRng,
+    secret_sharing::{
+        combine_committed_shares, commit_secret, split_committed_secret, CommittedShare,
+        SecretShare, MAX_SHARES, SHARE_COMMITMENT_SIZE,
+    },
+    SodiumPrivateKey,
+};
+use serde::de::DeserializeOwned;
+use std::{env, string::String, vec::Vec};
+
+/// Read the threshold of the secret sharing from `KEY_VAULT_SECRET_SHARING_THRESHOLD`.
+/// If it is not set, the key-vault nodes are replicas of each other and hold the whole secrets.
+pub fn secret_sharing_threshold_from_env(endpoint_count: usize) -> anyhow::Result<Option<usize>> {
+    let threshold: usize = match env::var("KEY_VAULT_SECRET_SHARING_THRESHOLD") {
+        Ok(threshold) if !threshold.is_empty() => threshold.parse().map_err(|e| {
+            anyhow!(
+                "Failed to parse KEY_VAULT_SECRET_SHARING_THRESHOLD: {:?}",
+                e
+            )
+        })?,
+        _ => return Ok(None),
+    };
+    // A threshold of one would give every key-vault node the whole secret.
+    if threshold < 2 || threshold > endpoint_count || endpoint_count > MAX_SHARES {
+        bail!(
+            "KEY_VAULT_SECRET_SHARING_THRESHOLD must be between 2 and the number of key-vault endpoints {}, but got {}",
+            endpoint_count,
+            threshold
+        );
+    }
+
+    Ok(Some(threshold))
+}
+
+/// Backups split by Shamir's k-of-n secret sharing, one share for each of the n independent key-vault nodes,
+/// so that no single key-vault operator can reconstruct the secrets.
+/// The i-th endpoint always stores the share evaluated at x = i + 1,
+/// so the order of the endpoints must not be changed once backups are stored.
+/// Each share is stored with the version of the secret and the commitment to it,
+/// which the recovered secret is checked against.
+/// The version of a path secret is its epoch, and the commitment is appended to its share.
+pub struct SharedBackup<'a> {
+    endpoints: &'a [String],
+    client_config: &'a ClientConfig,
+    threshold: usize,
+}
+
+impl<'a> SharedBackup<'a> {
+    pub fn new(endpoints: &'a [String], client_config: &'a ClientConfig, threshold: usize) -> Self {
+        SharedBackup {
+            endpoints,
+            client_config,
+            threshold,
+        }
+    }
+
+    pub fn backup_path_secret(&self, body: &BackupPathSecretRequestBody) -> anyhow::Result<()> {
+        let requests = self
+            .split_path_secret(body)?
+            .into_iter()
+            .map(|share| KeyVaultRequest::new(KeyVaultCmd::StorePathSecret, share))
+            .collect();
+        self.store_shares(requests)
+    }
+
+    pub fn recover_path_secret(
+        &self,
+        roster_idx: u32,
+        ps_id: &[u8],
+    ) -> anyhow::Result<RecoveredPathSecret> {
+        let request = KeyVaultRequest::new(
+            KeyVaultCmd::RecoverPathSecret,
+            RecoverPathSecretRequestBody::new(roster_idx, ps_id.to_vec()),
+        );
+        self.collect_responses(request, |shares: &[&(u8, RecoveredPathSecret)]| {
+            let path_secret = combine_committed_shares(
+                &shares
+                    .iter()
+                    .map(|(x, share)| path_secret_share(*x, share))
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            )?;
+
+            Ok(RecoveredPathSecret::new(
+                path_secret,
+                shares[0].1.epoch(),
+                ps_id.to_vec(),
+            ))
+        })
+    }
+
+    pub fn backup_path_secrets(&self, body: &BackupPathSecretsRequestBody) -> anyhow::Result<()> {
+        let mut bodies: Vec<Vec<BackupPathSecretRequestBody>> = vec![vec![]; self.endpoints.len()];
+        for backup_path_secret in &body.0 {
+            for (i, share) in self
+                .split_path_secret(backup_path_secret)?
+                .into_iter()
+                .enumerate()
+            {
+                bodies[i].push(share);
+            }
+        }
+        let requests = bodies
+            .into_iter()
+            .map(|shares| {
+                KeyVaultRequest::new(
+                    KeyVaultCmd::ManuallyStorePathSecrets,
+                    BackupPathSecretsRequestBody::new(shares),
+                )
+            })
+            .collect();
+        self.store_shares(requests)
+    }
+
+    pub fn recover_path_secrets(
+        &self,
+        body: RecoverPathSecretsRequestBody,
+    ) -> anyhow::Result<Vec<RecoveredPathSecret>> {
+        let request = KeyVaultRequest::new(KeyVaultCmd::ManuallyRecoverPathSecrets, body);
+        self.collect_responses(request, |shares: &[&(u8, Vec<RecoveredPathSecret>)]| {
+            let mut recovered_path_secrets = vec![];
+            for first in &shares[0].1 {
+                let path_secret_shares = shares
+                    .iter()
+                    .map(|(x, path_secrets)| {
+                        path_secrets
+                            .iter()
+                            .find(|ps| ps.id() == first.id())
+                            .ok_or_else(|| {
+                                anyhow!(
+                                    "The key-vault node {} doesn't have the share of path secret {:?}",
+                                    x,
+                                    first.id()
+                                )
+                            })
+                            .and_then(|ps| path_secret_share(*x, ps))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                recovered_path_secrets.push(RecoveredPathSecret::new(
+                    combine_committed_shares(&path_secret_shares)?,
+                    first.epoch(),
+                    first.id().to_vec(),
+                ));
+            }
+
+            Ok(recovered_path_secrets)
+        })
+    }
+
+    /// Store the shares of the key at the version next to the latest one in any reachable key-vault node,
+    /// unless the latest shares in all of them are already of the key.
+    pub fn backup_dec_key(
+        &self,
+        dec_key: &SodiumPrivateKey,
+        roster_idx: u32,
+        node_key: &[u8],
+    ) -> anyhow::Result<()> {
+        let mut latest_versions = vec![];
+        let mut last_err = None;
+        for endpoint in self.endpoints {
+            match self.list_dec_keys(endpoint, roster_idx) {
+                Ok(versions) => latest_versions.push(versions.into_iter().last()),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        if latest_versions.len() < self.threshold {
+            bail!(
+                "Only {} of {} key-vault nodes required to back up the key are reachable: {:?}",
+                latest_versions.len(),
+                self.threshold,
+                last_err
+            );
+        }
+        let secret = dec_key.to_bytes();
+        let is_backed_up = latest_versions.iter().all(|latest| {
+            latest.as_ref().map_or(false, |latest| {
+                latest.commitment() == &commit_secret(&secret, latest.version())[..]
+            })
+        });
+        if is_backed_up {
+            return Ok(());
+        }
+
+        let version = latest_versions
+            .iter()
+            .flatten()
+            .map(|latest| latest.version())
+            .max()
+            .map_or(1, |latest| latest + 1);
+        let mut rng = SgxRng::new()?;
+        let shares = split_committed_secret(
+            &secret,
+            version,
+            self.threshold,
+            self.endpoints.len(),
+            &mut rng,
+        )?;
+        // Any 32 bytes are a valid private key, so the key-vault nodes store the shares as is.
+        let requests = shares
+            .into_iter()
+            .map(|committed| {
+                let key_share = SodiumPrivateKey::from_bytes(committed.share().y())?;
+                Ok(KeyVaultRequest::new(
+                    KeyVaultCmd::StoreEnclaveDecryptionKey,
+                    BackupEnclaveDecryptionKeyRequestBody::new(
+                        key_share,
+                        roster_idx,
+                        node_key.to_vec(),
+                    )
+                    .with_share_commitment(version, committed.commitment().to_vec()),
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.store_shares(requests)
+    }
+
+    /// Recover the specified version of the key, or the latest one if not specified.
+    /// It fails unless the threshold number of key-vault nodes have the shares of the same latest version.
+    pub fn recover_dec_key(
+        &self,
+        roster_idx: u32,
+        version: Option<u32>,
+    ) -> anyhow::Result<SodiumPrivateKey> {
+        self.collect_shares(
+            |endpoint| {
+            let versions = self.list_dec_keys(endpoint, roster_idx)?;
+            let backed_up = match version {
+                Some(version) => versions.into_iter().find(|v| v.version() == version),
+                None => versions.into_iter().last(),
+            }
+            .ok_or_else(|| {
+                anyhow!(
+                    "Not found the share of the enclave decryption key (roster_idx: {:?}, version: {:?})",
+                    roster_idx,
+                    version
+                )
+            })?;
+            let request = KeyVaultRequest::new(
+                KeyVaultCmd::RecoverEnclaveDecryptionKey,
+                RecoverEnclaveDecryptionKeyRequestBody::new(roster_idx, Some(backed_up.version())),
+            );
+            let key_share: SodiumPrivateKey = Client::new(endpoint, self.client_config)
+                .and_then(|mut client| client.send_request(request))?;
+
+            Ok((backed_up, key_share))
+            },
+            |shares: &[&(u8, (BackedUpEnclaveDecryptionKey, SodiumPrivateKey))]| {
+                let dec_key = combine_committed_shares(
+                    &shares
+                        .iter()
+                        .map(|(x, (backed_up, key_share))| {
+                            CommittedShare::new(
+                                SecretShare::new(*x, key_share.to_bytes().to_vec()),
+                                backed_up.version(),
+                                backed_up.commitment().to_vec(),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )?;
+
+                SodiumPrivateKey::from_bytes(&dec_key)
+            },
+        )
+    }
+
+    fn list_dec_keys(
+        &self,
+        endpoint: &str,
+        roster_idx: u32,
+    ) -> anyhow::Result<Vec<BackedUpEnclaveDecryptionKey>> {
+        let request = KeyVaultRequest::new(
+            KeyVaultCmd::ListEnclaveDecryptionKeys,
+            ListEnclaveDecryptionKeysRequestBody::new(roster_idx),
+        );
+        Client::new(endpoint, self.client_config)
+            .and_then(|mut client| client.send_request(request))
+            .map_err(|e| anyhow!("Failed to list the shares in {}: {:?}", endpoint, e))
+    }
+
+    /// Split the path secret into the request bodies for each key-vault node.
+    fn split_path_secret(
+        &self,
+        body: &BackupPathSecretRequestBody,
+    ) -> anyhow::Result<Vec<BackupPathSecretRequestBody>> {
+        let mut rng = SgxRng::new()?;
+        let shares = split_committed_secret(
+            body.path_secret(),
+            body.epoch(),
+            self.threshold,
+            self.endpoints.len(),
+            &mut rng,
+        )?;
+
+        Ok(shares
+            .into_iter()
+            .map(|committed| {
+                let mut share = committed.share().y().to_vec();
+                share.extend_from_slice(committed.commitment());
+                BackupPathSecretRequestBody::new(
+                    share,
+                    body.epoch(),
+                    body.roster_idx(),
+                    body.id().to_vec(),
+                )
+            })
+            .collect())
+    }
+
+    /// Send the i-th request to the i-th key-vault node.
+    /// The backup succeeds if at least the threshold number of nodes stored their shares,
+    /// because the secret is still recoverable from them, and a failure of any node must not
+    /// block the backup of the secrets to the others.
+    /// Until the secret is backed up again, fewer nodes may fail on its recovery.
+    fn store_shares<B: RequestBody>(
+        &self,
+        requests: Vec<KeyVaultRequest<B>>,
+    ) -> anyhow::Result<()> {
+        let mut stored = 0;
+        let mut last_err = None;
+        for (endpoint, request) in self.endpoints.iter().zip(requests) {
+            match Client::new(endpoint, self.client_config)
+                .and_then(|mut client| client.send_request::<_, serde_json::Value>(request))
+            {
+                Ok(_) => stored += 1,
+                Err(e) => {
+                    last_err = Some(anyhow!(
+                        "Failed to store the share to {}: {:?}",
+                        endpoint,
+                        e
+                    ))
+                }
+            }
+        }
+        if stored < self.threshold {
+            bail!(
+                "Only {} of {} shares required to recover the secret are stored: {:?}",
+                stored,
+                self.threshold,
+                last_err
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Collect the responses to the request from the key-vault nodes paired with the x coordinates of their shares,
+    /// and combine them as described in `combine_fetched_shares`.
+    fn collect_responses<B, DE, R, C>(
+        &self,
+        request: KeyVaultRequest<B>,
+        combine: C,
+    ) -> anyhow::Result<R>
+    where
+        B: RequestBody,
+        DE: DeserializeOwned,
+        C: Fn(&[&(u8, DE)]) -> anyhow::Result<R>,
+    {
+        self.collect_shares(
+            |endpoint| {
+                Client::new(endpoint, self.client_config)
+                    .and_then(|mut client| client.send_request(request.clone()))
+            },
+            combine,
+        )
+    }
+
+    /// Fetch the shares from the key-vault nodes in order, paired with their x coordinates,
+    /// and combine them as described in `combine_fetched_shares`.
+    fn collect_shares<T, R, F, C>(&self, fetch: F, combine: C) -> anyhow::Result<R>
+    where
+        F: Fn(&str) -> anyhow::Result<T>,
+        C: Fn(&[&(u8, T)]) -> anyhow::Result<R>,
+    {
+        let fetched = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(i, endpoint)| fetch(endpoint).map(|share| ((i + 1) as u8, share)));
+        combine_fetched_shares(fetched, self.threshold, combine)
+    }
+}
+
+/// Fetch the shares lazily and try to combine every threshold number of them including the last fetched one,
+/// until any of them is combined successfully.
+/// So unreachable nodes and corrupt or stale shares are tolerated as long as
+/// the threshold number of valid shares are fetched.
+fn combine_fetched_shares<T, R, I, C>(fetched: I, threshold: usize, combine: C) -> anyhow::Result<R>
+where
+    I: Iterator<Item = anyhow::Result<(u8, T)>>,
+    C: Fn(&[&(u8, T)]) -> anyhow::Result<R>,
+{
+    let mut shares = vec![];
+    let mut last_err = None;
+    for share in fetched {
+        match share {
+            Ok(share) => shares.push(share),
+            Err(e) => {
+                last_err = Some(e);
+                continue;
+            }
+        }
+        if shares.len() < threshold {
+            continue;
+        }
+
+        let last = shares.len() - 1;
+        for mut subset in subsets(last, threshold - 1) {
+            subset.push(last);
+            let subset = subset.into_iter().map(|i| &shares[i]).collect::<Vec<_>>();
+            match combine(&subset) {
+                Ok(secret) => return Ok(secret),
+                Err(e) => last_err = Some(e),
+            }
+        }
+    }
+
+    bail!(
+        "Failed to recover the secret from {} fetched shares with the threshold {}: {:?}",
+        shares.len(),
+        threshold,
+        last_err
+    )
+}
+
+/// All the subsets of `0..n` with `k` elements.
+fn subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    if n < k {
+        return vec![];
+    }
+
+    let mut subsets_with_last = subsets(n - 1, k - 1);
+    for subset in &mut subsets_with_last {
+        subset.push(n - 1);
+    }
+    let mut ret = subsets(n - 1, k);
+    ret.extend(subsets_with_last);
+    ret
+}
+
+/// Split the recovered path secret into its share and the commitment appended to it.
+fn path_secret_share(x: u8, recovered: &RecoveredPathSecret) -> anyhow::Result<CommittedShare> {
+    let payload = recovered.path_secret();
+    if payload.len() <= SHARE_COMMITMENT_SIZE {
+        bail!(
+            "The share of path secret {:?} in the key-vault node {} has no commitment",
+            recovered.id(),
+            x
+        );
+    }
+    let (share, commitment) = payload.split_at(payload.len() - SHARE_COMMITMENT_SIZE);
+
+    Ok(CommittedShare::new(
+        SecretShare::new(x, share.to_vec()),
+        recovered.epoch(),
+        commitment.to_vec(),
+    ))
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_subsets, test_combine_fetched_shares_with_corrupt_share,)
+    }
+
+    fn test_subsets() {
+        assert_eq!(subsets(3, 0), vec![vec![]]);
+        assert_eq!(subsets(2, 3), Vec::<Vec<usize>>::new());
+        let mut subsets = subsets(4, 2);
+        subsets.sort();
+        assert_eq!(
+            subsets,
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 3]
+            ]
+        );
+    }
+
+    fn test_combine_fetched_shares_with_corrupt_share() {
+        let mut rng = SgxRng::new().unwrap();
+        let secret = [0x42u8; 32];
+        let mut shares = split_committed_secret(&secret, 1, 3, 5, &mut rng).unwrap();
+        // The first node returns a corrupt share and the second node is unreachable.
+        let mut corrupt = shares[0].share().y().to_vec();
+        corrupt[0] ^= 0xff;
+        shares[0] = CommittedShare::new(
+            SecretShare::new(shares[0].share().x(), corrupt),
+            shares[0].version(),
+            shares[0].commitment().to_vec(),
+        );
+        let combine = |shares: &[&(u8, CommittedShare)]| {
+            combine_committed_shares(
+                &shares
+                    .iter()
+                    .map(|(_, share)| share.clone())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let fetched = shares.iter().enumerate().map(|(i, share)| {
+            if i == 1 {
+                Err(anyhow!("The key-vault node is unreachable"))
+            } else {
+                Ok((share.share().x(), share.clone()))
+            }
+        });
+        assert_eq!(
+            combine_fetched_shares(fetched, 3, combine).unwrap(),
+            secret.to_vec()
+        );
+
+        // The corrupt share and fewer valid shares than the threshold can't recover the secret.
+        let fetched = shares
+            .iter()
+            .take(3)
+            .map(|share| Ok((share.share().x(), share.clone())));
+        assert!(combine_fetched_shares(fetched, 3, combine).is_err());
+    }
+}
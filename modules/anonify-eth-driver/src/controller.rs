@@ -7,6 +7,8 @@ use frame_host::ecall_controller::*;
 use frame_sodium::SodiumCiphertext;
 
 pub const EI_MAX_SIZE: usize = 2048;
+/// Max size for the ecalls passing attestation evidence, which carries the certificate chains and the collateral.
+pub const EVIDENCE_EI_MAX_SIZE: usize = 262144;
 
 pub struct CommandController;
 
@@ -83,6 +85,27 @@ impl EcallController for HandshakeController {
     }
 }
 
+pub struct SendWelcomeController;
+
+impl EcallController for SendWelcomeController {
+    type HI = host_input::SendWelcome;
+    type EI = input::SendWelcome;
+    type EO = output::ReturnHandshake;
+    type HO = host_output::Handshake;
+    const EI_MAX_SIZE: usize = EVIDENCE_EI_MAX_SIZE;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(input::SendWelcome::new(
+            host_input.roster_idx,
+            host_input.evidence,
+        ))
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(host_output::Handshake { enclave_output })
+    }
+}
+
 pub struct RegisterNotificationController;
 
 impl EcallController for RegisterNotificationController {
@@ -179,6 +202,42 @@ impl EcallController for GetEncryptionKeyController {
     }
 }
 
+pub struct GetGroupInfoController;
+
+impl EcallController for GetGroupInfoController {
+    type HI = host_input::GetGroupInfo;
+    type EI = input::Empty;
+    type EO = output::ReturnGroupInfo;
+    type HO = host_output::ReturnGroupInfo;
+    const EI_MAX_SIZE: usize = EI_MAX_SIZE;
+
+    fn translate_input(_host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(input::Empty::default())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(host_output::ReturnGroupInfo { enclave_output })
+    }
+}
+
+pub struct GetAttestationEvidenceController;
+
+impl EcallController for GetAttestationEvidenceController {
+    type HI = host_input::GetAttestationEvidence;
+    type EI = input::Empty;
+    type EO = output::ReturnAttestationEvidence;
+    type HO = host_output::ReturnAttestationEvidence;
+    const EI_MAX_SIZE: usize = EVIDENCE_EI_MAX_SIZE;
+
+    fn translate_input(_host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(input::Empty::default())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(host_output::ReturnAttestationEvidence { enclave_output })
+    }
+}
+
 pub struct BackupController;
 
 impl EcallController for BackupController {
@@ -282,6 +341,22 @@ pub mod host_input {
 
     impl HostInput for Handshake {}
 
+    pub struct SendWelcome {
+        pub(super) roster_idx: u32,
+        pub(super) evidence: Vec<u8>,
+    }
+
+    impl SendWelcome {
+        pub fn new(roster_idx: u32, evidence: Vec<u8>) -> Self {
+            SendWelcome {
+                roster_idx,
+                evidence,
+            }
+        }
+    }
+
+    impl HostInput for SendWelcome {}
+
     pub struct RegisterNotification {
         pub(super) ciphertext: SodiumCiphertext,
     }
@@ -360,6 +435,26 @@ pub mod host_input {
 
     impl HostInput for GetEncryptionKey {}
 
+    pub struct GetGroupInfo {}
+
+    impl GetGroupInfo {
+        pub fn new() -> Self {
+            GetGroupInfo {}
+        }
+    }
+
+    impl HostInput for GetGroupInfo {}
+
+    pub struct GetAttestationEvidence {}
+
+    impl GetAttestationEvidence {
+        pub fn new() -> Self {
+            GetAttestationEvidence {}
+        }
+    }
+
+    impl HostInput for GetAttestationEvidence {}
+
     pub struct Backup {}
 
     impl Backup {
@@ -446,6 +541,18 @@ pub mod host_output {
 
     impl HostOutput for ReturnEncryptionKey {}
 
+    pub struct ReturnGroupInfo {
+        pub enclave_output: output::ReturnGroupInfo,
+    }
+
+    impl HostOutput for ReturnGroupInfo {}
+
+    pub struct ReturnAttestationEvidence {
+        pub enclave_output: output::ReturnAttestationEvidence,
+    }
+
+    impl HostOutput for ReturnAttestationEvidence {}
+
     #[derive(Default)]
     pub struct Backup;
 
@@ -7,7 +7,7 @@ use crate::{
     eth::{EthSender, EventWatcher},
     utils::*,
 };
-use anonify_ecall_types::cmd::*;
+use anonify_ecall_types::{cmd::*, output};
 use frame_common::crypto::AccountId;
 use frame_host::ecall_controller::EcallController;
 use frame_sodium::{SodiumCiphertext, SodiumPubKey};
@@ -131,6 +131,7 @@ impl Dispatcher {
         // it spawns a new OS thread, and hosts an event loop.
         actix_rt::Arbiter::new().exec_fn(move || {
             actix_rt::spawn(async move {
+                let mut updated_epoch = None;
                 loop {
                     match this
                         .fetch_events(fetch_ciphertext_ecall_cmd, fetch_handshake_ecalll_cmd)
@@ -139,6 +140,26 @@ impl Dispatcher {
                         Ok(updated_states) => debug!("State updated: {:?}", updated_states),
                         Err(err) => error!("event fetched error: {:?}", err),
                     };
+                    // A member joined with a welcome message must update its leaf secret, which the sender knows.
+                    // The handshake is sent once per epoch, and retried if the group moves on without it.
+                    if fetch_handshake_ecalll_cmd.is_some() {
+                        match this.get_group_info() {
+                            Ok(group_info)
+                                if group_info.requires_update
+                                    && updated_epoch != Some(group_info.epoch) =>
+                            {
+                                match this.handshake(signer, gas).await {
+                                    Ok(tx_hash) => {
+                                        info!("A transaction hash of the update handshake after joining: {:?}", tx_hash);
+                                        updated_epoch = Some(group_info.epoch);
+                                    }
+                                    Err(err) => error!("update handshake error: {:?}", err),
+                                }
+                            }
+                            Ok(_) => {}
+                            Err(err) => error!("get group info error: {:?}", err),
+                        }
+                    }
                     actix_rt::time::delay_for(time::Duration::from_millis(sync_time)).await;
                 }
             });
@@ -282,6 +303,48 @@ impl Dispatcher {
         Ok(tx_hash)
     }
 
+    /// Add the member of `roster_idx` with a welcome message encrypted to the encryption key in its attestation evidence.
+    /// The evidence is verified in the enclave, so the welcome message is only sent to an enclave of the same code.
+    #[cfg(feature = "backup-enable")]
+    pub async fn send_welcome(
+        &self,
+        roster_idx: u32,
+        evidence: Vec<u8>,
+        signer: Address,
+        gas: u64,
+    ) -> Result<H256> {
+        let inner = self.inner.read();
+        let input = host_input::SendWelcome::new(roster_idx, evidence);
+        let eid = inner.enclave_id;
+        let host_output = SendWelcomeController::run(input, SEND_WELCOME_TREEKEM_CMD, eid)?;
+
+        let tx_hash = inner
+            .sender
+            .as_ref()
+            .ok_or(HostError::AddressNotSet)?
+            .handshake(&host_output, signer, gas)
+            .await?;
+
+        Ok(tx_hash)
+    }
+
+    /// Rotate the enclave decryption key and register the report containing the new encryption key.
+    pub async fn rotate_enclave_key(&self, signer: Address, gas: u64) -> Result<H256> {
+        let inner = self.inner.read();
+        let eid = inner.enclave_id;
+        let input = host_input::RegisterReport::new();
+        let host_output = RegisterReportController::run(input, ROTATE_ENCLAVE_KEY_CMD, eid)?;
+
+        let tx_hash = inner
+            .sender
+            .as_ref()
+            .ok_or(HostError::AddressNotSet)?
+            .register_report(&host_output, signer, gas)
+            .await?;
+
+        Ok(tx_hash)
+    }
+
     pub async fn get_account(&self, index: usize, password: Option<&str>) -> Result<Address> {
         self.inner
             .read()
@@ -303,6 +366,41 @@ impl Dispatcher {
             .enclave_encryption_key())
     }
 
+    pub fn get_group_info(&self) -> Result<output::ReturnGroupInfo> {
+        let input = host_input::GetGroupInfo::new();
+        let eid = self.inner.read().enclave_id;
+        let group_info = GetGroupInfoController::run(input, GET_GROUP_INFO_CMD, eid)?;
+
+        Ok(group_info.enclave_output)
+    }
+
+    /// The serialized attestation evidence of this enclave, whose REPORTDATA contains its encryption key.
+    pub fn get_attestation_evidence(&self) -> Result<Vec<u8>> {
+        let input = host_input::GetAttestationEvidence::new();
+        let eid = self.inner.read().enclave_id;
+        let evidence =
+            GetAttestationEvidenceController::run(input, GET_ATTESTATION_EVIDENCE_CMD, eid)?;
+
+        Ok(evidence.enclave_output.evidence())
+    }
+
+    /// Whether the report containing the given encryption key is registered on-chain.
+    pub async fn is_encryption_key_registered(
+        &self,
+        encryption_key: &SodiumPubKey,
+    ) -> Result<bool> {
+        let registered_key = self
+            .inner
+            .read()
+            .sender
+            .as_ref()
+            .ok_or(HostError::AddressNotSet)?
+            .get_encryption_key(encryption_key)
+            .await?;
+
+        Ok(registered_key.as_slice() == &encryption_key.to_bytes()[..])
+    }
+
     pub fn register_notification(&self, ciphertext: SodiumCiphertext) -> Result<()> {
         let inner = self.inner.read();
         let input = host_input::RegisterNotification::new(ciphertext);
@@ -10,6 +10,7 @@ use anyhow::anyhow;
 use ethabi::{Topic, TopicFilter};
 use frame_config::{REQUEST_RETRIES, RETRY_DELAY_MILLS};
 use frame_retrier::{strategy, Retry};
+use frame_sodium::SodiumPubKey;
 use opentelemetry::trace::TraceContextExt;
 use std::{env, fs, path::Path};
 use tracing::Span;
@@ -204,6 +205,20 @@ impl Web3Contract {
             .map_err(Into::into)
     }
 
+    /// Query the registered encryption key. Empty bytes are returned if the key isn't registered.
+    pub async fn get_encryption_key(&self, encryption_key: &SodiumPubKey) -> Result<Vec<u8>> {
+        self.contract
+            .query(
+                "getEncryptionKey",
+                encryption_key.to_bytes().to_vec(),
+                None,
+                Options::default(),
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    }
+
     pub async fn get_event(&self, cache: EventCache, key: Address) -> Result<Web3Logs> {
         // Read latest block number from in-memory event cache.
         let latest_fetched_num = cache
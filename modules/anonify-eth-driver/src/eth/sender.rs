@@ -2,6 +2,7 @@ use super::connection::{Web3Contract, Web3Http};
 use crate::{controller::*, error::Result, utils::*};
 use frame_config::{REQUEST_RETRIES, RETRY_DELAY_MILLS};
 use frame_retrier::{strategy, Retry};
+use frame_sodium::SodiumPubKey;
 use sgx_types::sgx_enclave_id_t;
 use tracing::info;
 use web3::types::{Address, TransactionReceipt, H256};
@@ -131,6 +132,17 @@ impl EthSender {
         .await
     }
 
+    pub async fn get_encryption_key(&self, encryption_key: &SodiumPubKey) -> Result<Vec<u8>> {
+        Retry::new(
+            "get_encryption_key",
+            *REQUEST_RETRIES,
+            strategy::FixedDelay::new(*RETRY_DELAY_MILLS),
+        )
+        .set_condition(query_retry_condition)
+        .spawn_async(|| async { self.contract.get_encryption_key(encryption_key).await })
+        .await
+    }
+
     pub fn get_contract(&self) -> &Web3Contract {
         &self.contract
     }
@@ -71,6 +71,18 @@ pub const fn event_fetch_retry_condition(res: &Result<Vec<Log>>) -> bool {
     }
 }
 
+pub const fn query_retry_condition(res: &Result<Vec<u8>>) -> bool {
+    match res {
+        Ok(_) => false,
+        Err(err) => match err {
+            HostError::Web3ContractError(web3_err) => {
+                !matches!(web3_err, web3::contract::Error::Abi(_))
+            }
+            _ => true,
+        },
+    }
+}
+
 /// Needed information to handle smart contracts.
 #[derive(Debug, Clone)]
 pub struct ContractInfo {
@@ -0,0 +1,174 @@
+//! The client's own key, which values are decrypted by.
+
+use crate::{
+    enclave_key::EnclaveKey,
+    error::{ClientError, Result},
+};
+use frame_sodium::{SodiumCiphertext, SodiumPrivateKey};
+use module_encrypted_sql_ops_ecall_types::client_types::ClientPayload;
+
+/// The key pair of a client, whose public key is passed to `ENCINTEGER_TO_CLIENT()`.
+/// It must be listed in `client_public_keys` of the enclave's owner config in hex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientKey(SodiumPrivateKey);
+
+impl ClientKey {
+    /// Generate a new key.
+    pub fn new_random() -> Result<Self> {
+        let private_key = SodiumPrivateKey::from_random(&mut rand::thread_rng())
+            .map_err(|e| ClientError::EncryptError(e.to_string()))?;
+        Ok(Self(private_key))
+    }
+
+    /// Restore the private key stored by [to_bytes()](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let private_key = SodiumPrivateKey::from_bytes(bytes)
+            .map_err(|_| ClientError::InvalidKeyError { size: bytes.len() })?;
+        Ok(Self(private_key))
+    }
+
+    /// Private key, which must be kept secret by the client.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// X25519 public key
+    pub fn public_key(&self) -> [u8; 32] {
+        self.0.public_key().to_bytes()
+    }
+
+    pub(crate) fn as_sodium_private_key(&self) -> &SodiumPrivateKey {
+        &self.0
+    }
+
+    /// Decrypt an INTEGER returned by `ENCINTEGER_TO_CLIENT()`,
+    /// which must be encrypted by the enclave and in `context`.
+    pub fn decrypt_integer(
+        &self,
+        ciphertext: &[u8],
+        enclave_key: &EnclaveKey,
+        context: &str,
+    ) -> Result<i32> {
+        let payload = self.decrypt(ciphertext, enclave_key)?;
+        if payload.context() != context {
+            return Err(ClientError::ContextMismatchError {
+                expected: context.to_string(),
+                actual: payload.context().to_string(),
+            });
+        }
+        payload.to_i32().ok_or(ClientError::DecryptError)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], enclave_key: &EnclaveKey) -> Result<ClientPayload> {
+        let ciphertext =
+            SodiumCiphertext::decode(ciphertext).map_err(|_| ClientError::DecryptError)?;
+        // The ciphertexts encrypted by others, e.g. forged by RDBMS, have other sender keys.
+        if ciphertext.sender_public_key() != enclave_key.as_sodium_pub_key() {
+            return Err(ClientError::DecryptError);
+        }
+        let plaintext = ciphertext
+            .decrypt(&self.0)
+            .map_err(|_| ClientError::DecryptError)?;
+        ClientPayload::from_bytes(&plaintext).ok_or(ClientError::DecryptError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enclave_key::MAX_CONTEXT_SIZE;
+
+    #[test]
+    fn test_encrypt_integer() {
+        // The enclave is played by a key pair of the client.
+        let enclave_private_key = ClientKey::new_random().unwrap();
+        let enclave_key = EnclaveKey::new_for_test(enclave_private_key.0.public_key());
+
+        let ciphertext = enclave_key.encrypt_integer(42, "t.c_enc").unwrap();
+        assert_ne!(
+            ciphertext,
+            enclave_key.encrypt_integer(42, "t.c_enc").unwrap()
+        );
+        let payload = SodiumCiphertext::decode(&ciphertext)
+            .unwrap()
+            .decrypt(&enclave_private_key.0)
+            .unwrap();
+        assert_eq!(
+            ClientPayload::from_bytes(&payload).unwrap(),
+            ClientPayload::from_i32(42, "t.c_enc".to_string())
+        );
+
+        assert!(enclave_key
+            .encrypt_integer(42, &"c".repeat(MAX_CONTEXT_SIZE + 1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_encrypt_integer_from() {
+        let client_key = ClientKey::new_random().unwrap();
+        let enclave_private_key = ClientKey::new_random().unwrap();
+        let enclave_key = EnclaveKey::new_for_test(enclave_private_key.0.public_key());
+
+        let ciphertext = SodiumCiphertext::decode(
+            &enclave_key
+                .encrypt_integer_from(&client_key, 42, "t.c_salary")
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ciphertext.sender_public_key(), &client_key.0.public_key());
+        let payload = ciphertext.decrypt(&enclave_private_key.0).unwrap();
+        assert_eq!(
+            ClientPayload::from_bytes(&payload).unwrap(),
+            ClientPayload::from_i32(42, "t.c_salary".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decrypt_integer() {
+        let client_key = ClientKey::new_random().unwrap();
+        let enclave_private_key = ClientKey::new_random().unwrap();
+        let enclave_key = EnclaveKey::new_for_test(enclave_private_key.0.public_key());
+        let encrypt_from = |sender: &ClientKey, context: &str| {
+            SodiumCiphertext::encrypt_from(
+                &mut rand::thread_rng(),
+                &sender.0,
+                &client_key.0.public_key(),
+                &ClientPayload::from_i32(42, context.to_string()).to_bytes(),
+            )
+            .unwrap()
+            .encode()
+        };
+
+        let ciphertext = encrypt_from(&enclave_private_key, "t.c_enc");
+        assert_eq!(
+            client_key
+                .decrypt_integer(&ciphertext, &enclave_key, "t.c_enc")
+                .unwrap(),
+            42
+        );
+        assert!(client_key
+            .decrypt_integer(&ciphertext, &enclave_key, "t.other")
+            .is_err());
+
+        // Encrypted by someone else than the enclave
+        let forged = encrypt_from(&ClientKey::new_random().unwrap(), "t.c_enc");
+        assert!(client_key
+            .decrypt_integer(&forged, &enclave_key, "t.c_enc")
+            .is_err());
+        // Encrypted to another client
+        assert!(ClientKey::new_random()
+            .unwrap()
+            .decrypt_integer(&ciphertext, &enclave_key, "t.c_enc")
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_bytes() {
+        let client_key = ClientKey::new_random().unwrap();
+        assert_eq!(
+            ClientKey::from_bytes(&client_key.to_bytes()).unwrap(),
+            client_key
+        );
+        assert!(ClientKey::from_bytes(&[0u8; 16]).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! The enclave's client key, which values are encrypted to.
+
+use crate::{
+    client_key::ClientKey,
+    error::{ClientError, Result},
+};
+use frame_sodium::{SodiumCiphertext, SodiumPrivateKey, SodiumPubKey};
+use module_encrypted_sql_ops_ecall_types::client_types::{
+    ClientPayload, CLIENT_KEY_REPORT_DATA_LABEL,
+};
+use remote_attestation::{AttestationEvidence, QuoteStatusPolicy};
+use serde::Deserialize;
+
+/// The maximum length of a context in bytes, which is checked by the enclave too
+pub const MAX_CONTEXT_SIZE: usize = 128;
+
+// Offsets are defined in "Attestation Service for Intel® Software Guard Extensions (Intel® SGX): API Documentation version 6.0"
+const MR_ENCLAVE_OFFSET: usize = 112;
+const REPORT_DATA_OFFSET: usize = 368;
+
+/// The client key in the format of `ENCRYPTED_SQL_OPS_CLIENT_KEY()`
+#[derive(Debug, Deserialize)]
+struct PublishedClientKey {
+    public_key: String,
+    evidence: AttestationEvidence,
+}
+
+/// Verifies the client key published by `ENCRYPTED_SQL_OPS_CLIENT_KEY()`,
+/// so that values are never encrypted to a key which RDBMS substitutes.
+#[derive(Debug, Clone)]
+pub struct EnclaveKeyVerifier {
+    mr_enclave: [u8; 32],
+    ias_root_cert: Vec<u8>,
+    dcap_root_cert: Option<Vec<u8>>,
+    quote_status_policy: QuoteStatusPolicy,
+}
+
+impl EnclaveKeyVerifier {
+    /// Trusts the enclave with `mr_enclave` attested by IAS, whose root CA is given in DER.
+    /// Only the quote status `OK` is accepted.
+    pub fn new(mr_enclave: [u8; 32], ias_root_cert: Vec<u8>) -> Self {
+        Self {
+            mr_enclave,
+            ias_root_cert,
+            dcap_root_cert: None,
+            quote_status_policy: QuoteStatusPolicy::strict(),
+        }
+    }
+
+    /// Trusts the DCAP attestation with its root CA in DER as well.
+    pub fn with_dcap_root_cert(mut self, dcap_root_cert: Vec<u8>) -> Self {
+        self.dcap_root_cert = Some(dcap_root_cert);
+        self
+    }
+
+    /// Accepts the quote statuses other than `OK` by the policy.
+    pub fn with_quote_status_policy(mut self, quote_status_policy: QuoteStatusPolicy) -> Self {
+        self.quote_status_policy = quote_status_policy;
+        self
+    }
+
+    /// Verify the JSON returned by `ENCRYPTED_SQL_OPS_CLIENT_KEY()`.
+    pub fn verify(&self, published: &str) -> Result<EnclaveKey> {
+        let published: PublishedClientKey = serde_json::from_str(published)
+            .map_err(|e| ClientError::MalformedClientKeyError(e.to_string()))?;
+        let public_key = hex::decode(&published.public_key)
+            .map_err(|e| ClientError::MalformedClientKeyError(e.to_string()))?;
+        let public_key = SodiumPubKey::from_bytes(&public_key)
+            .map_err(|e| ClientError::MalformedClientKeyError(e.to_string()))?;
+
+        let evidence = published.evidence.verify(
+            &self.ias_root_cert,
+            self.dcap_root_cert.as_deref(),
+            &self.quote_status_policy,
+        )?;
+        let quote_body = evidence.quote_body();
+        if quote_body.len() < REPORT_DATA_OFFSET + 64 {
+            return Err(ClientError::MalformedClientKeyError(format!(
+                "quote body is {} bytes",
+                quote_body.len()
+            )));
+        }
+
+        let mr_enclave = &quote_body[MR_ENCLAVE_OFFSET..MR_ENCLAVE_OFFSET + 32];
+        if mr_enclave != self.mr_enclave {
+            return Err(ClientError::MeasurementMismatchError {
+                expected: hex::encode(self.mr_enclave),
+                actual: hex::encode(mr_enclave),
+            });
+        }
+        let report_data = &quote_body[REPORT_DATA_OFFSET..REPORT_DATA_OFFSET + 64];
+        if report_data[..32] != public_key.to_bytes()
+            || report_data[32..] != CLIENT_KEY_REPORT_DATA_LABEL[..]
+        {
+            return Err(ClientError::ReportDataMismatchError);
+        }
+
+        Ok(EnclaveKey(public_key))
+    }
+}
+
+/// The attested client key of the enclave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnclaveKey(SodiumPubKey);
+
+impl EnclaveKey {
+    /// Encrypt an INTEGER to be passed to `ENCINTEGER_FROM_CLIENT()`.
+    /// `context`, e.g. `table.column`, is authenticated with the value as in `ENCINTEGER_FROM()`.
+    pub fn encrypt_integer(&self, value: i32, context: &str) -> Result<Vec<u8>> {
+        self.encrypt(ClientPayload::from_i32(value, context.to_string()), None)
+    }
+
+    /// Encrypt an INTEGER from `client_key`, which the enclave authenticates.
+    /// The values in `client_only_contexts` of the owner config must be encrypted this way
+    /// by a client listed in `client_public_keys`.
+    pub fn encrypt_integer_from(
+        &self,
+        client_key: &ClientKey,
+        value: i32,
+        context: &str,
+    ) -> Result<Vec<u8>> {
+        self.encrypt(
+            ClientPayload::from_i32(value, context.to_string()),
+            Some(client_key.as_sodium_private_key()),
+        )
+    }
+
+    /// X25519 public key
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub(crate) fn as_sodium_pub_key(&self) -> &SodiumPubKey {
+        &self.0
+    }
+
+    fn encrypt(
+        &self,
+        payload: ClientPayload,
+        sender: Option<&SodiumPrivateKey>,
+    ) -> Result<Vec<u8>> {
+        if payload.context().len() > MAX_CONTEXT_SIZE {
+            return Err(ClientError::ContextTooLongError {
+                size: payload.context().len(),
+            });
+        }
+
+        let mut rng = rand::thread_rng();
+        let ciphertext = match sender {
+            Some(sender) => {
+                SodiumCiphertext::encrypt_from(&mut rng, sender, &self.0, &payload.to_bytes())
+            }
+            None => SodiumCiphertext::encrypt(&mut rng, &self.0, &payload.to_bytes()),
+        }
+        .map_err(|e| ClientError::EncryptError(e.to_string()))?;
+        Ok(ciphertext.encode())
+    }
+}
+
+#[cfg(test)]
+impl EnclaveKey {
+    /// The key which isn't attested, only for testing.
+    pub(crate) fn new_for_test(public_key: SodiumPubKey) -> Self {
+        Self(public_key)
+    }
+}
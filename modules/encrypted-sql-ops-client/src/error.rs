@@ -0,0 +1,64 @@
+//! Error and Result types.
+
+use thiserror::Error;
+
+/// The result type in this crate.
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// The error type in this crate.
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The published client key isn't in the format of `ENCRYPTED_SQL_OPS_CLIENT_KEY()`.
+    #[error("published client key is malformed: {0}")]
+    MalformedClientKeyError(String),
+
+    /// The attestation evidence isn't verified by the trusted root CAs and quote status policy.
+    #[error("attestation evidence is not verified: {0}")]
+    AttestationError(#[from] remote_attestation::Error),
+
+    /// The client key is published by another enclave.
+    #[error("client key is published by enclave {actual}, while expected to be {expected}")]
+    MeasurementMismatchError {
+        /// hex-encoded trusted MRENCLAVE
+        expected: String,
+        /// hex-encoded MRENCLAVE in the evidence
+        actual: String,
+    },
+
+    /// The attestation evidence attests another key.
+    #[error("attestation evidence doesn't attest the client key")]
+    ReportDataMismatchError,
+
+    /// Context is longer than the limit of the enclave.
+    #[error("context is {size} bytes, while expected to be at most 128 bytes")]
+    ContextTooLongError {
+        /// length of the context
+        size: usize,
+    },
+
+    /// Error while encrypting a value.
+    #[error("failed to encrypt: {0}")]
+    EncryptError(String),
+
+    /// Ciphertext is malformed, not encrypted to the client's key or not encrypted by the enclave.
+    #[error("ciphertext is not encrypted by the enclave to the client's key")]
+    DecryptError,
+
+    /// Value is decrypted from another context than expected.
+    #[error(
+        "value in context {actual:?} is decrypted while expected to be in context {expected:?}"
+    )]
+    ContextMismatchError {
+        /// expected context
+        expected: String,
+        /// context of the value
+        actual: String,
+    },
+
+    /// Key is not 32 bytes.
+    #[error("key is {size} bytes, while expected to be 32 bytes")]
+    InvalidKeyError {
+        /// length of the key
+        size: usize,
+    },
+}
@@ -0,0 +1,23 @@
+//! Client library to pass values with the enclave of encrypted-sql-ops,
+//! so that their plain text never appears in RDBMS's query text, logs or memory.
+//!
+//! ```ignore
+//! let published: String = /* SELECT ENCRYPTED_SQL_OPS_CLIENT_KEY()::TEXT */;
+//! let enclave_key = EnclaveKeyVerifier::new(mr_enclave, ias_root_cert).verify(&published)?;
+//!
+//! let ciphertext = enclave_key.encrypt_integer(42, "t.c_enc")?;
+//! // INSERT INTO t (c_enc) VALUES (ENCINTEGER_FROM_CLIENT($1)) with ciphertext
+//!
+//! // SELECT ENCINTEGER_TO_CLIENT(c_enc, $1) FROM t with client_key.public_key()
+//! let value = client_key.decrypt_integer(&returned, &enclave_key, "t.c_enc")?;
+//! ```
+
+#![deny(missing_debug_implementations, missing_docs)]
+
+mod client_key;
+mod enclave_key;
+mod error;
+
+pub use client_key::ClientKey;
+pub use enclave_key::{EnclaveKey, EnclaveKeyVerifier, MAX_CONTEXT_SIZE};
+pub use error::{ClientError, Result};
@@ -0,0 +1,77 @@
+//! Data types between clients and enclave.
+//!
+//! They are encrypted to the enclave's client key or to a client's key by sealed boxes (X25519 + XSalsa20-Poly1305),
+//! so that their plain text never appears in RDBMS's process memory, query text or logs.
+
+use std::{convert::TryInto, string::String, vec::Vec};
+
+/// The label following the client key in the report data of its attestation,
+/// which tells the report data from the ones of the other keys attested by the same enclave.
+pub const CLIENT_KEY_REPORT_DATA_LABEL: &[u8; 32] = b"encrypted-sql-ops client key\0\0\0\0";
+
+/// The format version of [ClientPayload](ClientPayload)
+pub const CLIENT_PAYLOAD_VERSION: u8 = 1;
+
+/// Plain-text value with the context it's encrypted in, e.g. `table.column`.
+///
+/// Serialized as `version (1 byte) | context length (2 bytes, big endian) | context | value`,
+/// where the value is the big-endian representation of the plain type, e.g. 4 bytes for INTEGER.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ClientPayload {
+    context: String,
+    value: Vec<u8>,
+}
+
+impl ClientPayload {
+    /// Constructor
+    pub fn new(value: Vec<u8>, context: String) -> Self {
+        Self { value, context }
+    }
+
+    /// INTEGER value
+    pub fn from_i32(value: i32, context: String) -> Self {
+        Self::new(value.to_be_bytes().to_vec(), context)
+    }
+
+    /// Get the INTEGER value, or `None` if the value isn't 4 bytes.
+    pub fn to_i32(&self) -> Option<i32> {
+        let bytes = self.value.as_slice().try_into().ok()?;
+        Some(i32::from_be_bytes(bytes))
+    }
+
+    /// Context of the value
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (Vec<u8>, String) {
+        (self.value, self.context)
+    }
+
+    /// Serialize to be encrypted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(3 + self.context.len() + self.value.len());
+        bytes.push(CLIENT_PAYLOAD_VERSION);
+        bytes.extend_from_slice(&(self.context.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(self.context.as_bytes());
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+
+    /// Deserialize the decrypted bytes, or `None` if they're malformed.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&version, rest) = bytes.split_first()?;
+        if version != CLIENT_PAYLOAD_VERSION || rest.len() < 2 {
+            return None;
+        }
+        let (context_len, rest) = rest.split_at(2);
+        let context_len = u16::from_be_bytes(context_len.try_into().ok()?) as usize;
+        if rest.len() < context_len {
+            return None;
+        }
+        let (context, value) = rest.split_at(context_len);
+        let context = String::from_utf8(context.to_vec()).ok()?;
+        Some(Self::new(value.to_vec(), context))
+    }
+}
@@ -1,10 +1,57 @@
 //! Commands registered via [register_ecall!()](frame-enclave::register_ecall).
 //!
-//! Has 1-to-1 relationship with SQL function calls.
-//! TODO: introduce exit-less mechanism.
+//! Has 1-to-1 relationship with SQL function calls,
+//! except for the state functions of aggregates, each of which accumulates a batch of rows.
 
 #![allow(missing_docs)]
 
 pub const ENCINTEGER_FROM: u32 = 1;
 pub const ENCINTEGER_AVG_STATE_FUNC: u32 = 2;
 pub const ENCINTEGER_AVG_FINAL_FUNC: u32 = 3;
+pub const LOAD_MASTER_KEY: u32 = 4;
+pub const ENCINTEGER_MIGRATE: u32 = 5;
+pub const ENCINTEGER_SUM_STATE_FUNC: u32 = 6;
+pub const ENCINTEGER_SUM_FINAL_FUNC: u32 = 7;
+pub const ENCINTEGER_COUNT_STATE_FUNC: u32 = 8;
+pub const ENCINTEGER_COUNT_FINAL_FUNC: u32 = 9;
+pub const ENCINTEGER_MIN_STATE_FUNC: u32 = 10;
+pub const ENCINTEGER_MIN_FINAL_FUNC: u32 = 11;
+pub const ENCINTEGER_MAX_STATE_FUNC: u32 = 12;
+pub const ENCINTEGER_MAX_FINAL_FUNC: u32 = 13;
+pub const ENCINTEGER_CMP: u32 = 14;
+pub const ENCINTEGER_EQ: u32 = 15;
+pub const ENCBIGINT_FROM: u32 = 16;
+pub const ENCREAL_FROM: u32 = 17;
+pub const ENCTEXT_FROM: u32 = 18;
+pub const ENCBIGINT_EQ: u32 = 19;
+pub const ENCREAL_EQ: u32 = 20;
+pub const ENCTEXT_EQ: u32 = 21;
+pub const ENCBIGINT_CMP: u32 = 22;
+pub const ENCREAL_CMP: u32 = 23;
+pub const ENCBIGINT_SUM_STATE_FUNC: u32 = 24;
+pub const ENCBIGINT_SUM_FINAL_FUNC: u32 = 25;
+pub const ENCBIGINT_MIN_STATE_FUNC: u32 = 26;
+pub const ENCBIGINT_MIN_FINAL_FUNC: u32 = 27;
+pub const ENCBIGINT_MAX_STATE_FUNC: u32 = 28;
+pub const ENCBIGINT_MAX_FINAL_FUNC: u32 = 29;
+pub const ENCREAL_SUM_STATE_FUNC: u32 = 30;
+pub const ENCREAL_SUM_FINAL_FUNC: u32 = 31;
+pub const ENCREAL_AVG_STATE_FUNC: u32 = 32;
+pub const ENCREAL_AVG_FINAL_FUNC: u32 = 33;
+pub const GET_CLIENT_KEY: u32 = 34;
+pub const ENCINTEGER_FROM_CLIENT: u32 = 35;
+pub const ENCINTEGER_TO_CLIENT: u32 = 36;
+pub const ENCINTEGER_AVG_COMBINE_FUNC: u32 = 37;
+pub const ENCINTEGER_SUM_COMBINE_FUNC: u32 = 38;
+pub const ENCINTEGER_COUNT_COMBINE_FUNC: u32 = 39;
+pub const ENCINTEGER_MIN_COMBINE_FUNC: u32 = 40;
+pub const ENCINTEGER_MAX_COMBINE_FUNC: u32 = 41;
+pub const ENCBIGINT_SUM_COMBINE_FUNC: u32 = 42;
+pub const ENCBIGINT_MIN_COMBINE_FUNC: u32 = 43;
+pub const ENCBIGINT_MAX_COMBINE_FUNC: u32 = 44;
+pub const ENCREAL_SUM_COMBINE_FUNC: u32 = 45;
+pub const ENCREAL_AVG_COMBINE_FUNC: u32 = 46;
+pub const ENCINTEGER_TAG: u32 = 47;
+pub const ENCBIGINT_TAG: u32 = 48;
+pub const ENCREAL_TAG: u32 = 49;
+pub const ENCTEXT_TAG: u32 = 50;
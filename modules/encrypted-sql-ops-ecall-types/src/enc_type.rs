@@ -1,7 +1,40 @@
 //! Encrypted SQL types.
 
+use crate::serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, string::String};
+
 pub mod enc_aggregate_state;
 
+mod encbigint;
 mod encinteger;
+mod encreal;
+mod enctag;
+mod enctext;
 
+pub use encbigint::EncBigInt;
 pub use encinteger::EncInteger;
+pub use encreal::EncReal;
+pub use enctag::EncTag;
+pub use enctext::EncText;
+
+/// Encrypted SQL type, passed between host and enclave as it is.
+pub trait EncType: Clone + Debug + Serialize + DeserializeOwned {
+    /// Plain-text type of the SQL values, which the encrypted values are constructed from.
+    type Raw: Clone + Debug + Serialize + DeserializeOwned;
+}
+
+impl EncType for EncInteger {
+    type Raw = i32;
+}
+
+impl EncType for EncBigInt {
+    type Raw = i64;
+}
+
+impl EncType for EncReal {
+    type Raw = f32;
+}
+
+impl EncType for EncText {
+    type Raw = String;
+}
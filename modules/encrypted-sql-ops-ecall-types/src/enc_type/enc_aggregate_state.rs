@@ -2,6 +2,6 @@
 //!
 //! Concrete calculation on receiving next field value should be hidden inside enclave.
 
-mod enc_avg_state;
+mod enc_accumulator_state;
 
-pub use enc_avg_state::EncAvgState;
+pub use enc_accumulator_state::{EncAccumulator, EncAggregateState};
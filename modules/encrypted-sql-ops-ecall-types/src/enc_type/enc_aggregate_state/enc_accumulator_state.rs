@@ -0,0 +1,33 @@
+use crate::serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// State to calculate an aggregate (Encrypted).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub enum EncAggregateState {
+    /// Intermediate state
+    Interm {
+        /// current accumulator, which also tells the aggregate it belongs to
+        acc: EncAccumulator,
+    },
+
+    /// no values yet
+    Initial,
+}
+
+/// Encrypted accumulator of [EncAggregateState](EncAggregateState).
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EncAccumulator(Vec<u8>);
+
+impl From<Vec<u8>> for EncAccumulator {
+    fn from(encrypted: Vec<u8>) -> Self {
+        Self(encrypted)
+    }
+}
+
+impl From<EncAccumulator> for Vec<u8> {
+    fn from(e: EncAccumulator) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,26 @@
+use crate::serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// Encrypted BIGINT type.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EncBigInt(Vec<u8>);
+
+impl EncBigInt {
+    /// Get raw representation of encrypted BIGINT.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for EncBigInt {
+    fn from(encrypted: Vec<u8>) -> Self {
+        Self(encrypted)
+    }
+}
+
+impl From<EncBigInt> for Vec<u8> {
+    fn from(e: EncBigInt) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,26 @@
+use crate::serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// Encrypted REAL type.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EncReal(Vec<u8>);
+
+impl EncReal {
+    /// Get raw representation of encrypted REAL.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for EncReal {
+    fn from(encrypted: Vec<u8>) -> Self {
+        Self(encrypted)
+    }
+}
+
+impl From<EncReal> for Vec<u8> {
+    fn from(e: EncReal) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,28 @@
+use crate::serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// Deterministic tag of an encrypted value, equal for the equal values in the same tag domain.
+///
+/// It's a keyed hash rather than a ciphertext, so it's compared and hashed without enclave.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EncTag(Vec<u8>);
+
+impl EncTag {
+    /// Get raw representation of the tag.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for EncTag {
+    fn from(tag: Vec<u8>) -> Self {
+        Self(tag)
+    }
+}
+
+impl From<EncTag> for Vec<u8> {
+    fn from(e: EncTag) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,26 @@
+use crate::serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// Encrypted TEXT type.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EncText(Vec<u8>);
+
+impl EncText {
+    /// Get raw representation of encrypted TEXT.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for EncText {
+    fn from(encrypted: Vec<u8>) -> Self {
+        Self(encrypted)
+    }
+}
+
+impl From<EncText> for Vec<u8> {
+    fn from(e: EncText) -> Self {
+        e.0
+    }
+}
@@ -1,13 +1,39 @@
 //! Input/Output types of enclave side.
 
-mod enclave_enc_avg_state;
-mod enclave_enc_avg_state_with_next;
+mod enclave_aggregate_result;
+mod enclave_client_ciphertext;
+mod enclave_client_key;
+mod enclave_enc_aggregate_state;
+mod enclave_enc_aggregate_state_with_batch;
 mod enclave_enc_integer;
+mod enclave_enc_integer_for_client;
+mod enclave_enc_integer_with_context;
+mod enclave_enc_pair;
+mod enclave_enc_tag;
+mod enclave_enc_value;
+mod enclave_master_key;
+mod enclave_ordering;
+mod enclave_plain_bool;
 mod enclave_plain_integer;
-mod enclave_plain_real;
+mod enclave_plain_integer_with_context;
+mod enclave_plain_with_context;
 
-pub use enclave_enc_avg_state::EnclaveEncAvgState;
-pub use enclave_enc_avg_state_with_next::EnclaveEncAvgStateWithNext;
+pub use enclave_aggregate_result::EnclaveAggregateResult;
+pub use enclave_client_ciphertext::EnclaveClientCiphertext;
+pub use enclave_client_key::{EnclaveClientKey, EnclaveGetClientKey};
+pub use enclave_enc_aggregate_state::EnclaveEncAggregateState;
+pub use enclave_enc_aggregate_state_with_batch::{
+    EnclaveEncAggregateStateWithBatch, MAX_AGGREGATE_BATCH_SIZE,
+};
 pub use enclave_enc_integer::EnclaveEncInteger;
+pub use enclave_enc_integer_for_client::EnclaveEncIntegerForClient;
+pub use enclave_enc_integer_with_context::EnclaveEncIntegerWithContext;
+pub use enclave_enc_pair::EnclaveEncPair;
+pub use enclave_enc_tag::EnclaveEncTag;
+pub use enclave_enc_value::EnclaveEncValue;
+pub use enclave_master_key::{EnclaveLoadMasterKey, EnclaveMasterKeySource};
+pub use enclave_ordering::EnclaveOrdering;
+pub use enclave_plain_bool::EnclavePlainBool;
 pub use enclave_plain_integer::EnclavePlainInteger;
-pub use enclave_plain_real::EnclavePlainReal;
+pub use enclave_plain_integer_with_context::EnclavePlainIntegerWithContext;
+pub use enclave_plain_with_context::EnclavePlainWithContext;
@@ -0,0 +1,25 @@
+use crate::{
+    enc_type::{EncBigInt, EncInteger, EncReal},
+    serde::{Deserialize, Serialize},
+};
+use frame_common::EnclaveOutput;
+
+/// Result of an aggregate, which leaves enclave encrypted or in plain text as declared for the aggregate.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub enum EnclaveAggregateResult {
+    /// INTEGER encrypted in the context of the aggregated values.
+    EncInteger(EncInteger),
+    /// BIGINT encrypted in the context of the aggregated values.
+    EncBigInt(EncBigInt),
+    /// REAL encrypted in the context of the aggregated values.
+    EncReal(EncReal),
+    /// Plain-text BIGINT.
+    PlainBigInt(i64),
+    /// Plain-text REAL.
+    PlainReal(f32),
+    /// SQL NULL, e.g. SUM of no values.
+    Null,
+}
+
+impl EnclaveOutput for EnclaveAggregateResult {}
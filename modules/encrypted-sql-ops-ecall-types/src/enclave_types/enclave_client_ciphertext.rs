@@ -0,0 +1,26 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::{EnclaveInput, EnclaveOutput};
+use std::vec::Vec;
+
+/// [ClientPayload](crate::client_types::ClientPayload) encrypted by a client to the enclave's client key,
+/// or by enclave to a client's key.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveClientCiphertext(Vec<u8>);
+
+impl EnclaveInput for EnclaveClientCiphertext {}
+
+impl EnclaveOutput for EnclaveClientCiphertext {}
+
+impl From<Vec<u8>> for EnclaveClientCiphertext {
+    fn from(ciphertext: Vec<u8>) -> Self {
+        Self(ciphertext)
+    }
+}
+
+impl EnclaveClientCiphertext {
+    /// Get raw representation
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
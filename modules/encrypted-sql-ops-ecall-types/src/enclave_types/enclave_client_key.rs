@@ -0,0 +1,38 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::{EnclaveInput, EnclaveOutput};
+use std::vec::Vec;
+
+/// Request to publish the enclave's client key.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveGetClientKey;
+
+impl EnclaveInput for EnclaveGetClientKey {}
+
+/// The public key which clients encrypt values to, with the attestation evidence binding it to the enclave.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveClientKey {
+    /// X25519 public key
+    public_key: Vec<u8>,
+    /// JSON-serialized attestation evidence, whose report data is the public key followed by
+    /// [CLIENT_KEY_REPORT_DATA_LABEL](crate::client_types::CLIENT_KEY_REPORT_DATA_LABEL).
+    evidence: Vec<u8>,
+}
+
+impl EnclaveOutput for EnclaveClientKey {}
+
+impl EnclaveClientKey {
+    /// Constructor
+    pub fn new(public_key: Vec<u8>, evidence: Vec<u8>) -> Self {
+        Self {
+            public_key,
+            evidence,
+        }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (Vec<u8>, Vec<u8>) {
+        (self.public_key, self.evidence)
+    }
+}
@@ -0,0 +1,27 @@
+use frame_common::{EnclaveInput, EnclaveOutput};
+
+use crate::{
+    enc_type::enc_aggregate_state::EncAggregateState,
+    serde::{Deserialize, Serialize},
+};
+
+/// Intermediate state to calculate SUM, COUNT, MIN or MAX (encrypted).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncAggregateState(EncAggregateState);
+
+impl EnclaveInput for EnclaveEncAggregateState {}
+impl EnclaveOutput for EnclaveEncAggregateState {}
+
+impl From<EncAggregateState> for EnclaveEncAggregateState {
+    fn from(e: EncAggregateState) -> Self {
+        Self(e)
+    }
+}
+
+impl EnclaveEncAggregateState {
+    /// Get inner representation
+    pub fn into_enc_aggregate_state(self) -> EncAggregateState {
+        self.0
+    }
+}
@@ -0,0 +1,34 @@
+use frame_common::EnclaveInput;
+
+use crate::{
+    enc_type::{enc_aggregate_state::EncAggregateState, EncInteger},
+    serde::{Deserialize, Serialize},
+};
+use std::vec::Vec;
+
+/// The maximum number of values accumulated by an ecall of state functions,
+/// which keeps the ecall input within the host controllers' limit even for the values with the longest context.
+pub const MAX_AGGREGATE_BATCH_SIZE: usize = 256;
+
+/// Intermediate state to calculate an aggregate (SUM, COUNT, MIN, MAX or AVG) with the next values (encrypted),
+/// which are accumulated in order by an ecall.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncAggregateStateWithBatch<E = EncInteger> {
+    state: EncAggregateState,
+    batch: Vec<E>,
+}
+
+impl<E> EnclaveInput for EnclaveEncAggregateStateWithBatch<E> {}
+
+impl<E> EnclaveEncAggregateStateWithBatch<E> {
+    /// Constructor
+    pub fn new(state: EncAggregateState, batch: Vec<E>) -> Self {
+        Self { state, batch }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (EncAggregateState, Vec<E>) {
+        (self.state, self.batch)
+    }
+}
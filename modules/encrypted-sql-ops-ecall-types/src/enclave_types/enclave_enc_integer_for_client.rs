@@ -0,0 +1,28 @@
+use crate::{
+    enc_type::EncInteger,
+    serde::{Deserialize, Serialize},
+};
+use frame_common::EnclaveInput;
+use std::vec::Vec;
+
+/// Encrypted INTEGER with the public key of the client to re-encrypt it to.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncIntegerForClient {
+    value: EncInteger,
+    client_key: Vec<u8>,
+}
+
+impl EnclaveInput for EnclaveEncIntegerForClient {}
+
+impl EnclaveEncIntegerForClient {
+    /// Constructor
+    pub fn new(value: EncInteger, client_key: Vec<u8>) -> Self {
+        Self { value, client_key }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (EncInteger, Vec<u8>) {
+        (self.value, self.client_key)
+    }
+}
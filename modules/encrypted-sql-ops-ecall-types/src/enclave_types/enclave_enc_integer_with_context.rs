@@ -0,0 +1,28 @@
+use crate::{
+    enc_type::EncInteger,
+    serde::{Deserialize, Serialize},
+};
+use frame_common::EnclaveInput;
+use std::string::String;
+
+/// Encrypted INTEGER with the context to re-encrypt it in, e.g. `table.column`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncIntegerWithContext {
+    value: EncInteger,
+    context: String,
+}
+
+impl EnclaveInput for EnclaveEncIntegerWithContext {}
+
+impl EnclaveEncIntegerWithContext {
+    /// Constructor
+    pub fn new(value: EncInteger, context: String) -> Self {
+        Self { value, context }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (EncInteger, String) {
+        (self.value, self.context)
+    }
+}
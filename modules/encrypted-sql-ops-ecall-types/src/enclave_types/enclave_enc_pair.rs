@@ -0,0 +1,24 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::EnclaveInput;
+
+/// Pair of encrypted values of the same type to compare, or encrypted states of the same aggregate to combine.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncPair<E> {
+    lhs: E,
+    rhs: E,
+}
+
+impl<E> EnclaveInput for EnclaveEncPair<E> {}
+
+impl<E> EnclaveEncPair<E> {
+    /// Constructor
+    pub fn new(lhs: E, rhs: E) -> Self {
+        Self { lhs, rhs }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (E, E) {
+        (self.lhs, self.rhs)
+    }
+}
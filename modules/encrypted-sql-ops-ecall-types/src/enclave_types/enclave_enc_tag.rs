@@ -0,0 +1,25 @@
+use crate::{
+    enc_type::EncTag,
+    serde::{Deserialize, Serialize},
+};
+use frame_common::EnclaveOutput;
+
+/// Deterministic tag of an encrypted value.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncTag(EncTag);
+
+impl EnclaveOutput for EnclaveEncTag {}
+
+impl From<EncTag> for EnclaveEncTag {
+    fn from(e: EncTag) -> Self {
+        Self(e)
+    }
+}
+
+impl EnclaveEncTag {
+    /// Get inner representation
+    pub fn into_enctag(self) -> EncTag {
+        self.0
+    }
+}
@@ -0,0 +1,27 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::{EnclaveInput, EnclaveOutput};
+
+/// Encrypted value of any encrypted type.
+///
+/// Output of the constructors but INTEGER's, which has [EnclaveEncInteger](crate::enclave_types::EnclaveEncInteger),
+/// and input of the ones deriving from a single value, e.g. the tags.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveEncValue<E>(E);
+
+impl<E> EnclaveInput for EnclaveEncValue<E> {}
+
+impl<E> EnclaveOutput for EnclaveEncValue<E> {}
+
+impl<E> From<E> for EnclaveEncValue<E> {
+    fn from(e: E) -> Self {
+        Self(e)
+    }
+}
+
+impl<E> EnclaveEncValue<E> {
+    /// Get inner representation
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
@@ -0,0 +1,23 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::{EnclaveInput, EnclaveOutput};
+
+/// Request to load the master key, which never leaves the enclave.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclaveLoadMasterKey;
+
+impl EnclaveInput for EnclaveLoadMasterKey {}
+
+/// Where the loaded master key came from.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub enum EnclaveMasterKeySource {
+    /// Unsealed from the local storage.
+    Sealed,
+    /// Provisioned by the key-vault nodes and sealed to the local storage.
+    KeyVault,
+    /// Newly generated inside enclave and sealed to the local storage.
+    Generated,
+}
+
+impl EnclaveOutput for EnclaveMasterKeySource {}
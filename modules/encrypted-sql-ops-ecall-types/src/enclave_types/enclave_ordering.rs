@@ -0,0 +1,37 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::EnclaveOutput;
+use std::cmp::Ordering;
+
+/// Plain-text order of two values.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub enum EnclaveOrdering {
+    /// The left-hand side is less than the right-hand side.
+    Less,
+    /// Both are equal.
+    Equal,
+    /// The left-hand side is greater than the right-hand side.
+    Greater,
+}
+
+impl EnclaveOutput for EnclaveOrdering {}
+
+impl From<Ordering> for EnclaveOrdering {
+    fn from(o: Ordering) -> Self {
+        match o {
+            Ordering::Less => Self::Less,
+            Ordering::Equal => Self::Equal,
+            Ordering::Greater => Self::Greater,
+        }
+    }
+}
+
+impl From<EnclaveOrdering> for Ordering {
+    fn from(e: EnclaveOrdering) -> Self {
+        match e {
+            EnclaveOrdering::Less => Self::Less,
+            EnclaveOrdering::Equal => Self::Equal,
+            EnclaveOrdering::Greater => Self::Greater,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::EnclaveOutput;
+
+/// Plain-text BOOLEAN.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclavePlainBool(bool);
+
+impl EnclaveOutput for EnclavePlainBool {}
+
+impl From<bool> for EnclavePlainBool {
+    fn from(b: bool) -> Self {
+        Self(b)
+    }
+}
+
+impl EnclavePlainBool {
+    /// Gets raw representation
+    pub fn to_bool(&self) -> bool {
+        self.0
+    }
+}
@@ -0,0 +1,28 @@
+use crate::{
+    enclave_types::EnclavePlainInteger,
+    serde::{Deserialize, Serialize},
+};
+use frame_common::EnclaveInput;
+use std::string::String;
+
+/// Plain-text INTEGER with the context to encrypt it in, e.g. `table.column`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclavePlainIntegerWithContext {
+    integer: EnclavePlainInteger,
+    context: String,
+}
+
+impl EnclaveInput for EnclavePlainIntegerWithContext {}
+
+impl EnclavePlainIntegerWithContext {
+    /// Constructor
+    pub fn new(integer: EnclavePlainInteger, context: String) -> Self {
+        Self { integer, context }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (EnclavePlainInteger, String) {
+        (self.integer, self.context)
+    }
+}
@@ -0,0 +1,25 @@
+use crate::serde::{Deserialize, Serialize};
+use frame_common::EnclaveInput;
+use std::string::String;
+
+/// Plain-text value (BIGINT, REAL or TEXT) with the context to encrypt it in, e.g. `table.column`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct EnclavePlainWithContext<T> {
+    value: T,
+    context: String,
+}
+
+impl<T> EnclaveInput for EnclavePlainWithContext<T> {}
+
+impl<T> EnclavePlainWithContext<T> {
+    /// Constructor
+    pub fn new(value: T, context: String) -> Self {
+        Self { value, context }
+    }
+
+    /// Get raw representation
+    pub fn into_inner(self) -> (T, String) {
+        (self.value, self.context)
+    }
+}
@@ -16,6 +16,7 @@ use serde_sgx as serde;
 #[cfg(all(not(feature = "sgx"), feature = "std"))]
 use serde_std as serde;
 
+pub mod client_types;
 pub mod ecall_cmd;
 pub mod enc_type;
 pub mod enclave_types;
@@ -1,10 +1,21 @@
 //! Aggregate calculations over aggregate states.
 
-/// TODO: use generics
+use crate::error::Result;
+
+/// Calculation of an aggregate, which takes values one by one and emits a result.
 pub trait AggregateCalc {
+    /// Type of the aggregated values
+    type Input;
+
+    /// Type of the result
+    type Output;
+
     /// Takes a non-NULL value
-    fn accumulate(&mut self, val: i32);
+    fn accumulate(&mut self, val: Self::Input) -> Result<()>;
+
+    /// Takes the state over another part of the values, e.g. the ones scanned by a parallel worker
+    fn combine(&mut self, other: Self) -> Result<()>;
 
     /// Emits an aggregated value
-    fn finalize(self) -> f32;
+    fn finalize(self) -> Self::Output;
 }
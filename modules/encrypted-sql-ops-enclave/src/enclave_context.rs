@@ -2,13 +2,47 @@
 //!
 //! FIXME: Writing twice almost the same codes as KeyVaultEnclaveContext
 
-use frame_config::{ANONIFY_PARAMS_DIR, CMD_DEC_SECRET_DIR, IAS_ROOT_CERT};
+use crate::error::EnclaveError;
+use crate::owner_config::{OwnerConfig, SignedOwnerConfig, OWNER_PUBLIC_KEY};
+use crate::type_crypt::{
+    ClientCipher, CryptContext, MasterKey, TagKey, TypeCipher, DEFAULT_BUCKET_SIZE,
+};
+use anyhow::anyhow;
+use frame_config::{
+    ANONIFY_PARAMS_DIR, CMD_DEC_SECRET_DIR, IAS_ROOT_CERT, KEY_VAULT_ENCLAVE_MEASUREMENT,
+    PJ_ROOT_DIR,
+};
+use frame_mra_tls::{
+    key_vault::{
+        request::{KeyVaultCmd, KeyVaultRequest, ProvisionSharedKeyRequestBody},
+        response::ProvisionedSharedKey,
+    },
+    AttestedTlsConfig, Client, ClientConfig,
+};
 use frame_runtime::ConfigGetter;
-use frame_sodium::StoreEnclaveDecryptionKey;
+use frame_sodium::{SodiumPubKey, StoreEnclaveDecryptionKey};
 use frame_treekem::StorePathSecrets;
-use std::{env, string::String, vec::Vec};
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveMasterKeySource;
+use remote_attestation::provider_from_env;
+use sgx_types::sgx_report_data_t;
+use std::{
+    convert::TryInto,
+    env,
+    string::{String, ToString},
+    sync::SgxRwLock,
+    vec::Vec,
+};
+use tracing::info;
 
-/// FIXME: I don't get what is this!?
+/// The sealed master key is stored in ANONIFY_PARAMS_DIR/${MASTER_KEY_FILE_NAME}
+const MASTER_KEY_FILE_NAME: &str = "encrypted_sql_ops_master_key";
+/// The name of the master key shared in the key-vault nodes by the enclaves with the same MRSIGNER and ISVPRODID.
+/// The key-vault namespaces the shared keys by them, so the enclaves of the other products get other keys under the name.
+const MASTER_KEY_SHARED_NAME: &str = "encrypted-sql-ops-master-key";
+/// The version of the owner config loaded last time is sealed in ANONIFY_PARAMS_DIR/${OWNER_CONFIG_VERSION_FILE_NAME}
+const OWNER_CONFIG_VERSION_FILE_NAME: &str = "encrypted_sql_ops_owner_config_version";
+
+/// The context of the encrypted-sql-ops enclave, holding the master key and the owner config loaded in it.
 #[derive(Debug)]
 pub struct EncryptedSqlOpsEnclaveContext {
     version: usize,
@@ -18,6 +52,19 @@ pub struct EncryptedSqlOpsEnclaveContext {
     store_path_secrets: StorePathSecrets,
     store_enclave_dec_key: StoreEnclaveDecryptionKey,
     ias_root_cert: Vec<u8>,
+    /// The key-vault nodes provisioning the master key. Empty if the key is generated by each enclave.
+    key_vault_endpoints: Vec<String>,
+    master_key: SgxRwLock<Option<MasterKey>>,
+    /// The contexts whose values are only compared for equality, not to leak their order, listed in the owner config.
+    unordered_contexts: Vec<String>,
+    /// The contexts whose values are tagged deterministically, paired with the domains of their tags, listed in the owner config.
+    tagged_contexts: Vec<(String, String)>,
+    /// The contexts whose values are only encrypted by the allowed clients, listed in the owner config.
+    client_only_contexts: Vec<String>,
+    /// The size of the buckets which the lengths of encrypted TEXTs are padded to, set in the owner config.
+    text_bucket_size: usize,
+    /// The public keys of the clients which the values are decrypted for, listed in the owner config.
+    allowed_client_keys: Vec<SodiumPubKey>,
 }
 
 impl ConfigGetter for EncryptedSqlOpsEnclaveContext {
@@ -62,6 +109,14 @@ impl EncryptedSqlOpsEnclaveContext {
         let sub_key = env::var("SUB_KEY").expect("SUB_KEY is not set");
         let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
         let store_enclave_dec_key = StoreEnclaveDecryptionKey::new(&*ANONIFY_PARAMS_DIR);
+        let key_vault_endpoints = env::var("KEY_VAULT_ENDPOINT_FOR_ENCRYPTED_SQL_OPS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+        let owner_config =
+            load_owner_config(&store_enclave_dec_key).expect("Failed to load the owner config");
 
         Self {
             version,
@@ -71,6 +126,293 @@ impl EncryptedSqlOpsEnclaveContext {
             store_path_secrets,
             store_enclave_dec_key,
             ias_root_cert: (&*IAS_ROOT_CERT).to_vec(),
+            key_vault_endpoints,
+            master_key: SgxRwLock::new(None),
+            unordered_contexts: vec![],
+            tagged_contexts: vec![],
+            client_only_contexts: vec![],
+            text_bucket_size: DEFAULT_BUCKET_SIZE,
+            allowed_client_keys: vec![],
+        }
+        .with_owner_config(&owner_config)
+    }
+
+    fn with_owner_config(mut self, owner_config: &OwnerConfig) -> Self {
+        self.allowed_client_keys = owner_config
+            .client_public_keys()
+            .expect("Failed to parse the client public keys in the owner config");
+        self.unordered_contexts = owner_config.unordered_contexts().to_vec();
+        self.tagged_contexts = owner_config
+            .tagged_contexts()
+            .iter()
+            .map(|tagged| (tagged.context().to_string(), tagged.domain().to_string()))
+            .collect();
+        self.client_only_contexts = owner_config.client_only_contexts().to_vec();
+        self.text_bucket_size = owner_config.text_bucket_size();
+        self
+    }
+
+    /// The context with the owner config and the master key loaded, only for testing.
+    #[cfg(debug_assertions)]
+    pub(crate) fn new_for_test(owner_config: &OwnerConfig, master_key: MasterKey) -> Self {
+        Self {
+            version: 0,
+            ias_url: String::new(),
+            sub_key: String::new(),
+            spid: String::new(),
+            store_path_secrets: StorePathSecrets::default(),
+            store_enclave_dec_key: StoreEnclaveDecryptionKey::default(),
+            ias_root_cert: vec![],
+            key_vault_endpoints: vec![],
+            master_key: SgxRwLock::new(Some(master_key)),
+            unordered_contexts: vec![],
+            tagged_contexts: vec![],
+            client_only_contexts: vec![],
+            text_bucket_size: DEFAULT_BUCKET_SIZE,
+            allowed_client_keys: vec![],
+        }
+        .with_owner_config(owner_config)
+    }
+
+    /// Ensure that the values in the context can be ordered, i.e. it's not listed in `unordered_contexts` of the owner config.
+    pub fn ensure_ordered(&self, context: &CryptContext) -> anyhow::Result<()> {
+        if self
+            .unordered_contexts
+            .iter()
+            .any(|unordered| unordered == context.as_str())
+        {
+            return Err(EnclaveError::OrderingDisabledError {
+                context: context.as_str().to_string(),
+            }
+            .into());
         }
+        Ok(())
     }
+
+    /// Ensure that the SQL can encrypt values in the context, i.e. it's not listed in `client_only_contexts` of the owner config,
+    /// nor derived from one listed.
+    pub fn ensure_not_client_only(&self, context: &CryptContext) -> anyhow::Result<()> {
+        if self.is_client_only(context) {
+            return Err(EnclaveError::ClientOnlyContextError {
+                context: context.as_str().to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Ensure that the client can encrypt values in the context.
+    /// Anyone can encrypt values to the attested client key, including the database,
+    /// so the values in the client-only contexts must be encrypted from a client key listed in the owner config.
+    pub fn ensure_client_allowed(
+        &self,
+        context: &CryptContext,
+        sender: &SodiumPubKey,
+    ) -> anyhow::Result<()> {
+        if self.is_client_only(context) && !self.client_cipher()?.is_allowed_client(sender) {
+            return Err(EnclaveError::ClientOnlyContextError {
+                context: context.as_str().to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn is_client_only(&self, context: &CryptContext) -> bool {
+        self.client_only_contexts.iter().any(|client_only| {
+            context.as_str() == client_only
+                || context
+                    .as_str()
+                    .strip_prefix(client_only.as_str())
+                    .map_or(false, |derived| derived.starts_with('#'))
+        })
+    }
+
+    /// The domain of the tags of the values in the context, which must be listed in `tagged_contexts` of the owner config.
+    pub fn tag_domain(&self, context: &CryptContext) -> anyhow::Result<&str> {
+        self.tagged_contexts
+            .iter()
+            .find(|(tagged, _)| tagged == context.as_str())
+            .map(|(_, domain)| domain.as_str())
+            .ok_or_else(|| {
+                EnclaveError::TaggingDisabledError {
+                    context: context.as_str().to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// The cipher of the encrypted types by the master key loaded by [load_master_key()](Self::load_master_key).
+    pub fn type_cipher(&self) -> anyhow::Result<TypeCipher> {
+        let cipher = TypeCipher::new(self.master_key()?).with_bucket_size(self.text_bucket_size)?;
+        Ok(cipher)
+    }
+
+    /// The cipher of the values passed with clients by the client key derived from the master key.
+    pub fn client_cipher(&self) -> anyhow::Result<ClientCipher> {
+        let cipher = ClientCipher::new(&self.master_key()?, self.allowed_client_keys.clone())?;
+        Ok(cipher)
+    }
+
+    /// The key of the deterministic tags derived from the master key.
+    pub fn tag_key(&self) -> anyhow::Result<TagKey> {
+        Ok(TagKey::new(&self.master_key()?))
+    }
+
+    /// Attest the client key, returning the JSON-serialized evidence which clients verify before encrypting values to it.
+    pub fn attest_client_key(&self, cipher: &ClientCipher) -> anyhow::Result<Vec<u8>> {
+        let report_data = sgx_report_data_t {
+            d: cipher.report_data(),
+        };
+        let evidence = provider_from_env(
+            &self.spid,
+            &self.ias_url,
+            &self.sub_key,
+            self.ias_root_cert.clone(),
+        )?
+        .attest(&report_data)
+        .map_err(|e| anyhow!("{:?}", e))?;
+        serde_json::to_vec(&evidence).map_err(Into::into)
+    }
+
+    fn master_key(&self) -> anyhow::Result<MasterKey> {
+        let master_key = self
+            .master_key
+            .read()
+            .map_err(|e| anyhow!("Failed to acquire the master key lock: {:?}", e))?
+            .clone()
+            .ok_or(EnclaveError::MasterKeyNotLoadedError)?;
+        Ok(master_key)
+    }
+
+    /// Load the master key sealed in the local storage.
+    /// If not sealed yet, it's provisioned by the key-vault nodes if they are configured,
+    /// so that all the replicas share it, or newly generated otherwise. Then it's sealed to the local storage.
+    pub fn load_master_key(&self) -> anyhow::Result<EnclaveMasterKeySource> {
+        let is_sealed = self
+            .store_enclave_dec_key
+            .get_all_file_names()?
+            .iter()
+            .any(|file_name| file_name == MASTER_KEY_FILE_NAME);
+        let (master_key, source) = if is_sealed {
+            let master_key = self
+                .store_enclave_dec_key
+                .load_secret_from_local_filesystem(MASTER_KEY_FILE_NAME)?;
+            (
+                MasterKey::from_bytes(&master_key)?,
+                EnclaveMasterKeySource::Sealed,
+            )
+        } else {
+            let (master_key, source) = if self.key_vault_endpoints.is_empty() {
+                (MasterKey::new_random()?, EnclaveMasterKeySource::Generated)
+            } else {
+                (
+                    self.provision_master_key()?,
+                    EnclaveMasterKeySource::KeyVault,
+                )
+            };
+            self.store_enclave_dec_key
+                .save_secret_to_local_filesystem(master_key.as_bytes(), MASTER_KEY_FILE_NAME)?;
+            (master_key, source)
+        };
+        info!("Loaded the master key ({:?})", source);
+
+        *self
+            .master_key
+            .write()
+            .map_err(|e| anyhow!("Failed to acquire the master key lock: {:?}", e))? =
+            Some(master_key);
+        Ok(source)
+    }
+
+    /// Propose a new master key to the key-vault nodes over MRA-TLS,
+    /// which return the key proposed first by any enclave signed by the same key for the encrypted-sql-ops product.
+    fn provision_master_key(&self) -> anyhow::Result<MasterKey> {
+        let attested_tls_config = AttestedTlsConfig::new_by_ra(
+            &self.spid,
+            &self.ias_url,
+            &self.sub_key,
+            self.ias_root_cert.clone(),
+        )?;
+        let client_config = ClientConfig::from_attested_tls_config(attested_tls_config)?
+            .set_attestation_report_verifier(
+                self.ias_root_cert.clone(),
+                *KEY_VAULT_ENCLAVE_MEASUREMENT,
+            );
+
+        let proposed_key = MasterKey::new_random()?;
+        let request = KeyVaultRequest::new(
+            KeyVaultCmd::ProvisionSharedKey,
+            ProvisionSharedKeyRequestBody::new(
+                MASTER_KEY_SHARED_NAME.to_string(),
+                proposed_key.as_bytes().to_vec(),
+            ),
+        );
+        let provisioned: ProvisionedSharedKey =
+            Client::send_request_with_failover(&self.key_vault_endpoints, &client_config, request)?;
+
+        MasterKey::from_bytes(provisioned.key()).map_err(Into::into)
+    }
+}
+
+/// Load the config signed by the owner from `ENCRYPTED_SQL_OPS_OWNER_CONFIG_PATH`, relative to `PJ_ROOT_DIR`,
+/// verifying it by the owner's public key compiled into the enclave.
+/// Its version must be at least the one loaded last time, which is sealed so that the host can't roll it back by restarting.
+/// Returns the default config if no config file has ever been configured.
+fn load_owner_config(store: &StoreEnclaveDecryptionKey) -> anyhow::Result<OwnerConfig> {
+    let min_version = if store
+        .get_all_file_names()?
+        .iter()
+        .any(|file_name| file_name == OWNER_CONFIG_VERSION_FILE_NAME)
+    {
+        let sealed = store.load_secret_from_local_filesystem(OWNER_CONFIG_VERSION_FILE_NAME)?;
+        let sealed: [u8; 8] = sealed[..]
+            .try_into()
+            .map_err(|_| anyhow!("The sealed owner config version is malformed"))?;
+        Some(u64::from_le_bytes(sealed))
+    } else {
+        None
+    };
+
+    let config_path = match env::var("ENCRYPTED_SQL_OPS_OWNER_CONFIG_PATH") {
+        Ok(config_path) if !config_path.is_empty() => config_path,
+        _ => {
+            return match min_version {
+                None => Ok(OwnerConfig::default()),
+                Some(_) => Err(anyhow!(
+                    "ENCRYPTED_SQL_OPS_OWNER_CONFIG_PATH is not set while an owner config was loaded before"
+                )),
+            }
+        }
+    };
+    let public_key = OWNER_PUBLIC_KEY.ok_or_else(|| {
+        anyhow!("ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY is not compiled into the enclave")
+    })?;
+    let public_key = hex::decode(public_key.trim()).map_err(|e| {
+        anyhow!(
+            "Failed to decode ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY: {:?}",
+            e
+        )
+    })?;
+    let mut file_path = PJ_ROOT_DIR.clone();
+    file_path.push(config_path);
+
+    let content = std::untrusted::fs::read(file_path)?;
+    let config = SignedOwnerConfig::from_bytes(&content)?.verify(&public_key)?;
+    if let Some(min_version) = min_version {
+        if config.version() < min_version {
+            return Err(anyhow!(
+                "The owner config version {} must be at least the loaded version {}",
+                config.version(),
+                min_version
+            ));
+        }
+    }
+    store.save_secret_to_local_filesystem(
+        &config.version().to_le_bytes(),
+        OWNER_CONFIG_VERSION_FILE_NAME,
+    )?;
+    info!("The owner config version {} is loaded", config.version());
+
+    Ok(config)
 }
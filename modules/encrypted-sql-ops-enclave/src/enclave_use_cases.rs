@@ -1,9 +1,53 @@
 //! Use cases executed in enclave.
 
-mod enc_integer_avg_final_func_use_case;
-mod enc_integer_avg_state_func_use_case;
-mod enc_integer_from_use_case;
+mod enc_aggregate_combine_func_use_case;
+mod enc_aggregate_final_func_use_case;
+mod enc_aggregate_state_func_use_case;
+mod enc_cmp_use_case;
+mod enc_eq_use_case;
+mod enc_from_use_case;
+pub(crate) mod enc_integer_from_client_use_case;
+pub(crate) mod enc_integer_from_use_case;
+mod enc_integer_migrate_use_case;
+mod enc_integer_to_client_use_case;
+mod enc_tag_use_case;
+mod get_client_key_use_case;
+mod load_master_key_use_case;
 
-pub use enc_integer_avg_final_func_use_case::EncIntegerAvgFinalFuncUseCase;
-pub use enc_integer_avg_state_func_use_case::EncIntegerAvgStateFuncUseCase;
+pub use enc_aggregate_combine_func_use_case::{
+    EncAggregateCombineFuncUseCase, EncBigIntMaxCombineFuncUseCase, EncBigIntMinCombineFuncUseCase,
+    EncBigIntSumCombineFuncUseCase, EncIntegerAvgCombineFuncUseCase,
+    EncIntegerCountCombineFuncUseCase, EncIntegerMaxCombineFuncUseCase,
+    EncIntegerMinCombineFuncUseCase, EncIntegerSumCombineFuncUseCase, EncRealAvgCombineFuncUseCase,
+    EncRealSumCombineFuncUseCase,
+};
+pub use enc_aggregate_final_func_use_case::{
+    EncAggregateFinalFuncUseCase, EncBigIntMaxFinalFuncUseCase, EncBigIntMinFinalFuncUseCase,
+    EncBigIntSumFinalFuncUseCase, EncIntegerAvgFinalFuncUseCase, EncIntegerCountFinalFuncUseCase,
+    EncIntegerMaxFinalFuncUseCase, EncIntegerMinFinalFuncUseCase, EncIntegerSumFinalFuncUseCase,
+    EncRealAvgFinalFuncUseCase, EncRealSumFinalFuncUseCase,
+};
+pub use enc_aggregate_state_func_use_case::{
+    EncAggregateStateFuncUseCase, EncBigIntMaxStateFuncUseCase, EncBigIntMinStateFuncUseCase,
+    EncBigIntSumStateFuncUseCase, EncIntegerAvgStateFuncUseCase, EncIntegerCountStateFuncUseCase,
+    EncIntegerMaxStateFuncUseCase, EncIntegerMinStateFuncUseCase, EncIntegerSumStateFuncUseCase,
+    EncRealAvgStateFuncUseCase, EncRealSumStateFuncUseCase,
+};
+pub use enc_cmp_use_case::{
+    EncBigIntCmpUseCase, EncCmpUseCase, EncIntegerCmpUseCase, EncRealCmpUseCase,
+};
+pub use enc_eq_use_case::{
+    EncBigIntEqUseCase, EncEqUseCase, EncIntegerEqUseCase, EncRealEqUseCase, EncTextEqUseCase,
+};
+pub use enc_from_use_case::{
+    EncBigIntFromUseCase, EncFromUseCase, EncRealFromUseCase, EncTextFromUseCase,
+};
+pub use enc_integer_from_client_use_case::EncIntegerFromClientUseCase;
 pub use enc_integer_from_use_case::EncIntegerFromUseCase;
+pub use enc_integer_migrate_use_case::EncIntegerMigrateUseCase;
+pub use enc_integer_to_client_use_case::EncIntegerToClientUseCase;
+pub use enc_tag_use_case::{
+    EncBigIntTagUseCase, EncIntegerTagUseCase, EncRealTagUseCase, EncTagUseCase, EncTextTagUseCase,
+};
+pub use get_client_key_use_case::GetClientKeyUseCase;
+pub use load_master_key_use_case::LoadMasterKeyUseCase;
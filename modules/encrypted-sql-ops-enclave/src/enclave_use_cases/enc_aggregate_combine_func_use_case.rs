@@ -0,0 +1,76 @@
+use crate::aggregate_calc::AggregateCalc;
+use crate::enclave_context::EncryptedSqlOpsEnclaveContext;
+use crate::plain_types::{
+    Aggregate, Avg, BigIntMax, BigIntMin, BigIntSum, Count, Max, Min, PlainAggregateState, RealAvg,
+    RealSum, Sum,
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::enc_aggregate_state::EncAggregateState,
+    enclave_types::{EnclaveEncAggregateState, EnclaveEncPair},
+};
+use std::marker::PhantomData;
+
+/// Combine function of SUM(ENCINTEGER)
+pub type EncIntegerSumCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, Sum>;
+/// Combine function of COUNT(ENCINTEGER)
+pub type EncIntegerCountCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, Count>;
+/// Combine function of MIN(ENCINTEGER)
+pub type EncIntegerMinCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, Min>;
+/// Combine function of MAX(ENCINTEGER)
+pub type EncIntegerMaxCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, Max>;
+/// Combine function of AVG(ENCINTEGER)
+pub type EncIntegerAvgCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, Avg>;
+/// Combine function of SUM(ENCBIGINT)
+pub type EncBigIntSumCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, BigIntSum>;
+/// Combine function of MIN(ENCBIGINT)
+pub type EncBigIntMinCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, BigIntMin>;
+/// Combine function of MAX(ENCBIGINT)
+pub type EncBigIntMaxCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, BigIntMax>;
+/// Combine function of SUM(ENCREAL)
+pub type EncRealSumCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, RealSum>;
+/// Combine function of AVG(ENCREAL)
+pub type EncRealAvgCombineFuncUseCase<'c> = EncAggregateCombineFuncUseCase<'c, RealAvg>;
+
+/// Combine function of an [Aggregate](Aggregate) running inside enclave,
+/// which merges the states of parallel workers into one.
+///
+/// Both states must be of the aggregate and in the same context.
+#[derive(Clone, Debug)]
+pub struct EncAggregateCombineFuncUseCase<'c, A> {
+    enclave_input: EnclaveEncPair<EncAggregateState>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    aggregate: PhantomData<A>,
+}
+
+impl<'c, A: Aggregate> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext>
+    for EncAggregateCombineFuncUseCase<'c, A>
+{
+    type EI = EnclaveEncPair<EncAggregateState>;
+    type EO = EnclaveEncAggregateState;
+    const ENCLAVE_USE_CASE_ID: u32 = A::COMBINE_FUNC_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+            aggregate: PhantomData,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (enc_state, enc_other) = self.enclave_input.into_inner();
+
+        let cipher = self.enclave_context.type_cipher()?;
+        let mut plain_state = PlainAggregateState::<A>::from_encrypted(enc_state, &cipher)?;
+        let plain_other = PlainAggregateState::<A>::from_encrypted(enc_other, &cipher)?;
+
+        plain_state.combine(plain_other)?;
+
+        let enc_combined_state = plain_state.into_encrypted(&cipher)?;
+        Ok(EnclaveEncAggregateState::from(enc_combined_state))
+    }
+}
@@ -0,0 +1,95 @@
+use crate::aggregate_calc::AggregateCalc;
+use crate::enclave_context::EncryptedSqlOpsEnclaveContext;
+use crate::plain_types::{
+    Aggregate, Avg, BigIntMax, BigIntMin, BigIntSum, Count, Max, Min, PlainAggregateState,
+    PlainResult, RealAvg, RealSum, ResultPolicy, Sum,
+};
+use crate::type_crypt::AeadEncrypt;
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveAggregateResult, EnclaveEncAggregateState,
+};
+use std::marker::PhantomData;
+
+/// Finalize function of SUM(ENCINTEGER)
+pub type EncIntegerSumFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, Sum>;
+/// Finalize function of COUNT(ENCINTEGER)
+pub type EncIntegerCountFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, Count>;
+/// Finalize function of MIN(ENCINTEGER)
+pub type EncIntegerMinFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, Min>;
+/// Finalize function of MAX(ENCINTEGER)
+pub type EncIntegerMaxFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, Max>;
+/// Finalize function of AVG(ENCINTEGER)
+pub type EncIntegerAvgFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, Avg>;
+/// Finalize function of SUM(ENCBIGINT)
+pub type EncBigIntSumFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, BigIntSum>;
+/// Finalize function of MIN(ENCBIGINT)
+pub type EncBigIntMinFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, BigIntMin>;
+/// Finalize function of MAX(ENCBIGINT)
+pub type EncBigIntMaxFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, BigIntMax>;
+/// Finalize function of SUM(ENCREAL)
+pub type EncRealSumFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, RealSum>;
+/// Finalize function of AVG(ENCREAL)
+pub type EncRealAvgFinalFuncUseCase<'c> = EncAggregateFinalFuncUseCase<'c, RealAvg>;
+
+/// Finalize function of an [Aggregate](Aggregate) running inside enclave.
+///
+/// The result leaves enclave as declared by [RESULT_POLICY](Aggregate::RESULT_POLICY),
+/// encrypted in the context derived by [NAME](Aggregate::NAME) if so.
+#[derive(Clone, Debug)]
+pub struct EncAggregateFinalFuncUseCase<'c, A> {
+    enclave_input: EnclaveEncAggregateState,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    aggregate: PhantomData<A>,
+}
+
+impl<'c, A: Aggregate> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext>
+    for EncAggregateFinalFuncUseCase<'c, A>
+{
+    type EI = EnclaveEncAggregateState;
+    type EO = EnclaveAggregateResult;
+    const ENCLAVE_USE_CASE_ID: u32 = A::FINAL_FUNC_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+            aggregate: PhantomData,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let enc_current_state = self.enclave_input.into_enc_aggregate_state();
+        let cipher = self.enclave_context.type_cipher()?;
+        let plain_current_state =
+            PlainAggregateState::<A>::from_encrypted(enc_current_state, &cipher)?;
+        let context = plain_current_state.context.clone().unwrap_or_default();
+        let result_context = || context.derive(A::NAME);
+
+        let result = match (plain_current_state.finalize()?, A::RESULT_POLICY) {
+            (None, _) => EnclaveAggregateResult::Null,
+            (Some(PlainResult::Integer(i)), ResultPolicy::Plain) => {
+                EnclaveAggregateResult::PlainBigInt(i64::from(i.to_i32()))
+            }
+            (Some(PlainResult::BigInt(i)), ResultPolicy::Plain) => {
+                EnclaveAggregateResult::PlainBigInt(i.to_i64())
+            }
+            (Some(PlainResult::Real(f)), ResultPolicy::Plain) => {
+                EnclaveAggregateResult::PlainReal(f.to_f32())
+            }
+            (Some(PlainResult::Integer(i)), ResultPolicy::Encrypted) => {
+                EnclaveAggregateResult::EncInteger(i.encrypt(&cipher, &result_context()?)?)
+            }
+            (Some(PlainResult::BigInt(i)), ResultPolicy::Encrypted) => {
+                EnclaveAggregateResult::EncBigInt(i.encrypt(&cipher, &result_context()?)?)
+            }
+            (Some(PlainResult::Real(f)), ResultPolicy::Encrypted) => {
+                EnclaveAggregateResult::EncReal(f.encrypt(&cipher, &result_context()?)?)
+            }
+        };
+        Ok(result)
+    }
+}
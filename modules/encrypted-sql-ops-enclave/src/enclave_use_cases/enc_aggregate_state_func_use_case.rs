@@ -0,0 +1,90 @@
+use crate::aggregate_calc::AggregateCalc;
+use crate::enclave_context::EncryptedSqlOpsEnclaveContext;
+use crate::error::EnclaveError;
+use crate::plain_types::{
+    Aggregate, Avg, BigIntMax, BigIntMin, BigIntSum, Count, Max, Min, PlainAggregateState, RealAvg,
+    RealSum, Sum,
+};
+use crate::type_crypt::AeadDecrypt;
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveEncAggregateState, EnclaveEncAggregateStateWithBatch, MAX_AGGREGATE_BATCH_SIZE,
+};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// State function of SUM(ENCINTEGER)
+pub type EncIntegerSumStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, Sum>;
+/// State function of COUNT(ENCINTEGER)
+pub type EncIntegerCountStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, Count>;
+/// State function of MIN(ENCINTEGER)
+pub type EncIntegerMinStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, Min>;
+/// State function of MAX(ENCINTEGER)
+pub type EncIntegerMaxStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, Max>;
+/// State function of AVG(ENCINTEGER)
+pub type EncIntegerAvgStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, Avg>;
+/// State function of SUM(ENCBIGINT)
+pub type EncBigIntSumStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, BigIntSum>;
+/// State function of MIN(ENCBIGINT)
+pub type EncBigIntMinStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, BigIntMin>;
+/// State function of MAX(ENCBIGINT)
+pub type EncBigIntMaxStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, BigIntMax>;
+/// State function of SUM(ENCREAL)
+pub type EncRealSumStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, RealSum>;
+/// State function of AVG(ENCREAL)
+pub type EncRealAvgStateFuncUseCase<'c> = EncAggregateStateFuncUseCase<'c, RealAvg>;
+
+/// State function of an [Aggregate](Aggregate) running inside enclave,
+/// which accumulates a batch of values so that the state is decrypted and encrypted once per ecall.
+#[derive(Clone, Debug)]
+pub struct EncAggregateStateFuncUseCase<'c, A: Aggregate> {
+    enclave_input: EnclaveEncAggregateStateWithBatch<A::Enc>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    aggregate: PhantomData<A>,
+}
+
+impl<'c, A> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext>
+    for EncAggregateStateFuncUseCase<'c, A>
+where
+    A: Aggregate,
+    A::Enc: DeserializeOwned,
+{
+    type EI = EnclaveEncAggregateStateWithBatch<A::Enc>;
+    type EO = EnclaveEncAggregateState;
+    const ENCLAVE_USE_CASE_ID: u32 = A::STATE_FUNC_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+            aggregate: PhantomData,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (enc_current_state, enc_batch) = self.enclave_input.into_inner();
+        if enc_batch.len() > MAX_AGGREGATE_BATCH_SIZE {
+            return Err(EnclaveError::AggregateBatchTooLargeError {
+                size: enc_batch.len(),
+                max: MAX_AGGREGATE_BATCH_SIZE,
+            }
+            .into());
+        }
+
+        let cipher = self.enclave_context.type_cipher()?;
+        let mut plain_current_state =
+            PlainAggregateState::<A>::from_encrypted(enc_current_state, &cipher)?;
+        for enc_next in enc_batch {
+            let (plain_next, next_context) = enc_next.decrypt(&cipher)?;
+            plain_current_state.bind_context(next_context)?;
+
+            plain_current_state.accumulate(plain_next)?;
+        }
+
+        let enc_next_state = plain_current_state.into_encrypted(&cipher)?;
+        Ok(EnclaveEncAggregateState::from(enc_next_state))
+    }
+}
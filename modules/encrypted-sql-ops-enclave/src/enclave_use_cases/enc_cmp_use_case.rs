@@ -0,0 +1,58 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext, plain_types::PlainOrd, type_crypt::AeadDecrypt,
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::{EncBigInt, EncInteger, EncReal},
+    enclave_types::{EnclaveEncPair, EnclaveOrdering},
+};
+use serde::de::DeserializeOwned;
+
+/// EncIntegerCmp command running inside enclave.
+pub type EncIntegerCmpUseCase<'c> = EncCmpUseCase<'c, EncInteger>;
+/// EncBigIntCmp command running inside enclave.
+pub type EncBigIntCmpUseCase<'c> = EncCmpUseCase<'c, EncBigInt>;
+/// EncRealCmp command running inside enclave.
+pub type EncRealCmpUseCase<'c> = EncCmpUseCase<'c, EncReal>;
+
+/// Comparison of an encrypted type running inside enclave.
+///
+/// Only the values in the same context, whose ordering is not disabled, are compared.
+#[derive(Clone, Debug)]
+pub struct EncCmpUseCase<'c, E> {
+    enclave_input: EnclaveEncPair<E>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c, E> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncCmpUseCase<'c, E>
+where
+    E: AeadDecrypt + DeserializeOwned,
+    E::Decrypted: PlainOrd,
+{
+    type EI = EnclaveEncPair<E>;
+    type EO = EnclaveOrdering;
+    const ENCLAVE_USE_CASE_ID: u32 = <E::Decrypted as PlainOrd>::CMP_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (enc_lhs, enc_rhs) = self.enclave_input.into_inner();
+        let cipher = self.enclave_context.type_cipher()?;
+
+        let (plain_lhs, lhs_context) = enc_lhs.decrypt(&cipher)?;
+        let (plain_rhs, rhs_context) = enc_rhs.decrypt(&cipher)?;
+        lhs_context.ensure_eq(&rhs_context)?;
+        self.enclave_context.ensure_ordered(&lhs_context)?;
+
+        let ordering = plain_lhs.plain_cmp(&plain_rhs);
+        Ok(EnclaveOrdering::from(ordering))
+    }
+}
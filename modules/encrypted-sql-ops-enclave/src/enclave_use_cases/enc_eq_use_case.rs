@@ -0,0 +1,58 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext, plain_types::PlainEq, type_crypt::AeadDecrypt,
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::{EncBigInt, EncInteger, EncReal, EncText},
+    enclave_types::{EnclaveEncPair, EnclavePlainBool},
+};
+use serde::de::DeserializeOwned;
+
+/// EncIntegerEq command running inside enclave.
+pub type EncIntegerEqUseCase<'c> = EncEqUseCase<'c, EncInteger>;
+/// EncBigIntEq command running inside enclave.
+pub type EncBigIntEqUseCase<'c> = EncEqUseCase<'c, EncBigInt>;
+/// EncRealEq command running inside enclave.
+pub type EncRealEqUseCase<'c> = EncEqUseCase<'c, EncReal>;
+/// EncTextEq command running inside enclave.
+pub type EncTextEqUseCase<'c> = EncEqUseCase<'c, EncText>;
+
+/// Equality of an encrypted type running inside enclave.
+///
+/// Only the values in the same context are compared.
+#[derive(Clone, Debug)]
+pub struct EncEqUseCase<'c, E> {
+    enclave_input: EnclaveEncPair<E>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c, E> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncEqUseCase<'c, E>
+where
+    E: AeadDecrypt + DeserializeOwned,
+    E::Decrypted: PlainEq,
+{
+    type EI = EnclaveEncPair<E>;
+    type EO = EnclavePlainBool;
+    const ENCLAVE_USE_CASE_ID: u32 = <E::Decrypted as PlainEq>::EQ_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (enc_lhs, enc_rhs) = self.enclave_input.into_inner();
+        let cipher = self.enclave_context.type_cipher()?;
+
+        let (plain_lhs, lhs_context) = enc_lhs.decrypt(&cipher)?;
+        let (plain_rhs, rhs_context) = enc_rhs.decrypt(&cipher)?;
+        lhs_context.ensure_eq(&rhs_context)?;
+
+        Ok(EnclavePlainBool::from(plain_lhs.plain_eq(&plain_rhs)))
+    }
+}
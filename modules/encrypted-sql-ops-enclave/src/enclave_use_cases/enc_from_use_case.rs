@@ -0,0 +1,55 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext,
+    plain_types::{PlainBigInt, PlainFrom, PlainReal, PlainText},
+    type_crypt::CryptContext,
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveEncValue, EnclavePlainWithContext,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// EncBigIntFrom command running inside enclave.
+pub type EncBigIntFromUseCase<'c> = EncFromUseCase<'c, PlainBigInt>;
+/// EncRealFrom command running inside enclave.
+pub type EncRealFromUseCase<'c> = EncFromUseCase<'c, PlainReal>;
+/// EncTextFrom command running inside enclave.
+pub type EncTextFromUseCase<'c> = EncFromUseCase<'c, PlainText>;
+
+/// Constructor of an encrypted type from the SQL value, running inside enclave.
+#[derive(Clone, Debug)]
+pub struct EncFromUseCase<'c, P: PlainFrom> {
+    enclave_input: EnclavePlainWithContext<P::Raw>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c, P> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncFromUseCase<'c, P>
+where
+    P: PlainFrom,
+    P::Raw: DeserializeOwned,
+    P::Encrypted: Serialize,
+{
+    type EI = EnclavePlainWithContext<P::Raw>;
+    type EO = EnclaveEncValue<P::Encrypted>;
+    const ENCLAVE_USE_CASE_ID: u32 = P::FROM_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (raw, context) = self.enclave_input.into_inner();
+        let context = CryptContext::new(context)?;
+        self.enclave_context.ensure_not_client_only(&context)?;
+        let cipher = self.enclave_context.type_cipher()?;
+        let plain = P::from_raw(raw)?;
+        let encrypted = plain.encrypt(&cipher, &context)?;
+        Ok(EnclaveEncValue::from(encrypted))
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext,
+    error::EnclaveError,
+    plain_types::PlainInteger,
+    type_crypt::{AeadEncrypt, CryptContext},
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::ENCINTEGER_FROM_CLIENT,
+    enclave_types::{EnclaveClientCiphertext, EnclaveEncInteger},
+};
+use std::string::ToString;
+
+/// EncIntegerFromClient command running inside enclave.
+#[derive(Clone, Debug)]
+pub struct EncIntegerFromClientUseCase<'c> {
+    enclave_input: EnclaveClientCiphertext,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext>
+    for EncIntegerFromClientUseCase<'c>
+{
+    type EI = EnclaveClientCiphertext;
+    type EO = EnclaveEncInteger;
+    const ENCLAVE_USE_CASE_ID: u32 = ENCINTEGER_FROM_CLIENT;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (payload, sender) = self
+            .enclave_context
+            .client_cipher()?
+            .decrypt(&self.enclave_input.into_inner())?;
+        let plain_i32 = payload
+            .to_i32()
+            .map(PlainInteger::new)
+            .ok_or(EnclaveError::ClientCiphertextError)?;
+        // The context is chosen by the client, not by the SQL, so that the value can't be bound to another column.
+        let context = CryptContext::new(payload.context().to_string())?;
+        self.enclave_context
+            .ensure_client_allowed(&context, &sender)?;
+
+        let cipher = self.enclave_context.type_cipher()?;
+        let encinteger = plain_i32.encrypt(&cipher, &context)?;
+        Ok(EnclaveEncInteger::from(encinteger))
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{owner_config::OwnerConfig, type_crypt::MasterKey};
+    use frame_sodium::{rng::SgxRng, SodiumCiphertext, SodiumPrivateKey};
+    use module_encrypted_sql_ops_ecall_types::client_types::ClientPayload;
+    use std::format;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_client_only_context,)
+    }
+
+    fn test_client_only_context() {
+        let mut rng = SgxRng::new().unwrap();
+        let client_key = SodiumPrivateKey::from_random(&mut rng).unwrap();
+        let unlisted_key = SodiumPrivateKey::from_random(&mut rng).unwrap();
+        let owner_config: OwnerConfig = serde_json::from_str(&format!(
+            r#"{{"client_public_keys":["{}"],"client_only_contexts":["t.c_salary"]}}"#,
+            hex::encode(client_key.public_key().to_bytes())
+        ))
+        .unwrap();
+        let enclave_context = EncryptedSqlOpsEnclaveContext::new_for_test(
+            &owner_config,
+            MasterKey::new_random().unwrap(),
+        );
+        let enclave_key = enclave_context.client_cipher().unwrap().public_key();
+        let encrypt = |sender: Option<&SodiumPrivateKey>, context: &str| {
+            let payload = ClientPayload::from_i32(42, context.to_string()).to_bytes();
+            let mut rng = SgxRng::new().unwrap();
+            let ciphertext = match sender {
+                Some(sender) => {
+                    SodiumCiphertext::encrypt_from(&mut rng, sender, &enclave_key, &payload)
+                }
+                None => SodiumCiphertext::encrypt(&mut rng, &enclave_key, &payload),
+            }
+            .unwrap()
+            .encode();
+            EncIntegerFromClientUseCase::new(
+                EnclaveClientCiphertext::from(ciphertext),
+                &enclave_context,
+            )
+            .and_then(|use_case| use_case.run())
+        };
+
+        assert!(encrypt(None, "t.c_enc").is_ok());
+        assert!(encrypt(Some(&client_key), "t.c_salary").is_ok());
+        assert!(encrypt(Some(&client_key), "t.c_salary#MAX").is_ok());
+
+        // Anyone can encrypt values to the enclave's client key, including the database,
+        // so only the listed clients encrypt values in the client-only context.
+        assert!(encrypt(None, "t.c_salary").is_err());
+        assert!(encrypt(Some(&unlisted_key), "t.c_salary").is_err());
+    }
+}
@@ -1,22 +1,23 @@
 use crate::{
-    enclave_context::EncryptedSqlOpsEnclaveContext, plain_types::PlainInteger,
-    type_crypt::Pad16BytesEncrypt,
+    enclave_context::EncryptedSqlOpsEnclaveContext,
+    plain_types::PlainInteger,
+    type_crypt::{AeadEncrypt, CryptContext},
 };
 use frame_enclave::BasicEnclaveUseCase;
 use module_encrypted_sql_ops_ecall_types::{
     ecall_cmd::ENCINTEGER_FROM,
-    enclave_types::{EnclaveEncInteger, EnclavePlainInteger},
+    enclave_types::{EnclaveEncInteger, EnclavePlainIntegerWithContext},
 };
 
 /// EncIntegerFrom command running inside enclave.
 #[derive(Clone, Debug)]
 pub struct EncIntegerFromUseCase<'c> {
-    enclave_input: EnclavePlainInteger,
+    enclave_input: EnclavePlainIntegerWithContext,
     enclave_context: &'c EncryptedSqlOpsEnclaveContext,
 }
 
 impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncIntegerFromUseCase<'c> {
-    type EI = EnclavePlainInteger;
+    type EI = EnclavePlainIntegerWithContext;
     type EO = EnclaveEncInteger;
     const ENCLAVE_USE_CASE_ID: u32 = ENCINTEGER_FROM;
 
@@ -31,8 +32,58 @@ impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncIntegerFr
     }
 
     fn run(self) -> anyhow::Result<Self::EO> {
-        let plain_i32 = PlainInteger::from(self.enclave_input);
-        let encinteger = plain_i32.encrypt();
+        let (plain_integer, context) = self.enclave_input.into_inner();
+        let context = CryptContext::new(context)?;
+        self.enclave_context.ensure_not_client_only(&context)?;
+        let cipher = self.enclave_context.type_cipher()?;
+        let plain_i32 = PlainInteger::from(plain_integer);
+        let encinteger = plain_i32.encrypt(&cipher, &context)?;
         Ok(EnclaveEncInteger::from(encinteger))
     }
 }
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        owner_config::OwnerConfig,
+        type_crypt::{AeadDecrypt, MasterKey},
+    };
+    use module_encrypted_sql_ops_ecall_types::enclave_types::EnclavePlainInteger;
+    use std::string::ToString;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_client_only_context,)
+    }
+
+    fn test_client_only_context() {
+        let owner_config: OwnerConfig =
+            serde_json::from_str(r#"{"client_only_contexts":["t.c_salary"]}"#).unwrap();
+        let enclave_context = EncryptedSqlOpsEnclaveContext::new_for_test(
+            &owner_config,
+            MasterKey::new_random().unwrap(),
+        );
+        let encrypt = |context: &str| {
+            let enclave_input = EnclavePlainIntegerWithContext::new(
+                EnclavePlainInteger::from(42),
+                context.to_string(),
+            );
+            EncIntegerFromUseCase::new(enclave_input, &enclave_context)
+                .and_then(|use_case| use_case.run())
+        };
+
+        let (plain, context) = encrypt("t.c_enc")
+            .unwrap()
+            .into_encinteger()
+            .decrypt(&enclave_context.type_cipher().unwrap())
+            .unwrap();
+        assert_eq!((plain.to_i32(), context.as_str()), (42, "t.c_enc"));
+        assert!(encrypt("t.c_salary_2").is_ok());
+
+        // The SQL can't encrypt chosen values to compare them with the ones in the client-only context,
+        // nor with their aggregates.
+        assert!(encrypt("t.c_salary").is_err());
+        assert!(encrypt("t.c_salary#MAX").is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext,
+    type_crypt::{AeadDecrypt, AeadEncrypt, CryptContext},
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::ENCINTEGER_MIGRATE,
+    enclave_types::{EnclaveEncInteger, EnclaveEncIntegerWithContext},
+};
+
+/// EncIntegerMigrate command running inside enclave.
+#[derive(Clone, Debug)]
+pub struct EncIntegerMigrateUseCase<'c> {
+    enclave_input: EnclaveEncIntegerWithContext,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncIntegerMigrateUseCase<'c> {
+    type EI = EnclaveEncIntegerWithContext;
+    type EO = EnclaveEncInteger;
+    const ENCLAVE_USE_CASE_ID: u32 = ENCINTEGER_MIGRATE;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (encinteger, context) = self.enclave_input.into_inner();
+        let context = CryptContext::new(context)?;
+        let cipher = self.enclave_context.type_cipher()?.for_migration();
+
+        let (plain_i32, current_context) = encinteger.decrypt(&cipher)?;
+        // The values without context are bound to the given one, but the others can't be moved.
+        if !current_context.is_empty() {
+            current_context.ensure_eq(&context)?;
+        }
+
+        let encinteger = plain_i32.encrypt(&cipher, &context)?;
+        Ok(EnclaveEncInteger::from(encinteger))
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{enclave_context::EncryptedSqlOpsEnclaveContext, type_crypt::AeadDecrypt};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    client_types::ClientPayload,
+    ecall_cmd::ENCINTEGER_TO_CLIENT,
+    enclave_types::{EnclaveClientCiphertext, EnclaveEncIntegerForClient},
+};
+use std::string::ToString;
+
+/// EncIntegerToClient command running inside enclave.
+#[derive(Clone, Debug)]
+pub struct EncIntegerToClientUseCase<'c> {
+    enclave_input: EnclaveEncIntegerForClient,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncIntegerToClientUseCase<'c> {
+    type EI = EnclaveEncIntegerForClient;
+    type EO = EnclaveClientCiphertext;
+    const ENCLAVE_USE_CASE_ID: u32 = ENCINTEGER_TO_CLIENT;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let (encinteger, client_key) = self.enclave_input.into_inner();
+        let (plain_i32, context) = encinteger.decrypt(&self.enclave_context.type_cipher()?)?;
+
+        // The client checks the context to tell which column the value came from.
+        let payload = ClientPayload::from_i32(plain_i32.to_i32(), context.as_str().to_string());
+        let ciphertext = self
+            .enclave_context
+            .client_cipher()?
+            .encrypt(&payload, &client_key)?;
+        Ok(EnclaveClientCiphertext::from(ciphertext))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    enclave_context::EncryptedSqlOpsEnclaveContext, plain_types::PlainTag, type_crypt::AeadDecrypt,
+};
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::{EncBigInt, EncInteger, EncReal, EncTag, EncText},
+    enclave_types::{EnclaveEncTag, EnclaveEncValue},
+};
+use serde::de::DeserializeOwned;
+
+/// EncIntegerTag command running inside enclave.
+pub type EncIntegerTagUseCase<'c> = EncTagUseCase<'c, EncInteger>;
+/// EncBigIntTag command running inside enclave.
+pub type EncBigIntTagUseCase<'c> = EncTagUseCase<'c, EncBigInt>;
+/// EncRealTag command running inside enclave.
+pub type EncRealTagUseCase<'c> = EncTagUseCase<'c, EncReal>;
+/// EncTextTag command running inside enclave.
+pub type EncTextTagUseCase<'c> = EncTagUseCase<'c, EncText>;
+
+/// Deterministic tag of an encrypted value running inside enclave.
+///
+/// Only the values in the contexts listed in `tagged_contexts` of the owner config are tagged,
+/// since the tags reveal which values are equal.
+#[derive(Clone, Debug)]
+pub struct EncTagUseCase<'c, E> {
+    enclave_input: EnclaveEncValue<E>,
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c, E> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for EncTagUseCase<'c, E>
+where
+    E: AeadDecrypt + DeserializeOwned,
+    E::Decrypted: PlainTag,
+{
+    type EI = EnclaveEncValue<E>;
+    type EO = EnclaveEncTag;
+    const ENCLAVE_USE_CASE_ID: u32 = <E::Decrypted as PlainTag>::TAG_CMD;
+
+    fn new(
+        enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            enclave_input,
+            enclave_context,
+        })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let cipher = self.enclave_context.type_cipher()?;
+        let (plain, context) = self.enclave_input.into_inner().decrypt(&cipher)?;
+        let domain = self.enclave_context.tag_domain(&context)?;
+
+        let tag = self
+            .enclave_context
+            .tag_key()?
+            .tag(E::TYPE_LABEL, domain, &plain.tag_bytes());
+        Ok(EnclaveEncTag::from(EncTag::from(tag)))
+    }
+}
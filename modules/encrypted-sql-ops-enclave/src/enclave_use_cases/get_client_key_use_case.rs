@@ -0,0 +1,34 @@
+use crate::enclave_context::EncryptedSqlOpsEnclaveContext;
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::GET_CLIENT_KEY,
+    enclave_types::{EnclaveClientKey, EnclaveGetClientKey},
+};
+
+/// GetClientKey command running inside enclave.
+#[derive(Clone, Debug)]
+pub struct GetClientKeyUseCase<'c> {
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for GetClientKeyUseCase<'c> {
+    type EI = EnclaveGetClientKey;
+    type EO = EnclaveClientKey;
+    const ENCLAVE_USE_CASE_ID: u32 = GET_CLIENT_KEY;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        let cipher = self.enclave_context.client_cipher()?;
+        let evidence = self.enclave_context.attest_client_key(&cipher)?;
+        Ok(EnclaveClientKey::new(
+            cipher.public_key().to_bytes().to_vec(),
+            evidence,
+        ))
+    }
+}
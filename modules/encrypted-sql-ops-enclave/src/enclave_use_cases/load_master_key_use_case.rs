@@ -0,0 +1,29 @@
+use crate::enclave_context::EncryptedSqlOpsEnclaveContext;
+use frame_enclave::BasicEnclaveUseCase;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::LOAD_MASTER_KEY,
+    enclave_types::{EnclaveLoadMasterKey, EnclaveMasterKeySource},
+};
+
+/// LoadMasterKey command running inside enclave.
+#[derive(Clone, Debug)]
+pub struct LoadMasterKeyUseCase<'c> {
+    enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, EncryptedSqlOpsEnclaveContext> for LoadMasterKeyUseCase<'c> {
+    type EI = EnclaveLoadMasterKey;
+    type EO = EnclaveMasterKeySource;
+    const ENCLAVE_USE_CASE_ID: u32 = LOAD_MASTER_KEY;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c EncryptedSqlOpsEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        self.enclave_context.load_master_key()
+    }
+}
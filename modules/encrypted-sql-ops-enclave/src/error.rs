@@ -1,5 +1,6 @@
 //! Error and Result types.
 
+use std::string::String;
 use thiserror::Error;
 
 /// The result type in this crate.
@@ -8,7 +9,7 @@ pub type Result<T> = std::result::Result<T, EnclaveError>;
 /// The error type in this crate.
 #[derive(Error, Debug)]
 pub enum EnclaveError {
-    /// Error while decrypting data into plain text.
+    /// Error while decrypting legacy data into plain text.
     #[error("decrypted block is {decrypted_size} bytes, while expected to be 16 bytes ({plain_size}-byte sized type with 16-byte padding))")]
     DecryptError {
         /// data length after decryption
@@ -16,4 +17,151 @@ pub enum EnclaveError {
         /// expected length of plain text
         plain_size: usize,
     },
+
+    /// The master key is used before loaded.
+    #[error("master key is not loaded yet")]
+    MasterKeyNotLoadedError,
+
+    /// Error while generating a master key.
+    #[error("failed to generate a master key: {0}")]
+    MasterKeyGenerationError(String),
+
+    /// Unsealed or provisioned master key has a wrong length.
+    #[error("master key is {size} bytes, while expected to be 16 bytes")]
+    InvalidMasterKeyError {
+        /// actual length of the key
+        size: usize,
+    },
+
+    /// Error while generating a nonce.
+    #[error("failed to generate a nonce: {0}")]
+    NonceGenerationError(String),
+
+    /// Error while encrypting plain text.
+    #[error("failed to encrypt")]
+    EncryptError,
+
+    /// Ciphertext is not in the format of its type.
+    #[error("ciphertext of {size} bytes is malformed")]
+    MalformedCiphertextError {
+        /// length of the ciphertext
+        size: usize,
+    },
+
+    /// Ciphertext is in an unknown format version.
+    #[error("ciphertext version {version} is not supported")]
+    UnsupportedCiphertextVersionError {
+        /// version of the ciphertext
+        version: u8,
+    },
+
+    /// Ciphertext or its context has been tampered with, or was encrypted by another key.
+    #[error("ciphertext is not authentic")]
+    AuthenticationError,
+
+    /// Legacy ciphertext is decrypted except to migrate it.
+    #[error("legacy ciphertext is not accepted, migrate it by ENCINTEGER_MIGRATE()")]
+    LegacyCiphertextError,
+
+    /// Value is encrypted in the empty context, or decrypted from it except to migrate it.
+    #[error(
+        "context must not be empty, migrate the values without context by ENCINTEGER_MIGRATE()"
+    )]
+    EmptyContextError,
+
+    /// Context is longer than the limit.
+    #[error("context is {size} bytes, while expected to be at most 128 bytes")]
+    ContextTooLongError {
+        /// length of the context
+        size: usize,
+    },
+
+    /// Values in different contexts are operated together.
+    #[error("value in context {actual:?} is operated with values in context {expected:?}")]
+    ContextMismatchError {
+        /// context of the preceding values
+        expected: String,
+        /// context of the value
+        actual: String,
+    },
+
+    /// Aggregated value overflows.
+    #[error("aggregated value overflows")]
+    OverflowError,
+
+    /// State of an aggregate is operated by another aggregate.
+    #[error("state of aggregate {actual} is operated by aggregate {expected}")]
+    AggregateMismatchError {
+        /// tag of the operating aggregate
+        expected: u8,
+        /// tag of the state
+        actual: u8,
+    },
+
+    /// Values are ordered while ordering is disabled for their context.
+    #[error("ordering values in context {context:?} is disabled")]
+    OrderingDisabledError {
+        /// context of the values
+        context: String,
+    },
+
+    /// Values are tagged while tagging is not enabled for their context.
+    #[error("tagging values in context {context:?} is not enabled in the owner config")]
+    TaggingDisabledError {
+        /// context of the values
+        context: String,
+    },
+
+    /// Value is encrypted by the SQL, or by a client not listed in the owner config, in a client-only context.
+    #[error("values in context {context:?} are only encrypted by the clients listed in the owner config")]
+    ClientOnlyContextError {
+        /// context of the value
+        context: String,
+    },
+
+    /// Result doesn't fit in its encrypted type.
+    #[error("result {value} is out of the range of INTEGER")]
+    ResultOutOfRangeError {
+        /// the result
+        value: i64,
+    },
+
+    /// Variable-length values are padded to buckets smaller than the limit.
+    #[error("bucket size is {size} bytes, while expected to be at least 16 bytes")]
+    InvalidBucketSizeError {
+        /// the bucket size
+        size: usize,
+    },
+
+    /// TEXT is longer than the limit.
+    #[error("text is {size} bytes, while expected to be at most 4096 bytes")]
+    TextTooLongError {
+        /// length of the text
+        size: usize,
+    },
+
+    /// Error while deriving the client key.
+    #[error("failed to derive the client key: {0}")]
+    ClientKeyError(String),
+
+    /// Client ciphertext is malformed or not encrypted to the client key.
+    #[error("client ciphertext is malformed or not encrypted to the client key")]
+    ClientCiphertextError,
+
+    /// Values are encrypted to a client key which isn't allowed to receive them.
+    #[error("client key is not listed in the owner config")]
+    ClientKeyNotAllowedError,
+
+    /// Owner config is malformed or not signed by the owner.
+    #[error("owner config is not valid: {0}")]
+    OwnerConfigError(String),
+
+    /// Batch of the values accumulated by an ecall is larger than the limit.
+    #[error("batch of {size} values is larger than {max} values")]
+    AggregateBatchTooLargeError {
+        /// number of the values in the batch
+        size: usize,
+        /// the limit
+        max: usize,
+    },
 }
@@ -14,6 +14,7 @@ pub mod aggregate_calc;
 pub mod enclave_context;
 pub mod enclave_use_cases;
 pub mod error;
+pub mod owner_config;
 pub mod plain_types;
 pub mod type_crypt;
 
@@ -25,6 +26,17 @@ pub mod tests {
 
     /// called from test-utils crate
     pub fn run_tests() -> bool {
-        check_all_passed!(crate::plain_types::plain_avg_state::tests::run_tests(),)
+        check_all_passed!(
+            crate::plain_types::plain_aggregate_state::tests::run_tests(),
+            crate::plain_types::plain_real::tests::run_tests(),
+            crate::plain_types::plain_text::tests::run_tests(),
+            crate::owner_config::tests::run_tests(),
+            crate::enclave_use_cases::enc_integer_from_use_case::tests::run_tests(),
+            crate::enclave_use_cases::enc_integer_from_client_use_case::tests::run_tests(),
+            crate::type_crypt::master_key::tests::run_tests(),
+            crate::type_crypt::aead_crypt::tests::run_tests(),
+            crate::type_crypt::client_crypt::tests::run_tests(),
+            crate::type_crypt::tag_crypt::tests::run_tests(),
+        )
     }
 }
@@ -0,0 +1,357 @@
+//! The configuration of the enclave signed by the owner of the data.
+//!
+//! It decides who can read the values, so it's not taken from the host's environment, which the database's operator controls.
+//! It's signed by the owner's ECDSA P-256 key instead, whose public key is compiled into the enclave and so measured in MRENCLAVE.
+
+use crate::error::{EnclaveError, Result};
+use crate::type_crypt::{DEFAULT_BUCKET_SIZE, MIN_BUCKET_SIZE};
+use frame_sodium::SodiumPubKey;
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+use serde::{Deserialize, Serialize};
+use std::{format, string::String, vec::Vec};
+
+/// The hex encoded uncompressed P-256 public key of the owner, set by `ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY` when building the enclave.
+/// Without it, the enclave can't load any config and runs with the default one.
+pub const OWNER_PUBLIC_KEY: Option<&str> = option_env!("ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY");
+
+/// The settings of the enclave which the owner of the data decides.
+/// The default one decrypts no values for clients, orders the values in all the contexts, tags no values,
+/// lets the SQL encrypt values in all the contexts and pads TEXTs to the default buckets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerConfig {
+    /// A config can't be replaced by one with a smaller version,
+    /// so that an older signed config can't be replayed to restore a revoked setting.
+    #[serde(default)]
+    version: u64,
+    /// The hex encoded X25519 public keys of the clients which the values are decrypted for.
+    #[serde(default)]
+    client_public_keys: Vec<String>,
+    /// The contexts whose values are only compared for equality, not to leak their order.
+    #[serde(default)]
+    unordered_contexts: Vec<String>,
+    /// The contexts whose values are tagged deterministically.
+    #[serde(default)]
+    tagged_contexts: Vec<TaggedContext>,
+    /// The contexts whose values are only encrypted by the allowed clients, not by the SQL,
+    /// so that the database can't encrypt chosen values to compare them with the stored ones.
+    #[serde(default)]
+    client_only_contexts: Vec<String>,
+    /// The size of the buckets which the lengths of encrypted TEXTs are padded to, at least `MIN_BUCKET_SIZE`.
+    #[serde(default = "default_text_bucket_size")]
+    text_bucket_size: usize,
+}
+
+impl Default for OwnerConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            client_public_keys: vec![],
+            unordered_contexts: vec![],
+            tagged_contexts: vec![],
+            client_only_contexts: vec![],
+            text_bucket_size: DEFAULT_BUCKET_SIZE,
+        }
+    }
+}
+
+/// A context whose values are tagged, and the domain of the tags.
+/// The columns in the same domain have the same tags for the same values, so that they can be joined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaggedContext {
+    context: String,
+    /// Defaults to the context itself.
+    #[serde(default)]
+    domain: Option<String>,
+}
+
+impl TaggedContext {
+    /// The context of the tagged values
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// The domain of the tags
+    pub fn domain(&self) -> &str {
+        self.domain.as_deref().unwrap_or(&self.context)
+    }
+}
+
+fn default_text_bucket_size() -> usize {
+    DEFAULT_BUCKET_SIZE
+}
+
+impl OwnerConfig {
+    /// The version of the config
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The public keys of the clients which the values are decrypted for
+    pub fn client_public_keys(&self) -> Result<Vec<SodiumPubKey>> {
+        self.client_public_keys
+            .iter()
+            .map(|client_key| {
+                hex::decode(client_key)
+                    .ok()
+                    .and_then(|client_key| SodiumPubKey::from_bytes(&client_key).ok())
+                    .ok_or_else(|| {
+                        EnclaveError::OwnerConfigError(format!(
+                            "invalid client public key {:?}",
+                            client_key
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    /// The contexts whose values are only compared for equality
+    pub fn unordered_contexts(&self) -> &[String] {
+        &self.unordered_contexts[..]
+    }
+
+    /// The contexts whose values are tagged deterministically
+    pub fn tagged_contexts(&self) -> &[TaggedContext] {
+        &self.tagged_contexts[..]
+    }
+
+    /// The contexts whose values are only encrypted by the allowed clients
+    pub fn client_only_contexts(&self) -> &[String] {
+        &self.client_only_contexts[..]
+    }
+
+    /// The size of the buckets which the lengths of encrypted TEXTs are padded to
+    pub fn text_bucket_size(&self) -> usize {
+        self.text_bucket_size
+    }
+}
+
+/// A config file signed by the owner.
+/// `config` is the JSON encoded `OwnerConfig` exactly as it was signed,
+/// and `signature` is the hex encoded ASN.1 DER signature over it with SHA-256,
+/// as produced by `openssl dgst -sha256 -sign`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedOwnerConfig {
+    config: String,
+    signature: String,
+}
+
+impl SignedOwnerConfig {
+    /// Parse the JSON encoded signed config.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).map_err(|e| EnclaveError::OwnerConfigError(format!("{}", e)))
+    }
+
+    /// `public_key` is the uncompressed SEC1 encoded point of the owner's key.
+    /// The config is only parsed after its signature is verified.
+    pub fn verify(&self, public_key: &[u8]) -> Result<OwnerConfig> {
+        let signature = hex::decode(&self.signature).map_err(|e| {
+            EnclaveError::OwnerConfigError(format!("failed to decode the signature: {:?}", e))
+        })?;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key)
+            .verify(self.config.as_bytes(), &signature)
+            .map_err(|_| EnclaveError::OwnerConfigError("invalid signature".into()))?;
+        let config: OwnerConfig = serde_json::from_str(&self.config)
+            .map_err(|e| EnclaveError::OwnerConfigError(format!("{}", e)))?;
+        config.client_public_keys()?;
+        if config.text_bucket_size < MIN_BUCKET_SIZE {
+            return Err(EnclaveError::InvalidBucketSizeError {
+                size: config.text_bucket_size,
+            });
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use frame_sodium::{rng::SgxRng, SodiumPrivateKey};
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+    };
+    use std::string::ToString;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_signed_config,
+            test_host_injected_client_key,
+            test_unsigned_config,
+            test_config_signed_by_another_key,
+            test_small_bucket_size,
+        )
+    }
+
+    fn key_pair() -> EcdsaKeyPair {
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &SystemRandom::new())
+                .unwrap();
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref()).unwrap()
+    }
+
+    fn client_key() -> SodiumPubKey {
+        SodiumPrivateKey::from_random(&mut SgxRng::new().unwrap())
+            .unwrap()
+            .public_key()
+    }
+
+    fn sign(key_pair: &EcdsaKeyPair, config: &OwnerConfig) -> SignedOwnerConfig {
+        let config = serde_json::to_string(config).unwrap();
+        let signature = key_pair
+            .sign(&SystemRandom::new(), config.as_bytes())
+            .unwrap();
+        SignedOwnerConfig {
+            config,
+            signature: hex::encode(signature.as_ref()),
+        }
+    }
+
+    fn config_with(client_keys: &[SodiumPubKey]) -> OwnerConfig {
+        OwnerConfig {
+            version: 1,
+            client_public_keys: client_keys
+                .iter()
+                .map(|client_key| hex::encode(client_key.to_bytes()))
+                .collect(),
+            unordered_contexts: vec!["t.c_enc".to_string()],
+            tagged_contexts: vec![
+                TaggedContext {
+                    context: "t.c_dept".to_string(),
+                    domain: Some("dept".to_string()),
+                },
+                TaggedContext {
+                    context: "t.c_name".to_string(),
+                    domain: None,
+                },
+            ],
+            client_only_contexts: vec!["t.c_salary".to_string()],
+            text_bucket_size: 64,
+        }
+    }
+
+    fn test_signed_config() {
+        let key_pair = key_pair();
+        let client_key = client_key();
+        let config = config_with(std::slice::from_ref(&client_key));
+
+        let encoded = serde_json::to_vec(&sign(&key_pair, &config)).unwrap();
+        let verified = SignedOwnerConfig::from_bytes(&encoded)
+            .unwrap()
+            .verify(key_pair.public_key().as_ref())
+            .unwrap();
+        assert_eq!(verified, config);
+        assert_eq!(verified.version(), 1);
+        assert_eq!(verified.client_public_keys().unwrap(), vec![client_key]);
+        assert_eq!(verified.unordered_contexts(), &["t.c_enc".to_string()]);
+        assert_eq!(verified.client_only_contexts(), &["t.c_salary".to_string()]);
+        assert_eq!(verified.text_bucket_size(), 64);
+        let tagged = verified.tagged_contexts();
+        assert_eq!(
+            (tagged[0].context(), tagged[0].domain()),
+            ("t.c_dept", "dept")
+        );
+        assert_eq!(
+            (tagged[1].context(), tagged[1].domain()),
+            ("t.c_name", "t.c_name")
+        );
+
+        // The fields left out are the defaults.
+        let signed = SignedOwnerConfig {
+            config: r#"{"version":2}"#.to_string(),
+            signature: hex::encode(
+                key_pair
+                    .sign(&SystemRandom::new(), br#"{"version":2}"#)
+                    .unwrap()
+                    .as_ref(),
+            ),
+        };
+        let verified = signed.verify(key_pair.public_key().as_ref()).unwrap();
+        assert_eq!(verified.version(), 2);
+        assert_eq!(verified.text_bucket_size(), DEFAULT_BUCKET_SIZE);
+        assert!(verified.client_public_keys().unwrap().is_empty());
+        assert!(verified.unordered_contexts().is_empty());
+        assert!(verified.tagged_contexts().is_empty());
+        assert!(verified.client_only_contexts().is_empty());
+    }
+
+    fn test_small_bucket_size() {
+        let key_pair = key_pair();
+        let mut config = config_with(&[]);
+        config.text_bucket_size = MIN_BUCKET_SIZE - 1;
+        assert!(matches!(
+            sign(&key_pair, &config).verify(key_pair.public_key().as_ref()),
+            Err(EnclaveError::InvalidBucketSizeError { .. })
+        ));
+
+        config.text_bucket_size = MIN_BUCKET_SIZE;
+        assert!(sign(&key_pair, &config)
+            .verify(key_pair.public_key().as_ref())
+            .is_ok());
+    }
+
+    fn test_host_injected_client_key() {
+        let key_pair = key_pair();
+        let mut signed = sign(&key_pair, &config_with(&[client_key()]));
+
+        // The host appends its own key to the signed list.
+        let injected = hex::encode(client_key().to_bytes());
+        signed.config = signed
+            .config
+            .replace("\"]", &format!("\",\"{}\"]", injected));
+        assert!(signed.config.contains(&injected));
+        assert!(matches!(
+            signed.verify(key_pair.public_key().as_ref()),
+            Err(EnclaveError::OwnerConfigError(_))
+        ));
+
+        // Or lifts the restriction on ordering.
+        let mut signed = sign(&key_pair, &config_with(&[]));
+        signed.config = signed.config.replace("\"t.c_enc\"", "");
+        assert!(signed.verify(key_pair.public_key().as_ref()).is_err());
+
+        // Or lets the SQL encrypt values in a client-only context.
+        let mut signed = sign(&key_pair, &config_with(&[]));
+        signed.config = signed.config.replace("\"t.c_salary\"", "");
+        assert!(signed.verify(key_pair.public_key().as_ref()).is_err());
+
+        // Or enables tagging another context into a domain, to join it with a tagged one.
+        let mut signed = sign(&key_pair, &config_with(&[]));
+        signed.config = signed.config.replace(
+            "\"tagged_contexts\":[",
+            "\"tagged_contexts\":[{\"context\":\"u.c_secret\",\"domain\":\"dept\"},",
+        );
+        assert!(signed.config.contains("u.c_secret"));
+        assert!(signed.verify(key_pair.public_key().as_ref()).is_err());
+
+        // Or replaces the list entirely.
+        let forged = serde_json::to_string(&config_with(&[client_key()])).unwrap();
+        signed.config = forged;
+        assert!(signed.verify(key_pair.public_key().as_ref()).is_err());
+    }
+
+    fn test_unsigned_config() {
+        let key_pair = key_pair();
+        let unsigned = SignedOwnerConfig {
+            config: serde_json::to_string(&config_with(&[client_key()])).unwrap(),
+            signature: "".to_string(),
+        };
+        assert!(unsigned.verify(key_pair.public_key().as_ref()).is_err());
+
+        let malformed = SignedOwnerConfig {
+            signature: "not hex".to_string(),
+            ..unsigned
+        };
+        assert!(malformed.verify(key_pair.public_key().as_ref()).is_err());
+        assert!(SignedOwnerConfig::from_bytes(br#"{"client_public_keys":[]}"#).is_err());
+    }
+
+    fn test_config_signed_by_another_key() {
+        let owner = key_pair();
+        let host = key_pair();
+        let signed = sign(&host, &config_with(&[client_key()]));
+        assert!(signed.verify(host.public_key().as_ref()).is_ok());
+        assert!(signed.verify(owner.public_key().as_ref()).is_err());
+    }
+}
@@ -1,10 +1,23 @@
 //! Plain data types read/written only inside enclave.
 
-pub(crate) mod plain_avg_state;
+pub(crate) mod plain_aggregate_state;
+pub(crate) mod plain_real;
+pub(crate) mod plain_text;
 
+mod plain_accumulator;
+mod plain_bigint;
+mod plain_cmp;
+mod plain_from;
 mod plain_integer;
-mod plain_real;
 
-pub use plain_avg_state::PlainAvgState;
+pub use plain_accumulator::{AccValue, PlainAccumulator, ACC_VALUE_SIZE};
+pub use plain_aggregate_state::{
+    Aggregate, Avg, BigIntMax, BigIntMin, BigIntSum, Count, Max, Min, PlainAggregateState,
+    PlainResult, RealAvg, RealSum, ResultPolicy, Sum,
+};
+pub use plain_bigint::PlainBigInt;
+pub use plain_cmp::{PlainEq, PlainOrd, PlainTag};
+pub use plain_from::PlainFrom;
 pub use plain_integer::PlainInteger;
 pub use plain_real::PlainReal;
+pub use plain_text::{PlainText, MAX_TEXT_SIZE};
@@ -0,0 +1,129 @@
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt};
+use module_encrypted_sql_ops_ecall_types::enc_type::enc_aggregate_state::EncAccumulator;
+use std::{convert::TryInto, fmt::Debug, vec::Vec};
+
+/// The size of the accumulator values
+pub const ACC_VALUE_SIZE: usize = 16;
+
+/// Values of the accumulators, serialized into fixed-size bytes
+/// so that the states of the aggregates can't be distinguished by their lengths.
+pub trait AccValue: Copy + PartialEq + Debug {
+    /// Serialize, zero-padded
+    fn to_bytes(self) -> [u8; ACC_VALUE_SIZE];
+
+    /// Deserialize
+    fn from_bytes(bytes: [u8; ACC_VALUE_SIZE]) -> Self;
+}
+
+impl AccValue for i64 {
+    fn to_bytes(self) -> [u8; ACC_VALUE_SIZE] {
+        let mut bytes = [0u8; ACC_VALUE_SIZE];
+        bytes[..8].copy_from_slice(&self.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; ACC_VALUE_SIZE]) -> Self {
+        i64::from_be_bytes(bytes[..8].try_into().unwrap())
+    }
+}
+
+impl AccValue for f64 {
+    fn to_bytes(self) -> [u8; ACC_VALUE_SIZE] {
+        let mut bytes = [0u8; ACC_VALUE_SIZE];
+        bytes[..8].copy_from_slice(&self.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; ACC_VALUE_SIZE]) -> Self {
+        f64::from_be_bytes(bytes[..8].try_into().unwrap())
+    }
+}
+
+/// Sum and number of values
+impl AccValue for (f64, i64) {
+    fn to_bytes(self) -> [u8; ACC_VALUE_SIZE] {
+        let mut bytes = [0u8; ACC_VALUE_SIZE];
+        bytes[..8].copy_from_slice(&self.0.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.1.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; ACC_VALUE_SIZE]) -> Self {
+        (
+            f64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            i64::from_be_bytes(bytes[8..].try_into().unwrap()),
+        )
+    }
+}
+
+/// Sum and number of values
+impl AccValue for (i64, i64) {
+    fn to_bytes(self) -> [u8; ACC_VALUE_SIZE] {
+        let mut bytes = [0u8; ACC_VALUE_SIZE];
+        bytes[..8].copy_from_slice(&self.0.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.1.to_be_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: [u8; ACC_VALUE_SIZE]) -> Self {
+        (
+            i64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            i64::from_be_bytes(bytes[8..].try_into().unwrap()),
+        )
+    }
+}
+
+/// Plain representation of the accumulator of an aggregate,
+/// tagged with the aggregate it belongs to.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlainAccumulator {
+    tag: u8,
+    value: [u8; ACC_VALUE_SIZE],
+}
+
+impl PlainAccumulator {
+    /// Constructor
+    pub fn new<V: AccValue>(tag: u8, value: V) -> Self {
+        Self {
+            tag,
+            value: value.to_bytes(),
+        }
+    }
+
+    /// Tag of the aggregate
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    /// Get raw representation
+    pub fn value<V: AccValue>(&self) -> V {
+        V::from_bytes(self.value)
+    }
+}
+
+impl From<PlainAccumulator> for Vec<u8> {
+    fn from(p: PlainAccumulator) -> Self {
+        let mut v = vec![p.tag];
+        v.extend_from_slice(&p.value);
+        v
+    }
+}
+
+impl From<Vec<u8>> for PlainAccumulator {
+    fn from(v: Vec<u8>) -> Self {
+        Self {
+            tag: v[0],
+            value: v[1..].try_into().unwrap(),
+        }
+    }
+}
+
+impl AeadEncrypt for PlainAccumulator {
+    type Encrypted = EncAccumulator;
+}
+
+impl AeadDecrypt for EncAccumulator {
+    type Decrypted = PlainAccumulator;
+    const TYPE_LABEL: &'static [u8] = b"ENCACCUMULATOR";
+    const DECRYPTED_SIZE: Option<usize> = Some(1 + ACC_VALUE_SIZE);
+}
@@ -0,0 +1,732 @@
+use crate::aggregate_calc::AggregateCalc;
+use crate::error::{EnclaveError, Result};
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt, CryptContext, TypeCipher};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::*,
+    enc_type::{enc_aggregate_state::EncAggregateState, EncBigInt, EncInteger, EncReal},
+};
+use std::{convert::TryFrom, marker::PhantomData};
+
+use super::{AccValue, PlainAccumulator, PlainBigInt, PlainInteger, PlainReal};
+
+/// Whether the result of an aggregate leaves enclave encrypted or in plain text.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ResultPolicy {
+    /// Encrypted, since it may be one of the aggregated values, in the context derived from theirs by
+    /// [NAME](Aggregate::NAME), e.g. `t.c_enc#SUM`, so that it can't be written back among them.
+    Encrypted,
+    /// In plain text, since it reveals nothing more than the table itself.
+    Plain,
+}
+
+/// Result of an aggregate before applying its [ResultPolicy](ResultPolicy).
+#[derive(Clone, PartialEq, Debug)]
+pub enum PlainResult {
+    /// INTEGER
+    Integer(PlainInteger),
+    /// BIGINT
+    BigInt(PlainBigInt),
+    /// REAL
+    Real(PlainReal),
+}
+
+/// Aggregate over an encrypted type whose intermediate state is a single accumulator.
+pub trait Aggregate {
+    /// Encrypted type of the aggregated values
+    type Enc: AeadDecrypt;
+
+    /// Value of the accumulator
+    type Acc: AccValue;
+
+    /// Tag of the accumulators, so that the state of an aggregate can't be finalized by another one.
+    const TAG: u8;
+
+    /// Name of the aggregate, which derives the context of the encrypted result.
+    const NAME: &'static str;
+
+    /// Declared policy of the result.
+    const RESULT_POLICY: ResultPolicy;
+
+    /// Command of the state function.
+    const STATE_FUNC_CMD: u32;
+
+    /// Command of the finalize function.
+    const FINAL_FUNC_CMD: u32;
+
+    /// Command of the combine function.
+    const COMBINE_FUNC_CMD: u32;
+
+    /// Next accumulator from the current one, `None` before any value, and the next value.
+    fn step(
+        acc: Option<Self::Acc>,
+        val: <Self::Enc as AeadDecrypt>::Decrypted,
+    ) -> Result<Self::Acc>;
+
+    /// Accumulator over the values of two accumulators, e.g. of parallel workers.
+    fn merge(acc: Self::Acc, other: Self::Acc) -> Result<Self::Acc>;
+
+    /// Result from the accumulator, `None` for NULL.
+    fn result(acc: Option<Self::Acc>) -> Result<Option<PlainResult>>;
+}
+
+fn checked_sum(acc: Option<i64>, val: i64) -> Result<i64> {
+    acc.unwrap_or(0)
+        .checked_add(val)
+        .ok_or(EnclaveError::OverflowError)
+}
+
+fn integer_result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+    acc.map(|value| {
+        i32::try_from(value)
+            .map(|i| PlainResult::Integer(PlainInteger::new(i)))
+            .map_err(|_| EnclaveError::ResultOutOfRangeError { value })
+    })
+    .transpose()
+}
+
+fn bigint_result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+    Ok(acc.map(|i| PlainResult::BigInt(PlainBigInt::new(i))))
+}
+
+/// SUM of INTEGER, encrypted result in BIGINT as PostgreSQL's.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Sum;
+
+impl Aggregate for Sum {
+    type Enc = EncInteger;
+    type Acc = i64;
+    const TAG: u8 = 1;
+    const NAME: &'static str = "SUM";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCINTEGER_SUM_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCINTEGER_SUM_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCINTEGER_SUM_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainInteger) -> Result<i64> {
+        checked_sum(acc, i64::from(val.to_i32()))
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        checked_sum(Some(acc), other)
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        bigint_result(acc)
+    }
+}
+
+/// COUNT of INTEGER, plain result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Count;
+
+impl Aggregate for Count {
+    type Enc = EncInteger;
+    type Acc = i64;
+    const TAG: u8 = 2;
+    const NAME: &'static str = "COUNT";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Plain;
+    const STATE_FUNC_CMD: u32 = ENCINTEGER_COUNT_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCINTEGER_COUNT_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCINTEGER_COUNT_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, _val: PlainInteger) -> Result<i64> {
+        checked_sum(acc, 1)
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        checked_sum(Some(acc), other)
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        bigint_result(Some(acc.unwrap_or(0)))
+    }
+}
+
+/// MIN of INTEGER, encrypted result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Min;
+
+impl Aggregate for Min {
+    type Enc = EncInteger;
+    type Acc = i64;
+    const TAG: u8 = 3;
+    const NAME: &'static str = "MIN";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCINTEGER_MIN_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCINTEGER_MIN_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCINTEGER_MIN_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainInteger) -> Result<i64> {
+        let val = i64::from(val.to_i32());
+        Ok(acc.map_or(val, |acc| acc.min(val)))
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        Ok(acc.min(other))
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        integer_result(acc)
+    }
+}
+
+/// MAX of INTEGER, encrypted result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Max;
+
+impl Aggregate for Max {
+    type Enc = EncInteger;
+    type Acc = i64;
+    const TAG: u8 = 4;
+    const NAME: &'static str = "MAX";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCINTEGER_MAX_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCINTEGER_MAX_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCINTEGER_MAX_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainInteger) -> Result<i64> {
+        let val = i64::from(val.to_i32());
+        Ok(acc.map_or(val, |acc| acc.max(val)))
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        Ok(acc.max(other))
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        integer_result(acc)
+    }
+}
+
+/// SUM of BIGINT, encrypted result. Unlike PostgreSQL's, it's BIGINT and fails on overflow.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct BigIntSum;
+
+impl Aggregate for BigIntSum {
+    type Enc = EncBigInt;
+    type Acc = i64;
+    const TAG: u8 = 5;
+    const NAME: &'static str = "SUM";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCBIGINT_SUM_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCBIGINT_SUM_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCBIGINT_SUM_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainBigInt) -> Result<i64> {
+        checked_sum(acc, val.to_i64())
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        checked_sum(Some(acc), other)
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        bigint_result(acc)
+    }
+}
+
+/// MIN of BIGINT, encrypted result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct BigIntMin;
+
+impl Aggregate for BigIntMin {
+    type Enc = EncBigInt;
+    type Acc = i64;
+    const TAG: u8 = 6;
+    const NAME: &'static str = "MIN";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCBIGINT_MIN_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCBIGINT_MIN_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCBIGINT_MIN_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainBigInt) -> Result<i64> {
+        let val = val.to_i64();
+        Ok(acc.map_or(val, |acc| acc.min(val)))
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        Ok(acc.min(other))
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        bigint_result(acc)
+    }
+}
+
+/// MAX of BIGINT, encrypted result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct BigIntMax;
+
+impl Aggregate for BigIntMax {
+    type Enc = EncBigInt;
+    type Acc = i64;
+    const TAG: u8 = 7;
+    const NAME: &'static str = "MAX";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCBIGINT_MAX_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCBIGINT_MAX_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCBIGINT_MAX_COMBINE_FUNC;
+
+    fn step(acc: Option<i64>, val: PlainBigInt) -> Result<i64> {
+        let val = val.to_i64();
+        Ok(acc.map_or(val, |acc| acc.max(val)))
+    }
+
+    fn merge(acc: i64, other: i64) -> Result<i64> {
+        Ok(acc.max(other))
+    }
+
+    fn result(acc: Option<i64>) -> Result<Option<PlainResult>> {
+        bigint_result(acc)
+    }
+}
+
+/// SUM of REAL, encrypted result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct RealSum;
+
+impl Aggregate for RealSum {
+    type Enc = EncReal;
+    type Acc = f64;
+    const TAG: u8 = 8;
+    const NAME: &'static str = "SUM";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Encrypted;
+    const STATE_FUNC_CMD: u32 = ENCREAL_SUM_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCREAL_SUM_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCREAL_SUM_COMBINE_FUNC;
+
+    fn step(acc: Option<f64>, val: PlainReal) -> Result<f64> {
+        Ok(acc.unwrap_or(0.0) + f64::from(val.to_f32()))
+    }
+
+    fn merge(acc: f64, other: f64) -> Result<f64> {
+        Ok(acc + other)
+    }
+
+    fn result(acc: Option<f64>) -> Result<Option<PlainResult>> {
+        Ok(acc.map(|sum| PlainResult::Real(PlainReal::new(sum as f32))))
+    }
+}
+
+/// AVG of INTEGER, plain result.
+///
+/// The sum is accumulated in BIGINT, so that it doesn't overflow as soon as it exceeds INTEGER.
+/// NaN for no values, unlike PostgreSQL's NULL.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Avg;
+
+impl Aggregate for Avg {
+    type Enc = EncInteger;
+    type Acc = (i64, i64);
+    const TAG: u8 = 10;
+    const NAME: &'static str = "AVG";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Plain;
+    const STATE_FUNC_CMD: u32 = ENCINTEGER_AVG_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCINTEGER_AVG_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCINTEGER_AVG_COMBINE_FUNC;
+
+    fn step(acc: Option<(i64, i64)>, val: PlainInteger) -> Result<(i64, i64)> {
+        let (sum, n) = acc.unwrap_or((0, 0));
+        Ok((
+            checked_sum(Some(sum), i64::from(val.to_i32()))?,
+            checked_sum(Some(n), 1)?,
+        ))
+    }
+
+    fn merge(acc: (i64, i64), other: (i64, i64)) -> Result<(i64, i64)> {
+        Ok((
+            checked_sum(Some(acc.0), other.0)?,
+            checked_sum(Some(acc.1), other.1)?,
+        ))
+    }
+
+    fn result(acc: Option<(i64, i64)>) -> Result<Option<PlainResult>> {
+        let avg = acc.map_or(f64::NAN, |(sum, n)| sum as f64 / n as f64);
+        Ok(Some(PlainResult::Real(PlainReal::new(avg as f32))))
+    }
+}
+
+/// AVG of REAL, plain result as AVG of INTEGER.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct RealAvg;
+
+impl Aggregate for RealAvg {
+    type Enc = EncReal;
+    type Acc = (f64, i64);
+    const TAG: u8 = 9;
+    const NAME: &'static str = "AVG";
+    const RESULT_POLICY: ResultPolicy = ResultPolicy::Plain;
+    const STATE_FUNC_CMD: u32 = ENCREAL_AVG_STATE_FUNC;
+    const FINAL_FUNC_CMD: u32 = ENCREAL_AVG_FINAL_FUNC;
+    const COMBINE_FUNC_CMD: u32 = ENCREAL_AVG_COMBINE_FUNC;
+
+    fn step(acc: Option<(f64, i64)>, val: PlainReal) -> Result<(f64, i64)> {
+        let (sum, n) = acc.unwrap_or((0.0, 0));
+        Ok((sum + f64::from(val.to_f32()), checked_sum(Some(n), 1)?))
+    }
+
+    fn merge(acc: (f64, i64), other: (f64, i64)) -> Result<(f64, i64)> {
+        Ok((acc.0 + other.0, checked_sum(Some(acc.1), other.1)?))
+    }
+
+    fn result(acc: Option<(f64, i64)>) -> Result<Option<PlainResult>> {
+        Ok(acc.map(|(sum, n)| PlainResult::Real(PlainReal::new((sum / n as f64) as f32))))
+    }
+}
+
+/// Intermediate state to calculate an [Aggregate](Aggregate).
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlainAggregateState<A: Aggregate> {
+    acc: Option<A::Acc>,
+
+    /// context of the aggregated values, `None` before any value
+    pub context: Option<CryptContext>,
+
+    aggregate: PhantomData<A>,
+}
+
+impl<A: Aggregate> Default for PlainAggregateState<A> {
+    fn default() -> Self {
+        Self {
+            acc: None,
+            context: None,
+            aggregate: PhantomData,
+        }
+    }
+}
+
+impl<A: Aggregate> PlainAggregateState<A> {
+    /// Constructor from EncAggregateState, which must be of the same aggregate
+    pub fn from_encrypted(encrypted: EncAggregateState, cipher: &TypeCipher) -> Result<Self> {
+        match encrypted {
+            EncAggregateState::Interm { acc } => {
+                let (plain_acc, context) = acc.decrypt(cipher)?;
+                if plain_acc.tag() != A::TAG {
+                    return Err(EnclaveError::AggregateMismatchError {
+                        expected: A::TAG,
+                        actual: plain_acc.tag(),
+                    });
+                }
+                Ok(Self {
+                    acc: Some(plain_acc.value()),
+                    context: Some(context),
+                    aggregate: PhantomData,
+                })
+            }
+            EncAggregateState::Initial => Ok(Self::default()),
+        }
+    }
+
+    /// Encrypt to EncAggregateState in the context of the aggregated values
+    pub fn into_encrypted(self, cipher: &TypeCipher) -> Result<EncAggregateState> {
+        match self.acc {
+            Some(acc) => {
+                let context = self.context.unwrap_or_default();
+                let enc_acc = PlainAccumulator::new(A::TAG, acc).encrypt(cipher, &context)?;
+                Ok(EncAggregateState::Interm { acc: enc_acc })
+            }
+            None => Ok(EncAggregateState::Initial),
+        }
+    }
+
+    /// Bind the context of the next value, which must be the same as the aggregated values'.
+    pub fn bind_context(&mut self, context: CryptContext) -> Result<()> {
+        match &self.context {
+            Some(current) => current.ensure_eq(&context),
+            None => {
+                self.context = Some(context);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<A: Aggregate> AggregateCalc for PlainAggregateState<A> {
+    type Input = <A::Enc as AeadDecrypt>::Decrypted;
+    type Output = Result<Option<PlainResult>>;
+
+    fn accumulate(&mut self, val: Self::Input) -> Result<()> {
+        self.acc = Some(A::step(self.acc, val)?);
+        Ok(())
+    }
+
+    fn combine(&mut self, other: Self) -> Result<()> {
+        if let Some(context) = other.context {
+            self.bind_context(context)?;
+        }
+        self.acc = match (self.acc, other.acc) {
+            (Some(acc), Some(other)) => Some(A::merge(acc, other)?),
+            (acc, other) => acc.or(other),
+        };
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Option<PlainResult>> {
+        A::result(self.acc)
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::type_crypt::MasterKey;
+    use std::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_no_sample,
+            test_calculation,
+            test_bigint_calculation,
+            test_real_calculation,
+            test_overflow,
+            test_encrypted_state,
+            test_aggregate_mismatch,
+            test_combine,
+        )
+    }
+
+    fn aggregate<A, V>(vals: &[V]) -> Option<PlainResult>
+    where
+        A: Aggregate,
+        V: Clone + Into<<A::Enc as AeadDecrypt>::Decrypted>,
+    {
+        let mut state = PlainAggregateState::<A>::default();
+        for val in vals {
+            state.accumulate(val.clone().into()).unwrap();
+        }
+        state.finalize().unwrap()
+    }
+
+    fn integer(i: i32) -> Option<PlainResult> {
+        Some(PlainResult::Integer(PlainInteger::new(i)))
+    }
+
+    fn bigint(i: i64) -> Option<PlainResult> {
+        Some(PlainResult::BigInt(PlainBigInt::new(i)))
+    }
+
+    fn real(f: f32) -> Option<PlainResult> {
+        Some(PlainResult::Real(PlainReal::new(f)))
+    }
+
+    fn context() -> CryptContext {
+        CryptContext::new("t.c_enc".to_string()).unwrap()
+    }
+
+    fn integers(vals: &[i32]) -> Vec<PlainInteger> {
+        vals.iter().map(|i| PlainInteger::new(*i)).collect()
+    }
+
+    fn test_no_sample() {
+        let empty: &[PlainInteger] = &[];
+        assert_eq!(aggregate::<Sum, _>(empty), None);
+        assert_eq!(aggregate::<Count, _>(empty), bigint(0));
+        assert_eq!(aggregate::<Min, _>(empty), None);
+        assert_eq!(aggregate::<Max, _>(empty), None);
+        assert_eq!(aggregate::<RealAvg, PlainReal>(&[]), None);
+        assert!(matches!(
+            aggregate::<Avg, _>(empty),
+            Some(PlainResult::Real(avg)) if avg.to_f32().is_nan()
+        ));
+    }
+
+    fn test_calculation() {
+        let vals = integers(&[3, -1, 4, 1, 5]);
+        assert_eq!(aggregate::<Sum, _>(&vals), bigint(12));
+        assert_eq!(aggregate::<Count, _>(&vals), bigint(5));
+        assert_eq!(aggregate::<Min, _>(&vals), integer(-1));
+        assert_eq!(aggregate::<Max, _>(&vals), integer(5));
+        assert_eq!(aggregate::<Avg, _>(&vals), real(2.4));
+
+        // AVG of INTEGER sums in BIGINT
+        let vals = integers(&[i32::MAX, 1]);
+        assert_eq!(
+            aggregate::<Avg, _>(&vals),
+            real(((i64::from(i32::MAX) + 1) / 2) as f32)
+        );
+    }
+
+    fn test_bigint_calculation() {
+        let vals: Vec<PlainBigInt> = [i64::from(i32::MAX) * 2, -1, 4]
+            .iter()
+            .map(|i| PlainBigInt::new(*i))
+            .collect();
+        assert_eq!(
+            aggregate::<BigIntSum, _>(&vals),
+            bigint(i64::from(i32::MAX) * 2 + 3)
+        );
+        assert_eq!(aggregate::<BigIntMin, _>(&vals), bigint(-1));
+        assert_eq!(
+            aggregate::<BigIntMax, _>(&vals),
+            bigint(i64::from(i32::MAX) * 2)
+        );
+    }
+
+    fn test_real_calculation() {
+        let vals: Vec<PlainReal> = [1.5, -0.5, 3.0]
+            .iter()
+            .map(|f| PlainReal::new(*f))
+            .collect();
+        assert_eq!(aggregate::<RealSum, _>(&vals), real(4.0));
+        assert_eq!(aggregate::<RealAvg, _>(&vals), real(4.0 / 3.0));
+    }
+
+    fn test_overflow() {
+        // SUM of INTEGER doesn't overflow as BIGINT, and the result is BIGINT.
+        let mut state = PlainAggregateState::<Sum>::default();
+        state.accumulate(PlainInteger::new(i32::MAX)).unwrap();
+        state.accumulate(PlainInteger::new(i32::MAX)).unwrap();
+        assert_eq!(state.finalize().unwrap(), bigint(i64::from(i32::MAX) * 2));
+
+        let mut state = PlainAggregateState::<BigIntSum> {
+            acc: Some(i64::MAX),
+            ..Default::default()
+        };
+        assert!(matches!(
+            state.accumulate(PlainBigInt::new(1)),
+            Err(EnclaveError::OverflowError)
+        ));
+    }
+
+    fn test_encrypted_state() {
+        let cipher = TypeCipher::new(MasterKey::new_random().unwrap());
+        let context = context();
+
+        let initial = PlainAggregateState::<Max>::default()
+            .into_encrypted(&cipher)
+            .unwrap();
+        assert_eq!(initial, EncAggregateState::Initial);
+
+        let mut state = PlainAggregateState::<Max>::from_encrypted(initial, &cipher).unwrap();
+        state.bind_context(context.clone()).unwrap();
+        state.accumulate(PlainInteger::new(42)).unwrap();
+
+        let encrypted = state.clone().into_encrypted(&cipher).unwrap();
+        let decrypted = PlainAggregateState::<Max>::from_encrypted(encrypted, &cipher).unwrap();
+        assert_eq!(decrypted, state);
+        assert_eq!(decrypted.context, Some(context.clone()));
+
+        let mut state = PlainAggregateState::<RealAvg>::default();
+        state.bind_context(context.clone()).unwrap();
+        state.accumulate(PlainReal::new(0.25)).unwrap();
+        let encrypted = state.clone().into_encrypted(&cipher).unwrap();
+        let decrypted = PlainAggregateState::<RealAvg>::from_encrypted(encrypted, &cipher).unwrap();
+        assert_eq!(decrypted, state);
+
+        let mut state = PlainAggregateState::<Avg>::default();
+        state.bind_context(context).unwrap();
+        state.accumulate(PlainInteger::new(i32::MAX)).unwrap();
+        state.accumulate(PlainInteger::new(i32::MAX)).unwrap();
+        let encrypted = state.clone().into_encrypted(&cipher).unwrap();
+        let decrypted = PlainAggregateState::<Avg>::from_encrypted(encrypted, &cipher).unwrap();
+        assert_eq!(decrypted, state);
+    }
+
+    fn test_aggregate_mismatch() {
+        let cipher = TypeCipher::new(MasterKey::new_random().unwrap());
+
+        let mut state = PlainAggregateState::<Sum>::default();
+        state.bind_context(context()).unwrap();
+        state.accumulate(PlainInteger::new(42)).unwrap();
+        let encrypted = state.into_encrypted(&cipher).unwrap();
+
+        // The state of SUM can't be finalized by COUNT in plain text.
+        assert!(matches!(
+            PlainAggregateState::<Count>::from_encrypted(encrypted, &cipher),
+            Err(EnclaveError::AggregateMismatchError {
+                expected: 2,
+                actual: 1
+            })
+        ));
+
+        // Nor the state of AVG by SUM, whose sum is in it.
+        let mut state = PlainAggregateState::<Avg>::default();
+        state.bind_context(context()).unwrap();
+        state.accumulate(PlainInteger::new(42)).unwrap();
+        let encrypted = state.into_encrypted(&cipher).unwrap();
+        assert!(matches!(
+            PlainAggregateState::<Sum>::from_encrypted(encrypted, &cipher),
+            Err(EnclaveError::AggregateMismatchError {
+                expected: 1,
+                actual: 10
+            })
+        ));
+
+        // Nor the state of SUM of REAL by AVG.
+        let mut state = PlainAggregateState::<RealSum>::default();
+        state.bind_context(context()).unwrap();
+        state.accumulate(PlainReal::new(4.2)).unwrap();
+        let encrypted = state.into_encrypted(&cipher).unwrap();
+        assert!(matches!(
+            PlainAggregateState::<RealAvg>::from_encrypted(encrypted, &cipher),
+            Err(EnclaveError::AggregateMismatchError {
+                expected: 9,
+                actual: 8
+            })
+        ));
+    }
+
+    fn test_combine() {
+        fn combined<A, V>(vals: &[V], split: usize) -> Option<PlainResult>
+        where
+            A: Aggregate,
+            V: Clone + Into<<A::Enc as AeadDecrypt>::Decrypted>,
+        {
+            let partial = |vals: &[V]| {
+                let mut state = PlainAggregateState::<A>::default();
+                for val in vals {
+                    state.accumulate(val.clone().into()).unwrap();
+                }
+                state
+            };
+            let mut state = partial(&vals[..split]);
+            state.combine(partial(&vals[split..])).unwrap();
+            state.finalize().unwrap()
+        }
+
+        let vals = integers(&[3, -1, 4, 1, 5]);
+        for split in 0..=vals.len() {
+            assert_eq!(combined::<Sum, _>(&vals, split), bigint(12));
+            assert_eq!(combined::<Count, _>(&vals, split), bigint(5));
+            assert_eq!(combined::<Min, _>(&vals, split), integer(-1));
+            assert_eq!(combined::<Max, _>(&vals, split), integer(5));
+            assert_eq!(combined::<Avg, _>(&vals, split), real(2.4));
+        }
+        assert_eq!(combined::<Sum, PlainInteger>(&[], 0), None);
+        assert_eq!(combined::<Count, PlainInteger>(&[], 0), bigint(0));
+
+        let vals: Vec<PlainReal> = [1.5, -0.5, 3.0]
+            .iter()
+            .map(|f| PlainReal::new(*f))
+            .collect();
+        assert_eq!(combined::<RealSum, _>(&vals, 1), real(4.0));
+        assert_eq!(combined::<RealAvg, _>(&vals, 2), real(4.0 / 3.0));
+
+        let mut state = PlainAggregateState::<BigIntSum> {
+            acc: Some(i64::MAX),
+            ..Default::default()
+        };
+        let other = PlainAggregateState::<BigIntSum> {
+            acc: Some(1),
+            ..Default::default()
+        };
+        assert!(matches!(
+            state.combine(other),
+            Err(EnclaveError::OverflowError)
+        ));
+
+        let mut state = PlainAggregateState::<Max>::default();
+        state
+            .bind_context(CryptContext::new("t.c_enc".to_string()).unwrap())
+            .unwrap();
+        let mut other = PlainAggregateState::<Max>::default();
+        other
+            .bind_context(CryptContext::new("u.c_enc".to_string()).unwrap())
+            .unwrap();
+        assert!(state.combine(other).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::Result;
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{ENCBIGINT_CMP, ENCBIGINT_EQ, ENCBIGINT_FROM, ENCBIGINT_TAG},
+    enc_type::EncBigInt,
+};
+use std::{cmp::Ordering, convert::TryInto, vec::Vec};
+
+use super::{PlainEq, PlainFrom, PlainOrd, PlainTag};
+
+/// Plain representation of BIGINT.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PlainBigInt(i64);
+
+impl PlainBigInt {
+    /// Constructor
+    pub fn new(i: i64) -> Self {
+        Self(i)
+    }
+
+    /// Get raw representation
+    pub fn to_i64(&self) -> i64 {
+        self.0
+    }
+}
+
+impl From<PlainBigInt> for Vec<u8> {
+    fn from(p: PlainBigInt) -> Self {
+        p.0.to_be_bytes().to_vec()
+    }
+}
+
+impl From<Vec<u8>> for PlainBigInt {
+    fn from(v: Vec<u8>) -> Self {
+        Self(i64::from_be_bytes(v.try_into().unwrap()))
+    }
+}
+
+impl AeadEncrypt for PlainBigInt {
+    type Encrypted = EncBigInt;
+}
+
+impl AeadDecrypt for EncBigInt {
+    type Decrypted = PlainBigInt;
+    const TYPE_LABEL: &'static [u8] = b"ENCBIGINT";
+    const DECRYPTED_SIZE: Option<usize> = Some(8);
+}
+
+impl PlainFrom for PlainBigInt {
+    type Raw = i64;
+    const FROM_CMD: u32 = ENCBIGINT_FROM;
+
+    fn from_raw(raw: i64) -> Result<Self> {
+        Ok(Self(raw))
+    }
+}
+
+impl PlainEq for PlainBigInt {
+    const EQ_CMD: u32 = ENCBIGINT_EQ;
+
+    fn plain_eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PlainTag for PlainBigInt {
+    const TAG_CMD: u32 = ENCBIGINT_TAG;
+
+    fn tag_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+impl PlainOrd for PlainBigInt {
+    const CMP_CMD: u32 = ENCBIGINT_CMP;
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
@@ -0,0 +1,28 @@
+use std::{cmp::Ordering, vec::Vec};
+
+/// Plain types compared for equality inside enclave, by `=` and `<>` of their encrypted types.
+pub trait PlainEq {
+    /// Command of the equality
+    const EQ_CMD: u32;
+
+    /// Whether equal to the other value
+    fn plain_eq(&self, other: &Self) -> bool;
+}
+
+/// Plain types ordered inside enclave, by `<`, `<=`, `>`, `>=` and the btree operator class of their encrypted types.
+pub trait PlainOrd: PlainEq {
+    /// Command of the comparison
+    const CMP_CMD: u32;
+
+    /// Ordering against the other value
+    fn plain_cmp(&self, other: &Self) -> Ordering;
+}
+
+/// Plain types tagged deterministically inside enclave, so that their encrypted types are grouped and joined by the tags.
+pub trait PlainTag: PlainEq {
+    /// Command of the tag
+    const TAG_CMD: u32;
+
+    /// Bytes to tag, which are the same for the equal values by [plain_eq()](PlainEq::plain_eq).
+    fn tag_bytes(&self) -> Vec<u8>;
+}
@@ -0,0 +1,14 @@
+use crate::error::Result;
+use crate::type_crypt::AeadEncrypt;
+
+/// Plain types encrypted from SQL values by the constructors of their encrypted types, e.g. `ENCBIGINT_FROM()`.
+pub trait PlainFrom: AeadEncrypt {
+    /// Type of the SQL values
+    type Raw;
+
+    /// Command of the constructor
+    const FROM_CMD: u32;
+
+    /// Constructor from the SQL value
+    fn from_raw(raw: Self::Raw) -> Result<Self>;
+}
@@ -1,8 +1,12 @@
-use crate::type_crypt::{Pad16BytesDecrypt, Pad16BytesEncrypt};
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt};
 use module_encrypted_sql_ops_ecall_types::{
-    enc_type::EncInteger, enclave_types::EnclavePlainInteger,
+    ecall_cmd::{ENCINTEGER_CMP, ENCINTEGER_EQ, ENCINTEGER_TAG},
+    enc_type::EncInteger,
+    enclave_types::EnclavePlainInteger,
 };
-use std::{convert::TryInto, vec::Vec};
+use std::{cmp::Ordering, convert::TryInto, vec::Vec};
+
+use super::{PlainEq, PlainOrd, PlainTag};
 
 /// Plain representation of INTEGER.
 #[derive(Clone, PartialEq, Debug, Default)]
@@ -32,13 +36,40 @@ impl From<Vec<u8>> for PlainInteger {
     }
 }
 
-impl Pad16BytesEncrypt for PlainInteger {
+impl AeadEncrypt for PlainInteger {
     type Encrypted = EncInteger;
 }
 
-impl Pad16BytesDecrypt for EncInteger {
+impl AeadDecrypt for EncInteger {
     type Decrypted = PlainInteger;
-    const DECRYPTED_SIZE: usize = 4;
+    // Empty to decrypt the values encrypted before the types were labeled.
+    const TYPE_LABEL: &'static [u8] = b"";
+    const DECRYPTED_SIZE: Option<usize> = Some(4);
+    const HAS_LEGACY: bool = true;
+}
+
+impl PlainEq for PlainInteger {
+    const EQ_CMD: u32 = ENCINTEGER_EQ;
+
+    fn plain_eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PlainTag for PlainInteger {
+    const TAG_CMD: u32 = ENCINTEGER_TAG;
+
+    fn tag_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+impl PlainOrd for PlainInteger {
+    const CMP_CMD: u32 = ENCINTEGER_CMP;
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
 }
 
 impl From<EnclavePlainInteger> for PlainInteger {
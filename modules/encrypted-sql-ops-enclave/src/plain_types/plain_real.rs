@@ -1,3 +1,13 @@
+use crate::error::Result;
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{ENCREAL_CMP, ENCREAL_EQ, ENCREAL_FROM, ENCREAL_TAG},
+    enc_type::EncReal,
+};
+use std::{cmp::Ordering, convert::TryInto, vec::Vec};
+
+use super::{PlainEq, PlainFrom, PlainOrd, PlainTag};
+
 /// Plain representation of REAL (32-bit float).
 #[derive(Clone, PartialEq, Debug, Default)]
 pub struct PlainReal(f32);
@@ -13,3 +23,109 @@ impl PlainReal {
         self.0
     }
 }
+
+impl From<PlainReal> for Vec<u8> {
+    fn from(p: PlainReal) -> Self {
+        p.0.to_be_bytes().to_vec()
+    }
+}
+
+impl From<Vec<u8>> for PlainReal {
+    fn from(v: Vec<u8>) -> Self {
+        Self(f32::from_be_bytes(v.try_into().unwrap()))
+    }
+}
+
+impl AeadEncrypt for PlainReal {
+    type Encrypted = EncReal;
+}
+
+impl AeadDecrypt for EncReal {
+    type Decrypted = PlainReal;
+    const TYPE_LABEL: &'static [u8] = b"ENCREAL";
+    const DECRYPTED_SIZE: Option<usize> = Some(4);
+}
+
+impl PlainFrom for PlainReal {
+    type Raw = f32;
+    const FROM_CMD: u32 = ENCREAL_FROM;
+
+    fn from_raw(raw: f32) -> Result<Self> {
+        Ok(Self(raw))
+    }
+}
+
+impl PlainEq for PlainReal {
+    const EQ_CMD: u32 = ENCREAL_EQ;
+
+    fn plain_eq(&self, other: &Self) -> bool {
+        self.plain_cmp(other) == Ordering::Equal
+    }
+}
+
+impl PlainTag for PlainReal {
+    const TAG_CMD: u32 = ENCREAL_TAG;
+
+    /// All NaNs and both zeros have the same bytes, as they are equal.
+    fn tag_bytes(&self) -> Vec<u8> {
+        let f = if self.0.is_nan() {
+            f32::NAN
+        } else if self.0 == 0.0 {
+            0.0
+        } else {
+            self.0
+        };
+        f.to_be_bytes().to_vec()
+    }
+}
+
+impl PlainOrd for PlainReal {
+    const CMP_CMD: u32 = ENCREAL_CMP;
+
+    /// NaN equals to itself and is greater than any other value, as in PostgreSQL.
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use std::string::String;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_cmp, test_nan, test_tag_bytes)
+    }
+
+    fn cmp(lhs: f32, rhs: f32) -> Ordering {
+        PlainReal::new(lhs).plain_cmp(&PlainReal::new(rhs))
+    }
+
+    fn test_cmp() {
+        assert_eq!(cmp(-1.5, 2.0), Ordering::Less);
+        assert_eq!(cmp(f32::INFINITY, f32::MAX), Ordering::Greater);
+        assert_eq!(cmp(0.0, -0.0), Ordering::Equal);
+        assert!(PlainReal::new(0.0).plain_eq(&PlainReal::new(-0.0)));
+    }
+
+    fn test_nan() {
+        assert_eq!(cmp(f32::NAN, f32::INFINITY), Ordering::Greater);
+        assert_eq!(cmp(f32::NEG_INFINITY, f32::NAN), Ordering::Less);
+        assert!(PlainReal::new(f32::NAN).plain_eq(&PlainReal::new(f32::NAN)));
+    }
+
+    fn test_tag_bytes() {
+        let tag_bytes = |f: f32| PlainReal::new(f).tag_bytes();
+        assert_eq!(tag_bytes(0.0), tag_bytes(-0.0));
+        assert_eq!(tag_bytes(f32::NAN), tag_bytes(-f32::NAN));
+        assert_eq!(tag_bytes(f32::NAN), tag_bytes(f32::from_bits(0x7fc0_0001)));
+        assert_ne!(tag_bytes(1.5), tag_bytes(-1.5));
+        assert_ne!(tag_bytes(f32::NAN), tag_bytes(f32::INFINITY));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::error::{EnclaveError, Result};
+use crate::type_crypt::{AeadDecrypt, AeadEncrypt, TypeCipher};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{ENCTEXT_EQ, ENCTEXT_FROM, ENCTEXT_TAG},
+    enc_type::EncText,
+};
+use std::{
+    convert::TryInto,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use super::{PlainEq, PlainFrom, PlainTag};
+
+/// The maximum length of TEXT in bytes
+pub const MAX_TEXT_SIZE: usize = 4096;
+const LEN_SIZE: usize = 4;
+
+/// Plain representation of TEXT.
+///
+/// Serialized as `length (4 bytes, big endian) | UTF-8 bytes`, and zero-padded to a multiple of
+/// the [bucket size](TypeCipher::bucket_size) before encryption.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PlainText(String);
+
+impl PlainText {
+    /// Constructor
+    pub fn new(s: String) -> Result<Self> {
+        if s.len() > MAX_TEXT_SIZE {
+            return Err(EnclaveError::TextTooLongError { size: s.len() });
+        }
+        Ok(Self(s))
+    }
+
+    /// Get raw representation
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<PlainText> for Vec<u8> {
+    fn from(p: PlainText) -> Self {
+        let mut v = (p.0.len() as u32).to_be_bytes().to_vec();
+        v.extend_from_slice(p.0.as_bytes());
+        v
+    }
+}
+
+impl From<Vec<u8>> for PlainText {
+    fn from(v: Vec<u8>) -> Self {
+        let len = u32::from_be_bytes(v[..LEN_SIZE].try_into().unwrap()) as usize;
+        let bytes = &v[LEN_SIZE..LEN_SIZE + len];
+        Self(String::from_utf8_lossy(bytes).to_string())
+    }
+}
+
+impl AeadEncrypt for PlainText {
+    type Encrypted = EncText;
+
+    fn into_plain_bytes(self, cipher: &TypeCipher) -> Vec<u8> {
+        let mut plain: Vec<u8> = self.into();
+        let bucket_size = cipher.bucket_size();
+        let rem = plain.len() % bucket_size;
+        if rem != 0 {
+            plain.resize(plain.len() + bucket_size - rem, 0);
+        }
+        plain
+    }
+}
+
+impl AeadDecrypt for EncText {
+    type Decrypted = PlainText;
+    const TYPE_LABEL: &'static [u8] = b"ENCTEXT";
+    const DECRYPTED_SIZE: Option<usize> = None;
+}
+
+impl PlainFrom for PlainText {
+    type Raw = String;
+    const FROM_CMD: u32 = ENCTEXT_FROM;
+
+    fn from_raw(raw: String) -> Result<Self> {
+        Self::new(raw)
+    }
+}
+
+impl PlainEq for PlainText {
+    const EQ_CMD: u32 = ENCTEXT_EQ;
+
+    fn plain_eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PlainTag for PlainText {
+    const TAG_CMD: u32 = ENCTEXT_TAG;
+
+    /// The text without padding.
+    fn tag_bytes(&self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::type_crypt::{CryptContext, MasterKey, MIN_BUCKET_SIZE};
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_round_trip, test_bucket, test_too_long)
+    }
+
+    fn cipher(bucket_size: usize) -> TypeCipher {
+        TypeCipher::new(MasterKey::new_random().unwrap())
+            .with_bucket_size(bucket_size)
+            .unwrap()
+    }
+
+    fn context() -> CryptContext {
+        CryptContext::new("t.c_text".to_string()).unwrap()
+    }
+
+    fn encrypted_len(s: &str, cipher: &TypeCipher) -> usize {
+        PlainText::new(s.to_string())
+            .unwrap()
+            .encrypt(cipher, &context())
+            .unwrap()
+            .as_slice()
+            .len()
+    }
+
+    fn test_round_trip() {
+        let cipher = cipher(16);
+        for s in ["", "a", "暗号化されたテキスト", &"x".repeat(MAX_TEXT_SIZE)].iter() {
+            let plain = PlainText::new(s.to_string()).unwrap();
+            let encrypted = plain.clone().encrypt(&cipher, &context()).unwrap();
+            assert_eq!(encrypted.decrypt(&cipher).unwrap().0, plain);
+        }
+    }
+
+    fn test_bucket() {
+        // The length prefix and the text fill the buckets.
+        let cipher = cipher(32);
+        let one_bucket = encrypted_len("", &cipher);
+        assert_eq!(encrypted_len(&"x".repeat(28), &cipher), one_bucket);
+        assert_eq!(encrypted_len(&"x".repeat(29), &cipher), one_bucket + 32);
+        assert_eq!(encrypted_len(&"x".repeat(60), &cipher), one_bucket + 32);
+
+        let cipher = self::cipher(MIN_BUCKET_SIZE);
+        let one_bucket = encrypted_len("", &cipher);
+        assert_eq!(encrypted_len(&"x".repeat(12), &cipher), one_bucket);
+        assert_eq!(encrypted_len(&"x".repeat(13), &cipher), one_bucket + 16);
+
+        // Smaller buckets would reveal the lengths of short values.
+        for bucket_size in [0, 1, MIN_BUCKET_SIZE - 1].iter() {
+            assert!(matches!(
+                TypeCipher::new(MasterKey::new_random().unwrap())
+                    .with_bucket_size(*bucket_size),
+                Err(EnclaveError::InvalidBucketSizeError { size }) if size == *bucket_size
+            ));
+        }
+    }
+
+    fn test_too_long() {
+        assert!(matches!(
+            PlainText::new("x".repeat(MAX_TEXT_SIZE + 1)),
+            Err(EnclaveError::TextTooLongError { .. })
+        ));
+    }
+}
@@ -1,9 +1,31 @@
 //! Encryption / Decryption for [encrypted-sql-ops-ecall-types::enc_type](encrypted-sql-ops-ecall-types::enc_type).
+//!
+//! The values are encrypted by AES-128-GCM with random nonces in the following format:
+//!
+//! `version (1 byte) | context length (2 bytes, big endian) | context | nonce (12 bytes) | ciphertext | tag (16 bytes)`
+//!
+//! where the bytes before the nonce and the label of the type are authenticated as the associated data.
+//! The variable-length values are padded before encryption, so that their ciphertexts only leak their lengths rounded up to a bucket.
+//! The legacy values, which are 16-byte blocks encrypted by raw AES-128, are recognized by their length.
+//! They and the values without context are only decrypted to migrate them into a context.
+//!
+//! The values passed with clients are encrypted by sealed boxes to the client key, which is derived from the master key,
+//! or to the clients' keys, and re-encrypted into / from the encrypted types inside enclave.
+//!
+//! The values in the contexts opted in to tagging have deterministic tags by HMAC-SHA256,
+//! which leak the equality of the values in exchange for grouping and joining them.
 
-// 128-bit key
-// TODO: Generate inside enclave or acquire from sealed one.
-const MASTER_KEY: [u8; 16] = [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+pub(crate) mod aead_crypt;
+pub(crate) mod client_crypt;
+mod crypt_context;
+mod legacy_crypt;
+pub(crate) mod master_key;
+pub(crate) mod tag_crypt;
 
-mod pad16bytes_crypt;
-
-pub(crate) use pad16bytes_crypt::{Pad16BytesDecrypt, Pad16BytesEncrypt};
+pub use aead_crypt::{
+    AeadDecrypt, AeadEncrypt, TypeCipher, CIPHERTEXT_VERSION, DEFAULT_BUCKET_SIZE, MIN_BUCKET_SIZE,
+};
+pub use client_crypt::ClientCipher;
+pub use crypt_context::{CryptContext, MAX_CONTEXT_SIZE};
+pub use master_key::{MasterKey, MASTER_KEY_SIZE};
+pub use tag_crypt::{TagKey, ENC_TAG_SIZE};
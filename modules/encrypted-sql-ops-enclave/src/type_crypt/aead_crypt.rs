@@ -0,0 +1,428 @@
+use super::{legacy_crypt, CryptContext, MasterKey};
+use crate::error::{EnclaveError, Result};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, AeadInPlace, NewAead},
+    Aes128Gcm,
+};
+use sgx_trts::trts::rsgx_read_rand;
+use std::{convert::TryInto, vec::Vec};
+
+/// The version of the current ciphertext format
+pub const CIPHERTEXT_VERSION: u8 = 1;
+const CONTEXT_LEN_SIZE: usize = 2;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+/// The default size of the buckets which the lengths of variable-length values are padded to
+pub const DEFAULT_BUCKET_SIZE: usize = 32;
+/// The smallest bucket, below which the ciphertexts of short values would reveal their lengths almost exactly
+pub const MIN_BUCKET_SIZE: usize = 16;
+
+/// The master key and the policies to encrypt / decrypt the values.
+#[derive(Clone, Debug)]
+pub struct TypeCipher {
+    master_key: MasterKey,
+    migrating: bool,
+    bucket_size: usize,
+}
+
+impl TypeCipher {
+    /// The values are encrypted in non-empty contexts,
+    /// and the ones without context, including the legacy ones, are only decrypted [for_migration()](Self::for_migration).
+    pub fn new(master_key: MasterKey) -> Self {
+        Self {
+            master_key,
+            migrating: false,
+            bucket_size: DEFAULT_BUCKET_SIZE,
+        }
+    }
+
+    /// Pad the variable-length values to multiples of `bucket_size` bytes,
+    /// so that their ciphertexts only leak the lengths rounded up to the buckets.
+    /// It must be at least [MIN_BUCKET_SIZE].
+    pub fn with_bucket_size(self, bucket_size: usize) -> Result<Self> {
+        if bucket_size < MIN_BUCKET_SIZE {
+            return Err(EnclaveError::InvalidBucketSizeError { size: bucket_size });
+        }
+        Ok(Self {
+            bucket_size,
+            ..self
+        })
+    }
+
+    /// The size of the buckets which the variable-length values are padded to
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Decrypt the values without context, including the legacy ones, to migrate them into a context.
+    pub fn for_migration(self) -> Self {
+        Self {
+            migrating: true,
+            ..self
+        }
+    }
+
+    fn encrypt(
+        &self,
+        plain: Vec<u8>,
+        context: &CryptContext,
+        type_label: &[u8],
+    ) -> Result<Vec<u8>> {
+        if context.is_empty() {
+            return Err(EnclaveError::EmptyContextError);
+        }
+        self.seal(plain, context, type_label)
+    }
+
+    fn seal(&self, plain: Vec<u8>, context: &CryptContext, type_label: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rsgx_read_rand(&mut nonce)
+            .map_err(|e| EnclaveError::NonceGenerationError(format!("{:?}", e)))?;
+
+        let context = context.as_str().as_bytes();
+        let mut encrypted = Vec::with_capacity(
+            1 + CONTEXT_LEN_SIZE + context.len() + NONCE_SIZE + plain.len() + TAG_SIZE,
+        );
+        encrypted.push(CIPHERTEXT_VERSION);
+        encrypted.extend_from_slice(&(context.len() as u16).to_be_bytes());
+        encrypted.extend_from_slice(context);
+        let header_len = encrypted.len();
+        let associated_data = [&encrypted[..header_len], type_label].concat();
+        encrypted.extend_from_slice(&nonce);
+
+        let mut ciphertext = plain;
+        let tag = self
+            .cipher()
+            .encrypt_in_place_detached(
+                GenericArray::from_slice(&nonce),
+                &associated_data,
+                &mut ciphertext,
+            )
+            .map_err(|_| EnclaveError::EncryptError)?;
+        encrypted.extend_from_slice(&ciphertext);
+        encrypted.extend_from_slice(&tag);
+
+        Ok(encrypted)
+    }
+
+    fn decrypt(
+        &self,
+        encrypted: &[u8],
+        type_label: &[u8],
+        plain_size: Option<usize>,
+        has_legacy: bool,
+    ) -> Result<(Vec<u8>, CryptContext)> {
+        if has_legacy && encrypted.len() == legacy_crypt::BLOCK_SIZE {
+            if !self.migrating {
+                return Err(EnclaveError::LegacyCiphertextError);
+            }
+            let plain_size = plain_size.unwrap_or(legacy_crypt::BLOCK_SIZE);
+            let plain = legacy_crypt::decrypt(encrypted, &self.master_key, plain_size)?;
+            return Ok((plain, CryptContext::default()));
+        }
+
+        let malformed = || EnclaveError::MalformedCiphertextError {
+            size: encrypted.len(),
+        };
+        let version = *encrypted.first().ok_or_else(malformed)?;
+        if version != CIPHERTEXT_VERSION {
+            return Err(EnclaveError::UnsupportedCiphertextVersionError { version });
+        }
+        let context_len = encrypted
+            .get(1..1 + CONTEXT_LEN_SIZE)
+            .ok_or_else(malformed)?
+            .try_into()
+            .map(u16::from_be_bytes)
+            .map_err(|_| malformed())? as usize;
+        let header_len = 1 + CONTEXT_LEN_SIZE + context_len;
+        let ciphertext_len = encrypted
+            .len()
+            .checked_sub(header_len + NONCE_SIZE + TAG_SIZE)
+            .ok_or_else(malformed)?;
+        if matches!(plain_size, Some(plain_size) if plain_size != ciphertext_len) {
+            return Err(malformed());
+        }
+
+        let (header, rest) = encrypted.split_at(header_len);
+        let (nonce, rest) = rest.split_at(NONCE_SIZE);
+        let (ciphertext, tag) = rest.split_at(ciphertext_len);
+        let associated_data = [header, type_label].concat();
+        let mut plain = ciphertext.to_vec();
+        self.cipher()
+            .decrypt_in_place_detached(
+                GenericArray::from_slice(nonce),
+                &associated_data,
+                &mut plain,
+                GenericArray::from_slice(tag),
+            )
+            .map_err(|_| EnclaveError::AuthenticationError)?;
+        let context =
+            CryptContext::from_bytes(&header[1 + CONTEXT_LEN_SIZE..]).map_err(|_| malformed())?;
+        if context.is_empty() && !self.migrating {
+            return Err(EnclaveError::EmptyContextError);
+        }
+
+        Ok((plain, context))
+    }
+
+    fn cipher(&self) -> Aes128Gcm {
+        Aes128Gcm::new(GenericArray::from_slice(self.master_key.as_bytes()))
+    }
+}
+
+/// Encrypted types decrypted inside enclave.
+pub trait AeadDecrypt
+where
+    Self: Sized + Into<Vec<u8>>,
+{
+    /// Plain type of the values
+    type Decrypted: From<Vec<u8>>;
+
+    /// Label of the type authenticated with the values, so that the values of a type can't be decrypted as another type.
+    const TYPE_LABEL: &'static [u8];
+
+    /// Size of the plain values, `None` if variable-length.
+    const DECRYPTED_SIZE: Option<usize>;
+
+    /// Whether the values may be in the legacy format.
+    const HAS_LEGACY: bool = false;
+
+    /// Decrypt into the plain value and the context which it was encrypted in.
+    /// Fails if the value or its context has been tampered with.
+    fn decrypt(self, cipher: &TypeCipher) -> Result<(Self::Decrypted, CryptContext)> {
+        let (plain, context) = cipher.decrypt(
+            &self.into(),
+            Self::TYPE_LABEL,
+            Self::DECRYPTED_SIZE,
+            Self::HAS_LEGACY,
+        )?;
+        Ok((Self::Decrypted::from(plain), context))
+    }
+}
+
+/// Plain types encrypted inside enclave.
+pub trait AeadEncrypt
+where
+    Self: Sized + Into<Vec<u8>>,
+{
+    /// Encrypted type of the values
+    type Encrypted: AeadDecrypt + From<Vec<u8>>;
+
+    /// Serialize to the plain bytes to encrypt, which the variable-length types pad by [bucket_size()](TypeCipher::bucket_size).
+    fn into_plain_bytes(self, _cipher: &TypeCipher) -> Vec<u8> {
+        self.into()
+    }
+
+    /// Encrypt with a random nonce, so that the equal values have different ciphertexts.
+    fn encrypt(self, cipher: &TypeCipher, context: &CryptContext) -> Result<Self::Encrypted> {
+        let plain = self.into_plain_bytes(cipher);
+        cipher
+            .encrypt(plain, context, Self::Encrypted::TYPE_LABEL)
+            .map(Self::Encrypted::from)
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::plain_types::{PlainInteger, PlainReal};
+    use crate::type_crypt::MAX_CONTEXT_SIZE;
+    use module_encrypted_sql_ops_ecall_types::enc_type::{EncInteger, EncReal, EncText};
+    use std::string::{String, ToString};
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_round_trip,
+            test_empty_context,
+            test_randomized,
+            test_tampered,
+            test_truncated,
+            test_unsupported_version,
+            test_type_confusion,
+            test_legacy,
+            test_derived_context,
+        )
+    }
+
+    fn cipher() -> TypeCipher {
+        TypeCipher::new(MasterKey::new_random().unwrap())
+    }
+
+    fn context() -> CryptContext {
+        CryptContext::new("t.c_enc".to_string()).unwrap()
+    }
+
+    fn test_round_trip() {
+        let cipher = cipher();
+        let encrypted = PlainInteger::new(-42).encrypt(&cipher, &context()).unwrap();
+        assert_eq!(encrypted.as_slice()[0], CIPHERTEXT_VERSION);
+
+        let (plain, decrypted_context) = encrypted.decrypt(&cipher).unwrap();
+        assert_eq!(plain, PlainInteger::new(-42));
+        assert_eq!(decrypted_context, context());
+    }
+
+    fn test_empty_context() {
+        let cipher = cipher();
+        assert!(matches!(
+            PlainInteger::new(42).encrypt(&cipher, &CryptContext::default()),
+            Err(EnclaveError::EmptyContextError)
+        ));
+        assert!(matches!(
+            PlainInteger::new(42)
+                .encrypt(&cipher.clone().for_migration(), &CryptContext::default()),
+            Err(EnclaveError::EmptyContextError)
+        ));
+
+        // The values encrypted without context before it was mandatory are only decrypted to migrate them.
+        let unbound = EncInteger::from(
+            cipher
+                .seal(
+                    PlainInteger::new(42).into(),
+                    &CryptContext::default(),
+                    EncInteger::TYPE_LABEL,
+                )
+                .unwrap(),
+        );
+        assert!(matches!(
+            unbound.clone().decrypt(&cipher),
+            Err(EnclaveError::EmptyContextError)
+        ));
+        let (plain, decrypted_context) = unbound.decrypt(&cipher.for_migration()).unwrap();
+        assert_eq!(plain, PlainInteger::new(42));
+        assert!(decrypted_context.is_empty());
+    }
+
+    fn test_randomized() {
+        let cipher = cipher();
+        let encrypted1 = PlainInteger::new(1).encrypt(&cipher, &context()).unwrap();
+        let encrypted2 = PlainInteger::new(1).encrypt(&cipher, &context()).unwrap();
+        assert_ne!(encrypted1, encrypted2);
+        assert_eq!(
+            encrypted1.decrypt(&cipher).unwrap(),
+            encrypted2.decrypt(&cipher).unwrap()
+        );
+    }
+
+    fn test_tampered() {
+        let cipher = cipher();
+        let encrypted: Vec<u8> = PlainInteger::new(1)
+            .encrypt(&cipher, &context())
+            .unwrap()
+            .into();
+
+        // Any bit of the version, the context, the nonce, the ciphertext or the tag.
+        for i in 0..encrypted.len() {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 0x01;
+            assert!(EncInteger::from(tampered).decrypt(&cipher).is_err());
+        }
+
+        // The context can't be replaced with another one of the same length.
+        let mut moved = encrypted.clone();
+        moved[1 + CONTEXT_LEN_SIZE..1 + CONTEXT_LEN_SIZE + 7].copy_from_slice(b"u.c_enc");
+        assert!(matches!(
+            EncInteger::from(moved).decrypt(&cipher),
+            Err(EnclaveError::AuthenticationError)
+        ));
+
+        // Nor decrypted by another enclave's key.
+        assert!(matches!(
+            EncInteger::from(encrypted).decrypt(&self::cipher()),
+            Err(EnclaveError::AuthenticationError)
+        ));
+    }
+
+    fn test_truncated() {
+        let cipher = cipher();
+        let encrypted: Vec<u8> = PlainInteger::new(1)
+            .encrypt(&cipher, &context())
+            .unwrap()
+            .into();
+
+        for len in [0, 1, 3, encrypted.len() - 1].iter() {
+            assert!(EncInteger::from(encrypted[..*len].to_vec())
+                .decrypt(&cipher)
+                .is_err());
+        }
+        let mut extended = encrypted;
+        extended.push(0);
+        assert!(matches!(
+            EncInteger::from(extended).decrypt(&cipher),
+            Err(EnclaveError::MalformedCiphertextError { .. })
+        ));
+    }
+
+    fn test_unsupported_version() {
+        let cipher = cipher();
+        let mut encrypted: Vec<u8> = PlainInteger::new(1)
+            .encrypt(&cipher, &context())
+            .unwrap()
+            .into();
+        encrypted[0] = CIPHERTEXT_VERSION + 1;
+        assert!(matches!(
+            EncInteger::from(encrypted).decrypt(&cipher),
+            Err(EnclaveError::UnsupportedCiphertextVersionError { .. })
+        ));
+    }
+
+    fn test_type_confusion() {
+        let cipher = cipher();
+        let encrypted: Vec<u8> = PlainReal::new(1.0)
+            .encrypt(&cipher, &context())
+            .unwrap()
+            .into();
+
+        // REAL has the same length as INTEGER, but is labeled as another type.
+        assert!(matches!(
+            EncInteger::from(encrypted.clone()).decrypt(&cipher),
+            Err(EnclaveError::AuthenticationError)
+        ));
+        assert!(matches!(
+            EncText::from(encrypted).decrypt(&cipher),
+            Err(EnclaveError::AuthenticationError)
+        ));
+
+        // Only INTEGER has the legacy values.
+        let legacy = legacy_crypt::encrypt(PlainInteger::new(7).into(), &cipher.master_key);
+        assert!(EncReal::from(legacy)
+            .decrypt(&cipher.for_migration())
+            .is_err());
+    }
+
+    fn test_legacy() {
+        let master_key = MasterKey::new_random().unwrap();
+        let legacy = EncInteger::from(legacy_crypt::encrypt(
+            PlainInteger::new(7).into(),
+            &master_key,
+        ));
+
+        let cipher = TypeCipher::new(master_key);
+        assert!(matches!(
+            legacy.clone().decrypt(&cipher),
+            Err(EnclaveError::LegacyCiphertextError)
+        ));
+
+        let (plain, context) = legacy.decrypt(&cipher.for_migration()).unwrap();
+        assert_eq!(plain, PlainInteger::new(7));
+        assert!(context.is_empty());
+    }
+
+    fn test_derived_context() {
+        let cipher = cipher();
+        let derived = context().derive("SUM").unwrap();
+        assert_eq!(derived.as_str(), "t.c_enc#SUM");
+
+        // A result in the derived context can't be mixed with the values it's computed from.
+        let encrypted = PlainInteger::new(1).encrypt(&cipher, &derived).unwrap();
+        let (_, decrypted_context) = encrypted.decrypt(&cipher).unwrap();
+        assert!(context().ensure_eq(&decrypted_context).is_err());
+
+        let longest = CryptContext::new("c".repeat(MAX_CONTEXT_SIZE)).unwrap();
+        assert!(matches!(
+            longest.derive("SUM"),
+            Err(EnclaveError::ContextTooLongError { .. })
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+//! Encryption / Decryption of the values passed with clients,
+//! so that their plain text never appears in RDBMS.
+
+use crate::error::{EnclaveError, Result};
+use frame_sodium::{rng::SgxRng, SodiumCiphertext, SodiumPrivateKey, SodiumPubKey};
+use module_encrypted_sql_ops_ecall_types::client_types::{
+    ClientPayload, CLIENT_KEY_REPORT_DATA_LABEL,
+};
+use std::vec::Vec;
+
+use super::MasterKey;
+
+/// The label to derive the client key from the master key
+const CLIENT_KEY_LABEL: &[u8] = b"encrypted-sql-ops client key";
+
+/// The cipher of the values encrypted by clients to the enclave's client key,
+/// and of the ones encrypted by enclave to the allowed clients' keys.
+#[derive(Clone, Debug)]
+pub struct ClientCipher {
+    private_key: SodiumPrivateKey,
+    allowed_client_keys: Vec<SodiumPubKey>,
+}
+
+impl ClientCipher {
+    /// The client key is derived from the master key, so that it's shared by the enclaves sharing the master key.
+    pub fn new(master_key: &MasterKey, allowed_client_keys: Vec<SodiumPubKey>) -> Result<Self> {
+        let private_key = SodiumPrivateKey::from_bytes(&master_key.derive_secret(CLIENT_KEY_LABEL))
+            .map_err(|e| EnclaveError::ClientKeyError(format!("{:?}", e)))?;
+        Ok(Self {
+            private_key,
+            allowed_client_keys,
+        })
+    }
+
+    /// The public key which clients encrypt values to
+    pub fn public_key(&self) -> SodiumPubKey {
+        self.private_key.public_key()
+    }
+
+    /// The report data attesting the public key
+    pub fn report_data(&self) -> [u8; 64] {
+        let mut report_data = [0u8; 64];
+        report_data[..32].copy_from_slice(&self.public_key().to_bytes());
+        report_data[32..].copy_from_slice(CLIENT_KEY_REPORT_DATA_LABEL);
+        report_data
+    }
+
+    /// Decrypt a value encrypted by a client, returning it with the public key it's encrypted from.
+    /// The key is an ephemeral one unless the client encrypted it from its own key.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<(ClientPayload, SodiumPubKey)> {
+        let ciphertext = SodiumCiphertext::decode(ciphertext)
+            .map_err(|_| EnclaveError::ClientCiphertextError)?;
+        let plaintext = ciphertext
+            .decrypt(&self.private_key)
+            .map_err(|_| EnclaveError::ClientCiphertextError)?;
+        let payload =
+            ClientPayload::from_bytes(&plaintext).ok_or(EnclaveError::ClientCiphertextError)?;
+        Ok((payload, ciphertext.sender_public_key().clone()))
+    }
+
+    /// Whether the client is listed in the owner config
+    pub fn is_allowed_client(&self, client_key: &SodiumPubKey) -> bool {
+        self.allowed_client_keys.contains(client_key)
+    }
+
+    /// Encrypt a value to a client's key, which must be allowed to receive the values.
+    /// It's encrypted from the client key, so that the client can tell it from the ones forged by RDBMS.
+    pub fn encrypt(&self, payload: &ClientPayload, client_key: &[u8]) -> Result<Vec<u8>> {
+        let client_key = SodiumPubKey::from_bytes(client_key)
+            .map_err(|_| EnclaveError::ClientKeyNotAllowedError)?;
+        if !self.allowed_client_keys.contains(&client_key) {
+            return Err(EnclaveError::ClientKeyNotAllowedError);
+        }
+
+        let mut csprng =
+            SgxRng::new().map_err(|e| EnclaveError::NonceGenerationError(format!("{:?}", e)))?;
+        let ciphertext = SodiumCiphertext::encrypt_from(
+            &mut csprng,
+            &self.private_key,
+            &client_key,
+            &payload.to_bytes(),
+        )
+        .map_err(|_| EnclaveError::EncryptError)?;
+        Ok(ciphertext.encode())
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use std::string::{String, ToString};
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_client_key_derivation,
+            test_decrypt_from_client,
+            test_decrypt_from_allowed_client,
+            test_encrypt_to_allowed_client,
+        )
+    }
+
+    fn test_client_key_derivation() {
+        let master_key = MasterKey::new_random().unwrap();
+        let cipher = ClientCipher::new(&master_key, vec![]).unwrap();
+        assert_eq!(
+            cipher.public_key(),
+            ClientCipher::new(&master_key, vec![]).unwrap().public_key()
+        );
+        assert_ne!(
+            cipher.public_key(),
+            ClientCipher::new(&MasterKey::new_random().unwrap(), vec![])
+                .unwrap()
+                .public_key()
+        );
+
+        let report_data = cipher.report_data();
+        assert_eq!(&report_data[..32], &cipher.public_key().to_bytes()[..]);
+        assert_eq!(&report_data[32..], &CLIENT_KEY_REPORT_DATA_LABEL[..]);
+    }
+
+    fn test_decrypt_from_client() {
+        let cipher = ClientCipher::new(&MasterKey::new_random().unwrap(), vec![]).unwrap();
+        let payload = ClientPayload::from_i32(42, "t.c_enc".to_string());
+        let ciphertext = SodiumCiphertext::encrypt(
+            &mut SgxRng::new().unwrap(),
+            &cipher.public_key(),
+            &payload.to_bytes(),
+        )
+        .unwrap()
+        .encode();
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap().0, payload);
+
+        // Encrypted to another enclave's key
+        let other_cipher = ClientCipher::new(&MasterKey::new_random().unwrap(), vec![]).unwrap();
+        assert!(other_cipher.decrypt(&ciphertext).is_err());
+        assert!(cipher.decrypt(&[0u8; 16]).is_err());
+    }
+
+    fn test_decrypt_from_allowed_client() {
+        let client_key = SodiumPrivateKey::from_random(&mut SgxRng::new().unwrap()).unwrap();
+        let cipher = ClientCipher::new(
+            &MasterKey::new_random().unwrap(),
+            vec![client_key.public_key()],
+        )
+        .unwrap();
+        let payload = ClientPayload::from_i32(42, "t.c_enc".to_string());
+
+        let ciphertext = SodiumCiphertext::encrypt_from(
+            &mut SgxRng::new().unwrap(),
+            &client_key,
+            &cipher.public_key(),
+            &payload.to_bytes(),
+        )
+        .unwrap()
+        .encode();
+        let (decrypted, sender) = cipher.decrypt(&ciphertext).unwrap();
+        assert_eq!(decrypted, payload);
+        assert!(cipher.is_allowed_client(&sender));
+
+        // Encrypted by anyone from an ephemeral key
+        let ciphertext = SodiumCiphertext::encrypt(
+            &mut SgxRng::new().unwrap(),
+            &cipher.public_key(),
+            &payload.to_bytes(),
+        )
+        .unwrap()
+        .encode();
+        let (_, sender) = cipher.decrypt(&ciphertext).unwrap();
+        assert!(!cipher.is_allowed_client(&sender));
+    }
+
+    fn test_encrypt_to_allowed_client() {
+        let client_key = SodiumPrivateKey::from_random(&mut SgxRng::new().unwrap()).unwrap();
+        let cipher = ClientCipher::new(
+            &MasterKey::new_random().unwrap(),
+            vec![client_key.public_key()],
+        )
+        .unwrap();
+        let payload = ClientPayload::from_i32(42, "t.c_enc".to_string());
+
+        let ciphertext = cipher
+            .encrypt(&payload, &client_key.public_key().to_bytes())
+            .unwrap();
+        let ciphertext = SodiumCiphertext::decode(&ciphertext).unwrap();
+        assert_eq!(ciphertext.sender_public_key(), &cipher.public_key());
+        let decrypted = ciphertext.decrypt(&client_key).unwrap();
+        assert_eq!(ClientPayload::from_bytes(&decrypted).unwrap(), payload);
+
+        let other_key = SodiumPrivateKey::from_random(&mut SgxRng::new().unwrap()).unwrap();
+        assert!(cipher
+            .encrypt(&payload, &other_key.public_key().to_bytes())
+            .is_err());
+        assert!(cipher.encrypt(&payload, &[0u8; 16]).is_err());
+    }
+}
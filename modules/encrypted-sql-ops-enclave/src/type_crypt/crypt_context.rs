@@ -0,0 +1,59 @@
+use crate::error::{EnclaveError, Result};
+use std::string::String;
+
+/// The maximum length of a context in bytes
+pub const MAX_CONTEXT_SIZE: usize = 128;
+
+/// The context which a value is encrypted in, e.g. `table.column`.
+/// It's authenticated as the associated data of the ciphertext, so it can't be altered,
+/// and the values in different contexts can't be mixed, e.g. in an aggregate.
+///
+/// The empty context is the one of the values encrypted without any context, including the legacy ones,
+/// which are only decrypted to migrate them into a non-empty context.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct CryptContext(String);
+
+impl CryptContext {
+    /// Constructor
+    pub fn new(context: String) -> Result<Self> {
+        if context.len() > MAX_CONTEXT_SIZE {
+            return Err(EnclaveError::ContextTooLongError {
+                size: context.len(),
+            });
+        }
+        Ok(Self(context))
+    }
+
+    pub(super) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let context = String::from_utf8(bytes.to_vec())
+            .map_err(|_| EnclaveError::MalformedCiphertextError { size: bytes.len() })?;
+        Self::new(context)
+    }
+
+    /// Get raw representation
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the values in this context have no context.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Context of the values computed from the ones in this context, e.g. `t.c_enc#SUM` for their sum,
+    /// so that they can't be mixed with the ones in this context, e.g. written back into the column.
+    pub fn derive(&self, label: &str) -> Result<Self> {
+        Self::new(format!("{}#{}", self.0, label))
+    }
+
+    /// Ensure that the other value is in the same context, e.g. to aggregate them together.
+    pub fn ensure_eq(&self, other: &Self) -> Result<()> {
+        if self != other {
+            return Err(EnclaveError::ContextMismatchError {
+                expected: self.0.clone(),
+                actual: other.0.clone(),
+            });
+        }
+        Ok(())
+    }
+}
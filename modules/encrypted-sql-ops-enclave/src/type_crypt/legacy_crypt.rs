@@ -0,0 +1,59 @@
+//! The legacy format, a value zero-padded to a 16-byte block and encrypted by raw AES-128.
+//! It's neither randomized nor authenticated, so it's only decrypted to be migrated.
+
+use crate::error::{EnclaveError, Result};
+use aes::{cipher::generic_array::GenericArray, Aes128, BlockDecrypt, NewBlockCipher};
+use std::{convert::TryInto, vec::Vec};
+
+use super::MasterKey;
+
+/// The size of a legacy ciphertext
+pub(super) const BLOCK_SIZE: usize = 16;
+
+pub(super) fn decrypt(
+    encrypted: &[u8],
+    master_key: &MasterKey,
+    plain_size: usize,
+) -> Result<Vec<u8>> {
+    let key = GenericArray::from_slice(master_key.as_bytes());
+    let mut enc_block = GenericArray::clone_from_slice(encrypted);
+
+    let cipher = Aes128::new(&key);
+
+    let dec_block = {
+        cipher.decrypt_block(&mut enc_block);
+        enc_block
+    };
+    let decrypted = dec_block.to_vec();
+    let decrypted: [u8; BLOCK_SIZE] =
+        decrypted
+            .try_into()
+            .map_err(|orig_vec: Vec<u8>| EnclaveError::DecryptError {
+                decrypted_size: orig_vec.len(),
+                plain_size,
+            })?;
+
+    Ok(decrypted[..plain_size].to_vec())
+}
+
+/// Encrypt in the legacy format to test the migration.
+#[cfg(debug_assertions)]
+pub(super) fn encrypt(plain: Vec<u8>, master_key: &MasterKey) -> Vec<u8> {
+    use aes::BlockEncrypt;
+
+    let key = GenericArray::from_slice(master_key.as_bytes());
+
+    let mut plain_block = {
+        let mut bytes = plain;
+        bytes.resize(BLOCK_SIZE, 0);
+        GenericArray::clone_from_slice(&bytes)
+    };
+
+    let cipher = Aes128::new(&key);
+
+    let enc_block = {
+        cipher.encrypt_block(&mut plain_block);
+        plain_block
+    };
+    enc_block.to_vec()
+}
@@ -0,0 +1,118 @@
+use crate::error::{EnclaveError, Result};
+use frame_common::traits::Keccak256;
+use sgx_trts::trts::rsgx_read_rand;
+use std::{convert::TryInto, fmt};
+
+/// 128-bit key
+pub const MASTER_KEY_SIZE: usize = 16;
+
+/// The key encrypting all the values of the encrypted types.
+/// It's generated inside enclave and stored only sealed, either locally or in the key-vault nodes.
+#[derive(Clone, PartialEq)]
+pub struct MasterKey([u8; MASTER_KEY_SIZE]);
+
+impl MasterKey {
+    /// Generate a new key from the enclave's random source.
+    pub fn new_random() -> Result<Self> {
+        let mut key = [0u8; MASTER_KEY_SIZE];
+        rsgx_read_rand(&mut key)
+            .map_err(|e| EnclaveError::MasterKeyGenerationError(format!("{:?}", e)))?;
+        Ok(Self(key))
+    }
+
+    /// Restore the key unsealed or provisioned.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let key = bytes
+            .try_into()
+            .map_err(|_| EnclaveError::InvalidMasterKeyError { size: bytes.len() })?;
+        Ok(Self(key))
+    }
+
+    /// Get raw representation, which must not leave enclave unsealed.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    /// Derive a secret for another purpose, e.g. the client key, by hashing the key with the label of the purpose.
+    /// The enclaves sharing the master key derive the same secret without sealing it separately.
+    pub fn derive_secret(&self, label: &[u8]) -> [u8; 32] {
+        [self.as_bytes(), label].concat().keccak256()
+    }
+}
+
+impl fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MasterKey").finish()
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::plain_types::PlainInteger;
+    use crate::type_crypt::{AeadDecrypt, AeadEncrypt, CryptContext, TypeCipher};
+    use frame_sodium::sealing::{seal_secret, unseal_secret};
+    use std::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_random_master_keys,
+            test_invalid_master_key,
+            test_sealed_master_key,
+            test_encrypt_by_master_key,
+            test_derive_secret,
+        )
+    }
+
+    fn test_random_master_keys() {
+        let key = MasterKey::new_random().unwrap();
+        assert_ne!(key, MasterKey::new_random().unwrap());
+        assert_eq!(key.as_bytes().len(), MASTER_KEY_SIZE);
+    }
+
+    fn test_invalid_master_key() {
+        assert!(MasterKey::from_bytes(&[0u8; MASTER_KEY_SIZE]).is_ok());
+        assert!(MasterKey::from_bytes(&[0u8; MASTER_KEY_SIZE - 1]).is_err());
+        assert!(MasterKey::from_bytes(&[0u8; 32]).is_err());
+    }
+
+    fn test_sealed_master_key() {
+        let key = MasterKey::new_random().unwrap();
+        let sealed = seal_secret(key.as_bytes()).unwrap();
+        assert_ne!(&sealed[..], key.as_bytes());
+
+        let unsealed = MasterKey::from_bytes(&unseal_secret(&sealed).unwrap()).unwrap();
+        assert_eq!(unsealed, key);
+    }
+
+    fn test_encrypt_by_master_key() {
+        let cipher = TypeCipher::new(MasterKey::new_random().unwrap());
+        let context = CryptContext::new("t.c_enc".to_string()).unwrap();
+        let encrypted = PlainInteger::new(42).encrypt(&cipher, &context).unwrap();
+        assert_eq!(
+            encrypted.clone().decrypt(&cipher).unwrap().0,
+            PlainInteger::new(42)
+        );
+
+        // The values encrypted by another enclave's key are not decrypted.
+        let other_cipher = TypeCipher::new(MasterKey::new_random().unwrap());
+        assert!(encrypted.decrypt(&other_cipher).is_err());
+    }
+
+    fn test_derive_secret() {
+        let key = MasterKey::new_random().unwrap();
+        let secret = key.derive_secret(b"test label");
+        assert_eq!(secret, key.derive_secret(b"test label"));
+        assert_ne!(secret, key.derive_secret(b"other label"));
+        assert_ne!(
+            secret,
+            MasterKey::new_random()
+                .unwrap()
+                .derive_secret(b"test label")
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Deterministic tags of the values, so that RDBMS can group and join them without decrypting.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{fmt, vec::Vec};
+
+use super::MasterKey;
+
+/// The label to derive the tag key from the master key
+const TAG_KEY_LABEL: &[u8] = b"encrypted-sql-ops tag key";
+/// The size of the tags in bytes
+pub const ENC_TAG_SIZE: usize = 32;
+
+/// The key computing the tags by HMAC-SHA256, separate from the master key encrypting the values.
+#[derive(Clone)]
+pub struct TagKey([u8; 32]);
+
+impl TagKey {
+    /// The tag key is derived from the master key, so that the enclaves sharing the master key compute the same tags.
+    pub fn new(master_key: &MasterKey) -> Self {
+        Self(master_key.derive_secret(TAG_KEY_LABEL))
+    }
+
+    /// Tag of a plain value of the type labeled `type_label`, which is the same only for the equal values of the type in `domain`.
+    ///
+    /// `type_label | domain | plain` is tagged, with the lengths of the first two prefixed (4 bytes, big endian).
+    pub fn tag(&self, type_label: &[u8], domain: &str, plain: &[u8]) -> Vec<u8> {
+        // HMAC takes keys of any size.
+        let mut mac = Hmac::<Sha256>::new_varkey(&self.0).expect("invalid HMAC key size");
+        mac.input(&(type_label.len() as u32).to_be_bytes());
+        mac.input(type_label);
+        mac.input(&(domain.len() as u32).to_be_bytes());
+        mac.input(domain.as_bytes());
+        mac.input(plain);
+        mac.result().code().to_vec()
+    }
+}
+
+impl fmt::Debug for TagKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TagKey").finish()
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use std::string::String;
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(test_deterministic_tag, test_tag_separation)
+    }
+
+    fn test_deterministic_tag() {
+        let master_key = MasterKey::new_random().unwrap();
+        let tag = TagKey::new(&master_key).tag(b"ENCBIGINT", "t.c_enc", &[1, 2, 3]);
+        assert_eq!(tag.len(), ENC_TAG_SIZE);
+        assert_eq!(
+            tag,
+            TagKey::new(&master_key).tag(b"ENCBIGINT", "t.c_enc", &[1, 2, 3])
+        );
+
+        // The tags are not derived from the master key itself.
+        assert_ne!(&tag[..], &master_key.derive_secret(TAG_KEY_LABEL)[..]);
+    }
+
+    fn test_tag_separation() {
+        let key = TagKey::new(&MasterKey::new_random().unwrap());
+        let tag = key.tag(b"ENCBIGINT", "t.c_enc", &[1, 2, 3]);
+
+        assert_ne!(tag, key.tag(b"ENCBIGINT", "t.c_enc", &[1, 2, 4]));
+        assert_ne!(tag, key.tag(b"ENCBIGINT", "u.c_enc", &[1, 2, 3]));
+        assert_ne!(tag, key.tag(b"ENCREAL", "t.c_enc", &[1, 2, 3]));
+        // The lengths keep the boundaries, e.g. the domain from the value.
+        assert_ne!(tag, key.tag(b"ENCBIGINT", "t.c_enc\u{1}", &[2, 3]));
+        assert_ne!(
+            tag,
+            TagKey::new(&MasterKey::new_random().unwrap()).tag(b"ENCBIGINT", "t.c_enc", &[1, 2, 3])
+        );
+    }
+}
@@ -1,6 +1,16 @@
 //! Invokes ecall.
 
-pub mod encinteger_avg_final_func;
-pub mod encinteger_avg_state_func;
+pub mod enc_aggregate_combine_func;
+pub mod enc_aggregate_final_func;
+pub mod enc_aggregate_state_func;
+pub mod enc_cmp;
+pub mod enc_eq;
+pub mod enc_from;
+pub mod enc_tag;
 pub mod encinteger_from;
+pub mod encinteger_from_client;
+pub mod encinteger_migrate;
+pub mod encinteger_to_client;
+pub mod get_client_key;
 pub mod host_types;
+pub mod load_master_key;
@@ -0,0 +1,31 @@
+//! Workflow def.
+
+use super::host_types::{HostEncAggregateState, HostEncPair};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::enc_aggregate_state::EncAggregateState,
+    enclave_types::{EnclaveEncAggregateState, EnclaveEncPair},
+};
+
+/// Combine function of the custom aggregates, e.g. `SUM`, `COUNT`, `MIN` and `MAX` of `ENCINTEGER`,
+/// which merges the states of parallel workers.
+///
+/// The aggregate is chosen by the command, e.g. `ENCINTEGER_SUM_COMBINE_FUNC`.
+#[derive(Debug)]
+pub struct EncAggregateCombineFuncController;
+
+impl EcallController for EncAggregateCombineFuncController {
+    type HI = HostEncPair<EncAggregateState>;
+    type EI = EnclaveEncPair<EncAggregateState>;
+    type EO = EnclaveEncAggregateState;
+    type HO = HostEncAggregateState;
+    const EI_MAX_SIZE: usize = 1024;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
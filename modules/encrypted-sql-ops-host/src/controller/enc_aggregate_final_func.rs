@@ -0,0 +1,29 @@
+//! Workflow def.
+
+use super::host_types::{HostAggregateResult, HostEncAggregateState};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveAggregateResult, EnclaveEncAggregateState,
+};
+
+/// Finalize function of the custom aggregates, e.g. `SUM`, `COUNT`, `MIN` and `MAX` of `ENCINTEGER`.
+///
+/// The aggregate is chosen by the command, e.g. `ENCINTEGER_SUM_FINAL_FUNC`.
+#[derive(Debug)]
+pub struct EncAggregateFinalFuncController;
+
+impl EcallController for EncAggregateFinalFuncController {
+    type HI = HostEncAggregateState;
+    type EI = EnclaveEncAggregateState;
+    type EO = EnclaveAggregateResult;
+    type HO = HostAggregateResult;
+    const EI_MAX_SIZE: usize = 1024;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
@@ -0,0 +1,33 @@
+//! Workflow def.
+
+use super::host_types::{HostEncAggregateState, HostEncAggregateStateWithBatch};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncType,
+    enclave_types::{EnclaveEncAggregateState, EnclaveEncAggregateStateWithBatch},
+};
+use std::marker::PhantomData;
+
+/// State function of the custom aggregates, e.g. `SUM`, `COUNT`, `MIN` and `MAX` of `ENCINTEGER`,
+/// which accumulates a batch of values by an ecall.
+///
+/// The aggregate is chosen by the command, e.g. `ENCINTEGER_SUM_STATE_FUNC`.
+#[derive(Debug)]
+pub struct EncAggregateStateFuncController<E>(PhantomData<E>);
+
+impl<E: EncType> EcallController for EncAggregateStateFuncController<E> {
+    type HI = HostEncAggregateStateWithBatch<E>;
+    type EI = EnclaveEncAggregateStateWithBatch<E>;
+    type EO = EnclaveEncAggregateState;
+    type HO = HostEncAggregateState;
+    // MAX_AGGREGATE_BATCH_SIZE values of at most 175 bytes each with the state
+    const EI_MAX_SIZE: usize = 65536;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
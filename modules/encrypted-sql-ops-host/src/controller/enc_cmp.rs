@@ -0,0 +1,31 @@
+//! Workflow def.
+
+use super::host_types::{HostEncPair, HostOrdering};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncType,
+    enclave_types::{EnclaveEncPair, EnclaveOrdering},
+};
+use std::marker::PhantomData;
+
+/// Comparison of two encrypted values, which backs `<`, `<=`, `>`, `>=` and the btree operator class.
+///
+/// The type is chosen by the command, e.g. `ENCINTEGER_CMP`.
+#[derive(Debug)]
+pub struct EncCmpController<E>(PhantomData<E>);
+
+impl<E: EncType> EcallController for EncCmpController<E> {
+    type HI = HostEncPair<E>;
+    type EI = EnclaveEncPair<E>;
+    type EO = EnclaveOrdering;
+    type HO = HostOrdering;
+    const EI_MAX_SIZE: usize = 1024;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
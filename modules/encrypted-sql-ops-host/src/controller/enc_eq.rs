@@ -0,0 +1,32 @@
+//! Workflow def.
+
+use super::host_types::{HostEncPair, HostPlainBool};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncType,
+    enclave_types::{EnclaveEncPair, EnclavePlainBool},
+};
+use std::marker::PhantomData;
+
+/// Equality of two encrypted values, which backs `=` and `<>` even for the values whose order is hidden.
+///
+/// The type is chosen by the command, e.g. `ENCINTEGER_EQ`.
+#[derive(Debug)]
+pub struct EncEqController<E>(PhantomData<E>);
+
+impl<E: EncType> EcallController for EncEqController<E> {
+    type HI = HostEncPair<E>;
+    type EI = EnclaveEncPair<E>;
+    type EO = EnclavePlainBool;
+    type HO = HostPlainBool;
+    // Large enough for a pair of the longest TEXTs.
+    const EI_MAX_SIZE: usize = 16384;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.to_bool().into())
+    }
+}
@@ -0,0 +1,32 @@
+//! Workflow def.
+
+use super::host_types::{HostEncValue, HostPlainWithContext};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncType,
+    enclave_types::{EnclaveEncValue, EnclavePlainWithContext},
+};
+use std::marker::PhantomData;
+
+/// Constructor of `ENCBIGINT`, `ENCREAL` or `ENCTEXT` from the plain-text value.
+///
+/// The type is chosen by the command, e.g. `ENCBIGINT_FROM`.
+#[derive(Debug)]
+pub struct EncFromController<E>(PhantomData<E>);
+
+impl<E: EncType> EcallController for EncFromController<E> {
+    type HI = HostPlainWithContext<E::Raw>;
+    type EI = EnclavePlainWithContext<E::Raw>;
+    type EO = EnclaveEncValue<E>;
+    type HO = HostEncValue<E>;
+    // Large enough for the longest TEXT.
+    const EI_MAX_SIZE: usize = 8192;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
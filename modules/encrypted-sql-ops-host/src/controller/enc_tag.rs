@@ -0,0 +1,32 @@
+//! Workflow def.
+
+use super::host_types::{HostEncTag, HostEncValue};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncType,
+    enclave_types::{EnclaveEncTag, EnclaveEncValue},
+};
+use std::marker::PhantomData;
+
+/// Deterministic tag of an encrypted value, which backs grouping and joining the values by their tags.
+///
+/// The type is chosen by the command, e.g. `ENCINTEGER_TAG`.
+#[derive(Debug)]
+pub struct EncTagController<E>(PhantomData<E>);
+
+impl<E: EncType> EcallController for EncTagController<E> {
+    type HI = HostEncValue<E>;
+    type EI = EnclaveEncValue<E>;
+    type EO = EnclaveEncTag;
+    type HO = HostEncTag;
+    // Large enough for the longest TEXT.
+    const EI_MAX_SIZE: usize = 8192;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(host_input.into())
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(enclave_output.into())
+    }
+}
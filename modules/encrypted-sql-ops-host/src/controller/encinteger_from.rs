@@ -2,12 +2,16 @@
 //!
 //! FIXME: Workflow -> Controller
 
-use super::host_types::{HostEncInteger, HostPlainInteger};
+use super::host_types::{HostEncInteger, HostPlainIntegerWithContext};
 use frame_host::ecall_controller::EcallController;
-use module_encrypted_sql_ops_ecall_types::enclave_types::{EnclaveEncInteger, EnclavePlainInteger};
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveEncInteger, EnclavePlainIntegerWithContext,
+};
 
 /// Constructor of `ENCINTEGER` custom type.
 ///
+/// The value is encrypted with the context, e.g. `table.column`, which it can't be moved out of.
+///
 /// # Important notice
 ///
 /// Encrypted type constructors are virtually vulnerable in that:
@@ -27,14 +31,14 @@ use module_encrypted_sql_ops_ecall_types::enclave_types::{EnclaveEncInteger, Enc
 pub struct EncIntegerFromController;
 
 impl EcallController for EncIntegerFromController {
-    type HI = HostPlainInteger;
-    type EI = EnclavePlainInteger;
+    type HI = HostPlainIntegerWithContext;
+    type EI = EnclavePlainIntegerWithContext;
     type EO = EnclaveEncInteger;
     type HO = HostEncInteger;
-    const EI_MAX_SIZE: usize = 64;
+    const EI_MAX_SIZE: usize = 256;
 
     fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
-        Ok(EnclavePlainInteger::from(host_input.to_i32()))
+        Ok(EnclavePlainIntegerWithContext::from(host_input))
     }
 
     fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
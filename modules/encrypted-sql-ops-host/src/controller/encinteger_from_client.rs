@@ -0,0 +1,27 @@
+//! Workflow def.
+
+use super::host_types::{HostClientCiphertext, HostEncInteger};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveClientCiphertext, EnclaveEncInteger,
+};
+
+/// Re-encrypts an INTEGER encrypted by a client into `ENCINTEGER` in the context chosen by the client.
+#[derive(Debug)]
+pub struct EncIntegerFromClientController;
+
+impl EcallController for EncIntegerFromClientController {
+    type HI = HostClientCiphertext;
+    type EI = EnclaveClientCiphertext;
+    type EO = EnclaveEncInteger;
+    type HO = HostEncInteger;
+    const EI_MAX_SIZE: usize = 4096;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(EnclaveClientCiphertext::from(host_input))
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(HostEncInteger::from(enclave_output))
+    }
+}
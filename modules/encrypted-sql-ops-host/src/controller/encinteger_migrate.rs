@@ -0,0 +1,30 @@
+//! Workflow def.
+
+use super::host_types::{HostEncInteger, HostEncIntegerWithContext};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveEncInteger, EnclaveEncIntegerWithContext,
+};
+
+/// Re-encrypts an `ENCINTEGER` value in the current format with a fresh nonce.
+///
+/// A value without context, including a legacy one, is bound to the given context,
+/// while a value with context can only be re-encrypted in the same context.
+#[derive(Debug)]
+pub struct EncIntegerMigrateController;
+
+impl EcallController for EncIntegerMigrateController {
+    type HI = HostEncIntegerWithContext;
+    type EI = EnclaveEncIntegerWithContext;
+    type EO = EnclaveEncInteger;
+    type HO = HostEncInteger;
+    const EI_MAX_SIZE: usize = 512;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(EnclaveEncIntegerWithContext::from(host_input))
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(HostEncInteger::from(enclave_output))
+    }
+}
@@ -0,0 +1,28 @@
+//! Workflow def.
+
+use super::host_types::{HostClientCiphertext, HostEncIntegerForClient};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveClientCiphertext, EnclaveEncIntegerForClient,
+};
+
+/// Re-encrypts an `ENCINTEGER` value to a client's key, which must be allowed to receive the values,
+/// instead of decrypting it in host.
+#[derive(Debug)]
+pub struct EncIntegerToClientController;
+
+impl EcallController for EncIntegerToClientController {
+    type HI = HostEncIntegerForClient;
+    type EI = EnclaveEncIntegerForClient;
+    type EO = EnclaveClientCiphertext;
+    type HO = HostClientCiphertext;
+    const EI_MAX_SIZE: usize = 4096;
+
+    fn translate_input(host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(EnclaveEncIntegerForClient::from(host_input))
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(HostClientCiphertext::from(enclave_output))
+    }
+}
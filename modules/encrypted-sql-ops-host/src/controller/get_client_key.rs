@@ -0,0 +1,27 @@
+//! Workflow def.
+
+use super::host_types::{HostClientKey, HostGetClientKey};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{EnclaveClientKey, EnclaveGetClientKey};
+
+/// Publishes the public key which clients encrypt values to,
+/// with the attestation evidence which clients verify before trusting it.
+#[derive(Debug)]
+pub struct GetClientKeyController;
+
+impl EcallController for GetClientKeyController {
+    type HI = HostGetClientKey;
+    type EI = EnclaveGetClientKey;
+    type EO = EnclaveClientKey;
+    type HO = HostClientKey;
+    // The DCAP evidence includes the collateral, which is tens of kilobytes.
+    const EI_MAX_SIZE: usize = 65536;
+
+    fn translate_input(_host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(EnclaveGetClientKey)
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(HostClientKey::from(enclave_output))
+    }
+}
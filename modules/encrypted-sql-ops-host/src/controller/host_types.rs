@@ -1,16 +1,38 @@
 //! Input/Output types from/to host.
 //! Basically all of them should be encrypted.
 
-mod host_enc_avg_state_with_next;
+mod host_aggregate_result;
+mod host_client_ciphertext;
+mod host_client_key;
+mod host_enc_aggregate_state;
+mod host_enc_aggregate_state_with_batch;
 mod host_enc_integer;
-mod host_input_enc_avg_state;
-mod host_output_enc_avg_state;
+mod host_enc_integer_for_client;
+mod host_enc_integer_with_context;
+mod host_enc_pair;
+mod host_enc_tag;
+mod host_enc_value;
+mod host_master_key;
+mod host_ordering;
+mod host_plain_bool;
 mod host_plain_integer;
-mod host_plain_real;
+mod host_plain_integer_with_context;
+mod host_plain_with_context;
 
-pub use host_enc_avg_state_with_next::HostEncAvgStateWithNext;
+pub use host_aggregate_result::HostAggregateResult;
+pub use host_client_ciphertext::HostClientCiphertext;
+pub use host_client_key::{HostClientKey, HostGetClientKey};
+pub use host_enc_aggregate_state::HostEncAggregateState;
+pub use host_enc_aggregate_state_with_batch::HostEncAggregateStateWithBatch;
 pub use host_enc_integer::HostEncInteger;
-pub use host_input_enc_avg_state::HostInputEncAvgState;
-pub use host_output_enc_avg_state::HostOutputEncAvgState;
+pub use host_enc_integer_for_client::HostEncIntegerForClient;
+pub use host_enc_integer_with_context::HostEncIntegerWithContext;
+pub use host_enc_pair::HostEncPair;
+pub use host_enc_tag::HostEncTag;
+pub use host_enc_value::HostEncValue;
+pub use host_master_key::{HostLoadMasterKey, HostMasterKeySource};
+pub use host_ordering::HostOrdering;
+pub use host_plain_bool::HostPlainBool;
 pub use host_plain_integer::HostPlainInteger;
-pub use host_plain_real::HostPlainReal;
+pub use host_plain_integer_with_context::HostPlainIntegerWithContext;
+pub use host_plain_with_context::HostPlainWithContext;
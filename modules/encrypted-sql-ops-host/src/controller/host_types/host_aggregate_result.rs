@@ -0,0 +1,39 @@
+//! Output to host.
+
+use frame_host::ecall_controller::HostOutput;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::{EncBigInt, EncInteger, EncReal},
+    enclave_types::EnclaveAggregateResult,
+};
+
+/// Result of an aggregate, either encrypted or in plain text as the enclave declares for the aggregate.
+#[derive(Clone, PartialEq, Debug)]
+pub enum HostAggregateResult {
+    /// Encrypted INTEGER
+    EncInteger(EncInteger),
+    /// Encrypted BIGINT
+    EncBigInt(EncBigInt),
+    /// Encrypted REAL
+    EncReal(EncReal),
+    /// Plain-text BIGINT
+    PlainBigInt(i64),
+    /// Plain-text REAL
+    PlainReal(f32),
+    /// SQL NULL
+    Null,
+}
+
+impl HostOutput for HostAggregateResult {}
+
+impl From<EnclaveAggregateResult> for HostAggregateResult {
+    fn from(e: EnclaveAggregateResult) -> Self {
+        match e {
+            EnclaveAggregateResult::EncInteger(encinteger) => Self::EncInteger(encinteger),
+            EnclaveAggregateResult::EncBigInt(encbigint) => Self::EncBigInt(encbigint),
+            EnclaveAggregateResult::EncReal(encreal) => Self::EncReal(encreal),
+            EnclaveAggregateResult::PlainBigInt(i) => Self::PlainBigInt(i),
+            EnclaveAggregateResult::PlainReal(f) => Self::PlainReal(f),
+            EnclaveAggregateResult::Null => Self::Null,
+        }
+    }
+}
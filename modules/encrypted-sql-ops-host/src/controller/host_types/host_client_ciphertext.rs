@@ -0,0 +1,38 @@
+//! Input from / output to host.
+
+use frame_host::ecall_controller::{HostInput, HostOutput};
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveClientCiphertext;
+
+/// Value encrypted by a client to the client key, or by enclave to a client's key.
+/// Its plain text never appears in host.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostClientCiphertext(Vec<u8>);
+
+impl HostInput for HostClientCiphertext {}
+
+impl HostOutput for HostClientCiphertext {}
+
+impl From<Vec<u8>> for HostClientCiphertext {
+    fn from(ciphertext: Vec<u8>) -> Self {
+        Self(ciphertext)
+    }
+}
+
+impl From<EnclaveClientCiphertext> for HostClientCiphertext {
+    fn from(e: EnclaveClientCiphertext) -> Self {
+        Self(e.into_inner())
+    }
+}
+
+impl From<HostClientCiphertext> for EnclaveClientCiphertext {
+    fn from(h: HostClientCiphertext) -> Self {
+        Self::from(h.0)
+    }
+}
+
+impl HostClientCiphertext {
+    /// Get raw representation
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
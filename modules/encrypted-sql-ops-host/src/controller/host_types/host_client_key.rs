@@ -0,0 +1,41 @@
+//! Input from / output to host to publish the client key.
+
+use frame_host::ecall_controller::{HostInput, HostOutput};
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveClientKey;
+
+/// Request to publish the client key.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct HostGetClientKey;
+
+impl HostInput for HostGetClientKey {}
+
+/// The public key which clients encrypt values to, with its attestation evidence.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct HostClientKey {
+    public_key: Vec<u8>,
+    evidence: Vec<u8>,
+}
+
+impl HostOutput for HostClientKey {}
+
+impl From<EnclaveClientKey> for HostClientKey {
+    fn from(e: EnclaveClientKey) -> Self {
+        let (public_key, evidence) = e.into_inner();
+        Self {
+            public_key,
+            evidence,
+        }
+    }
+}
+
+impl HostClientKey {
+    /// X25519 public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// JSON-serialized attestation evidence
+    pub fn evidence(&self) -> &[u8] {
+        &self.evidence
+    }
+}
@@ -0,0 +1,37 @@
+//! Input from / Output to host.
+
+use frame_host::ecall_controller::{HostInput, HostOutput};
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::enc_aggregate_state::EncAggregateState, enclave_types::EnclaveEncAggregateState,
+};
+
+/// Encrypted state of SUM, COUNT, MIN or MAX.
+#[derive(Clone, Debug)]
+pub struct HostEncAggregateState(EncAggregateState);
+
+impl HostInput for HostEncAggregateState {}
+impl HostOutput for HostEncAggregateState {}
+
+impl From<HostEncAggregateState> for EncAggregateState {
+    fn from(h: HostEncAggregateState) -> Self {
+        h.0
+    }
+}
+
+impl From<EncAggregateState> for HostEncAggregateState {
+    fn from(e: EncAggregateState) -> Self {
+        Self(e)
+    }
+}
+
+impl From<HostEncAggregateState> for EnclaveEncAggregateState {
+    fn from(h: HostEncAggregateState) -> Self {
+        Self::from(h.0)
+    }
+}
+
+impl From<EnclaveEncAggregateState> for HostEncAggregateState {
+    fn from(e: EnclaveEncAggregateState) -> Self {
+        Self::from(e.into_enc_aggregate_state())
+    }
+}
@@ -0,0 +1,29 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::{enc_aggregate_state::EncAggregateState, EncInteger},
+    enclave_types::EnclaveEncAggregateStateWithBatch,
+};
+
+/// Encrypted state of an aggregate with the next values.
+#[derive(Clone, Debug)]
+pub struct HostEncAggregateStateWithBatch<E = EncInteger> {
+    state: EncAggregateState,
+    batch: Vec<E>,
+}
+
+impl<E> HostInput for HostEncAggregateStateWithBatch<E> {}
+
+impl<E> HostEncAggregateStateWithBatch<E> {
+    /// Constructor
+    pub fn new(state: EncAggregateState, batch: Vec<E>) -> Self {
+        Self { state, batch }
+    }
+}
+
+impl<E> From<HostEncAggregateStateWithBatch<E>> for EnclaveEncAggregateStateWithBatch<E> {
+    fn from(h: HostEncAggregateStateWithBatch<E>) -> Self {
+        Self::new(h.state, h.batch)
+    }
+}
@@ -0,0 +1,28 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncInteger, enclave_types::EnclaveEncIntegerForClient,
+};
+
+/// Encrypted INTEGER with the public key of the client to re-encrypt it to.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostEncIntegerForClient {
+    value: EncInteger,
+    client_key: Vec<u8>,
+}
+
+impl HostInput for HostEncIntegerForClient {}
+
+impl HostEncIntegerForClient {
+    /// Constructor
+    pub fn new(value: EncInteger, client_key: Vec<u8>) -> Self {
+        Self { value, client_key }
+    }
+}
+
+impl From<HostEncIntegerForClient> for EnclaveEncIntegerForClient {
+    fn from(h: HostEncIntegerForClient) -> Self {
+        Self::new(h.value, h.client_key)
+    }
+}
@@ -0,0 +1,28 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::{
+    enc_type::EncInteger, enclave_types::EnclaveEncIntegerWithContext,
+};
+
+/// Encrypted INTEGER with the context to re-encrypt it in.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostEncIntegerWithContext {
+    value: EncInteger,
+    context: String,
+}
+
+impl HostInput for HostEncIntegerWithContext {}
+
+impl HostEncIntegerWithContext {
+    /// Constructor
+    pub fn new(value: EncInteger, context: String) -> Self {
+        Self { value, context }
+    }
+}
+
+impl From<HostEncIntegerWithContext> for EnclaveEncIntegerWithContext {
+    fn from(h: HostEncIntegerWithContext) -> Self {
+        Self::new(h.value, h.context)
+    }
+}
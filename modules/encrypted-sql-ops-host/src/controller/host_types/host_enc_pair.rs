@@ -0,0 +1,26 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveEncPair;
+
+/// Pair of encrypted values of the same type to compare, or encrypted states of the same aggregate to combine.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostEncPair<E> {
+    lhs: E,
+    rhs: E,
+}
+
+impl<E> HostInput for HostEncPair<E> {}
+
+impl<E> HostEncPair<E> {
+    /// Constructor
+    pub fn new(lhs: E, rhs: E) -> Self {
+        Self { lhs, rhs }
+    }
+}
+
+impl<E> From<HostEncPair<E>> for EnclaveEncPair<E> {
+    fn from(h: HostEncPair<E>) -> Self {
+        Self::new(h.lhs, h.rhs)
+    }
+}
@@ -0,0 +1,22 @@
+//! Output to host.
+
+use frame_host::ecall_controller::HostOutput;
+use module_encrypted_sql_ops_ecall_types::{enc_type::EncTag, enclave_types::EnclaveEncTag};
+
+/// Deterministic tag of an encrypted value.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostEncTag(EncTag);
+
+impl HostOutput for HostEncTag {}
+
+impl From<HostEncTag> for EncTag {
+    fn from(h: HostEncTag) -> Self {
+        h.0
+    }
+}
+
+impl From<EnclaveEncTag> for HostEncTag {
+    fn from(e: EnclaveEncTag) -> Self {
+        Self(e.into_enctag())
+    }
+}
@@ -0,0 +1,39 @@
+//! Input from / Output to host.
+
+use frame_host::ecall_controller::{HostInput, HostOutput};
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveEncValue;
+
+/// Encrypted value of any encrypted type.
+///
+/// Output of the constructors but INTEGER's, which has [HostEncInteger](super::HostEncInteger),
+/// and input of the ones deriving from a single value, e.g. the tags.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostEncValue<E>(E);
+
+impl<E> HostInput for HostEncValue<E> {}
+
+impl<E> HostOutput for HostEncValue<E> {}
+
+impl<E> HostEncValue<E> {
+    /// Constructor
+    pub fn new(e: E) -> Self {
+        Self(e)
+    }
+
+    /// Get inner representation
+    pub fn into_inner(self) -> E {
+        self.0
+    }
+}
+
+impl<E> From<EnclaveEncValue<E>> for HostEncValue<E> {
+    fn from(e: EnclaveEncValue<E>) -> Self {
+        Self(e.into_inner())
+    }
+}
+
+impl<E> From<HostEncValue<E>> for EnclaveEncValue<E> {
+    fn from(h: HostEncValue<E>) -> Self {
+        Self::from(h.0)
+    }
+}
@@ -0,0 +1,29 @@
+//! Input from / output to host to load the master key.
+
+use frame_host::ecall_controller::{HostInput, HostOutput};
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveMasterKeySource;
+
+/// Request to load the master key inside enclave.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct HostLoadMasterKey;
+
+impl HostInput for HostLoadMasterKey {}
+
+/// Where the loaded master key came from. The key itself never appears in host.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct HostMasterKeySource(EnclaveMasterKeySource);
+
+impl HostOutput for HostMasterKeySource {}
+
+impl From<EnclaveMasterKeySource> for HostMasterKeySource {
+    fn from(e: EnclaveMasterKeySource) -> Self {
+        Self(e)
+    }
+}
+
+impl HostMasterKeySource {
+    /// Get inner representation
+    pub fn into_inner(self) -> EnclaveMasterKeySource {
+        self.0
+    }
+}
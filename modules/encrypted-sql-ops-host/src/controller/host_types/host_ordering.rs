@@ -0,0 +1,23 @@
+//! Output to host.
+
+use frame_host::ecall_controller::HostOutput;
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclaveOrdering;
+use std::cmp::Ordering;
+
+/// Plain-text order of two encrypted values.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostOrdering(Ordering);
+
+impl HostOutput for HostOrdering {}
+
+impl From<EnclaveOrdering> for HostOrdering {
+    fn from(e: EnclaveOrdering) -> Self {
+        Self(Ordering::from(e))
+    }
+}
+
+impl From<HostOrdering> for Ordering {
+    fn from(h: HostOrdering) -> Self {
+        h.0
+    }
+}
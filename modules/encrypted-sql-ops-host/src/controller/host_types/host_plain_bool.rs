@@ -0,0 +1,21 @@
+//! Output to host.
+
+use frame_host::ecall_controller::HostOutput;
+
+/// Plain-text representation in Rust of SQL BOOLEAN.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostPlainBool(bool);
+
+impl HostOutput for HostPlainBool {}
+
+impl From<bool> for HostPlainBool {
+    fn from(b: bool) -> Self {
+        Self(b)
+    }
+}
+
+impl From<HostPlainBool> for bool {
+    fn from(h: HostPlainBool) -> Self {
+        h.0
+    }
+}
@@ -0,0 +1,28 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclavePlainInteger, EnclavePlainIntegerWithContext,
+};
+
+/// Plain-text representation in Rust of SQL INTEGER with the context to encrypt it in.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HostPlainIntegerWithContext {
+    integer: i32,
+    context: String,
+}
+
+impl HostInput for HostPlainIntegerWithContext {}
+
+impl HostPlainIntegerWithContext {
+    /// Constructor
+    pub fn new(integer: i32, context: String) -> Self {
+        Self { integer, context }
+    }
+}
+
+impl From<HostPlainIntegerWithContext> for EnclavePlainIntegerWithContext {
+    fn from(h: HostPlainIntegerWithContext) -> Self {
+        Self::new(EnclavePlainInteger::from(h.integer), h.context)
+    }
+}
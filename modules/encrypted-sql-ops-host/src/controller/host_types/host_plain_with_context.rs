@@ -0,0 +1,26 @@
+//! Input from host.
+
+use frame_host::ecall_controller::HostInput;
+use module_encrypted_sql_ops_ecall_types::enclave_types::EnclavePlainWithContext;
+
+/// Plain-text representation in Rust of SQL BIGINT, REAL or TEXT with the context to encrypt it in.
+#[derive(Clone, PartialEq, Debug)]
+pub struct HostPlainWithContext<T> {
+    value: T,
+    context: String,
+}
+
+impl<T> HostInput for HostPlainWithContext<T> {}
+
+impl<T> HostPlainWithContext<T> {
+    /// Constructor
+    pub fn new(value: T, context: String) -> Self {
+        Self { value, context }
+    }
+}
+
+impl<T> From<HostPlainWithContext<T>> for EnclavePlainWithContext<T> {
+    fn from(h: HostPlainWithContext<T>) -> Self {
+        Self::new(h.value, h.context)
+    }
+}
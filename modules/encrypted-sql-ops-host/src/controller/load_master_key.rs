@@ -0,0 +1,30 @@
+//! Workflow def.
+
+use super::host_types::{HostLoadMasterKey, HostMasterKeySource};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::enclave_types::{
+    EnclaveLoadMasterKey, EnclaveMasterKeySource,
+};
+
+/// Loads the master key of the encrypted types inside enclave on extension library load.
+///
+/// The key is unsealed from the local storage if it has been sealed,
+/// otherwise provisioned by the key-vault nodes if they are configured, or newly generated.
+#[derive(Debug)]
+pub struct LoadMasterKeyController;
+
+impl EcallController for LoadMasterKeyController {
+    type HI = HostLoadMasterKey;
+    type EI = EnclaveLoadMasterKey;
+    type EO = EnclaveMasterKeySource;
+    type HO = HostMasterKeySource;
+    const EI_MAX_SIZE: usize = 64;
+
+    fn translate_input(_host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(EnclaveLoadMasterKey)
+    }
+
+    fn translate_output(enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(HostMasterKeySource::from(enclave_output))
+    }
+}
@@ -15,6 +15,12 @@ pub mod input {
     pub struct CallServerStopper;
 
     impl EnclaveInput for CallServerStopper {}
+
+    #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+    #[serde(crate = "crate::serde")]
+    pub struct CallMeasurementPolicyReloader;
+
+    impl EnclaveInput for CallMeasurementPolicyReloader {}
 }
 
 pub mod output {
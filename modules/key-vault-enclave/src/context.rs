@@ -1,19 +1,37 @@
-use frame_config::{ANONIFY_PARAMS_DIR, CMD_DEC_SECRET_DIR, IAS_ROOT_CERT};
+use anyhow::anyhow;
+use frame_config::{
+    ANONIFY_ENCLAVE_MEASUREMENT, ANONIFY_PARAMS_DIR, CMD_DEC_SECRET_DIR, IAS_ROOT_CERT, PJ_ROOT_DIR,
+};
+use frame_mra_tls::{MeasurementPolicy, RenewalHandle, SharedMeasurementPolicy, ShutdownHandle};
 use frame_runtime::traits::*;
 use frame_sodium::StoreEnclaveDecryptionKey;
 use frame_treekem::StorePathSecrets;
-use std::{env, string::String, vec::Vec};
+use std::{convert::TryInto, env, string::String, sync::SgxMutex, vec::Vec};
+use tracing::info;
+
+/// The hex encoded uncompressed P-256 public key of the policy signer, set by `MEASUREMENT_POLICY_PUBLIC_KEY` when building the enclave.
+/// It's compiled into the enclave and so measured in MRENCLAVE, since the host's environment can't be trusted to decide which enclaves are accepted.
+pub const MEASUREMENT_POLICY_PUBLIC_KEY: Option<&str> =
+    option_env!("MEASUREMENT_POLICY_PUBLIC_KEY");
+/// The version of the measurement policy applied last time is sealed in ANONIFY_PARAMS_DIR/${MEASUREMENT_POLICY_VERSION_FILE_NAME}
+const MEASUREMENT_POLICY_VERSION_FILE_NAME: &str = "kv_measurement_policy_version";
 
 #[derive(Debug)]
 pub struct KeyVaultEnclaveContext {
     version: usize,
     ias_url: String,
     sub_key: String,
-    key_vault_endpoint: String,
+    key_vault_endpoints: Vec<String>,
     spid: String,
     store_path_secrets: StorePathSecrets,
     store_enclave_dec_key: StoreEnclaveDecryptionKey,
     ias_root_cert: Vec<u8>,
+    /// The handles of the running servers, which are shut down by `ServerStopper`.
+    shutdown_handles: SgxMutex<Vec<ShutdownHandle>>,
+    /// The handles of the background renewals of the attested certificates, which are stopped with the servers.
+    renewal_handles: SgxMutex<Vec<RenewalHandle>>,
+    /// The policy deciding which state runtime enclaves are accepted as clients.
+    state_runtime_policy: SharedMeasurementPolicy,
 }
 
 impl ConfigGetter for KeyVaultEnclaveContext {
@@ -33,8 +51,8 @@ impl ConfigGetter for KeyVaultEnclaveContext {
         &self.sub_key
     }
 
-    fn key_vault_endpoint(&self) -> &str {
-        &self.key_vault_endpoint
+    fn key_vault_endpoints(&self) -> &[String] {
+        &self.key_vault_endpoints
     }
 
     fn spid(&self) -> &str {
@@ -61,20 +79,123 @@ impl KeyVaultEnclaveContext {
         let ias_url = env::var("IAS_URL").expect("IAS_URL is not set");
         let sub_key = env::var("SUB_KEY").expect("SUB_KEY is not set");
         assert!(!sub_key.is_empty(), "SUB_KEY shouldn't be empty");
-        let key_vault_endpoint = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
-            .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set");
+        let key_vault_endpoints = env::var("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME")
+            .expect("KEY_VAULT_ENDPOINT_FOR_STATE_RUNTIME is not set")
+            .split(',')
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
         let store_path_secrets = StorePathSecrets::new(&*CMD_DEC_SECRET_DIR);
         let store_enclave_dec_key = StoreEnclaveDecryptionKey::new(&*ANONIFY_PARAMS_DIR);
+        let state_runtime_policy = load_state_runtime_policy(&store_enclave_dec_key)
+            .expect("Failed to load the measurement policy of the state runtime enclaves")
+            .unwrap_or_else(|| MeasurementPolicy::from_measurement(*ANONIFY_ENCLAVE_MEASUREMENT));
 
         Self {
             version,
             ias_url,
             sub_key,
-            key_vault_endpoint,
+            key_vault_endpoints,
             spid,
             store_path_secrets,
             store_enclave_dec_key,
             ias_root_cert: (&*IAS_ROOT_CERT).to_vec(),
+            shutdown_handles: SgxMutex::new(vec![]),
+            renewal_handles: SgxMutex::new(vec![]),
+            state_runtime_policy: SharedMeasurementPolicy::new(state_runtime_policy),
+        }
+    }
+
+    pub fn state_runtime_policy(&self) -> &SharedMeasurementPolicy {
+        &self.state_runtime_policy
+    }
+
+    /// Reload the signed policy file, so that a new state runtime enclave build can be accepted
+    /// without restarting the servers. Only a policy with a greater version is applied.
+    pub fn reload_state_runtime_policy(&self) -> anyhow::Result<()> {
+        let policy = load_state_runtime_policy(&self.store_enclave_dec_key)?
+            .ok_or_else(|| anyhow!("STATE_RUNTIME_MEASUREMENT_POLICY_PATH is not set"))?;
+        let version = policy.version();
+        self.state_runtime_policy.update(policy)?;
+        info!("The measurement policy version {} is applied", version);
+
+        Ok(())
+    }
+
+    pub fn register_shutdown_handle(&self, shutdown_handle: ShutdownHandle) {
+        self.shutdown_handles.lock().unwrap().push(shutdown_handle);
+    }
+
+    pub fn register_renewal_handle(&self, renewal_handle: RenewalHandle) {
+        self.renewal_handles.lock().unwrap().push(renewal_handle);
+    }
+
+    /// Stop all running servers from accepting new connections.
+    /// Each server returns after serving its in-flight requests.
+    pub fn shutdown_servers(&self) {
+        for shutdown_handle in self.shutdown_handles.lock().unwrap().drain(..) {
+            shutdown_handle.shutdown();
+        }
+        for renewal_handle in self.renewal_handles.lock().unwrap().drain(..) {
+            renewal_handle.stop();
         }
     }
 }
+
+/// Load the signed measurement policy of the state runtime enclaves from `STATE_RUNTIME_MEASUREMENT_POLICY_PATH`, relative to `PJ_ROOT_DIR`,
+/// verifying it by the policy signer's public key compiled into the enclave.
+/// Its version must be at least the one applied last time, which is sealed so that the host can't roll it back by restarting.
+/// Returns `None` if no policy file has ever been configured.
+fn load_state_runtime_policy(
+    store: &StoreEnclaveDecryptionKey,
+) -> anyhow::Result<Option<MeasurementPolicy>> {
+    let min_version = if store
+        .get_all_file_names()?
+        .iter()
+        .any(|file_name| file_name == MEASUREMENT_POLICY_VERSION_FILE_NAME)
+    {
+        let sealed =
+            store.load_secret_from_local_filesystem(MEASUREMENT_POLICY_VERSION_FILE_NAME)?;
+        let sealed: [u8; 8] = sealed[..]
+            .try_into()
+            .map_err(|_| anyhow!("The sealed measurement policy version is malformed"))?;
+        Some(u64::from_le_bytes(sealed))
+    } else {
+        None
+    };
+
+    let policy_path = match env::var("STATE_RUNTIME_MEASUREMENT_POLICY_PATH") {
+        Ok(policy_path) if !policy_path.is_empty() => policy_path,
+        _ => {
+            return match min_version {
+                None => Ok(None),
+                Some(_) => Err(anyhow!(
+                    "STATE_RUNTIME_MEASUREMENT_POLICY_PATH is not set while a measurement policy was applied before"
+                )),
+            }
+        }
+    };
+    let public_key = MEASUREMENT_POLICY_PUBLIC_KEY
+        .ok_or_else(|| anyhow!("MEASUREMENT_POLICY_PUBLIC_KEY is not compiled into the enclave"))?;
+    let public_key = hex::decode(public_key.trim())
+        .map_err(|e| anyhow!("Failed to decode MEASUREMENT_POLICY_PUBLIC_KEY: {:?}", e))?;
+    let mut file_path = PJ_ROOT_DIR.clone();
+    file_path.push(policy_path);
+
+    let policy = MeasurementPolicy::load_signed(file_path, &public_key)?;
+    if let Some(min_version) = min_version {
+        if policy.version() < min_version {
+            return Err(anyhow!(
+                "The measurement policy version {} must be at least the applied version {}",
+                policy.version(),
+                min_version
+            ));
+        }
+    }
+    store.save_secret_to_local_filesystem(
+        &policy.version().to_le_bytes(),
+        MEASUREMENT_POLICY_VERSION_FILE_NAME,
+    )?;
+
+    Ok(Some(policy))
+}
@@ -1,51 +1,99 @@
-use anyhow::anyhow;
-use frame_common::crypto::ExportPathSecret;
+use crate::replication::Replicator;
+use anyhow::{anyhow, ensure};
+use frame_common::{crypto::ExportPathSecret, traits::Keccak256};
 use frame_mra_tls::{
     key_vault::{
+        error::KeyVaultError,
         request::{
             BackupEnclaveDecryptionKeyRequestBody, BackupPathSecretRequestBody,
-            BackupPathSecretsRequestBody, RecoverPathSecretRequestBody,
-            RecoverPathSecretsRequestBody,
+            BackupPathSecretsRequestBody, KeyVaultCmd, KeyVaultCommand,
+            ListEnclaveDecryptionKeysRequestBody, ProvisionSharedKeyRequestBody,
+            RawKeyVaultRequest, RecoverEnclaveDecryptionKeyRequestBody,
+            RecoverPathSecretRequestBody, RecoverPathSecretsRequestBody, RecoverWelcomeRequestBody,
+            ReplicateRequestBody, StoreWelcomeRequestBody,
+        },
+        response::{
+            BackedUpEnclaveDecryptionKey, Capabilities, KeyVaultResponse, ProvisionedSharedKey,
+            RecoveredPathSecret, RecoveredWelcome,
         },
-        response::RecoveredPathSecret,
     },
-    RequestHandler,
+    PeerIdentity, RequestHandler,
 };
-use frame_sodium::{SealedEnclaveDecryptionKey, StoreEnclaveDecryptionKey};
+use frame_sodium::{SealedEnclaveDecryptionKey, SodiumPrivateKey, StoreEnclaveDecryptionKey};
 use frame_treekem::{PathSecret, StorePathSecrets};
+use lazy_static::lazy_static;
 use serde_json::Value;
-use std::{string::ToString, vec::Vec};
+use std::{
+    fs,
+    path::Path,
+    string::{String, ToString},
+    sync::SgxMutex,
+    vec::Vec,
+};
+use tracing::warn;
+
+/// The enclave decryption keys are stored in the following location,
+/// where ${enclave} is `${mr_signer}_${isv_prod_id}` of the requesting enclave.
+/// - ANONIFY_PARAMS_DIR/kv_enclave_decryption_keys/${enclave}/${roster_idx}/${version}_${fingerprint}
+const DEC_KEY_DIR_NAME: &str = "kv_enclave_decryption_keys";
+/// The sealed binding of the roster index to the node which stored its first backup.
+/// - ANONIFY_PARAMS_DIR/kv_enclave_decryption_keys/${enclave}/${roster_idx}/binding
+const DEC_KEY_BINDING_FILE_NAME: &str = "binding";
+/// The sealed MRENCLAVE which the binding migrated from the MRENCLAVE namespace was made with.
+/// - ANONIFY_PARAMS_DIR/kv_enclave_decryption_keys/${enclave}/${roster_idx}/legacy-binding
+const LEGACY_BINDING_FILE_NAME: &str = "legacy-binding";
+/// The single backup file before the backups were namespaced, which is migrated on the first access.
+/// - ANONIFY_PARAMS_DIR/kv_enclave_decryption_key
+const LEGACY_DEC_KEY_FILE_NAME: &str = "kv_enclave_decryption_key";
+/// The shared keys are stored in the following location.
+/// - ANONIFY_PARAMS_DIR/kv_shared_keys/${enclave}/${name}
+const SHARED_KEY_DIR_NAME: &str = "kv_shared_keys";
+/// The product ID of the enclaves built without being assigned their own one in config/enclave_prod_ids.
+const UNASSIGNED_ISV_PROD_ID: u16 = 0;
 
-const DEC_KEY_FILE_NAME: &str = "kv_enclave_decryption_key";
+lazy_static! {
+    /// Serializes the provisions of the shared keys across the handlers,
+    /// so that no different keys are stored under the same name.
+    static ref SHARED_KEY_LOCK: SgxMutex<()> = SgxMutex::new(());
+    /// Serializes the accesses to the enclave decryption key backups,
+    /// so that the versions and the bindings are consistent across the handlers.
+    static ref DEC_KEY_LOCK: SgxMutex<()> = SgxMutex::new(());
+    /// Serializes the migrations of the path secrets stored before the namespacing,
+    /// so that they are moved to a single namespace.
+    static ref PATH_SECRET_LOCK: SgxMutex<()> = SgxMutex::new(());
+}
 
 #[derive(Default, Clone)]
 pub struct KeyVaultHandler {
     store_path_secrets: StorePathSecrets,
     store_enclave_dec_key: StoreEnclaveDecryptionKey,
+    replicator: Option<Replicator>,
+    is_replica: bool,
 }
 
 impl RequestHandler for KeyVaultHandler {
-    fn handle_json(&self, msg: &[u8]) -> anyhow::Result<Vec<u8>> {
-        let decoded: Value = serde_json::from_slice(&msg)?;
-        let cmd = decoded["cmd"]
-            .as_str()
-            .ok_or_else(|| anyhow!("msg doesn't contain cmd"))?;
-
-        match cmd {
-            "StorePathSecret" => self.store_path_secret(decoded["body"].clone()),
-            "RecoverPathSecret" => self.recover_path_secret(decoded["body"].clone()),
-            "ManuallyStorePathSecrets" => self.manually_store_path_secrets(decoded["body"].clone()),
-            "ManuallyRecoverPathSecrets" => {
-                self.manually_recover_path_secrets(decoded["body"].clone())
-            }
-            "StoreEnclaveDecryptionKey" => {
-                self.store_enclave_decryption_key(decoded["body"].clone())
-            }
-            "RecoverEnclaveDecryptionKey" => {
-                self.recover_enclave_decryption_key(decoded["body"].clone())
+    fn handle_json(&self, msg: &[u8], peer: Option<&PeerIdentity>) -> anyhow::Result<Vec<u8>> {
+        let request = RawKeyVaultRequest::decode(msg)?;
+        let version = request.version();
+        let result = request.into_command().and_then(|command| {
+            // The server reads the frames up to the largest limit of the commands,
+            // so the request is checked against its own one.
+            let max_frame_len = command.cmd().max_frame_len();
+            if msg.len() as u64 > max_frame_len {
+                return Err(KeyVaultError::invalid_request(format!(
+                    "The request of {:?} exceeds the max frame length: {} > {}",
+                    command.cmd(),
+                    msg.len(),
+                    max_frame_len
+                )));
             }
-            _ => unreachable!("got unknown command: {:?}", cmd),
+            self.handle_command(command, peer)
+        });
+        if let Err(e) = &result {
+            warn!("Failed to handle the key-vault request: {}", e);
         }
+
+        KeyVaultResponse::encode(version, result)
     }
 }
 
@@ -57,85 +105,398 @@ impl KeyVaultHandler {
         Self {
             store_path_secrets,
             store_enclave_dec_key,
+            replicator: None,
+            is_replica: false,
+        }
+    }
+
+    /// A handler for the replication endpoint, which only stores the requests forwarded by the peers.
+    pub fn new_replica(
+        store_path_secrets: StorePathSecrets,
+        store_enclave_dec_key: StoreEnclaveDecryptionKey,
+    ) -> Self {
+        Self {
+            store_path_secrets,
+            store_enclave_dec_key,
+            replicator: None,
+            is_replica: true,
+        }
+    }
+
+    pub fn with_replicator(mut self, replicator: Option<Replicator>) -> Self {
+        self.replicator = replicator;
+        self
+    }
+
+    /// The commands which this handler accepts, advertised to the clients.
+    fn capabilities(&self) -> Capabilities {
+        let commands = if self.is_replica {
+            vec![KeyVaultCmd::Replicate, KeyVaultCmd::GetCapabilities]
+        } else {
+            vec![
+                KeyVaultCmd::StorePathSecret,
+                KeyVaultCmd::RecoverPathSecret,
+                KeyVaultCmd::ManuallyStorePathSecrets,
+                KeyVaultCmd::ManuallyRecoverPathSecrets,
+                KeyVaultCmd::StoreEnclaveDecryptionKey,
+                KeyVaultCmd::RecoverEnclaveDecryptionKey,
+                KeyVaultCmd::ListEnclaveDecryptionKeys,
+                KeyVaultCmd::StoreWelcome,
+                KeyVaultCmd::RecoverWelcome,
+                KeyVaultCmd::ProvisionSharedKey,
+                KeyVaultCmd::GetCapabilities,
+            ]
+        };
+        Capabilities::new(commands)
+    }
+
+    fn handle_command(
+        &self,
+        command: KeyVaultCommand,
+        peer: Option<&PeerIdentity>,
+    ) -> Result<Value, KeyVaultError> {
+        let cmd = command.cmd();
+        if !self.capabilities().supports(cmd) {
+            return Err(KeyVaultError::unauthorized(format!(
+                "{:?} is not accepted on this endpoint",
+                cmd
+            )));
+        }
+
+        match command {
+            KeyVaultCommand::GetCapabilities(_) => {
+                serde_json::to_value(self.capabilities()).map_err(KeyVaultError::internal)
+            }
+            KeyVaultCommand::Replicate(body) => self.replicate(body),
+            command => {
+                // A write is stored locally only after the quorum of the peers acknowledged it,
+                // and a shared key is answered only if the quorum stores the same one.
+                match &self.replicator {
+                    Some(replicator) if matches!(cmd, KeyVaultCmd::ProvisionSharedKey) => {
+                        let forwarded =
+                            serde_json::to_value(&command).map_err(KeyVaultError::internal)?;
+                        replicator
+                            .replicate_agreed(&forwarded, peer, || self.dispatch(command, peer))
+                    }
+                    Some(replicator) if cmd.is_write() => {
+                        let forwarded =
+                            serde_json::to_value(&command).map_err(KeyVaultError::internal)?;
+                        replicator.replicate(&forwarded, peer, || self.dispatch(command, peer))
+                    }
+                    _ => self.dispatch(command, peer),
+                }
+            }
         }
     }
 
-    fn store_enclave_decryption_key(&self, body: Value) -> anyhow::Result<Vec<u8>> {
-        let enclave_dec_key: BackupEnclaveDecryptionKeyRequestBody = serde_json::from_value(body)?;
+    fn dispatch(
+        &self,
+        command: KeyVaultCommand,
+        peer: Option<&PeerIdentity>,
+    ) -> Result<Value, KeyVaultError> {
+        match command {
+            KeyVaultCommand::StorePathSecret(body) => self.store_path_secret(body, peer),
+            KeyVaultCommand::RecoverPathSecret(body) => self.recover_path_secret(body, peer),
+            KeyVaultCommand::ManuallyStorePathSecrets(body) => {
+                self.manually_store_path_secrets(body, peer)
+            }
+            KeyVaultCommand::ManuallyRecoverPathSecrets(body) => {
+                self.manually_recover_path_secrets(body, peer)
+            }
+            KeyVaultCommand::StoreEnclaveDecryptionKey(body) => {
+                self.store_enclave_decryption_key(body, peer)
+            }
+            KeyVaultCommand::RecoverEnclaveDecryptionKey(body) => {
+                self.recover_enclave_decryption_key(body, peer)
+            }
+            KeyVaultCommand::ListEnclaveDecryptionKeys(body) => {
+                self.list_enclave_decryption_keys(body, peer)
+            }
+            KeyVaultCommand::StoreWelcome(body) => self.store_welcome(body, peer),
+            KeyVaultCommand::RecoverWelcome(body) => self.recover_welcome(body, peer),
+            KeyVaultCommand::ProvisionSharedKey(body) => self.provision_shared_key(body, peer),
+            command => {
+                return Err(KeyVaultError::unsupported_command(&format!(
+                    "{:?}",
+                    command.cmd()
+                )))
+            }
+        }
+        .map_err(KeyVaultError::internal)
+    }
+
+    /// Store the write request forwarded by a peer key-vault node on behalf of the origin enclave.
+    /// It is never forwarded again.
+    fn replicate(&self, replicate: ReplicateRequestBody) -> Result<Value, KeyVaultError> {
+        let command: KeyVaultCommand = serde_json::from_value(replicate.request().clone())
+            .map_err(KeyVaultError::invalid_request)?;
+        if !command.cmd().is_write() {
+            return Err(KeyVaultError::invalid_request(format!(
+                "Only write requests can be replicated, but got {:?}",
+                command.cmd()
+            )));
+        }
+
+        self.dispatch(command, replicate.origin())
+    }
+
+    /// Store the enclave decryption key as a new version,
+    /// unless it's the same as the latest one.
+    /// Only the node the roster index is bound to can add a new version.
+    /// A share of the secret sharing is stored at the version given by the client with the commitment to the whole key,
+    /// so that the client can check the shares recovered from the key-vault nodes are of the same key.
+    fn store_enclave_decryption_key(
+        &self,
+        enclave_dec_key: BackupEnclaveDecryptionKeyRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let roster_idx = enclave_dec_key.roster_idx();
+        let _guard = DEC_KEY_LOCK
+            .lock()
+            .map_err(|e| anyhow!("Failed to acquire the enclave decryption key lock: {:?}", e))?;
+        let store_dec_key = self.dec_key_dir(peer, roster_idx)?;
+        let fingerprint = dec_key_fingerprint(enclave_dec_key.dec_key());
+
+        let versions = backed_up_versions(&store_dec_key, roster_idx)?;
+        if let Some(latest) = versions.last() {
+            if latest.fingerprint() == fingerprint.as_slice() {
+                return serde_json::to_value(latest).map_err(Into::into);
+            }
+        }
+        bind_node(&store_dec_key, peer, roster_idx, enclave_dec_key.node_key())?;
+        let next_version = versions.last().map_or(1, |latest| latest.version() + 1);
+        let version = match enclave_dec_key.version() {
+            Some(version) => {
+                ensure!(
+                    version >= next_version,
+                    "The version {} of roster index {} is already backed up",
+                    version,
+                    roster_idx
+                );
+                version
+            }
+            None => next_version,
+        };
+        let backed_up = BackedUpEnclaveDecryptionKey::new(roster_idx, version, fingerprint)
+            .with_commitment(enclave_dec_key.commitment().to_vec());
 
         let encoded = enclave_dec_key.dec_key().try_into_sealing()?;
         let sealed = SealedEnclaveDecryptionKey::decode(&encoded)?;
+        store_dec_key.save_to_local_filesystem(&sealed, dec_key_file_name(&backed_up))?;
 
-        self.store_enclave_dec_key
+        serde_json::to_value(&backed_up).map_err(Into::into)
+    }
+
+    /// Store the proposed key unless a key is already stored under the name, and return the stored one.
+    /// The keys are namespaced by the requesting enclave's identity as the enclave decryption keys are.
+    /// In a cluster, the key is answered only if a majority of the nodes store the same one,
+    /// so the enclaves provisioned through different nodes never seal different keys.
+    fn provision_shared_key(
+        &self,
+        provision: ProvisionSharedKeyRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let name = provision.name();
+        ensure!(
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "Invalid shared key name: {:?}",
+            name
+        );
+        let peer =
+            peer.ok_or_else(|| anyhow!("The shared keys are only available to attested clients"))?;
+        let _guard = SHARED_KEY_LOCK
+            .lock()
+            .map_err(|e| anyhow!("Failed to acquire the shared key lock: {:?}", e))?;
+        let store_shared_keys = self
+            .store_enclave_dec_key
             .clone()
-            .save_to_local_filesystem(&sealed, DEC_KEY_FILE_NAME)?;
+            .create_dir_all(SHARED_KEY_DIR_NAME)?;
+        migrate_mr_enclave_dir(store_shared_keys.local_dir_path(), peer)?;
+        let store_shared_key = store_shared_keys.create_dir_all(enclave_namespace(peer)?)?;
 
-        serde_json::to_vec(&sealed).map_err(Into::into)
+        let is_stored = store_shared_key
+            .get_all_file_names()?
+            .iter()
+            .any(|file_name| file_name == name);
+        let key = if is_stored {
+            store_shared_key.load_secret_from_local_filesystem(name)?
+        } else {
+            store_shared_key.save_secret_to_local_filesystem(provision.proposed_key(), name)?;
+            provision.proposed_key().to_vec()
+        };
+
+        serde_json::to_value(&ProvisionedSharedKey::new(key)).map_err(Into::into)
     }
 
-    fn store_path_secret(&self, body: Value) -> anyhow::Result<Vec<u8>> {
-        let backup_path_secret: BackupPathSecretRequestBody = serde_json::from_value(body)?;
+    fn store_path_secret(
+        &self,
+        backup_path_secret: BackupPathSecretRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
         let eps = PathSecret::from(backup_path_secret.path_secret())
             .try_into_exporting(backup_path_secret.epoch(), backup_path_secret.id())?;
-        self.store_path_secrets
-            .clone()
-            .create_dir_all(backup_path_secret.roster_idx().to_string())?
+        self.path_secrets_dir(peer, backup_path_secret.roster_idx())?
             .save_to_local_filesystem(&eps)?;
 
-        serde_json::to_vec(&eps).map_err(Into::into)
+        serde_json::to_value(&eps).map_err(Into::into)
     }
 
-    fn recover_enclave_decryption_key(&self, _body: Value) -> anyhow::Result<Vec<u8>> {
-        let dec_key = self
+    /// Recover the specified version of the enclave decryption key, or the latest one if not specified.
+    fn recover_enclave_decryption_key(
+        &self,
+        recover_dec_key: RecoverEnclaveDecryptionKeyRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let roster_idx = recover_dec_key.roster_idx();
+        let _guard = DEC_KEY_LOCK
+            .lock()
+            .map_err(|e| anyhow!("Failed to acquire the enclave decryption key lock: {:?}", e))?;
+        let store_dec_key = self.dec_key_dir(peer, roster_idx)?;
+
+        let versions = backed_up_versions(&store_dec_key, roster_idx)?;
+        let backed_up = match recover_dec_key.version() {
+            Some(version) => versions.iter().find(|v| v.version() == version),
+            None => versions.last(),
+        }
+        .ok_or_else(|| {
+            anyhow!(
+                "Not found the enclave decryption key (roster_idx: {:?}, version: {:?})",
+                roster_idx,
+                recover_dec_key.version()
+            )
+        })?;
+        let dec_key = store_dec_key
+            .load_from_local_filesystem(dec_key_file_name(backed_up))?
+            .into_sodium_priv_key()?;
+
+        serde_json::to_value(&dec_key).map_err(Into::into)
+    }
+
+    fn list_enclave_decryption_keys(
+        &self,
+        list_dec_keys: ListEnclaveDecryptionKeysRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let roster_idx = list_dec_keys.roster_idx();
+        let _guard = DEC_KEY_LOCK
+            .lock()
+            .map_err(|e| anyhow!("Failed to acquire the enclave decryption key lock: {:?}", e))?;
+        let store_dec_key = self.dec_key_dir(peer, roster_idx)?;
+        let versions = backed_up_versions(&store_dec_key, roster_idx)?;
+
+        serde_json::to_value(&versions).map_err(Into::into)
+    }
+
+    /// The directory of the enclave decryption key backups namespaced by the requesting enclave's identity,
+    /// so a client can only access the backups stored by an enclave with the same MRSIGNER and ISVPRODID,
+    /// including the upgraded builds of it.
+    /// The enclave decryption key is shared by the group, so any of them can read the backups of any roster index.
+    /// The backup stored before the namespacing is migrated to the first directory accessed without backups.
+    fn dec_key_dir(
+        &self,
+        peer: Option<&PeerIdentity>,
+        roster_idx: u32,
+    ) -> anyhow::Result<StoreEnclaveDecryptionKey> {
+        let peer = peer.ok_or_else(|| {
+            anyhow!("The enclave decryption key backups are only available to attested clients")
+        })?;
+
+        let store_namespaces = self
             .store_enclave_dec_key
             .clone()
-            .load_from_local_filesystem(DEC_KEY_FILE_NAME)?
+            .create_dir_all(DEC_KEY_DIR_NAME)?;
+        if migrate_mr_enclave_dir(store_namespaces.local_dir_path(), peer)? {
+            remember_legacy_bindings(
+                &store_namespaces
+                    .clone()
+                    .create_dir_all(enclave_namespace(peer)?)?,
+                peer,
+            )?;
+        }
+        let store_dec_key = store_namespaces
+            .create_dir_all(enclave_namespace(peer)?)?
+            .create_dir_all(roster_idx.to_string())?;
+        let has_legacy = self
+            .store_enclave_dec_key
+            .get_all_file_names()?
+            .iter()
+            .any(|file_name| file_name == LEGACY_DEC_KEY_FILE_NAME);
+        if has_legacy && backed_up_versions(&store_dec_key, roster_idx)?.is_empty() {
+            self.migrate_legacy_dec_key(&store_dec_key, roster_idx)?;
+        }
+
+        Ok(store_dec_key)
+    }
+
+    /// Move the backup stored before the namespacing into the directory as the first version.
+    fn migrate_legacy_dec_key(
+        &self,
+        store_dec_key: &StoreEnclaveDecryptionKey,
+        roster_idx: u32,
+    ) -> anyhow::Result<()> {
+        let dec_key = self
+            .store_enclave_dec_key
+            .load_from_local_filesystem(LEGACY_DEC_KEY_FILE_NAME)?
             .into_sodium_priv_key()?;
+        let backed_up =
+            BackedUpEnclaveDecryptionKey::new(roster_idx, 1, dec_key_fingerprint(&dec_key));
+        let encoded = dec_key.try_into_sealing()?;
+        let sealed = SealedEnclaveDecryptionKey::decode(&encoded)?;
+        store_dec_key.save_to_local_filesystem(&sealed, dec_key_file_name(&backed_up))?;
+        self.store_enclave_dec_key
+            .remove_from_local_filesystem(LEGACY_DEC_KEY_FILE_NAME)?;
+        warn!(
+            "Migrated the legacy enclave decryption key backup to roster index {}",
+            roster_idx
+        );
 
-        serde_json::to_vec(&dec_key).map_err(Into::into)
+        Ok(())
     }
 
-    fn recover_path_secret(&self, body: Value) -> anyhow::Result<Vec<u8>> {
-        let recover_path_secret: RecoverPathSecretRequestBody = serde_json::from_value(body)?;
+    fn recover_path_secret(
+        &self,
+        recover_path_secret: RecoverPathSecretRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
         let ps_id = recover_path_secret.id();
         let eps = self
-            .store_path_secrets
-            .clone()
-            .create_dir_all(recover_path_secret.roster_idx().to_string())?
+            .path_secrets_dir(peer, recover_path_secret.roster_idx())?
             .load_from_local_filesystem(ps_id)?;
         let path_secret = PathSecret::try_from_importing(eps.clone())?;
         let rps =
             RecoveredPathSecret::new(path_secret.as_bytes().to_vec(), eps.epoch(), ps_id.to_vec());
 
-        serde_json::to_vec(&rps).map_err(Into::into)
+        serde_json::to_value(&rps).map_err(Into::into)
     }
 
-    fn manually_store_path_secrets(&self, body: Value) -> anyhow::Result<Vec<u8>> {
+    fn manually_store_path_secrets(
+        &self,
+        backup_path_secrets: BackupPathSecretsRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
         let mut epss: Vec<ExportPathSecret> = vec![];
-        let backup_path_secrets: BackupPathSecretsRequestBody = serde_json::from_value(body)?;
 
         for backup_path_secret in backup_path_secrets.0 {
             let eps = PathSecret::from(backup_path_secret.path_secret())
                 .try_into_exporting(backup_path_secret.epoch(), backup_path_secret.id())?;
-            let store_path_secrets = self
-                .store_path_secrets
-                .clone()
-                .create_dir_all(backup_path_secret.roster_idx().to_string())?;
-            store_path_secrets.save_to_local_filesystem(&eps)?;
+            self.path_secrets_dir(peer, backup_path_secret.roster_idx())?
+                .save_to_local_filesystem(&eps)?;
             epss.push(eps);
         }
 
-        serde_json::to_vec(&epss).map_err(Into::into)
+        serde_json::to_value(&epss).map_err(Into::into)
     }
 
-    fn manually_recover_path_secrets(&self, body: Value) -> anyhow::Result<Vec<u8>> {
+    fn manually_recover_path_secrets(
+        &self,
+        recover_path_secret: RecoverPathSecretsRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
         let mut recovered_path_secrets: Vec<RecoveredPathSecret> = vec![];
-
-        let recover_path_secret: RecoverPathSecretsRequestBody = serde_json::from_value(body)?;
-        let store_path_secrets = self
-            .store_path_secrets
-            .clone()
-            .create_dir_all(recover_path_secret.roster_idx().to_string())?;
+        let store_path_secrets = self.path_secrets_dir(peer, recover_path_secret.roster_idx())?;
         let ps_ids = store_path_secrets.get_all_path_secret_ids()?;
 
         for ps_id in ps_ids {
@@ -145,6 +506,436 @@ impl KeyVaultHandler {
             recovered_path_secrets.push(rps);
         }
 
-        serde_json::to_vec(&recovered_path_secrets).map_err(Into::into)
+        serde_json::to_value(&recovered_path_secrets).map_err(Into::into)
+    }
+
+    /// Store the welcome message for the joiner of roster_idx.
+    /// A welcome message can't be replaced with the one of the same or an older epoch,
+    /// so a replayed request can't roll the joiner back to a stale group state.
+    fn store_welcome(
+        &self,
+        store_welcome: StoreWelcomeRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let welcome_dir = self.welcome_dir(peer, store_welcome.roster_idx())?;
+        if let Some(stored_epoch) = welcome_dir.load_welcome_epoch()? {
+            ensure!(
+                store_welcome.epoch() > stored_epoch,
+                "The welcome message for roster index {} at epoch {} is not newer than the stored one at epoch {}",
+                store_welcome.roster_idx(),
+                store_welcome.epoch(),
+                stored_epoch
+            );
+        }
+        welcome_dir.save_welcome_to_local_filesystem(
+            store_welcome.epoch(),
+            store_welcome.encrypted_welcome(),
+        )?;
+
+        serde_json::to_value(&store_welcome.roster_idx()).map_err(Into::into)
+    }
+
+    fn recover_welcome(
+        &self,
+        recover_welcome: RecoverWelcomeRequestBody,
+        peer: Option<&PeerIdentity>,
+    ) -> anyhow::Result<Value> {
+        let encrypted_welcome = self
+            .welcome_dir(peer, recover_welcome.roster_idx())?
+            .load_welcome_from_local_filesystem()?;
+
+        serde_json::to_value(&RecoveredWelcome::new(encrypted_welcome)).map_err(Into::into)
+    }
+
+    /// The directory of the path secrets of roster_idx namespaced by the requesting enclave's identity,
+    /// so only an enclave with the same MRSIGNER and ISVPRODID can store or recover them.
+    /// The path secrets stored before the namespacing are migrated to the first directory accessed without path secrets.
+    fn path_secrets_dir(
+        &self,
+        peer: Option<&PeerIdentity>,
+        roster_idx: u32,
+    ) -> anyhow::Result<StorePathSecrets> {
+        let peer =
+            peer.ok_or_else(|| anyhow!("The path secrets are only available to attested clients"))?;
+        let _guard = PATH_SECRET_LOCK
+            .lock()
+            .map_err(|e| anyhow!("Failed to acquire the path secret lock: {:?}", e))?;
+
+        let store_roster = self
+            .store_path_secrets
+            .clone()
+            .create_dir_all(roster_idx.to_string())?;
+        migrate_mr_enclave_dir(store_roster.local_dir_path(), peer)?;
+        let store_namespace = store_roster
+            .clone()
+            .create_dir_all(enclave_namespace(peer)?)?;
+        migrate_legacy_path_secrets(&store_roster, &store_namespace)?;
+
+        Ok(store_namespace)
+    }
+
+    /// The directory of the welcome message for roster_idx namespaced by the requesting enclave's identity,
+    /// so only an enclave with the same MRSIGNER and ISVPRODID can store or recover it.
+    fn welcome_dir(
+        &self,
+        peer: Option<&PeerIdentity>,
+        roster_idx: u32,
+    ) -> anyhow::Result<StorePathSecrets> {
+        let peer = peer.ok_or_else(|| {
+            anyhow!("The welcome messages are only available to attested clients")
+        })?;
+
+        let store_roster = self
+            .store_path_secrets
+            .clone()
+            .create_dir_all(roster_idx.to_string())?;
+        migrate_mr_enclave_dir(store_roster.local_dir_path(), peer)?;
+        store_roster.create_dir_all(enclave_namespace(peer)?)
+    }
+}
+
+/// Returns the backed up versions in ascending order.
+fn backed_up_versions(
+    store_dec_key: &StoreEnclaveDecryptionKey,
+    roster_idx: u32,
+) -> anyhow::Result<Vec<BackedUpEnclaveDecryptionKey>> {
+    let mut versions: Vec<BackedUpEnclaveDecryptionKey> = store_dec_key
+        .get_all_file_names()?
+        .iter()
+        .filter_map(|file_name| parse_dec_key_file_name(roster_idx, file_name))
+        .collect();
+    versions.sort_by_key(|backed_up| backed_up.version());
+
+    Ok(versions)
+}
+
+/// The file name is `<version>_<fingerprint>`, followed by `_<commitment>` if the key is a share.
+fn dec_key_file_name(backed_up: &BackedUpEnclaveDecryptionKey) -> String {
+    let mut file_name = format!(
+        "{}_{}",
+        backed_up.version(),
+        hex::encode(backed_up.fingerprint())
+    );
+    if !backed_up.commitment().is_empty() {
+        file_name.push('_');
+        file_name.push_str(&hex::encode(backed_up.commitment()));
+    }
+    file_name
+}
+
+fn parse_dec_key_file_name(
+    roster_idx: u32,
+    file_name: &str,
+) -> Option<BackedUpEnclaveDecryptionKey> {
+    let mut parts = file_name.splitn(3, '_');
+    let version = parts.next()?.parse().ok()?;
+    let fingerprint = hex::decode(parts.next()?).ok()?;
+    let commitment = match parts.next() {
+        Some(commitment) => hex::decode(commitment).ok()?,
+        None => vec![],
+    };
+
+    Some(
+        BackedUpEnclaveDecryptionKey::new(roster_idx, version, fingerprint)
+            .with_commitment(commitment),
+    )
+}
+
+/// The directory name of the enclave, which stays the same across the builds signed by the same key for the same product.
+/// The ISVPRODID 0 is refused, because it's the default in the enclave configs
+/// and may be shared by different products signed by the same key.
+fn enclave_namespace(peer: &PeerIdentity) -> anyhow::Result<String> {
+    ensure!(
+        peer.isv_prod_id() != UNASSIGNED_ISV_PROD_ID,
+        "The enclave signed by {} has no product ID assigned",
+        hex::encode(peer.mr_signer())
+    );
+
+    Ok(format!(
+        "{}_{}",
+        hex::encode(peer.mr_signer()),
+        peer.isv_prod_id()
+    ))
+}
+
+/// Move the path secrets stored directly in the directory of the roster index before the namespacing
+/// into the namespace, unless it already has path secrets.
+fn migrate_legacy_path_secrets(
+    store_roster: &StorePathSecrets,
+    store_namespace: &StorePathSecrets,
+) -> anyhow::Result<()> {
+    let mut legacy = vec![];
+    for entry in fs::read_dir(store_roster.local_dir_path())? {
+        let path = entry?.path();
+        if path.is_file() {
+            legacy.push(path);
+        }
+    }
+    if legacy.is_empty() || !store_namespace.get_all_path_secret_ids()?.is_empty() {
+        return Ok(());
+    }
+
+    for path in &legacy {
+        if let Some(file_name) = path.file_name() {
+            fs::rename(path, store_namespace.local_dir_path().join(file_name))?;
+        }
+    }
+    warn!(
+        "Migrated {} legacy path secrets to {:?}",
+        legacy.len(),
+        store_namespace.local_dir_path()
+    );
+
+    Ok(())
+}
+
+/// Move the directory namespaced by the peer's MRENCLAVE, as it was before namespacing by MRSIGNER and ISVPRODID,
+/// to the new namespace unless it already exists.
+/// Only the build which stored the entries can migrate them, so it has to access the key-vault before being upgraded.
+/// Returns whether the directory is migrated.
+fn migrate_mr_enclave_dir(parent: &Path, peer: &PeerIdentity) -> anyhow::Result<bool> {
+    let legacy = parent.join(hex::encode(peer.mr_enclave()));
+    let namespaced = parent.join(enclave_namespace(peer)?);
+    if namespaced.exists() || !legacy.is_dir() {
+        return Ok(false);
+    }
+    if let Err(e) = fs::rename(&legacy, &namespaced) {
+        // Another handler has just migrated it.
+        if namespaced.exists() {
+            return Ok(false);
+        }
+        return Err(e.into());
+    }
+    warn!("Migrated {:?} to {:?}", legacy, namespaced);
+
+    Ok(true)
+}
+
+/// The bindings in the directories migrated from the MRENCLAVE namespace were made with the MRENCLAVE,
+/// so it's sealed next to each of them to verify them once more.
+fn remember_legacy_bindings(
+    store_namespace: &StoreEnclaveDecryptionKey,
+    peer: &PeerIdentity,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(store_namespace.local_dir_path())? {
+        let roster_dir = entry?.path();
+        if !roster_dir.join(DEC_KEY_BINDING_FILE_NAME).is_file() {
+            continue;
+        }
+        if let Some(roster_idx) = roster_dir.file_name().and_then(|name| name.to_str()) {
+            store_namespace
+                .clone()
+                .create_dir_all(roster_idx)?
+                .save_secret_to_local_filesystem(peer.mr_enclave(), LEGACY_BINDING_FILE_NAME)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Bind the roster index to the node which stores its first backup, or verify that it's bound to the node,
+/// so that a node can't add a version to the backups of another node's roster index.
+/// The node is identified by the secret it seals, because the nodes in a group share the same enclave identity.
+/// A binding migrated from the MRENCLAVE namespace is verified with the MRENCLAVE and then rebound.
+fn bind_node(
+    store_dec_key: &StoreEnclaveDecryptionKey,
+    peer: Option<&PeerIdentity>,
+    roster_idx: u32,
+    node_key: &[u8],
+) -> anyhow::Result<()> {
+    let peer = peer.ok_or_else(|| {
+        anyhow!("The enclave decryption key backups are only available to attested clients")
+    })?;
+    ensure!(
+        !node_key.is_empty(),
+        "The node key is required to back up the enclave decryption key of roster index {}",
+        roster_idx
+    );
+    let mut enclave_id = peer.mr_signer().to_vec();
+    enclave_id.extend_from_slice(&peer.isv_prod_id().to_le_bytes());
+    let binding = node_binding(&enclave_id, roster_idx, node_key);
+    let file_names = store_dec_key.get_all_file_names()?;
+    let is_bound = file_names
+        .iter()
+        .any(|file_name| file_name == DEC_KEY_BINDING_FILE_NAME);
+    if is_bound {
+        let bound = store_dec_key.load_secret_from_local_filesystem(DEC_KEY_BINDING_FILE_NAME)?;
+        if bound == binding {
+            return Ok(());
+        }
+        let is_legacy = file_names
+            .iter()
+            .any(|file_name| file_name == LEGACY_BINDING_FILE_NAME);
+        let legacy_mr_enclave = if is_legacy {
+            Some(store_dec_key.load_secret_from_local_filesystem(LEGACY_BINDING_FILE_NAME)?)
+        } else {
+            None
+        };
+        ensure!(
+            legacy_mr_enclave.map_or(false, |mr_enclave| {
+                bound == node_binding(&mr_enclave, roster_idx, node_key)
+            }),
+            "The roster index {} is bound to another node",
+            roster_idx
+        );
+        store_dec_key.remove_from_local_filesystem(LEGACY_BINDING_FILE_NAME)?;
+    }
+    store_dec_key.save_secret_to_local_filesystem(&binding, DEC_KEY_BINDING_FILE_NAME)?;
+
+    Ok(())
+}
+
+/// The binding is the hash of the node key with the enclave identity and the roster index,
+/// so a sealed binding copied to another directory doesn't match.
+fn node_binding(enclave_id: &[u8], roster_idx: u32, node_key: &[u8]) -> Vec<u8> {
+    let mut preimage = Vec::with_capacity(enclave_id.len() + 4 + node_key.len());
+    preimage.extend_from_slice(enclave_id);
+    preimage.extend_from_slice(&roster_idx.to_le_bytes());
+    preimage.extend_from_slice(node_key);
+    let hash: [u8; 32] = preimage[..].keccak256();
+    hash.to_vec()
+}
+
+/// The fingerprint of the enclave decryption key is the hash of the corresponding encryption key.
+fn dec_key_fingerprint(dec_key: &SodiumPrivateKey) -> Vec<u8> {
+    let hash: [u8; 32] = dec_key.public_key().to_bytes()[..].keccak256();
+    hash.to_vec()
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+    use test_utils::{run_tests, runner::*};
+
+    const TEST_PATH_SECRETS_DIR: &str = ".anonify/test_kv_path_secrets";
+    const TEST_DEC_KEY_DIR: &str = ".anonify/test_kv_dec_key";
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_path_secrets_namespaced_by_enclave,
+            test_migrate_legacy_path_secrets,
+            test_entries_namespaced_by_product,
+            test_refuse_unassigned_product,
+        )
+    }
+
+    fn handler() -> KeyVaultHandler {
+        KeyVaultHandler::new(
+            StorePathSecrets::new(TEST_PATH_SECRETS_DIR),
+            StoreEnclaveDecryptionKey::new(TEST_DEC_KEY_DIR),
+        )
+    }
+
+    fn clean_up(handler: &KeyVaultHandler) {
+        fs::remove_dir_all(handler.store_path_secrets.local_dir_path()).unwrap();
+        fs::remove_dir_all(handler.store_enclave_dec_key.local_dir_path()).unwrap();
+    }
+
+    fn peer(isv_prod_id: u16) -> PeerIdentity {
+        PeerIdentity::new([1u8; 32], [2u8; 32], isv_prod_id, 0)
+    }
+
+    fn test_path_secrets_namespaced_by_enclave() {
+        let handler = handler();
+        let (peer1, peer2) = (peer(1), peer(2));
+        let path_secret = PathSecret::new_from_random_sgx().as_bytes().to_vec();
+        let backup = BackupPathSecretRequestBody::new(path_secret.clone(), 1, 0, vec![3u8; 32]);
+        handler.store_path_secret(backup, Some(&peer1)).unwrap();
+
+        let recover = RecoverPathSecretRequestBody::new(0, vec![3u8; 32]);
+        let recovered: RecoveredPathSecret = serde_json::from_value(
+            handler
+                .recover_path_secret(recover.clone(), Some(&peer1))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(recovered.path_secret(), &path_secret[..]);
+        assert!(handler
+            .recover_path_secret(recover.clone(), Some(&peer2))
+            .is_err());
+        assert!(handler.recover_path_secret(recover, None).is_err());
+
+        let recovered: Vec<RecoveredPathSecret> = serde_json::from_value(
+            handler
+                .manually_recover_path_secrets(RecoverPathSecretsRequestBody::new(0), Some(&peer2))
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(recovered.is_empty());
+
+        clean_up(&handler);
+    }
+
+    fn test_migrate_legacy_path_secrets() {
+        let handler = handler();
+        let (peer1, peer2) = (peer(1), peer(2));
+        let eps = PathSecret::new_from_random_sgx()
+            .try_into_exporting(1, &[4u8; 32])
+            .unwrap();
+        handler
+            .store_path_secrets
+            .clone()
+            .create_dir_all("0")
+            .unwrap()
+            .save_to_local_filesystem(&eps)
+            .unwrap();
+
+        let recover = RecoverPathSecretRequestBody::new(0, vec![4u8; 32]);
+        assert!(handler
+            .recover_path_secret(recover.clone(), Some(&peer1))
+            .is_ok());
+        // The legacy path secrets are moved to the namespace accessed first.
+        assert!(handler
+            .recover_path_secret(recover.clone(), Some(&peer2))
+            .is_err());
+        assert!(handler.recover_path_secret(recover, Some(&peer1)).is_ok());
+
+        clean_up(&handler);
+    }
+
+    fn test_entries_namespaced_by_product() {
+        let handler = handler();
+        let (peer1, peer2) = (peer(1), peer(2));
+        handler
+            .store_welcome(
+                StoreWelcomeRequestBody::new(0, 1, b"welcome".to_vec()),
+                Some(&peer1),
+            )
+            .unwrap();
+        assert!(handler
+            .recover_welcome(RecoverWelcomeRequestBody::new(0), Some(&peer1))
+            .is_ok());
+        assert!(handler
+            .recover_welcome(RecoverWelcomeRequestBody::new(0), Some(&peer2))
+            .is_err());
+
+        // Another product can't get the encrypted-sql-ops master key by proposing under its name.
+        let provision = |key: Vec<u8>, peer: &PeerIdentity| -> ProvisionedSharedKey {
+            let provision =
+                ProvisionSharedKeyRequestBody::new("encrypted-sql-ops-master-key".to_string(), key);
+            serde_json::from_value(handler.provision_shared_key(provision, Some(peer)).unwrap())
+                .unwrap()
+        };
+        assert_eq!(provision(vec![1u8; 32], &peer1).key(), &[1u8; 32][..]);
+        assert_eq!(provision(vec![2u8; 32], &peer2).key(), &[2u8; 32][..]);
+        assert_eq!(provision(vec![3u8; 32], &peer1).key(), &[1u8; 32][..]);
+
+        clean_up(&handler);
+    }
+
+    fn test_refuse_unassigned_product() {
+        let handler = handler();
+        let unassigned = peer(UNASSIGNED_ISV_PROD_ID);
+        let backup = BackupPathSecretRequestBody::new(vec![5u8; 32], 1, 0, vec![6u8; 32]);
+        assert!(handler
+            .store_path_secret(backup, Some(&unassigned))
+            .is_err());
+        let provision = ProvisionSharedKeyRequestBody::new("master-key".to_string(), vec![7u8; 32]);
+        assert!(handler
+            .provision_shared_key(provision, Some(&unassigned))
+            .is_err());
+
+        clean_up(&handler);
     }
 }
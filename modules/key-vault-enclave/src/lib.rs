@@ -7,8 +7,23 @@ extern crate sgx_tstd as std;
 
 pub mod context;
 mod handlers;
+mod replication;
 pub mod server;
 
+#[cfg(debug_assertions)]
+pub mod tests {
+    use super::*;
+    use std::prelude::v1::*;
+    use test_utils::check_all_passed;
+
+    pub fn run_tests() -> bool {
+        check_all_passed!(
+            handlers::tests::run_tests(),
+            replication::tests::run_tests(),
+        )
+    }
+}
+
 pub mod use_case {
-    pub use crate::server::{ServerStarter, ServerStopper};
+    pub use crate::server::{MeasurementPolicyReloader, ServerStarter, ServerStopper};
 }
@@ -0,0 +1,359 @@
+use anyhow::{anyhow, bail};
+use frame_mra_tls::{
+    key_vault::{
+        error::KeyVaultError,
+        request::{KeyVaultCmd, KeyVaultRequest, ReplicateRequestBody},
+    },
+    Client, ClientConfig, PeerIdentity,
+};
+use serde_json::Value;
+use std::{env, string::String, vec::Vec};
+use tracing::warn;
+
+/// Forwards write requests to the peer key-vault nodes in the cluster
+/// and counts their acknowledgements against the write quorum.
+#[derive(Clone)]
+pub struct Replicator {
+    peers: Vec<String>,
+    client_config: ClientConfig,
+    write_quorum: usize,
+}
+
+impl Replicator {
+    /// Returns `None` if no peers are configured, in which case the node runs standalone.
+    pub fn from_env(client_config: ClientConfig) -> anyhow::Result<Option<Self>> {
+        let peers: Vec<String> = match env::var("KEY_VAULT_PEER_ENDPOINTS") {
+            Ok(peers) => peers
+                .split(',')
+                .map(|peer| peer.trim().to_string())
+                .filter(|peer| !peer.is_empty())
+                .collect(),
+            Err(_) => vec![],
+        };
+        if peers.is_empty() {
+            return Ok(None);
+        }
+
+        // The cluster size includes this node itself.
+        let cluster_size = peers.len() + 1;
+        let write_quorum = match env::var("KEY_VAULT_WRITE_QUORUM") {
+            Ok(quorum) if !quorum.is_empty() => quorum
+                .parse()
+                .map_err(|e| anyhow!("Failed to parse KEY_VAULT_WRITE_QUORUM: {:?}", e))?,
+            _ => cluster_size / 2 + 1,
+        };
+        if write_quorum == 0 || write_quorum > cluster_size {
+            bail!(
+                "KEY_VAULT_WRITE_QUORUM must be between 1 and the cluster size {}, but got {}",
+                cluster_size,
+                write_quorum
+            );
+        }
+
+        Ok(Some(Replicator {
+            peers,
+            client_config,
+            write_quorum,
+        }))
+    }
+
+    /// Forward the write request to all peers, and store it locally only after the write quorum acknowledged it.
+    /// This node counts toward the quorum once its local store succeeds,
+    /// so a write rejected by the quorum is never stored locally nor answered as successful.
+    /// The peers seal the entries with their own keys, so only the plaintext is sent
+    /// over the mutually attested TLS connections.
+    pub fn replicate<T, F>(
+        &self,
+        request: &Value,
+        origin: Option<&PeerIdentity>,
+        store_locally: F,
+    ) -> Result<T, KeyVaultError>
+    where
+        F: FnOnce() -> Result<T, KeyVaultError>,
+    {
+        let body = ReplicateRequestBody::new(origin.copied(), request.clone());
+        replicate_then_store(
+            &self.peers,
+            self.write_quorum,
+            |peer| {
+                let replicate_request = KeyVaultRequest::new(KeyVaultCmd::Replicate, body.clone());
+                Client::new(peer, &self.client_config)
+                    .and_then(|mut client| client.send_request::<_, Value>(replicate_request))
+                    .map(|_| ())
+            },
+            store_locally,
+        )
+    }
+
+    /// Forward the first-writer-wins request, such as provisioning a shared key, to all peers,
+    /// which answer the entry they store whether it's the proposed one or not,
+    /// and answer the entry stored by a majority of the cluster, and at least by the write quorum.
+    /// The nodes never overwrite such entries, so no two different entries can be answered
+    /// even if they are proposed through different nodes at the same time.
+    pub fn replicate_agreed<F>(
+        &self,
+        request: &Value,
+        origin: Option<&PeerIdentity>,
+        store_locally: F,
+    ) -> Result<Value, KeyVaultError>
+    where
+        F: FnOnce() -> Result<Value, KeyVaultError>,
+    {
+        let body = ReplicateRequestBody::new(origin.copied(), request.clone());
+        let majority = (self.peers.len() + 1) / 2 + 1;
+        replicate_then_agree(
+            &self.peers,
+            self.write_quorum.max(majority),
+            |peer| {
+                let replicate_request = KeyVaultRequest::new(KeyVaultCmd::Replicate, body.clone());
+                Client::new(peer, &self.client_config)
+                    .and_then(|mut client| client.send_request(replicate_request))
+            },
+            store_locally,
+        )
+    }
+}
+
+/// Forward a write to each peer by `forward` and count the acknowledgements,
+/// then run `store_locally` only if the peers and this node together can reach the write quorum.
+fn replicate_then_store<T, R, F>(
+    peers: &[String],
+    write_quorum: usize,
+    mut forward: R,
+    store_locally: F,
+) -> Result<T, KeyVaultError>
+where
+    R: FnMut(&str) -> anyhow::Result<()>,
+    F: FnOnce() -> Result<T, KeyVaultError>,
+{
+    let mut peer_acks = 0;
+    for peer in peers {
+        match forward(peer) {
+            Ok(()) => peer_acks += 1,
+            Err(e) => warn!("Failed to replicate the request to {}: {:?}", peer, e),
+        }
+    }
+
+    // This node acknowledges the write by storing it locally.
+    if peer_acks + 1 < write_quorum {
+        return Err(KeyVaultError::internal(format!(
+            "Only {} of {} key-vault nodes acknowledged the write, but the quorum is {}",
+            peer_acks,
+            peers.len() + 1,
+            write_quorum
+        )));
+    }
+
+    store_locally()
+}
+
+/// Forward a first-writer-wins write to each peer by `forward` and collect the entries they answer,
+/// then store it locally by `store_locally` and answer the entry which at least `quorum` nodes agree on.
+fn replicate_then_agree<R, F>(
+    peers: &[String],
+    quorum: usize,
+    mut forward: R,
+    store_locally: F,
+) -> Result<Value, KeyVaultError>
+where
+    R: FnMut(&str) -> anyhow::Result<Value>,
+    F: FnOnce() -> Result<Value, KeyVaultError>,
+{
+    let mut answers = Vec::with_capacity(peers.len() + 1);
+    for peer in peers {
+        match forward(peer) {
+            Ok(answer) => answers.push(answer),
+            Err(e) => warn!("Failed to replicate the request to {}: {:?}", peer, e),
+        }
+    }
+    if answers.len() + 1 < quorum {
+        return Err(KeyVaultError::internal(format!(
+            "Only {} of {} key-vault nodes acknowledged the write, but the quorum is {}",
+            answers.len(),
+            peers.len() + 1,
+            quorum
+        )));
+    }
+    answers.push(store_locally()?);
+
+    let (agreed, votes) = answers
+        .iter()
+        .map(|answer| (answer, answers.iter().filter(|a| *a == answer).count()))
+        .max_by_key(|(_, votes)| *votes)
+        .expect("at least this node answers");
+    if votes < quorum {
+        return Err(KeyVaultError::internal(format!(
+            "Only {} of {} key-vault nodes agreed on the stored entry, but the quorum is {}",
+            votes,
+            peers.len() + 1,
+            quorum
+        )));
+    }
+
+    Ok(agreed.clone())
+}
+
+#[cfg(debug_assertions)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{
+        cell::{Cell, RefCell},
+        prelude::v1::*,
+    };
+    use test_utils::{run_tests, runner::*};
+
+    pub(crate) fn run_tests() -> bool {
+        run_tests!(
+            test_store_locally_after_quorum,
+            test_not_store_locally_without_quorum,
+            test_local_store_failure_after_quorum,
+            test_provision_through_different_nodes,
+            test_provision_resolved_by_majority,
+            test_provision_without_majority,
+        )
+    }
+
+    fn peers() -> Vec<String> {
+        vec![
+            "peer1:12345".to_string(),
+            "peer2:12345".to_string(),
+            "peer3:12345".to_string(),
+        ]
+    }
+
+    fn test_store_locally_after_quorum() {
+        let forwarded = Cell::new(0);
+        let stored_after = Cell::new(None);
+        let res: Result<u32, KeyVaultError> = replicate_then_store(
+            &peers(),
+            3,
+            |peer| {
+                forwarded.set(forwarded.get() + 1);
+                if peer == "peer3:12345" {
+                    Err(anyhow!("unreachable"))
+                } else {
+                    Ok(())
+                }
+            },
+            || {
+                stored_after.set(Some(forwarded.get()));
+                Ok(1)
+            },
+        );
+
+        assert_eq!(res.unwrap(), 1);
+        // The local store runs after all the peers are tried.
+        assert_eq!(stored_after.get(), Some(3));
+    }
+
+    fn test_not_store_locally_without_quorum() {
+        let is_stored = Cell::new(false);
+        let res: Result<(), KeyVaultError> = replicate_then_store(
+            &peers(),
+            3,
+            |peer| {
+                if peer == "peer1:12345" {
+                    Ok(())
+                } else {
+                    Err(anyhow!("unreachable"))
+                }
+            },
+            || {
+                is_stored.set(true);
+                Ok(())
+            },
+        );
+
+        assert!(res.is_err());
+        assert!(!is_stored.get());
+    }
+
+    fn test_local_store_failure_after_quorum() {
+        let res: Result<(), KeyVaultError> = replicate_then_store(
+            &peers(),
+            2,
+            |_| Ok(()),
+            || Err(KeyVaultError::internal("disk full")),
+        );
+
+        assert!(res.is_err());
+    }
+
+    /// A key-vault node which stores the first proposed entry and answers it afterwards.
+    struct FirstWriterWins {
+        stored: RefCell<Option<Value>>,
+    }
+
+    impl FirstWriterWins {
+        fn new(stored: Option<Value>) -> Self {
+            FirstWriterWins {
+                stored: RefCell::new(stored),
+            }
+        }
+
+        fn provision(&self, proposed: &Value) -> Value {
+            self.stored
+                .borrow_mut()
+                .get_or_insert_with(|| proposed.clone())
+                .clone()
+        }
+    }
+
+    /// Provision the proposed entry through the node at `origin` in the cluster of `nodes`.
+    fn provision_through(
+        nodes: &[FirstWriterWins],
+        origin: usize,
+        proposed: &Value,
+    ) -> Result<Value, KeyVaultError> {
+        let peers: Vec<String> = (0..nodes.len())
+            .filter(|idx| *idx != origin)
+            .map(|idx| idx.to_string())
+            .collect();
+        let majority = nodes.len() / 2 + 1;
+        replicate_then_agree(
+            &peers,
+            majority,
+            |peer| Ok(nodes[peer.parse::<usize>()?].provision(proposed)),
+            || Ok(nodes[origin].provision(proposed)),
+        )
+    }
+
+    fn test_provision_through_different_nodes() {
+        let nodes: Vec<_> = (0..3).map(|_| FirstWriterWins::new(None)).collect();
+
+        let first = provision_through(&nodes, 0, &json!({ "key": [1] })).unwrap();
+        let second = provision_through(&nodes, 1, &json!({ "key": [2] })).unwrap();
+
+        assert_eq!(first, json!({ "key": [1] }));
+        assert_eq!(second, first);
+        for node in &nodes {
+            assert_eq!(node.stored.borrow().as_ref(), Some(&first));
+        }
+    }
+
+    fn test_provision_resolved_by_majority() {
+        // The proposals through the node 0 and the node 2 raced, and each of them stored its own one.
+        let nodes = vec![
+            FirstWriterWins::new(Some(json!({ "key": [1] }))),
+            FirstWriterWins::new(None),
+            FirstWriterWins::new(Some(json!({ "key": [2] }))),
+        ];
+
+        let first = provision_through(&nodes, 0, &json!({ "key": [1] })).unwrap();
+        let second = provision_through(&nodes, 2, &json!({ "key": [2] })).unwrap();
+
+        assert_eq!(first, json!({ "key": [1] }));
+        assert_eq!(second, first);
+    }
+
+    fn test_provision_without_majority() {
+        let nodes = vec![
+            FirstWriterWins::new(Some(json!({ "key": [1] }))),
+            FirstWriterWins::new(Some(json!({ "key": [2] }))),
+        ];
+
+        assert!(provision_through(&nodes, 0, &json!({ "key": [1] })).is_err());
+        assert!(provision_through(&nodes, 1, &json!({ "key": [2] })).is_err());
+    }
+}
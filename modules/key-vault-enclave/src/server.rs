@@ -1,13 +1,20 @@
 use crate::context::KeyVaultEnclaveContext;
 use crate::handlers::KeyVaultHandler;
-use frame_config::{ANONIFY_ENCLAVE_MEASUREMENT, IAS_ROOT_CERT};
+use crate::replication::Replicator;
+use frame_config::{
+    ATTESTATION_RENEWAL_INTERVAL_SECS, IAS_ROOT_CERT, KEY_VAULT_ENCLAVE_MEASUREMENT,
+    KEY_VAULT_MAX_WELCOME_FRAME_LEN,
+};
 use frame_enclave::BasicEnclaveUseCase;
-use frame_mra_tls::{AttestedTlsConfig, Server, ServerConfig};
+use frame_mra_tls::{AttestedTlsConfig, ClientConfig, Server, ServerConfig};
 use frame_runtime::traits::*;
-use key_vault_ecall_types::cmd::{START_SERVER_CMD, STOP_SERVER_CMD};
+use key_vault_ecall_types::cmd::{
+    RELOAD_MEASUREMENT_POLICY_CMD, START_SERVER_CMD, STOP_SERVER_CMD,
+};
 use key_vault_ecall_types::*;
 
-use std::env;
+use std::{env, thread, time::Duration};
+use tracing::error;
 
 /// A server starter
 #[derive(Debug, Clone)]
@@ -34,16 +41,60 @@ impl<'c> BasicEnclaveUseCase<'c, KeyVaultEnclaveContext> for ServerStarter<'c> {
 
         let attested_tls_config =
             AttestedTlsConfig::new_by_ra(&spid, &ias_url, &sub_key, IAS_ROOT_CERT.to_vec())?;
+        // All the servers and the replicator present the renewed certificate without restarting.
+        let renewal_handle = attested_tls_config
+            .spawn_renewal(Duration::from_secs(*ATTESTATION_RENEWAL_INTERVAL_SECS))?;
+        self.enclave_context.register_renewal_handle(renewal_handle);
 
-        let server_config = ServerConfig::from_attested_tls_config(attested_tls_config)?
-            .set_attestation_report_verifier(IAS_ROOT_CERT.to_vec(), *ANONIFY_ENCLAVE_MEASUREMENT);
+        let server_config = ServerConfig::from_attested_tls_config(attested_tls_config.clone())?
+            .set_measurement_policy_verifier(
+                IAS_ROOT_CERT.to_vec(),
+                self.enclave_context.state_runtime_policy().clone(),
+            )
+            .set_max_frame_len(*KEY_VAULT_MAX_WELCOME_FRAME_LEN);
 
-        let key_vault_address = env::var("KEY_VAULT_ENDPOINT_FOR_KEY_VAULT")?;
-        let mut server = Server::new(key_vault_address, server_config);
         let store_path_secrets = self.enclave_context.store_path_secrets();
         let store_enclave_dec_key = self.enclave_context.store_enclave_dec_key();
+
+        // The peer key-vault nodes are mutually attested with the key-vault enclave's measurement.
+        let replicator_config =
+            ClientConfig::from_attested_tls_config(attested_tls_config.clone())?
+                .set_attestation_report_verifier(
+                    IAS_ROOT_CERT.to_vec(),
+                    *KEY_VAULT_ENCLAVE_MEASUREMENT,
+                );
+        let replicator = Replicator::from_env(replicator_config)?;
+        if replicator.is_some() {
+            let replication_config = ServerConfig::from_attested_tls_config(attested_tls_config)?
+                .set_attestation_report_verifier(
+                    IAS_ROOT_CERT.to_vec(),
+                    *KEY_VAULT_ENCLAVE_MEASUREMENT,
+                )
+                .set_max_frame_len(*KEY_VAULT_MAX_WELCOME_FRAME_LEN);
+            let replication_address = env::var("KEY_VAULT_REPLICATION_ADDRESS")?;
+            let replica_handler = KeyVaultHandler::new_replica(
+                store_path_secrets.clone(),
+                store_enclave_dec_key.clone(),
+            );
+            let mut replication_server = Server::new(replication_address, replication_config);
+            self.enclave_context
+                .register_shutdown_handle(replication_server.shutdown_handle());
+            // Serve the replication requests in another thread,
+            // so that forwarding a write to the peers doesn't block their client-facing servers.
+            thread::spawn(move || {
+                if let Err(e) = replication_server.run(replica_handler) {
+                    error!("The replication server stopped: {:?}", e);
+                }
+            });
+        }
+
+        let key_vault_address = env::var("KEY_VAULT_ENDPOINT_FOR_KEY_VAULT")?;
+        let mut server = Server::new(key_vault_address, server_config);
+        self.enclave_context
+            .register_shutdown_handle(server.shutdown_handle());
         let handler =
-            KeyVaultHandler::new(store_path_secrets.clone(), store_enclave_dec_key.clone());
+            KeyVaultHandler::new(store_path_secrets.clone(), store_enclave_dec_key.clone())
+                .with_replicator(replicator);
         server.run(handler).unwrap();
 
         Ok(output::Empty::default())
@@ -69,6 +120,31 @@ impl<'c> BasicEnclaveUseCase<'c, KeyVaultEnclaveContext> for ServerStopper<'c> {
     }
 
     fn run(self) -> anyhow::Result<Self::EO> {
+        self.enclave_context.shutdown_servers();
+        Ok(output::Empty::default())
+    }
+}
+
+/// A reloader of the measurement policy of the state runtime enclaves
+#[derive(Debug, Clone)]
+pub struct MeasurementPolicyReloader<'c> {
+    enclave_context: &'c KeyVaultEnclaveContext,
+}
+
+impl<'c> BasicEnclaveUseCase<'c, KeyVaultEnclaveContext> for MeasurementPolicyReloader<'c> {
+    type EI = input::CallMeasurementPolicyReloader;
+    type EO = output::Empty;
+    const ENCLAVE_USE_CASE_ID: u32 = RELOAD_MEASUREMENT_POLICY_CMD;
+
+    fn new(
+        _enclave_input: Self::EI,
+        enclave_context: &'c KeyVaultEnclaveContext,
+    ) -> anyhow::Result<Self> {
+        Ok(Self { enclave_context })
+    }
+
+    fn run(self) -> anyhow::Result<Self::EO> {
+        self.enclave_context.reload_state_runtime_policy()?;
         Ok(output::Empty::default())
     }
 }
@@ -39,6 +39,24 @@ impl EcallController for StopServerController {
     }
 }
 
+pub struct ReloadMeasurementPolicyController;
+
+impl EcallController for ReloadMeasurementPolicyController {
+    type HI = host_input::ReloadMeasurementPolicy;
+    type EI = input::CallMeasurementPolicyReloader;
+    type EO = output::Empty;
+    type HO = host_output::ReloadMeasurementPolicy;
+    const EI_MAX_SIZE: usize = EI_MAX_SIZE;
+
+    fn translate_input(_host_input: Self::HI) -> anyhow::Result<Self::EI> {
+        Ok(input::CallMeasurementPolicyReloader::default())
+    }
+
+    fn translate_output(_enclave_output: Self::EO) -> anyhow::Result<Self::HO> {
+        Ok(host_output::ReloadMeasurementPolicy::default())
+    }
+}
+
 pub mod host_input {
     use super::*;
 
@@ -61,6 +79,16 @@ pub mod host_input {
     }
 
     impl HostInput for StopServer {}
+
+    pub struct ReloadMeasurementPolicy {}
+
+    impl ReloadMeasurementPolicy {
+        pub fn new() -> Self {
+            ReloadMeasurementPolicy {}
+        }
+    }
+
+    impl HostInput for ReloadMeasurementPolicy {}
 }
 
 pub mod host_output {
@@ -75,4 +103,9 @@ pub mod host_output {
     pub struct StopServer;
 
     impl HostOutput for StopServer {}
+
+    #[derive(Default)]
+    pub struct ReloadMeasurementPolicy;
+
+    impl HostOutput for ReloadMeasurementPolicy {}
 }
@@ -46,6 +46,16 @@ impl Dispatcher {
         Ok(())
     }
 
+    /// Reload the signed measurement policy of the state runtime enclaves in the running enclave.
+    pub async fn reload_measurement_policy(&self) -> Result<()> {
+        let eid = self.inner.read().enclave_id;
+        let input = host_input::ReloadMeasurementPolicy::new();
+        let _host_output =
+            ReloadMeasurementPolicyController::run(input, RELOAD_MEASUREMENT_POLICY_CMD, eid)?;
+
+        Ok(())
+    }
+
     pub fn set_healthy(self) -> Self {
         self.inner.write().is_healthy = true;
         self
@@ -1,6 +1,7 @@
 use crate::Server;
 use actix_web::{web, HttpResponse, Responder};
 use std::sync::Arc;
+use tracing::error;
 
 pub async fn handle_health_check(server: web::Data<Arc<Server>>) -> impl Responder {
     if server.dispatcher.is_healthy() {
@@ -9,3 +10,14 @@ pub async fn handle_health_check(server: web::Data<Arc<Server>>) -> impl Respond
         HttpResponse::ServiceUnavailable().finish()
     }
 }
+
+/// Apply the updated measurement policy file of the state runtime enclaves without restarting the key-vault.
+pub async fn handle_reload_measurement_policy(server: web::Data<Arc<Server>>) -> impl Responder {
+    match server.dispatcher.reload_measurement_policy().await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => {
+            error!("Failed to reload the measurement policy: {:?}", e);
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+    }
+}
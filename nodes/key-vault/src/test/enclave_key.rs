@@ -61,10 +61,7 @@ async fn test_enclave_key_backup() {
         .to_path_buf()
         .join(SR_DEC_KEY_FILE_NAME)
         .exists());
-    assert!((&*ANONIFY_PARAMS_DIR)
-        .to_path_buf()
-        .join(KV_DEC_KEY_FILE_NAME)
-        .exists());
+    assert!(remote_dec_key_exists("0"));
     assert!(!logs_contain("ERROR"));
 }
 
@@ -124,10 +121,7 @@ async fn test_enclave_key_manually_backup() {
     clear_remote_dec_key_file();
 
     // ensure clearing remote enclave_key
-    assert!(!(&*ANONIFY_PARAMS_DIR)
-        .to_path_buf()
-        .join(KV_DEC_KEY_FILE_NAME)
-        .exists());
+    assert!(!remote_dec_key_exists("0"));
 
     // backup enclave_key to key-vault server
     let req = test::TestRequest::post().uri("/api/v1/backup").to_request();
@@ -136,10 +130,7 @@ async fn test_enclave_key_manually_backup() {
     actix_rt::time::delay_for(time::Duration::from_millis(SYNC_TIME + 500)).await;
 
     // check recovering enclave_key from remote
-    assert!((&*ANONIFY_PARAMS_DIR)
-        .to_path_buf()
-        .join(KV_DEC_KEY_FILE_NAME)
-        .exists());
+    assert!(remote_dec_key_exists("0"));
 
     assert!(!logs_contain("ERROR"));
 }
@@ -221,3 +212,104 @@ async fn test_enclave_key_manually_recover() {
 
     assert!(!logs_contain("ERROR"));
 }
+
+#[actix_rt::test]
+async fn test_enclave_key_roster_binding() {
+    set_env_vars();
+    set_server_env_vars();
+    clear_dec_key_files();
+    clear_local_node_key_file();
+
+    // Setup key-vault server
+    let key_vault_server_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize server enclave.");
+    let key_vault_server_eid = key_vault_server_enclave.geteid();
+    let key_vault_server = Arc::new(KeyVaultServer::new(key_vault_server_eid).run().await);
+    let _key_vault_app = test::init_service(App::new().data(key_vault_server.clone())).await;
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    // The first backup binds the roster index to the node
+    env::set_var("ENCLAVE_PKG_NAME", "erc20");
+    let app_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize client enclave.");
+    let _erc20_server = ERC20Server::new(app_enclave.geteid()).await.run().await;
+    assert!(remote_dec_key_binding_exists("0"));
+    let versions = remote_dec_key_versions("0");
+    assert_eq!(versions.len(), 1);
+
+    // Another node claiming the same roster index can re-store the latest key, but can't add a version
+    clear_local_node_key_file();
+    let other_app_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize client enclave.");
+    let other_erc20_server = ERC20Server::new(other_app_enclave.geteid())
+        .await
+        .run()
+        .await;
+    let other_erc20_server = Arc::new(other_erc20_server);
+    let mut other_app = test::init_service(
+        App::new()
+            .data(other_erc20_server.clone())
+            .route("/api/v1/key_rotation", web::post().to(handle_key_rotation)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/api/v1/key_rotation")
+        .to_request();
+    let resp = test::call_service(&mut other_app, req).await;
+    assert!(resp.status().is_server_error(), "response: {:?}", resp);
+    assert_eq!(remote_dec_key_versions("0"), versions);
+}
+
+#[actix_rt::test]
+async fn test_enclave_key_legacy_migration() {
+    set_env_vars();
+    set_server_env_vars();
+    clear_dec_key_files();
+
+    // Setup key-vault server
+    let key_vault_server_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize server enclave.");
+    let key_vault_server_eid = key_vault_server_enclave.geteid();
+    let key_vault_server = Arc::new(KeyVaultServer::new(key_vault_server_eid).run().await);
+    let _key_vault_app = test::init_service(App::new().data(key_vault_server.clone())).await;
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    env::set_var("ENCLAVE_PKG_NAME", "erc20");
+    let app_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize client enclave.");
+    let _erc20_server = ERC20Server::new(app_enclave.geteid()).await.run().await;
+
+    // Move the backup to the path before the namespacing, sealed by the same key-vault enclave
+    let versions = remote_dec_key_versions("0");
+    assert_eq!(versions.len(), 1);
+    fs::rename(
+        &versions[0],
+        ANONIFY_PARAMS_DIR.join(KV_LEGACY_DEC_KEY_FILE_NAME),
+    )
+    .unwrap();
+    clear_remote_dec_key_file();
+    clear_local_dec_key_file();
+
+    // A node without the local key recovers the legacy backup, which is migrated to its namespace
+    let recovered_app_enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize client enclave.");
+    let _recovered_erc20_server = ERC20Server::new(recovered_app_enclave.geteid())
+        .await
+        .run()
+        .await;
+    assert!(!ANONIFY_PARAMS_DIR
+        .join(KV_LEGACY_DEC_KEY_FILE_NAME)
+        .exists());
+    assert_eq!(remote_dec_key_versions("0").len(), 1);
+    assert!((&*ANONIFY_PARAMS_DIR)
+        .to_path_buf()
+        .join(SR_DEC_KEY_FILE_NAME)
+        .exists());
+    assert!(!logs_contain("ERROR"));
+}
@@ -10,7 +10,11 @@ use rand_core::{CryptoRng, RngCore};
 use serde_json::json;
 #[cfg(test)]
 use std::str::FromStr;
-use std::{env, fs, path::Path, sync::Arc};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 #[cfg(test)]
 use test_utils::tracing::logs_contain;
 use web3::{contract::Options, types::Address};
@@ -22,7 +26,9 @@ mod treekem;
 const SYNC_TIME: u64 = 1500;
 
 const SR_DEC_KEY_FILE_NAME: &'static str = "sr_enclave_decryption_key";
-const KV_DEC_KEY_FILE_NAME: &'static str = "kv_enclave_decryption_key";
+const KV_DEC_KEY_DIR_NAME: &'static str = "kv_enclave_decryption_keys";
+const KV_LEGACY_DEC_KEY_FILE_NAME: &'static str = "kv_enclave_decryption_key";
+const SR_NODE_KEY_FILE_NAME: &'static str = "sr_node_key";
 
 #[actix_rt::test]
 async fn test_health_check() {
@@ -78,7 +84,6 @@ fn set_env_vars() {
     *SUBSCRIBER_INIT;
     env::set_var("RUST_LOG", "DEBUG");
     env::set_var("MY_ROSTER_IDX", "0");
-    env::set_var("MAX_ROSTER_IDX", "2");
     env::set_var(
         "IAS_URL",
         "https://api.trustedservices.intel.com/sgx/dev/attestation/v3/report",
@@ -143,15 +148,79 @@ fn clear_local_dec_key_file() {
 }
 
 fn clear_remote_dec_key_file() {
-    let target = ANONIFY_PARAMS_DIR.join(KV_DEC_KEY_FILE_NAME);
+    let target = ANONIFY_PARAMS_DIR.join(KV_DEC_KEY_DIR_NAME);
+    if target.exists() {
+        fs::remove_dir_all(target).unwrap();
+    }
+}
+
+fn clear_legacy_remote_dec_key_file() {
+    let target = ANONIFY_PARAMS_DIR.join(KV_LEGACY_DEC_KEY_FILE_NAME);
+    if target.exists() {
+        fs::remove_file(target).unwrap();
+    }
+}
+
+/// Whether any version of the enclave decryption key of the roster index is backed up in the key-vault node.
+fn remote_dec_key_exists(roster_idx: &str) -> bool {
+    let target = ANONIFY_PARAMS_DIR.join(KV_DEC_KEY_DIR_NAME);
+    match fs::read_dir(target) {
+        Ok(measurements) => measurements.filter_map(|entry| entry.ok()).any(|entry| {
+            fs::read_dir(entry.path().join(roster_idx))
+                .map(|mut versions| versions.next().is_some())
+                .unwrap_or(false)
+        }),
+        Err(_) => false,
+    }
+}
+
+fn clear_local_node_key_file() {
+    let target = ANONIFY_PARAMS_DIR.join(SR_NODE_KEY_FILE_NAME);
     if target.exists() {
         fs::remove_file(target).unwrap();
     }
 }
 
+/// The directories of the enclave decryption key backups of the roster index for each measurement.
+fn remote_dec_key_dirs(roster_idx: &str) -> Vec<PathBuf> {
+    match fs::read_dir(ANONIFY_PARAMS_DIR.join(KV_DEC_KEY_DIR_NAME)) {
+        Ok(measurements) => measurements
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join(roster_idx))
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// The backed up versions of the enclave decryption key of the roster index, in ascending order.
+fn remote_dec_key_versions(roster_idx: &str) -> Vec<PathBuf> {
+    let mut versions: Vec<PathBuf> = remote_dec_key_dirs(roster_idx)
+        .into_iter()
+        .flat_map(|dir| fs::read_dir(dir).unwrap())
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.contains('_'))
+        })
+        .collect();
+    versions.sort();
+
+    versions
+}
+
+fn remote_dec_key_binding_exists(roster_idx: &str) -> bool {
+    remote_dec_key_dirs(roster_idx)
+        .iter()
+        .any(|dir| dir.join("binding").exists())
+}
+
 fn clear_dec_key_files() {
     clear_local_dec_key_file();
     clear_remote_dec_key_file();
+    clear_legacy_remote_dec_key_file();
 }
 
 fn get_local_id() -> Option<String> {
@@ -95,6 +95,50 @@ pub mod enclave_encryption_key {
     }
 }
 
+pub mod attestation_evidence {
+    pub mod get {
+        use super::super::*;
+
+        #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+        pub struct Response {
+            /// The attestation evidence of this node's enclave, whose REPORTDATA contains its encryption key.
+            pub evidence: serde_json::Value,
+        }
+    }
+}
+
+pub mod group {
+    pub mod get {
+        use super::super::*;
+
+        #[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+        pub struct Response {
+            pub epoch: u32,
+            pub my_roster_idx: u32,
+            /// The latest of the previous epochs whose keychains are still retained after the handshakes.
+            pub prior_epoch: Option<u32>,
+            pub members: Vec<Member>,
+            pub enclave_encryption_key: SodiumPubKey,
+            /// Whether the report containing this node's encryption key is registered on-chain.
+            pub is_report_registered: bool,
+            /// Whether this node joined with a welcome message and its update handshake is pending.
+            pub requires_update: bool,
+        }
+
+        #[derive(Debug, Clone, Eq, PartialEq, Default, Deserialize, Serialize)]
+        pub struct Member {
+            pub roster_idx: u32,
+            pub is_occupied: bool,
+            pub sender_generation: Option<u32>,
+            pub receiver_generation: Option<u32>,
+            /// The epoch which the member's last handshake moved the group to.
+            pub epoch: Option<u32>,
+            /// The hex-encoded MRENCLAVE of the member's enclave attested by this node.
+            pub mr_enclave: Option<String>,
+        }
+    }
+}
+
 pub mod key_rotation {
     pub mod post {
         use super::super::*;
@@ -124,6 +168,34 @@ pub mod register_notification {
     }
 }
 
+pub mod welcome {
+    pub mod post {
+        use super::super::*;
+
+        #[derive(Debug, Clone, Deserialize, Serialize)]
+        pub struct Request {
+            /// The roster index which the joiner is added to.
+            pub roster_idx: u32,
+            /// The joiner's attestation evidence got from its `attestation_evidence` endpoint.
+            pub evidence: serde_json::Value,
+        }
+
+        impl Request {
+            pub fn new(roster_idx: u32, evidence: serde_json::Value) -> Self {
+                Request {
+                    roster_idx,
+                    evidence,
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+        pub struct Response {
+            pub tx_hash: H256,
+        }
+    }
+}
+
 pub mod register_report {
     pub mod post {
         use super::super::*;
@@ -44,6 +44,7 @@ pub async fn handle_send_command(
         )
         .await
         .map_err(ServerError::from)?;
+    server.key_rotation_tracker.increment_commands();
 
     Ok(HttpResponse::Accepted().json(state_runtime_node_api::state::post::Response { tx_hash }))
 }
@@ -53,11 +54,7 @@ pub async fn handle_key_rotation(server: web::Data<Arc<Server>>) -> Result<HttpR
     Span::current().record("trace_id", &tracing::field::display(&get_trace_id()));
     Span::current().record("instance_id", &tracing::field::display(&server.instance_id));
 
-    let tx_hash = server
-        .dispatcher
-        .handshake(server.sender_address, DEFAULT_GAS)
-        .await
-        .map_err(ServerError::from)?;
+    let tx_hash = server.rotate_keys().await.map_err(ServerError::from)?;
 
     Ok(HttpResponse::Accepted()
         .json(state_runtime_node_api::key_rotation::post::Response { tx_hash }))
@@ -129,6 +126,74 @@ pub async fn handle_enclave_encryption_key(server: web::Data<Arc<Server>>) -> Re
     ))
 }
 
+/// Return the attestation evidence of this node's enclave,
+/// which a member verifies in its enclave to add this node with a welcome message.
+#[tracing::instrument(skip(server), fields(trace_id, instance_id))]
+pub async fn handle_attestation_evidence(server: web::Data<Arc<Server>>) -> Result<HttpResponse> {
+    Span::current().record("trace_id", &tracing::field::display(&get_trace_id()));
+    Span::current().record("instance_id", &tracing::field::display(&server.instance_id));
+
+    let evidence = server
+        .dispatcher
+        .get_attestation_evidence()
+        .map_err(ServerError::from)?;
+    let evidence = serde_json::from_slice(&evidence).map_err(anyhow::Error::from)?;
+
+    Ok(HttpResponse::Ok()
+        .json(state_runtime_node_api::attestation_evidence::get::Response { evidence }))
+}
+
+#[tracing::instrument(skip(server), fields(trace_id, instance_id))]
+pub async fn handle_get_group(server: web::Data<Arc<Server>>) -> Result<HttpResponse> {
+    Span::current().record("trace_id", &tracing::field::display(&get_trace_id()));
+    Span::current().record("instance_id", &tracing::field::display(&server.instance_id));
+
+    if !matches!(server.cmd_encryption_algo, CmdEncryptionAlgo::TreeKem) {
+        return Err(ServerError::from(anyhow::anyhow!(
+            "The group membership is only available in the TreeKEM mode"
+        )));
+    }
+
+    let group_info = server
+        .dispatcher
+        .get_group_info()
+        .map_err(ServerError::from)?;
+    let enclave_encryption_key = server
+        .dispatcher
+        .get_enclave_encryption_key()
+        .map_err(ServerError::from)?;
+    let is_report_registered = server
+        .dispatcher
+        .is_encryption_key_registered(&enclave_encryption_key)
+        .await
+        .map_err(ServerError::from)?;
+
+    let members = group_info
+        .members
+        .into_iter()
+        .map(|member| state_runtime_node_api::group::get::Member {
+            roster_idx: member.roster_idx,
+            is_occupied: member.is_occupied,
+            sender_generation: member.sender_generation,
+            receiver_generation: member.receiver_generation,
+            epoch: member.epoch,
+            mr_enclave: member.mr_enclave.map(hex::encode),
+        })
+        .collect();
+
+    Ok(
+        HttpResponse::Ok().json(state_runtime_node_api::group::get::Response {
+            epoch: group_info.epoch,
+            my_roster_idx: group_info.my_roster_idx,
+            prior_epoch: group_info.prior_epoch,
+            members,
+            enclave_encryption_key,
+            is_report_registered,
+            requires_update: group_info.requires_update,
+        }),
+    )
+}
+
 #[tracing::instrument(skip(server, req), fields(trace_id, instance_id))]
 pub async fn handle_register_notification(
     server: web::Data<Arc<Server>>,
@@ -160,6 +225,32 @@ pub async fn handle_register_report(server: web::Data<Arc<Server>>) -> Result<Ht
         .json(state_runtime_node_api::register_report::post::Response { tx_hash }))
 }
 
+/// Add a new member to the group with a welcome message, so it doesn't need to replay the handshakes.
+#[cfg(feature = "backup-enable")]
+#[tracing::instrument(skip(server, req), fields(trace_id, instance_id))]
+pub async fn handle_send_welcome(
+    server: web::Data<Arc<Server>>,
+    req: web::Json<state_runtime_node_api::welcome::post::Request>,
+) -> Result<HttpResponse> {
+    Span::current().record("trace_id", &tracing::field::display(&get_trace_id()));
+    Span::current().record("instance_id", &tracing::field::display(&server.instance_id));
+
+    if !matches!(server.cmd_encryption_algo, CmdEncryptionAlgo::TreeKem) {
+        return Err(ServerError::from(anyhow::anyhow!(
+            "Welcome messages are only available in the TreeKEM mode"
+        )));
+    }
+
+    let evidence = serde_json::to_vec(&req.evidence).map_err(anyhow::Error::from)?;
+    let tx_hash = server
+        .dispatcher
+        .send_welcome(req.roster_idx, evidence, server.sender_address, DEFAULT_GAS)
+        .await
+        .map_err(ServerError::from)?;
+
+    Ok(HttpResponse::Accepted().json(state_runtime_node_api::welcome::post::Response { tx_hash }))
+}
+
 #[cfg(feature = "backup-enable")]
 #[tracing::instrument(skip(server), fields(trace_id, instance_id))]
 pub async fn handle_backup(server: web::Data<Arc<Server>>) -> Result<HttpResponse> {
@@ -0,0 +1,190 @@
+use crate::Server;
+use rand::Rng;
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{error, info};
+
+/// How often the scheduler checks whether the keys should be rotated.
+const CHECK_INTERVAL_MILLS: u64 = 1000;
+
+/// A policy deciding when the state runtime node rotates its keys automatically.
+/// In the TreeKEM mode, a handshake is sent, and in the enclave key mode,
+/// the enclave decryption key is regenerated and the new encryption key is registered on-chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRotationPolicy {
+    /// Rotate after this number of commands is sent through this node.
+    max_commands: Option<u64>,
+    /// Rotate after this period passes since the last rotation.
+    max_interval: Option<Duration>,
+    /// The upper bound of the random delay before rotating,
+    /// so that several nodes don't send rotations at the same time.
+    max_jitter: Duration,
+}
+
+impl KeyRotationPolicy {
+    pub fn new(
+        max_commands: Option<u64>,
+        max_interval: Option<Duration>,
+        max_jitter: Duration,
+    ) -> Self {
+        KeyRotationPolicy {
+            max_commands,
+            max_interval,
+            max_jitter,
+        }
+    }
+
+    /// Load the policy from `KEY_ROTATION_COMMANDS`, `KEY_ROTATION_INTERVAL_SECS` and `KEY_ROTATION_JITTER_SECS`.
+    /// Returns `None` if neither of the triggers is set, so keys are rotated only manually.
+    pub fn from_env() -> Option<Self> {
+        let max_commands = env_var_u64("KEY_ROTATION_COMMANDS");
+        let max_interval = env_var_u64("KEY_ROTATION_INTERVAL_SECS").map(Duration::from_secs);
+        let max_jitter = Duration::from_secs(env_var_u64("KEY_ROTATION_JITTER_SECS").unwrap_or(0));
+
+        if max_commands.is_none() && max_interval.is_none() {
+            return None;
+        }
+
+        Some(KeyRotationPolicy::new(
+            max_commands,
+            max_interval,
+            max_jitter,
+        ))
+    }
+
+    /// Whether the keys should be rotated with the number of commands and the time since the last rotation.
+    pub fn is_due(&self, commands: u64, elapsed: Duration) -> bool {
+        let commands_exceeded = self.max_commands.map_or(false, |max| commands >= max);
+        let interval_exceeded = self.max_interval.map_or(false, |max| elapsed >= max);
+
+        commands_exceeded || interval_exceeded
+    }
+
+    pub fn jitter<R: Rng>(&self, rng: &mut R) -> Duration {
+        let max_jitter_mills = self.max_jitter.as_millis() as u64;
+        if max_jitter_mills == 0 {
+            return Duration::from_millis(0);
+        }
+
+        Duration::from_millis(rng.gen_range(0, max_jitter_mills + 1))
+    }
+}
+
+fn env_var_u64(key: &str) -> Option<u64> {
+    env::var(key).ok().filter(|v| !v.is_empty()).map(|v| {
+        v.parse()
+            .unwrap_or_else(|_| panic!("Failed to parse {} to u64", key))
+    })
+}
+
+/// Tracks the number of commands and the time since the last key rotation.
+#[derive(Debug, Clone)]
+pub struct KeyRotationTracker {
+    commands: Arc<AtomicU64>,
+    last_rotated_at: Arc<Mutex<Instant>>,
+}
+
+impl Default for KeyRotationTracker {
+    fn default() -> Self {
+        KeyRotationTracker {
+            commands: Arc::new(AtomicU64::new(0)),
+            last_rotated_at: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl KeyRotationTracker {
+    pub fn increment_commands(&self) {
+        self.commands.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn commands(&self) -> u64 {
+        self.commands.load(Ordering::SeqCst)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.last_rotated_at.lock().unwrap().elapsed()
+    }
+
+    pub fn reset(&self) {
+        self.commands.store(0, Ordering::SeqCst);
+        *self.last_rotated_at.lock().unwrap() = Instant::now();
+    }
+}
+
+/// Spawn a task rotating the keys of the server according to the policy.
+pub fn spawn_key_rotation_scheduler(server: Server, policy: KeyRotationPolicy) {
+    info!("Starting the key rotation scheduler: {:?}", policy);
+    actix_rt::spawn(async move {
+        loop {
+            actix_rt::time::delay_for(Duration::from_millis(CHECK_INTERVAL_MILLS)).await;
+            let tracker = &server.key_rotation_tracker;
+            if !policy.is_due(tracker.commands(), tracker.elapsed()) {
+                continue;
+            }
+
+            let jitter = policy.jitter(&mut rand::thread_rng());
+            actix_rt::time::delay_for(jitter).await;
+            // Another rotation may be done manually while waiting.
+            if !policy.is_due(tracker.commands(), tracker.elapsed()) {
+                continue;
+            }
+
+            match server.rotate_keys().await {
+                Ok(tx_hash) => info!("Keys are rotated automatically: {:?}", tx_hash),
+                Err(err) => error!("Failed to rotate keys automatically: {:?}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due_by_commands() {
+        let policy = KeyRotationPolicy::new(Some(10), None, Duration::from_secs(0));
+
+        assert!(!policy.is_due(9, Duration::from_secs(3600)));
+        assert!(policy.is_due(10, Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn test_is_due_by_interval() {
+        let policy =
+            KeyRotationPolicy::new(None, Some(Duration::from_secs(60)), Duration::from_secs(0));
+
+        assert!(!policy.is_due(1000, Duration::from_secs(59)));
+        assert!(policy.is_due(0, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_jitter_is_bounded() {
+        let policy = KeyRotationPolicy::new(Some(1), None, Duration::from_secs(5));
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            assert!(policy.jitter(&mut rng) <= Duration::from_secs(5));
+        }
+        let no_jitter = KeyRotationPolicy::new(Some(1), None, Duration::from_secs(0));
+        assert_eq!(no_jitter.jitter(&mut rng), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn test_tracker_reset() {
+        let tracker = KeyRotationTracker::default();
+        tracker.increment_commands();
+        tracker.increment_commands();
+        assert_eq!(tracker.commands(), 2);
+
+        tracker.reset();
+        assert_eq!(tracker.commands(), 0);
+    }
+}
@@ -1,12 +1,14 @@
 use anonify_ecall_types::cmd::*;
 use anonify_eth_driver::{Dispatcher, EventCache};
 use frame_config::{ANONIFY_ABI_PATH, ANONIFY_BIN_PATH, FACTORY_ABI_PATH};
+use key_rotation::{spawn_key_rotation_scheduler, KeyRotationPolicy, KeyRotationTracker};
 use sgx_types::sgx_enclave_id_t;
 use std::{env, str::FromStr};
-use web3::types::Address;
+use web3::types::{Address, H256};
 
 mod error;
 pub mod handlers;
+pub mod key_rotation;
 #[cfg(test)]
 mod test;
 
@@ -22,6 +24,7 @@ pub struct Server {
     pub dispatcher: Dispatcher,
     pub cmd_encryption_algo: CmdEncryptionAlgo,
     pub instance_id: String,
+    pub key_rotation_tracker: KeyRotationTracker,
 }
 
 impl Server {
@@ -66,6 +69,7 @@ impl Server {
             dispatcher,
             cmd_encryption_algo: CmdEncryptionAlgo::EnclaveKey,
             instance_id,
+            key_rotation_tracker: KeyRotationTracker::default(),
         }
     }
 
@@ -110,8 +114,33 @@ impl Server {
         };
 
         self.dispatcher = dispatcher;
+        if let Some(policy) = KeyRotationPolicy::from_env() {
+            spawn_key_rotation_scheduler(self.clone(), policy);
+        }
+
         self
     }
+
+    /// Rotate the keys used for encrypting commands.
+    /// - TreeKEM: send a handshake to update the group key.
+    /// - Enclave key: regenerate the enclave decryption key and register the new encryption key on-chain.
+    pub async fn rotate_keys(&self) -> anonify_eth_driver::error::Result<H256> {
+        let tx_hash = match self.cmd_encryption_algo {
+            CmdEncryptionAlgo::TreeKem => {
+                self.dispatcher
+                    .handshake(self.sender_address, DEFAULT_GAS)
+                    .await?
+            }
+            CmdEncryptionAlgo::EnclaveKey => {
+                self.dispatcher
+                    .rotate_enclave_key(self.sender_address, DEFAULT_GAS)
+                    .await?
+            }
+        };
+        self.key_rotation_tracker.reset();
+
+        Ok(tx_hash)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,22 @@
+#!/bin/bash
+set -ex
+
+# Tests of encrypted-sql-ops over the host controllers, which need neither PostgreSQL nor SGX hardware.
+
+export PATH=~/.cargo/bin:$PATH
+export SGX_MODE=${SGX_MODE:-SW}
+export RUSTFLAGS=-Ctarget-feature=+aes,+sse2,+sse4.1,+ssse3
+ANONIFY_ROOT="$(cd $(dirname $0); pwd)/.."
+export PJ_ROOT_DIR=$ANONIFY_ROOT
+# The owner config is signed by a test key compiled into the enclave.
+export ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY=$(${ANONIFY_ROOT}/scripts/sign-encrypted-sql-ops-owner-config.sh ${ANONIFY_ROOT}/.anonify/encrypted-sql-ops-host-test \
+    '{"version":1,"tagged_contexts":[{"context":"t.c_enc"},{"context":"t.c_joined","domain":"joined"},{"context":"u.c_joined","domain":"joined"}]}')
+export ENCRYPTED_SQL_OPS_OWNER_CONFIG_PATH=.anonify/encrypted-sql-ops-host-test/owner_config.json
+
+# Generate signed.so and measurement.txt
+cd ${ANONIFY_ROOT}/scripts
+export ENCLAVE_PKG_NAME=encrypted_sql_ops
+make DEBUG=1 ENCLAVE_DIR=example/encrypted-sql-ops/enclave
+
+cd ${ANONIFY_ROOT}
+RUST_BACKTRACE=1 cargo test -p encrypted-sql-ops-tests -- --nocapture
@@ -10,6 +10,10 @@ export SGX_MODE=HW
 export RUSTFLAGS=-Ctarget-feature=+aes,+sse2,+sse4.1,+ssse3
 ANONIFY_ROOT="$(cd $(dirname $0); pwd)/.."
 export PJ_ROOT_DIR=$ANONIFY_ROOT
+# The owner config is signed by a test key compiled into the enclave.
+export ENCRYPTED_SQL_OPS_OWNER_PUBLIC_KEY=$(${ANONIFY_ROOT}/scripts/sign-encrypted-sql-ops-owner-config.sh ${ANONIFY_ROOT}/.anonify/encrypted-sql-ops-pg-test \
+    '{"version":1,"tagged_contexts":[{"context":"g.c_dept"},{"context":"j1.c_enc","domain":"dept"},{"context":"j2.c_enc","domain":"dept"}]}')
+export ENCRYPTED_SQL_OPS_OWNER_CONFIG_PATH=.anonify/encrypted-sql-ops-pg-test/owner_config.json
 ANONIFY_TAG=v0.5.11
 
 sudo chown anonify-dev:anonify-dev -R ~/{.rustup,.pgx,anonify}  # in case fixuid is too slow
@@ -0,0 +1,24 @@
+#!/bin/bash
+
+set -e
+
+# Sign an owner config of encrypted-sql-ops by the owner key in the given directory, generating the key if missing.
+# Writes ${dir}/owner_config.json and prints the hex encoded public key to build the enclave with.
+# Usage: sign-encrypted-sql-ops-owner-config.sh <dir> '<config json>'
+
+dir=$1
+config=$2
+mkdir -p "${dir}"
+cd "${dir}"
+
+if [ ! -f owner_key.pem ]; then
+    openssl ecparam -name prime256v1 -genkey -noout -out owner_key.pem 2>/dev/null
+fi
+
+echo -n "${config}" > config.json
+signature=$(openssl dgst -sha256 -sign owner_key.pem config.json | xxd -p | tr -d '\n')
+escaped=$(echo -n "${config}" | sed -e 's/\\/\\\\/g' -e 's/"/\\"/g')
+echo -n "{\"config\":\"${escaped}\",\"signature\":\"${signature}\"}" > owner_config.json
+rm config.json
+
+openssl ec -in owner_key.pem -pubout -outform DER 2>/dev/null | tail -c 65 | xxd -p | tr -d '\n'
@@ -0,0 +1,291 @@
+use crate::{eid, encbigint_is, encinteger_from, encinteger_is, CONTEXT};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::*,
+    enc_type::{enc_aggregate_state::EncAggregateState, EncInteger},
+    enclave_types::MAX_AGGREGATE_BATCH_SIZE,
+};
+use module_encrypted_sql_ops_host::controller::{
+    enc_aggregate_combine_func::EncAggregateCombineFuncController,
+    enc_aggregate_final_func::EncAggregateFinalFuncController,
+    enc_aggregate_state_func::EncAggregateStateFuncController,
+    enc_eq::EncEqController,
+    host_types::{
+        HostAggregateResult, HostEncAggregateState, HostEncAggregateStateWithBatch, HostEncPair,
+    },
+};
+
+const BATCH_SIZES: &[usize] = &[1, 3, MAX_AGGREGATE_BATCH_SIZE];
+
+/// Expected result of an aggregate, whose cases expect failure by `None`.
+#[derive(Clone, Copy, Debug)]
+enum Expected {
+    EncInteger(i32),
+    EncBigInt(i64),
+    PlainBigInt(i64),
+    Null,
+}
+
+/// Aggregate of `ENCINTEGER`, chosen by the commands.
+#[derive(Clone, Copy, Debug)]
+struct Aggregate {
+    name: &'static str,
+    state_cmd: u32,
+    final_cmd: u32,
+}
+
+const SUM: Aggregate = Aggregate {
+    name: "SUM",
+    state_cmd: ENCINTEGER_SUM_STATE_FUNC,
+    final_cmd: ENCINTEGER_SUM_FINAL_FUNC,
+};
+const COUNT: Aggregate = Aggregate {
+    name: "COUNT",
+    state_cmd: ENCINTEGER_COUNT_STATE_FUNC,
+    final_cmd: ENCINTEGER_COUNT_FINAL_FUNC,
+};
+const MIN: Aggregate = Aggregate {
+    name: "MIN",
+    state_cmd: ENCINTEGER_MIN_STATE_FUNC,
+    final_cmd: ENCINTEGER_MIN_FINAL_FUNC,
+};
+const MAX: Aggregate = Aggregate {
+    name: "MAX",
+    state_cmd: ENCINTEGER_MAX_STATE_FUNC,
+    final_cmd: ENCINTEGER_MAX_FINAL_FUNC,
+};
+
+fn encintegers(values: &[i32]) -> Vec<EncInteger> {
+    values
+        .iter()
+        .map(|&i| encinteger_from(i, CONTEXT))
+        .collect()
+}
+
+fn avg(values: &[i32], batch_size: usize) -> anyhow::Result<f32> {
+    let state = aggregate_state(encintegers(values), batch_size, ENCINTEGER_AVG_STATE_FUNC)?;
+    avg_final(state)
+}
+
+fn avg_final(state: EncAggregateState) -> anyhow::Result<f32> {
+    match aggregate_final(state, ENCINTEGER_AVG_FINAL_FUNC)? {
+        HostAggregateResult::PlainReal(avg) => Ok(avg),
+        other => anyhow::bail!("AVG is expected to be in plain text, but got {:?}", other),
+    }
+}
+
+fn aggregate_state(
+    values: Vec<EncInteger>,
+    batch_size: usize,
+    state_cmd: u32,
+) -> anyhow::Result<EncAggregateState> {
+    values
+        .chunks(batch_size)
+        .try_fold(EncAggregateState::Initial, |state, batch| {
+            let host_input = HostEncAggregateStateWithBatch::new(state, batch.to_vec());
+            EncAggregateStateFuncController::<EncInteger>::run(host_input, state_cmd, eid())
+                .map(EncAggregateState::from)
+        })
+}
+
+fn aggregate_final(
+    state: EncAggregateState,
+    final_cmd: u32,
+) -> anyhow::Result<HostAggregateResult> {
+    let host_input = HostEncAggregateState::from(state);
+    EncAggregateFinalFuncController::run(host_input, final_cmd, eid())
+}
+
+/// Context of the encrypted results of `aggregate`, derived from the one of the values.
+fn result_context(aggregate: Aggregate) -> String {
+    format!("{}#{}", CONTEXT, aggregate.name)
+}
+
+fn matches(result: HostAggregateResult, expected: Expected, context: &str) -> bool {
+    match (result, expected) {
+        (HostAggregateResult::EncInteger(encinteger), Expected::EncInteger(i)) => {
+            encinteger_is(encinteger, i, context)
+        }
+        (HostAggregateResult::EncBigInt(encbigint), Expected::EncBigInt(i)) => {
+            encbigint_is(encbigint, i, context)
+        }
+        (HostAggregateResult::PlainBigInt(i), Expected::PlainBigInt(expected)) => i == expected,
+        (HostAggregateResult::Null, Expected::Null) => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn test_encinteger_avg() {
+    let cases: &[(&[i32], Option<f32>)] = &[
+        (&[], Some(f32::NAN)),
+        (&[1], Some(1.0)),
+        (&[1, 2, 3, 4], Some(2.5)),
+        (&[-3, 3], Some(0.0)),
+        (&[i32::MAX], Some(i32::MAX as f32)),
+        // The sum is kept in BIGINT.
+        (&[i32::MAX, 1], Some((i64::from(i32::MAX) + 1) as f32 / 2.0)),
+        (
+            &[i32::MIN, -1],
+            Some((i64::from(i32::MIN) - 1) as f32 / 2.0),
+        ),
+    ];
+
+    for &batch_size in BATCH_SIZES {
+        for &(values, expected) in cases {
+            let result = avg(values, batch_size);
+            match expected {
+                Some(e) if e.is_nan() => assert!(result.unwrap().is_nan()),
+                Some(e) => assert_eq!(result.unwrap(), e, "{:?}", values),
+                None => assert!(result.is_err(), "{:?}", values),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_encinteger_aggregates() {
+    let ten: &[i32] = &[3, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+    let cases: &[(Aggregate, &[i32], Option<Expected>)] = &[
+        (SUM, &[], Some(Expected::Null)),
+        (SUM, ten, Some(Expected::EncBigInt(39))),
+        // The sum is BIGINT as PostgreSQL's.
+        (
+            SUM,
+            &[i32::MAX, 1],
+            Some(Expected::EncBigInt(i64::from(i32::MAX) + 1)),
+        ),
+        (
+            SUM,
+            &[i32::MIN, -1],
+            Some(Expected::EncBigInt(i64::from(i32::MIN) - 1)),
+        ),
+        (COUNT, &[], Some(Expected::PlainBigInt(0))),
+        (COUNT, ten, Some(Expected::PlainBigInt(10))),
+        (MIN, &[], Some(Expected::Null)),
+        (MIN, ten, Some(Expected::EncInteger(1))),
+        (
+            MIN,
+            &[i32::MAX, i32::MIN],
+            Some(Expected::EncInteger(i32::MIN)),
+        ),
+        (MAX, &[], Some(Expected::Null)),
+        (MAX, ten, Some(Expected::EncInteger(9))),
+        (
+            MAX,
+            &[i32::MIN, i32::MAX],
+            Some(Expected::EncInteger(i32::MAX)),
+        ),
+    ];
+
+    for &batch_size in BATCH_SIZES {
+        for &(aggregate, values, expected) in cases {
+            let result = aggregate_state(encintegers(values), batch_size, aggregate.state_cmd)
+                .and_then(|state| aggregate_final(state, aggregate.final_cmd));
+            match expected {
+                Some(expected) => assert!(
+                    matches(result.unwrap(), expected, &result_context(aggregate)),
+                    "{:?}({:?})",
+                    aggregate,
+                    values
+                ),
+                None => assert!(result.is_err(), "{:?}({:?})", aggregate, values),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_batch_too_large() {
+    let values = encintegers(&vec![1; MAX_AGGREGATE_BATCH_SIZE + 1]);
+
+    assert!(aggregate_state(values.clone(), values.len(), ENCINTEGER_AVG_STATE_FUNC).is_err());
+    assert!(aggregate_state(values, MAX_AGGREGATE_BATCH_SIZE + 1, SUM.state_cmd).is_err());
+}
+
+#[test]
+fn test_context_mismatch() {
+    let values = vec![
+        encinteger_from(1, CONTEXT),
+        encinteger_from(2, "another.c_enc"),
+    ];
+
+    // Either in a batch or across batches.
+    for &batch_size in &[1, 2] {
+        assert!(aggregate_state(values.clone(), batch_size, ENCINTEGER_AVG_STATE_FUNC).is_err());
+        assert!(aggregate_state(values.clone(), batch_size, SUM.state_cmd).is_err());
+    }
+}
+
+#[test]
+fn test_aggregate_mismatch() {
+    let sum = aggregate_state(encintegers(&[1, 2]), 2, SUM.state_cmd).unwrap();
+
+    assert!(aggregate_final(sum.clone(), MAX.final_cmd).is_err());
+    let host_input = HostEncAggregateStateWithBatch::new(sum, encintegers(&[3]));
+    assert!(
+        EncAggregateStateFuncController::<EncInteger>::run(host_input, MAX.state_cmd, eid())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_result_context() {
+    let max = aggregate_state(encintegers(&[1, 2]), 2, MAX.state_cmd)
+        .and_then(|state| aggregate_final(state, MAX.final_cmd))
+        .unwrap();
+    let max = match max {
+        HostAggregateResult::EncInteger(encinteger) => encinteger,
+        other => panic!("MAX is expected to be encrypted, but got {:?}", other),
+    };
+
+    // The result can't be written back among the values it's computed from.
+    let host_input = HostEncPair::new(max.clone(), encinteger_from(2, CONTEXT));
+    assert!(EncEqController::run(host_input, ENCINTEGER_EQ, eid()).is_err());
+    assert!(aggregate_state(
+        vec![encinteger_from(1, CONTEXT), max.clone()],
+        2,
+        SUM.state_cmd
+    )
+    .is_err());
+    assert!(encinteger_is(max, 2, &result_context(MAX)));
+}
+
+#[test]
+fn test_combine() {
+    let (lhs, rhs) = ([1, 2, 3, 4], [5, 6, 7, 8, 9, 10]);
+
+    let host_input = HostEncPair::new(
+        aggregate_state(encintegers(&lhs), 3, ENCINTEGER_AVG_STATE_FUNC).unwrap(),
+        aggregate_state(encintegers(&rhs), 3, ENCINTEGER_AVG_STATE_FUNC).unwrap(),
+    );
+    let avg =
+        EncAggregateCombineFuncController::run(host_input, ENCINTEGER_AVG_COMBINE_FUNC, eid())
+            .unwrap();
+    assert_eq!(avg_final(avg.into()).unwrap(), 5.5);
+
+    let sum = aggregate_state(encintegers(&lhs), 3, SUM.state_cmd).unwrap();
+    let host_input = HostEncPair::new(
+        sum.clone(),
+        aggregate_state(encintegers(&rhs), 3, SUM.state_cmd).unwrap(),
+    );
+    let combined =
+        EncAggregateCombineFuncController::run(host_input, ENCINTEGER_SUM_COMBINE_FUNC, eid())
+            .unwrap();
+    let result = aggregate_final(combined.into(), SUM.final_cmd).unwrap();
+    assert!(matches(
+        result,
+        Expected::EncBigInt(55),
+        &result_context(SUM)
+    ));
+
+    // The states of different aggregates can't be combined.
+    let host_input = HostEncPair::new(
+        sum,
+        aggregate_state(encintegers(&rhs), 3, MAX.state_cmd).unwrap(),
+    );
+    assert!(
+        EncAggregateCombineFuncController::run(host_input, ENCINTEGER_SUM_COMBINE_FUNC, eid())
+            .is_err()
+    );
+}
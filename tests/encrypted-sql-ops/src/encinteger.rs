@@ -0,0 +1,147 @@
+use crate::{eid, encinteger_from, encinteger_is, CONTEXT};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{
+        ENCBIGINT_FROM, ENCINTEGER_AVG_STATE_FUNC, ENCINTEGER_EQ, ENCINTEGER_FROM,
+        ENCINTEGER_MIGRATE, ENCINTEGER_SUM_STATE_FUNC,
+    },
+    enc_type::{enc_aggregate_state::EncAggregateState, EncBigInt, EncInteger},
+};
+use module_encrypted_sql_ops_host::controller::{
+    enc_aggregate_state_func::EncAggregateStateFuncController,
+    enc_eq::EncEqController,
+    enc_from::EncFromController,
+    encinteger_from::EncIntegerFromController,
+    encinteger_migrate::EncIntegerMigrateController,
+    host_types::{
+        HostEncAggregateStateWithBatch, HostEncIntegerWithContext, HostEncPair,
+        HostPlainIntegerWithContext, HostPlainWithContext,
+    },
+};
+
+#[test]
+fn test_encinteger_from() {
+    let cases: &[(i32, &str)] = &[
+        (0, CONTEXT),
+        (1, CONTEXT),
+        (-1, CONTEXT),
+        (i32::MAX, CONTEXT),
+        (i32::MIN, CONTEXT),
+        (1, "another.c_enc"),
+    ];
+
+    for &(i, context) in cases {
+        let encinteger = encinteger_from(i, context);
+        // Random nonces make equal values have different ciphertexts.
+        assert_ne!(encinteger, encinteger_from(i, context), "{}", i);
+
+        assert!(encinteger_is(encinteger.clone(), i, context), "{}", i);
+        assert!(
+            !encinteger_is(encinteger.clone(), i.wrapping_add(1), context),
+            "{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_empty_context() {
+    // New values must be bound to a context.
+    let host_input = HostPlainIntegerWithContext::new(1, String::new());
+    assert!(EncIntegerFromController::run(host_input, ENCINTEGER_FROM, eid()).is_err());
+    let host_input = HostPlainWithContext::new(1, String::new());
+    assert!(EncFromController::<EncBigInt>::run(host_input, ENCBIGINT_FROM, eid()).is_err());
+
+    // Nor migrated into the empty context.
+    let host_input = HostEncIntegerWithContext::new(encinteger_from(1, CONTEXT), String::new());
+    assert!(EncIntegerMigrateController::run(host_input, ENCINTEGER_MIGRATE, eid()).is_err());
+}
+
+#[test]
+fn test_encinteger_migrate() {
+    let migrate = |encinteger: EncInteger, context: &str| -> anyhow::Result<EncInteger> {
+        let host_input = HostEncIntegerWithContext::new(encinteger, context.to_string());
+        EncIntegerMigrateController::run(host_input, ENCINTEGER_MIGRATE, eid()).map(Into::into)
+    };
+
+    // A value in the current format is re-encrypted in its context, but can't be moved to another one.
+    let encinteger = encinteger_from(7, CONTEXT);
+    let migrated = migrate(encinteger.clone(), CONTEXT).unwrap();
+    assert_ne!(migrated, encinteger);
+    assert!(encinteger_is(migrated, 7, CONTEXT));
+    assert!(migrate(encinteger, "another.c_enc").is_err());
+}
+
+#[test]
+fn test_encinteger_context_mismatch() {
+    let host_input = HostEncPair::new(
+        encinteger_from(1, CONTEXT),
+        encinteger_from(1, "another.c_enc"),
+    );
+    assert!(EncEqController::run(host_input, ENCINTEGER_EQ, eid()).is_err());
+}
+
+#[test]
+fn test_malformed_ciphertexts() {
+    let valid: Vec<u8> = encinteger_from(1, CONTEXT).into();
+    let encbigint: EncBigInt = EncFromController::<EncBigInt>::run(
+        HostPlainWithContext::new(1, CONTEXT.to_string()),
+        ENCBIGINT_FROM,
+        eid(),
+    )
+    .unwrap()
+    .into_inner();
+
+    // version | context length | context | nonce | ciphertext | tag
+    let context_at = 3;
+    let nonce_at = context_at + CONTEXT.len();
+    let ciphertext_at = nonce_at + 12;
+    let tag_at = valid.len() - 16;
+    let flip = |i: usize| {
+        let mut c = valid.clone();
+        c[i] ^= 0x01;
+        c
+    };
+    let cases: Vec<(&str, Vec<u8>)> = vec![
+        ("empty", vec![]),
+        ("version only", valid[..1].to_vec()),
+        ("without tag", valid[..tag_at].to_vec()),
+        ("truncated tag", valid[..valid.len() - 1].to_vec()),
+        ("trailing byte", [valid.as_slice(), &[0]].concat()),
+        ("unknown version", flip(0)),
+        ("wrong context length", flip(2)),
+        ("tampered context", flip(context_at)),
+        ("tampered nonce", flip(nonce_at)),
+        ("tampered ciphertext", flip(ciphertext_at)),
+        ("tampered tag", flip(tag_at)),
+        ("ENCBIGINT", encbigint.into()),
+    ];
+
+    for (name, malformed) in cases {
+        let malformed = EncInteger::from(malformed);
+
+        let host_input = HostEncPair::new(malformed.clone(), EncInteger::from(valid.clone()));
+        assert!(
+            EncEqController::run(host_input, ENCINTEGER_EQ, eid()).is_err(),
+            "{}",
+            name
+        );
+
+        // A malformed value fails the whole batch.
+        let batch = vec![
+            EncInteger::from(valid.clone()),
+            malformed,
+            EncInteger::from(valid.clone()),
+        ];
+        for &state_cmd in &[ENCINTEGER_AVG_STATE_FUNC, ENCINTEGER_SUM_STATE_FUNC] {
+            let host_input =
+                HostEncAggregateStateWithBatch::new(EncAggregateState::Initial, batch.clone());
+            assert!(
+                EncAggregateStateFuncController::<EncInteger>::run(host_input, state_cmd, eid())
+                    .is_err(),
+                "{}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Tests of encrypted-sql-ops over the host controllers, without PostgreSQL.
+//!
+//! The enclave is built by `scripts/encrypted-sql-ops-host-test.sh`, in simulation mode unless `SGX_MODE=HW`.
+
+use frame_host::{ecall_controller::EcallController, EnclaveDir};
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{ENCBIGINT_EQ, ENCBIGINT_FROM, ENCINTEGER_EQ, ENCINTEGER_FROM, LOAD_MASTER_KEY},
+    enc_type::{EncBigInt, EncInteger},
+};
+use module_encrypted_sql_ops_host::controller::{
+    enc_eq::EncEqController,
+    enc_from::EncFromController,
+    encinteger_from::EncIntegerFromController,
+    host_types::{
+        HostEncPair, HostLoadMasterKey, HostPlainIntegerWithContext, HostPlainWithContext,
+    },
+    load_master_key::LoadMasterKeyController,
+};
+use once_cell::sync::Lazy;
+use sgx_urts::SgxEnclave;
+
+#[cfg(test)]
+mod aggregates;
+#[cfg(test)]
+mod encinteger;
+#[cfg(test)]
+mod tags;
+
+/// Context of the values in the tests, as if they were in a column.
+pub const CONTEXT: &str = "t.c_enc";
+
+static ENCLAVE: Lazy<SgxEnclave> = Lazy::new(|| {
+    let enclave = EnclaveDir::new()
+        .init_enclave(true)
+        .expect("Failed to initialize enclave.");
+    LoadMasterKeyController::run(HostLoadMasterKey, LOAD_MASTER_KEY, enclave.geteid())
+        .expect("failed to load the master key in enclave");
+    enclave
+});
+
+/// Enclave ID of the enclave shared by the tests, which has the master key loaded.
+pub fn eid() -> u64 {
+    ENCLAVE.geteid()
+}
+
+/// Encrypts `i` in `context` as `ENCINTEGER_FROM(i, context)` does.
+pub fn encinteger_from(i: i32, context: &str) -> EncInteger {
+    let host_input = HostPlainIntegerWithContext::new(i, context.to_string());
+    EncIntegerFromController::run(host_input, ENCINTEGER_FROM, eid())
+        .expect("failed to encrypt integer")
+        .into()
+}
+
+/// Whether `encinteger` is `i` in `context`, which reveals nothing more than `=` does.
+pub fn encinteger_is(encinteger: EncInteger, i: i32, context: &str) -> bool {
+    let host_input = HostEncPair::new(encinteger, encinteger_from(i, context));
+    EncEqController::run(host_input, ENCINTEGER_EQ, eid())
+        .expect("failed to compare integers")
+        .into()
+}
+
+/// Whether `encbigint` is `i` in `context`, which reveals nothing more than `=` does.
+pub fn encbigint_is(encbigint: EncBigInt, i: i64, context: &str) -> bool {
+    let expected: EncBigInt = EncFromController::<EncBigInt>::run(
+        HostPlainWithContext::new(i, context.to_string()),
+        ENCBIGINT_FROM,
+        eid(),
+    )
+    .expect("failed to encrypt bigint")
+    .into_inner();
+    let host_input = HostEncPair::new(encbigint, expected);
+    EncEqController::run(host_input, ENCBIGINT_EQ, eid())
+        .expect("failed to compare bigints")
+        .into()
+}
@@ -0,0 +1,71 @@
+use crate::{eid, encinteger_from, CONTEXT};
+use frame_host::ecall_controller::EcallController;
+use module_encrypted_sql_ops_ecall_types::{
+    ecall_cmd::{ENCINTEGER_TAG, ENCTEXT_FROM, ENCTEXT_TAG},
+    enc_type::{EncInteger, EncTag, EncText},
+};
+use module_encrypted_sql_ops_host::controller::{
+    enc_from::EncFromController,
+    enc_tag::EncTagController,
+    host_types::{HostEncValue, HostPlainWithContext},
+};
+
+// Listed in `tagged_contexts` of the owner config signed by the test script, along with `CONTEXT`.
+const JOINED_CONTEXTS: [&str; 2] = ["t.c_joined", "u.c_joined"];
+
+fn encinteger_tag(i: i32, context: &str) -> anyhow::Result<EncTag> {
+    let host_input = HostEncValue::new(encinteger_from(i, context));
+    EncTagController::<EncInteger>::run(host_input, ENCINTEGER_TAG, eid()).map(Into::into)
+}
+
+fn enctext_tag(s: &str, context: &str) -> EncTag {
+    let enctext = EncFromController::<EncText>::run(
+        HostPlainWithContext::new(s.to_string(), context.to_string()),
+        ENCTEXT_FROM,
+        eid(),
+    )
+    .expect("failed to encrypt text")
+    .into_inner();
+    EncTagController::<EncText>::run(HostEncValue::new(enctext), ENCTEXT_TAG, eid())
+        .expect("failed to tag text")
+        .into()
+}
+
+#[test]
+fn test_tag_deterministic() {
+    for &i in &[0, 1, -1, i32::MAX, i32::MIN] {
+        // Unlike the ciphertexts, the tags of the equal values are equal.
+        assert_eq!(
+            encinteger_tag(i, CONTEXT).unwrap(),
+            encinteger_tag(i, CONTEXT).unwrap(),
+            "{}",
+            i
+        );
+        assert_ne!(
+            encinteger_tag(i, CONTEXT).unwrap(),
+            encinteger_tag(i.wrapping_add(1), CONTEXT).unwrap(),
+            "{}",
+            i
+        );
+    }
+
+    assert_eq!(enctext_tag("a", CONTEXT), enctext_tag("a", CONTEXT));
+    assert_ne!(enctext_tag("a", CONTEXT), enctext_tag("b", CONTEXT));
+}
+
+#[test]
+fn test_tag_domains() {
+    let [t, u] = JOINED_CONTEXTS;
+    assert_eq!(encinteger_tag(1, t).unwrap(), encinteger_tag(1, u).unwrap());
+    assert_ne!(
+        encinteger_tag(1, t).unwrap(),
+        encinteger_tag(1, CONTEXT).unwrap()
+    );
+    // Values of different types have different tags even in the same domain.
+    assert_ne!(encinteger_tag(1, t).unwrap(), enctext_tag("\0\0\0\u{1}", t));
+}
+
+#[test]
+fn test_tag_not_enabled() {
+    assert!(encinteger_tag(1, "another.c_enc").is_err());
+}
@@ -77,7 +77,6 @@ lazy_static! {
 pub fn set_env_vars() {
     lazy_static::initialize(&ENV_LOGGER_INIT);
     env::set_var("MY_ROSTER_IDX", "0");
-    env::set_var("MAX_ROSTER_IDX", "2");
     env::set_var(
         "IAS_URL",
         "https://api.trustedservices.intel.com/sgx/dev/attestation/v3/report",
@@ -0,0 +1,375 @@
+//! A local stand-in of Intel Attestation Service for tests.
+//! It signs attestation reports of any quotes with the keys generated on start,
+//! whose root certificate must never be trusted in production.
+
+use anyhow::{anyhow, ensure, Result};
+use openssl::{
+    asn1::Asn1Time,
+    bn::{BigNum, MsbOption},
+    hash::MessageDigest,
+    pkey::{PKey, Private},
+    rsa::Rsa,
+    x509::{
+        extension::{BasicConstraints, KeyUsage, SubjectKeyIdentifier},
+        X509Name, X509,
+    },
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+};
+use serde_json::json;
+use std::{
+    env,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+const ORGANIZATION: &str = "Anonify Mock IAS (test only, not Intel)";
+/// The quote header and the ISV enclave report, which precede the quote signature
+const QUOTE_BODY_LEN: usize = 432;
+
+/// The response of the report API, whose headers are the same as IAS's ones.
+#[derive(Debug, Clone)]
+pub struct MockReport {
+    pub body: Vec<u8>,
+    /// `X-IASReport-Signature` header
+    pub signature: String,
+    /// `X-IASReport-Signing-Certificate` header
+    pub signing_certificate: String,
+}
+
+pub struct MockIas {
+    signing_key: RsaKeyPair,
+    /// The PEM encoded signing certificate followed by the root certificate
+    cert_chain: String,
+    root_cert: X509,
+    quote_status: String,
+    advisory_ids: Vec<String>,
+    next_id: AtomicU64,
+}
+
+impl MockIas {
+    /// Generate a new root CA and the report signing key issued by it,
+    /// so the reports are verified only by `root_cert_pem` of this instance.
+    pub fn new(quote_status: &str, advisory_ids: Vec<String>) -> Result<Self> {
+        let root_key = PKey::from_rsa(Rsa::generate(2048)?)?;
+        let root_cert = issue_cert(
+            "Mock Attestation Report Signing CA",
+            &root_key,
+            None,
+            &root_key,
+        )?;
+        let signing_key = PKey::from_rsa(Rsa::generate(2048)?)?;
+        let signing_cert = issue_cert(
+            "Mock Attestation Report Signing",
+            &signing_key,
+            Some(&root_cert),
+            &root_key,
+        )?;
+        let cert_chain = format!(
+            "{}{}",
+            String::from_utf8(signing_cert.to_pem()?)?,
+            String::from_utf8(root_cert.to_pem()?)?
+        );
+        let signing_key = RsaKeyPair::from_pkcs8(&signing_key.private_key_to_pkcs8()?)
+            .map_err(|e| anyhow!("Invalid signing key: {}", e))?;
+
+        Ok(MockIas {
+            signing_key,
+            cert_chain,
+            root_cert,
+            quote_status: quote_status.to_string(),
+            advisory_ids,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// The quote status is set by `MOCK_IAS_QUOTE_STATUS` (`OK` by default),
+    /// and the comma-separated advisories by `MOCK_IAS_ADVISORY_IDS`.
+    pub fn from_env() -> Result<Self> {
+        let quote_status = match env::var("MOCK_IAS_QUOTE_STATUS") {
+            Ok(status) if !status.is_empty() => status,
+            _ => "OK".to_string(),
+        };
+        let advisory_ids = env::var("MOCK_IAS_ADVISORY_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+
+        Self::new(&quote_status, advisory_ids)
+    }
+
+    /// The PEM encoded root certificate, which the enclaves must trust as `IAS_ROOT_CERT_PATH`.
+    pub fn root_cert_pem(&self) -> Result<String> {
+        Ok(String::from_utf8(self.root_cert.to_pem()?)?)
+    }
+
+    /// Issue a signed attestation report of the base64 encoded quote
+    /// in the format of the API `version` (3 or 4).
+    pub fn report(&self, version: u64, isv_enclave_quote: &str) -> Result<MockReport> {
+        ensure!(
+            version == 3 || version == 4,
+            "Unsupported API version: {}",
+            version
+        );
+        let quote = base64::decode(isv_enclave_quote)?;
+        ensure!(quote.len() >= QUOTE_BODY_LEN, "Invalid isvEnclaveQuote");
+
+        let mut report = json!({
+            "id": self.next_id.fetch_add(1, Ordering::SeqCst).to_string(),
+            "timestamp": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+            "version": version,
+            "isvEnclaveQuoteStatus": self.quote_status,
+            "isvEnclaveQuoteBody": base64::encode(&quote[..QUOTE_BODY_LEN]),
+        });
+        // The advisories are reported since the API version 4
+        if version == 4 && !self.advisory_ids.is_empty() {
+            report["advisoryURL"] = json!("https://security-center.intel.com");
+            report["advisoryIDs"] = json!(self.advisory_ids);
+        }
+        let body = serde_json::to_vec(&report)?;
+
+        let mut signature = vec![0; self.signing_key.public_modulus_len()];
+        self.signing_key
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                &body,
+                &mut signature,
+            )
+            .map_err(|e| anyhow!("Failed to sign the report: {}", e))?;
+
+        Ok(MockReport {
+            body,
+            signature: base64::encode(&signature),
+            signing_certificate: percent_encode(&self.cert_chain),
+        })
+    }
+}
+
+/// Issue the certificate of the key by the issuer, or a self-signed CA certificate without the issuer,
+/// in the same profile as the IAS report signing certificates.
+fn issue_cert(
+    common_name: &str,
+    key: &PKey<Private>,
+    issuer: Option<&X509>,
+    issuer_key: &PKey<Private>,
+) -> Result<X509> {
+    let mut name = X509Name::builder()?;
+    name.append_entry_by_text("C", "JP")?;
+    name.append_entry_by_text("O", ORGANIZATION)?;
+    name.append_entry_by_text("CN", common_name)?;
+    let name = name.build();
+
+    let mut serial_number = BigNum::new()?;
+    serial_number.rand(64, MsbOption::MAYBE_ZERO, false)?;
+    let serial_number = serial_number.to_asn1_integer()?;
+    let not_before = Asn1Time::days_from_now(0)?;
+    let not_after = Asn1Time::days_from_now(365)?;
+
+    let mut cert = X509::builder()?;
+    cert.set_version(2)?;
+    cert.set_serial_number(&serial_number)?;
+    cert.set_subject_name(&name)?;
+    cert.set_issuer_name(issuer.map_or(&name, |issuer| issuer.subject_name()))?;
+    cert.set_pubkey(key)?;
+    cert.set_not_before(&not_before)?;
+    cert.set_not_after(&not_after)?;
+    if issuer.is_some() {
+        cert.append_extension(BasicConstraints::new().critical().build()?)?;
+        cert.append_extension(
+            KeyUsage::new()
+                .critical()
+                .digital_signature()
+                .non_repudiation()
+                .build()?,
+        )?;
+    } else {
+        cert.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+        cert.append_extension(
+            KeyUsage::new()
+                .critical()
+                .key_cert_sign()
+                .crl_sign()
+                .build()?,
+        )?;
+    }
+    let subject_key_id = SubjectKeyIdentifier::new()
+        .build(&cert.x509v3_context(issuer.map(|issuer| &**issuer), None))?;
+    cert.append_extension(subject_key_id)?;
+    cert.sign(issuer_key, MessageDigest::sha256())?;
+
+    Ok(cert.build())
+}
+
+/// Percent-encode all characters but the unreserved ones as IAS does.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use remote_attestation::{AttestedReport, QuoteStatus, QuoteStatusPolicy};
+    use serde_json::Value;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const IAS_ROOT_CERT: &str = include_str!("../../../config/ias_root_cert.pem");
+
+    fn root_cert(mock_ias: &MockIas) -> Vec<u8> {
+        pem::parse(mock_ias.root_cert_pem().unwrap())
+            .unwrap()
+            .contents
+    }
+
+    fn attested_report(report: MockReport) -> AttestedReport {
+        AttestedReport::from_ias_response(
+            report.body,
+            &report.signature,
+            &report.signing_certificate,
+        )
+        .unwrap()
+    }
+
+    fn quote() -> String {
+        let mut quote = vec![0u8; QUOTE_BODY_LEN + 64];
+        quote[368..400].copy_from_slice(&[7u8; 32]);
+        base64::encode(&quote)
+    }
+
+    #[test]
+    fn test_report_verified_by_mock_root() {
+        let mock_ias = MockIas::new("OK", vec![]).unwrap();
+        let report = attested_report(mock_ias.report(4, &quote()).unwrap())
+            .verify_attested_report_with_policy(root_cert(&mock_ias), &QuoteStatusPolicy::default())
+            .unwrap();
+
+        let quote_body = report.get_quote_body().unwrap();
+        assert_eq!(quote_body.len(), QUOTE_BODY_LEN);
+        assert_eq!(&quote_body[368..400], &[7u8; 32]);
+        assert_eq!(report.quote_status().unwrap(), QuoteStatus::Ok);
+        assert!(report.advisory_ids().unwrap().is_empty());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert!(report.timestamp().unwrap() <= now);
+        assert!(report.timestamp().unwrap() + 60 > now);
+    }
+
+    #[test]
+    fn test_verify_report_by_policy() {
+        let mock_ias =
+            MockIas::new("GROUP_OUT_OF_DATE", vec!["INTEL-SA-00334".to_string()]).unwrap();
+        let report = attested_report(mock_ias.report(4, &quote()).unwrap());
+
+        assert!(report
+            .clone()
+            .verify_attested_report_with_policy(root_cert(&mock_ias), &QuoteStatusPolicy::strict())
+            .is_err());
+        let policy = QuoteStatusPolicy::new(
+            vec![QuoteStatus::GroupOutOfDate],
+            Some(vec!["INTEL-SA-00334".to_string()]),
+        )
+        .unwrap();
+        assert!(report
+            .clone()
+            .verify_attested_report_with_policy(root_cert(&mock_ias), &policy)
+            .is_ok());
+        let policy = QuoteStatusPolicy::new(
+            vec![QuoteStatus::GroupOutOfDate],
+            Some(vec!["INTEL-SA-00219".to_string()]),
+        )
+        .unwrap();
+        assert!(report
+            .verify_attested_report_with_policy(root_cert(&mock_ias), &policy)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_tampered_report() {
+        let mock_ias = MockIas::new("GROUP_OUT_OF_DATE", vec![]).unwrap();
+        let mut report = mock_ias.report(4, &quote()).unwrap();
+        let mut body: Value = serde_json::from_slice(&report.body).unwrap();
+        body["isvEnclaveQuoteStatus"] = "OK".into();
+        report.body = serde_json::to_vec(&body).unwrap();
+
+        assert!(attested_report(report)
+            .verify_attested_report_with_policy(root_cert(&mock_ias), &QuoteStatusPolicy::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_untrusted_root() {
+        // The mock reports must not be accepted by the real IAS root CA.
+        let report = MockIas::new("OK", vec![])
+            .unwrap()
+            .report(4, &quote())
+            .unwrap();
+        let ias_root_cert = pem::parse(IAS_ROOT_CERT).unwrap().contents;
+
+        assert!(attested_report(report)
+            .verify_attested_report_with_policy(ias_root_cert, &QuoteStatusPolicy::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_report_with_advisories() {
+        let mock_ias =
+            MockIas::new("GROUP_OUT_OF_DATE", vec!["INTEL-SA-00334".to_string()]).unwrap();
+
+        let report = mock_ias.report(4, &quote()).unwrap();
+        let report = AttestedReport::from_ias_response(
+            report.body,
+            &report.signature,
+            &report.signing_certificate,
+        )
+        .unwrap();
+        assert_eq!(report.quote_status().unwrap(), QuoteStatus::GroupOutOfDate);
+        assert_eq!(report.advisory_ids().unwrap(), vec!["INTEL-SA-00334"]);
+
+        let report = mock_ias.report(3, &quote()).unwrap();
+        let report = AttestedReport::from_ias_response(
+            report.body,
+            &report.signature,
+            &report.signing_certificate,
+        )
+        .unwrap();
+        assert!(report.advisory_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reject_report_of_another_mock_ias() {
+        let report = MockIas::new("OK", vec![])
+            .unwrap()
+            .report(4, &quote())
+            .unwrap();
+        let another = MockIas::new("OK", vec![]).unwrap();
+
+        assert!(AttestedReport::from_ias_response(
+            report.body,
+            &report.signature,
+            &report.signing_certificate,
+        )
+        .unwrap()
+        .verify_attested_report_with_policy(root_cert(&another), &QuoteStatusPolicy::default())
+        .is_err());
+    }
+
+    #[test]
+    fn test_reject_invalid_request() {
+        let mock_ias = MockIas::new("OK", vec![]).unwrap();
+        assert!(mock_ias.report(2, &quote()).is_err());
+        assert!(mock_ias.report(4, &base64::encode(&[0u8; 16])).is_err());
+        assert!(mock_ias.report(4, "not base64").is_err());
+    }
+}
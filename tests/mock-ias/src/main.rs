@@ -0,0 +1,69 @@
+use actix_web::{web, App, HttpResponse, HttpServer};
+use mock_ias::MockIas;
+use serde::Deserialize;
+use std::{env, fs, io, path::PathBuf, sync::Arc};
+use tracing::{error, info};
+
+#[derive(Debug, Deserialize)]
+struct ReportRequest {
+    #[serde(rename = "isvEnclaveQuote")]
+    isv_enclave_quote: String,
+}
+
+async fn handle_report(
+    mock_ias: web::Data<Arc<MockIas>>,
+    version: web::Path<u64>,
+    req: web::Json<ReportRequest>,
+) -> HttpResponse {
+    match mock_ias.report(version.into_inner(), &req.isv_enclave_quote) {
+        Ok(report) => HttpResponse::Ok()
+            .content_type("application/json")
+            .header("X-IASReport-Signature", report.signature)
+            .header(
+                "X-IASReport-Signing-Certificate",
+                report.signing_certificate,
+            )
+            .body(report.body),
+        Err(e) => {
+            error!("Failed to issue an attestation report: {:?}", e);
+            // IAS responds 400 to invalid quotes
+            HttpResponse::BadRequest().finish()
+        }
+    }
+}
+
+#[actix_web::main]
+async fn main() -> io::Result<()> {
+    tracing_subscriber::fmt::init();
+    let address = env::var("MOCK_IAS_ADDRESS").unwrap_or_else(|_| "0.0.0.0:8090".to_string());
+    let mock_ias = Arc::new(MockIas::from_env().expect("Failed to initialize the mock IAS"));
+    // The enclaves read the root certificate of the keys generated on this start by `IAS_ROOT_CERT_PATH`
+    let root_cert_path = match env::var("MOCK_IAS_ROOT_CERT_PATH") {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => PathBuf::from(".anonify/mock_ias_root_cert.pem"),
+    };
+    if let Some(dir) = root_cert_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let root_cert = mock_ias
+        .root_cert_pem()
+        .expect("Failed to encode the root certificate");
+    fs::write(&root_cert_path, root_cert)?;
+    info!("Wrote the root certificate to {}", root_cert_path.display());
+
+    HttpServer::new(move || {
+        App::new()
+            .data(mock_ias.clone())
+            .route(
+                "/sgx/dev/attestation/v{version}/report",
+                web::post().to(handle_report),
+            )
+            .route(
+                "/sgx/attestation/v{version}/report",
+                web::post().to(handle_report),
+            )
+    })
+    .bind(address)?
+    .run()
+    .await
+}
@@ -1,5 +1,7 @@
 #[cfg(test)]
 use frame_host::EnclaveDir;
+#[cfg(test)]
+use mock_ias::MockIas;
 use sgx_types::{sgx_enclave_id_t, sgx_status_t};
 
 extern "C" {
@@ -8,8 +10,30 @@ extern "C" {
 
 #[test]
 fn test_in_enclave() {
+    set_mock_ias_report();
     let enclave = EnclaveDir::new().init_enclave(true).unwrap();
     let ret = unsafe { ecall_run_tests(enclave.geteid()) };
 
     assert_eq!(ret, sgx_status_t::SGX_SUCCESS);
 }
+
+/// Pass the root certificate of a mock IAS generated for this run and a report signed by it to the tests in the enclave.
+/// The quote body has MRENCLAVE `0x11` * 32, MRSIGNER `0x22` * 32 and the report data `0x07` * 32 followed by zeros.
+#[cfg(test)]
+fn set_mock_ias_report() {
+    let mut quote = vec![0u8; 496];
+    quote[112..144].copy_from_slice(&[0x11; 32]);
+    quote[176..208].copy_from_slice(&[0x22; 32]);
+    quote[368..400].copy_from_slice(&[0x07; 32]);
+
+    let mock_ias = MockIas::new("OK", vec![]).unwrap();
+    let report = mock_ias.report(4, &base64::encode(&quote)).unwrap();
+    let report = serde_json::json!({
+        "body": String::from_utf8(report.body).unwrap(),
+        "signature": report.signature,
+        "signing_certificate": report.signing_certificate,
+    });
+
+    std::env::set_var("MOCK_IAS_ROOT_CERT", mock_ias.root_cert_pem().unwrap());
+    std::env::set_var("MOCK_IAS_REPORT", report.to_string());
+}
@@ -141,7 +141,6 @@ pub fn test_end(ntestcases: u64, failurecases: Vec<String>) -> bool {
 pub fn set_env_vars() {
     env::set_var("RUST_LOG", "DEBUG");
     env::set_var("MY_ROSTER_IDX", "0");
-    env::set_var("MAX_ROSTER_IDX", "2");
     env::set_var(
         "IAS_URL",
         "https://api.trustedservices.intel.com/sgx/dev/attestation/v3/report",